@@ -0,0 +1,18 @@
+#![no_main]
+
+use gridoxide::project::parse_project;
+use libfuzzer_sys::fuzz_target;
+
+// Anything parse_project accepts must also build an engine state that
+// passes the invariants the audio thread relies on
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(project) = parse_project(json) {
+        let state = project.to_state();
+        if let Err(e) = state.check_invariants() {
+            panic!("accepted project breaks invariants: {:#}", e);
+        }
+    }
+});
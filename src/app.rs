@@ -1,38 +1,90 @@
 use std::io::{self, Stdout};
+use std::panic;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use crossterm::cursor::Show;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::event::{
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
 use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+    disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+    LeaveAlternateScreen,
 };
 use crossterm::ExecutableCommand;
 use parking_lot::RwLock;
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph};
 use ratatui::Terminal;
+use tracing::{debug, error, info, warn};
 
-use crate::audio::{AudioEngine, SequencerState};
-use crate::command::{Command, CommandBus, CommandSender, CommandSource};
-use crate::event::EventLog;
+use crate::audio::{
+    self, capture_buffer, is_audio_thread, level_db, AnalyzerTap, AudioEngine, SequencerState, TrackState, CROSSFADER_STEP,
+    MAX_CAPTURE_STEPS, MAX_GLYPH_CHARS,
+};
+use crate::command::{
+    Command, CommandBus, CommandSender, CommandSource, MacroStore, MAX_MACRO_COMMANDS,
+};
+use crate::config::{Config, AUTOSAVE_MINUTES, BACKUP_COUNTS, BUFFER_SIZES};
+use crate::crash::write_crash_report;
+use crate::event::{EventLog, SessionStats};
 use crate::fx::{FilterType, FxParamId, FxType, MasterFxParamId};
 use crate::mcp::{start_socket_server, GridoxideMcp};
-use crate::project;
-use crate::project::renderer::{ExportMode, export_wav};
-use crate::samples;
-use crate::sequencer::{PlaybackMode, Variation, NUM_PATTERNS};
-use crate::synth::{load_wav, SynthType};
+use crate::midi::{self, MidiSync, NoteOn};
+use crate::project::{self, ProjectData};
+use crate::project::backup::{list_backups, rotate_backups};
+use crate::project::gain::{suggest_gain_staging, GainStaging};
+use crate::project::kit::assign_kit;
+use crate::project::midi::import_midi;
+use crate::project::queue::{ExportQueue, ExportTarget, JobStatus};
+use crate::project::renderer::{export_hits, ExportMode};
+use crate::project::template::{absolute_sample_path, add_template_track, TemplateStore};
+use crate::project::view::{load_view_state, save_view_state, ViewState};
+use crate::samples::{self, SampleWatcher};
+use crate::script::{self, Script, ScriptHost, ScriptRun, MAX_SCRIPTS, SCRIPT_TEMPLATE};
+use crate::sequencer::pattern::{Pattern, StepRow};
+use crate::sequencer::{
+    chord_label, pattern_label, random_seed, PlaybackMode, TrackAutomation, Variation,
+    CHORD_DEGREES, MAX_FADE_OUT_BARS, MAX_LOCKS_PER_STEP, MAX_NUDGE_MS, MAX_STEPS, MAX_SWING, MAX_TRACKS, MAX_TRACK_TRANSPOSE,
+    MAX_BPM, MAX_TRANSPOSE, MIN_BPM, NUM_PATTERNS, PATTERNS_PER_BANK, STEPS,
+};
+use crate::synth::{
+    bpm_pitch_shift, detect_bpm, detect_root_note, key_pitch_shift, load_wav, normalize, prepare_sample, split_cents,
+    tuning_label, write_wav, ExternalConnection, ParkedSynths, PluginStart, SampleAudio, SampleInfo, SampleOptions, SynthType, ToneMode, MAX_SAMPLE_LAYERS,
+    NOTE_NAMES,
+};
 use crate::ui::{
-    get_param_descriptors, get_snapshot_param_value, render_browser, render_fx, render_grid,
-    render_help, render_mixer, render_params, render_song, render_transport, BrowserState,
-    FxEditorState, GridState, HelpState, MixerField, MixerState, ParamEditorState, SongState,
-    Theme, TransportInfo,
+    get_lockable_descriptors, get_param_descriptors, get_snapshot_param_value, is_compact, is_too_small, render_browser, render_fx, render_grid,
+    render_bank, render_help, render_mixer, render_modal, render_notifications, render_params, render_scope, render_scripts,
+    render_settings, render_song, render_stats, sample_layers, render_step_inspector, render_too_small, render_transport,
+    render_lock_editor, render_export_queue, screen_areas, track_steps, BankState, BrowserState,
+    FormField, FxEditorState, Gesture, GridState, HelpState, LockEditor, LongPress, MixerField, MixerState, Modal, ModalEvent,
+    NotificationLog, ParamEditorState, Prompt, PromptHistory, PromptKind, ScopeState, ScriptsState, SettingsField,
+    SettingsState, Severity, SongState, Theme, TransportInfo,
 };
 use crate::ui::help::help_line_count;
 
+/// DJ sweep movement per Alt+Left/Right press or key repeat
+const DJ_SWEEP_STEP: f32 = 0.05;
+/// Sweep is released once no sweep key has arrived for this long
+/// (longer than the usual terminal key-repeat delay)
+const DJ_SWEEP_RELEASE: Duration = Duration::from_millis(500);
+
+/// Longest the UI waits for the audio thread to apply a command
+const ENGINE_WAIT: Duration = Duration::from_millis(250);
+
+/// Swing change per z / Z press (percent)
+const SWING_STEP: i16 = 5;
+
+/// Preview duck levels cycled in the sample browser (dB), after "off"
+const PREVIEW_DUCK_STEPS: [f32; 4] = [-6.0, -12.0, -18.0, -24.0];
+
 /// Current UI view
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum View {
@@ -41,15 +93,176 @@ pub enum View {
     Mixer,
     Fx,
     Song,
+    Bank,
+    Scope,
+    Scripts,
+    Settings,
     Help,
 }
 
+impl View {
+    /// Views restored when a project is reopened (see ViewState)
+    const SAVED: [View; 8] = [
+        View::Grid,
+        View::Params,
+        View::Mixer,
+        View::Fx,
+        View::Song,
+        View::Bank,
+        View::Scope,
+        View::Scripts,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            View::Grid => "grid",
+            View::Params => "params",
+            View::Mixer => "mixer",
+            View::Fx => "fx",
+            View::Song => "song",
+            View::Bank => "bank",
+            View::Scope => "scope",
+            View::Scripts => "scripts",
+            View::Settings => "settings",
+            View::Help => "help",
+        }
+    }
+}
+
+/// Song view dialog: copy selected tracks of the current pattern into another slot
+struct CopyTracksDialog {
+    src: usize,
+    dst: usize,
+    /// Track under the cursor
+    cursor: usize,
+    selected: Vec<bool>,
+    merge: bool,
+}
+
+/// Synth types offered by the add-track picker, keys 1-9
+const ADD_TRACK_TYPES: [SynthType; 9] = [
+    SynthType::Kick,
+    SynthType::Snare,
+    SynthType::HiHat,
+    SynthType::Bass,
+    SynthType::Sampler,
+    SynthType::Input,
+    SynthType::Tone,
+    SynthType::Stab,
+    SynthType::Acid,
+];
+
+/// What an open modal is for
+enum Dialog {
+    Prompt(PromptKind),
+    /// New-tab picker: an empty project, then these files
+    NewTab(Vec<PathBuf>),
+    ReplayMacro(Vec<String>),
+    /// Add-track picker: ADD_TRACK_TYPES, then the saved templates
+    AddTrack,
+    /// Close the active tab despite unsaved changes
+    CloseTab,
+    /// Clear a pattern slot from the bank view
+    ClearPattern(usize),
+    /// Load the WAVs of a folder onto tracks from the first
+    LoadKit(PathBuf),
+    /// Stems export options: directory, mode, tone tracks
+    ExportStems,
+    /// Backups of the active tab's project, newest first
+    RestoreBackup(Vec<PathBuf>),
+    /// Capture length for recording the audio input into a track
+    RecordSample(usize),
+    /// Start the plugin command a loaded project gives a track
+    StartPlugin(usize, String),
+}
+
+/// Maximum number of open project tabs
+const MAX_TABS: usize = 4;
+
+/// Capture lengths offered when recording the audio input, in steps
+const CAPTURE_STEP_CHOICES: [&str; 5] = ["4", "8", "16", "32", "64"];
+
+/// An open project. The active tab's project lives in the audio engine;
+/// the others are parked here until switched to.
+struct ProjectTab {
+    /// Project file (None until saved or loaded)
+    path: Option<PathBuf>,
+    /// Changed since the last save or load
+    dirty: bool,
+    parked: Option<Box<SequencerState>>,
+    /// The parked project's synths, with their samples and plugins
+    synths: ParkedSynths,
+}
+
+impl ProjectTab {
+    fn new(path: Option<PathBuf>) -> Self {
+        Self {
+            path,
+            dirty: false,
+            parked: None,
+            synths: ParkedSynths::default(),
+        }
+    }
+
+    /// Name shown in the tab bar
+    fn label(&self) -> String {
+        self.path
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map_or_else(|| "untitled".to_string(), |s| s.to_string_lossy().to_string())
+    }
+
+    /// Directory sample paths are resolved against
+    fn dir(&self) -> PathBuf {
+        self.path
+            .as_ref()
+            .and_then(|p| p.parent())
+            .filter(|p| !p.as_os_str().is_empty())
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf)
+    }
+}
+
+/// Pattern or track sound copied for pasting, possibly into another tab
+enum TabClipboard {
+    Pattern(Pattern),
+    Sound(TrackState),
+}
+
+/// Passes a loop-record session runs before it stops recording
+const LOOP_RECORD_BARS: usize = 4;
+
+/// Loop-record session: pad hits overdub into `pattern` for LOOP_RECORD_BARS
+/// passes of the one-bar loop. Kept after recording ends for undo.
+struct LoopRecord {
+    pattern: usize,
+    /// Hits are being written (the slot is playing and passes remain)
+    recording: bool,
+    /// Index of the pass being recorded
+    pass: usize,
+    last_step: usize,
+    /// Pattern before each pass, for undo-last-pass
+    snapshots: Vec<Pattern>,
+}
+
+/// Track row saved by a Fill tap, restored when a held Fill ends
+struct FillSnapshot {
+    pattern: usize,
+    track: usize,
+    variation: Variation,
+    row: StepRow,
+}
+
 /// Application state
 pub struct App {
     /// Current theme
     theme: Theme,
     /// Audio engine
-    _audio: AudioEngine,
+    audio: AudioEngine,
+    /// Whether opening the hardware input was attempted (for Input tracks)
+    input_requested: bool,
+    /// MIDI sync mode whose port was last opened, and the port to look for
+    midi_sync_seen: MidiSync,
+    midi_port: Option<String>,
     /// Command sender for dispatching commands
     command_sender: CommandSender,
     /// Event log for MCP "listening"
@@ -66,6 +279,8 @@ pub struct App {
     fx_editor: FxEditorState,
     /// Song/arrangement editor state
     song_state: SongState,
+    /// Pattern bank view state
+    bank_state: BankState,
     /// Help view state
     help_state: HelpState,
     /// Sample browser state (modal overlay, None when closed)
@@ -78,41 +293,122 @@ pub struct App {
     should_quit: bool,
     /// Shutdown flag for the MCP socket server
     mcp_shutdown: Arc<AtomicBool>,
-    /// Last project file path (for repeat save/load)
-    project_path: Option<PathBuf>,
-    /// Temporary status message (e.g., "Saved: project.grox")
-    status_message: Option<(String, Instant)>,
-    /// Pending add-track mode: waiting for type selection
-    adding_track: bool,
+    /// Open projects; the active one owns the audio engine and playback
+    tabs: Vec<ProjectTab>,
+    active_tab: usize,
+    /// Last Ctrl+C / Ctrl+B copy, shared by all tabs
+    tab_clipboard: Option<TabClipboard>,
+    /// Session notification log; the latest entry shows in the footer
+    notifications: NotificationLog,
+    /// Session stats panel (Ctrl+P) is open
+    stats_open: bool,
+    /// Exports rendering one at a time in the background, shared with MCP
+    exports: Arc<ExportQueue>,
+    /// Export queue panel (Ctrl+Q) is open, and its selected job
+    queue_open: bool,
+    queue_cursor: usize,
+    /// Open modal dialog (None when closed); it takes all keys
+    modal: Option<Modal<Dialog>>,
+    /// What was entered in earlier text prompts
+    prompt_history: PromptHistory,
+    /// Last event log id checked for agent (MCP) actions
+    agent_event_id: u64,
+    /// Audio xrun count already written to the log
+    xruns_logged: u64,
+    /// Headroom mode already warned about the master going over (until
+    /// the clip indicators are reset)
+    over_warned: bool,
+    /// Copy-tracks dialog (None when closed)
+    copy_tracks: Option<CopyTracksDialog>,
+    /// Commands recorded so far while macro recording is on
+    macro_recording: Option<Vec<Command>>,
+    /// Current or last loop-record session
+    loop_record: Option<LoopRecord>,
+    /// DJ sweep position and when a sweep key last arrived (None when neutral)
+    dj_sweep: Option<(f32, Instant)>,
+    /// Spectrum analyzer tap (shared with the audio thread)
+    analyzer: Arc<AnalyzerTap>,
+    /// Scope view state
+    scope_state: ScopeState,
+    /// Runs project scripts at bar boundaries
+    script_host: ScriptHost,
+    /// Scripts view state
+    scripts_state: ScriptsState,
+    /// Script to open in $EDITOR on the next loop iteration (needs the terminal)
+    pending_script_edit: Option<String>,
+    /// Press-and-hold detection for Space and F in the grid
+    long_press: LongPress,
+    /// Step last toggled by a Space tap (a hold undoes that toggle)
+    space_tap: (usize, usize),
+    /// Whether the step inspector popup is open (on the grid cursor)
+    step_inspector: bool,
+    /// Parameter lock editor popup, when open (on the grid cursor)
+    lock_editor: Option<LockEditor>,
+    /// Row before the last Fill tap, and whether that Fill is being held
+    fill_snapshot: Option<FillSnapshot>,
+    fill_held: bool,
+    /// Scrub key held: moving the cursor left/right plays each step column
+    scrub_held: bool,
+    /// Watches sample directories for added/edited files (None if watching failed)
+    sample_watcher: Option<SampleWatcher>,
+    /// Settings from config.json, saved whenever the settings view changes one
+    config: Config,
+    /// Settings view state, and the view it returns to
+    settings_state: SettingsState,
+    settings_return: View,
+    /// When dirty projects were last autosaved
+    last_autosave: Instant,
+    /// Plugin commands from loaded projects waiting for the user to allow them
+    pending_plugins: Vec<(usize, String)>,
+    /// Plugin commands allowed this session, on top of the config's
+    trusted_plugins: Vec<String>,
+    /// Plugins starting in the background report back here
+    plugin_tx: Sender<PluginStart>,
+    plugin_rx: Receiver<PluginStart>,
 }
 
 impl App {
-    /// Create a new application with the specified theme
-    pub fn new(theme: Theme) -> Result<Self> {
+    /// Create a new application with the specified theme, cue output and settings
+    pub fn new(theme: Theme, cue: Option<&str>, config: Config) -> Result<Self> {
         // Create command bus
         let command_bus = CommandBus::new();
         let command_sender = command_bus.sender();
         let command_receiver = command_bus.receiver();
 
         // Create audio engine with command receiver
-        let audio = AudioEngine::new(command_receiver)?;
+        let audio = AudioEngine::new(
+            command_receiver,
+            cue,
+            config.output_device.as_deref(),
+            config.buffer_size,
+        )?;
         let sequencer_state = audio.state.clone();
+        let analyzer = audio.analyzer.clone();
 
         // Create event log
         let event_log = Arc::new(RwLock::new(EventLog::new()));
 
         // Start MCP socket server (shares same command bus and state as TUI)
         let mcp_shutdown = Arc::new(AtomicBool::new(false));
+        let exports = Arc::new(ExportQueue::new());
         let mcp_handler = Arc::new(GridoxideMcp::new(
             command_sender.clone(),
             event_log.clone(),
             sequencer_state.clone(),
+            exports.clone(),
         ));
         start_socket_server(mcp_handler, mcp_shutdown.clone());
 
-        Ok(Self {
+        // Watch sample directories for hot reload
+        let sample_watcher = SampleWatcher::new(&samples::search_dirs()).ok();
+        let (plugin_tx, plugin_rx) = unbounded();
+
+        let mut app = Self {
             theme,
-            _audio: audio,
+            audio,
+            input_requested: false,
+            midi_sync_seen: MidiSync::Internal,
+            midi_port: None,
             command_sender,
             event_log,
             sequencer_state,
@@ -121,16 +417,56 @@ impl App {
             mixer_state: MixerState::new(),
             fx_editor: FxEditorState::new(),
             song_state: SongState::new(),
+            bank_state: BankState::new(),
             help_state: HelpState::new(),
             browser_state: None,
             view: View::Grid,
             prev_view: View::Grid,
             should_quit: false,
             mcp_shutdown,
-            project_path: None,
-            status_message: None,
-            adding_track: false,
-        })
+            tabs: vec![ProjectTab::new(None)],
+            active_tab: 0,
+            tab_clipboard: None,
+            notifications: NotificationLog::new(),
+            stats_open: false,
+            exports,
+            queue_open: false,
+            queue_cursor: 0,
+            modal: None,
+            prompt_history: PromptHistory::default(),
+            agent_event_id: 0,
+            xruns_logged: 0,
+            over_warned: false,
+            copy_tracks: None,
+            macro_recording: None,
+            loop_record: None,
+            dj_sweep: None,
+            analyzer,
+            scope_state: ScopeState::new(),
+            script_host: ScriptHost::new(),
+            scripts_state: ScriptsState::new(),
+            pending_script_edit: None,
+            long_press: LongPress::new(false),
+            space_tap: (0, 0),
+            step_inspector: false,
+            lock_editor: None,
+            fill_snapshot: None,
+            fill_held: false,
+            scrub_held: false,
+            sample_watcher,
+            config,
+            settings_state: SettingsState::new(),
+            settings_return: View::Grid,
+            last_autosave: Instant::now(),
+            pending_plugins: Vec::new(),
+            trusted_plugins: Vec::new(),
+            plugin_tx,
+            plugin_rx,
+        };
+        if app.config.metronome {
+            app.dispatch(Command::SetMetronome(true));
+        }
+        Ok(app)
     }
 
     /// Get a clone of the command sender (for MCP)
@@ -148,20 +484,89 @@ impl App {
         self.sequencer_state.clone()
     }
 
+    /// Load a startup project (from --project or config.json) and
+    /// optionally start playback. Saves go to this path even if loading fails.
+    pub fn open_on_start(&mut self, path: PathBuf, play: bool) {
+        self.tabs[self.active_tab].path = Some(path);
+        self.load_project_action();
+        if play {
+            self.dispatch(Command::Play);
+        }
+    }
+
+    /// MIDI ports opened from now on are the first whose name contains
+    /// `port` (from --midi-port)
+    pub fn set_midi_port(&mut self, port: Option<String>) {
+        self.midi_port = port;
+    }
+
+    /// Start with a MIDI sync mode (from --midi-sync)
+    pub fn set_midi_sync(&mut self, mode: MidiSync) {
+        self.dispatch(Command::SetMidiSync(mode));
+    }
+
+    /// Open the MIDI input for playing tracks from a controller (from --midi-in)
+    pub fn open_midi_input(&mut self) {
+        match self.audio.connect_midi_input(self.midi_port.as_deref()) {
+            Ok(Some(port)) => self.notify(Severity::Success, format!("MIDI input: {}", port)),
+            Ok(None) => {}
+            Err(e) => self.notify(Severity::Error, format!("MIDI input unavailable: {:#}", e)),
+        }
+    }
+
     /// Run the main application loop
     pub fn run(&mut self) -> Result<()> {
         let mut terminal = Self::setup_terminal()?;
+        let releases = Self::enable_key_releases(&mut terminal);
+        self.long_press = LongPress::new(releases);
+        Self::install_panic_hook(self.event_log.clone(), releases);
 
         let result = self.main_loop(&mut terminal);
 
         // Signal socket server to shut down
         self.mcp_shutdown.store(true, Ordering::Relaxed);
 
-        Self::restore_terminal(&mut terminal)?;
+        Self::restore_terminal(&mut terminal, releases)?;
 
         result
     }
 
+    /// On a UI thread panic, restore the terminal before the panic message
+    /// prints and write a crash report. Panics on other threads only write
+    /// the report (and log it) since the TUI keeps running.
+    fn install_panic_hook(event_log: Arc<RwLock<EventLog>>, releases: bool) {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // Caught by the output callback and reported as an audio error
+            if is_audio_thread() {
+                return;
+            }
+            error!("{}", info);
+            let report = write_crash_report(info, &event_log);
+            if thread::current().name() != Some("main") {
+                if let Some(mut log) = event_log.try_write_for(Duration::from_millis(200)) {
+                    let path = report
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_else(|e| e.to_string());
+                    log.log_error(format!("Panic: {} (crash report: {})", info, path));
+                }
+                return;
+            }
+            let mut stdout = io::stdout();
+            if releases {
+                let _ = stdout.execute(PopKeyboardEnhancementFlags);
+            }
+            let _ = disable_raw_mode();
+            let _ = stdout.execute(LeaveAlternateScreen);
+            let _ = stdout.execute(Show);
+            default_hook(info);
+            match report {
+                Ok(path) => eprintln!("Crash report written to {}", path.display()),
+                Err(e) => eprintln!("Failed to write crash report: {:#}", e),
+            }
+        }));
+    }
+
     /// Setup the terminal for TUI
     fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
         enable_raw_mode()?;
@@ -172,8 +577,32 @@ impl App {
         Ok(terminal)
     }
 
+    /// Ask the terminal to report key repeat/release events (kitty keyboard
+    /// protocol) for press-and-hold gestures. False if unsupported.
+    fn enable_key_releases(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> bool {
+        if !matches!(supports_keyboard_enhancement(), Ok(true)) {
+            return false;
+        }
+        terminal
+            .backend_mut()
+            .execute(PushKeyboardEnhancementFlags(
+                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                    | KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES
+                    // Shifted keys arrive as their shifted character ('A', '{')
+                    | KeyboardEnhancementFlags::REPORT_ALTERNATE_KEYS,
+            ))
+            .is_ok()
+    }
+
     /// Restore terminal to normal state
-    fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> Result<()> {
+    fn restore_terminal(
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        releases: bool,
+    ) -> Result<()> {
+        if releases {
+            let _ = terminal.backend_mut().execute(PopKeyboardEnhancementFlags);
+        }
         disable_raw_mode()?;
         terminal.backend_mut().execute(LeaveAlternateScreen)?;
         terminal.show_cursor()?;
@@ -188,13 +617,38 @@ impl App {
             // Poll for events with timeout for responsive UI (~60fps)
             if event::poll(Duration::from_millis(16))? {
                 if let Event::Key(key) = event::read()? {
-                    // Only handle key press events (not release)
-                    if key.kind == KeyEventKind::Press {
+                    // Repeats act like presses; releases only end hold gestures
+                    if key.kind == KeyEventKind::Release {
+                        self.handle_key_release(key);
+                    } else {
                         self.handle_key(key);
                     }
                 }
             }
 
+            self.poll_sample_changes();
+            self.poll_dj_sweep();
+            self.poll_input_tracks();
+            self.poll_midi_sync();
+            self.poll_midi_notes();
+            self.poll_scripts();
+            self.poll_long_press();
+            self.poll_loop_record();
+            self.poll_audio_errors();
+            self.poll_sample_captures();
+            // Synths of a replaced project are freed here, not on the audio thread
+            while self.audio.replaced_synths.try_recv().is_ok() {}
+            self.poll_plugins();
+            self.poll_headroom();
+            self.poll_waveform();
+            self.poll_export_queue();
+            self.poll_autosave();
+            self.poll_agent_actions();
+
+            if let Some(name) = self.pending_script_edit.take() {
+                self.edit_script(terminal, &name)?;
+            }
+
             if self.should_quit {
                 break;
             }
@@ -205,15 +659,109 @@ impl App {
 
     /// Dispatch a command through the command bus
     fn dispatch(&mut self, cmd: Command) {
+        // Starting the song over an empty arrangement or slots plays silence: say why
+        let song_start = match cmd {
+            Command::SetPlaybackMode(mode) => mode == PlaybackMode::Song,
+            Command::Play => self.sequencer_state.read().playback_mode == PlaybackMode::Song,
+            _ => false,
+        };
+        if song_start {
+            let warnings = self.sequencer_state.read().song_warnings();
+            if !warnings.is_empty() {
+                self.notify(Severity::Warning, warnings.join("; "));
+            }
+        }
+        if let Some(recorded) = self.macro_recording.as_mut() {
+            if cmd.is_loggable() && recorded.len() < MAX_MACRO_COMMANDS {
+                recorded.push(cmd.clone());
+            }
+        }
+        // Normalize/trim changes re-read the sample here instead of on the audio thread
+        let loads = project::sample_option_reload(&self.sequencer_state.read(), &cmd);
+        self.sequencer_state.write().apply_shared(&cmd);
         // Log the command
         self.event_log.write().log(cmd.clone(), CommandSource::Tui);
         // Send to audio thread
         self.command_sender.send(cmd, CommandSource::Tui);
+        for load in loads.into_iter().flatten() {
+            self.dispatch(load);
+        }
     }
 
-    /// Set a temporary status message shown in the footer
+    /// Show an informational message in the footer (and log it)
     fn set_status(&mut self, msg: String) {
-        self.status_message = Some((msg, Instant::now()));
+        self.notify(Severity::Info, msg);
+    }
+
+    /// Add a notification to the session log; it shows in the footer briefly
+    fn notify(&mut self, severity: Severity, msg: String) {
+        match severity {
+            Severity::Info => debug!("{}", msg),
+            Severity::Success => info!("{}", msg),
+            Severity::Warning => warn!("{}", msg),
+            Severity::Error => error!("{}", msg),
+        }
+        self.notifications.push(severity, msg);
+    }
+
+    /// Surface errors reported by the audio thread and log new xruns
+    fn poll_audio_errors(&mut self) {
+        let xruns = self.audio.xruns.load(Ordering::Relaxed);
+        if xruns > self.xruns_logged {
+            warn!(new = xruns - self.xruns_logged, total = xruns, "Audio xruns");
+            self.xruns_logged = xruns;
+        }
+        while let Ok(message) = self.audio.errors.try_recv() {
+            self.event_log.write().log_error(message.clone());
+            self.notify(Severity::Error, message);
+        }
+    }
+
+    /// Keep the params view's sample waveform in step with the track's
+    /// sample and its normalize/trim options
+    fn poll_waveform(&mut self) {
+        if self.view == View::Params {
+            self.param_editor.refresh_waveform(&self.sequencer_state.read());
+        }
+    }
+
+    /// Without the soft clipper nothing tames overs, so warn once when the
+    /// master first goes over
+    fn poll_headroom(&mut self) {
+        let (headroom, clips, peak) = {
+            let state = self.sequencer_state.read();
+            (state.headroom, state.master_meter.clips, state.master_meter.peak_hold)
+        };
+        if !headroom || clips == 0 {
+            self.over_warned = false;
+            return;
+        }
+        if !self.over_warned {
+            self.over_warned = true;
+            self.notify(
+                Severity::Warning,
+                format!("Master over: peak {:+.1} dBFS with no clipper (C in the mixer resets)", level_db(peak)),
+            );
+        }
+    }
+
+    /// Log commands the MCP agent sent since the last check, and mark the
+    /// active tab dirty on edits from either side
+    fn poll_agent_actions(&mut self) {
+        let events = self.event_log.read().get_events_since(self.agent_event_id);
+        let Some(last) = events.last() else {
+            return;
+        };
+        self.agent_event_id = last.id;
+        for event in events {
+            if event.command.modifies_project() {
+                self.tabs[self.active_tab].dirty = true;
+            }
+            if event.source == CommandSource::Mcp {
+                self.notifications
+                    .push(Severity::Info, format!("Agent: {}", event.command.description()));
+            }
+        }
     }
 
     /// Get the current number of tracks
@@ -221,17 +769,57 @@ impl App {
         self.sequencer_state.read().num_tracks()
     }
 
+    /// Triplet flag per track
+    fn track_triplets(&self) -> Vec<bool> {
+        self.sequencer_state.read().tracks.iter().map(|t| t.triplet).collect()
+    }
+
+    /// Output sample rate that loaded sample buffers are resampled to
+    fn sample_rate(&self) -> f32 {
+        self.sequencer_state.read().sample_rate
+    }
+
     /// Handle key press events
     fn handle_key(&mut self, key: KeyEvent) {
+        if self.modal.is_some() {
+            self.handle_modal_key(key);
+            return;
+        }
+
+        if self.notifications.open {
+            self.handle_notifications_key(key.code);
+            return;
+        }
+
+        if self.queue_open {
+            self.handle_queue_key(key.code);
+            return;
+        }
+
+        // Any key closes the stats panel
+        if self.stats_open {
+            self.stats_open = false;
+            return;
+        }
+
         // Browser modal intercepts all keys when open
         if self.browser_state.is_some() {
             self.handle_browser_key(key.code);
             return;
         }
 
-        // Add-track type selection mode
-        if self.adding_track {
-            self.handle_add_track_key(key.code);
+        if self.step_inspector {
+            self.handle_inspector_key(key.code);
+            return;
+        }
+
+        if self.lock_editor.is_some() {
+            self.handle_lock_editor_key(key.code);
+            return;
+        }
+
+        if self.copy_tracks.is_some() {
+            self.handle_copy_tracks_key(key.code);
             return;
         }
 
@@ -242,10 +830,18 @@ impl App {
                     self.save_project_action();
                     return;
                 }
+                KeyCode::Char('a') => {
+                    self.open_save_as_prompt();
+                    return;
+                }
                 KeyCode::Char('o') => {
                     self.load_project_action();
                     return;
                 }
+                KeyCode::Char('u') => {
+                    self.open_backup_picker();
+                    return;
+                }
                 KeyCode::Char('e') => {
                     self.export_pattern_action();
                     return;
@@ -254,6 +850,87 @@ impl App {
                     self.export_song_action();
                     return;
                 }
+                KeyCode::Char('k') => {
+                    self.export_kit_action();
+                    return;
+                }
+                KeyCode::Char('d') => {
+                    self.open_export_stems_dialog();
+                    return;
+                }
+                KeyCode::Char('g') => {
+                    self.open_settings();
+                    return;
+                }
+                KeyCode::Char('r') => {
+                    self.toggle_macro_recording();
+                    return;
+                }
+                KeyCode::Char('y') => {
+                    self.open_macro_picker();
+                    return;
+                }
+                KeyCode::Char('n') => {
+                    self.long_press.cancel();
+                    self.notifications.show();
+                    return;
+                }
+                KeyCode::Char('p') => {
+                    self.long_press.cancel();
+                    self.stats_open = true;
+                    return;
+                }
+                KeyCode::Char('q') => {
+                    self.long_press.cancel();
+                    self.queue_open = true;
+                    // Start on the oldest job still to finish
+                    let jobs = self.exports.jobs();
+                    self.queue_cursor = jobs.iter().position(|j| !j.status.is_finished()).unwrap_or(jobs.len().saturating_sub(1));
+                    return;
+                }
+                KeyCode::Char('t') => {
+                    self.open_tab_picker();
+                    return;
+                }
+                KeyCode::Char('x') => {
+                    self.close_tab_action();
+                    return;
+                }
+                KeyCode::Left => {
+                    self.switch_tab(-1);
+                    return;
+                }
+                KeyCode::Right => {
+                    self.switch_tab(1);
+                    return;
+                }
+                KeyCode::Char('c') => {
+                    self.copy_pattern_to_clipboard();
+                    return;
+                }
+                KeyCode::Char('b') => {
+                    self.copy_sound_to_clipboard();
+                    return;
+                }
+                KeyCode::Char('v') => {
+                    self.paste_from_clipboard();
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        // Alt+Left/Right: DJ sweep (held, returns to neutral on release)
+        if key.modifiers.contains(KeyModifiers::ALT) {
+            match key.code {
+                KeyCode::Left => {
+                    self.nudge_dj_sweep(-DJ_SWEEP_STEP);
+                    return;
+                }
+                KeyCode::Right => {
+                    self.nudge_dj_sweep(DJ_SWEEP_STEP);
+                    return;
+                }
                 _ => {}
             }
         }
@@ -271,214 +948,1910 @@ impl App {
             View::Mixer => self.handle_mixer_key(key.code),
             View::Fx => self.handle_fx_key(key.code),
             View::Song => self.handle_song_key(key.code),
+            View::Bank => self.handle_bank_key(key.code),
+            View::Scope => self.handle_scope_key(key.code),
+            View::Scripts => self.handle_scripts_key(key.code),
+            View::Settings => self.handle_settings_key(key.code),
             View::Help => self.handle_help_key(key.code),
         }
     }
 
     fn save_project_action(&mut self) {
-        let path = self
-            .project_path
+        let path = self.tabs[self.active_tab]
+            .path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("project.grox"));
+        self.save_project_to(path);
+    }
+
+    /// Ask for a file name and save there; the tab keeps that path
+    fn open_save_as_prompt(&mut self) {
+        self.long_press.cancel();
+        let current = self.tabs[self.active_tab]
+            .path
             .clone()
             .unwrap_or_else(|| PathBuf::from("project.grox"));
+        let prompt = Prompt::new(
+            PromptKind::SaveAs,
+            &current.to_string_lossy(),
+            Self::validate_project_path,
+            &self.prompt_history,
+        );
+        self.modal = Some(Modal::prompt("Save As", prompt, Dialog::Prompt(PromptKind::SaveAs)));
+    }
+
+    /// Type in an exact tempo
+    fn open_bpm_prompt(&mut self) {
+        self.long_press.cancel();
+        let bpm = self.sequencer_state.read().bpm;
+        let prompt = Prompt::new(PromptKind::Bpm, &format!("{}", bpm), Self::validate_bpm, &self.prompt_history);
+        self.modal = Some(Modal::prompt("BPM", prompt, Dialog::Prompt(PromptKind::Bpm)));
+    }
+
+    /// Type in a glyph for the mixer's selected track; empty goes back to
+    /// the synth type's
+    fn open_glyph_prompt(&mut self) {
+        let track = self.mixer_state.selected_track;
+        let Some(glyph) = self.sequencer_state.read().tracks.get(track).map(|t| t.glyph.clone()) else {
+            return;
+        };
+        let prompt = Prompt::new(
+            PromptKind::Glyph,
+            glyph.as_deref().unwrap_or(""),
+            Self::validate_glyph,
+            &self.prompt_history,
+        );
+        self.modal = Some(Modal::prompt("Track Glyph", prompt, Dialog::Prompt(PromptKind::Glyph)));
+    }
+
+    fn validate_project_path(text: &str) -> Result<(), String> {
+        if text.is_empty() {
+            return Err("Enter a file name".to_string());
+        }
+        let path = Path::new(text);
+        if path.file_name().is_none() || text.ends_with('/') {
+            return Err("Not a file name".to_string());
+        }
+        match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() && !dir.is_dir() => {
+                Err(format!("No such directory: {}", dir.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_bpm(text: &str) -> Result<(), String> {
+        match text.parse::<f32>() {
+            Ok(bpm) if (MIN_BPM..=MAX_BPM).contains(&bpm) => Ok(()),
+            Ok(_) => Err(format!("BPM must be {}-{}", MIN_BPM, MAX_BPM)),
+            Err(_) => Err("Not a number".to_string()),
+        }
+    }
+
+    fn validate_glyph(text: &str) -> Result<(), String> {
+        if text.chars().count() > MAX_GLYPH_CHARS {
+            return Err(format!("At most {} characters (empty: from the synth type)", MAX_GLYPH_CHARS));
+        }
+        Ok(())
+    }
+
+    fn validate_sample_dir(text: &str) -> Result<(), String> {
+        if text.is_empty() {
+            return Err("Enter a directory".to_string());
+        }
+        if !Path::new(text).is_dir() {
+            return Err(format!("No such directory: {}", text));
+        }
+        Ok(())
+    }
+
+    /// Route a key to the open modal and act on how it closed
+    fn handle_modal_key(&mut self, key: KeyEvent) {
+        let Some(modal) = self.modal.as_mut() else {
+            return;
+        };
+        let event = modal.handle_key(key);
+        if matches!(event, ModalEvent::Open) {
+            return;
+        }
+        let Some(modal) = self.modal.take() else {
+            return;
+        };
+        match (&modal.tag, event) {
+            (_, ModalEvent::Cancel) => self.set_status("Cancelled".to_string()),
+            (Dialog::Prompt(kind), ModalEvent::Text(text)) => self.prompt_entered(*kind, text),
+            (Dialog::NewTab(files), ModalEvent::Picked(index)) => {
+                let path = index.checked_sub(1).and_then(|i| files.get(i).cloned());
+                self.open_tab(path);
+            }
+            (Dialog::ReplayMacro(names), ModalEvent::Picked(index)) => {
+                let name = names[index].clone();
+                self.replay_macro(&name);
+            }
+            (Dialog::AddTrack, ModalEvent::Picked(index)) => match ADD_TRACK_TYPES.get(index) {
+                Some(&synth_type) => self.add_track_of_type(synth_type),
+                None => self.add_template_track_action(index - ADD_TRACK_TYPES.len()),
+            },
+            (Dialog::CloseTab, ModalEvent::Confirm) => self.close_tab(),
+            (Dialog::ClearPattern(index), ModalEvent::Confirm) => {
+                self.dispatch(Command::ClearPattern(*index));
+                self.set_status(format!("Cleared {}", pattern_label(*index)));
+            }
+            (Dialog::LoadKit(dir), ModalEvent::Confirm) => self.load_kit(dir),
+            (Dialog::ExportStems, ModalEvent::Submit) => self.start_stems_export(&modal),
+            (Dialog::RecordSample(track), ModalEvent::Submit) => self.start_sample_capture(*track, &modal),
+            (Dialog::RestoreBackup(backups), ModalEvent::Picked(index)) => {
+                if let Some(backup) = backups.get(index).cloned() {
+                    self.restore_backup(backup);
+                }
+            }
+            (Dialog::StartPlugin(track, command), ModalEvent::Confirm) => {
+                self.trusted_plugins.push(command.clone());
+                self.start_plugin(*track, command.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn prompt_entered(&mut self, kind: PromptKind, text: String) {
+        self.prompt_history.push(kind, &text);
+        match kind {
+            PromptKind::SaveAs => {
+                let mut path = PathBuf::from(text);
+                if path.extension().is_none() {
+                    path.set_extension("grox");
+                }
+                self.save_project_to(path);
+            }
+            PromptKind::Bpm => {
+                if let Ok(bpm) = text.parse::<f32>() {
+                    self.dispatch(Command::SetBpm(bpm));
+                    self.set_status(format!("BPM: {}", bpm));
+                }
+            }
+            PromptKind::SampleDir => {
+                let dir = PathBuf::from(text);
+                if !self.config.sample_dirs.contains(&dir) {
+                    self.set_status(format!("Sample dir added: {}", dir.display()));
+                    self.config.sample_dirs.push(dir);
+                    self.apply_sample_dirs();
+                }
+            }
+            PromptKind::Glyph => {
+                let track = self.mixer_state.selected_track;
+                let glyph = (!text.is_empty()).then_some(text);
+                self.dispatch(Command::SetTrackGlyph { track, glyph: glyph.clone() });
+                match glyph {
+                    Some(glyph) => self.set_status(format!("Track glyph: {}", glyph)),
+                    None => self.set_status("Track glyph from the synth type".to_string()),
+                }
+            }
+        }
+    }
+
+    fn save_project_to(&mut self, path: PathBuf) {
         let state = self.sequencer_state.read().clone();
+        self.back_up_project(&path);
         match project::save_project(&state, &path) {
             Ok(()) => {
                 let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
-                self.set_status(format!("Saved: {}", name));
-                self.project_path = Some(path);
+                self.notify(Severity::Success, format!("Saved: {}", name));
+                self.save_view_state(&path);
+                let tab = &mut self.tabs[self.active_tab];
+                tab.path = Some(path);
+                tab.dirty = false;
+            }
+            Err(e) => {
+                self.notify(Severity::Error, format!("Save failed, {} is unchanged: {:#}", path.display(), e));
+            }
+        }
+    }
+
+    /// Rotate the backups of a project about to be saved over (a failed
+    /// backup doesn't stop the save)
+    fn back_up_project(&mut self, path: &Path) {
+        if let Err(e) = rotate_backups(path, self.config.backups as usize) {
+            self.notify(Severity::Warning, format!("Backup failed: {:#}", e));
+        }
+    }
+
+    /// Offer the active tab's project backups, newest first, to restore one
+    fn open_backup_picker(&mut self) {
+        let Some(path) = self.tabs[self.active_tab].path.clone() else {
+            self.set_status("Project not saved yet: no backups".to_string());
+            return;
+        };
+        let backups = list_backups(&path);
+        if backups.is_empty() {
+            let hint = if self.config.backups == 0 { " (turn them on in settings, Ctrl+G)" } else { "" };
+            self.set_status(format!("No backups of {}{}", path.display(), hint));
+            return;
+        }
+        let items = backups
+            .iter()
+            .zip('1'..='9')
+            .map(|((backup, modified), key)| {
+                let name = backup.file_name().unwrap_or_default().to_string_lossy().to_string();
+                let age = modified
+                    .and_then(|m| m.elapsed().ok())
+                    .map_or(String::new(), |age| format!("  {} ago", format_age(age)));
+                (key, format!("{}{}", name, age))
+            })
+            .collect();
+        let paths = backups.into_iter().map(|(backup, _)| backup).take(9).collect();
+        self.long_press.cancel();
+        self.modal = Some(Modal::picker("Restore Backup", items, Dialog::RestoreBackup(paths)));
+    }
+
+    /// Load a backup into the active tab. The tab keeps its project path
+    /// and is left unsaved, so Ctrl+S writes the backup back over it.
+    fn restore_backup(&mut self, backup: PathBuf) {
+        match project::load_project(&backup) {
+            Ok(mut project_data) => {
+                let warnings = project_data.validate_and_clamp();
+                let project_dir = backup.parent().unwrap_or(Path::new("."));
+                self.install_project(project_data.to_state(), &project_data, project_dir);
+                let name = backup.file_name().unwrap_or_default().to_string_lossy().to_string();
+                self.notify(
+                    Severity::Success,
+                    format!("Restored {}{} (Ctrl+S to keep it)", name, clamp_summary(&warnings)),
+                );
+                self.agent_event_id = self.event_log.read().latest_id();
+                self.tabs[self.active_tab].dirty = true;
             }
             Err(e) => {
-                self.set_status(format!("Save failed: {}", e));
+                self.notify(Severity::Error, format!("Restore failed: {:#}", e));
             }
         }
     }
 
     fn load_project_action(&mut self) {
-        let path = self
-            .project_path
+        let path = self.tabs[self.active_tab]
+            .path
             .clone()
             .unwrap_or_else(|| PathBuf::from("project.grox"));
         match project::load_project(&path) {
-            Ok(project_data) => {
-                // Load sample buffers for sampler tracks
-                let project_dir = path.parent().unwrap_or(Path::new("."));
-                let sample_buffers = project_data.load_sample_buffers(project_dir);
+            Ok(mut project_data) => {
+                let warnings = project_data.validate_and_clamp();
 
+                let project_dir = path.parent().unwrap_or(Path::new("."));
                 let new_state = project_data.to_state();
-                self.dispatch(Command::LoadProject(Box::new(new_state)));
-
-                // Send sample buffers to audio thread
-                for sb in sample_buffers {
-                    self.dispatch(Command::LoadSample {
-                        track: sb.track,
-                        buffer: sb.buffer,
-                        path: sb.path,
-                    });
-                }
+                self.install_project(new_state, &project_data, project_dir);
+                self.restore_view_state(&path, &project_data);
 
                 let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
-                self.set_status(format!("Loaded: {}", name));
-                self.project_path = Some(path);
+                self.set_status(format!("Loaded: {}{}", name, clamp_summary(&warnings)));
+                // Skip the dirty flag for commands logged before the load
+                self.agent_event_id = self.event_log.read().latest_id();
+                let tab = &mut self.tabs[self.active_tab];
+                tab.path = Some(path);
+                tab.dirty = false;
             }
             Err(e) => {
-                self.set_status(format!("Load failed: {}", e));
+                self.notify(Severity::Error, format!("Load failed: {}", e));
             }
         }
     }
 
-    fn export_pattern_action(&mut self) {
-        let state = self.sequencer_state.read().clone();
-        let pat_idx = state.current_pattern;
-        let filename = format!("pattern_{:02}.wav", pat_idx);
-        let path = PathBuf::from(&filename);
-        match export_wav(&state, ExportMode::Pattern(pat_idx), &path) {
-            Ok(result) => {
-                self.set_status(format!("Exported: {} ({:.1}s)", filename, result.duration_secs));
-            }
-            Err(e) => {
-                self.set_status(format!("Export failed: {}", e));
-            }
+    /// Where the interface is, for reopening the project there. Help and
+    /// settings are left for the view they were opened from.
+    fn view_state(&self) -> ViewState {
+        let view = match self.view {
+            View::Help => self.prev_view,
+            View::Settings => self.settings_return,
+            view => view,
+        };
+        ViewState {
+            view: view.name().to_string(),
+            grid_track: self.grid_state.cursor_track,
+            grid_step: self.grid_state.cursor_step,
+            mixer_track: self.mixer_state.selected_track,
+            mixer_field: self.mixer_state.selected_field.index(),
         }
     }
 
-    fn export_song_action(&mut self) {
-        let state = self.sequencer_state.read().clone();
-        let path = PathBuf::from("song.wav");
-        match export_wav(&state, ExportMode::Song, &path) {
-            Ok(result) => {
-                self.set_status(format!("Exported: song.wav ({:.1}s)", result.duration_secs));
-            }
-            Err(e) => {
-                self.set_status(format!("Export failed: {}", e));
-            }
+    fn save_view_state(&self, project: &Path) {
+        if let Err(e) = save_view_state(project, &self.view_state()) {
+            warn!("{:#}", e);
         }
     }
 
-    /// Enter add-track mode — shows type picker in status bar
-    fn add_track_action(&mut self) {
-        let num = self.num_tracks();
-        if num >= 16 {
-            self.set_status("Max 16 tracks".to_string());
+    /// Go back to the view and selections saved with a project, kept
+    /// within its tracks and steps
+    fn restore_view_state(&mut self, project: &Path, data: &ProjectData) {
+        let view = match load_view_state(project) {
+            Ok(Some(view)) => view,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("{:#}", e);
+                return;
+            }
+        };
+        if let Some(saved) = View::SAVED.iter().find(|v| v.name() == view.view) {
+            self.view = *saved;
+        }
+        let num_tracks = data.tracks.len();
+        if num_tracks == 0 {
             return;
         }
-        self.adding_track = true;
-        self.set_status("[1]Kick [2]Snare [3]HiHat [4]Bass [5]Sampler [Esc]Cancel".to_string());
+        let track = view.grid_track.min(num_tracks - 1);
+        let length = data.pattern_bank.get(data.current_pattern).length;
+        self.grid_state.cursor_track = track;
+        self.grid_state.cursor_step = view.grid_step.min(track_steps(data.tracks[track].triplet, length) - 1);
+        self.mixer_state.selected_track = view.mixer_track.min(num_tracks - 1);
+        self.mixer_state.selected_field = MixerField::from_index(view.mixer_field);
     }
 
-    /// Handle key in add-track type selection mode
-    fn handle_add_track_key(&mut self, key: KeyCode) {
-        let synth_type = match key {
-            KeyCode::Char('1') => Some(SynthType::Kick),
-            KeyCode::Char('2') => Some(SynthType::Snare),
-            KeyCode::Char('3') => Some(SynthType::HiHat),
-            KeyCode::Char('4') => Some(SynthType::Bass),
-            KeyCode::Char('5') => Some(SynthType::Sampler),
-            KeyCode::Esc => {
-                self.adding_track = false;
-                self.set_status("Cancelled".to_string());
-                return;
+    /// Load a state into the audio engine, then its samples (resolved
+    /// against `project_dir`). External synths start in the background if
+    /// their command is allowed, otherwise once the user confirms it.
+    fn install_project(&mut self, state: SequencerState, data: &ProjectData, project_dir: &Path) {
+        let sample_rate = self.sample_rate();
+        let sample_buffers = data.load_sample_buffers(project_dir, sample_rate);
+
+        // Checked here rather than on the audio thread, which only swaps it in
+        debug_assert!(state.check_invariants().is_ok(), "{:?}", state.check_invariants());
+        self.dispatch(Command::LoadProject(Box::new(state), ParkedSynths::default()));
+
+        // Send sample buffers to audio thread
+        for sb in sample_buffers {
+            self.dispatch(sb.into_command(data.sample_stream_mb));
+        }
+        self.queue_plugins(data, true);
+    }
+
+    /// Queue a loaded project's plugins: allowed ones start in the
+    /// background if `start`, the others wait for the user to confirm them
+    fn queue_plugins(&mut self, data: &ProjectData, start: bool) {
+        // Confirmations left from the project being replaced no longer apply
+        self.pending_plugins.clear();
+        for (track, command) in data.external_commands() {
+            if self.config.plugins.contains(&command) || self.trusted_plugins.contains(&command) {
+                if start {
+                    self.start_plugin(track, command);
+                }
+            } else {
+                self.pending_plugins.push((track, command));
+            }
+        }
+    }
+
+    /// Start a track's plugin off the UI thread (see `poll_plugins`)
+    fn start_plugin(&mut self, track: usize, command: String) {
+        let sample_rate = self.sample_rate();
+        ExternalConnection::spawn_in_background(track, command, sample_rate, self.plugin_tx.clone());
+    }
+
+    /// Connect plugins that finished starting, and ask about the next
+    /// project plugin waiting to be allowed
+    fn poll_plugins(&mut self) {
+        while let Ok(start) = self.plugin_rx.try_recv() {
+            // The track may have been changed or the project replaced meanwhile
+            if self.sequencer_state.read().plugin_command(start.track) != Some(start.command.as_str()) {
+                continue;
             }
-            _ => None,
+            match start.connection {
+                Ok(connection) => {
+                    self.dispatch(Command::LoadExternalSynth { track: start.track, connection });
+                }
+                Err(e) => {
+                    self.notify(Severity::Warning, format!("Track {} plugin didn't start: {:#}", start.track + 1, e));
+                }
+            }
+        }
+        if self.modal.is_some() || self.pending_plugins.is_empty() {
+            return;
+        }
+        let (track, command) = self.pending_plugins.remove(0);
+        let message = format!(
+            "Track {} runs the command `{}`. Only start plugins you trust. Start it?",
+            track + 1,
+            command
+        );
+        self.modal = Some(Modal::confirm("Start Plugin", message, Dialog::StartPlugin(track, command)));
+    }
+
+    /// Load a full state (tab switch, sound paste) into the audio engine
+    fn install_state(&mut self, state: SequencerState) {
+        let data = ProjectData::from_state(&state);
+        let dir = self.tabs[self.active_tab].dir();
+        self.install_project(state, &data, &dir);
+    }
+
+    /// Load the active tab's parked project back into the audio engine.
+    /// Its parked synths go back as they were; without them (e.g. after
+    /// the output was reopened) samples are read and plugins started again.
+    fn unpark_active_tab(&mut self) {
+        let tab = &mut self.tabs[self.active_tab];
+        let Some(state) = tab.parked.take() else {
+            return;
         };
+        let synths = std::mem::take(&mut tab.synths);
+        if synths.len() != state.tracks.len() {
+            self.install_state(*state);
+            return;
+        }
+        let data = ProjectData::from_state(&state);
+        self.dispatch(Command::LoadProject(state, synths));
+        // Plugins still run in the parked synths; unconfirmed ones are asked about again
+        self.queue_plugins(&data, false);
+    }
 
-        if let Some(st) = synth_type {
-            self.adding_track = false;
-            let state = self.sequencer_state.read();
-            let count = state.tracks.iter()
-                .filter(|t| t.synth_type == st)
-                .count();
-            drop(state);
-            let name = if count == 0 {
-                st.display_name().to_string()
-            } else {
-                format!("{} {}", st.display_name(), count + 1)
-            };
-            self.dispatch(Command::AddTrack {
-                synth_type: st,
-                name: name.clone(),
-            });
-            self.set_status(format!("Added: {}", name));
+    /// Current engine state with the working pattern written back to its bank slot
+    fn snapshot_state(&self) -> SequencerState {
+        let mut state = self.sequencer_state.read().clone();
+        let current = state.current_pattern;
+        *state.pattern_bank.get_mut(current) = state.pattern.clone();
+        state
+    }
+
+    /// Offer an empty project or a .grox file from the working directory
+    fn open_tab_picker(&mut self) {
+        if self.tabs.len() >= MAX_TABS {
+            self.notify(Severity::Warning, format!("Max {} tabs", MAX_TABS));
+            return;
         }
+        let mut files: Vec<PathBuf> = std::fs::read_dir(".")
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok().map(|e| e.path()))
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "grox"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        files.truncate(9);
+        let mut items = vec![('0', "Empty project".to_string())];
+        items.extend(files.iter().zip('1'..='9').map(|(path, key)| {
+            (key, path.file_name().unwrap_or_default().to_string_lossy().to_string())
+        }));
+        self.long_press.cancel();
+        self.modal = Some(Modal::picker("New Tab", items, Dialog::NewTab(files)));
     }
 
-    /// Remove current track (minimum 1 track must remain)
-    fn remove_track_action(&mut self) {
-        let num_tracks = self.num_tracks();
-        if num_tracks <= 1 {
-            self.set_status("Cannot remove last track".to_string());
+    /// Open a new tab with the project at `path`, or an empty one
+    fn open_tab(&mut self, path: Option<PathBuf>) {
+        let parked = self.park_active_tab();
+        self.tabs.push(ProjectTab::new(path.clone()));
+        self.active_tab = self.tabs.len() - 1;
+        let mut empty = SequencerState::new();
+        empty.sample_rate = self.sample_rate();
+        self.install_state(empty);
+        self.collect_parked_synths(parked);
+        if path.is_some() {
+            self.load_project_action();
+        } else {
+            self.set_status(format!("Opened tab {}", self.active_tab + 1));
+        }
+    }
+
+    /// Move the engine's project into the active tab (before switching
+    /// away) and return the tab. Its synths follow once the next project
+    /// is loaded (see `collect_parked_synths`).
+    fn park_active_tab(&mut self) -> usize {
+        self.dispatch(Command::Stop);
+        // Snapshot the state the engine left after stopping
+        self.wait_for_engine();
+        let state = self.snapshot_state();
+        self.tabs[self.active_tab].parked = Some(Box::new(state));
+        self.step_inspector = false;
+        self.lock_editor = None;
+        self.fill_snapshot = None;
+        // Synths an earlier load replaced aren't this tab's
+        while self.audio.replaced_synths.try_recv().is_ok() {}
+        self.active_tab
+    }
+
+    /// Park the synths the last project load replaced with `tab`, once
+    /// the engine has handed them back
+    fn collect_parked_synths(&mut self, tab: usize) {
+        self.wait_for_engine();
+        if let Ok(synths) = self.audio.replaced_synths.try_recv() {
+            self.tabs[tab].synths = ParkedSynths::new(synths);
+        }
+    }
+
+    /// Wait (up to ENGINE_WAIT) for the audio thread to apply every
+    /// command sent so far
+    fn wait_for_engine(&self) {
+        let deadline = Instant::now() + ENGINE_WAIT;
+        while !self.command_sender.all_applied() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Switch to the tab `delta` places away (wrapping)
+    fn switch_tab(&mut self, delta: isize) {
+        let count = self.tabs.len() as isize;
+        if count < 2 {
+            return;
+        }
+        let target = (self.active_tab as isize + delta).rem_euclid(count) as usize;
+        let parked = self.park_active_tab();
+        self.active_tab = target;
+        self.unpark_active_tab();
+        self.collect_parked_synths(parked);
+        // Loading a tab isn't an edit
+        self.agent_event_id = self.event_log.read().latest_id();
+        self.set_status(format!("Tab {}: {}", target + 1, self.tabs[target].label()));
+    }
+
+    /// Close the active tab, asking first if it has unsaved changes
+    fn close_tab_action(&mut self) {
+        if self.tabs.len() < 2 {
+            self.notify(Severity::Warning, "Cannot close the last tab".to_string());
+            return;
+        }
+        if self.tabs[self.active_tab].dirty {
+            let message = format!("{} has unsaved changes.\nClose it anyway?", self.tabs[self.active_tab].label());
+            self.modal = Some(Modal::confirm("Close Tab", message, Dialog::CloseTab));
             return;
         }
+        self.close_tab();
+    }
+
+    fn close_tab(&mut self) {
+        let closed = self.tabs.remove(self.active_tab);
+        self.active_tab = self.active_tab.min(self.tabs.len() - 1);
+        self.unpark_active_tab();
+        self.agent_event_id = self.event_log.read().latest_id();
+        self.set_status(format!("Closed {}", closed.label()));
+    }
+
+    /// Copy the current pattern (both variations) to the tab clipboard
+    fn copy_pattern_to_clipboard(&mut self) {
+        let state = self.sequencer_state.read();
+        let index = state.current_pattern;
+        let pattern = state.pattern.clone();
+        drop(state);
+        self.tab_clipboard = Some(TabClipboard::Pattern(pattern));
+        self.set_status(format!("Copied pattern {} (Ctrl+V to paste)", pattern_label(index)));
+    }
+
+    /// Copy the grid cursor track's sound (synth, params, mixer, FX)
+    fn copy_sound_to_clipboard(&mut self) {
         let track = self.grid_state.cursor_track;
-        let name = {
-            let state = self.sequencer_state.read();
-            state.tracks[track].name.clone()
+        let Some(mut sound) = self.sequencer_state.read().tracks.get(track).cloned() else {
+            return;
         };
-        self.dispatch(Command::RemoveTrack(track));
-        // Adjust cursor if it's now out of bounds
-        if self.grid_state.cursor_track >= num_tracks - 1 {
-            self.grid_state.cursor_track = num_tracks - 2;
+        // Other tabs resolve samples against their own directory
+        absolute_sample_path(&mut sound.params_snapshot, &self.tabs[self.active_tab].dir());
+        let name = sound.name.clone();
+        self.tab_clipboard = Some(TabClipboard::Sound(sound));
+        self.set_status(format!("Copied sound {} (Ctrl+V to paste)", name));
+    }
+
+    /// Paste the clipboard: a pattern replaces the current pattern (tracks
+    /// matched by index), a sound is added as a new track
+    fn paste_from_clipboard(&mut self) {
+        match &self.tab_clipboard {
+            None => self.set_status("Clipboard empty (Ctrl+C pattern, Ctrl+B sound)".to_string()),
+            Some(TabClipboard::Pattern(src)) => {
+                let state = self.sequencer_state.read();
+                let index = state.current_pattern;
+                let notes: Vec<u8> = state.tracks.iter().map(|t| t.default_note).collect();
+                drop(state);
+                let mut pattern = Pattern::new_with_notes(&notes);
+                let tracks: Vec<usize> = (0..notes.len()).collect();
+                pattern.copy_tracks_from(src, &tracks, false);
+                self.dispatch(Command::ReplacePattern { index, pattern: Box::new(pattern) });
+                self.set_status(format!("Pasted pattern into {}", pattern_label(index)));
+            }
+            Some(TabClipboard::Sound(sound)) => {
+                let mut sound = sound.clone();
+                let mut state = self.snapshot_state();
+                if state.tracks.len() >= MAX_TRACKS {
+                    self.notify(Severity::Warning, format!("Max {} tracks", MAX_TRACKS));
+                    return;
+                }
+                sound.mute = false;
+                sound.solo = false;
+                for index in 0..NUM_PATTERNS {
+                    state.pattern_bank.get_mut(index).add_track(sound.default_note);
+                }
+                state.pattern = state.pattern_bank.get(state.current_pattern).clone();
+                let name = sound.name.clone();
+                state.tracks.push(sound);
+                // Rebuilds the engine's synths, which stops playback
+                self.install_state(state);
+                self.set_status(format!("Pasted sound {} as a new track", name));
+            }
         }
-        self.set_status(format!("Removed: {}", name));
     }
 
-    /// Handle keys in grid view
-    fn handle_grid_key(&mut self, key: KeyEvent) {
-        let num_tracks = self.num_tracks();
-        let has_shift = key.modifiers.contains(KeyModifiers::SHIFT);
-        let has_ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    fn export_pattern_action(&mut self) {
+        let state = self.sequencer_state.read().clone();
+        let pat_idx = state.current_pattern;
+        let path = PathBuf::from(format!("pattern_{:02}.wav", pat_idx));
+        self.queue_export(state, ExportMode::Pattern(pat_idx), ExportTarget::Wav(path));
+    }
 
-        // Handle velocity/probability adjustments with modifiers
-        if has_shift && !has_ctrl {
-            match key.code {
-                KeyCode::Up => {
-                    self.adjust_step_velocity(10);
-                    return;
+    /// Queue an export of `state`; it renders on the export worker after
+    /// any jobs already queued
+    fn queue_export(&mut self, state: SequencerState, mode: ExportMode, target: ExportTarget) {
+        let sample_rate = state.sample_rate as u32;
+        let ahead = self.exports.pending();
+        let id = self.exports.push(state, mode, target, sample_rate);
+        let label = self.exports.job(id).map(|job| job.label).unwrap_or_default();
+        let wait = if ahead > 0 { format!(", {} ahead", ahead) } else { String::new() };
+        self.set_status(format!("Queued export #{}: {}{} (Ctrl+Q: queue)", id, label, wait));
+    }
+
+    fn import_midi_action(&mut self) {
+        let path = PathBuf::from("import.mid");
+        let state = self.sequencer_state.read();
+        let default_notes: Vec<u8> = state.tracks.iter().map(|t| t.default_note).collect();
+        let triplets: Vec<bool> = state.tracks.iter().map(|t| t.triplet).collect();
+        let slots: Vec<usize> = (0..NUM_PATTERNS)
+            .filter(|&i| !state.pattern_bank.has_content(i))
+            .collect();
+        drop(state);
+        if slots.is_empty() {
+            self.notify(Severity::Warning, "No empty pattern slots".to_string());
+            return;
+        }
+        match import_midi(&path, &default_notes, &triplets, slots.len()) {
+            Ok(import) => {
+                let (sections, notes, skipped) = (import.sections.len(), import.notes, import.skipped);
+                for cmd in import.commands(&slots, true) {
+                    self.dispatch(cmd);
                 }
-                KeyCode::Down => {
-                    self.adjust_step_velocity(-10);
-                    return;
+                self.set_status(format!(
+                    "Imported import.mid: {} notes into {} patterns ({} skipped)",
+                    notes, sections, skipped
+                ));
+            }
+            Err(e) => {
+                self.notify(Severity::Error, format!("Import failed: {}", e));
+            }
+        }
+    }
+
+    /// Export every track's sound as a full-velocity one-shot into kit/
+    fn export_kit_action(&mut self) {
+        let state = self.sequencer_state.read().clone();
+        match export_hits(&state, Path::new("kit"), 127, state.sample_rate as u32) {
+            Ok(hits) => {
+                self.notify(Severity::Success, format!("Exported {} one-shot hits to kit/", hits.len()));
+            }
+            Err(e) => {
+                self.notify(Severity::Error, format!("Export failed: {}", e));
+            }
+        }
+    }
+
+    /// Ask where and what to export as stems
+    fn open_export_stems_dialog(&mut self) {
+        self.long_press.cancel();
+        let state = self.sequencer_state.read();
+        let song = !state.arrangement.is_empty();
+        let export_tone = state.export_tone;
+        drop(state);
+        let fields = vec![
+            FormField::text("Directory", "stems"),
+            FormField::choice("Mode", &["song", "pattern"], if song { 0 } else { 1 }),
+            FormField::toggle("Tone tracks", export_tone),
+        ];
+        self.modal = Some(Modal::form("Export Stems", fields, Dialog::ExportStems));
+    }
+
+    /// Ask how many steps of the audio input to record into the cursor
+    /// track, or cancel the capture already armed or running
+    fn open_record_dialog(&mut self) {
+        if self.sequencer_state.read().capture.is_some() {
+            self.dispatch(Command::CancelSampleCapture);
+            self.set_status("Sample capture cancelled".to_string());
+            return;
+        }
+        let track = self.grid_state.cursor_track;
+        let fields = vec![FormField::choice("Steps", &CAPTURE_STEP_CHOICES, 2)];
+        let title = format!("Record Input to Track {}", track + 1);
+        self.modal = Some(Modal::form(&title, fields, Dialog::RecordSample(track)));
+    }
+
+    /// Open the audio input and arm a capture of the chosen length
+    fn start_sample_capture(&mut self, track: usize, form: &Modal<Dialog>) {
+        let steps = form
+            .field(0)
+            .and_then(|f| f.text().parse().ok())
+            .unwrap_or(16)
+            .min(MAX_CAPTURE_STEPS);
+        if let Err(e) = self.audio.start_input() {
+            self.notify(Severity::Error, format!("Audio input unavailable: {}", e));
+            return;
+        }
+        let state = self.sequencer_state.read();
+        let buffer = capture_buffer(steps, state.bpm, state.sample_rate);
+        drop(state);
+        self.dispatch(Command::RecordSample { track, steps, buffer });
+        let when = if self.sequencer_state.read().playing { "from the next step" } else { "now" };
+        self.set_status(format!("Recording {} steps of input into track {} {}", steps, track + 1, when));
+    }
+
+    /// Normalize finished input captures, write them to the recordings
+    /// folder and load them into their tracks
+    fn poll_sample_captures(&mut self) {
+        while let Ok(capture) = self.audio.captures.try_recv() {
+            // Cancelled or replaced: only its buffer came back, to be freed here
+            if capture.discarded {
+                continue;
+            }
+            let (track, mut buffer, underruns) = (capture.track, capture.buffer, capture.underruns);
+            let Some(name) = self.sequencer_state.read().tracks.get(track).map(|t| t.name.clone()) else {
+                continue;
+            };
+            normalize(&mut buffer);
+            let secs = buffer.len() as f32 / self.sample_rate();
+            let file = samples::recording_path(&name);
+            let written = file
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| write_wav(&file, &buffer, self.sample_rate() as u32));
+            // A recording that couldn't be written still plays, it just isn't in saves
+            let path = match written {
+                Ok(()) => file.to_string_lossy().to_string(),
+                Err(e) => {
+                    self.notify(Severity::Warning, format!("Recording not saved, projects won't keep it: {:#}", e));
+                    String::new()
                 }
-                _ => {}
+            };
+            let options = self.sequencer_state.read().sample_options(track);
+            let buffer = prepare_sample(buffer, options);
+            let info = SampleInfo::detect(&buffer, self.sample_rate());
+            self.dispatch(Command::LoadSample { track, buffer: buffer.into(), path, options, info });
+            if underruns > 0 {
+                self.notify(
+                    Severity::Warning,
+                    format!("Recorded {:.1}s into track {}; the input fell behind for {} samples", secs, track + 1, underruns),
+                );
+            } else {
+                self.notify(Severity::Success, format!("Recorded {:.1}s into track {}", secs, track + 1));
             }
         }
+    }
 
-        if has_ctrl && !has_shift {
-            match key.code {
-                KeyCode::Up => {
-                    self.adjust_step_probability(10);
-                    return;
+    /// Queue the stems export from the options form
+    fn start_stems_export(&mut self, form: &Modal<Dialog>) {
+        let dir = form.field(0).map_or("", |f| f.text()).trim().to_string();
+        if dir.is_empty() {
+            self.notify(Severity::Warning, "Export cancelled: no directory".to_string());
+            return;
+        }
+        let mut state = self.sequencer_state.read().clone();
+        let mode = match form.field(1).map(|f| f.text()) {
+            Some("pattern") => ExportMode::Pattern(state.current_pattern),
+            _ => ExportMode::Song,
+        };
+        state.export_tone = form.field(2).is_some_and(|f| f.is_on());
+        self.queue_export(state, mode, ExportTarget::Stems(PathBuf::from(dir)));
+    }
+
+    /// Report exports the queue finished since the last frame
+    fn poll_export_queue(&mut self) {
+        for job in self.exports.take_finished() {
+            match job.status {
+                JobStatus::Done if job.warning => {
+                    self.notify(Severity::Warning, format!("Exported #{}: {}", job.id, job.message))
                 }
-                KeyCode::Down => {
-                    self.adjust_step_probability(-10);
-                    return;
+                JobStatus::Done => self.notify(Severity::Success, format!("Exported #{}: {}", job.id, job.message)),
+                JobStatus::Failed => {
+                    self.notify(Severity::Error, format!("Export #{} failed ({}): {}", job.id, job.label, job.message))
                 }
                 _ => {}
             }
         }
+    }
+
+    fn export_song_action(&mut self) {
+        let state = self.sequencer_state.read().clone();
+        self.queue_export(state, ExportMode::Song, ExportTarget::Wav(PathBuf::from("song.wav")));
+    }
+
+    /// Open the add-track picker: synth types, then saved templates
+    fn add_track_action(&mut self) {
+        let num = self.num_tracks();
+        if num >= MAX_TRACKS {
+            self.notify(Severity::Warning, format!("Max {} tracks", MAX_TRACKS));
+            return;
+        }
+        let mut items: Vec<(char, String)> = ADD_TRACK_TYPES
+            .iter()
+            .zip('1'..='9')
+            .map(|(st, key)| (key, st.display_name().to_string()))
+            .collect();
+        let names = TemplateStore::load().map(|store| store.names()).unwrap_or_default();
+        items.extend(names.into_iter().zip('a'..='z').map(|(name, key)| (key, format!("{} (template)", name))));
+        self.modal = Some(Modal::picker("Add Track", items, Dialog::AddTrack));
+    }
 
-        match key.code {
-            // Quit
-            KeyCode::Char('q') | KeyCode::Esc => {
-                self.should_quit = true;
+    /// Save the grid cursor track as a favorite, named after the track
+    fn save_track_template_action(&mut self) {
+        let track = self.grid_state.cursor_track;
+        let Some(sound) = self.sequencer_state.read().tracks.get(track).cloned() else {
+            return;
+        };
+        let result = TemplateStore::load().and_then(|mut store| {
+            store.insert(&sound.name, &sound, &self.tabs[self.active_tab].dir());
+            store.save()
+        });
+        match result {
+            Ok(()) => self.notify(
+                Severity::Success,
+                format!("Saved template {} (Shift+A to add)", sound.name),
+            ),
+            Err(e) => self.notify(Severity::Error, format!("Template save failed: {:#}", e)),
+        }
+    }
+
+    /// Add a new track from the favorite at `index` (sorted by name)
+    fn add_template_track_action(&mut self, index: usize) {
+        let store = match TemplateStore::load() {
+            Ok(store) => store,
+            Err(e) => {
+                self.notify(Severity::Error, format!("{:#}", e));
+                return;
             }
+        };
+        let Some(template) = store.templates.values().nth(index) else {
+            return;
+        };
+        let added = add_template_track(&self.sequencer_state.read(), template);
+        match added {
+            Ok(state) => {
+                let name = state.tracks.last().map(|t| t.name.clone()).unwrap_or_default();
+                // Rebuilds the engine's synths, which stops playback
+                self.install_state(state);
+                self.set_status(format!("Added: {} (template)", name));
+            }
+            Err(e) => self.notify(Severity::Warning, format!("{:#}", e)),
+        }
+    }
 
-            // Switch to params view
-            KeyCode::Tab | KeyCode::Char('e') => {
-                self.view = View::Params;
-                // Sync param editor track with grid cursor
-                self.param_editor.switch_track(self.grid_state.cursor_track, num_tracks);
+    /// Add a new track of a synth type, numbered after others of its type
+    fn add_track_of_type(&mut self, st: SynthType) {
+        let state = self.sequencer_state.read();
+        let count = state.tracks.iter()
+            .filter(|t| t.synth_type == st)
+            .count();
+        drop(state);
+        let name = if count == 0 {
+            st.display_name().to_string()
+        } else {
+            format!("{} {}", st.display_name(), count + 1)
+        };
+        self.dispatch(Command::AddTrack {
+            synth_type: st,
+            name: name.clone(),
+        });
+        self.set_status(format!("Added: {}", name));
+    }
+
+    /// Start recording a macro, or stop and save it under the next free name
+    fn toggle_macro_recording(&mut self) {
+        let Some(commands) = self.macro_recording.take() else {
+            self.macro_recording = Some(Vec::new());
+            self.set_status("Recording macro... (Ctrl+R to stop)".to_string());
+            return;
+        };
+        if commands.is_empty() {
+            self.set_status("Macro discarded (no actions recorded)".to_string());
+            return;
+        }
+        let result = MacroStore::load().and_then(|mut store| {
+            let name = store.next_name();
+            let count = commands.len();
+            store.macros.insert(name.clone(), commands);
+            store.save().map(|()| (name, count))
+        });
+        match result {
+            Ok((name, count)) => {
+                self.set_status(format!("Saved macro '{}' ({} actions)", name, count));
+            }
+            Err(e) => self.notify(Severity::Error, format!("Macro save failed: {}", e)),
+        }
+    }
+
+    /// Open the macro replay picker
+    fn open_macro_picker(&mut self) {
+        match MacroStore::load() {
+            Ok(store) if !store.macros.is_empty() => {
+                let names: Vec<String> = store.names().into_iter().take(9).collect();
+                let items = names.iter().cloned().zip('1'..='9').map(|(name, key)| (key, name)).collect();
+                self.modal = Some(Modal::picker("Replay Macro", items, Dialog::ReplayMacro(names)));
             }
+            Ok(_) => self.set_status("No macros yet (Ctrl+R to record)".to_string()),
+            Err(e) => self.notify(Severity::Error, format!("Macro load failed: {}", e)),
+        }
+    }
 
-            // Toggle step at cursor
-            KeyCode::Char(' ') | KeyCode::Enter => {
-                let cmd = Command::ToggleStep {
-                    track: self.grid_state.cursor_track,
-                    step: self.grid_state.cursor_step,
-                };
-                self.dispatch(cmd);
+    fn replay_macro(&mut self, name: &str) {
+        let commands = match MacroStore::load() {
+            Ok(store) => store.get(name).cloned().unwrap_or_default(),
+            Err(e) => {
+                self.notify(Severity::Error, format!("Macro load failed: {}", e));
+                return;
+            }
+        };
+        let count = commands.len();
+        for cmd in commands {
+            self.dispatch(cmd);
+        }
+        self.set_status(format!("Replayed '{}' ({} actions)", name, count));
+    }
+
+    /// Open the copy-tracks dialog for the current pattern
+    fn open_copy_tracks_action(&mut self) {
+        let state = self.sequencer_state.read();
+        let src = state.current_pattern;
+        let dialog = CopyTracksDialog {
+            src,
+            dst: (src + 1) % NUM_PATTERNS,
+            cursor: 0,
+            selected: vec![false; state.tracks.len()],
+            merge: false,
+        };
+        drop(state);
+        self.copy_tracks = Some(dialog);
+    }
+
+    /// Handle key while the copy-tracks dialog is open
+    fn handle_copy_tracks_key(&mut self, key: KeyCode) {
+        let Some(dialog) = self.copy_tracks.as_mut() else {
+            return;
+        };
+        let num_tracks = dialog.selected.len();
+        match key {
+            KeyCode::Esc => {
+                self.copy_tracks = None;
+                self.set_status("Cancelled".to_string());
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                dialog.cursor = (dialog.cursor + num_tracks - 1) % num_tracks;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                dialog.cursor = (dialog.cursor + 1) % num_tracks;
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                dialog.dst = (dialog.dst + NUM_PATTERNS - 1) % NUM_PATTERNS;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                dialog.dst = (dialog.dst + 1) % NUM_PATTERNS;
+            }
+            KeyCode::Char(' ') => {
+                dialog.selected[dialog.cursor] = !dialog.selected[dialog.cursor];
+            }
+            KeyCode::Char('a') => {
+                let all = dialog.selected.iter().all(|&s| s);
+                dialog.selected.iter_mut().for_each(|s| *s = !all);
+            }
+            KeyCode::Char('m') => {
+                dialog.merge = !dialog.merge;
+            }
+            KeyCode::Enter => {
+                let tracks: Vec<usize> = (0..num_tracks).filter(|&t| dialog.selected[t]).collect();
+                if tracks.is_empty() {
+                    self.set_status("Select at least one track (Space)".to_string());
+                    return;
+                }
+                let (src, dst, merge) = (dialog.src, dialog.dst, dialog.merge);
+                self.copy_tracks = None;
+                if src == dst && !merge {
+                    self.set_status("Source and destination are the same".to_string());
+                    return;
+                }
+                let count = tracks.len();
+                self.dispatch(Command::CopyPatternTracks { src, dst, tracks, merge });
+                self.set_status(format!(
+                    "{} {} track(s) {} -> {}",
+                    if merge { "Merged" } else { "Copied" },
+                    count,
+                    pattern_label(src),
+                    pattern_label(dst)
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Footer prompt for the copy-tracks dialog
+    fn copy_tracks_prompt(&self, dialog: &CopyTracksDialog) -> String {
+        let state = self.sequencer_state.read();
+        let name = state
+            .tracks
+            .get(dialog.cursor)
+            .map(|t| t.name.as_str())
+            .unwrap_or("?");
+        let count = dialog.selected.iter().filter(|&&s| s).count();
+        format!(
+            "Copy {} -> {} (Left/Right) | {} [{}] (Up/Down, Space, A:all) | {} selected | M:Merge {} | Enter:Copy Esc:Cancel",
+            pattern_label(dialog.src),
+            pattern_label(dialog.dst),
+            name,
+            if dialog.selected.get(dialog.cursor).copied().unwrap_or(false) { "x" } else { " " },
+            count,
+            if dialog.merge { "on" } else { "off" }
+        )
+    }
+
+    /// Select the pattern `offset` slots ahead, wrapping around the bank
+    fn step_pattern(&mut self, offset: usize) {
+        let current = self.sequencer_state.read().current_pattern;
+        self.dispatch(Command::SelectPattern((current + offset) % NUM_PATTERNS));
+    }
+
+    fn step_transpose(&mut self, delta: i8) {
+        let current = self.sequencer_state.read().transpose;
+        let transpose = (current + delta).clamp(-MAX_TRANSPOSE, MAX_TRANSPOSE);
+        self.dispatch(Command::SetTranspose(transpose));
+        self.set_status(format!("Transpose {:+} semitones (melodic tracks)", transpose));
+    }
+
+    /// Shift the cursor track's notes at trigger time, leaving its steps alone
+    fn step_track_transpose(&mut self, delta: i8) {
+        let track = self.grid_state.cursor_track;
+        let Some((name, current)) = self
+            .sequencer_state
+            .read()
+            .tracks
+            .get(track)
+            .map(|t| (t.name.clone(), t.transpose))
+        else {
+            return;
+        };
+        let semitones = (current + delta).clamp(-MAX_TRACK_TRANSPOSE, MAX_TRACK_TRANSPOSE);
+        self.dispatch(Command::SetTrackTranspose { track, semitones });
+        self.set_status(format!("{} transpose {:+} semitones", name, semitones));
+    }
+
+    /// Enter crossfader mode with the next slot that has content on deck B,
+    /// or leave it (deck B's slot stays if the fader is past halfway)
+    fn toggle_deck_b(&mut self) {
+        let state = self.sequencer_state.read();
+        if state.deck_b.is_some() {
+            drop(state);
+            self.dispatch(Command::SetDeckB(None));
+            self.set_status("Crossfader off".to_string());
+            return;
+        }
+        let current = state.current_pattern;
+        let slot = (1..NUM_PATTERNS)
+            .map(|offset| (current + offset) % NUM_PATTERNS)
+            .find(|&slot| state.pattern_bank.has_content(slot))
+            .unwrap_or((current + 1) % NUM_PATTERNS);
+        drop(state);
+        self.dispatch(Command::SetDeckB(Some(slot)));
+        self.set_status(format!("Deck B: {} ([ ] crossfade)", pattern_label(slot)));
+    }
+
+    fn step_deck_b(&mut self, offset: usize) {
+        let Some(slot) = self.sequencer_state.read().deck_b else {
+            return;
+        };
+        self.dispatch(Command::SetDeckB(Some((slot + offset) % NUM_PATTERNS)));
+    }
+
+    fn move_crossfader(&mut self, delta: f32) {
+        let state = self.sequencer_state.read();
+        if state.deck_b.is_none() {
+            return;
+        }
+        let position = (state.crossfader + delta).clamp(0.0, 1.0);
+        drop(state);
+        self.dispatch(Command::SetCrossfader(position));
+    }
+
+    /// Remove current track (minimum 1 track must remain)
+    fn remove_track_action(&mut self) {
+        let num_tracks = self.num_tracks();
+        if num_tracks <= 1 {
+            self.notify(Severity::Warning, "Cannot remove last track".to_string());
+            return;
+        }
+        let track = self.grid_state.cursor_track;
+        let name = {
+            let state = self.sequencer_state.read();
+            state.tracks[track].name.clone()
+        };
+        self.dispatch(Command::RemoveTrack(track));
+        // Adjust cursor if it's now out of bounds
+        if self.grid_state.cursor_track >= num_tracks - 1 {
+            self.grid_state.cursor_track = num_tracks - 2;
+        }
+        self.set_status(format!("Removed: {}", name));
+    }
+
+    /// Handle keys in grid view
+    fn handle_grid_key(&mut self, key: KeyEvent) {
+        let num_tracks = self.num_tracks();
+        let triplets = self.track_triplets();
+        let length = self.sequencer_state.read().pattern.length;
+        let has_shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let has_ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        // Handle velocity/probability adjustments with modifiers
+        if has_shift && !has_ctrl {
+            match key.code {
+                KeyCode::Up => {
+                    self.adjust_step_velocity(10);
+                    return;
+                }
+                KeyCode::Down => {
+                    self.adjust_step_velocity(-10);
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        if has_ctrl && !has_shift {
+            match key.code {
+                KeyCode::Up => {
+                    self.adjust_step_probability(10);
+                    return;
+                }
+                KeyCode::Down => {
+                    self.adjust_step_probability(-10);
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        match key.code {
+            // Quit
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_quit = true;
+            }
+
+            // Switch to params view
+            KeyCode::Tab | KeyCode::Char('e') => {
+                self.view = View::Params;
+                // Sync param editor track with grid cursor
+                self.param_editor.switch_track(self.grid_state.cursor_track, num_tracks);
+            }
+
+            // Space: tap toggles the step, hold opens the step inspector
+            KeyCode::Char(' ') => {
+                if let Some(gesture) = self.long_press.key_event(key.code, key.kind) {
+                    self.grid_gesture(key.code, gesture);
+                }
+            }
+
+            // Toggle step at cursor
+            KeyCode::Enter => {
+                let cmd = Command::ToggleStep {
+                    track: self.grid_state.cursor_track,
+                    step: self.grid_state.cursor_step,
+                };
+                self.dispatch(cmd);
+            }
+
+            // Play/Pause toggle
+            KeyCode::Char('p') => {
+                let playing = self.sequencer_state.read().playing;
+                if playing {
+                    self.dispatch(Command::Pause);
+                } else {
+                    self.dispatch(Command::Play);
+                }
+            }
+
+            // Stop (reset to beginning)
+            KeyCode::Char('s') => {
+                self.dispatch(Command::Stop);
+            }
+
+            // Navigation
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.grid_state.move_cursor(-1, 0, num_tracks, &triplets, length);
+                if self.scrub_held {
+                    self.scrub_cursor_column();
+                }
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.grid_state.move_cursor(1, 0, num_tracks, &triplets, length);
+                if self.scrub_held {
+                    self.scrub_cursor_column();
+                }
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.grid_state.move_cursor(0, -1, num_tracks, &triplets, length);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.grid_state.move_cursor(0, 1, num_tracks, &triplets, length);
+            }
+
+            // Velocity adjust with 'v' and 'V'
+            KeyCode::Char('v') => {
+                self.adjust_step_velocity(-10);
+            }
+            KeyCode::Char('V') => {
+                self.adjust_step_velocity(10);
+            }
+
+            // Probability adjust with 'r' and 'R' (for "random")
+            KeyCode::Char('r') => {
+                self.adjust_step_probability(-10);
+            }
+            KeyCode::Char('R') => {
+                self.adjust_step_probability(10);
+            }
+
+            // BPM control
+            KeyCode::Char('t') => self.open_bpm_prompt(),
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                let current_bpm = self.sequencer_state.read().bpm;
+                self.dispatch(Command::SetBpm(current_bpm + 5.0));
+            }
+            KeyCode::Char('-') | KeyCode::Char('_') => {
+                let current_bpm = self.sequencer_state.read().bpm;
+                self.dispatch(Command::SetBpm(current_bpm - 5.0));
+            }
+
+            // Global transpose of melodic tracks
+            KeyCode::Char('(') => self.step_transpose(-1),
+            KeyCode::Char(')') => self.step_transpose(1),
+            // Cursor track transpose: semitone / octave
+            KeyCode::Char(';') => self.step_track_transpose(-1),
+            KeyCode::Char('\'') => self.step_track_transpose(1),
+            KeyCode::Char(':') => self.step_track_transpose(-12),
+            KeyCode::Char('"') => self.step_track_transpose(12),
+
+            // Clock source: internal / MIDI slave / MIDI master
+            KeyCode::Char('M') => self.cycle_midi_sync(),
+
+            // Clear current track
+            KeyCode::Char('c') => {
+                self.dispatch(Command::ClearTrack(self.grid_state.cursor_track));
+            }
+
+            // Scrub: tap plays the cursor's step column, hold and move to step through
+            KeyCode::Char('b') => {
+                if let Some(gesture) = self.long_press.key_event(key.code, key.kind) {
+                    self.grid_gesture(key.code, gesture);
+                }
+            }
+
+            // Fill current track (hold for a momentary fill)
+            KeyCode::Char('f') => {
+                if let Some(gesture) = self.long_press.key_event(key.code, key.kind) {
+                    self.grid_gesture(key.code, gesture);
+                }
+            }
+
+            // Note down 1 semitone
+            KeyCode::Char('[') => {
+                self.adjust_step_note(-1);
+            }
+            // Note up 1 semitone
+            KeyCode::Char(']') => {
+                self.adjust_step_note(1);
+            }
+            // Note down 1 octave (Shift+[)
+            KeyCode::Char('{') => {
+                self.adjust_step_note(-12);
+            }
+            // Note up 1 octave (Shift+])
+            KeyCode::Char('}') => {
+                self.adjust_step_note(12);
+            }
+
+            // Pads: play tracks 1-9 (recorded while loop-recording)
+            KeyCode::Char(c @ '1'..='9') => {
+                self.pad_hit(c as usize - '1' as usize);
+            }
+            // Utility tone pad: start/stop, W picks what it plays
+            KeyCode::Char('0') => self.toggle_tone(),
+            KeyCode::Char('W') => self.cycle_tone_mode(),
+            KeyCode::Char('z') => self.step_swing(-SWING_STEP),
+            KeyCode::Char('Z') => self.step_swing(SWING_STEP),
+            KeyCode::Char('n') => self.step_pattern_length(-1),
+            KeyCode::Char('N') => self.step_pattern_length(1),
+            KeyCode::Char('w') => self.toggle_track_wrap(),
+            KeyCode::Char('H') => {
+                self.grid_state.heat_map = !self.grid_state.heat_map;
+                self.set_status(format!("Heat map: {}", if self.grid_state.heat_map { "on" } else { "off" }));
+            }
+            // Loop-record into the next empty slot / stop
+            KeyCode::Char('O') => {
+                self.toggle_loop_record();
+            }
+            KeyCode::Char('u') => {
+                self.undo_loop_pass();
+            }
+            // Quantize current track halfway to the grid
+            KeyCode::Char('Q') => {
+                let track = self.grid_state.cursor_track;
+                self.dispatch(Command::QuantizeTrack { track, strength: 50 });
+                self.set_status(format!("Quantized track {} by 50%", track + 1));
+            }
+
+            // Pattern selection
+            KeyCode::Char(',') => self.step_pattern(NUM_PATTERNS - 1),
+            KeyCode::Char('.') => self.step_pattern(1),
+            // Bank paging: same slot in the previous/next bank
+            KeyCode::Char('<') => self.step_pattern(NUM_PATTERNS - PATTERNS_PER_BANK),
+            KeyCode::Char('>') => self.step_pattern(PATTERNS_PER_BANK),
+
+            // Toggle variation A/B
+            KeyCode::Char('x') => {
+                self.dispatch(Command::ToggleVariation);
+            }
+            // Copy current variation to other (Shift+X)
+            KeyCode::Char('X') => {
+                let current_var = self.sequencer_state.read().current_variation;
+                let (from, to) = match current_var {
+                    Variation::A => (Variation::A, Variation::B),
+                    Variation::B => (Variation::B, Variation::A),
+                };
+                self.dispatch(Command::CopyVariation { from, to });
+                self.set_status(format!("Copied variation {:?} to {:?}", from, to));
+            }
+
+            // Tie the cursor step onto the one before it
+            KeyCode::Char('i') => self.toggle_step_tie(),
+
+            // Note-off step: release the track's note here
+            KeyCode::Char('o') => self.toggle_step_off(),
+
+            // Acid articulation: accent (a) and slide (Shift+S)
+            KeyCode::Char('a') => self.toggle_step_accent(),
+            KeyCode::Char('S') => self.toggle_step_slide(),
+
+            // Parameter locks of the cursor step (Shift+K)
+            KeyCode::Char('K') => {
+                self.long_press.cancel();
+                self.lock_editor = Some(LockEditor::new());
+            }
+
+            // Record the audio input into the cursor track (Shift+I)
+            KeyCode::Char('I') => self.open_record_dialog(),
+
+            // Open sample browser for sampler tracks (Shift+L)
+            KeyCode::Char('L') => {
+                self.open_browser_for_track(self.grid_state.cursor_track);
+            }
+
+            // Chop sampler track into slices across the steps (Shift+C)
+            KeyCode::Char('C') => {
+                let track = self.grid_state.cursor_track;
+                let is_sampler = self
+                    .sequencer_state
+                    .read()
+                    .tracks
+                    .get(track)
+                    .is_some_and(|t| t.synth_type == SynthType::Sampler);
+                if is_sampler {
+                    self.dispatch(Command::ChopToSteps(track));
+                    self.set_status(format!("Chopped track {} into {} slices", track + 1, STEPS));
+                } else {
+                    self.notify(Severity::Warning, "Chop needs a sampler track".to_string());
+                }
+            }
+
+            // Add track (Shift+A cycles: sampler, kick, snare, hihat, bass)
+            KeyCode::Char('A') => {
+                self.add_track_action();
+            }
+
+            // Remove current track (Shift+D)
+            KeyCode::Char('D') => {
+                self.remove_track_action();
+            }
+
+            // Save current track as a template favorite (Shift+F)
+            KeyCode::Char('F') => {
+                self.save_track_template_action();
+            }
+
+            // Toggle triplet grid for current track
+            KeyCode::Char('T') => {
+                let track = self.grid_state.cursor_track;
+                if let Some(&triplet) = triplets.get(track) {
+                    self.dispatch(Command::SetTrackTriplet { track, enabled: !triplet });
+                    // Keep the cursor on a step that exists in the new grid
+                    let (from, to) = (track_steps(triplet, length), track_steps(!triplet, length));
+                    self.grid_state.cursor_step = (self.grid_state.cursor_step * to / from).min(to - 1);
+                    let name = self.sequencer_state.read().tracks[track].name.clone();
+                    self.set_status(format!(
+                        "{}: {} steps{}",
+                        name,
+                        to,
+                        if triplet { "" } else { " (triplet)" }
+                    ));
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Tap and hold actions for Space (step inspector) and F (momentary fill)
+    fn grid_gesture(&mut self, code: KeyCode, gesture: Gesture) {
+        let track = self.grid_state.cursor_track;
+        let step = self.grid_state.cursor_step;
+        match (code, gesture) {
+            (KeyCode::Char(' '), Gesture::Tap) => {
+                self.space_tap = (track, step);
+                self.dispatch(Command::ToggleStep { track, step });
+            }
+            (KeyCode::Char(' '), Gesture::HoldStart) => {
+                // Undo the tap's toggle and inspect that step. The inspector
+                // is modal, so the gesture ends here rather than on release.
+                let (track, step) = self.space_tap;
+                self.dispatch(Command::ToggleStep { track, step });
+                self.grid_state.cursor_track = track;
+                self.grid_state.cursor_step = step;
+                self.long_press.cancel();
+                self.step_inspector = true;
+            }
+            (KeyCode::Char('f'), Gesture::Tap) => {
+                let state = self.sequencer_state.read();
+                let variation = state.current_variation;
+                self.fill_snapshot = state.pattern.steps(variation).get(track).map(|row| FillSnapshot {
+                    pattern: state.current_pattern,
+                    track,
+                    variation,
+                    row: *row,
+                });
+                drop(state);
+                self.fill_held = false;
+                self.dispatch(Command::FillTrack(track));
+            }
+            (KeyCode::Char('f'), Gesture::HoldStart) => {
+                if self.fill_snapshot.is_some() {
+                    self.fill_held = true;
+                    self.set_status(if self.long_press.releases() {
+                        "Fill held: release F to restore".to_string()
+                    } else {
+                        "Fill latched: press F to restore".to_string()
+                    });
+                }
+            }
+            (KeyCode::Char('f'), Gesture::HoldEnd) => {
+                if std::mem::take(&mut self.fill_held) {
+                    self.restore_fill();
+                }
+            }
+            (KeyCode::Char('b'), Gesture::Tap) => {
+                self.scrub_held = false;
+                self.scrub_cursor_column();
+            }
+            (KeyCode::Char('b'), Gesture::HoldStart) => {
+                self.scrub_held = true;
+                self.set_status(if self.long_press.releases() {
+                    "Scrubbing: Left/Right step through, release B to stop".to_string()
+                } else {
+                    "Scrubbing: Left/Right step through, press B to stop".to_string()
+                });
+            }
+            (KeyCode::Char('b'), Gesture::HoldEnd) => {
+                self.scrub_held = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Play the step column under the grid cursor once (scrubbing). Triplet
+    /// cursors play the column their step starts in.
+    fn scrub_cursor_column(&mut self) {
+        let state = self.sequencer_state.read();
+        if state.playing {
+            drop(state);
+            self.set_status("Stop playback to scrub".to_string());
+            return;
+        }
+        let length = state.pattern.length;
+        let triplet = state.tracks.get(self.grid_state.cursor_track).is_some_and(|t| t.triplet);
+        drop(state);
+        let step = self.grid_state.cursor_step * length / track_steps(triplet, length);
+        self.dispatch(Command::ScrubStep(step));
+    }
+
+    /// Put back the row a held Fill replaced
+    fn restore_fill(&mut self) {
+        let Some(snap) = self.fill_snapshot.take() else {
+            return;
+        };
+        let state = self.sequencer_state.read();
+        let mut pattern = state.pattern_bank.get(snap.pattern).clone();
+        drop(state);
+        // Skip it if the pattern's length changed meanwhile
+        let Some(row) = pattern.steps_mut(snap.variation).get_mut(snap.track).filter(|r| r.len() == snap.row.len()) else {
+            return;
+        };
+        *row = snap.row;
+        self.dispatch(Command::ReplacePattern {
+            index: snap.pattern,
+            pattern: Box::new(pattern),
+        });
+        self.set_status("Fill released".to_string());
+    }
+
+    /// Key releases only matter for hold gestures in the grid
+    fn handle_key_release(&mut self, key: KeyEvent) {
+        if self.view != View::Grid || self.step_inspector || self.lock_editor.is_some() || self.browser_state.is_some() {
+            return;
+        }
+        if let KeyCode::Char(' ' | 'f') = key.code {
+            if let Some(gesture) = self.long_press.key_event(key.code, key.kind) {
+                self.grid_gesture(key.code, gesture);
+            }
+        }
+    }
+
+    /// Start loop-recording into the next empty slot, or stop recording
+    fn toggle_loop_record(&mut self) {
+        if let Some(rec) = self.loop_record.as_mut().filter(|r| r.recording || r.snapshots.is_empty()) {
+            let label = pattern_label(rec.pattern);
+            if rec.recording {
+                rec.recording = false;
+                self.set_status(format!("Loop-record into {} stopped (U undoes the last pass)", label));
+            } else {
+                self.loop_record = None;
+                self.set_status(format!("Loop-record into {} cancelled", label));
+            }
+            return;
+        }
+        let state = self.sequencer_state.read();
+        let dst = (0..NUM_PATTERNS).find(|&i| !state.pattern_bank.has_content(i));
+        let (playing, mode) = (state.playing, state.playback_mode);
+        drop(state);
+        let Some(dst) = dst else {
+            self.notify(Severity::Warning, "No empty pattern slot to record into".to_string());
+            return;
+        };
+        if mode == PlaybackMode::Song {
+            self.dispatch(Command::SetPlaybackMode(PlaybackMode::Pattern));
+        }
+        self.dispatch(Command::SelectPattern(dst));
+        if !playing {
+            self.dispatch(Command::Play);
+        }
+        self.loop_record = Some(LoopRecord {
+            pattern: dst,
+            recording: false,
+            pass: 0,
+            last_step: 0,
+            snapshots: Vec::new(),
+        });
+        self.set_status(format!(
+            "Loop-record armed: {} bars into {}, pads 1-9 (O stops)",
+            LOOP_RECORD_BARS,
+            pattern_label(dst)
+        ));
+    }
+
+    /// Follow the loop-record session: start once the slot plays, snapshot
+    /// each pass at the bar line, stop after the last pass
+    fn poll_loop_record(&mut self) {
+        let Some(rec) = self.loop_record.as_mut() else {
+            return;
+        };
+        let state = self.sequencer_state.read();
+        let armed = rec.snapshots.is_empty();
+        if !armed && !rec.recording {
+            return;
+        }
+        // Armed: wait for Play and the switch to the slot at the bar line
+        if armed && (!state.playing || state.current_pattern != rec.pattern) {
+            return;
+        }
+        if !state.playing || state.current_pattern != rec.pattern {
+            drop(state);
+            rec.recording = false;
+            let passes = rec.pass;
+            self.set_status(format!("Loop-record stopped after {} full bar(s)", passes));
+            return;
+        }
+        let step = state.current_step;
+        if armed {
+            rec.recording = true;
+            rec.last_step = step;
+            rec.snapshots.push(state.pattern.clone());
+            return;
+        }
+        let wrapped = step < rec.last_step;
+        rec.last_step = step;
+        if !wrapped {
+            return;
+        }
+        rec.pass += 1;
+        if rec.pass < LOOP_RECORD_BARS {
+            rec.snapshots.push(state.pattern.clone());
+            return;
+        }
+        rec.recording = false;
+        let label = pattern_label(rec.pattern);
+        drop(state);
+        self.notify(
+            Severity::Success,
+            format!("Recorded {} bars into {} (U undoes the last pass)", LOOP_RECORD_BARS, label),
+        );
+    }
+
+    /// Undo the last loop-record pass. While recording, the undone pass is
+    /// recorded again.
+    fn undo_loop_pass(&mut self) {
+        let Some(rec) = self.loop_record.as_mut().filter(|r| !r.snapshots.is_empty()) else {
+            self.set_status("Nothing to undo (O starts loop-record)".to_string());
+            return;
+        };
+        let index = if rec.recording {
+            rec.pass.saturating_sub(1)
+        } else {
+            rec.snapshots.len() - 1
+        };
+        let pattern = rec.snapshots[index].clone();
+        let slot = rec.pattern;
+        if rec.recording {
+            rec.snapshots.truncate(index + 1);
+        } else {
+            rec.snapshots.truncate(index);
+        }
+        rec.pass = index;
+        if !rec.recording && rec.snapshots.is_empty() {
+            self.loop_record = None;
+        }
+        self.dispatch(Command::ReplacePattern {
+            index: slot,
+            pattern: Box::new(pattern),
+        });
+        self.set_status(format!("Undid loop-record pass {} in {}", index + 1, pattern_label(slot)));
+    }
+
+    /// Pad hit on a track, written into the pattern while loop-recording
+    fn pad_hit(&mut self, track: usize) {
+        if track >= self.num_tracks() {
+            return;
+        }
+        let record = self.loop_record.as_ref().is_some_and(|r| r.recording);
+        self.dispatch(Command::PadHit {
+            track,
+            velocity: 127,
+            record,
+        });
+    }
+
+    /// Play notes from the MIDI input: drum channel pads play the tracks in
+    /// order, other channels the grid cursor track at the played pitch.
+    /// While loop-recording they're written to the nearest step.
+    fn poll_midi_notes(&mut self) {
+        let notes: Vec<NoteOn> = self.audio.midi_notes.try_iter().collect();
+        if notes.is_empty() {
+            return;
+        }
+        let default_notes: Vec<u8> = self
+            .sequencer_state
+            .read()
+            .tracks
+            .iter()
+            .map(|t| t.default_note)
+            .collect();
+        let record = self.loop_record.as_ref().is_some_and(|r| r.recording);
+        for input in notes {
+            let (track, note) = match input.pad_track(default_notes.len()) {
+                Some(track) => (track, default_notes[track]),
+                None => (self.grid_state.cursor_track, input.note),
+            };
+            if track >= default_notes.len() {
+                continue;
+            }
+            let velocity = input.velocity;
+            self.dispatch(if record {
+                Command::RecordNote { track, note, velocity }
+            } else {
+                Command::PlayNote { track, note, velocity }
+            });
+        }
+    }
+
+    /// Turn a key held past the hold time into a hold gesture
+    fn poll_long_press(&mut self) {
+        if let Some((code, gesture)) = self.long_press.poll() {
+            if self.view == View::Grid && !self.step_inspector && self.lock_editor.is_none() {
+                self.grid_gesture(code, gesture);
+            } else {
+                self.long_press.cancel();
+            }
+        }
+    }
+
+    /// Handle keys in the step inspector popup
+    fn handle_inspector_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char(' ') => {
+                self.step_inspector = false;
+            }
+            KeyCode::Up => self.adjust_step_velocity(10),
+            KeyCode::Down => self.adjust_step_velocity(-10),
+            KeyCode::Right => self.adjust_step_probability(10),
+            KeyCode::Left => self.adjust_step_probability(-10),
+            KeyCode::Char('[') => self.adjust_step_note(-1),
+            KeyCode::Char(']') => self.adjust_step_note(1),
+            KeyCode::Char('{') => self.adjust_step_note(-12),
+            KeyCode::Char('}') => self.adjust_step_note(12),
+            KeyCode::Char('i') => self.toggle_step_tie(),
+            KeyCode::Char('o') => self.toggle_step_off(),
+            KeyCode::Char('a') => self.toggle_step_accent(),
+            KeyCode::Char('S') => self.toggle_step_slide(),
+            KeyCode::Char('t') => {
+                self.dispatch(Command::ToggleStep {
+                    track: self.grid_state.cursor_track,
+                    step: self.grid_state.cursor_step,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle keys in the parameter lock editor popup
+    fn handle_lock_editor_key(&mut self, key: KeyCode) {
+        let Some(editor) = self.lock_editor.as_mut() else {
+            return;
+        };
+        let track = self.grid_state.cursor_track;
+        let step = self.grid_state.cursor_step;
+        let state = self.sequencer_state.read();
+        let descriptors = get_lockable_descriptors(&state, track);
+        let variation = state.current_variation;
+        let locked = state.pattern.param_locks(track, step, variation).count();
+        let Some(desc) = descriptors.get(editor.param).cloned() else {
+            drop(state);
+            self.lock_editor = None;
+            return;
+        };
+        let lock = state.pattern.param_lock(track, step, variation, &desc.key);
+        let own = get_snapshot_param_value(&state, track, &desc.key);
+        drop(state);
+
+        let delta = match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('K') => {
+                self.lock_editor = None;
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                editor.move_cursor(-1, descriptors.len());
+                return;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                editor.move_cursor(1, descriptors.len());
+                return;
+            }
+            KeyCode::Char('x') | KeyCode::Delete | KeyCode::Backspace => {
+                if lock.is_some() {
+                    self.dispatch(Command::SetParamLock { track, step, key: desc.key, value: None });
+                }
+                return;
+            }
+            KeyCode::Char('c') => {
+                self.dispatch(Command::ClearParamLocks { track, step });
+                self.set_status(format!("Cleared locks on step {}", step + 1));
+                return;
+            }
+            KeyCode::Left | KeyCode::Char('h') => -0.02,
+            KeyCode::Right | KeyCode::Char('l') => 0.02,
+            KeyCode::Char('H') => -0.1,
+            KeyCode::Char('L') => 0.1,
+            _ => return,
+        };
+        if lock.is_none() && locked >= MAX_LOCKS_PER_STEP {
+            self.notify(Severity::Warning, format!("A step holds at most {} locks", MAX_LOCKS_PER_STEP));
+            return;
+        }
+        // A new lock starts from the track's own value
+        let value = (lock.unwrap_or(own) + delta * (desc.max - desc.min)).clamp(desc.min, desc.max);
+        self.dispatch(Command::SetParamLock { track, step, key: desc.key, value: Some(value) });
+    }
+
+    /// Handle keys in params view
+    fn handle_params_key(&mut self, key: KeyCode) {
+        if self.param_editor.layer_index.is_some() && self.handle_layer_map_key(key) {
+            return;
+        }
+        let num_tracks = self.num_tracks();
+        let param_count = {
+            let state = self.sequencer_state.read();
+            get_param_descriptors(&state, self.param_editor.track).len()
+        };
+
+        match key {
+            // Quit
+            KeyCode::Char('q') => {
+                self.should_quit = true;
+            }
+
+            // Cycle to mixer view (Esc goes back to grid)
+            KeyCode::Tab => {
+                self.view = View::Mixer;
+            }
+            KeyCode::Esc => {
+                self.view = View::Grid;
+            }
+
+            // Navigate params
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.param_editor.move_selection(-1, param_count);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.param_editor.move_selection(1, param_count);
+            }
+
+            // Switch track (1-9)
+            KeyCode::Char(c @ '1'..='9') => {
+                let track = (c as usize) - ('1' as usize);
+                self.param_editor.switch_track(track, num_tracks);
+            }
+
+            // Adjust value (fine)
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.adjust_current_param(-0.05);
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.adjust_current_param(0.05);
+            }
+
+            // Adjust value (coarse)
+            KeyCode::Char('[') => {
+                self.adjust_current_param(-0.2);
+            }
+            KeyCode::Char(']') => {
+                self.adjust_current_param(0.2);
+            }
+
+            // Open sample browser for sampler tracks (Shift+L)
+            KeyCode::Char('L') => {
+                self.open_browser_for_track(self.param_editor.track);
+            }
+
+            // Reload sample from disk (Shift+R)
+            KeyCode::Char('R') => {
+                self.reload_sample_action(self.param_editor.track);
+            }
+
+            // Cycle the project key: none, C .. B
+            KeyCode::Char('K') => {
+                let key = self.sequencer_state.read().key;
+                let next = match key {
+                    None => Some(0),
+                    Some(k) if k >= 11 => None,
+                    Some(k) => Some(k + 1),
+                };
+                self.dispatch(Command::SetProjectKey(next));
+                match next {
+                    Some(k) => self.set_status(format!("Project key: {}", NOTE_NAMES[k as usize])),
+                    None => self.set_status("Project key cleared".to_string()),
+                }
+            }
+
+            // Tune the sample's detected root to the project key
+            KeyCode::Char('T') => {
+                self.tune_sample_to_key_action(self.param_editor.track);
+            }
+
+            // Repitch a loop so its detected tempo matches the project
+            KeyCode::Char('B') => {
+                self.fit_sample_to_bpm_action(self.param_editor.track);
+            }
+
+            // Edit the sampler's velocity layer map
+            KeyCode::Char('V') => {
+                let track = self.param_editor.track;
+                let state = self.sequencer_state.read();
+                let is_sampler = state.tracks.get(track).is_some_and(|t| t.synth_type == SynthType::Sampler);
+                let has_layers = !sample_layers(&state, track).is_empty();
+                drop(state);
+                if !is_sampler {
+                    self.notify(Severity::Warning, "Velocity layers need a sampler track".to_string());
+                } else if has_layers {
+                    self.param_editor.layer_index = Some(0);
+                } else {
+                    self.open_layer_browser(track, 0);
+                }
             }
 
-            // Play/Pause toggle
+            // Play/Stop still works in params view
             KeyCode::Char('p') => {
                 let playing = self.sequencer_state.read().playing;
                 if playing {
@@ -488,182 +2861,389 @@ impl App {
                 }
             }
 
-            // Stop (reset to beginning)
             KeyCode::Char('s') => {
                 self.dispatch(Command::Stop);
             }
 
-            // Navigation
+            _ => {}
+        }
+    }
+
+    /// Handle keys while editing the velocity layer map. Returns false for
+    /// keys left to the params view (transport, quit, view switching).
+    fn handle_layer_map_key(&mut self, key: KeyCode) -> bool {
+        let track = self.param_editor.track;
+        let layers = sample_layers(&self.sequencer_state.read(), track);
+        let Some(index) = self.param_editor.layer_index.filter(|&i| i < layers.len()) else {
+            self.param_editor.layer_index = None;
+            return false;
+        };
+        let layer = &layers[index];
+        let set_range = |min: i32, max: i32| Command::SetSampleLayerRange {
+            track,
+            layer: index,
+            min_velocity: min.clamp(0, 127) as u8,
+            max_velocity: max.clamp(0, 127) as u8,
+        };
+        let (min, max) = (layer.min_velocity as i32, layer.max_velocity as i32);
+        match key {
+            KeyCode::Esc | KeyCode::Char('V') => {
+                self.param_editor.layer_index = None;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.param_editor.layer_index = Some((index + layers.len() - 1) % layers.len());
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.param_editor.layer_index = Some((index + 1) % layers.len());
+            }
+            // Low edge
             KeyCode::Left | KeyCode::Char('h') => {
-                self.grid_state.move_cursor(-1, 0, num_tracks);
+                self.dispatch(set_range(min - 10, max));
             }
             KeyCode::Right | KeyCode::Char('l') => {
-                self.grid_state.move_cursor(1, 0, num_tracks);
+                self.dispatch(set_range((min + 10).min(max), max));
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.grid_state.move_cursor(0, -1, num_tracks);
+            // High edge
+            KeyCode::Char('[') => {
+                self.dispatch(set_range(min, (max - 10).max(min)));
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.grid_state.move_cursor(0, 1, num_tracks);
+            KeyCode::Char(']') => {
+                self.dispatch(set_range(min, max + 10));
             }
-
-            // Velocity adjust with 'v' and 'V'
-            KeyCode::Char('v') => {
-                self.adjust_step_velocity(-10);
+            KeyCode::Char('a') => {
+                if layers.len() >= MAX_SAMPLE_LAYERS {
+                    self.notify(Severity::Warning, format!("Max {} velocity layers", MAX_SAMPLE_LAYERS));
+                } else {
+                    self.open_layer_browser(track, layers.len());
+                }
             }
-            KeyCode::Char('V') => {
-                self.adjust_step_velocity(10);
+            KeyCode::Enter => {
+                self.open_layer_browser(track, index);
             }
-
-            // Probability adjust with 'r' and 'R' (for "random")
-            KeyCode::Char('r') => {
-                self.adjust_step_probability(-10);
+            KeyCode::Char('x') => {
+                self.dispatch(Command::RemoveSampleLayer { track, layer: index });
+                self.param_editor.layer_index = match layers.len() - 1 {
+                    0 => None,
+                    remaining => Some(index.min(remaining - 1)),
+                };
+                self.set_status(format!("Removed velocity layer {}", index + 1));
             }
-            KeyCode::Char('R') => {
-                self.adjust_step_probability(10);
+            _ => return false,
+        }
+        true
+    }
+
+    /// Open the sample browser to load a velocity layer (`layer` past the end adds one)
+    fn open_layer_browser(&mut self, track: usize, layer: usize) {
+        self.open_browser_for_track(track);
+        if let Some(ref mut browser) = self.browser_state {
+            browser.target_layer = Some(layer);
+        }
+    }
+
+    /// Set a sampler's pitch shift so its detected root plays the project
+    /// key's tonic (C without a key) from C4
+    fn tune_sample_to_key_action(&mut self, track: usize) {
+        let state = self.sequencer_state.read();
+        let sampler = state.tracks.get(track).filter(|t| t.synth_type == SynthType::Sampler);
+        let is_sampler = sampler.is_some();
+        let detected = sampler.and_then(|t| t.params_snapshot.get("detected_note")?.as_f64());
+        let key = state.key;
+        drop(state);
+        if !is_sampler {
+            self.notify(Severity::Warning, "Tuning needs a sampler track".to_string());
+            return;
+        }
+        let Some(root) = detected else {
+            self.notify(Severity::Warning, "No pitch detected in this sample".to_string());
+            return;
+        };
+        let shift = key_pitch_shift(root as f32, key);
+        self.dispatch(Command::SetTrackParam {
+            track,
+            key: "pitch_shift".to_string(),
+            value: shift,
+        });
+        let (note, cents) = split_cents(root as f32);
+        self.set_status(format!(
+            "Tuned {} to {}: pitch {:+.2} st",
+            tuning_label(note, cents),
+            NOTE_NAMES[key.unwrap_or(0) as usize],
+            shift
+        ));
+    }
+
+    /// Set a sampler loop's pitch shift so its detected tempo plays at the
+    /// project BPM (repitching, so the pitch moves with the speed)
+    fn fit_sample_to_bpm_action(&mut self, track: usize) {
+        let state = self.sequencer_state.read();
+        let sampler = state.tracks.get(track).filter(|t| t.synth_type == SynthType::Sampler);
+        let is_sampler = sampler.is_some();
+        let detected = sampler.and_then(|t| t.params_snapshot.get("detected_bpm")?.as_f64());
+        let bpm = state.bpm;
+        drop(state);
+        if !is_sampler {
+            self.notify(Severity::Warning, "Fitting needs a sampler track".to_string());
+            return;
+        }
+        let Some(loop_bpm) = detected else {
+            self.notify(Severity::Warning, "No tempo detected in this sample".to_string());
+            return;
+        };
+        let shift = bpm_pitch_shift(loop_bpm as f32, bpm);
+        self.dispatch(Command::SetTrackParam {
+            track,
+            key: "pitch_shift".to_string(),
+            value: shift,
+        });
+        self.set_status(format!(
+            "Fitted {:.1} BPM loop to {:.0} BPM: pitch {:+.2} st",
+            loop_bpm, bpm, shift
+        ));
+    }
+
+    /// Handle keys in mixer view
+    fn handle_mixer_key(&mut self, key: KeyCode) {
+        let num_tracks = self.num_tracks();
+        // Any key but a second a drops a pending gain suggestion
+        let gain_staging = self.mixer_state.gain_staging.take();
+        match key {
+            // Quit
+            KeyCode::Char('q') => {
+                self.should_quit = true;
             }
 
-            // BPM control
-            KeyCode::Char('+') | KeyCode::Char('=') => {
-                let current_bpm = self.sequencer_state.read().bpm;
-                self.dispatch(Command::SetBpm(current_bpm + 5.0));
+            // Tab cycles to FX view, Esc goes back to grid
+            KeyCode::Tab => {
+                self.view = View::Fx;
             }
-            KeyCode::Char('-') | KeyCode::Char('_') => {
-                let current_bpm = self.sequencer_state.read().bpm;
-                self.dispatch(Command::SetBpm(current_bpm - 5.0));
+            KeyCode::Esc => {
+                self.view = View::Grid;
             }
 
-            // Clear current track
-            KeyCode::Char('c') => {
-                self.dispatch(Command::ClearTrack(self.grid_state.cursor_track));
+            // Select track (1-9)
+            KeyCode::Char(c @ '1'..='9') => {
+                let track = (c as usize) - ('1' as usize);
+                self.mixer_state.select_track(track, num_tracks);
             }
 
-            // Fill current track
-            KeyCode::Char('f') => {
-                self.dispatch(Command::FillTrack(self.grid_state.cursor_track));
+            // Navigate fields
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.mixer_state.move_field(-1);
             }
-
-            // Note down 1 semitone
-            KeyCode::Char('[') => {
-                self.adjust_step_note(-1);
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.mixer_state.move_field(1);
             }
-            // Note up 1 semitone
-            KeyCode::Char(']') => {
-                self.adjust_step_note(1);
+
+            // Adjust value or toggle
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.adjust_mixer_value(-1);
             }
-            // Note down 1 octave (Shift+[)
-            KeyCode::Char('{') => {
-                self.adjust_step_note(-12);
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.adjust_mixer_value(1);
             }
-            // Note up 1 octave (Shift+])
-            KeyCode::Char('}') => {
-                self.adjust_step_note(12);
+
+            // Toggle mute on selected track
+            KeyCode::Char('m') => {
+                self.dispatch(Command::ToggleMute(self.mixer_state.selected_track));
             }
 
-            // Pattern selection
-            KeyCode::Char(',') | KeyCode::Char('<') => {
-                let current = self.sequencer_state.read().current_pattern;
-                let new_pat = if current == 0 { NUM_PATTERNS - 1 } else { current - 1 };
-                self.dispatch(Command::SelectPattern(new_pat));
+            // Toggle solo on selected track
+            KeyCode::Char('o') => {
+                self.dispatch(Command::ToggleSolo(self.mixer_state.selected_track));
             }
-            KeyCode::Char('.') | KeyCode::Char('>') => {
-                let current = self.sequencer_state.read().current_pattern;
-                let new_pat = (current + 1) % NUM_PATTERNS;
-                self.dispatch(Command::SelectPattern(new_pat));
+
+            // Keep the selected track audible while others are soloed
+            KeyCode::Char('O') => {
+                self.dispatch(Command::ToggleSoloSafe(self.mixer_state.selected_track));
             }
 
-            // Toggle variation A/B
-            KeyCode::Char('x') => {
-                self.dispatch(Command::ToggleVariation);
+            // Pre-listen the selected track on the cue bus
+            KeyCode::Char('u') => {
+                self.toggle_cue(self.mixer_state.selected_track);
             }
-            // Copy current variation to other (Shift+X)
-            KeyCode::Char('X') => {
-                let current_var = self.sequencer_state.read().current_variation;
-                let (from, to) = match current_var {
-                    Variation::A => (Variation::A, Variation::B),
-                    Variation::B => (Variation::B, Variation::A),
-                };
-                self.dispatch(Command::CopyVariation { from, to });
-                self.set_status(format!("Copied variation {:?} to {:?}", from, to));
+
+            // Glyph shown before the selected track's name
+            KeyCode::Char('i') => {
+                self.open_glyph_prompt();
             }
 
-            // Open sample browser for sampler tracks (Shift+L)
-            KeyCode::Char('L') => {
-                self.open_browser_for_track(self.grid_state.cursor_track);
+            // Play/Stop
+            KeyCode::Char('p') => {
+                let playing = self.sequencer_state.read().playing;
+                if playing {
+                    self.dispatch(Command::Pause);
+                } else {
+                    self.dispatch(Command::Play);
+                }
+            }
+            KeyCode::Char('s') => {
+                self.dispatch(Command::Stop);
             }
 
-            // Add track (Shift+A cycles: sampler, kick, snare, hihat, bass)
-            KeyCode::Char('A') => {
-                self.add_track_action();
+            // Clear the clip indicators
+            KeyCode::Char('c') => {
+                self.dispatch(Command::ResetClipIndicators);
+                self.set_status("Clip indicators reset".to_string());
             }
 
-            // Remove current track (Shift+D)
-            KeyCode::Char('D') => {
-                self.remove_track_action();
+            // Headroom monitoring: bypass the master soft clipper
+            KeyCode::Char('H') => {
+                let enabled = !self.sequencer_state.read().headroom;
+                self.dispatch(Command::SetHeadroom(enabled));
+                if enabled {
+                    self.set_status("Headroom monitoring on: no clipper, overs are flagged".to_string());
+                } else {
+                    self.set_status("Headroom monitoring off".to_string());
+                }
             }
 
+            // Suggest gain staging; a second a applies it
+            KeyCode::Char('a') => match gain_staging {
+                Some(staging) => self.apply_gain_staging(&staging),
+                None => self.suggest_gain_staging_action(),
+            },
+
             _ => {}
         }
     }
 
-    /// Handle keys in params view
-    fn handle_params_key(&mut self, key: KeyCode) {
-        let num_tracks = self.num_tracks();
-        let param_count = {
-            let state = self.sequencer_state.read();
-            get_param_descriptors(&state, self.param_editor.track).len()
+    /// Measure the song (or the current pattern) offline and show the
+    /// suggested volume trims in the mixer
+    fn suggest_gain_staging_action(&mut self) {
+        let state = self.sequencer_state.read().clone();
+        let mode = if state.playback_mode == PlaybackMode::Song && !state.arrangement.entries.is_empty() {
+            ExportMode::Song
+        } else {
+            ExportMode::Pattern(state.current_pattern)
         };
+        let staging = suggest_gain_staging(&state, &mode, state.sample_rate as u32);
+        if !staging.changes() {
+            self.set_status("Gain staging: levels already balanced".to_string());
+            return;
+        }
+        self.set_status(format!(
+            "Gain staging: master peak {:.1} -> {:.1} dBFS, a again to apply",
+            staging.peak_db, staging.suggested_peak_db
+        ));
+        self.mixer_state.gain_staging = Some(staging);
+    }
+
+    fn apply_gain_staging(&mut self, staging: &GainStaging) {
+        let mut changed = 0;
+        for trim in staging.trims.iter().filter(|t| t.suggested != t.volume) {
+            self.dispatch(Command::SetTrackVolume { track: trim.track, volume: trim.suggested });
+            changed += 1;
+        }
+        self.notify(Severity::Success, format!("Gain staging applied to {} tracks", changed));
+    }
 
+    /// Handle keys in FX view
+    fn handle_fx_key(&mut self, key: KeyCode) {
+        let num_tracks = self.num_tracks();
         match key {
             // Quit
             KeyCode::Char('q') => {
                 self.should_quit = true;
             }
 
-            // Cycle to mixer view (Esc goes back to grid)
+            // Tab cycles to Song view, Esc goes back to grid
             KeyCode::Tab => {
-                self.view = View::Mixer;
+                self.view = View::Song;
             }
             KeyCode::Esc => {
                 self.view = View::Grid;
             }
 
+            // Select track (1-9) or master (m)
+            KeyCode::Char(c @ '1'..='9') => {
+                let track = (c as usize) - ('1' as usize);
+                self.fx_editor.select_track(track, num_tracks);
+            }
+            KeyCode::Char('m') => {
+                self.fx_editor.select_track(num_tracks, num_tracks);
+            }
+
             // Navigate params
             KeyCode::Up | KeyCode::Char('k') => {
-                self.param_editor.move_selection(-1, param_count);
+                self.fx_editor.move_selection(-1, num_tracks);
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                self.param_editor.move_selection(1, param_count);
-            }
-
-            // Switch track (1-9)
-            KeyCode::Char(c @ '1'..='9') => {
-                let track = (c as usize) - ('1' as usize);
-                self.param_editor.switch_track(track, num_tracks);
+                self.fx_editor.move_selection(1, num_tracks);
             }
 
             // Adjust value (fine)
             KeyCode::Left | KeyCode::Char('h') => {
-                self.adjust_current_param(-0.05);
+                self.adjust_fx_param(-0.05);
             }
             KeyCode::Right | KeyCode::Char('l') => {
-                self.adjust_current_param(0.05);
+                self.adjust_fx_param(0.05);
             }
 
             // Adjust value (coarse)
             KeyCode::Char('[') => {
-                self.adjust_current_param(-0.2);
+                self.adjust_fx_param(-0.2);
             }
             KeyCode::Char(']') => {
-                self.adjust_current_param(0.2);
+                self.adjust_fx_param(0.2);
+            }
+
+            // Toggle effect enabled (Enter/Space)
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                self.toggle_current_fx();
+            }
+
+            // Auto gain-match for filter/distortion A/B
+            KeyCode::Char('a') => {
+                let enabled = !self.sequencer_state.read().gain_match;
+                self.dispatch(Command::SetGainMatch(enabled));
+                self.set_status(format!("Gain match {}", if enabled { "on" } else { "off" }));
+            }
+
+            // Play/Stop
+            KeyCode::Char('p') => {
+                let playing = self.sequencer_state.read().playing;
+                if playing {
+                    self.dispatch(Command::Pause);
+                } else {
+                    self.dispatch(Command::Play);
+                }
+            }
+            KeyCode::Char('s') => {
+                self.dispatch(Command::Stop);
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Handle keys in the spectrum analyzer view
+    fn handle_scope_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('q') => {
+                self.should_quit = true;
+            }
+
+            // Tab cycles to Scripts, Esc goes back to grid
+            KeyCode::Tab => {
+                self.view = View::Scripts;
+            }
+            KeyCode::Esc => {
+                self.view = View::Grid;
             }
 
-            // Open sample browser for sampler tracks (Shift+L)
-            KeyCode::Char('L') => {
-                self.open_browser_for_track(self.param_editor.track);
+            // Overlay a track's spectrum (visual only, audible solo unchanged)
+            KeyCode::Char(c @ '1'..='9') => {
+                let track = (c as usize) - ('1' as usize);
+                let num_tracks = self.num_tracks();
+                self.scope_state.toggle_overlay(track, num_tracks);
+                self.analyzer.set_overlay(self.scope_state.overlay_track);
+            }
+            KeyCode::Char('0') => {
+                self.scope_state.overlay_track = None;
+                self.analyzer.set_overlay(None);
             }
 
-            // Play/Stop still works in params view
             KeyCode::Char('p') => {
                 let playing = self.sequencer_state.read().playing;
                 if playing {
@@ -672,7 +3252,6 @@ impl App {
                     self.dispatch(Command::Play);
                 }
             }
-
             KeyCode::Char('s') => {
                 self.dispatch(Command::Stop);
             }
@@ -681,56 +3260,86 @@ impl App {
         }
     }
 
-    /// Handle keys in mixer view
-    fn handle_mixer_key(&mut self, key: KeyCode) {
-        let num_tracks = self.num_tracks();
+    /// Handle keys in the Scripts view
+    fn handle_scripts_key(&mut self, key: KeyCode) {
+        let scripts = self.sequencer_state.read().scripts.clone();
+        let selected = scripts.get(self.scripts_state.cursor).cloned();
         match key {
-            // Quit
             KeyCode::Char('q') => {
                 self.should_quit = true;
             }
 
-            // Tab cycles to FX view, Esc goes back to grid
-            KeyCode::Tab => {
-                self.view = View::Fx;
-            }
-            KeyCode::Esc => {
+            // Tab cycles to Grid, Esc goes back to grid
+            KeyCode::Tab | KeyCode::Esc => {
                 self.view = View::Grid;
             }
 
-            // Select track (1-9)
-            KeyCode::Char(c @ '1'..='9') => {
-                let track = (c as usize) - ('1' as usize);
-                self.mixer_state.select_track(track, num_tracks);
-            }
-
-            // Navigate fields
             KeyCode::Up | KeyCode::Char('k') => {
-                self.mixer_state.move_field(-1);
+                self.scripts_state.cursor = self.scripts_state.cursor.saturating_sub(1);
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                self.mixer_state.move_field(1);
+                if self.scripts_state.cursor + 1 < scripts.len() {
+                    self.scripts_state.cursor += 1;
+                }
             }
 
-            // Adjust value or toggle
-            KeyCode::Left | KeyCode::Char('h') => {
-                self.adjust_mixer_value(-1);
+            KeyCode::Char(' ') => {
+                if let Some(script) = selected {
+                    self.dispatch(Command::SetScriptEnabled {
+                        name: script.name,
+                        enabled: !script.enabled,
+                    });
+                }
             }
-            KeyCode::Right | KeyCode::Char('l') => {
-                self.adjust_mixer_value(1);
+            KeyCode::Left | KeyCode::Right => {
+                if let Some(mut script) = selected {
+                    script.every = if key == KeyCode::Right {
+                        (script.every + 1).min(16)
+                    } else {
+                        script.every.saturating_sub(1).max(1)
+                    };
+                    self.dispatch(Command::SetScript(script));
+                }
             }
 
-            // Toggle mute on selected track
-            KeyCode::Char('m') => {
-                self.dispatch(Command::ToggleMute(self.mixer_state.selected_track));
+            // New script from the template, opened in the editor
+            KeyCode::Char('n') => {
+                if scripts.len() >= MAX_SCRIPTS {
+                    self.notify(Severity::Warning, format!("Script limit reached ({})", MAX_SCRIPTS));
+                    return;
+                }
+                let name = (1..)
+                    .map(|i| format!("script{}", i))
+                    .find(|n| !scripts.iter().any(|s| &s.name == n))
+                    .unwrap_or_default();
+                self.dispatch(Command::SetScript(Script::new(&name, SCRIPT_TEMPLATE)));
+                self.scripts_state.cursor = scripts.len();
+                self.pending_script_edit = Some(name);
             }
-
-            // Toggle solo on selected track
-            KeyCode::Char('o') => {
-                self.dispatch(Command::ToggleSolo(self.mixer_state.selected_track));
+            KeyCode::Char('e') => {
+                if let Some(script) = selected {
+                    self.pending_script_edit = Some(script.name);
+                }
+            }
+            KeyCode::Char('l') => self.load_script_files_action(&scripts),
+            KeyCode::Char('r') => {
+                if let Some(script) = selected {
+                    let run = self.script_host.run_now(&script, &self.sequencer_state);
+                    let changes = run.commands.len();
+                    self.apply_script_run(run);
+                    if self.script_host.status.get(&script.name).is_some_and(|s| s.error.is_none()) {
+                        self.set_status(format!("Ran '{}': {} changes", script.name, changes));
+                    }
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(script) = selected {
+                    self.dispatch(Command::RemoveScript(script.name.clone()));
+                    self.set_status(format!("Removed script '{}'", script.name));
+                    self.scripts_state.cursor = self.scripts_state.cursor.min(scripts.len().saturating_sub(2));
+                }
             }
 
-            // Play/Stop
             KeyCode::Char('p') => {
                 let playing = self.sequencer_state.read().playing;
                 if playing {
@@ -747,69 +3356,189 @@ impl App {
         }
     }
 
-    /// Handle keys in FX view
-    fn handle_fx_key(&mut self, key: KeyCode) {
-        let num_tracks = self.num_tracks();
+    /// Add (or replace by name) every .rhai file from the script directories
+    fn load_script_files_action(&mut self, current: &[Script]) {
+        let files = script::load_script_files();
+        if files.is_empty() {
+            self.set_status("No .rhai files in ./scripts/ or ~/.gridoxide/scripts/".to_string());
+            return;
+        }
+        let mut count = current.len();
+        let mut loaded = 0;
+        for file in files {
+            let existing = current.iter().find(|s| s.name == file.name);
+            if existing.is_none() && count >= MAX_SCRIPTS {
+                continue;
+            }
+            // Keep the schedule of scripts that are being refreshed
+            let script = match existing {
+                Some(s) => Script { source: file.source, ..s.clone() },
+                None => {
+                    count += 1;
+                    file
+                }
+            };
+            self.dispatch(Command::SetScript(script));
+            loaded += 1;
+        }
+        self.set_status(format!("Loaded {} script(s)", loaded));
+    }
+
+    /// Open a script in $VISUAL/$EDITOR (default vi) and store the result
+    fn edit_script(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+        name: &str,
+    ) -> Result<()> {
+        let Some(mut script) = self
+            .sequencer_state
+            .read()
+            .scripts
+            .iter()
+            .find(|s| s.name == name)
+            .cloned()
+        else {
+            return Ok(());
+        };
+        let path = std::env::temp_dir().join(format!("gridoxide-{}.rhai", name));
+        if let Err(e) = std::fs::write(&path, &script.source) {
+            self.notify(Severity::Error, format!("Edit failed: {}", e));
+            return Ok(());
+        }
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        let releases = self.long_press.releases();
+        Self::restore_terminal(terminal, releases)?;
+        // Through the shell so editors with arguments ("code -w") work
+        let status = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("sh")
+            .arg(&path)
+            .status();
+        enable_raw_mode()?;
+        terminal.backend_mut().execute(EnterAlternateScreen)?;
+        if releases {
+            Self::enable_key_releases(terminal);
+        }
+        terminal.clear()?;
+
+        let source = match status {
+            Ok(s) if s.success() => std::fs::read_to_string(&path),
+            Ok(s) => Err(io::Error::other(format!("editor exited with {}", s))),
+            Err(e) => Err(e),
+        };
+        let _ = std::fs::remove_file(&path);
+        match source {
+            Ok(source) if source != script.source => {
+                let checked = script::check_syntax(&source);
+                script.source = source;
+                self.dispatch(Command::SetScript(script));
+                match checked {
+                    Ok(()) => self.set_status(format!("Saved script '{}'", name)),
+                    Err(e) => self.notify(Severity::Error, format!("Saved '{}' with errors: {:#}", name, e)),
+                }
+            }
+            Ok(_) => {}
+            Err(e) => self.notify(Severity::Error, format!("Edit failed: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Run scripts due at this bar boundary
+    fn poll_scripts(&mut self) {
+        if let Some(run) = self.script_host.poll(&self.sequencer_state) {
+            self.apply_script_run(run);
+        }
+    }
+
+    /// Send script changes to the engine. They are logged but not recorded
+    /// into macros (replaying a macro would repeat generated changes).
+    fn apply_script_run(&mut self, run: ScriptRun) {
+        for cmd in run.commands {
+            let loads = project::sample_option_reload(&self.sequencer_state.read(), &cmd);
+            self.sequencer_state.write().apply_shared(&cmd);
+            self.event_log.write().log(cmd.clone(), CommandSource::Tui);
+            self.command_sender.send(cmd, CommandSource::Tui);
+            for load in loads.into_iter().flatten() {
+                self.command_sender.send(load, CommandSource::Tui);
+            }
+        }
+        if let Some(error) = run.errors.into_iter().next() {
+            self.notify(Severity::Error, format!("Script error: {}", error));
+        }
+    }
+
+    /// Handle keys in the pattern bank view
+    fn handle_bank_key(&mut self, key: KeyCode) {
+        let cursor = self.bank_state.cursor;
         match key {
-            // Quit
             KeyCode::Char('q') => {
                 self.should_quit = true;
             }
 
-            // Tab cycles to Song view, Esc goes back to grid
+            // Tab cycles to Scope, Esc goes back to grid
             KeyCode::Tab => {
-                self.view = View::Song;
+                self.view = View::Scope;
             }
             KeyCode::Esc => {
                 self.view = View::Grid;
             }
 
-            // Select track (1-9) or master (m)
-            KeyCode::Char(c @ '1'..='9') => {
-                let track = (c as usize) - ('1' as usize);
-                self.fx_editor.select_track(track, num_tracks);
-            }
-            KeyCode::Char('m') => {
-                self.fx_editor.select_track(num_tracks, num_tracks);
-            }
-
-            // Navigate params
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.fx_editor.move_selection(-1);
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.fx_editor.move_selection(1);
-            }
+            KeyCode::Left | KeyCode::Char('h') => self.bank_state.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Char('l') => self.bank_state.move_cursor(1, 0),
+            KeyCode::Up | KeyCode::Char('k') => self.bank_state.move_cursor(0, -1),
+            KeyCode::Down | KeyCode::Char('j') => self.bank_state.move_cursor(0, 1),
+            KeyCode::Char('<') => self.bank_state.step_bank(-1),
+            KeyCode::Char('>') => self.bank_state.step_bank(1),
 
-            // Adjust value (fine)
-            KeyCode::Left | KeyCode::Char('h') => {
-                self.adjust_fx_param(-0.05);
-            }
-            KeyCode::Right | KeyCode::Char('l') => {
-                self.adjust_fx_param(0.05);
+            // Switch to the slot (at the next bar while playing)
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                self.dispatch(Command::SelectPattern(cursor));
+                if self.sequencer_state.read().playing {
+                    self.set_status(format!("{} queued", pattern_label(cursor)));
+                }
             }
 
-            // Adjust value (coarse)
-            KeyCode::Char('[') => {
-                self.adjust_fx_param(-0.2);
-            }
-            KeyCode::Char(']') => {
-                self.adjust_fx_param(0.2);
+            // First c marks the source, the second copies it onto the cursor slot
+            KeyCode::Char('c') => match self.bank_state.copy_from.take() {
+                Some(src) if src != cursor => {
+                    self.dispatch(Command::CopyPattern { src, dst: cursor });
+                    self.set_status(format!("Copied {} -> {}", pattern_label(src), pattern_label(cursor)));
+                }
+                Some(_) => self.set_status("Copy cancelled".to_string()),
+                None => {
+                    self.bank_state.copy_from = Some(cursor);
+                    self.set_status(format!("Copy {}: move to a slot and press c", pattern_label(cursor)));
+                }
+            },
+
+            KeyCode::Char('x') | KeyCode::Delete => {
+                let has_content = {
+                    let state = self.sequencer_state.read();
+                    if cursor == state.current_pattern {
+                        state.pattern.has_content()
+                    } else {
+                        state.pattern_bank.has_content(cursor)
+                    }
+                };
+                if has_content {
+                    let message = format!("Clear pattern {}?", pattern_label(cursor));
+                    self.modal = Some(Modal::confirm("Clear Pattern", message, Dialog::ClearPattern(cursor)));
+                }
             }
 
-            // Toggle effect enabled (Enter/Space)
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                self.toggle_current_fx();
+            // Chain: append the slot to the arrangement
+            KeyCode::Char('a') => {
+                self.dispatch(Command::AppendArrangement { pattern: cursor, repeats: 1 });
+                self.set_status(format!("Chained {} to the song", pattern_label(cursor)));
             }
 
-            // Play/Stop
             KeyCode::Char('p') => {
                 let playing = self.sequencer_state.read().playing;
-                if playing {
-                    self.dispatch(Command::Pause);
-                } else {
-                    self.dispatch(Command::Play);
-                }
+                self.dispatch(if playing { Command::Pause } else { Command::Play });
             }
             KeyCode::Char('s') => {
                 self.dispatch(Command::Stop);
@@ -827,9 +3556,11 @@ impl App {
                 self.should_quit = true;
             }
 
-            // Tab cycles to Grid, Esc goes back to grid
+            // Tab cycles to the pattern bank, Esc goes back to grid
             KeyCode::Tab => {
-                self.view = View::Grid;
+                self.bank_state.cursor = self.sequencer_state.read().current_pattern;
+                self.bank_state.copy_from = None;
+                self.view = View::Bank;
             }
             KeyCode::Esc => {
                 self.view = View::Grid;
@@ -854,7 +3585,7 @@ impl App {
                 let state = self.sequencer_state.read();
                 let pos = self.song_state.cursor_position;
                 if pos < state.arrangement.len() {
-                    let entry = state.arrangement.entries[pos];
+                    let entry = state.arrangement.entries[pos].clone();
                     drop(state);
                     if entry.repeats > 1 {
                         self.dispatch(Command::SetArrangementEntry {
@@ -869,7 +3600,7 @@ impl App {
                 let state = self.sequencer_state.read();
                 let pos = self.song_state.cursor_position;
                 if pos < state.arrangement.len() {
-                    let entry = state.arrangement.entries[pos];
+                    let entry = state.arrangement.entries[pos].clone();
                     drop(state);
                     if entry.repeats < 16 {
                         self.dispatch(Command::SetArrangementEntry {
@@ -881,12 +3612,16 @@ impl App {
                 }
             }
 
+            // Cycle the selected entry's chord degree (off, I .. vii)
+            KeyCode::Char('r') => self.cycle_entry_chord(true),
+            KeyCode::Char('R') => self.cycle_entry_chord(false),
+
             // Cycle pattern index on selected entry
             KeyCode::Char('-') => {
                 let state = self.sequencer_state.read();
                 let pos = self.song_state.cursor_position;
                 if pos < state.arrangement.len() {
-                    let entry = state.arrangement.entries[pos];
+                    let entry = state.arrangement.entries[pos].clone();
                     drop(state);
                     let new_pat = if entry.pattern == 0 { NUM_PATTERNS - 1 } else { entry.pattern - 1 };
                     self.dispatch(Command::SetArrangementEntry {
@@ -900,7 +3635,7 @@ impl App {
                 let state = self.sequencer_state.read();
                 let pos = self.song_state.cursor_position;
                 if pos < state.arrangement.len() {
-                    let entry = state.arrangement.entries[pos];
+                    let entry = state.arrangement.entries[pos].clone();
                     drop(state);
                     let new_pat = (entry.pattern + 1) % NUM_PATTERNS;
                     self.dispatch(Command::SetArrangementEntry {
@@ -954,61 +3689,383 @@ impl App {
                 }
             }
 
-            // Pattern selection (same as grid)
-            KeyCode::Char(',') | KeyCode::Char('<') => {
-                let current = self.sequencer_state.read().current_pattern;
-                let new_pat = if current == 0 { NUM_PATTERNS - 1 } else { current - 1 };
-                self.dispatch(Command::SelectPattern(new_pat));
-            }
-            KeyCode::Char('.') | KeyCode::Char('>') => {
-                let current = self.sequencer_state.read().current_pattern;
-                let new_pat = (current + 1) % NUM_PATTERNS;
-                self.dispatch(Command::SelectPattern(new_pat));
+            // Pattern selection (same as grid)
+            KeyCode::Char(',') => self.step_pattern(NUM_PATTERNS - 1),
+            KeyCode::Char('.') => self.step_pattern(1),
+            // Bank paging: same slot in the previous/next bank
+            KeyCode::Char('<') => self.step_pattern(NUM_PATTERNS - PATTERNS_PER_BANK),
+            KeyCode::Char('>') => self.step_pattern(PATTERNS_PER_BANK),
+
+            // Crossfader mode: deck B, its slot, and the fader
+            KeyCode::Char('b') => self.toggle_deck_b(),
+            KeyCode::Char('{') => self.step_deck_b(NUM_PATTERNS - 1),
+            KeyCode::Char('}') => self.step_deck_b(1),
+            KeyCode::Char('[') => self.move_crossfader(-CROSSFADER_STEP),
+            KeyCode::Char(']') => self.move_crossfader(CROSSFADER_STEP),
+
+            // Toggle Pattern/Song mode
+            KeyCode::Char('m') => {
+                let current_mode = self.sequencer_state.read().playback_mode;
+                let new_mode = match current_mode {
+                    PlaybackMode::Pattern => PlaybackMode::Song,
+                    PlaybackMode::Song => PlaybackMode::Pattern,
+                };
+                self.dispatch(Command::SetPlaybackMode(new_mode));
+            }
+
+            // Copy current pattern to next empty slot (or prompt)
+            KeyCode::Char('c') => {
+                let state = self.sequencer_state.read();
+                let src = state.current_pattern;
+                // Find next empty slot
+                let dst = (0..NUM_PATTERNS)
+                    .find(|&i| i != src && !state.pattern_bank.has_content(i));
+                drop(state);
+                if let Some(dst) = dst {
+                    self.dispatch(Command::CopyPattern { src, dst });
+                }
+            }
+
+            // Mutate current pattern into the next empty slot
+            KeyCode::Char('v') => {
+                let state = self.sequencer_state.read();
+                let src = state.current_pattern;
+                let dst = (0..NUM_PATTERNS)
+                    .find(|&i| i != src && !state.pattern_bank.has_content(i));
+                // Mutate here, not on the audio thread (it allocates)
+                let mutated = state.pattern.mutated(0.25, random_seed());
+                drop(state);
+                match dst {
+                    Some(dst) => {
+                        self.dispatch(Command::ReplacePattern {
+                            index: dst,
+                            pattern: Box::new(mutated),
+                        });
+                        self.set_status(format!(
+                            "Mutated {} -> {}",
+                            pattern_label(src),
+                            pattern_label(dst)
+                        ));
+                    }
+                    None => self.notify(Severity::Warning, "No empty pattern slot".to_string()),
+                }
+            }
+
+            // Copy selected tracks into another slot
+            KeyCode::Char('C') => {
+                self.open_copy_tracks_action();
+            }
+
+            // Clear current pattern slot
+            KeyCode::Char('x') => {
+                let current = self.sequencer_state.read().current_pattern;
+                self.dispatch(Command::ClearPattern(current));
+            }
+
+            // Import import.mid into empty slots + arrangement
+            KeyCode::Char('i') => {
+                self.import_midi_action();
+            }
+
+            // Cycle mute automation for track 1-9 on the selected entry
+            KeyCode::Char(c @ '1'..='9') => {
+                self.cycle_entry_mute(c as usize - '1' as usize);
+            }
+
+            // Switch between the arrangement list and timeline
+            KeyCode::Char('t') => {
+                self.song_state.timeline = !self.song_state.timeline;
+            }
+
+            // Cycle the song-end fade-out: off, 1, 2, 4, 8, 16 bars
+            KeyCode::Char('f') => {
+                let bars = self.sequencer_state.read().arrangement.fade_out_bars;
+                let next = match bars {
+                    b if b >= MAX_FADE_OUT_BARS => 0,
+                    b => (b + 1).next_power_of_two(),
+                };
+                self.dispatch(Command::SetFadeOutBars(next));
+                if next == 0 {
+                    self.set_status("Fade-out off".to_string());
+                } else {
+                    self.set_status(format!("Fade-out over the last {} bars", next));
+                }
+            }
+
+            // Play/Stop
+            KeyCode::Char('p') => {
+                let playing = self.sequencer_state.read().playing;
+                if playing {
+                    self.dispatch(Command::Pause);
+                } else {
+                    self.dispatch(Command::Play);
+                }
+            }
+            KeyCode::Char('s') => {
+                self.dispatch(Command::Stop);
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Step the selected entry's chord degree through off, I, ii .. vii and back
+    fn cycle_entry_chord(&mut self, forward: bool) {
+        let position = self.song_state.cursor_position;
+        let entry = self.sequencer_state.read().arrangement.entries.get(position).map(|e| e.chord);
+        let Some(chord) = entry else {
+            return;
+        };
+        // 0 = off, 1.. = degree + 1
+        let count = CHORD_DEGREES.len() + 1;
+        let index = chord.map_or(0, |d| d as usize + 1);
+        let index = if forward { (index + 1) % count } else { (index + count - 1) % count };
+        let chord = index.checked_sub(1).map(|d| d as u8);
+        self.dispatch(Command::SetEntryChord { position, chord });
+        match chord {
+            Some(_) => self.set_status(format!("Entry {} chord: {}", position + 1, chord_label(chord))),
+            None => self.set_status(format!("Entry {} chord off", position + 1)),
+        }
+    }
+
+    /// Cycle a track's mute automation on the selected arrangement entry:
+    /// none -> mute -> unmute -> none (solo/volume automation is kept)
+    fn cycle_entry_mute(&mut self, track: usize) {
+        let position = self.song_state.cursor_position;
+        let state = self.sequencer_state.read();
+        if track >= state.tracks.len() {
+            return;
+        }
+        let Some(entry) = state.arrangement.entries.get(position) else {
+            return;
+        };
+        let mut automation = entry.automation.clone();
+        drop(state);
+        let index = match automation.iter().position(|a| a.track == track) {
+            Some(i) => i,
+            None => {
+                automation.push(TrackAutomation::new(track));
+                automation.len() - 1
+            }
+        };
+        let item = &mut automation[index];
+        item.mute = match item.mute {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+        let label = match item.mute {
+            Some(true) => "mutes",
+            Some(false) => "unmutes",
+            None => "leaves mute of",
+        };
+        self.dispatch(Command::SetEntryAutomation { position, automation });
+        self.set_status(format!("Entry {} {} track {}", position + 1, label, track + 1));
+    }
+
+    /// Handle keys in the notification log overlay
+    fn handle_notifications_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.notifications.open = false;
+            }
+            // Scroll offset counts back from the newest entry
+            KeyCode::Up | KeyCode::Char('k') => self.notifications.scroll_by(1),
+            KeyCode::Down | KeyCode::Char('j') => self.notifications.scroll_by(-1),
+            KeyCode::PageUp => self.notifications.scroll_by(10),
+            KeyCode::PageDown => self.notifications.scroll_by(-10),
+            KeyCode::Home => self.notifications.scroll_by(isize::MAX),
+            KeyCode::End => self.notifications.scroll = 0,
+            KeyCode::Char('c') => self.notifications.clear(),
+            _ => {}
+        }
+    }
+
+    /// Handle keys in the export queue panel
+    fn handle_queue_key(&mut self, key: KeyCode) {
+        let jobs = self.exports.jobs();
+        match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.queue_open = false,
+            KeyCode::Up | KeyCode::Char('k') => self.queue_cursor = self.queue_cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.queue_cursor = (self.queue_cursor + 1).min(jobs.len().saturating_sub(1));
+            }
+            KeyCode::Char('x') => {
+                let Some(job) = jobs.get(self.queue_cursor) else {
+                    return;
+                };
+                if self.exports.cancel(job.id) {
+                    self.set_status(format!("Cancelled export #{}", job.id));
+                } else {
+                    self.set_status(format!("Export #{} is {}: only queued jobs can be cancelled", job.id, job.status.name()));
+                }
+            }
+            KeyCode::Char('c') => {
+                self.exports.clear_finished();
+                self.queue_cursor = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the settings view from any view, with fresh device lists
+    fn open_settings(&mut self) {
+        self.long_press.cancel();
+        if self.view != View::Settings {
+            self.settings_return = self.view;
+            self.view = View::Settings;
+        }
+        self.settings_state.devices = audio::output_device_names().unwrap_or_default();
+        self.settings_state.midi_ports = midi::port_names().map(|(inputs, _)| inputs).unwrap_or_default();
+    }
+
+    /// Handle keys in settings view
+    fn handle_settings_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => self.view = self.settings_return,
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.settings_state.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.settings_state.move_cursor(1),
+            KeyCode::Left | KeyCode::Char('h') => self.change_setting(-1),
+            KeyCode::Right | KeyCode::Char('l') => self.change_setting(1),
+            KeyCode::Enter if self.settings_state.field() == SettingsField::SampleDirs => {
+                let prompt = Prompt::new(PromptKind::SampleDir, "", Self::validate_sample_dir, &self.prompt_history);
+                self.modal = Some(Modal::prompt("Add Sample Dir", prompt, Dialog::Prompt(PromptKind::SampleDir)));
+            }
+            KeyCode::Backspace | KeyCode::Delete if self.settings_state.field() == SettingsField::SampleDirs => {
+                if let Some(dir) = self.config.sample_dirs.pop() {
+                    self.set_status(format!("Sample dir removed: {}", dir.display()));
+                    self.apply_sample_dirs();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Step the setting under the cursor, apply it and save the config
+    fn change_setting(&mut self, delta: isize) {
+        match self.settings_state.field() {
+            SettingsField::OutputDevice => {
+                let options: Vec<Option<String>> = std::iter::once(None)
+                    .chain(self.settings_state.devices.iter().cloned().map(Some))
+                    .collect();
+                let previous = self.config.output_device.clone();
+                self.config.output_device = cycle_option(&options, &self.config.output_device, delta);
+                if self.reopen_audio(previous, self.config.buffer_size) {
+                    let name = self.config.output_device.clone().unwrap_or_else(|| "system default".to_string());
+                    self.set_status(format!("Output device: {}", name));
+                }
             }
-
-            // Toggle Pattern/Song mode
-            KeyCode::Char('m') => {
-                let current_mode = self.sequencer_state.read().playback_mode;
-                let new_mode = match current_mode {
-                    PlaybackMode::Pattern => PlaybackMode::Song,
-                    PlaybackMode::Song => PlaybackMode::Pattern,
-                };
-                self.dispatch(Command::SetPlaybackMode(new_mode));
+            SettingsField::BufferSize => {
+                let options: Vec<Option<u32>> =
+                    std::iter::once(None).chain(BUFFER_SIZES.iter().copied().map(Some)).collect();
+                let previous = self.config.buffer_size;
+                self.config.buffer_size = cycle_option(&options, &self.config.buffer_size, delta);
+                if self.reopen_audio(self.config.output_device.clone(), previous) {
+                    let size = self.config.buffer_size.map_or("device default".to_string(), |f| format!("{} frames", f));
+                    self.set_status(format!("Buffer size: {}", size));
+                }
             }
-
-            // Copy current pattern to next empty slot (or prompt)
-            KeyCode::Char('c') => {
-                let state = self.sequencer_state.read();
-                let src = state.current_pattern;
-                // Find next empty slot
-                let dst = (0..NUM_PATTERNS)
-                    .find(|&i| i != src && !state.pattern_bank.has_content(i));
-                drop(state);
-                if let Some(dst) = dst {
-                    self.dispatch(Command::CopyPattern { src, dst });
+            SettingsField::Theme => {
+                let name = cycle_option(Theme::available_themes(), &self.theme.name, delta);
+                if let Some(theme) = Theme::from_name(name) {
+                    self.theme = theme;
+                    self.config.theme = Some(name.to_string());
                 }
             }
-
-            // Clear current pattern slot
-            KeyCode::Char('x') => {
-                let current = self.sequencer_state.read().current_pattern;
-                self.dispatch(Command::ClearPattern(current));
+            SettingsField::SampleDirs => return,
+            SettingsField::Autosave => {
+                let options: Vec<u32> = std::iter::once(0).chain(AUTOSAVE_MINUTES).collect();
+                self.config.autosave_minutes = cycle_option(&options, &self.config.autosave_minutes, delta);
+                self.last_autosave = Instant::now();
             }
-
-            // Play/Stop
-            KeyCode::Char('p') => {
-                let playing = self.sequencer_state.read().playing;
-                if playing {
-                    self.dispatch(Command::Pause);
-                } else {
-                    self.dispatch(Command::Play);
+            SettingsField::Backups => {
+                let options: Vec<u32> = std::iter::once(0).chain(BACKUP_COUNTS).collect();
+                self.config.backups = cycle_option(&options, &self.config.backups, delta);
+            }
+            SettingsField::Metronome => {
+                self.config.metronome = !self.config.metronome;
+                self.dispatch(Command::SetMetronome(self.config.metronome));
+            }
+            SettingsField::MidiSync => {
+                let mode = self.sequencer_state.read().midi_sync;
+                let mode = if delta > 0 { mode.next() } else { mode.next().next() };
+                self.dispatch(Command::SetMidiSync(mode));
+                self.config.midi_sync = Some(mode.name().to_string());
+            }
+            SettingsField::MidiIn => {
+                self.config.midi_in = !self.config.midi_in;
+                if self.config.midi_in {
+                    self.open_midi_input();
+                } else if self.sequencer_state.read().midi_sync == MidiSync::Slave {
+                    self.set_status("MIDI input stays open for the slave clock".to_string());
+                } else if self.audio.disconnect_midi_input() {
+                    self.set_status("MIDI input closed".to_string());
                 }
             }
-            KeyCode::Char('s') => {
-                self.dispatch(Command::Stop);
+            SettingsField::MidiPort => {
+                let options: Vec<Option<String>> = std::iter::once(None)
+                    .chain(self.settings_state.midi_ports.iter().cloned().map(Some))
+                    .collect();
+                self.config.midi_port = cycle_option(&options, &self.config.midi_port, delta);
+                self.midi_port = self.config.midi_port.clone();
+                if self.audio.disconnect_midi_input() {
+                    self.open_midi_input();
+                }
             }
+        }
+        self.save_config();
+    }
 
-            _ => {}
+    fn save_config(&mut self) {
+        if let Err(e) = self.config.save() {
+            self.notify(Severity::Error, format!("Settings not saved: {:#}", e));
+        }
+    }
+
+    /// Search the configured sample directories from now on, and watch them
+    fn apply_sample_dirs(&mut self) {
+        samples::set_extra_dirs(&self.config.sample_dirs);
+        self.sample_watcher = SampleWatcher::new(&samples::search_dirs()).ok();
+        self.save_config();
+    }
+
+    /// Save every tab with unsaved changes and a file once the autosave
+    /// interval has passed
+    fn poll_autosave(&mut self) {
+        let minutes = self.config.autosave_minutes;
+        if minutes == 0 || self.last_autosave.elapsed() < Duration::from_secs(minutes as u64 * 60) {
+            return;
+        }
+        self.last_autosave = Instant::now();
+        let mut saved = 0;
+        for i in 0..self.tabs.len() {
+            let tab = &self.tabs[i];
+            let Some(path) = tab.path.clone().filter(|_| tab.dirty) else {
+                continue;
+            };
+            self.back_up_project(&path);
+            let tab = &self.tabs[i];
+            let result = match tab.parked {
+                Some(ref state) => project::save_project(state, &path),
+                None => {
+                    self.save_view_state(&path);
+                    project::save_project(&self.sequencer_state.read().clone(), &path)
+                }
+            };
+            match result {
+                Ok(()) => {
+                    self.tabs[i].dirty = false;
+                    saved += 1;
+                }
+                Err(e) => self.notify(
+                    Severity::Error,
+                    format!("Autosave failed, {} is unchanged: {:#}", path.display(), e),
+                ),
+            }
+        }
+        if saved > 0 {
+            self.notify(Severity::Info, format!("Autosaved {} project(s)", saved));
         }
     }
 
@@ -1051,6 +4108,283 @@ impl App {
         self.browser_state = Some(BrowserState::new(entries, track, track_name));
     }
 
+    /// Put the WAVs of a kit folder on the first tracks, named by what
+    /// their file names say they are
+    fn load_kit(&mut self, dir: &Path) {
+        let assigned = assign_kit(&self.sequencer_state.read(), dir, MAX_TRACKS);
+        match assigned {
+            Ok((state, slots)) => {
+                let names: Vec<&str> = slots.iter().map(|s| s.name.as_str()).collect();
+                let summary = format!("Loaded kit: {}", names.join(", "));
+                // Rebuilds the engine's synths, which stops playback
+                self.install_state(state);
+                self.set_status(summary);
+            }
+            Err(e) => self.notify(Severity::Warning, format!("{:#}", e)),
+        }
+    }
+
+    /// Open the hardware input the first time an Input track exists, or
+    /// when a sample capture (e.g. from MCP) needs it
+    fn poll_input_tracks(&mut self) {
+        if self.sequencer_state.read().capture.is_some() && !self.audio.input_open() {
+            if let Err(e) = self.audio.start_input() {
+                self.dispatch(Command::CancelSampleCapture);
+                self.notify(Severity::Error, format!("Sample capture cancelled, audio input unavailable: {}", e));
+            }
+            return;
+        }
+        if self.input_requested {
+            return;
+        }
+        let has_input = self
+            .sequencer_state
+            .read()
+            .tracks
+            .iter()
+            .any(|t| t.synth_type == SynthType::Input);
+        if has_input {
+            self.input_requested = true;
+            if let Err(e) = self.audio.start_input() {
+                self.notify(Severity::Error, format!("Audio input unavailable: {}", e));
+            }
+        }
+    }
+
+    /// Reopen the audio output with the configured device and buffer size
+    /// and load the project back into it. If it can't be opened, the
+    /// previous device and buffer size are restored and false returned.
+    fn reopen_audio(&mut self, previous_device: Option<String>, previous_buffer: Option<u32>) -> bool {
+        let state = self.snapshot_state();
+        let opened = match self.audio.reopen(self.config.output_device.as_deref(), self.config.buffer_size) {
+            Ok(()) => true,
+            Err(e) => {
+                self.notify(Severity::Error, format!("Audio output unavailable, keeping the previous one: {:#}", e));
+                self.config.output_device = previous_device;
+                self.config.buffer_size = previous_buffer;
+                if let Err(e) = self.audio.reopen(self.config.output_device.as_deref(), self.config.buffer_size) {
+                    self.notify(Severity::Error, format!("Audio output lost: {:#}", e));
+                }
+                false
+            }
+        };
+        // Parked synths run at the old rate, so other tabs load afresh
+        for tab in &mut self.tabs {
+            tab.synths = ParkedSynths::default();
+        }
+        // The new stream starts empty: settings kept outside the project go back too
+        let (midi_sync, gain_match, headroom, metronome) =
+            (state.midi_sync, state.gain_match, state.headroom, state.metronome);
+        self.install_state(state);
+        self.dispatch(Command::SetMidiSync(midi_sync));
+        self.dispatch(Command::SetGainMatch(gain_match));
+        self.dispatch(Command::SetHeadroom(headroom));
+        self.dispatch(Command::SetMetronome(metronome));
+        // Input tracks reopen the hardware input at the new rate
+        self.input_requested = false;
+        self.agent_event_id = self.event_log.read().latest_id();
+        opened
+    }
+
+    /// Open the MIDI port a newly selected sync mode needs; fall back to the
+    /// internal clock if it can't be opened
+    fn poll_midi_sync(&mut self) {
+        let mode = self.sequencer_state.read().midi_sync;
+        if mode == self.midi_sync_seen {
+            return;
+        }
+        self.midi_sync_seen = mode;
+        match self.audio.connect_midi_clock(mode, self.midi_port.as_deref()) {
+            Ok(Some(port)) => {
+                let direction = if mode == MidiSync::Slave { "input" } else { "output" };
+                self.notify(Severity::Success, format!("MIDI clock {}: {}", direction, port));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                self.notify(Severity::Error, format!("MIDI clock unavailable: {:#}", e));
+                self.dispatch(Command::SetMidiSync(MidiSync::Internal));
+            }
+        }
+    }
+
+    /// Cycle the clock source: internal, MIDI slave, MIDI master
+    fn step_swing(&mut self, delta: i16) {
+        let swing = (self.sequencer_state.read().swing as i16 + delta).clamp(0, MAX_SWING as i16) as u8;
+        self.dispatch(Command::SetSwing(swing));
+        self.set_status(format!("Swing: {}%", swing));
+    }
+
+    /// Shorten or lengthen the current pattern by `delta` steps
+    fn step_pattern_length(&mut self, delta: i32) {
+        let (pattern, length) = {
+            let state = self.sequencer_state.read();
+            (state.current_pattern, state.pattern.length)
+        };
+        let length = (length as i32 + delta).clamp(1, MAX_STEPS as i32) as usize;
+        self.dispatch(Command::SetPatternLength { pattern, length });
+        let triplet = self.track_triplets().get(self.grid_state.cursor_track).copied().unwrap_or(false);
+        self.grid_state.cursor_step = self.grid_state.cursor_step.min(track_steps(triplet, length) - 1);
+        self.set_status(format!("Pattern {}: {} steps", pattern_label(pattern), length));
+    }
+
+    /// Loop the cursor track after the cursor step (polymeter), or back
+    /// with the pattern if it already wraps there
+    fn toggle_track_wrap(&mut self) {
+        let (track, step) = (self.grid_state.cursor_track, self.grid_state.cursor_step);
+        let (pattern, current, steps, name) = {
+            let state = self.sequencer_state.read();
+            let Some(t) = state.tracks.get(track) else {
+                return;
+            };
+            (
+                state.current_pattern,
+                state.pattern.track_length(track),
+                track_steps(t.triplet, state.pattern.length),
+                t.name.clone(),
+            )
+        };
+        // Wrapping after the last step is the same as looping with the pattern
+        let length = if current == step + 1 || step + 1 >= steps { 0 } else { step + 1 };
+        self.dispatch(Command::SetTrackLength { pattern, track, length });
+        if length == 0 {
+            self.set_status(format!("{} loops with the pattern", name));
+        } else {
+            self.set_status(format!("{} loops every {} steps", name, length));
+        }
+    }
+
+    fn cycle_midi_sync(&mut self) {
+        let mode = self.sequencer_state.read().midi_sync.next();
+        self.dispatch(Command::SetMidiSync(mode));
+        self.set_status(format!("MIDI sync: {}", mode.name()));
+    }
+
+    /// Start the utility tone generator, or fade it out if it's sounding
+    fn toggle_tone(&mut self) {
+        let (playing, mode) = {
+            let state = self.sequencer_state.read();
+            (state.tone_playing, ToneMode::from_param(state.tone.mode))
+        };
+        if playing {
+            self.dispatch(Command::StopTone);
+            self.set_status("Tone: off".to_string());
+        } else {
+            self.dispatch(Command::PlayTone);
+            self.set_status(format!("Tone: {}", mode.name()));
+        }
+    }
+
+    fn cycle_tone_mode(&mut self) {
+        let mode = ToneMode::from_param(self.sequencer_state.read().tone.mode).next();
+        self.dispatch(Command::SetToneParam {
+            key: "mode".to_string(),
+            value: mode.param(),
+        });
+        self.set_status(format!("Tone mode: {}", mode.name()));
+    }
+
+    /// Move the DJ sweep while its key is held (repeats keep it alive)
+    fn nudge_dj_sweep(&mut self, delta: f32) {
+        let position = self.dj_sweep.map_or(0.0, |(p, _)| p);
+        let position = (position + delta).clamp(-1.0, 1.0);
+        self.dj_sweep = Some((position, Instant::now()));
+        self.dispatch(Command::SetDjFilter(position));
+    }
+
+    /// Terminals don't report key release: once sweep key repeats stop,
+    /// send the filter back to neutral
+    fn poll_dj_sweep(&mut self) {
+        if let Some((_, last)) = self.dj_sweep {
+            if last.elapsed() >= DJ_SWEEP_RELEASE {
+                self.dj_sweep = None;
+                self.dispatch(Command::ReleaseDjFilter);
+            }
+        }
+    }
+
+    /// Pick up sample files changed on disk: refresh the browser and flag affected tracks
+    fn poll_sample_changes(&mut self) {
+        let changes = match self.sample_watcher {
+            Some(ref watcher) => watcher.take_changes(),
+            None => return,
+        };
+        if changes.is_empty() {
+            return;
+        }
+
+        // Refresh the open browser, keeping the cursor on the same file
+        if let Some(ref mut browser) = self.browser_state {
+            let selected = browser.selected_entry().map(|e| e.path.clone());
+            browser.entries = samples::scan_samples(&samples::search_dirs());
+            browser.cursor = selected
+                .and_then(|p| browser.entries.iter().position(|e| e.path == p))
+                .unwrap_or(0);
+            browser.previewing = None;
+        }
+
+        // Offer reload for sampler tracks whose source file changed
+        let state = self.sequencer_state.read();
+        let changed_tracks: Vec<usize> = state
+            .tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.synth_type == SynthType::Sampler)
+            .filter(|(_, t)| {
+                t.params_snapshot
+                    .get("wav_path")
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|p| changes.iter().any(|c| samples::same_sample_path(Path::new(p), c)))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        drop(state);
+
+        if let Some(&track) = changed_tracks.first() {
+            self.set_status(format!(
+                "Sample on track {} changed on disk (Params view: R to reload)",
+                track + 1
+            ));
+        } else if self.browser_state.is_some() {
+            self.set_status(format!("{} sample file(s) changed", changes.len()));
+        }
+    }
+
+    /// Reload a sampler track's sample from its source file
+    fn reload_sample_action(&mut self, track: usize) {
+        let state = self.sequencer_state.read();
+        let wav_path = state
+            .tracks
+            .get(track)
+            .filter(|t| t.synth_type == SynthType::Sampler)
+            .and_then(|t| t.params_snapshot.get("wav_path").and_then(|v| v.as_str()))
+            .map(|p| p.to_string());
+        drop(state);
+
+        let Some(wav_path) = wav_path else {
+            self.set_status("No sample loaded on this track".to_string());
+            return;
+        };
+        match load_wav(Path::new(&wav_path), self.sample_rate()) {
+            Ok(buffer) => {
+                let options = self.sequencer_state.read().sample_options(track);
+                let buffer = prepare_sample(buffer, options);
+                let info = SampleInfo::detect(&buffer, self.sample_rate());
+                let stream_mb = self.sequencer_state.read().sample_stream_mb;
+                self.dispatch(Command::LoadSample {
+                    track,
+                    buffer: SampleAudio::new(buffer, stream_mb),
+                    path: wav_path.clone(),
+                    options,
+                    info,
+                });
+                self.set_status(format!("Reloaded: {}", wav_path));
+            }
+            Err(e) => {
+                self.notify(Severity::Error, format!("Reload failed: {}", e));
+            }
+        }
+    }
+
     /// Handle keys in the sample browser modal
     fn handle_browser_key(&mut self, key: KeyCode) {
         let browser = match self.browser_state.as_mut() {
@@ -1068,20 +4402,54 @@ impl App {
             KeyCode::Down | KeyCode::Char('j') => {
                 browser.move_down();
             }
+            KeyCode::Char('n') => {
+                browser.normalize = !browser.normalize;
+            }
+            KeyCode::Char('t') => {
+                browser.trim_silence = !browser.trim_silence;
+            }
+            // Cycle how far the mix ducks under a preview: off, -6 .. -24 dB
+            // Kit: the selected sample's folder onto tracks 1-N
+            KeyCode::Char('K') => {
+                let Some(dir) = browser.selected_entry().and_then(|e| e.path.parent()).map(Path::to_path_buf) else {
+                    return;
+                };
+                let count = samples::folder_samples(&dir).len().min(MAX_TRACKS);
+                let folder = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+                let message = format!(
+                    "Load {} samples from {}/ onto tracks 1-{}? Those tracks become samplers.",
+                    count, folder, count
+                );
+                self.browser_state = None;
+                self.modal = Some(Modal::confirm("Load Kit", message, Dialog::LoadKit(dir)));
+            }
+            KeyCode::Char('d') => {
+                let current = self.sequencer_state.read().preview_duck_db;
+                let next = PREVIEW_DUCK_STEPS
+                    .iter()
+                    .copied()
+                    .find(|&db| db < current - 0.5)
+                    .unwrap_or(0.0);
+                self.dispatch(Command::SetPreviewDuck(next));
+            }
             KeyCode::Char(' ') => {
                 // Preview selected sample
                 if let Some(entry) = browser.selected_entry() {
                     let path = entry.path.clone();
                     let cursor = browser.cursor;
-                    match load_wav(&path, 44100.0) {
+                    let sample_rate = self.sample_rate();
+                    match load_wav(&path, sample_rate) {
                         Ok(buffer) => {
+                            let root = detect_root_note(&buffer, sample_rate);
+                            let bpm = detect_bpm(&buffer, sample_rate);
                             self.dispatch(Command::PreviewSample(buffer));
                             if let Some(ref mut b) = self.browser_state {
+                                b.detected.insert(cursor, (root, bpm));
                                 b.previewing = Some(cursor);
                             }
                         }
                         Err(e) => {
-                            self.set_status(format!("Preview failed: {}", e));
+                            self.notify(Severity::Error, format!("Preview failed: {}", e));
                         }
                     }
                 }
@@ -1093,18 +4461,55 @@ impl App {
                         let path = entry.path.clone();
                         let relative = entry.relative.clone();
                         let track = browser.target_track;
-                        match load_wav(&path, 44100.0) {
+                        match load_wav(&path, self.sample_rate()) {
                             Ok(buffer) => {
                                 let path_str = path.to_string_lossy().to_string();
-                                self.dispatch(Command::LoadSample {
-                                    track,
-                                    buffer,
-                                    path: path_str,
-                                });
-                                self.set_status(format!("Loaded: {}", relative));
+                                // Layers follow the track's options; a main sample brings its own
+                                let options = match browser.target_layer {
+                                    Some(_) => self.sequencer_state.read().sample_options(track),
+                                    None => SampleOptions {
+                                        normalize: browser.normalize,
+                                        trim_silence: browser.trim_silence,
+                                    },
+                                };
+                                let buffer = prepare_sample(buffer, options);
+                                // Main samples are analyzed and may stream; layers stay in memory
+                                let (load, incoming) = match browser.target_layer {
+                                    Some(layer) => {
+                                        let incoming = buffer.len() * std::mem::size_of::<f32>();
+                                        let buffer = buffer.into();
+                                        (Command::LoadSampleLayer { track, layer, buffer, path: path_str }, incoming)
+                                    }
+                                    None => {
+                                        let info = SampleInfo::detect(&buffer, self.sample_rate());
+                                        let stream_mb = self.sequencer_state.read().sample_stream_mb;
+                                        let buffer = SampleAudio::new(buffer, stream_mb);
+                                        let incoming = buffer.memory_bytes();
+                                        (Command::LoadSample { track, buffer, path: path_str, options, info }, incoming)
+                                    }
+                                };
+                                let over_budget = {
+                                    let state = self.sequencer_state.read();
+                                    // A layer adds to the track; a main sample replaces it
+                                    let current = match browser.target_layer {
+                                        Some(_) => 0,
+                                        None => state.tracks.get(track).map_or(0, |t| t.sample_bytes),
+                                    };
+                                    let total = state.sample_memory_bytes() - current + incoming;
+                                    total as f32 > state.sample_budget_mb * 1024.0 * 1024.0
+                                };
+                                self.dispatch(load);
+                                if let Some(layer) = browser.target_layer {
+                                    self.param_editor.layer_index = Some(layer);
+                                }
+                                if over_budget {
+                                    self.notify(Severity::Warning, format!("Loaded: {} (over sample memory budget)", relative));
+                                } else {
+                                    self.set_status(format!("Loaded: {}", relative));
+                                }
                             }
                             Err(e) => {
-                                self.set_status(format!("Load failed: {}", e));
+                                self.notify(Severity::Error, format!("Load failed: {}", e));
                             }
                         }
                     }
@@ -1118,8 +4523,16 @@ impl App {
     fn toggle_current_fx(&mut self) {
         let num_tracks = self.num_tracks();
         if self.fx_editor.is_master(num_tracks) {
-            // Master: toggle reverb
-            self.dispatch(Command::ToggleMasterFxEnabled);
+            // Master: toggle the limiter on its rows, else the reverb
+            if matches!(
+                self.fx_editor.master_param(),
+                Some(MasterFxParamId::LimiterCeiling | MasterFxParamId::LimiterRelease)
+            ) {
+                let enabled = !self.sequencer_state.read().master_fx.limiter_enabled;
+                self.dispatch(Command::SetLimiterEnabled(enabled));
+            } else {
+                self.dispatch(Command::ToggleMasterFxEnabled);
+            }
         } else {
             let track = self.fx_editor.track;
             let (section, _) = self.fx_editor.current_section_and_param();
@@ -1127,6 +4540,11 @@ impl App {
                 0 => FxType::Filter,
                 1 => FxType::Distortion,
                 2 => FxType::Delay,
+                // Modulation has no bypass: Enter switches the source off
+                3 => {
+                    self.dispatch(Command::SetFxModSource { track, source: None });
+                    return;
+                }
                 _ => return,
             };
             self.dispatch(Command::ToggleFxEnabled { track, fx });
@@ -1137,12 +4555,12 @@ impl App {
     fn adjust_fx_param(&mut self, delta_normalized: f32) {
         let num_tracks = self.num_tracks();
         if self.fx_editor.is_master(num_tracks) {
-            // Master FX
-            let params = MasterFxParamId::all();
-            if self.fx_editor.param_index >= params.len() {
+            // Master FX: the mode row toggles normal/gated reverb
+            let Some(param) = self.fx_editor.master_param() else {
+                let mode = self.sequencer_state.read().master_fx.reverb_mode.toggled();
+                self.dispatch(Command::SetReverbMode(mode));
                 return;
-            }
-            let param = params[self.fx_editor.param_index];
+            };
             let (min, max, _default) = param.range();
             let current = crate::ui::fx::get_master_fx_param_value(
                 &self.sequencer_state.read(),
@@ -1174,6 +4592,33 @@ impl App {
                 return;
             }
 
+            // Modulation source cycles off -> other tracks; target toggles
+            if section == 3 && local_idx < 2 {
+                let state = self.sequencer_state.read();
+                let Some(fx) = state.tracks.get(track).map(|t| t.fx.clone()) else {
+                    return;
+                };
+                drop(state);
+                if local_idx == 1 {
+                    let target = fx.mod_target.toggled();
+                    self.dispatch(Command::SetFxModTarget { track, target });
+                    return;
+                }
+                // Slot 0 is "off", slot n is track n-1; this track is skipped
+                let slots = num_tracks + 1;
+                let dir = if delta_normalized > 0.0 { 1 } else { slots - 1 };
+                let mut slot = fx.modulator(track, num_tracks).map_or(0, |s| s + 1);
+                loop {
+                    slot = (slot + dir) % slots;
+                    if slot != track + 1 {
+                        break;
+                    }
+                }
+                let source = slot.checked_sub(1);
+                self.dispatch(Command::SetFxModSource { track, source });
+                return;
+            }
+
             // Map (section, local_idx) to FxParamId
             let param = match (section, local_idx) {
                 (0, 1) => FxParamId::FilterCutoff,
@@ -1183,6 +4628,8 @@ impl App {
                 (2, 0) => FxParamId::DelayTime,
                 (2, 1) => FxParamId::DelayFeedback,
                 (2, 2) => FxParamId::DelayMix,
+                (3, 2) => FxParamId::ModDepth,
+                (4, 0) => FxParamId::ChainMix,
                 _ => return,
             };
 
@@ -1201,6 +4648,15 @@ impl App {
         }
     }
 
+    /// Toggle a track's cue, if there's a cue output to hear it on
+    fn toggle_cue(&mut self, track: usize) {
+        if self.sequencer_state.read().cue_output.is_none() {
+            self.set_status("No cue output (start with --cue)".to_string());
+            return;
+        }
+        self.dispatch(Command::ToggleCue(track));
+    }
+
     /// Adjust a mixer value based on current field selection
     fn adjust_mixer_value(&mut self, direction: i32) {
         let track = self.mixer_state.selected_track;
@@ -1235,6 +4691,23 @@ impl App {
                 drop(state);
                 self.dispatch(Command::ToggleSolo(track));
             }
+            MixerField::SoloSafe => {
+                drop(state);
+                self.dispatch(Command::ToggleSoloSafe(track));
+            }
+            MixerField::Cue => {
+                drop(state);
+                self.toggle_cue(track);
+            }
+            MixerField::Nudge => {
+                let current = state.tracks[track].nudge_ms;
+                drop(state);
+                let new_nudge = (current + direction as f32).clamp(0.0, MAX_NUDGE_MS);
+                self.dispatch(Command::SetTrackNudge {
+                    track,
+                    ms: new_nudge,
+                });
+            }
         }
     }
 
@@ -1259,6 +4732,46 @@ impl App {
         });
     }
 
+    /// Tie the current step onto the one before it, or untie it
+    fn toggle_step_tie(&mut self) {
+        let track = self.grid_state.cursor_track;
+        let step = self.grid_state.cursor_step;
+        let tie = !self.sequencer_state.read().pattern.get_step(track, step).tie;
+        self.dispatch(Command::SetStepTie { track, step, tie });
+        let action = if tie { "Tied" } else { "Untied" };
+        self.set_status(format!("{} step {}", action, step + 1));
+    }
+
+    /// Make the current step a note-off step, or clear it
+    fn toggle_step_off(&mut self) {
+        let track = self.grid_state.cursor_track;
+        let step = self.grid_state.cursor_step;
+        let off = !self.sequencer_state.read().pattern.get_step(track, step).off;
+        self.dispatch(Command::SetStepOff { track, step, off });
+        let action = if off { "Note-off on" } else { "Cleared note-off on" };
+        self.set_status(format!("{} step {}", action, step + 1));
+    }
+
+    /// Make the current step slide into its note, or not
+    fn toggle_step_slide(&mut self) {
+        let track = self.grid_state.cursor_track;
+        let step = self.grid_state.cursor_step;
+        let slide = !self.sequencer_state.read().pattern.get_step(track, step).slide;
+        self.dispatch(Command::SetStepSlide { track, step, slide });
+        let action = if slide { "Slide into" } else { "No slide into" };
+        self.set_status(format!("{} step {}", action, step + 1));
+    }
+
+    /// Accent the current step, or clear its accent
+    fn toggle_step_accent(&mut self) {
+        let track = self.grid_state.cursor_track;
+        let step = self.grid_state.cursor_step;
+        let accent = !self.sequencer_state.read().pattern.get_step(track, step).accent;
+        self.dispatch(Command::SetStepAccent { track, step, accent });
+        let action = if accent { "Accented" } else { "Unaccented" };
+        self.set_status(format!("{} step {}", action, step + 1));
+    }
+
     /// Adjust the velocity of the current step in grid view
     fn adjust_step_velocity(&mut self, delta: i32) {
         let track = self.grid_state.cursor_track;
@@ -1334,16 +4847,12 @@ impl App {
         let bg_block = Block::default().style(Style::default().bg(self.theme.bg));
         frame.render_widget(bg_block, area);
 
-        // Layout: header, transport, main content, footer
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3), // Header
-                Constraint::Length(3), // Transport
-                Constraint::Min(6),    // Main content (grid or params)
-                Constraint::Length(3), // Footer
-            ])
-            .split(area);
+        if is_too_small(area) {
+            render_too_small(frame, area, &self.theme);
+            return;
+        }
+        let compact = is_compact(area);
+        let chunks = screen_areas(area);
 
         // Get current state
         let state = self.sequencer_state.read();
@@ -1371,13 +4880,20 @@ impl App {
             arrangement_position: state.arrangement_position,
             arrangement_len: state.arrangement.len(),
             cursor_note,
-            pending_pattern: None,
+            pending_pattern: state.pending_pattern,
             current_variation: state.current_variation,
+            transpose: state.transpose,
+            midi_sync: state.midi_sync,
+            swing: state.swing,
+            headroom: state.headroom,
+            master_over: state.master_meter.clipping(),
+            capture: state.capture,
         };
         render_transport(
             frame,
             chunks[1],
             &transport_info,
+            compact,
             &self.theme,
         );
 
@@ -1385,16 +4901,34 @@ impl App {
         match self.view {
             View::Grid => {
                 let track_names: Vec<String> = state.tracks.iter().map(|t| t.name.clone()).collect();
+                let glyphs: Vec<String> = state.tracks.iter().map(|t| t.display_glyph().to_string()).collect();
+                let triplets: Vec<bool> = state.tracks.iter().map(|t| t.triplet).collect();
+                let transposes: Vec<i8> = state.tracks.iter().map(|t| t.transpose).collect();
                 render_grid(
                     frame,
                     chunks[2],
                     &state.pattern,
                     &self.grid_state,
                     state.current_step,
+                    state.run_steps,
                     state.playing,
                     &track_names,
+                    &glyphs,
+                    &triplets,
+                    &transposes,
                     &self.theme,
                 );
+                if self.step_inspector {
+                    let (track, step) = (self.grid_state.cursor_track, self.grid_state.cursor_step);
+                    if let Some(name) = track_names.get(track) {
+                        let data = state.pattern.get_step_var(track, step, state.current_variation);
+                        render_step_inspector(frame, chunks[2], name, step, data, &self.theme);
+                    }
+                }
+                if let Some(ref editor) = self.lock_editor {
+                    let (track, step) = (self.grid_state.cursor_track, self.grid_state.cursor_step);
+                    render_lock_editor(frame, chunks[2], &state, track, step, editor, &self.theme);
+                }
             }
             View::Params => {
                 render_params(frame, chunks[2], &state, &self.param_editor, &self.theme);
@@ -1408,6 +4942,38 @@ impl App {
             View::Song => {
                 render_song(frame, chunks[2], &state, &self.song_state, &self.theme);
             }
+            View::Bank => {
+                render_bank(frame, chunks[2], &state, &self.bank_state, &self.theme);
+            }
+            View::Scope => {
+                let (master, track) = self.analyzer.snapshot();
+                let overlay_name = self
+                    .scope_state
+                    .overlay_track
+                    .and_then(|t| state.tracks.get(t))
+                    .map(|t| t.name.clone());
+                let sample_rate = state.sample_rate;
+                drop(state);
+                let overlay = overlay_name.as_deref().zip(track.as_deref());
+                render_scope(frame, chunks[2], &master, overlay, sample_rate, &self.theme);
+            }
+            View::Scripts => {
+                let scripts = state.scripts.clone();
+                drop(state);
+                render_scripts(
+                    frame,
+                    chunks[2],
+                    &scripts,
+                    &self.script_host.status,
+                    &self.scripts_state,
+                    &self.theme,
+                );
+            }
+            View::Settings => {
+                let midi_sync = state.midi_sync;
+                drop(state);
+                render_settings(frame, chunks[2], &self.settings_state, &self.config, midi_sync, &self.theme);
+            }
             View::Help => {
                 drop(state);
                 render_help(frame, chunks[2], &self.help_state, &self.theme);
@@ -1418,7 +4984,28 @@ impl App {
 
         // Render browser overlay on top if active
         if let Some(ref browser) = self.browser_state {
-            render_browser(frame, chunks[2], browser, &self.theme);
+            let state = self.sequencer_state.read();
+            let memory = (state.sample_memory_bytes(), state.sample_budget_mb);
+            let duck_db = state.preview_duck_db;
+            drop(state);
+            render_browser(frame, chunks[2], browser, memory, duck_db, &self.theme);
+        }
+
+        if self.notifications.open {
+            render_notifications(frame, chunks[2], &self.notifications, &self.theme);
+        }
+
+        if self.stats_open {
+            let stats = SessionStats::collect(&self.event_log.read(), &self.sequencer_state.read());
+            render_stats(frame, chunks[2], &stats, &self.theme);
+        }
+
+        if self.queue_open {
+            render_export_queue(frame, chunks[2], &self.exports.jobs(), self.queue_cursor, &self.theme);
+        }
+
+        if let Some(ref modal) = self.modal {
+            render_modal(frame, chunks[2], modal, &self.theme);
         }
     }
 
@@ -1430,6 +5017,10 @@ impl App {
             View::Mixer => "[MIXER]",
             View::Fx => "[FX]",
             View::Song => "[SONG]",
+            View::Bank => "[BANK]",
+            View::Scope => "[SCOPE]",
+            View::Scripts => "[SCRIPTS]",
+            View::Settings => "[SETTINGS]",
             View::Help => "[HELP]",
         };
         let title = format!(
@@ -1437,7 +5028,21 @@ impl App {
             env!("CARGO_PKG_VERSION"),
             view_indicator
         );
-        let header = Paragraph::new(title)
+        // Tab bar once a second project is open; * marks unsaved changes
+        let mut spans = vec![Span::raw(title)];
+        if self.tabs.len() > 1 {
+            for (i, tab) in self.tabs.iter().enumerate() {
+                let label = format!(" {}:{}{} ", i + 1, tab.label(), if tab.dirty { "*" } else { "" });
+                let style = if i == self.active_tab {
+                    Style::default().fg(self.theme.bg).bg(self.theme.highlight)
+                } else {
+                    Style::default().fg(self.theme.dimmed).not_bold()
+                };
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(label, style));
+            }
+        }
+        let header = Paragraph::new(Line::from(spans))
             .style(
                 Style::default()
                     .fg(self.theme.highlight)
@@ -1456,19 +5061,38 @@ impl App {
 
     /// Render the footer with help or status message
     fn render_footer(&self, frame: &mut Frame, area: Rect) {
-        // Show status message if recent (within 3 seconds)
-        let text = if let Some((ref msg, instant)) = self.status_message {
-            if instant.elapsed().as_secs() < 3 {
-                msg.clone()
-            } else {
-                self.footer_help()
+        // Show the latest notification if recent, in its severity color
+        let mut color = self.theme.dimmed;
+        let mut text = if let Some(ref dialog) = self.copy_tracks {
+            self.copy_tracks_prompt(dialog)
+        } else if let Some(note) = self.notifications.current() {
+            if note.severity != Severity::Info {
+                color = note.severity.color(&self.theme);
             }
+            note.message.clone()
         } else {
             self.footer_help()
         };
+        if self.notifications.unseen() > 0 {
+            text = format!("[! {} C-n] {}", self.notifications.unseen(), text);
+        }
+        if let Some((position, _)) = self.dj_sweep {
+            let side = if position < 0.0 { "LP" } else { "HP" };
+            text = format!("[DJ {} {:.0}%] {}", side, position.abs() * 100.0, text);
+        }
+        if let Some(ref recorded) = self.macro_recording {
+            text = format!("[REC {}] {}", recorded.len(), text);
+        }
+        if let Some(rec) = self.loop_record.as_ref().filter(|r| r.recording || r.snapshots.is_empty()) {
+            text = if rec.recording {
+                format!("[LOOP {} {}/{}] {}", pattern_label(rec.pattern), rec.pass + 1, LOOP_RECORD_BARS, text)
+            } else {
+                format!("[LOOP {} armed] {}", pattern_label(rec.pattern), text)
+            };
+        }
 
         let footer = Paragraph::new(text)
-            .style(Style::default().fg(self.theme.dimmed).bg(self.theme.bg))
+            .style(Style::default().fg(color).bg(self.theme.bg))
             .alignment(Alignment::Center)
             .block(
                 Block::default()
@@ -1494,11 +5118,27 @@ impl App {
                 self.theme.name
             ),
             View::Fx => format!(
-                "1-9:Track | M:Master | Up/Down:Select | Left/Right:Adjust | SPACE:Toggle FX | G:Help | TAB:Song | Q:Quit | {}",
+                "1-9:Track | M:Master | Up/Down:Select | Left/Right:Adjust | SPACE:Toggle FX | A:Gain match | G:Help | TAB:Song | Q:Quit | {}",
                 self.theme.name
             ),
             View::Song => format!(
-                "Up/Down:Move | Left/Right:Repeats | +/-:Pattern | A:Add | D:Delete | M:Mode | G:Help | TAB:Grid | Q:Quit | {}",
+                "Up/Down:Move | Left/Right:Repeats | +/-:Pattern | A:Add | D:Delete | M:Mode | G:Help | TAB:Bank | Q:Quit | {}",
+                self.theme.name
+            ),
+            View::Bank => format!(
+                "Arrows:Move | </>:Bank | Enter:Select | C:Copy | X:Clear | A:Chain | P:Play | G:Help | TAB:Scope | Q:Quit | {}",
+                self.theme.name
+            ),
+            View::Scope => format!(
+                "1-9:Overlay track | 0:Master only | P:Play | S:Stop | G:Help | TAB:Scripts | Q:Quit | {}",
+                self.theme.name
+            ),
+            View::Scripts => format!(
+                "Up/Down:Select | SPACE:On/Off | Left/Right:Every | N:New | E:Edit | L:Load | R:Run | D:Delete | TAB:Grid | Q:Quit | {}",
+                self.theme.name
+            ),
+            View::Settings => format!(
+                "Up/Down:Select | Left/Right:Change | Enter:Add dir | Bksp:Remove dir | Esc:Back | Q:Quit | {}",
                 self.theme.name
             ),
             View::Help => format!(
@@ -1508,3 +5148,32 @@ impl App {
         }
     }
 }
+
+/// The option `delta` places from `current` (wrapping), or the first if
+/// `current` isn't one of them
+fn cycle_option<T: Clone + PartialEq>(options: &[T], current: &T, delta: isize) -> T {
+    let index = options.iter().position(|o| o == current).map_or(0, |i| {
+        (i as isize + delta).rem_euclid(options.len() as isize) as usize
+    });
+    options[index].clone()
+}
+
+/// What a project load's clamp warnings add to its status message
+fn clamp_summary(warnings: &[String]) -> String {
+    match warnings.first() {
+        Some(first) if warnings.len() > 1 => format!(" ({} values clamped, e.g. {})", warnings.len(), first),
+        Some(first) => format!(" ({})", first),
+        None => String::new(),
+    }
+}
+
+/// A file's age in its largest whole unit, e.g. "5 min"
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => format!("{} s", secs),
+        60..=3599 => format!("{} min", secs / 60),
+        3600..=86_399 => format!("{} h", secs / 3600),
+        _ => format!("{} d", secs / 86_400),
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use parking_lot::Mutex;
+
+/// Samples per analysis window (power of two for the FFT)
+pub const ANALYZER_SIZE: usize = 2048;
+
+/// Floor of the displayed range in dB
+pub const ANALYZER_FLOOR_DB: f32 = -72.0;
+
+const NO_OVERLAY: usize = usize::MAX;
+
+/// Latest complete analysis windows, shared between audio thread and UI
+struct AnalyzerFrames {
+    master: Vec<f32>,
+    track: Vec<f32>,
+    /// Track the `track` window was captured from
+    track_index: usize,
+}
+
+/// Master output tap for the spectrum analyzer, plus the post-fader signal
+/// of one overlay track. The overlay is captured whether or not the track is
+/// audible, so it never touches mute/solo.
+pub struct AnalyzerTap {
+    overlay: AtomicUsize,
+    frames: Mutex<AnalyzerFrames>,
+}
+
+impl AnalyzerTap {
+    pub fn new() -> Self {
+        Self {
+            overlay: AtomicUsize::new(NO_OVERLAY),
+            frames: Mutex::new(AnalyzerFrames {
+                master: vec![0.0; ANALYZER_SIZE],
+                track: vec![0.0; ANALYZER_SIZE],
+                track_index: NO_OVERLAY,
+            }),
+        }
+    }
+
+    /// Choose which track is captured for the overlay (None = master only)
+    pub fn set_overlay(&self, track: Option<usize>) {
+        self.overlay.store(track.unwrap_or(NO_OVERLAY), Ordering::Relaxed);
+    }
+
+    pub fn overlay(&self) -> Option<usize> {
+        match self.overlay.load(Ordering::Relaxed) {
+            NO_OVERLAY => None,
+            track => Some(track),
+        }
+    }
+
+    /// Copy out the latest master window and, if an overlay track is
+    /// selected and already captured, its window
+    pub fn snapshot(&self) -> (Vec<f32>, Option<Vec<f32>>) {
+        let frames = self.frames.lock();
+        let track = (self.overlay() == Some(frames.track_index)).then(|| frames.track.clone());
+        (frames.master.clone(), track)
+    }
+}
+
+impl Default for AnalyzerTap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Audio-thread side of the tap: fills a window, then publishes it
+/// without blocking (a window is skipped if the UI holds the lock)
+pub struct AnalyzerCapture {
+    master: Vec<f32>,
+    track: Vec<f32>,
+    pos: usize,
+}
+
+impl AnalyzerCapture {
+    pub fn new() -> Self {
+        Self {
+            master: vec![0.0; ANALYZER_SIZE],
+            track: vec![0.0; ANALYZER_SIZE],
+            pos: 0,
+        }
+    }
+
+    /// Add one sample of master (mono) and overlay-track signal
+    pub fn push(&mut self, tap: &AnalyzerTap, master: f32, track: f32) {
+        self.master[self.pos] = master;
+        self.track[self.pos] = track;
+        self.pos += 1;
+        if self.pos < ANALYZER_SIZE {
+            return;
+        }
+        self.pos = 0;
+        if let Some(mut frames) = tap.frames.try_lock() {
+            frames.master.copy_from_slice(&self.master);
+            frames.track.copy_from_slice(&self.track);
+            frames.track_index = tap.overlay.load(Ordering::Relaxed);
+        }
+    }
+}
+
+impl Default for AnalyzerCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// In-place iterative radix-2 FFT (length must be a power of two)
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f32::consts::PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let a = start + k;
+                let b = a + len / 2;
+                let tr = re[b] * cos - im[b] * sin;
+                let ti = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// Magnitude spectrum of a window in `bands` log-spaced bands from 20 Hz to
+/// 20 kHz, in dB (clamped to ANALYZER_FLOOR_DB)
+pub fn spectrum_bands(samples: &[f32], sample_rate: f32, bands: usize) -> Vec<f32> {
+    let n = samples.len();
+    // Hann window
+    let mut re: Vec<f32> = samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let w = 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / n as f32).cos();
+            s * w
+        })
+        .collect();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+
+    // Hann window coherent gain is 0.5, so a full-scale sine reads ~0 dB
+    let scale = 4.0 / n as f32;
+    let bin_hz = sample_rate / n as f32;
+    let (lo, hi) = (20.0f32, 20000.0f32.min(sample_rate * 0.5));
+    (0..bands)
+        .map(|b| {
+            let f0 = lo * (hi / lo).powf(b as f32 / bands as f32);
+            let f1 = lo * (hi / lo).powf((b + 1) as f32 / bands as f32);
+            let first = ((f0 / bin_hz) as usize).clamp(1, n / 2 - 1);
+            let last = ((f1 / bin_hz) as usize).clamp(first, n / 2 - 1);
+            let peak = (first..=last)
+                .map(|k| (re[k] * re[k] + im[k] * im[k]).sqrt() * scale)
+                .fold(0.0f32, f32::max);
+            (20.0 * peak.max(1e-9).log10()).max(ANALYZER_FLOOR_DB)
+        })
+        .collect()
+}
@@ -0,0 +1,262 @@
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+
+/// Longest capture, in steps (four bars)
+pub const MAX_CAPTURE_STEPS: usize = 64;
+/// Captures held for the UI while its queue is full
+const HELD_CAPTURES: usize = 4;
+
+/// Where a sample capture is, for the UI (runtime only)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CaptureStatus {
+    pub track: usize,
+    pub steps: usize,
+    /// Fraction captured so far; None while armed for the next step
+    pub progress: Option<f32>,
+    /// Frames the input had nothing for while capturing
+    pub underruns: usize,
+}
+
+/// A capture from the hardware input back from the audio thread: finished
+/// for the UI to normalize, write out and load into its track, or
+/// discarded so its buffer is freed there
+pub struct CapturedSample {
+    pub track: usize,
+    pub buffer: Vec<f32>,
+    pub underruns: usize,
+    /// Cancelled, replaced or its track removed before it finished
+    pub discarded: bool,
+}
+
+/// Buffer for a capture of `steps` steps at `bpm`, allocated by the sender
+/// of RecordSample so the audio thread never allocates one
+pub fn capture_buffer(steps: usize, bpm: f32, sample_rate: f32) -> Vec<f32> {
+    let samples_per_step = sample_rate * 60.0 / bpm.max(1.0) / 4.0;
+    Vec::with_capacity((steps as f32 * samples_per_step).ceil() as usize + 1)
+}
+
+/// Recording from the hardware input into a sampler track: armed until
+/// the next step (or at once when stopped), then capturing a fixed length
+pub struct SampleCapture {
+    pub track: usize,
+    pub steps: usize,
+    length: usize,
+    buffer: Vec<f32>,
+    started: bool,
+    underruns: usize,
+}
+
+impl SampleCapture {
+    /// Arm a capture of `steps` steps lasting `length` samples in all,
+    /// recorded into `buffer` (see `capture_buffer`). It never grows past
+    /// its capacity, so capturing doesn't allocate.
+    pub fn new(track: usize, steps: usize, length: usize, mut buffer: Vec<f32>) -> Self {
+        buffer.clear();
+        let length = length.min(buffer.capacity()).max(1);
+        Self {
+            track,
+            steps,
+            length,
+            buffer,
+            started: false,
+            underruns: 0,
+        }
+    }
+
+    /// Start capturing (a step has begun)
+    pub fn start(&mut self) {
+        self.started = true;
+    }
+
+    /// Record one input sample if capturing; true once the capture is full.
+    /// A frame without input is counted rather than recorded as silence.
+    pub fn push(&mut self, sample: Option<f32>) -> bool {
+        if self.started && self.buffer.len() < self.length.min(self.buffer.capacity()) {
+            match sample {
+                Some(sample) => self.buffer.push(sample),
+                None => self.underruns += 1,
+            }
+        }
+        self.buffer.len() >= self.length.min(self.buffer.capacity())
+    }
+
+    pub fn status(&self) -> CaptureStatus {
+        CaptureStatus {
+            track: self.track,
+            steps: self.steps,
+            progress: self.started.then(|| self.buffer.len() as f32 / self.length as f32),
+            underruns: self.underruns,
+        }
+    }
+
+    pub fn finish(self) -> CapturedSample {
+        self.into_sample(false)
+    }
+
+    /// Give the buffer back without a take to save
+    pub fn discard(self) -> CapturedSample {
+        self.into_sample(true)
+    }
+
+    fn into_sample(self, discarded: bool) -> CapturedSample {
+        CapturedSample {
+            track: self.track,
+            buffer: self.buffer,
+            underruns: self.underruns,
+            discarded,
+        }
+    }
+}
+
+/// Captures on their way from the audio thread to the UI, which frees or
+/// saves them. Ones the full queue turns away are held and sent again.
+pub struct CaptureReturn {
+    queue: Sender<CapturedSample>,
+    held: Vec<CapturedSample>,
+}
+
+impl CaptureReturn {
+    /// Made before the audio thread starts: room for the held captures
+    /// is reserved here
+    pub fn new(queue: Sender<CapturedSample>) -> Self {
+        Self {
+            queue,
+            held: Vec::with_capacity(HELD_CAPTURES),
+        }
+    }
+
+    pub fn send(&mut self, capture: CapturedSample) {
+        // Earlier captures go first
+        self.flush();
+        if !self.held.is_empty() {
+            self.hold(capture);
+            return;
+        }
+        if let Err(TrySendError::Full(capture)) = self.queue.try_send(capture) {
+            self.hold(capture);
+        }
+    }
+
+    /// Send held captures while the queue has room
+    pub fn flush(&mut self) {
+        while !self.held.is_empty() {
+            let capture = self.held.remove(0);
+            if let Err(TrySendError::Full(capture)) = self.queue.try_send(capture) {
+                self.held.insert(0, capture);
+                return;
+            }
+        }
+    }
+
+    fn hold(&mut self, capture: CapturedSample) {
+        // Past the reserved room the UI has stopped draining; dropping
+        // here beats allocating on the audio thread
+        if self.held.len() < self.held.capacity() {
+            self.held.push(capture);
+        }
+    }
+}
+
+/// Jitter buffer in front of the input queue: the input and output
+/// callbacks run on different clocks and block sizes, so reading starts
+/// only once `prefill` samples are queued, and again after it runs dry
+pub struct InputJitter {
+    prefill: usize,
+    primed: bool,
+}
+
+impl InputJitter {
+    pub fn new(prefill: usize) -> Self {
+        Self {
+            prefill: prefill.max(1),
+            primed: false,
+        }
+    }
+
+    /// The next input sample, or None while the queue refills
+    pub fn pull(&mut self, input: &Receiver<f32>) -> Option<f32> {
+        if !self.primed {
+            if input.len() < self.prefill {
+                return None;
+            }
+            self.primed = true;
+        }
+        let sample = input.try_recv().ok();
+        self.primed = sample.is_some();
+        sample
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::bounded;
+
+    #[test]
+    fn counts_missing_input_instead_of_recording_silence() {
+        let mut capture = SampleCapture::new(0, 1, 3, Vec::with_capacity(3));
+        // Armed, nothing is recorded or counted
+        assert!(!capture.push(None));
+        assert!(!capture.push(Some(0.5)));
+        capture.start();
+        assert!(!capture.push(Some(0.1)));
+        assert!(!capture.push(None));
+        assert!(!capture.push(Some(0.2)));
+        assert!(capture.push(Some(0.3)));
+        assert_eq!(capture.status().underruns, 1);
+        let sample = capture.finish();
+        assert_eq!(sample.buffer, vec![0.1, 0.2, 0.3]);
+        assert_eq!(sample.underruns, 1);
+        assert!(!sample.discarded);
+    }
+
+    #[test]
+    fn never_grows_the_buffer_it_was_given() {
+        let buffer = capture_buffer(4, 120.0, 8000.0);
+        assert!(buffer.capacity() >= 4000);
+        // Asked for more than the buffer holds (the tempo rose meanwhile)
+        let mut capture = SampleCapture::new(0, 4, 10_000, Vec::with_capacity(2));
+        capture.start();
+        assert!(!capture.push(Some(0.1)));
+        assert!(capture.push(Some(0.2)));
+        assert!(capture.push(Some(0.3)));
+        assert_eq!(capture.discard().buffer, vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn holds_captures_until_the_queue_has_room() {
+        let (tx, rx) = bounded(1);
+        let mut returns = CaptureReturn::new(tx);
+        let take = |track| SampleCapture::new(track, 1, 1, Vec::with_capacity(1)).discard();
+        returns.send(take(0));
+        returns.send(take(1));
+        returns.send(take(2));
+        assert_eq!(rx.try_recv().map(|c| c.track).ok(), Some(0));
+        assert!(rx.try_recv().is_err());
+        returns.flush();
+        assert_eq!(rx.try_recv().map(|c| c.track).ok(), Some(1));
+        returns.send(take(3));
+        assert_eq!(rx.try_recv().map(|c| c.track).ok(), Some(2));
+        returns.flush();
+        assert_eq!(rx.try_recv().map(|c| c.track).ok(), Some(3));
+    }
+
+    #[test]
+    fn waits_for_the_prefill_after_running_dry() {
+        let (tx, rx) = bounded(16);
+        let mut jitter = InputJitter::new(3);
+        tx.send(1.0).unwrap();
+        tx.send(2.0).unwrap();
+        assert_eq!(jitter.pull(&rx), None);
+        tx.send(3.0).unwrap();
+        assert_eq!(jitter.pull(&rx), Some(1.0));
+        assert_eq!(jitter.pull(&rx), Some(2.0));
+        assert_eq!(jitter.pull(&rx), Some(3.0));
+        // Dry: refills before reading again
+        assert_eq!(jitter.pull(&rx), None);
+        tx.send(4.0).unwrap();
+        assert_eq!(jitter.pull(&rx), None);
+        tx.send(5.0).unwrap();
+        tx.send(6.0).unwrap();
+        assert_eq!(jitter.pull(&rx), Some(4.0));
+    }
+}
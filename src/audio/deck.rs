@@ -0,0 +1,77 @@
+use crate::sequencer::{Trigger, TriggerDelay};
+use crate::synth::SoundSource;
+
+/// Crossfader move per key press
+pub const CROSSFADER_STEP: f32 = 0.125;
+
+/// Deck B of the crossfader performance mode: a second pattern slot played
+/// through its own copies of the track voices. Deck A is the current pattern.
+pub struct DeckB {
+    /// Pattern slot playing on deck B
+    pub slot: usize,
+    /// Per-track voice copies (None for sources that can't be copied)
+    pub voices: Vec<Option<Box<dyn SoundSource>>>,
+    /// Per-track held triggers, nudged like the main voices
+    pub delays: Vec<TriggerDelay>,
+}
+
+impl DeckB {
+    /// Copy the current voices and nudges for a deck playing `slot`
+    pub fn new(slot: usize, synths: &[Box<dyn SoundSource>], delays: &[TriggerDelay]) -> Self {
+        Self {
+            slot,
+            voices: synths.iter().map(|s| s.voice_copy()).collect(),
+            delays: delays
+                .iter()
+                .map(|d| {
+                    let mut delay = d.clone();
+                    delay.clear();
+                    delay
+                })
+                .collect(),
+        }
+    }
+
+    /// Copy a track's voice again after its sound changed (sample loads,
+    /// layers, synth type)
+    pub fn refresh(&mut self, track: usize, synth: &dyn SoundSource) {
+        if let Some(voice) = self.voices.get_mut(track) {
+            *voice = synth.voice_copy();
+        }
+    }
+
+    /// Play a due step trigger (a note, or a note-off) on a track's voice
+    pub fn trigger(&mut self, track: usize, trigger: Trigger) {
+        if let Some(Some(voice)) = self.voices.get_mut(track) {
+            if trigger.off {
+                voice.release();
+            } else {
+                voice.set_articulation(trigger.slide, trigger.accent);
+                voice.trigger_with_note_velocity(trigger.note, trigger.velocity);
+            }
+        }
+    }
+
+    pub fn next_sample(&mut self, track: usize) -> f32 {
+        match self.voices.get_mut(track) {
+            Some(Some(voice)) => voice.next_sample(),
+            _ => 0.0,
+        }
+    }
+
+    /// Silence the voices and drop held triggers (on stop)
+    pub fn stop(&mut self) {
+        for voice in self.voices.iter_mut().flatten() {
+            voice.stop();
+        }
+        for delay in self.delays.iter_mut() {
+            delay.clear();
+        }
+    }
+}
+
+/// Equal-power deck gains (A, B) for a crossfader position from 0 (A) to 1 (B)
+pub fn crossfade_gains(position: f32) -> (f32, f32) {
+    let angle = position.clamp(0.0, 1.0) * std::f32::consts::FRAC_PI_2;
+    (angle.cos(), angle.sin())
+}
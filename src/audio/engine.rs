@@ -1,23 +1,59 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream, StreamConfig};
+use cpal::{Device, SampleFormat, Stream, StreamConfig, SupportedStreamConfig};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use midir::MidiInputConnection;
 use parking_lot::RwLock;
 use serde_json::Value;
+use tracing::{info, warn};
 
+use super::analyzer::{AnalyzerCapture, AnalyzerTap};
+use super::capture::{CaptureReturn, CaptureStatus, CapturedSample, InputJitter, SampleCapture, MAX_CAPTURE_STEPS};
+use super::deck::{crossfade_gains, DeckB};
+use super::locks::HeldLocks;
+use super::meter::{LevelMeter, MeterBallistics};
+use super::metronome::Metronome;
 use crate::command::{Command, CommandReceiver};
+use crate::midi::clock::{CLOCK_CONTINUE, CLOCK_PULSE, CLOCK_START, CLOCK_STOP};
+use crate::midi::{self, ClockMessage, MidiSync, NoteOn};
 use crate::fx::{
-    configure_fx_chain, FxParamId, FxType, MasterFxParamId, MasterFxState, StereoReverb,
-    TrackFxChain, TrackFxState,
+    configure_fx_chain, configure_limiter, configure_mid_side, configure_reverb, DcBlocker, DjFilter,
+    FxParamId, FxType, Limiter, MasterFxParamId, MasterFxState, MidSide, SmoothedParam, StereoReverb,
+    TrackFxChain, TrackFxState, TruePeak,
 };
+use crate::script::{Script, MAX_SCRIPTS};
 use crate::sequencer::{
-    Arrangement, Clock, Pattern, PatternBank, PlaybackMode, Variation, NUM_PATTERNS,
+    apply_automation, chord_shift, looped_step_at, pattern_label, played_note, triplet_steps, Arrangement, Clock, Pattern, PatternBank,
+    PlaybackMode, Trigger, TriggerDelay, Variation, VolumeFade, MAX_FADE_OUT_BARS, MAX_NUDGE_MS, MAX_TRACKS,
+    MAX_SWING, MAX_TRACK_TRANSPOSE, MAX_TRANSPOSE, NUM_PATTERNS, STEPS, TRIPLET_STEPS,
 };
 use crate::synth::{
-    create_synth, SoundSource, SynthType,
+    create_synth, external_command, Interpolation, SampleOptions, SoundSource, StreamResampler, SynthType,
+    ToneGenerator, ToneParams, LOAD_QUALITY,
 };
 
+/// Sample rate assumed before the output device is opened
+pub const DEFAULT_SAMPLE_RATE: f32 = 44100.0;
+
+/// Default sample memory budget per project (MB)
+pub const DEFAULT_SAMPLE_BUDGET_MB: f32 = 256.0;
+
+/// Default level the mix ducks to while a sample preview plays (dB)
+pub const DEFAULT_PREVIEW_DUCK_DB: f32 = -12.0;
+/// Deepest preview duck (dB)
+pub const MAX_PREVIEW_DUCK_DB: f32 = -36.0;
+/// Time constant of the duck easing in and back out
+const PREVIEW_DUCK_SECS: f32 = 0.1;
+/// Longest track glyph (chars)
+pub const MAX_GLYPH_CHARS: usize = 2;
+
 /// Per-track state shared between audio thread and UI/MCP
 #[derive(Clone, Debug)]
 pub struct TrackState {
@@ -29,7 +65,54 @@ pub struct TrackState {
     pub pan: f32,
     pub mute: bool,
     pub solo: bool,
+    /// Stays in the mix while other tracks are soloed (unless muted)
+    pub solo_safe: bool,
     pub fx: TrackFxState,
+    /// Timing offset in ms (track triggers late by this amount)
+    pub nudge_ms: f32,
+    /// Play TRIPLET_STEPS steps per bar instead of STEPS
+    pub triplet: bool,
+    /// Semitones added to the track's step notes at trigger time
+    pub transpose: i8,
+    /// 1-2 char marker shown before the name (None: from the synth type)
+    pub glyph: Option<String>,
+    /// Bytes of sample data held by the track's synth (runtime only)
+    pub sample_bytes: usize,
+    /// Post-fader peak level and clip count (runtime only)
+    pub meter: LevelMeter,
+    /// Heard on the cue bus, even while muted (runtime only)
+    pub cue: bool,
+    /// Project v3 extension map (kept for round-tripping)
+    pub extensions: serde_json::Map<String, Value>,
+}
+
+impl TrackState {
+    /// The glyph shown for the track: its own, or the synth type's
+    pub fn display_glyph(&self) -> &str {
+        self.glyph.as_deref().unwrap_or_else(|| self.synth_type.glyph())
+    }
+
+    /// Whether the track's step notes are pitches (see SoundSource::is_melodic)
+    pub fn is_melodic(&self) -> bool {
+        let sliced = self.synth_type == SynthType::Sampler
+            && self.params_snapshot.get("slice_count").and_then(|v| v.as_u64()).unwrap_or(1) > 1;
+        self.synth_type.is_melodic() && !sliced
+    }
+
+    /// Whether the track is heard in the mix, given whether any track is soloed
+    pub fn is_audible(&self, any_solo: bool) -> bool {
+        track_audible(self.mute, self.solo, self.solo_safe, any_solo)
+    }
+}
+
+/// Mute/solo rule: with any track soloed, soloed and solo-safe (unmuted)
+/// tracks play; otherwise every unmuted track does
+pub fn track_audible(mute: bool, solo: bool, solo_safe: bool, any_solo: bool) -> bool {
+    if any_solo {
+        solo || (solo_safe && !mute)
+    } else {
+        !mute
+    }
 }
 
 /// Shared state between audio thread and UI/MCP
@@ -38,6 +121,12 @@ pub struct SequencerState {
     pub playing: bool,
     pub bpm: f32,
     pub current_step: usize,
+    /// Steps played since playback started (positions tracks with their
+    /// own loop length)
+    pub run_steps: usize,
+    /// Bars whose last step has played since playback started (scripts
+    /// run once per bar)
+    pub run_bars: usize,
     pub pattern: Pattern,
     // Dynamic tracks
     pub tracks: Vec<TrackState>,
@@ -46,12 +135,65 @@ pub struct SequencerState {
     // Pattern bank + arrangement
     pub pattern_bank: PatternBank,
     pub current_pattern: usize,
+    /// Pattern switched to at the next bar while playing (runtime only)
+    pub pending_pattern: Option<usize>,
     pub playback_mode: PlaybackMode,
     pub arrangement: Arrangement,
     pub arrangement_position: usize,
     pub arrangement_repeat: usize,
     // Pattern variation (A/B)
     pub current_variation: Variation,
+    // Sample memory budget (MB) for reporting
+    pub sample_budget_mb: f32,
+    // Samples larger than this (MB) stream from disk (0 = never)
+    pub sample_stream_mb: f32,
+    // Mix level under a browser preview during playback, in dB (0 = no duck)
+    pub preview_duck_db: f32,
+    // Sampler interpolation used for offline rendering
+    pub render_interpolation: Interpolation,
+    // Whether tone tracks sound in offline renders (off: speaker checks and sirens stay out)
+    pub export_tone: bool,
+    // Output device sample rate (runtime, not saved)
+    pub sample_rate: f32,
+    // DJ sweep filter position, -1 (low-pass) to 1 (high-pass) (runtime, not saved)
+    pub dj_filter: f32,
+    // Loudness-match filter/distortion toggles for A/B (runtime, not saved)
+    pub gain_match: bool,
+    // Headroom monitoring: the master skips the soft clipper, and so do
+    // exports (runtime, not saved)
+    pub headroom: bool,
+    // Beat click on the live output (runtime, not saved)
+    pub metronome: bool,
+    // User scripts run at bar boundaries by the UI thread
+    pub scripts: Vec<Script>,
+    // Project key as a pitch class (C = 0), used to tune samples
+    pub key: Option<u8>,
+    // Semitones added to melodic tracks' step notes at trigger time
+    pub transpose: i8,
+    // Off-beat 16th delay, percent of half a step (0 = straight)
+    pub swing: u8,
+    // Master peak/RMS level and clip count after the limiter, before the
+    // soft clipper (runtime, not saved)
+    pub master_meter: LevelMeter,
+    // Highest master true peak since the clip indicators were reset, and
+    // the limiter's deepest gain reduction in dB since the last sync (runtime, not saved)
+    pub master_true_peak: f32,
+    pub limiter_reduction: f32,
+    // Where the cue bus is heard, None without a cue output (runtime, not saved)
+    pub cue_output: Option<String>,
+    // Pattern slot on deck B while the crossfader mode is on (runtime, not saved)
+    pub deck_b: Option<usize>,
+    // Crossfader position, 0 (deck A) to 1 (deck B) (runtime, not saved)
+    pub crossfader: f32,
+    // MIDI clock sync mode (runtime, not saved)
+    pub midi_sync: MidiSync,
+    // Utility tone generator settings and whether it is sounding (runtime, not saved)
+    pub tone: ToneParams,
+    pub tone_playing: bool,
+    // Sample capture from the hardware input, armed or running (runtime, not saved)
+    pub capture: Option<CaptureStatus>,
+    // Project v3 top-level extension map (kept for round-tripping)
+    pub extensions: serde_json::Map<String, Value>,
 }
 
 impl SequencerState {
@@ -73,7 +215,16 @@ impl SequencerState {
                 pan: 0.0,
                 mute: false,
                 solo: false,
+                solo_safe: false,
                 fx: TrackFxState::default(),
+                nudge_ms: 0.0,
+                triplet: false,
+                transpose: 0,
+                glyph: None,
+                sample_bytes: 0,
+                meter: LevelMeter::default(),
+                cue: false,
+                extensions: serde_json::Map::new(),
             })
             .collect();
 
@@ -81,16 +232,44 @@ impl SequencerState {
             playing: false,
             bpm: 120.0,
             current_step: 0,
+            run_steps: 0,
+            run_bars: 0,
             pattern: Pattern::new(),
             tracks,
             master_fx: MasterFxState::default(),
             pattern_bank: PatternBank::new(),
             current_pattern: 0,
+            pending_pattern: None,
             playback_mode: PlaybackMode::Pattern,
             arrangement: Arrangement::new(),
             arrangement_position: 0,
             arrangement_repeat: 0,
             current_variation: Variation::A,
+            sample_budget_mb: DEFAULT_SAMPLE_BUDGET_MB,
+            sample_stream_mb: 0.0,
+            preview_duck_db: DEFAULT_PREVIEW_DUCK_DB,
+            render_interpolation: Interpolation::Cubic,
+            export_tone: false,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            dj_filter: 0.0,
+            gain_match: false,
+            headroom: false,
+            metronome: false,
+            scripts: Vec::new(),
+            key: None,
+            transpose: 0,
+            swing: 0,
+            master_meter: LevelMeter::default(),
+            master_true_peak: 0.0,
+            limiter_reduction: 0.0,
+            cue_output: None,
+            deck_b: None,
+            crossfader: 0.0,
+            midi_sync: MidiSync::Internal,
+            tone: ToneParams::default(),
+            tone_playing: false,
+            capture: None,
+            extensions: serde_json::Map::new(),
         }
     }
 
@@ -98,6 +277,126 @@ impl SequencerState {
     pub fn num_tracks(&self) -> usize {
         self.tracks.len()
     }
+
+    /// Plugin command of an external synth track
+    pub fn plugin_command(&self, track: usize) -> Option<&str> {
+        self.tracks
+            .get(track)
+            .filter(|t| t.synth_type == SynthType::External)
+            .and_then(|t| external_command(&t.params_snapshot))
+    }
+
+    /// Check structural invariants the UI relies on: every pattern has one
+    /// row per track in both variations, indices and arrangement are in range.
+    pub fn check_invariants(&self) -> Result<()> {
+        let num_tracks = self.tracks.len();
+        if self.pattern.num_tracks() != num_tracks {
+            anyhow::bail!(
+                "Current pattern has {} tracks but state has {}",
+                self.pattern.num_tracks(),
+                num_tracks
+            );
+        }
+        self.pattern.validate()?;
+        self.pattern_bank.validate(num_tracks)?;
+        self.arrangement.validate()?;
+        if self.current_pattern >= NUM_PATTERNS {
+            anyhow::bail!("Current pattern index {} out of range", self.current_pattern);
+        }
+        Ok(())
+    }
+
+    /// Whether a pattern slot has any active step (the live copy for the
+    /// current pattern, whose bank slot may lag behind edits)
+    pub fn slot_has_content(&self, index: usize) -> bool {
+        if index == self.current_pattern {
+            self.pattern.has_content()
+        } else {
+            self.pattern_bank.has_content(index)
+        }
+    }
+
+    /// Why song playback would be (partly) silent: an empty arrangement, or
+    /// entries playing empty pattern slots
+    pub fn song_warnings(&self) -> Vec<String> {
+        if self.arrangement.is_empty() {
+            return vec!["Song mode: the arrangement is empty, nothing will play".to_string()];
+        }
+        let mut empty: Vec<usize> = self
+            .arrangement
+            .entries
+            .iter()
+            .map(|e| e.pattern)
+            .filter(|&p| !self.slot_has_content(p))
+            .collect();
+        empty.sort_unstable();
+        empty.dedup();
+        empty
+            .into_iter()
+            .map(|p| {
+                let positions: Vec<String> = self
+                    .arrangement
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| e.pattern == p)
+                    .map(|(i, _)| (i + 1).to_string())
+                    .collect();
+                format!(
+                    "Arrangement plays empty pattern {} (entry {})",
+                    pattern_label(p),
+                    positions.join(", ")
+                )
+            })
+            .collect()
+    }
+
+    /// Total bytes of sample data across all tracks
+    pub fn sample_memory_bytes(&self) -> usize {
+        self.tracks.iter().map(|t| t.sample_bytes).sum()
+    }
+
+    /// Whether loaded samples exceed the project's memory budget
+    pub fn over_sample_budget(&self) -> bool {
+        self.sample_memory_bytes() as f32 > self.sample_budget_mb * 1024.0 * 1024.0
+    }
+
+    /// Apply a command that only changes state the audio thread never
+    /// reads (scripts, key, sample budget, render settings). The UI and MCP
+    /// call this under a blocking write when they dispatch, since the
+    /// engine only gets the lock with try_write and could drop the change.
+    /// Returns false for every other command.
+    pub fn apply_shared(&mut self, cmd: &Command) -> bool {
+        match cmd {
+            Command::SetScript(script) => {
+                if let Some(existing) = self.scripts.iter_mut().find(|s| s.name == script.name) {
+                    *existing = script.clone();
+                } else if self.scripts.len() < MAX_SCRIPTS {
+                    self.scripts.push(script.clone());
+                }
+            }
+            Command::RemoveScript(name) => self.scripts.retain(|s| &s.name != name),
+            Command::SetScriptEnabled { name, enabled } => {
+                if let Some(script) = self.scripts.iter_mut().find(|s| &s.name == name) {
+                    script.enabled = *enabled;
+                }
+            }
+            Command::SetProjectKey(key) => self.key = key.map(|k| k % 12),
+            Command::SetSampleBudget(mb) => self.sample_budget_mb = mb.max(1.0),
+            Command::SetSampleStreaming(mb) => self.sample_stream_mb = mb.max(0.0),
+            Command::SetRenderInterpolation(interpolation) => self.render_interpolation = *interpolation,
+            Command::SetExportTone(enabled) => self.export_tone = *enabled,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Normalize/trim options new samples on a track are loaded with
+    pub fn sample_options(&self, track: usize) -> SampleOptions {
+        self.tracks
+            .get(track)
+            .map_or_else(SampleOptions::default, |t| SampleOptions::from_params(&t.params_snapshot))
+    }
 }
 
 impl Default for SequencerState {
@@ -106,50 +405,535 @@ impl Default for SequencerState {
     }
 }
 
+/// Longest input backlog held for the output callback
+const INPUT_BUFFER_SECONDS: f32 = 0.1;
+/// Input queued before the output callback reads it, so input blocks
+/// arriving late don't leave it short
+const INPUT_PREFILL_SECONDS: f32 = 0.02;
+/// Longest cue backlog held for a separate cue device (the two device
+/// clocks drift, so this bounds the cue's extra latency)
+const CUE_BUFFER_SECONDS: f32 = 0.05;
+
+/// Cue target that plays the cue bus on channels 3/4 of the main output
+pub const CUE_CHANNELS: &str = "channels";
+
+/// Where the output callback sends the cue bus
+enum CueRoute {
+    /// No cue output: previews play in the main mix
+    None,
+    /// Channels 3/4 of the main output
+    Channels,
+    /// A second output device, fed through a queue (frames are dropped if
+    /// it's full)
+    Device(Sender<(f32, f32)>),
+}
+/// Audio thread errors waiting for the UI before new ones are dropped
+const AUDIO_ERROR_QUEUE: usize = 64;
+/// MIDI messages queued between the MIDI threads and their readers
+const MIDI_QUEUE: usize = 256;
+/// Finished sample captures waiting for the UI
+const CAPTURE_QUEUE: usize = 4;
+/// Replaced project synths waiting for the UI
+const SYNTH_RETURN_QUEUE: usize = 4;
+
+thread_local! {
+    static AUDIO_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Whether the current thread runs the output callback (whose panics are
+/// caught and reported through `AudioEngine::errors`)
+pub fn is_audio_thread() -> bool {
+    AUDIO_THREAD.with(|t| t.get())
+}
+
+/// Names of the available output devices (for --cue)
+pub fn output_device_names() -> Result<Vec<String>> {
+    Ok(cpal::default_host()
+        .output_devices()?
+        .filter_map(|d| d.name().ok())
+        .collect())
+}
+
+/// The output stream and what's tied to its device and sample rate
+struct Output {
+    _stream: Stream,
+    /// Second output device playing the cue bus
+    _cue_stream: Option<Stream>,
+    /// Queue the hardware input feeds this stream's callback through
+    input_tx: Sender<f32>,
+}
+
 /// Audio engine managing the audio output stream and sequencer
 pub struct AudioEngine {
-    _stream: Stream,
+    /// None only while the output is being reopened
+    output: Option<Output>,
     pub state: Arc<RwLock<SequencerState>>,
+    /// Spectrum analyzer tap on the master output
+    pub analyzer: Arc<AnalyzerTap>,
+    /// Commands for the output callback, kept for reopening it
+    command_rx: CommandReceiver,
+    /// Cue output asked for at startup (see `new`)
+    cue: Option<String>,
+    /// Hardware input capture (opened on demand for Input tracks)
+    input_stream: Option<Stream>,
+    /// Sample captures from the input, finished and waiting to be saved
+    pub captures: Receiver<CapturedSample>,
+    capture_tx: Sender<CapturedSample>,
+    /// Synths a project load replaced, to be dropped (or parked) off the
+    /// audio thread
+    pub replaced_synths: Receiver<Vec<Box<dyn SoundSource>>>,
+    synth_return: Sender<Vec<Box<dyn SoundSource>>>,
+    /// MIDI input (opened on demand for slave sync or note input) and the
+    /// queue its clock messages reach the output callback through
+    midi_input: Option<MidiInputConnection<()>>,
+    midi_in_tx: Sender<ClockMessage>,
+    midi_in_rx: Receiver<ClockMessage>,
+    /// NoteOns from the MIDI input, for the UI to map onto tracks
+    pub midi_notes: Receiver<NoteOn>,
+    midi_notes_tx: Sender<NoteOn>,
+    /// Bytes for the MIDI clock output thread (started on demand for master sync)
+    midi_out_rx: Receiver<u8>,
+    midi_out_tx: Sender<u8>,
+    midi_output_open: bool,
+    /// Errors from the audio callbacks (stream errors, caught panics)
+    pub errors: Receiver<String>,
+    error_tx: Sender<String>,
+    /// Output callbacks that took longer than their buffer lasts
+    pub xruns: Arc<AtomicU64>,
 }
 
 impl AudioEngine {
-    /// Initialize the audio engine with default output device
-    pub fn new(command_rx: CommandReceiver) -> Result<Self> {
+    /// Initialize the audio engine on the `output` device (the default
+    /// if None or not found), with a `buffer_size` in frames if given.
+    /// `cue` is CUE_CHANNELS or the name of an output device for the cue
+    /// bus; a cue output that can't be opened is reported through `errors`.
+    pub fn new(
+        command_rx: CommandReceiver,
+        cue: Option<&str>,
+        output: Option<&str>,
+        buffer_size: Option<u32>,
+    ) -> Result<Self> {
+        let (midi_in_tx, midi_in_rx) = bounded(MIDI_QUEUE);
+        let (midi_out_tx, midi_out_rx) = bounded(MIDI_QUEUE);
+        let (midi_notes_tx, midi_notes) = bounded(MIDI_QUEUE);
+        let (error_tx, errors) = bounded(AUDIO_ERROR_QUEUE);
+        let (capture_tx, captures) = bounded(CAPTURE_QUEUE);
+        let (synth_return, replaced_synths) = bounded(SYNTH_RETURN_QUEUE);
+        let mut engine = Self {
+            output: None,
+            state: Arc::new(RwLock::new(SequencerState::new())),
+            analyzer: Arc::new(AnalyzerTap::new()),
+            command_rx,
+            cue: cue.map(str::to_string),
+            input_stream: None,
+            captures,
+            capture_tx,
+            replaced_synths,
+            synth_return,
+            midi_input: None,
+            midi_in_tx,
+            midi_in_rx,
+            midi_notes,
+            midi_notes_tx,
+            midi_out_rx,
+            midi_out_tx,
+            midi_output_open: false,
+            errors,
+            error_tx,
+            xruns: Arc::new(AtomicU64::new(0)),
+        };
+        engine.output = Some(engine.open_output(output, buffer_size)?);
+        Ok(engine)
+    }
+
+    /// Close the output and open it again on the `output` device with a
+    /// `buffer_size` (as in `new`). The new callback starts with an empty
+    /// project at the new device's rate, so load the project back into
+    /// it. MIDI ports stay open; the hardware input is closed.
+    pub fn reopen(&mut self, output: Option<&str>, buffer_size: Option<u32>) -> Result<()> {
+        // A device may refuse a second stream, so the old ones close first
+        self.input_stream = None;
+        self.output = None;
+        self.output = Some(self.open_output(output, buffer_size)?);
+        Ok(())
+    }
+
+    /// Open the output stream (and the cue output) and start playing it
+    fn open_output(&self, output: Option<&str>, buffer_size: Option<u32>) -> Result<Output> {
         let host = cpal::default_host();
-        let device = host
-            .default_output_device()
-            .context("No output device available")?;
+        let named = output.and_then(|name| {
+            let device = host
+                .output_devices()
+                .ok()?
+                .find(|d| d.name().is_ok_and(|n| n == name));
+            if device.is_none() {
+                warn!(device = name, "Output device not found, using the default");
+                let _ = self
+                    .error_tx
+                    .try_send(format!("Output device '{}' not found, using the default", name));
+            }
+            device
+        });
+        let device = match named {
+            Some(device) => device,
+            None => host
+                .default_output_device()
+                .context("No output device available")?,
+        };
+
+        let cue = self.cue.as_deref();
+        let default_config = device.default_output_config()?;
+        let config = match cue {
+            Some(CUE_CHANNELS) => {
+                Self::cue_channel_config(&device, &default_config).unwrap_or(default_config)
+            }
+            _ => default_config,
+        };
+        info!(
+            device = device.name().unwrap_or_default(),
+            sample_rate = config.sample_rate().0,
+            channels = config.channels(),
+            format = ?config.sample_format(),
+            "Audio output opened"
+        );
+        // Buffers loaded elsewhere are resampled to the device rate
+        {
+            let mut state = self.state.write();
+            state.sample_rate = config.sample_rate().0 as f32;
+            state.cue_output = None;
+        }
+        // Input samples (mono) waiting for the output callback
+        let input_capacity = (config.sample_rate().0 as f32 * INPUT_BUFFER_SECONDS) as usize;
+        let (input_tx, input_rx) = bounded(input_capacity.max(1));
 
-        let config = device.default_output_config()?;
-        let state = Arc::new(RwLock::new(SequencerState::new()));
+        let mut cue_stream = None;
+        let cue_route = match cue {
+            None => CueRoute::None,
+            Some(CUE_CHANNELS) if config.channels() >= 4 => {
+                self.state.write().cue_output = Some("channels 3/4".to_string());
+                CueRoute::Channels
+            }
+            Some(CUE_CHANNELS) => {
+                let message = "Cue output unavailable: the output device has fewer than 4 channels";
+                warn!("{}", message);
+                let _ = self.error_tx.try_send(message.to_string());
+                CueRoute::None
+            }
+            Some(name) => match Self::open_cue_device(&host, name, config.sample_rate(), self.error_tx.clone()) {
+                Ok((stream, tx)) => {
+                    cue_stream = Some(stream);
+                    self.state.write().cue_output = Some(name.to_string());
+                    CueRoute::Device(tx)
+                }
+                Err(e) => {
+                    warn!("Cue output unavailable: {:#}", e);
+                    let _ = self.error_tx.try_send(format!("Cue output unavailable: {:#}", e));
+                    CueRoute::None
+                }
+            },
+        };
 
+        let mut stream_config: StreamConfig = config.clone().into();
+        if let Some(frames) = buffer_size {
+            stream_config.buffer_size = cpal::BufferSize::Fixed(frames);
+        }
         let stream = match config.sample_format() {
             SampleFormat::F32 => {
-                Self::build_stream::<f32>(&device, &config.into(), command_rx, state.clone())?
+                Self::build_stream::<f32>(
+                    &device,
+                    &stream_config,
+                    self.command_rx.clone(),
+                    self.state.clone(),
+                    self.analyzer.clone(),
+                    input_rx,
+                    self.midi_in_rx.clone(),
+                    self.midi_out_tx.clone(),
+                    cue_route,
+                    self.capture_tx.clone(),
+                    self.synth_return.clone(),
+                    self.error_tx.clone(),
+                    self.xruns.clone(),
+                )?
             }
             SampleFormat::I16 => {
-                Self::build_stream::<i16>(&device, &config.into(), command_rx, state.clone())?
+                Self::build_stream::<i16>(
+                    &device,
+                    &stream_config,
+                    self.command_rx.clone(),
+                    self.state.clone(),
+                    self.analyzer.clone(),
+                    input_rx,
+                    self.midi_in_rx.clone(),
+                    self.midi_out_tx.clone(),
+                    cue_route,
+                    self.capture_tx.clone(),
+                    self.synth_return.clone(),
+                    self.error_tx.clone(),
+                    self.xruns.clone(),
+                )?
             }
             SampleFormat::U16 => {
-                Self::build_stream::<u16>(&device, &config.into(), command_rx, state.clone())?
+                Self::build_stream::<u16>(
+                    &device,
+                    &stream_config,
+                    self.command_rx.clone(),
+                    self.state.clone(),
+                    self.analyzer.clone(),
+                    input_rx,
+                    self.midi_in_rx.clone(),
+                    self.midi_out_tx.clone(),
+                    cue_route,
+                    self.capture_tx.clone(),
+                    self.synth_return.clone(),
+                    self.error_tx.clone(),
+                    self.xruns.clone(),
+                )?
             }
             format => anyhow::bail!("Unsupported sample format: {:?}", format),
         };
 
         stream.play()?;
 
-        Ok(Self {
+        Ok(Output {
             _stream: stream,
-            state,
+            _cue_stream: cue_stream,
+            input_tx,
         })
     }
 
+    /// Open the default input device and start feeding Input tracks.
+    /// Does nothing if the input is already running.
+    pub fn start_input(&mut self) -> Result<()> {
+        if self.input_stream.is_some() {
+            return Ok(());
+        }
+        let host = cpal::default_host();
+        let device = host
+            .default_input_device()
+            .context("No input device available")?;
+        let input_tx = self.output.as_ref().context("No audio output open")?.input_tx.clone();
+        let default = device.default_input_config()?;
+        // Capture at the device's own rate, converted to the output rate
+        let config = default.config();
+        let output_rate = self.state.read().sample_rate;
+        let stream = match default.sample_format() {
+            SampleFormat::F32 => {
+                Self::build_input_stream::<f32>(
+                    &device,
+                    &config,
+                    output_rate,
+                    input_tx.clone(),
+                    self.error_tx.clone(),
+                )?
+            }
+            SampleFormat::I16 => {
+                Self::build_input_stream::<i16>(
+                    &device,
+                    &config,
+                    output_rate,
+                    input_tx.clone(),
+                    self.error_tx.clone(),
+                )?
+            }
+            SampleFormat::U16 => {
+                Self::build_input_stream::<u16>(
+                    &device,
+                    &config,
+                    output_rate,
+                    input_tx.clone(),
+                    self.error_tx.clone(),
+                )?
+            }
+            format => anyhow::bail!("Unsupported input sample format: {:?}", format),
+        };
+        stream.play()?;
+        info!(
+            device = device.name().unwrap_or_default(),
+            sample_rate = config.sample_rate.0,
+            channels = config.channels,
+            "Audio input opened"
+        );
+        self.input_stream = Some(stream);
+        Ok(())
+    }
+
+    /// Whether the hardware input is running
+    pub fn input_open(&self) -> bool {
+        self.input_stream.is_some()
+    }
+
+    /// Open the MIDI port `mode` syncs through (input for slave, output for
+    /// master), matching `port` by name. Returns the port name when one was
+    /// opened; an open port is kept for the rest of the session.
+    pub fn connect_midi_clock(&mut self, mode: MidiSync, port: Option<&str>) -> Result<Option<String>> {
+        match mode {
+            MidiSync::Internal => Ok(None),
+            MidiSync::Slave => self.connect_midi_input(port),
+            MidiSync::Master if !self.midi_output_open => {
+                let name = midi::open_clock_output(port, self.midi_out_rx.clone())?;
+                self.midi_output_open = true;
+                Ok(Some(name))
+            }
+            MidiSync::Master => Ok(None),
+        }
+    }
+
+    /// Open the MIDI input (clock and notes), matching `port` by name.
+    /// Returns the port name when it was opened by this call.
+    pub fn connect_midi_input(&mut self, port: Option<&str>) -> Result<Option<String>> {
+        if self.midi_input.is_some() {
+            return Ok(None);
+        }
+        let (connection, name) =
+            midi::open_input(port, self.midi_in_tx.clone(), self.midi_notes_tx.clone())?;
+        self.midi_input = Some(connection);
+        Ok(Some(name))
+    }
+
+    /// Close the MIDI input; false if it wasn't open
+    pub fn disconnect_midi_input(&mut self) -> bool {
+        self.midi_input.take().is_some()
+    }
+
+    /// Build the capture stream: downmix to mono, convert to `output_rate`
+    /// and queue for the output callback (samples are dropped if the queue
+    /// is full)
+    fn build_input_stream<T>(
+        device: &Device,
+        config: &StreamConfig,
+        output_rate: f32,
+        tx: Sender<f32>,
+        errors: Sender<String>,
+    ) -> Result<Stream>
+    where
+        T: cpal::SizedSample,
+        f32: cpal::FromSample<T>,
+    {
+        let channels = config.channels as usize;
+        let input_rate = config.sample_rate.0 as f32;
+        let mut resampler =
+            ((input_rate - output_rate).abs() > 1.0).then(|| StreamResampler::new(input_rate, output_rate, LOAD_QUALITY));
+        let stream = device.build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                for frame in data.chunks(channels) {
+                    let sum: f32 = frame.iter().map(|&s| cpal::Sample::to_sample::<f32>(s)).sum();
+                    let sample = sum / channels as f32;
+                    match resampler.as_mut() {
+                        Some(resampler) => resampler.push(sample, |s| {
+                            let _ = tx.try_send(s);
+                        }),
+                        None => {
+                            let _ = tx.try_send(sample);
+                        }
+                    }
+                }
+            },
+            move |err| {
+                let _ = errors.try_send(format!("Audio input stream error: {}", err));
+            },
+            None,
+        )?;
+        Ok(stream)
+    }
+
+    /// A config of the main output device with at least 4 channels at its
+    /// default rate and format, for a cue on channels 3/4
+    fn cue_channel_config(device: &Device, default: &SupportedStreamConfig) -> Option<SupportedStreamConfig> {
+        device
+            .supported_output_configs()
+            .ok()?
+            .filter(|c| {
+                c.channels() >= 4
+                    && c.sample_format() == default.sample_format()
+                    && c.min_sample_rate() <= default.sample_rate()
+                    && c.max_sample_rate() >= default.sample_rate()
+            })
+            .min_by_key(|c| c.channels())
+            .map(|c| c.with_sample_rate(default.sample_rate()))
+    }
+
+    /// Open the named output device for the cue bus at the main output's
+    /// rate. Returns the stream and the queue that feeds it.
+    fn open_cue_device(
+        host: &cpal::Host,
+        name: &str,
+        sample_rate: cpal::SampleRate,
+        errors: Sender<String>,
+    ) -> Result<(Stream, Sender<(f32, f32)>)> {
+        let device = host
+            .output_devices()?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+            .with_context(|| format!("No output device named '{}'", name))?;
+        let default = device.default_output_config()?;
+        let config = StreamConfig {
+            channels: default.channels(),
+            sample_rate,
+            buffer_size: cpal::BufferSize::Default,
+        };
+        let capacity = (sample_rate.0 as f32 * CUE_BUFFER_SECONDS) as usize;
+        let (tx, rx) = bounded(capacity.max(1));
+        let stream = match default.sample_format() {
+            SampleFormat::F32 => Self::build_cue_stream::<f32>(&device, &config, rx, errors)?,
+            SampleFormat::I16 => Self::build_cue_stream::<i16>(&device, &config, rx, errors)?,
+            SampleFormat::U16 => Self::build_cue_stream::<u16>(&device, &config, rx, errors)?,
+            format => anyhow::bail!("Unsupported cue sample format: {:?}", format),
+        };
+        stream.play()?;
+        info!(device = name, channels = config.channels, "Cue output opened");
+        Ok((stream, tx))
+    }
+
+    /// Build the cue device stream: play queued cue frames (silence when
+    /// the queue runs dry)
+    fn build_cue_stream<T>(
+        device: &Device,
+        config: &StreamConfig,
+        rx: Receiver<(f32, f32)>,
+        errors: Sender<String>,
+    ) -> Result<Stream>
+    where
+        T: cpal::SizedSample + cpal::FromSample<f32>,
+    {
+        let channels = config.channels as usize;
+        let stream = device.build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(channels) {
+                    let (left, right) = rx.try_recv().unwrap_or((0.0, 0.0));
+                    for (ch, channel_sample) in frame.iter_mut().enumerate() {
+                        let sample = match ch {
+                            0 => left,
+                            1 => right,
+                            _ => (left + right) * 0.5,
+                        };
+                        *channel_sample = T::from_sample(sample);
+                    }
+                }
+            },
+            move |err| {
+                let _ = errors.try_send(format!("Cue output stream error: {}", err));
+            },
+            None,
+        )?;
+        Ok(stream)
+    }
+
     /// Build the audio stream for a specific sample format
     fn build_stream<T>(
         device: &Device,
         config: &StreamConfig,
         command_rx: CommandReceiver,
         state: Arc<RwLock<SequencerState>>,
+        analyzer: Arc<AnalyzerTap>,
+        input_rx: Receiver<f32>,
+        midi_in_rx: Receiver<ClockMessage>,
+        midi_out_tx: Sender<u8>,
+        cue: CueRoute,
+        captures: Sender<CapturedSample>,
+        synth_return: Sender<Vec<Box<dyn SoundSource>>>,
+        errors: Sender<String>,
+        xruns: Arc<AtomicU64>,
     ) -> Result<Stream>
     where
         T: cpal::SizedSample + cpal::FromSample<f32>,
@@ -181,17 +965,51 @@ impl AudioEngine {
         let mut local_arrangement_repeat: usize = 0;
         let mut pending_pattern_switch: Option<usize> = None;
         let mut local_variation = Variation::A;
+        // Song mode: apply the entry's automation when its first step plays
+        let mut entry_automation_due = true;
+        // Song mode fade-out: bars started since the song began, the current
+        // master gain, and whether to stop at the next bar line (song end)
+        let mut song_bar: usize = 0;
+        let mut master_fade = 1.0f32;
+        let mut song_end_due = false;
 
         // Local mixer state (dynamic)
-        let mut local_volumes: Vec<f32> = vec![0.8; num_tracks];
-        let mut local_pans: Vec<f32> = vec![0.0; num_tracks];
+        // Volume/pan ramp toward new values to avoid clicks
+        let mut local_volumes: Vec<SmoothedParam> = vec![SmoothedParam::new(0.8, sample_rate); num_tracks];
+        let mut local_pans: Vec<SmoothedParam> = vec![SmoothedParam::new(0.0, sample_rate); num_tracks];
         let mut local_mutes: Vec<bool> = vec![false; num_tracks];
+        let mut local_triplets: Vec<bool> = vec![false; num_tracks];
+        let mut local_transposes: Vec<i8> = vec![0; num_tracks];
         let mut local_solos: Vec<bool> = vec![false; num_tracks];
+        let mut local_solo_safe: Vec<bool> = vec![false; num_tracks];
+        let mut local_cues: Vec<bool> = vec![false; num_tracks];
+        // Volume fades from arrangement automation
+        let mut volume_fades: Vec<VolumeFade> = vec![VolumeFade::default(); num_tracks];
+
+        // Per-track timing nudge (delayed triggers)
+        let mut trigger_delays: Vec<TriggerDelay> = (0..num_tracks)
+            .map(|_| TriggerDelay::new())
+            .collect();
+        // Parameters each track's last trigger locked
+        let mut held_locks: Vec<HeldLocks> = (0..num_tracks).map(|_| HeldLocks::default()).collect();
+        // Tracks holding a note on for the tied steps after it
+        let mut tied_notes: Vec<bool> = vec![false; num_tracks];
 
         // Per-track FX chains
         let mut fx_chains: Vec<TrackFxChain> = (0..num_tracks)
             .map(|_| TrackFxChain::new(sample_rate))
             .collect();
+        // Auto-gain on filter/distortion, applied to every chain
+        let mut gain_match = false;
+        // Headroom monitoring: no soft clipper on the master
+        let mut headroom = false;
+        let mut metronome_on = false;
+        let mut metronome = Metronome::new(sample_rate);
+        // Hardware input being recorded into a sampler track
+        let mut sample_capture: Option<SampleCapture> = None;
+        // Captures go back to the UI, finished or not, so it frees their buffers
+        let mut capture_return = CaptureReturn::new(captures);
+        let mut input_jitter = InputJitter::new((sample_rate * INPUT_PREFILL_SECONDS) as usize);
 
         // Local FX state for syncing to shared state
         let mut local_track_fx: Vec<TrackFxState> = (0..num_tracks)
@@ -203,9 +1021,48 @@ impl AudioEngine {
         let mut reverb = StereoReverb::new(sample_rate);
         let mut reverb_enabled = false;
 
+        // Master mid/side stage
+        let mut mid_side = MidSide::new(sample_rate);
+
+        // DJ sweep filter (performance control, after mid/side)
+        let mut dj_filter = DjFilter::new(sample_rate);
+
+        // Master limiter (after the DC blockers) and the true-peak meter
+        let mut limiter = Limiter::new(sample_rate);
+        let mut limiter_enabled = false;
+        let mut limiter_reduction: f32 = 0.0;
+        let mut true_peak_meters = [TruePeak::new(), TruePeak::new()];
+        let mut master_true_peak: f32 = 0.0;
+
+        // Output DC blockers (left, right)
+        let mut dc_blockers = [DcBlocker::new(sample_rate), DcBlocker::new(sample_rate)];
+
+        // Peak/clip meters: per track post-fader, master before the soft clipper
+        let mut track_meters: Vec<LevelMeter> = vec![LevelMeter::default(); num_tracks];
+        let mut master_meter = LevelMeter::default();
+        let ballistics = MeterBallistics::new(sample_rate);
+
         // Preview sample buffer (one-shot playback through master bus)
         let mut preview_buffer: Option<Vec<f32>> = None;
         let mut preview_pos: usize = 0;
+        // Mix gain under a preview, easing toward the duck level and back
+        let mut preview_duck_db = DEFAULT_PREVIEW_DUCK_DB;
+        let mut duck_gain = 1.0f32;
+        let duck_coeff = 1.0 - (-1.0 / (PREVIEW_DUCK_SECS * sample_rate)).exp();
+        // Global transpose of melodic tracks (semitones)
+        let mut transpose: i8 = 0;
+        // MIDI clock sync: follow the input's clock or send our own
+        let mut midi_sync = MidiSync::Internal;
+        // Utility tone generator: not on any track, so never in a pattern or export
+        let mut tone = ToneGenerator::new(sample_rate);
+        let mut tone_playing = false;
+
+        // Crossfader mode: deck B plays a second slot through copied voices
+        let mut deck_b: Option<DeckB> = None;
+        let mut crossfader = SmoothedParam::new(0.0, sample_rate);
+
+        // With a cue output, previews play there instead of in the mix
+        let has_cue = !matches!(cue, CueRoute::None);
 
         // Simple xorshift PRNG for probability (RT-safe, no heap allocation)
         let mut prng_state: u32 = 0xDEAD_BEEF;
@@ -216,642 +1073,1788 @@ impl AudioEngine {
             prng_state
         };
 
+        // Spectrum analyzer capture (master + overlay track)
+        let mut analyzer_capture = AnalyzerCapture::new();
+
         // For periodic state sync
         let mut sync_counter = 0usize;
         let sync_interval = (sample_rate / 60.0) as usize; // ~60 times per second
 
-        let stream = device.build_output_stream(
-            config,
-            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
-                let num_synths = synths.len();
+        // One buffer of processing; the stream callback runs it under catch_unwind
+        let mut process = move |data: &mut [T]| {
+            let num_synths = synths.len();
+            capture_return.flush();
 
-                // Process commands from the command bus
-                while let Some((cmd, _source)) = command_rx.try_recv() {
-                    match cmd {
-                        Command::Play => {
-                            clock.play();
-                            if let Some(mut state) = state.try_write() {
-                                state.playing = true;
-                            }
+            // MIDI clock input: in slave mode pulses drive the clock and
+            // Start/Continue/Stop the transport; otherwise it's ignored
+            let mut midi_pulses = 0u32;
+            let mut midi_transport: [Option<Command>; 2] = [None, None];
+            while let Ok(message) = midi_in_rx.try_recv() {
+                if midi_sync != MidiSync::Slave {
+                    continue;
+                }
+                match message {
+                    ClockMessage::Pulse => midi_pulses += 1,
+                    ClockMessage::Start => midi_transport = [Some(Command::Stop), Some(Command::Play)],
+                    ClockMessage::Continue => midi_transport = [Some(Command::Play), None],
+                    ClockMessage::Stop => midi_transport = [Some(Command::Pause), None],
+                }
+                if message != ClockMessage::Pulse {
+                    // Only pulses after the transport change count toward steps
+                    midi_pulses = 0;
+                }
+            }
+            let mut midi_commands = midi_transport.into_iter().flatten();
+
+            // Process MIDI transport, then commands from the command bus
+            while let Some(cmd) = midi_commands
+                .next()
+                .or_else(|| command_rx.try_recv().map(|(cmd, _source)| cmd))
+            {
+                match cmd {
+                    Command::Play => {
+                        if midi_sync == MidiSync::Master && !clock.is_playing() {
+                            let message = if clock.current_step() == 0 { CLOCK_START } else { CLOCK_CONTINUE };
+                            let _ = midi_out_tx.try_send(message);
                         }
-                        Command::Pause => {
-                            clock.pause();
-                            if let Some(mut state) = state.try_write() {
-                                state.playing = false;
-                            }
+                        clock.play();
+                        master_fade = 1.0;
+                        if let Some(mut state) = state.try_write() {
+                            state.playing = true;
                         }
-                        Command::Stop => {
-                            clock.stop();
-                            // Silence all synths immediately
-                            for synth in synths.iter_mut() {
-                                synth.stop();
-                            }
-                            // Apply any pending pattern switch immediately on stop
-                            if let Some(new_pat) = pending_pattern_switch.take() {
-                                // Copy current pattern back to bank
-                                *local_pattern_bank.get_mut(local_current_pattern) = pattern.clone();
-                                local_current_pattern = new_pat;
-                                pattern = local_pattern_bank.get(new_pat).clone();
-                            }
-                            // Reset song position
-                            local_arrangement_position = 0;
-                            local_arrangement_repeat = 0;
+                    }
+                    Command::Pause => {
+                        if midi_sync == MidiSync::Master && clock.is_playing() {
+                            let _ = midi_out_tx.try_send(CLOCK_STOP);
+                        }
+                        clock.pause();
+                        if let Some(mut state) = state.try_write() {
+                            state.playing = false;
+                        }
+                    }
+                    Command::Stop => {
+                        if midi_sync == MidiSync::Master && clock.is_playing() {
+                            let _ = midi_out_tx.try_send(CLOCK_STOP);
+                        }
+                        clock.stop();
+                        // Silence all synths immediately
+                        for synth in synths.iter_mut() {
+                            synth.stop();
+                        }
+                        for delay in trigger_delays.iter_mut() {
+                            delay.clear();
+                        }
+                        for (held, synth) in held_locks.iter_mut().zip(synths.iter_mut()) {
+                            held.release(synth.as_mut());
+                        }
+                        release_tied(&mut tied_notes, &mut synths);
+                        if let Some(deck) = deck_b.as_mut() {
+                            deck.stop();
+                        }
+                        for fade in volume_fades.iter_mut() {
+                            *fade = VolumeFade::default();
+                        }
+                        entry_automation_due = true;
+                        song_bar = 0;
+                        song_end_due = false;
+                        master_fade = 1.0;
+                        // Apply any pending pattern switch immediately on stop
+                        if let Some(new_pat) = pending_pattern_switch.take() {
+                            // Copy current pattern back to bank
+                            local_pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
+                            local_current_pattern = new_pat;
+                            pattern.clone_from(local_pattern_bank.get(new_pat));
+                        }
+                        // Reset song position
+                        local_arrangement_position = 0;
+                        local_arrangement_repeat = 0;
+                        if let Some(mut state) = state.try_write() {
+                            state.playing = false;
+                            state.current_step = 0;
+                            state.current_pattern = local_current_pattern;
+                            state.pattern.clone_from(&pattern);
+                            state.arrangement_position = 0;
+                            state.arrangement_repeat = 0;
+                        }
+                    }
+                    Command::SetMidiSync(mode) => {
+                        if midi_sync == MidiSync::Master && mode != MidiSync::Master && clock.is_playing() {
+                            let _ = midi_out_tx.try_send(CLOCK_STOP);
+                        }
+                        midi_sync = mode;
+                        clock.set_external(mode == MidiSync::Slave);
+                        if let Some(mut state) = state.try_write() {
+                            state.midi_sync = mode;
+                        }
+                    }
+                    Command::SetBpm(bpm) => {
+                        clock.set_bpm(bpm);
+                        if let Some(mut state) = state.try_write() {
+                            state.bpm = clock.bpm();
+                        }
+                    }
+                    Command::SetSwing(amount) => {
+                        let amount = amount.min(MAX_SWING);
+                        clock.set_swing(amount);
+                        if let Some(mut state) = state.try_write() {
+                            state.swing = amount;
+                        }
+                    }
+                    Command::ToggleStep { track, step } => {
+                        if track < num_synths {
+                            pattern.toggle_var(track, step, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).toggle_var(track, step, local_variation);
                             if let Some(mut state) = state.try_write() {
-                                state.playing = false;
-                                state.current_step = 0;
-                                state.current_pattern = local_current_pattern;
-                                state.pattern = pattern.clone();
-                                state.arrangement_position = 0;
-                                state.arrangement_repeat = 0;
+                                state.pattern.clone_from(&pattern);
+                                state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                             }
                         }
-                        Command::SetBpm(bpm) => {
-                            clock.set_bpm(bpm);
+                    }
+                    Command::QuantizeTrack { track, strength } => {
+                        if track < num_synths {
+                            pattern.quantize_track_var(track, strength, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                             if let Some(mut state) = state.try_write() {
-                                state.bpm = clock.bpm();
+                                state.pattern.clone_from(&pattern);
+                                state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                             }
                         }
-                        Command::ToggleStep { track, step } => {
-                            if track < num_synths {
-                                pattern.toggle_var(track, step, local_variation);
-                                local_pattern_bank.get_mut(local_current_pattern).toggle_var(track, step, local_variation);
+                    }
+                    Command::PadHit { track, velocity, record } => {
+                        if track < num_synths {
+                            let note = synths[track].default_note();
+                            let melodic = synths[track].is_melodic();
+                            let note = played_note(note, melodic, transpose, local_transposes[track]);
+                            synths[track].trigger_with_note_velocity(note, velocity);
+                            if record && clock.is_playing() {
+                                let (step, fraction) = clock.position();
+                                // Micro-timing is kept in grid steps past the (swung) step
+                                let late = fraction * clock.step_length(clock.run_step());
+                                // Triplet tracks snap to their own grid; others keep the micro-timing
+                                let length = pattern.length;
+                                let (track_step, offset) = if local_triplets[track] {
+                                    let pos = (step as f32 + fraction) * TRIPLET_STEPS as f32 / STEPS as f32;
+                                    (pos.round() as usize % triplet_steps(length), 0)
+                                } else if late >= 0.99 || fraction >= 0.99 {
+                                    ((step + 1) % length, 0)
+                                } else {
+                                    (step, (late * 100.0) as u8)
+                                };
+                                pattern.record_hit_var(track, track_step, note, velocity, offset, local_variation);
+                                local_pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                                 if let Some(mut state) = state.try_write() {
-                                    state.pattern = pattern.clone();
-                                    *state.pattern_bank.get_mut(local_current_pattern) = pattern.clone();
+                                    state.pattern.clone_from(&pattern);
+                                    state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                                 }
                             }
                         }
-                        Command::ClearTrack(track) => {
-                            if track < num_synths {
-                                pattern.clear_track_var(track, local_variation);
-                                local_pattern_bank.get_mut(local_current_pattern).clear_track_var(track, local_variation);
-                                if let Some(mut state) = state.try_write() {
-                                    state.pattern = pattern.clone();
-                                    *state.pattern_bank.get_mut(local_current_pattern) = pattern.clone();
-                                }
-                            }
+                    }
+                    Command::PlayNote { track, note, velocity } => {
+                        if track < num_synths {
+                            let melodic = synths[track].is_melodic();
+                            let note = played_note(note, melodic, transpose, local_transposes[track]);
+                            synths[track].trigger_with_note_velocity(note, velocity);
                         }
-                        Command::FillTrack(track) => {
-                            if track < num_synths {
-                                pattern.fill_track_var(track, local_variation);
-                                local_pattern_bank.get_mut(local_current_pattern).fill_track_var(track, local_variation);
-                                if let Some(mut state) = state.try_write() {
-                                    state.pattern = pattern.clone();
-                                    *state.pattern_bank.get_mut(local_current_pattern) = pattern.clone();
+                    }
+                    Command::ScrubStep(step) => {
+                        // Playback owns the triggers while it runs
+                        if !clock.is_playing() && step < pattern.length {
+                            for i in 0..num_synths {
+                                let loop_length = pattern.track_loop(i, local_triplets[i]);
+                                let Some((track_step, offset)) = looped_step_at(step, step, local_triplets[i], loop_length) else {
+                                    continue;
+                                };
+                                // Every hit plays (no probability roll); tied steps carry no hit of their own
+                                let sd = pattern.get_step_var(i, track_step, local_variation);
+                                let steps = pattern.track_steps(i, local_triplets[i]);
+                                let plays = sd.active && !pattern.is_tied_var(i, track_step, steps, local_variation);
+                                if !plays && !sd.off {
+                                    continue;
                                 }
-                            }
-                        }
-                        Command::SetStepNote { track, step, note } => {
-                            if track < num_synths {
-                                pattern.set_note_var(track, step, note, local_variation);
-                                local_pattern_bank.get_mut(local_current_pattern).set_note_var(track, step, note, local_variation);
-                                if let Some(mut state) = state.try_write() {
-                                    state.pattern.set_note_var(track, step, note, local_variation);
-                                    state.pattern_bank.get_mut(local_current_pattern).set_note_var(track, step, note, local_variation);
+                                let extra = ((offset + sd.offset as f32 / 100.0) * clock.samples_per_step()) as usize;
+                                let melodic = synths[i].is_melodic();
+                                let note = played_note(sd.note, melodic, transpose, local_transposes[i]);
+                                let trigger = Trigger {
+                                    note,
+                                    velocity: sd.velocity,
+                                    step: track_step,
+                                    hold: false,
+                                    off: !plays,
+                                    slide: sd.slide,
+                                    accent: sd.accent,
+                                };
+                                if let Some(t) = trigger_delays[i].schedule(trigger, extra) {
+                                    held_locks[i].play(synths[i].as_mut(), pattern.param_locks(i, t.step, local_variation), t);
                                 }
                             }
                         }
-                        Command::SetStepVelocity { track, step, velocity } => {
-                            if track < num_synths {
-                                pattern.set_velocity_var(track, step, velocity, local_variation);
-                                local_pattern_bank.get_mut(local_current_pattern).set_velocity_var(track, step, velocity, local_variation);
-                                if let Some(mut state) = state.try_write() {
-                                    state.pattern.set_velocity_var(track, step, velocity, local_variation);
-                                    state.pattern_bank.get_mut(local_current_pattern).set_velocity_var(track, step, velocity, local_variation);
-                                }
+                    }
+                    Command::RecordSample { track, steps, buffer } => {
+                        // A replayed command has no buffer, and this thread can't make one
+                        if track < num_synths && buffer.capacity() > 0 {
+                            let steps = steps.clamp(1, MAX_CAPTURE_STEPS);
+                            let length = (steps as f32 * clock.samples_per_step()) as usize;
+                            let mut capture = SampleCapture::new(track, steps, length, buffer);
+                            // Stopped, there's no step to wait for
+                            if !clock.is_playing() {
+                                capture.start();
                             }
-                        }
-                        Command::SetStepProbability { track, step, probability } => {
-                            if track < num_synths {
-                                pattern.set_probability_var(track, step, probability, local_variation);
-                                local_pattern_bank.get_mut(local_current_pattern).set_probability_var(track, step, probability, local_variation);
-                                if let Some(mut state) = state.try_write() {
-                                    state.pattern.set_probability_var(track, step, probability, local_variation);
-                                    state.pattern_bank.get_mut(local_current_pattern).set_probability_var(track, step, probability, local_variation);
-                                }
+                            if let Some(mut state) = state.try_write() {
+                                state.capture = Some(capture.status());
                             }
-                        }
-                        // Dynamic track parameter
-                        Command::SetTrackParam { track, ref key, value } => {
-                            if track < num_synths {
-                                synths[track].set_param(key, value);
-                                if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].params_snapshot = synths[track].serialize_params();
-                                }
+                            if let Some(replaced) = sample_capture.replace(capture) {
+                                capture_return.send(replaced.discard());
                             }
+                        } else {
+                            capture_return.send(CapturedSample {
+                                track,
+                                buffer,
+                                underruns: 0,
+                                discarded: true,
+                            });
                         }
-                        Command::SetTrackVolume { track, volume } => {
-                            if track < num_synths {
-                                let v = volume.clamp(0.0, 1.0);
-                                local_volumes[track] = v;
-                                if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].volume = v;
-                                }
-                            }
+                    }
+                    Command::CancelSampleCapture => {
+                        if let Some(capture) = sample_capture.take() {
+                            capture_return.send(capture.discard());
                         }
-                        Command::SetTrackPan { track, pan } => {
-                            if track < num_synths {
-                                let p = pan.clamp(-1.0, 1.0);
-                                local_pans[track] = p;
-                                if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].pan = p;
-                                }
-                            }
+                        if let Some(mut state) = state.try_write() {
+                            state.capture = None;
                         }
-                        Command::ToggleMute(track) => {
-                            if track < num_synths {
-                                local_mutes[track] = !local_mutes[track];
-                                if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].mute = local_mutes[track];
-                                }
+                    }
+                    Command::PlayTone => {
+                        tone.trigger();
+                        tone_playing = true;
+                        if let Some(mut state) = state.try_write() {
+                            state.tone_playing = true;
+                        }
+                    }
+                    Command::StopTone => tone.release(),
+                    Command::SetToneParam { key, value } => {
+                        if tone.set_param(&key, value) {
+                            if let Some(mut state) = state.try_write() {
+                                state.tone = tone.params().clone();
                             }
                         }
-                        Command::ToggleSolo(track) => {
-                            if track < num_synths {
-                                local_solos[track] = !local_solos[track];
+                    }
+                    Command::RecordNote { track, note, velocity } => {
+                        if track < num_synths {
+                            let melodic = synths[track].is_melodic();
+                            let played = played_note(note, melodic, transpose, local_transposes[track]);
+                            synths[track].trigger_with_note_velocity(played, velocity);
+                            if clock.is_playing() {
+                                // Quantized to the nearest step of the track's grid
+                                let (step, fraction) = clock.position();
+                                let length = pattern.length;
+                                let track_step = if local_triplets[track] {
+                                    let pos = (step as f32 + fraction) * TRIPLET_STEPS as f32 / STEPS as f32;
+                                    pos.round() as usize % triplet_steps(length)
+                                } else {
+                                    (step + fraction.round() as usize) % length
+                                };
+                                pattern.record_hit_var(track, track_step, note, velocity, 0, local_variation);
+                                local_pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                                 if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].solo = local_solos[track];
+                                    state.pattern.clone_from(&pattern);
+                                    state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                                 }
                             }
                         }
-                        // Per-track FX commands
-                        Command::SetFxParam { track, param, value } => {
-                            if track < num_synths {
-                                apply_fx_param(&mut fx_chains[track], &mut local_track_fx[track], param, value);
-                                if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].fx = local_track_fx[track].clone();
-                                }
+                    }
+                    Command::ClearTrack(track) => {
+                        if track < num_synths {
+                            pattern.clear_track_var(track, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).clear_track_var(track, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.clone_from(&pattern);
+                                state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                             }
                         }
-                        Command::SetFxFilterType { track, filter_type } => {
-                            if track < num_synths {
-                                fx_chains[track].filter.set_filter_type(filter_type);
-                                local_track_fx[track].filter_type = filter_type;
-                                if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].fx.filter_type = filter_type;
-                                }
+                    }
+                    Command::FillTrack(track) => {
+                        if track < num_synths {
+                            pattern.fill_track_var(track, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).fill_track_var(track, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.clone_from(&pattern);
+                                state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                             }
                         }
-                        Command::ToggleFxEnabled { track, fx } => {
-                            if track < num_synths {
-                                match fx {
-                                    FxType::Filter => {
-                                        fx_chains[track].filter_enabled = !fx_chains[track].filter_enabled;
-                                        local_track_fx[track].filter_enabled = fx_chains[track].filter_enabled;
-                                    }
-                                    FxType::Distortion => {
-                                        fx_chains[track].dist_enabled = !fx_chains[track].dist_enabled;
-                                        local_track_fx[track].dist_enabled = fx_chains[track].dist_enabled;
-                                    }
-                                    FxType::Delay => {
-                                        fx_chains[track].delay_enabled = !fx_chains[track].delay_enabled;
-                                        local_track_fx[track].delay_enabled = fx_chains[track].delay_enabled;
-                                    }
-                                }
-                                if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].fx = local_track_fx[track].clone();
-                                }
+                    }
+                    Command::ChopToSteps(track) => {
+                        if track < num_synths && synths[track].synth_type() == SynthType::Sampler {
+                            synths[track].set_param("slice_count", STEPS as f32);
+                            if let Some(Some(voice)) = deck_b.as_mut().map(|d| &mut d.voices[track]) {
+                                voice.set_param("slice_count", STEPS as f32);
+                            }
+                            pattern.chop_track_var(track, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).chop_track_var(track, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].params_snapshot = synths[track].serialize_params();
+                                state.pattern.clone_from(&pattern);
+                                state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
+                            }
+                        }
+                    }
+                    Command::SetStepNote { track, step, note } => {
+                        if track < num_synths {
+                            pattern.set_note_var(track, step, note, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).set_note_var(track, step, note, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.set_note_var(track, step, note, local_variation);
+                                state.pattern_bank.get_mut(local_current_pattern).set_note_var(track, step, note, local_variation);
                             }
                         }
-                        // Master FX commands
-                        Command::SetMasterFxParam { param, value } => {
-                            apply_master_fx_param(&mut reverb, &mut local_master_fx, param, value);
-                            reverb_enabled = local_master_fx.reverb_enabled;
+                    }
+                    Command::SetStepVelocity { track, step, velocity } => {
+                        if track < num_synths {
+                            pattern.set_velocity_var(track, step, velocity, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).set_velocity_var(track, step, velocity, local_variation);
                             if let Some(mut state) = state.try_write() {
-                                state.master_fx = local_master_fx.clone();
+                                state.pattern.set_velocity_var(track, step, velocity, local_variation);
+                                state.pattern_bank.get_mut(local_current_pattern).set_velocity_var(track, step, velocity, local_variation);
                             }
                         }
-                        Command::ToggleMasterFxEnabled => {
-                            reverb_enabled = !reverb_enabled;
-                            local_master_fx.reverb_enabled = reverb_enabled;
+                    }
+                    Command::SetStepProbability { track, step, probability } => {
+                        if track < num_synths {
+                            pattern.set_probability_var(track, step, probability, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).set_probability_var(track, step, probability, local_variation);
                             if let Some(mut state) = state.try_write() {
-                                state.master_fx.reverb_enabled = reverb_enabled;
+                                state.pattern.set_probability_var(track, step, probability, local_variation);
+                                state.pattern_bank.get_mut(local_current_pattern).set_probability_var(track, step, probability, local_variation);
                             }
                         }
-
-                        // Pattern Bank commands
-                        Command::SelectPattern(p) => {
-                            if p < NUM_PATTERNS {
-                                // Save current pattern to bank
-                                *local_pattern_bank.get_mut(local_current_pattern) = pattern.clone();
-
-                                if clock.is_playing() {
-                                    // Queue for boundary switch
-                                    pending_pattern_switch = Some(p);
-                                } else {
-                                    // Apply immediately when stopped
-                                    local_current_pattern = p;
-                                    pattern = local_pattern_bank.get(p).clone();
-                                    pending_pattern_switch = None;
+                    }
+                    Command::SetStepTie { track, step, tie } => {
+                        if track < num_synths {
+                            pattern.set_tie_var(track, step, tie, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).set_tie_var(track, step, tie, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.set_tie_var(track, step, tie, local_variation);
+                                state.pattern_bank.get_mut(local_current_pattern).set_tie_var(track, step, tie, local_variation);
+                            }
+                        }
+                    }
+                    Command::SetStepOff { track, step, off } => {
+                        if track < num_synths {
+                            pattern.set_note_off_var(track, step, off, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).set_note_off_var(track, step, off, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.set_note_off_var(track, step, off, local_variation);
+                                state.pattern_bank.get_mut(local_current_pattern).set_note_off_var(track, step, off, local_variation);
+                            }
+                        }
+                    }
+                    Command::SetStepSlide { track, step, slide } => {
+                        if track < num_synths {
+                            pattern.set_slide_var(track, step, slide, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).set_slide_var(track, step, slide, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.set_slide_var(track, step, slide, local_variation);
+                                state.pattern_bank.get_mut(local_current_pattern).set_slide_var(track, step, slide, local_variation);
+                            }
+                        }
+                    }
+                    Command::SetStepAccent { track, step, accent } => {
+                        if track < num_synths {
+                            pattern.set_accent_var(track, step, accent, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).set_accent_var(track, step, accent, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.set_accent_var(track, step, accent, local_variation);
+                                state.pattern_bank.get_mut(local_current_pattern).set_accent_var(track, step, accent, local_variation);
+                            }
+                        }
+                    }
+                    Command::SetParamLock { track, step, ref key, value } => {
+                        if track < num_synths && pattern.set_param_lock(track, step, local_variation, key, value) {
+                            local_pattern_bank.get_mut(local_current_pattern).set_param_lock(track, step, local_variation, key, value);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.clone_from(&pattern);
+                                state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
+                            }
+                        }
+                    }
+                    Command::ClearParamLocks { track, step } => {
+                        if track < num_synths {
+                            pattern.clear_param_locks(track, step, local_variation);
+                            local_pattern_bank.get_mut(local_current_pattern).clear_param_locks(track, step, local_variation);
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern.clone_from(&pattern);
+                                state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
+                            }
+                        }
+                    }
+                    // Dynamic track parameter
+                    Command::SetTrackParam { track, ref key, value } => {
+                        if track < num_synths {
+                            synths[track].set_param(key, value);
+                            held_locks[track].forget(key);
+                            if let Some(Some(voice)) = deck_b.as_mut().map(|d| &mut d.voices[track]) {
+                                voice.set_param(key, value);
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].params_snapshot = synths[track].serialize_params();
+                            }
+                        }
+                    }
+                    Command::SetSampleLayerRange { track, layer, min_velocity, max_velocity } => {
+                        if track < num_synths {
+                            synths[track].set_layer_range(layer, min_velocity, max_velocity);
+                            if let Some(Some(voice)) = deck_b.as_mut().map(|d| &mut d.voices[track]) {
+                                voice.set_layer_range(layer, min_velocity, max_velocity);
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].params_snapshot = synths[track].serialize_params();
+                            }
+                        }
+                    }
+                    Command::RemoveSampleLayer { track, layer } => {
+                        if track < num_synths {
+                            synths[track].remove_layer(layer);
+                            if let Some(Some(voice)) = deck_b.as_mut().map(|d| &mut d.voices[track]) {
+                                voice.remove_layer(layer);
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].params_snapshot = synths[track].serialize_params();
+                                state.tracks[track].sample_bytes = synths[track].sample_memory_bytes();
+                            }
+                        }
+                    }
+                    Command::SetTrackVolume { track, volume } => {
+                        if track < num_synths {
+                            let v = volume.clamp(0.0, 1.0);
+                            local_volumes[track].set_target(v);
+                            // A manual move overrides a running fade
+                            volume_fades[track] = VolumeFade::default();
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].volume = v;
+                            }
+                        }
+                    }
+                    Command::SetTrackPan { track, pan } => {
+                        if track < num_synths {
+                            let p = pan.clamp(-1.0, 1.0);
+                            local_pans[track].set_target(p);
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].pan = p;
+                            }
+                        }
+                    }
+                    Command::SetTrackTranspose { track, semitones } => {
+                        if track < num_synths {
+                            local_transposes[track] = semitones.clamp(-MAX_TRACK_TRANSPOSE, MAX_TRACK_TRANSPOSE);
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].transpose = local_transposes[track];
+                            }
+                        }
+                    }
+                    Command::SetTrackGlyph { track, glyph } => {
+                        if track < num_synths {
+                            // Callers trim; anything else falls back to the synth type's
+                            let glyph = glyph.filter(|g| !g.is_empty() && g.chars().count() <= MAX_GLYPH_CHARS);
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].glyph = glyph;
+                            }
+                        }
+                    }
+                    Command::SetTrackNudge { track, ms } => {
+                        if track < num_synths {
+                            let ms = ms.clamp(0.0, MAX_NUDGE_MS);
+                            trigger_delays[track].set_nudge_ms(ms, sample_rate);
+                            if let Some(deck) = deck_b.as_mut() {
+                                deck.delays[track].set_nudge_ms(ms, sample_rate);
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].nudge_ms = ms;
+                            }
+                        }
+                    }
+                    // Applied by the sender (SequencerState::apply_shared)
+                    Command::SetRenderInterpolation(_)
+                    | Command::SetExportTone(_)
+                    | Command::SetScript(_)
+                    | Command::SetProjectKey(_)
+                    | Command::RemoveScript(_)
+                    | Command::SetScriptEnabled { .. }
+                    | Command::SetSampleBudget(_)
+                    | Command::SetSampleStreaming(_) => {}
+                    Command::SetPreviewDuck(db) => {
+                        preview_duck_db = db.clamp(MAX_PREVIEW_DUCK_DB, 0.0);
+                        if let Some(mut state) = state.try_write() {
+                            state.preview_duck_db = preview_duck_db;
+                        }
+                    }
+                    Command::SetTrackTriplet { track, enabled } => {
+                        if track < num_synths {
+                            local_triplets[track] = enabled;
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].triplet = enabled;
+                            }
+                        }
+                    }
+                    Command::ToggleMute(track) => {
+                        if track < num_synths {
+                            local_mutes[track] = !local_mutes[track];
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].mute = local_mutes[track];
+                            }
+                        }
+                    }
+                    Command::ToggleSolo(track) => {
+                        if track < num_synths {
+                            local_solos[track] = !local_solos[track];
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].solo = local_solos[track];
+                            }
+                        }
+                    }
+                    Command::ToggleSoloSafe(track) => {
+                        if track < num_synths {
+                            local_solo_safe[track] = !local_solo_safe[track];
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].solo_safe = local_solo_safe[track];
+                            }
+                        }
+                    }
+                    Command::ToggleCue(track) => {
+                        if track < num_synths {
+                            local_cues[track] = !local_cues[track];
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].cue = local_cues[track];
+                            }
+                        }
+                    }
+                    // Per-track FX commands
+                    Command::SetFxParam { track, param, value } => {
+                        if track < num_synths {
+                            apply_fx_param(&mut fx_chains[track], &mut local_track_fx[track], param, value);
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].fx = local_track_fx[track].clone();
+                            }
+                        }
+                    }
+                    Command::SetFxFilterType { track, filter_type } => {
+                        if track < num_synths {
+                            fx_chains[track].filter.set_filter_type(filter_type);
+                            local_track_fx[track].filter_type = filter_type;
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].fx.filter_type = filter_type;
+                            }
+                        }
+                    }
+                    Command::SetFxModSource { track, source } => {
+                        if track < num_synths {
+                            local_track_fx[track].mod_source = source.filter(|&s| s != track && s < num_synths);
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].fx.mod_source = local_track_fx[track].mod_source;
+                            }
+                        }
+                    }
+                    Command::SetFxModTarget { track, target } => {
+                        if track < num_synths {
+                            fx_chains[track].mod_target = target;
+                            local_track_fx[track].mod_target = target;
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].fx.mod_target = target;
+                            }
+                        }
+                    }
+                    Command::ToggleFxEnabled { track, fx } => {
+                        if track < num_synths {
+                            match fx {
+                                FxType::Filter => {
+                                    fx_chains[track].filter_enabled = !fx_chains[track].filter_enabled;
+                                    local_track_fx[track].filter_enabled = fx_chains[track].filter_enabled;
                                 }
-
-                                if let Some(mut state) = state.try_write() {
-                                    state.pattern_bank = local_pattern_bank.clone();
-                                    if !clock.is_playing() {
-                                        state.current_pattern = p;
-                                        state.pattern = pattern.clone();
-                                    }
+                                FxType::Distortion => {
+                                    fx_chains[track].dist_enabled = !fx_chains[track].dist_enabled;
+                                    local_track_fx[track].dist_enabled = fx_chains[track].dist_enabled;
+                                }
+                                FxType::Delay => {
+                                    fx_chains[track].delay_enabled = !fx_chains[track].delay_enabled;
+                                    local_track_fx[track].delay_enabled = fx_chains[track].delay_enabled;
                                 }
                             }
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].fx = local_track_fx[track].clone();
+                            }
                         }
-                        Command::CopyPattern { src, dst } => {
-                            if src < NUM_PATTERNS && dst < NUM_PATTERNS {
-                                let src_pattern = local_pattern_bank.get(src).clone();
-                                *local_pattern_bank.get_mut(dst) = src_pattern;
-                                // If we copied into the active pattern, update local
-                                if dst == local_current_pattern {
-                                    pattern = local_pattern_bank.get(dst).clone();
+                    }
+                    // Master FX commands
+                    Command::SetMasterFxParam { param, value } => {
+                        apply_master_fx_param(
+                            &mut reverb,
+                            &mut mid_side,
+                            &mut limiter,
+                            &mut local_master_fx,
+                            param,
+                            value,
+                        );
+                        reverb_enabled = local_master_fx.reverb_enabled;
+                        if let Some(mut state) = state.try_write() {
+                            state.master_fx = local_master_fx.clone();
+                        }
+                    }
+                    Command::SetGainMatch(enabled) => {
+                        gain_match = enabled;
+                        for chain in fx_chains.iter_mut() {
+                            chain.gain_match = enabled;
+                        }
+                        if let Some(mut state) = state.try_write() {
+                            state.gain_match = enabled;
+                        }
+                    }
+                    Command::SetHeadroom(enabled) => {
+                        headroom = enabled;
+                        if let Some(mut state) = state.try_write() {
+                            state.headroom = enabled;
+                        }
+                    }
+                    Command::SetMetronome(enabled) => {
+                        metronome_on = enabled;
+                        if let Some(mut state) = state.try_write() {
+                            state.metronome = enabled;
+                        }
+                    }
+                    Command::SetDjFilter(position) => {
+                        dj_filter.set_position(position);
+                        if let Some(mut state) = state.try_write() {
+                            state.dj_filter = position.clamp(-1.0, 1.0);
+                        }
+                    }
+                    Command::ResetClipIndicators => {
+                        for meter in track_meters.iter_mut() {
+                            meter.reset_clips();
+                        }
+                        master_meter.reset_clips();
+                        master_true_peak = 0.0;
+                        if let Some(mut state) = state.try_write() {
+                            for track in state.tracks.iter_mut() {
+                                track.meter.reset_clips();
+                            }
+                            state.master_meter.reset_clips();
+                            state.master_true_peak = 0.0;
+                        }
+                    }
+                    Command::ReleaseDjFilter => {
+                        // Glide back to neutral over one beat
+                        dj_filter.release(60.0 / clock.bpm());
+                        if let Some(mut state) = state.try_write() {
+                            state.dj_filter = 0.0;
+                        }
+                    }
+                    Command::SetTranspose(semitones) => {
+                        transpose = semitones.clamp(-MAX_TRANSPOSE, MAX_TRANSPOSE);
+                        if let Some(mut state) = state.try_write() {
+                            state.transpose = transpose;
+                        }
+                    }
+                    Command::SetDeckB(Some(slot)) => {
+                        if slot < NUM_PATTERNS {
+                            match deck_b.as_mut() {
+                                Some(deck) => deck.slot = slot,
+                                None => {
+                                    deck_b = Some(DeckB::new(slot, &synths, &trigger_delays));
+                                    crossfader.reset(0.0);
                                 }
-                                if let Some(mut state) = state.try_write() {
-                                    state.pattern_bank = local_pattern_bank.clone();
-                                    if dst == local_current_pattern {
-                                        state.pattern = pattern.clone();
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.deck_b = Some(slot);
+                                state.crossfader = crossfader.target();
+                            }
+                        }
+                    }
+                    Command::SetDeckB(None) => {
+                        if let Some(mut deck) = deck_b.take() {
+                            // Faded over to B: deck B's slot becomes the current
+                            // pattern and its voices ring on in place of deck A's
+                            if crossfader.target() > 0.5 {
+                                local_pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
+                                local_current_pattern = deck.slot;
+                                pattern.clone_from(local_pattern_bank.get(deck.slot));
+                                pending_pattern_switch = None;
+                                for ((synth, voice), held) in
+                                    synths.iter_mut().zip(deck.voices.iter_mut()).zip(held_locks.iter_mut())
+                                {
+                                    if let Some(voice) = voice {
+                                        std::mem::swap(synth, voice);
+                                        held.clear();
                                     }
                                 }
+                                std::mem::swap(&mut trigger_delays, &mut deck.delays);
+                                tied_notes.fill(false);
+                            }
+                            crossfader.reset(0.0);
+                            if let Some(mut state) = state.try_write() {
+                                state.deck_b = None;
+                                state.crossfader = 0.0;
+                                state.current_pattern = local_current_pattern;
+                                state.pattern.clone_from(&pattern);
+                                state.pattern_bank.clone_from(&local_pattern_bank);
                             }
                         }
-                        Command::ClearPattern(p) => {
-                            if p < NUM_PATTERNS {
-                                // Clear both variations
-                                local_pattern_bank.get_mut(p).clear_all_var(Variation::A);
-                                local_pattern_bank.get_mut(p).clear_all_var(Variation::B);
-                                if p == local_current_pattern {
-                                    pattern = local_pattern_bank.get(p).clone();
+                    }
+                    Command::SetCrossfader(position) => {
+                        crossfader.set_target(position.clamp(0.0, 1.0));
+                        if let Some(mut state) = state.try_write() {
+                            state.crossfader = crossfader.target();
+                        }
+                    }
+                    Command::ToggleMasterFxEnabled => {
+                        reverb_enabled = !reverb_enabled;
+                        local_master_fx.reverb_enabled = reverb_enabled;
+                        if let Some(mut state) = state.try_write() {
+                            state.master_fx.reverb_enabled = reverb_enabled;
+                        }
+                    }
+                    Command::SetLimiterEnabled(enabled) => {
+                        limiter_enabled = enabled;
+                        local_master_fx.limiter_enabled = enabled;
+                        if let Some(mut state) = state.try_write() {
+                            state.master_fx.limiter_enabled = enabled;
+                        }
+                    }
+                    Command::SetReverbMode(mode) => {
+                        reverb.set_mode(mode);
+                        local_master_fx.reverb_mode = mode;
+                        if let Some(mut state) = state.try_write() {
+                            state.master_fx.reverb_mode = mode;
+                        }
+                    }
+
+                    // Pattern Bank commands
+                    Command::SelectPattern(p) => {
+                        if p < NUM_PATTERNS {
+                            // Save current pattern to bank
+                            local_pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
+
+                            if clock.is_playing() {
+                                // Queue for boundary switch
+                                pending_pattern_switch = Some(p);
+                            } else {
+                                // Apply immediately when stopped
+                                local_current_pattern = p;
+                                pattern.clone_from(local_pattern_bank.get(p));
+                                pending_pattern_switch = None;
+                            }
+
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                state.pending_pattern = pending_pattern_switch;
+                                if !clock.is_playing() {
+                                    state.current_pattern = p;
+                                    state.pattern.clone_from(&pattern);
                                 }
-                                if let Some(mut state) = state.try_write() {
-                                    state.pattern_bank = local_pattern_bank.clone();
-                                    if p == local_current_pattern {
-                                        state.pattern = pattern.clone();
-                                    }
+                            }
+                        }
+                    }
+                    Command::CopyPattern { src, dst } => {
+                        if src < NUM_PATTERNS && dst < NUM_PATTERNS {
+                            if let Some((from, to)) = local_pattern_bank.pair_mut(src, dst) {
+                                to.clone_from(from);
+                            }
+                            // If we copied into the active pattern, update local
+                            if dst == local_current_pattern {
+                                pattern.clone_from(local_pattern_bank.get(dst));
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                if dst == local_current_pattern {
+                                    state.pattern.clone_from(&pattern);
                                 }
                             }
                         }
-
-                        // Playback mode
-                        Command::SetPlaybackMode(mode) => {
-                            local_playback_mode = mode;
-                            if mode == PlaybackMode::Song {
-                                local_arrangement_position = 0;
-                                local_arrangement_repeat = 0;
+                    }
+                    Command::CopyPatternTracks { src, dst, tracks, merge } => {
+                        if src < NUM_PATTERNS && dst < NUM_PATTERNS {
+                            if let Some((from, to)) = local_pattern_bank.pair_mut(src, dst) {
+                                to.copy_tracks_from(from, &tracks, merge);
+                            }
+                            if dst == local_current_pattern {
+                                pattern.clone_from(local_pattern_bank.get(dst));
                             }
                             if let Some(mut state) = state.try_write() {
-                                state.playback_mode = mode;
-                                state.arrangement_position = local_arrangement_position;
-                                state.arrangement_repeat = local_arrangement_repeat;
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                if dst == local_current_pattern {
+                                    state.pattern.clone_from(&pattern);
+                                }
                             }
                         }
-
-                        // Arrangement commands
-                        Command::AppendArrangement { pattern: p, repeats } => {
-                            local_arrangement.append(p, repeats);
+                    }
+                    Command::ReplacePattern { index, pattern: new_pattern } => {
+                        // Ignore patterns built for a different track layout
+                        if index < NUM_PATTERNS
+                            && new_pattern.num_tracks() == local_pattern_bank.get(index).num_tracks()
+                        {
+                            *local_pattern_bank.get_mut(index) = *new_pattern;
+                            if index == local_current_pattern {
+                                pattern.clone_from(local_pattern_bank.get(index));
+                            }
                             if let Some(mut state) = state.try_write() {
-                                state.arrangement = local_arrangement.clone();
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                if index == local_current_pattern {
+                                    state.pattern.clone_from(&pattern);
+                                }
                             }
                         }
-                        Command::InsertArrangement { position, pattern: p, repeats } => {
-                            local_arrangement.insert(position, p, repeats);
+                    }
+                    Command::ClearPattern(p) => {
+                        if p < NUM_PATTERNS {
+                            // Clear both variations
+                            local_pattern_bank.get_mut(p).clear_all_var(Variation::A);
+                            local_pattern_bank.get_mut(p).clear_all_var(Variation::B);
+                            if p == local_current_pattern {
+                                pattern.clone_from(local_pattern_bank.get(p));
+                            }
                             if let Some(mut state) = state.try_write() {
-                                state.arrangement = local_arrangement.clone();
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                if p == local_current_pattern {
+                                    state.pattern.clone_from(&pattern);
+                                }
                             }
                         }
-                        Command::RemoveArrangement(pos) => {
-                            local_arrangement.remove(pos);
-                            // Adjust position if needed
-                            if local_arrangement_position >= local_arrangement.len() && local_arrangement.len() > 0 {
-                                local_arrangement_position = local_arrangement.len() - 1;
+                    }
+
+                    Command::SetPatternLength { pattern: p, length } => {
+                        if p < NUM_PATTERNS {
+                            if p == local_current_pattern {
+                                local_pattern_bank.get_mut(p).clone_from(&pattern);
+                            }
+                            local_pattern_bank.get_mut(p).set_length(length);
+                            if p == local_current_pattern {
+                                pattern.clone_from(local_pattern_bank.get(p));
                             }
                             if let Some(mut state) = state.try_write() {
-                                state.arrangement = local_arrangement.clone();
-                                state.arrangement_position = local_arrangement_position;
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                if p == local_current_pattern {
+                                    state.pattern.clone_from(&pattern);
+                                }
                             }
                         }
-                        Command::SetArrangementEntry { position, pattern: p, repeats } => {
-                            local_arrangement.set_entry(position, p, repeats);
+                    }
+                    Command::SetTrackLength { pattern: p, track, length } => {
+                        if p < NUM_PATTERNS {
+                            if p == local_current_pattern {
+                                pattern.set_track_length(track, length);
+                                local_pattern_bank.get_mut(p).clone_from(&pattern);
+                            } else {
+                                local_pattern_bank.get_mut(p).set_track_length(track, length);
+                            }
                             if let Some(mut state) = state.try_write() {
-                                state.arrangement = local_arrangement.clone();
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                if p == local_current_pattern {
+                                    state.pattern.clone_from(&pattern);
+                                }
                             }
                         }
-                        Command::ClearArrangement => {
-                            local_arrangement.clear();
+                    }
+
+                    // Playback mode
+                    Command::SetPlaybackMode(mode) => {
+                        local_playback_mode = mode;
+                        if mode == PlaybackMode::Song {
                             local_arrangement_position = 0;
                             local_arrangement_repeat = 0;
+                            entry_automation_due = true;
+                        }
+                        song_bar = 0;
+                        song_end_due = false;
+                        master_fade = 1.0;
+                        if let Some(mut state) = state.try_write() {
+                            state.playback_mode = mode;
+                            state.arrangement_position = local_arrangement_position;
+                            state.arrangement_repeat = local_arrangement_repeat;
+                        }
+                    }
+
+                    // Arrangement commands
+                    Command::AppendArrangement { pattern: p, repeats } => {
+                        local_arrangement.append(p, repeats);
+                        if let Some(mut state) = state.try_write() {
+                            state.arrangement.clone_from(&local_arrangement);
+                        }
+                    }
+                    Command::InsertArrangement { position, pattern: p, repeats } => {
+                        local_arrangement.insert(position, p, repeats);
+                        if let Some(mut state) = state.try_write() {
+                            state.arrangement.clone_from(&local_arrangement);
+                        }
+                    }
+                    Command::RemoveArrangement(pos) => {
+                        local_arrangement.remove(pos);
+                        // Adjust position if needed
+                        if local_arrangement_position >= local_arrangement.len() && local_arrangement.len() > 0 {
+                            local_arrangement_position = local_arrangement.len() - 1;
+                        }
+                        if let Some(mut state) = state.try_write() {
+                            state.arrangement.clone_from(&local_arrangement);
+                            state.arrangement_position = local_arrangement_position;
+                        }
+                    }
+                    Command::SetArrangementEntry { position, pattern: p, repeats } => {
+                        local_arrangement.set_entry(position, p, repeats);
+                        if let Some(mut state) = state.try_write() {
+                            state.arrangement.clone_from(&local_arrangement);
+                        }
+                    }
+                    Command::SetEntryChord { position, chord } => {
+                        local_arrangement.set_chord(position, chord);
+                        if let Some(mut state) = state.try_write() {
+                            state.arrangement.clone_from(&local_arrangement);
+                        }
+                    }
+                    Command::SetEntryAutomation { position, ref automation } => {
+                        local_arrangement.set_automation(position, automation.clone());
+                        if let Some(mut state) = state.try_write() {
+                            state.arrangement.clone_from(&local_arrangement);
+                        }
+                    }
+                    Command::SetFadeOutBars(bars) => {
+                        local_arrangement.fade_out_bars = bars.min(MAX_FADE_OUT_BARS);
+                        if local_arrangement.fade_out_bars == 0 {
+                            song_end_due = false;
+                        }
+                        if let Some(mut state) = state.try_write() {
+                            state.arrangement.fade_out_bars = local_arrangement.fade_out_bars;
+                        }
+                    }
+                    Command::ClearArrangement => {
+                        local_arrangement.clear();
+                        local_arrangement_position = 0;
+                        local_arrangement_repeat = 0;
+                        if let Some(mut state) = state.try_write() {
+                            state.arrangement.clone_from(&local_arrangement);
+                            state.arrangement_position = 0;
+                            state.arrangement_repeat = 0;
+                        }
+                    }
+
+                    Command::AddTrack { synth_type, ref name } => {
+                        if !clock.is_playing() {
+                            let new_synth = create_synth(synth_type, sample_rate, None);
+                            let default_note = new_synth.default_note();
+                            synths.push(new_synth);
+                            local_volumes.push(SmoothedParam::new(0.8, sample_rate));
+                            local_pans.push(SmoothedParam::new(0.0, sample_rate));
+                            local_mutes.push(false);
+                            local_triplets.push(false);
+                            local_transposes.push(0);
+                            local_solos.push(false);
+                            local_solo_safe.push(false);
+                            local_cues.push(false);
+                            volume_fades.push(VolumeFade::default());
+                            track_meters.push(LevelMeter::default());
+                            trigger_delays.push(TriggerDelay::new());
+                            held_locks.push(HeldLocks::default());
+                            tied_notes.push(false);
+                            let mut chain = TrackFxChain::new(sample_rate);
+                            chain.gain_match = gain_match;
+                            fx_chains.push(chain);
+                            local_track_fx.push(TrackFxState::default());
+                            if let Some(deck) = deck_b.as_mut() {
+                                deck.voices.push(synths.last().and_then(|s| s.voice_copy()));
+                                deck.delays.push(TriggerDelay::new());
+                            }
+                            // Add track to all patterns
+                            for pat in local_pattern_bank.patterns.iter_mut() {
+                                pat.add_track(default_note);
+                            }
+                            pattern.clone_from(local_pattern_bank.get(local_current_pattern));
                             if let Some(mut state) = state.try_write() {
-                                state.arrangement = local_arrangement.clone();
-                                state.arrangement_position = 0;
-                                state.arrangement_repeat = 0;
+                                state.tracks.push(TrackState {
+                                    synth_type,
+                                    name: name.clone(),
+                                    default_note,
+                                    params_snapshot: synths.last().unwrap().serialize_params(),
+                                    volume: 0.8,
+                                    pan: 0.0,
+                                    mute: false,
+                                    solo: false,
+                                    solo_safe: false,
+                                    fx: TrackFxState::default(),
+                                    nudge_ms: 0.0,
+                                    triplet: false,
+                                    transpose: 0,
+                                    glyph: None,
+                                    sample_bytes: 0,
+                                    meter: LevelMeter::default(),
+                                    cue: false,
+                                    extensions: serde_json::Map::new(),
+                                });
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                state.pattern.clone_from(&pattern);
                             }
                         }
+                    }
 
-                        Command::AddTrack { synth_type, ref name } => {
-                            if !clock.is_playing() {
-                                let new_synth = create_synth(synth_type, sample_rate, None);
-                                let default_note = new_synth.default_note();
-                                synths.push(new_synth);
-                                local_volumes.push(0.8);
-                                local_pans.push(0.0);
-                                local_mutes.push(false);
-                                local_solos.push(false);
-                                fx_chains.push(TrackFxChain::new(sample_rate));
-                                local_track_fx.push(TrackFxState::default());
-                                // Add track to all patterns
-                                for pat in local_pattern_bank.patterns.iter_mut() {
-                                    pat.add_track(default_note);
+                    Command::RemoveTrack(track) => {
+                        if !clock.is_playing() && track < synths.len() && synths.len() > 1 {
+                            synths.remove(track);
+                            local_volumes.remove(track);
+                            local_pans.remove(track);
+                            local_mutes.remove(track);
+                            local_triplets.remove(track);
+                            local_transposes.remove(track);
+                            local_solos.remove(track);
+                            local_solo_safe.remove(track);
+                            local_cues.remove(track);
+                            volume_fades.remove(track);
+                            track_meters.remove(track);
+                            trigger_delays.remove(track);
+                            held_locks.remove(track);
+                            tied_notes.remove(track);
+                            fx_chains.remove(track);
+                            local_track_fx.remove(track);
+                            for fx in local_track_fx.iter_mut() {
+                                fx.remove_track(track);
+                            }
+                            if let Some(deck) = deck_b.as_mut() {
+                                deck.voices.remove(track);
+                                deck.delays.remove(track);
+                            }
+                            // Remove track from all patterns
+                            for pat in local_pattern_bank.patterns.iter_mut() {
+                                pat.remove_track(track);
+                            }
+                            local_arrangement.remove_track(track);
+                            pattern.clone_from(local_pattern_bank.get(local_current_pattern));
+                            // A capture into the removed track is dropped; later tracks move up
+                            if sample_capture.as_ref().is_some_and(|c| c.track == track) {
+                                if let Some(capture) = sample_capture.take() {
+                                    capture_return.send(capture.discard());
                                 }
-                                pattern = local_pattern_bank.get(local_current_pattern).clone();
-                                if let Some(mut state) = state.try_write() {
-                                    state.tracks.push(TrackState {
-                                        synth_type,
-                                        name: name.clone(),
-                                        default_note,
-                                        params_snapshot: synths.last().unwrap().serialize_params(),
-                                        volume: 0.8,
-                                        pan: 0.0,
-                                        mute: false,
-                                        solo: false,
-                                        fx: TrackFxState::default(),
-                                    });
-                                    state.pattern_bank = local_pattern_bank.clone();
-                                    state.pattern = pattern.clone();
+                            } else if let Some(capture) = sample_capture.as_mut().filter(|c| c.track > track) {
+                                capture.track -= 1;
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.capture = sample_capture.as_ref().map(|c| c.status());
+                                state.tracks.remove(track);
+                                for (t, fx) in state.tracks.iter_mut().zip(&local_track_fx) {
+                                    t.fx.mod_source = fx.mod_source;
                                 }
+                                state.arrangement.clone_from(&local_arrangement);
+                                state.pattern_bank.clone_from(&local_pattern_bank);
+                                state.pattern.clone_from(&pattern);
                             }
                         }
+                    }
 
-                        Command::RemoveTrack(track) => {
-                            if !clock.is_playing() && track < synths.len() && synths.len() > 1 {
-                                synths.remove(track);
-                                local_volumes.remove(track);
-                                local_pans.remove(track);
-                                local_mutes.remove(track);
-                                local_solos.remove(track);
-                                fx_chains.remove(track);
-                                local_track_fx.remove(track);
-                                // Remove track from all patterns
-                                for pat in local_pattern_bank.patterns.iter_mut() {
-                                    pat.remove_track(track);
-                                }
-                                pattern = local_pattern_bank.get(local_current_pattern).clone();
+                    Command::LoadSample { track, buffer, ref path, options, info } => {
+                        if track < synths.len() {
+                            // Convert non-sampler tracks to sampler
+                            if synths[track].synth_type() != SynthType::Sampler {
+                                synths[track] = create_synth(SynthType::Sampler, sample_rate, None);
+                                held_locks[track].clear();
+                                tied_notes[track] = false;
                                 if let Some(mut state) = state.try_write() {
-                                    state.tracks.remove(track);
-                                    state.pattern_bank = local_pattern_bank.clone();
-                                    state.pattern = pattern.clone();
+                                    state.tracks[track].synth_type = SynthType::Sampler;
                                 }
                             }
+                            synths[track].load_buffer(buffer, path, info);
+                            synths[track].set_param("normalize", if options.normalize { 1.0 } else { 0.0 });
+                            synths[track].set_param("trim_silence", if options.trim_silence { 1.0 } else { 0.0 });
+                            if let Some(deck) = deck_b.as_mut() {
+                                deck.refresh(track, synths[track].as_ref());
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].params_snapshot = synths[track].serialize_params();
+                                state.tracks[track].sample_bytes = synths[track].sample_memory_bytes();
+                            }
                         }
+                    }
 
-                        Command::LoadSample { track, buffer, ref path } => {
-                            if track < synths.len() {
-                                // Convert non-sampler tracks to sampler
-                                if synths[track].synth_type() != SynthType::Sampler {
-                                    synths[track] = create_synth(SynthType::Sampler, sample_rate, None);
-                                    if let Some(mut state) = state.try_write() {
-                                        state.tracks[track].synth_type = SynthType::Sampler;
-                                    }
-                                }
-                                synths[track].load_buffer(buffer, path);
+                    Command::LoadSampleLayer { track, layer, buffer, ref path } => {
+                        // Layers only apply to samplers; other tracks ignore them
+                        if track < synths.len() {
+                            synths[track].load_layer_buffer(layer, buffer, path);
+                            if let Some(deck) = deck_b.as_mut() {
+                                deck.refresh(track, synths[track].as_ref());
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].params_snapshot = synths[track].serialize_params();
+                                state.tracks[track].sample_bytes = synths[track].sample_memory_bytes();
+                            }
+                        }
+                    }
+
+                    Command::LoadExternalSynth { track, connection } => {
+                        if track < synths.len() {
+                            // Convert other tracks to External
+                            if synths[track].synth_type() != SynthType::External {
+                                synths[track] = create_synth(SynthType::External, sample_rate, None);
+                                held_locks[track].clear();
+                                tied_notes[track] = false;
                                 if let Some(mut state) = state.try_write() {
-                                    state.tracks[track].params_snapshot = synths[track].serialize_params();
+                                    state.tracks[track].synth_type = SynthType::External;
                                 }
                             }
+                            synths[track].connect_external(connection);
+                            if let Some(deck) = deck_b.as_mut() {
+                                deck.refresh(track, synths[track].as_ref());
+                            }
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[track].params_snapshot = synths[track].serialize_params();
+                            }
                         }
+                    }
 
-                        Command::PreviewSample(buffer) => {
-                            preview_buffer = Some(buffer);
-                            preview_pos = 0;
-                        }
+                    Command::PreviewSample(buffer) => {
+                        preview_buffer = Some(buffer);
+                        preview_pos = 0;
+                    }
 
-                        // Pattern Variations
-                        Command::SetVariation(v) => {
-                            local_variation = v;
-                            if let Some(mut state) = state.try_write() {
-                                state.current_variation = v;
-                            }
+                    // Pattern Variations
+                    Command::SetVariation(v) => {
+                        local_variation = v;
+                        if let Some(mut state) = state.try_write() {
+                            state.current_variation = v;
                         }
-                        Command::ToggleVariation => {
-                            local_variation = match local_variation {
-                                Variation::A => Variation::B,
-                                Variation::B => Variation::A,
-                            };
-                            if let Some(mut state) = state.try_write() {
-                                state.current_variation = local_variation;
-                            }
+                    }
+                    Command::ToggleVariation => {
+                        local_variation = match local_variation {
+                            Variation::A => Variation::B,
+                            Variation::B => Variation::A,
+                        };
+                        if let Some(mut state) = state.try_write() {
+                            state.current_variation = local_variation;
                         }
-                        Command::CopyVariation { from, to } => {
-                            pattern.copy_variation(from, to);
-                            local_pattern_bank.get_mut(local_current_pattern).copy_variation(from, to);
-                            if let Some(mut state) = state.try_write() {
-                                state.pattern = pattern.clone();
-                                *state.pattern_bank.get_mut(local_current_pattern) = pattern.clone();
-                            }
+                    }
+                    Command::CopyVariation { from, to } => {
+                        pattern.copy_variation(from, to);
+                        local_pattern_bank.get_mut(local_current_pattern).copy_variation(from, to);
+                        if let Some(mut state) = state.try_write() {
+                            state.pattern.clone_from(&pattern);
+                            state.pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
                         }
+                    }
 
-                        Command::LoadProject(new_state) => {
-                            // Stop playback
-                            clock.stop();
-                            clock.set_bpm(new_state.bpm);
-                            pending_pattern_switch = None;
+                    Command::LoadProject(new_state, parked) => {
+                        // Stop playback
+                        clock.stop();
+                        clock.set_bpm(new_state.bpm);
+                        clock.set_swing(new_state.swing);
+                        pending_pattern_switch = None;
 
-                            // Reconstruct synths from track data
-                            synths.clear();
-                            local_volumes.clear();
-                            local_pans.clear();
-                            local_mutes.clear();
-                            local_solos.clear();
-                            fx_chains.clear();
-                            local_track_fx.clear();
+                        // Take the parked synths back, or reconstruct them
+                        // from track data; the replaced ones are dropped
+                        // (or parked) by the UI
+                        let parked = parked.take();
+                        let restored = parked.len() == new_state.tracks.len();
+                        let next = if restored { parked } else { Vec::with_capacity(new_state.tracks.len()) };
+                        let _ = synth_return.try_send(std::mem::replace(&mut synths, next));
+                        local_volumes.clear();
+                        local_pans.clear();
+                        local_mutes.clear();
+                        local_triplets.clear();
+                        local_transposes.clear();
+                        local_solos.clear();
+                        local_solo_safe.clear();
+                        local_cues.clear();
+                        volume_fades.clear();
+                        track_meters.clear();
+                        master_meter = LevelMeter::default();
+                        trigger_delays.clear();
+                        held_locks.clear();
+                        tied_notes.clear();
+                        fx_chains.clear();
+                        local_track_fx.clear();
+                        deck_b = None;
+                        crossfader.reset(0.0);
+                        if let Some(capture) = sample_capture.take() {
+                            capture_return.send(capture.discard());
+                        }
 
-                            for track in &new_state.tracks {
-                                let synth = create_synth(
+                        for track in &new_state.tracks {
+                            if !restored {
+                                synths.push(create_synth(
                                     track.synth_type,
                                     sample_rate,
                                     Some(&track.params_snapshot),
-                                );
-                                synths.push(synth);
-                                local_volumes.push(track.volume);
-                                local_pans.push(track.pan);
-                                local_mutes.push(track.mute);
-                                local_solos.push(track.solo);
-                                let mut chain = TrackFxChain::new(sample_rate);
-                                configure_fx_chain(&mut chain, &track.fx);
-                                fx_chains.push(chain);
-                                local_track_fx.push(track.fx.clone());
-                            }
-
-                            // Restore master FX
-                            reverb.set_decay(new_state.master_fx.reverb_decay);
-                            reverb.set_mix(new_state.master_fx.reverb_mix);
-                            reverb.set_damping(new_state.master_fx.reverb_damping);
-                            reverb_enabled = new_state.master_fx.reverb_enabled;
-                            local_master_fx = new_state.master_fx.clone();
-
-                            // Restore pattern bank + arrangement + variation
-                            local_pattern_bank = new_state.pattern_bank.clone();
-                            local_current_pattern = new_state.current_pattern;
-                            pattern = local_pattern_bank.get(local_current_pattern).clone();
-                            local_playback_mode = new_state.playback_mode;
-                            local_arrangement = new_state.arrangement.clone();
-                            local_arrangement_position = 0;
-                            local_arrangement_repeat = 0;
-                            local_variation = new_state.current_variation;
-
-                            // Sync shared state
-                            if let Some(mut state) = state.try_write() {
-                                *state = *new_state;
-                                state.playing = false;
-                                state.current_step = 0;
-                                state.arrangement_position = 0;
-                                state.arrangement_repeat = 0;
+                                ));
                             }
+                            local_volumes.push(SmoothedParam::new(track.volume, sample_rate));
+                            local_pans.push(SmoothedParam::new(track.pan, sample_rate));
+                            local_mutes.push(track.mute);
+                            local_triplets.push(track.triplet);
+                            local_transposes.push(track.transpose);
+                            local_solos.push(track.solo);
+                            local_solo_safe.push(track.solo_safe);
+                            local_cues.push(track.cue);
+                            volume_fades.push(VolumeFade::default());
+                            track_meters.push(LevelMeter::default());
+                            let mut delay = TriggerDelay::new();
+                            delay.set_nudge_ms(track.nudge_ms, sample_rate);
+                            trigger_delays.push(delay);
+                            held_locks.push(HeldLocks::default());
+                            tied_notes.push(false);
+                            let mut chain = TrackFxChain::new(sample_rate);
+                            configure_fx_chain(&mut chain, &track.fx);
+                            chain.gain_match = gain_match;
+                            fx_chains.push(chain);
+                            local_track_fx.push(track.fx.clone());
+                        }
+
+                        // Restore master FX
+                        configure_reverb(&mut reverb, &new_state.master_fx);
+                        reverb_enabled = new_state.master_fx.reverb_enabled;
+                        configure_mid_side(&mut mid_side, &new_state.master_fx);
+                        configure_limiter(&mut limiter, &new_state.master_fx);
+                        limiter_enabled = new_state.master_fx.limiter_enabled;
+                        local_master_fx = new_state.master_fx.clone();
+
+                        // Restore pattern bank + arrangement + variation
+                        local_pattern_bank.clone_from(&new_state.pattern_bank);
+                        local_current_pattern = new_state.current_pattern;
+                        pattern.clone_from(local_pattern_bank.get(local_current_pattern));
+                        local_playback_mode = new_state.playback_mode;
+                        local_arrangement.clone_from(&new_state.arrangement);
+                        local_arrangement_position = 0;
+                        local_arrangement_repeat = 0;
+                        entry_automation_due = true;
+                        song_bar = 0;
+                        song_end_due = false;
+                        master_fade = 1.0;
+                        preview_duck_db = new_state.preview_duck_db;
+                        transpose = new_state.transpose;
+                        local_variation = new_state.current_variation;
+                        dj_filter.reset(0.0);
+
+                        // Sync shared state
+                        if let Some(mut state) = state.try_write() {
+                            let cue_output = state.cue_output.take();
+                            *state = *new_state;
+                            state.cue_output = cue_output;
+                            state.midi_sync = midi_sync;
+                            state.tone = tone.params().clone();
+                            state.tone_playing = tone_playing;
+                            state.sample_rate = sample_rate;
+                            state.gain_match = gain_match;
+                            state.headroom = headroom;
+                            state.metronome = metronome_on;
+                            state.playing = false;
+                            state.current_step = 0;
+                            state.arrangement_position = 0;
+                            state.arrangement_repeat = 0;
                         }
                     }
                 }
+            }
+            command_rx.mark_applied();
 
-                // Generate audio
-                for frame in data.chunks_mut(channels) {
-                    let num_synths = synths.len();
+            for _ in 0..midi_pulses {
+                clock.midi_pulse();
+            }
 
-                    // Check for step trigger
-                    if let Some(step) = clock.tick() {
-                        // Notify all synths of step tick (for hold_steps countdown)
-                        for synth in synths.iter_mut() {
-                            synth.step_tick();
+            let overlay_track = analyzer.overlay();
+
+            // Tempo-synced loops follow the clock, including MIDI sync
+            let bpm = clock.bpm();
+            for synth in synths.iter_mut() {
+                synth.set_tempo(bpm);
+            }
+            if let Some(deck) = deck_b.as_mut() {
+                for voice in deck.voices.iter_mut().flatten() {
+                    voice.set_tempo(bpm);
+                }
+            }
+
+            // Generate audio
+            for frame in data.chunks_mut(channels) {
+                let num_synths = synths.len();
+
+                // Check for step trigger (the clock wraps at the playing pattern's end)
+                clock.set_length(pattern.length);
+                let mut tick = clock.tick();
+                if clock.take_midi_pulse() && midi_sync == MidiSync::Master {
+                    let _ = midi_out_tx.try_send(CLOCK_PULSE);
+                }
+                if tick == Some(0) && song_end_due {
+                    // The faded-out song has ended: stop at the bar line
+                    song_end_due = false;
+                    song_bar = 0;
+                    tick = None;
+                    if midi_sync == MidiSync::Master {
+                        let _ = midi_out_tx.try_send(CLOCK_STOP);
+                    }
+                    clock.stop();
+                    for synth in synths.iter_mut() {
+                        synth.stop();
+                    }
+                    for delay in trigger_delays.iter_mut() {
+                        delay.clear();
+                    }
+                    for (held, synth) in held_locks.iter_mut().zip(synths.iter_mut()) {
+                        held.release(synth.as_mut());
+                    }
+                    release_tied(&mut tied_notes, &mut synths);
+                    if let Some(deck) = deck_b.as_mut() {
+                        deck.stop();
+                    }
+                    if let Some(mut state) = state.try_write() {
+                        state.playing = false;
+                        state.current_step = 0;
+                        state.arrangement_position = 0;
+                        state.arrangement_repeat = 0;
+                    }
+                }
+                if let Some(step) = tick {
+                    if step == 0 && local_playback_mode == PlaybackMode::Song {
+                        song_bar += 1;
+                    }
+                    // An armed capture starts with the step
+                    if let Some(capture) = sample_capture.as_mut() {
+                        capture.start();
+                    }
+                    if metronome_on && step % 4 == 0 {
+                        metronome.trigger(step == 0);
+                    }
+                    // Notify all synths of step tick (for hold_steps countdown)
+                    for synth in synths.iter_mut() {
+                        synth.step_tick();
+                    }
+                    if let Some(deck) = deck_b.as_mut() {
+                        for voice in deck.voices.iter_mut().flatten() {
+                            voice.step_tick();
+                        }
+                    }
+                    if step == 0 && entry_automation_due && local_playback_mode == PlaybackMode::Song {
+                        entry_automation_due = false;
+                        if let Some(entry) = local_arrangement.entries.get(local_arrangement_position) {
+                            let entry_samples = (entry.repeats * pattern.length) as f32 * clock.samples_per_step();
+                            apply_automation(
+                                &entry.automation,
+                                entry_samples as usize,
+                                sample_rate,
+                                &mut local_mutes,
+                                &mut local_solos,
+                                &mut volume_fades,
+                                |t| local_volumes[t].target(),
+                            );
+                            if !entry.automation.is_empty() {
+                                if let Some(mut state) = state.try_write() {
+                                    for (t, track) in state.tracks.iter_mut().enumerate() {
+                                        track.mute = local_mutes[t];
+                                        track.solo = local_solos[t];
+                                    }
+                                }
+                            }
                         }
-                        // Trigger synths based on pattern (with velocity and probability)
-                        for i in 0..num_synths {
-                            let sd = pattern.get_step_var(i, step, local_variation);
-                            if sd.active {
+                    }
+                    // Song mode: the entry's chord degree moves melodic tracks too
+                    let step_transpose = match local_playback_mode {
+                        PlaybackMode::Song => local_arrangement
+                            .entries
+                            .get(local_arrangement_position)
+                            .map_or(transpose, |e| transpose + chord_shift(e.chord)),
+                        PlaybackMode::Pattern => transpose,
+                    };
+                    // Trigger synths based on pattern (with velocity and probability)
+                    for i in 0..num_synths {
+                        // Triplet tracks may have no step here, or one partway through it
+                        // Tracks with their own loop length count from playback start
+                        let loop_length = pattern.track_loop(i, local_triplets[i]);
+                        let at = looped_step_at(step, clock.run_step(), local_triplets[i], loop_length);
+                        if let Some((track_step, offset)) = at {
+                            let sd = pattern.get_step_var(i, track_step, local_variation);
+                            // A tied step carries the held note on; any other step ends it
+                            if tied_notes[i] && !(sd.active && sd.tie) {
+                                synths[i].note_off();
+                                tied_notes[i] = false;
+                            }
+                            if sd.active && !tied_notes[i] {
                                 // Check probability (100 = always trigger)
                                 let should_trigger = sd.probability >= 100
                                     || (next_prng() % 100) < sd.probability as u32;
                                 if should_trigger {
-                                    synths[i].trigger_with_note_velocity(sd.note, sd.velocity);
+                                    let offset = offset + sd.offset as f32 / 100.0;
+                                    let extra = (offset * clock.samples_per_step()) as usize;
+                                    let melodic = synths[i].is_melodic();
+                                    let note = played_note(sd.note, melodic, step_transpose, local_transposes[i]);
+                                    let steps = pattern.track_steps(i, local_triplets[i]);
+                                    let hold = pattern.is_tied_var(i, (track_step + 1) % steps, steps, local_variation);
+                                    tied_notes[i] = hold;
+                                    let trigger = Trigger {
+                                        note,
+                                        velocity: sd.velocity,
+                                        step: track_step,
+                                        hold,
+                                        off: false,
+                                        slide: sd.slide,
+                                        accent: sd.accent,
+                                    };
+                                    if let Some(t) = trigger_delays[i].schedule(trigger, extra) {
+                                        held_locks[i].play(synths[i].as_mut(), pattern.param_locks(i, t.step, local_variation), t);
+                                    }
+                                }
+                            } else if sd.off && !sd.active {
+                                // Note-off step: release the sounding note on the step's timing
+                                let extra = ((offset + sd.offset as f32 / 100.0) * clock.samples_per_step()) as usize;
+                                let trigger = Trigger::note_off(sd.note, track_step);
+                                if let Some(t) = trigger_delays[i].schedule(trigger, extra) {
+                                    held_locks[i].play(synths[i].as_mut(), pattern.param_locks(i, t.step, local_variation), t);
                                 }
                             }
                         }
+                        // Deck B's slot on the same clock (the live copy if it's also deck A)
+                        if let Some(deck) = deck_b.as_mut() {
+                            let deck_pattern = if deck.slot == local_current_pattern {
+                                &pattern
+                            } else {
+                                local_pattern_bank.get(deck.slot)
+                            };
+                            let loop_length = deck_pattern.track_loop(i, local_triplets[i]);
+                            let Some((track_step, offset)) =
+                                looped_step_at(step, clock.run_step(), local_triplets[i], loop_length)
+                            else {
+                                continue;
+                            };
+                            let sd = deck_pattern.get_step_var(i, track_step, local_variation);
+                            // Deck B plays a run of tied steps as one hit
+                            let steps = deck_pattern.track_steps(i, local_triplets[i]);
+                            let plays = sd.active
+                                && !deck_pattern.is_tied_var(i, track_step, steps, local_variation)
+                                && (sd.probability >= 100 || (next_prng() % 100) < sd.probability as u32);
+                            if plays || (sd.off && !sd.active) {
+                                let offset = offset + sd.offset as f32 / 100.0;
+                                let extra = (offset * clock.samples_per_step()) as usize;
+                                let melodic = synths[i].is_melodic();
+                                let note = played_note(sd.note, melodic, step_transpose, local_transposes[i]);
+                                // Deck B plays without parameter locks
+                                let trigger = Trigger {
+                                    note,
+                                    velocity: sd.velocity,
+                                    step: track_step,
+                                    hold: false,
+                                    off: !plays,
+                                    slide: sd.slide,
+                                    accent: sd.accent,
+                                };
+                                if let Some(t) = deck.delays[i].schedule(trigger, extra) {
+                                    deck.trigger(i, t);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                // Fire nudged triggers that are now due
+                for i in 0..num_synths {
+                    while let Some(t) = trigger_delays[i].pop_due() {
+                        held_locks[i].play(synths[i].as_mut(), pattern.param_locks(i, t.step, local_variation), t);
+                    }
+                    trigger_delays[i].tick();
+                    if let Some(deck) = deck_b.as_mut() {
+                        while let Some(t) = deck.delays[i].pop_due() {
+                            deck.trigger(i, t);
+                        }
+                        deck.delays[i].tick();
                     }
+                }
 
-                    // Pattern boundary logic
-                    if clock.take_pattern_wrap() {
-                        match local_playback_mode {
-                            PlaybackMode::Pattern => {
-                                // Apply pending pattern switch at boundary
-                                if let Some(new_pat) = pending_pattern_switch.take() {
-                                    *local_pattern_bank.get_mut(local_current_pattern) = pattern.clone();
-                                    local_current_pattern = new_pat;
-                                    pattern = local_pattern_bank.get(new_pat).clone();
-                                    if let Some(mut state) = state.try_write() {
-                                        state.current_pattern = new_pat;
-                                        state.pattern = pattern.clone();
-                                        state.pattern_bank = local_pattern_bank.clone();
-                                    }
+                // Pattern boundary logic
+                if clock.take_pattern_wrap() {
+                    match local_playback_mode {
+                        PlaybackMode::Pattern => {
+                            // Apply pending pattern switch at boundary
+                            if let Some(new_pat) = pending_pattern_switch.take() {
+                                local_pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
+                                local_current_pattern = new_pat;
+                                pattern.clone_from(local_pattern_bank.get(new_pat));
+                                if let Some(mut state) = state.try_write() {
+                                    state.current_pattern = new_pat;
+                                    state.pattern.clone_from(&pattern);
+                                    state.pattern_bank.clone_from(&local_pattern_bank);
                                 }
                             }
-                            PlaybackMode::Song => {
-                                if !local_arrangement.is_empty() {
-                                    let entry = local_arrangement.entries[local_arrangement_position];
-                                    local_arrangement_repeat += 1;
-                                    if local_arrangement_repeat >= entry.repeats {
-                                        // Advance to next entry
-                                        local_arrangement_repeat = 0;
-                                        local_arrangement_position = (local_arrangement_position + 1)
-                                            % local_arrangement.len();
-                                        // Load new pattern from bank
-                                        let new_entry = local_arrangement.entries[local_arrangement_position];
-                                        *local_pattern_bank.get_mut(local_current_pattern) = pattern.clone();
-                                        local_current_pattern = new_entry.pattern;
-                                        pattern = local_pattern_bank.get(new_entry.pattern).clone();
-                                        if let Some(mut state) = state.try_write() {
-                                            state.current_pattern = local_current_pattern;
-                                            state.pattern = pattern.clone();
-                                            state.arrangement_position = local_arrangement_position;
-                                            state.arrangement_repeat = local_arrangement_repeat;
-                                        }
-                                    } else if let Some(mut state) = state.try_write() {
+                        }
+                        PlaybackMode::Song => {
+                            if !local_arrangement.is_empty() {
+                                let repeats = local_arrangement.entries[local_arrangement_position].repeats;
+                                local_arrangement_repeat += 1;
+                                if local_arrangement_repeat >= repeats {
+                                    // Advance to next entry
+                                    local_arrangement_repeat = 0;
+                                    local_arrangement_position = (local_arrangement_position + 1)
+                                        % local_arrangement.len();
+                                    if local_arrangement_position == 0 && local_arrangement.fade_out_bars > 0 {
+                                        song_end_due = true;
+                                    }
+                                    // Load new pattern from bank
+                                    let new_pattern = local_arrangement.entries[local_arrangement_position].pattern;
+                                    local_pattern_bank.get_mut(local_current_pattern).clone_from(&pattern);
+                                    local_current_pattern = new_pattern;
+                                    pattern.clone_from(local_pattern_bank.get(new_pattern));
+                                    entry_automation_due = true;
+                                    if let Some(mut state) = state.try_write() {
+                                        state.current_pattern = local_current_pattern;
+                                        state.pattern.clone_from(&pattern);
+                                        state.arrangement_position = local_arrangement_position;
                                         state.arrangement_repeat = local_arrangement_repeat;
                                     }
+                                } else if let Some(mut state) = state.try_write() {
+                                    state.arrangement_repeat = local_arrangement_repeat;
                                 }
                             }
                         }
                     }
+                }
 
-                    // Get raw synth output and apply per-track FX
-                    let any_solo = local_solos.iter().any(|&s| s);
+                // Get raw synth output and apply per-track FX
+                let any_solo = local_solos.iter().any(|&s| s);
 
-                    let mut left = 0.0f32;
-                    let mut right = 0.0f32;
-                    for i in 0..num_synths {
-                        let raw = fx_chains[i].process(synths[i].next_sample());
-                        let audible = if any_solo {
-                            local_solos[i]
-                        } else {
-                            !local_mutes[i]
-                        };
-                        if !audible {
-                            continue;
+                let mut left = 0.0f32;
+                let mut right = 0.0f32;
+                // Cued tracks post-fader, whether or not they're in the mix
+                let mut cue_left = 0.0f32;
+                let mut cue_right = 0.0f32;
+                let mut cue_active = false;
+                let mut overlay_sample = 0.0f32;
+                // One hardware input sample per frame, shared by all Input tracks
+                let input = input_jitter.pull(&input_rx);
+                let input_sample = input.unwrap_or(0.0);
+                if let Some(capture) = sample_capture.as_mut() {
+                    if capture.push(input) {
+                        // Saving and loading it is up to the UI thread
+                        if let Some(capture) = sample_capture.take() {
+                            capture_return.send(capture.finish());
+                        }
+                    }
+                }
+                let (deck_a_gain, deck_b_gain) = crossfade_gains(crossfader.next());
+                // Voices first, so any track can modulate any other this sample
+                let mut voices = [0.0f32; MAX_TRACKS];
+                for i in 0..num_synths {
+                    synths[i].set_input(input_sample);
+                    voices[i] = synths[i].next_sample();
+                    // Decks share the track's FX and fader
+                    if let Some(deck) = deck_b.as_mut() {
+                        voices[i] = voices[i] * deck_a_gain + deck.next_sample(i) * deck_b_gain;
+                    }
+                }
+                for i in 0..num_synths {
+                    let modulator = local_track_fx[i].modulator(i, num_synths).map(|s| voices[s]);
+                    let raw = fx_chains[i].process(voices[i], modulator);
+                    if let Some(v) = volume_fades[i].next() {
+                        local_volumes[i].set_target(v);
+                        if !volume_fades[i].is_active() {
+                            if let Some(mut state) = state.try_write() {
+                                state.tracks[i].volume = v;
+                            }
                         }
-                        let s = raw * local_volumes[i];
-                        let angle = (local_pans[i] + 1.0) * 0.25 * std::f32::consts::PI;
-                        left += s * angle.cos();
-                        right += s * angle.sin();
                     }
+                    // Advance ramps even while silent so unmuting lands on the current value
+                    let volume = local_volumes[i].next();
+                    let pan = local_pans[i].next();
+                    // Analyzer overlay sees the track even when muted/not soloed
+                    if overlay_track == Some(i) {
+                        overlay_sample = raw * volume;
+                    }
+                    let audible = track_audible(local_mutes[i], local_solos[i], local_solo_safe[i], any_solo);
+                    // Silent tracks meter silence so their hold falls back
+                    track_meters[i].process(if audible { raw * volume } else { 0.0 }, &ballistics);
+                    let s = raw * volume;
+                    let angle = (pan + 1.0) * 0.25 * std::f32::consts::PI;
+                    let (sl, sr) = (s * angle.cos(), s * angle.sin());
+                    if local_cues[i] {
+                        cue_left += sl;
+                        cue_right += sr;
+                        cue_active = true;
+                    }
+                    if !audible {
+                        continue;
+                    }
+                    left += sl;
+                    right += sr;
+                }
 
-                    // Preview sample (one-shot, no FX, straight to mix)
-                    if let Some(ref buf) = preview_buffer {
-                        if preview_pos < buf.len() {
-                            let preview_sample = buf[preview_pos] * 0.8;
+                // Duck the mix under a preview while playing (unless it's on the cue)
+                let duck_target = if preview_buffer.is_some() && clock.is_playing() && !has_cue {
+                    10.0f32.powf(preview_duck_db / 20.0)
+                } else {
+                    1.0
+                };
+                duck_gain += (duck_target - duck_gain) * duck_coeff;
+                left *= duck_gain;
+                right *= duck_gain;
+
+                // Preview sample (one-shot, no FX, straight to the cue or mix)
+                if let Some(ref buf) = preview_buffer {
+                    if preview_pos < buf.len() {
+                        let preview_sample = buf[preview_pos] * 0.8;
+                        if has_cue {
+                            cue_left += preview_sample;
+                            cue_right += preview_sample;
+                            cue_active = true;
+                        } else {
                             left += preview_sample;
                             right += preview_sample;
-                            preview_pos += 1;
-                        } else {
-                            preview_buffer = None;
-                            preview_pos = 0;
                         }
+                        preview_pos += 1;
+                    } else {
+                        preview_buffer = None;
+                        preview_pos = 0;
                     }
+                }
 
-                    // Master reverb
-                    if reverb_enabled {
-                        let (rl, rr) = reverb.process_stereo(left, right);
-                        left = rl;
-                        right = rr;
+                // Utility tone, before the master FX so sirens get the reverb
+                if tone.is_active() {
+                    let s = tone.next_sample();
+                    left += s;
+                    right += s;
+                } else if tone_playing {
+                    // Ran its length or faded out after StopTone
+                    if let Some(mut state) = state.try_write() {
+                        tone_playing = false;
+                        state.tone_playing = false;
                     }
+                }
+
+                // Master reverb
+                if reverb_enabled {
+                    let (rl, rr) = reverb.process_stereo(left, right);
+                    left = rl;
+                    right = rr;
+                }
+
+                // Master mid/side
+                let (ml, mr) = mid_side.process_stereo(left, right);
+                left = ml;
+                right = mr;
+
+                // DJ sweep filter
+                let (dl, dr) = dj_filter.process_stereo(left, right);
+                left = dl;
+                right = dr;
+
+                // Song fade-out (holds while stopped so the tail stays faded)
+                if clock.is_playing() && local_playback_mode == PlaybackMode::Song && song_bar > 0 {
+                    let (step, fraction) = clock.position();
+                    let bars = (song_bar - 1) as f32 + (step as f32 + fraction) / pattern.length as f32;
+                    master_fade = local_arrangement.fade_out_gain(bars);
+                }
+                left *= master_fade;
+                right *= master_fade;
+
+                // Remove DC offset before the output stage
+                left = dc_blockers[0].process(left);
+                right = dc_blockers[1].process(right);
+
+                if limiter_enabled {
+                    (left, right) = limiter.process_stereo(left, right);
+                    limiter_reduction = limiter_reduction.max(limiter.reduction_db());
+                }
 
-                    // Soft clip both channels
+                master_meter.process(left.abs().max(right.abs()), &ballistics);
+                let true_peak = true_peak_meters[0].process(left).max(true_peak_meters[1].process(right));
+                master_true_peak = master_true_peak.max(true_peak);
+
+                // Soft clip both channels (headroom monitoring lets overs
+                // through for the meter to flag)
+                if !headroom {
                     left = soft_clip(left);
                     right = soft_clip(right);
+                }
 
-                    // Write stereo output (left to ch0, right to ch1, mono fallback for others)
-                    for (ch, channel_sample) in frame.iter_mut().enumerate() {
-                        let sample = match ch {
-                            0 => left,
-                            1 => right,
-                            _ => (left + right) * 0.5,
-                        };
-                        *channel_sample = T::from_sample(sample);
-                    }
+                analyzer_capture.push(&analyzer, (left + right) * 0.5, overlay_sample);
 
-                    // Periodic state sync (for UI to read current_step + params snapshots)
-                    sync_counter += 1;
-                    if sync_counter >= sync_interval {
-                        sync_counter = 0;
-                        if let Some(mut state) = state.try_write() {
-                            state.current_step = clock.current_step();
-                            state.playing = clock.is_playing();
-                            state.pattern = pattern.clone();
-                            state.current_pattern = local_current_pattern;
-                            state.playback_mode = local_playback_mode;
-                            state.arrangement_position = local_arrangement_position;
-                            state.arrangement_repeat = local_arrangement_repeat;
-                            // Sync param snapshots
-                            for (i, synth) in synths.iter().enumerate() {
-                                if i < state.tracks.len() {
-                                    state.tracks[i].params_snapshot = synth.serialize_params();
-                                }
+                // Metronome click, after the meters and scope
+                let click = metronome.next_sample();
+                left += click;
+                right += click;
+
+                // Headphones follow the master while nothing is cued
+                if cue_active {
+                    cue_left = soft_clip(cue_left);
+                    cue_right = soft_clip(cue_right);
+                } else {
+                    cue_left = left;
+                    cue_right = right;
+                }
+                if let CueRoute::Device(ref tx) = cue {
+                    let _ = tx.try_send((cue_left, cue_right));
+                }
+                let cue_channels = matches!(cue, CueRoute::Channels);
+
+                // Write stereo output (left to ch0, right to ch1, cue to ch2/ch3
+                // when cueing on channels, mono fallback for others)
+                for (ch, channel_sample) in frame.iter_mut().enumerate() {
+                    let sample = match ch {
+                        0 => left,
+                        1 => right,
+                        2 if cue_channels => cue_left,
+                        3 if cue_channels => cue_right,
+                        _ => (left + right) * 0.5,
+                    };
+                    *channel_sample = T::from_sample(sample);
+                }
+
+                // Periodic state sync (for UI to read current_step + params snapshots)
+                sync_counter += 1;
+                if sync_counter >= sync_interval {
+                    sync_counter = 0;
+                    if let Some(mut state) = state.try_write() {
+                        state.current_step = clock.current_step();
+                        state.run_steps = clock.run_steps();
+                        state.run_bars = clock.run_bars();
+                        state.playing = clock.is_playing();
+                        if clock.is_external() {
+                            state.bpm = clock.bpm();
+                        }
+                        state.pattern.clone_from(&pattern);
+                        state.current_pattern = local_current_pattern;
+                        state.pending_pattern = pending_pattern_switch;
+                        state.playback_mode = local_playback_mode;
+                        state.arrangement_position = local_arrangement_position;
+                        state.arrangement_repeat = local_arrangement_repeat;
+                        state.capture = sample_capture.as_ref().map(|c| c.status());
+                        // Sync param snapshots, sample memory and meters
+                        for (i, synth) in synths.iter().enumerate() {
+                            if i < state.tracks.len() {
+                                state.tracks[i].params_snapshot = held_locks[i].unlocked_params(synth.as_ref());
+                                state.tracks[i].sample_bytes = synth.sample_memory_bytes();
+                                state.tracks[i].meter = track_meters[i].take_sync();
                             }
                         }
+                        state.master_meter = master_meter.take_sync();
+                        state.master_true_peak = master_true_peak;
+                        state.limiter_reduction = limiter_reduction;
+                        limiter_reduction = 0.0;
                     }
                 }
+            }
+        };
+
+        // Last panic message reported, so a panic repeating every buffer is reported once
+        let mut last_panic: Option<String> = None;
+        let stream_errors = errors.clone();
+        let stream = device.build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                AUDIO_THREAD.with(|t| t.set(true));
+                let started = Instant::now();
+                match panic::catch_unwind(AssertUnwindSafe(|| process(data))) {
+                    Ok(()) => last_panic = None,
+                    Err(payload) => {
+                        // Output silence for the failed buffer
+                        for sample in data.iter_mut() {
+                            *sample = T::from_sample(0.0f32);
+                        }
+                        let message = panic_message(payload.as_ref());
+                        if last_panic.as_deref() != Some(message.as_str()) {
+                            let _ = errors.try_send(format!("Audio thread panic: {}", message));
+                            last_panic = Some(message);
+                        }
+                    }
+                }
+                // Processing slower than real time means the device ran dry
+                let frames = data.len() / channels;
+                if started.elapsed().as_secs_f32() * sample_rate > frames as f32 {
+                    xruns.fetch_add(1, Ordering::Relaxed);
+                }
             },
-            |err| {
-                eprintln!("Audio stream error: {}", err);
+            move |err| {
+                let _ = stream_errors.try_send(format!("Audio stream error: {}", err));
             },
             None,
         )?;
@@ -860,49 +2863,86 @@ impl AudioEngine {
     }
 }
 
+/// Text of a caught panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Release the notes tracks are holding for tied steps (on stop)
+fn release_tied(tied_notes: &mut [bool], synths: &mut [Box<dyn SoundSource>]) {
+    for (tied, synth) in tied_notes.iter_mut().zip(synths.iter_mut()) {
+        if std::mem::take(tied) {
+            synth.note_off();
+        }
+    }
+}
+
 /// Apply a per-track FX parameter change
 fn apply_fx_param(chain: &mut TrackFxChain, local: &mut TrackFxState, param: FxParamId, value: f32) {
     match param {
         FxParamId::FilterCutoff => {
             let v = value.clamp(20.0, 20000.0);
-            chain.filter.set_cutoff(v);
+            chain.set_param(param, v);
             local.filter_cutoff = v;
         }
         FxParamId::FilterResonance => {
             let v = value.clamp(0.0, 0.95);
-            chain.filter.set_resonance(v);
+            chain.set_param(param, v);
             local.filter_resonance = v;
         }
         FxParamId::DistDrive => {
             let v = value.clamp(0.0, 1.0);
-            chain.distortion.set_drive(v);
+            chain.set_param(param, v);
             local.dist_drive = v;
         }
         FxParamId::DistMix => {
             let v = value.clamp(0.0, 1.0);
-            chain.distortion.set_mix(v);
+            chain.set_param(param, v);
             local.dist_mix = v;
         }
         FxParamId::DelayTime => {
             let v = value.clamp(10.0, 500.0);
-            chain.delay.set_time(v);
+            chain.set_param(param, v);
             local.delay_time = v;
         }
         FxParamId::DelayFeedback => {
             let v = value.clamp(0.0, 0.9);
-            chain.delay.set_feedback(v);
+            chain.set_param(param, v);
             local.delay_feedback = v;
         }
         FxParamId::DelayMix => {
             let v = value.clamp(0.0, 1.0);
-            chain.delay.set_mix(v);
+            chain.set_param(param, v);
             local.delay_mix = v;
         }
+        FxParamId::ModDepth => {
+            let v = value.clamp(0.0, 1.0);
+            chain.set_param(param, v);
+            local.mod_depth = v;
+        }
+        FxParamId::ChainMix => {
+            let v = value.clamp(0.0, 1.0);
+            chain.set_param(param, v);
+            local.chain_mix = v;
+        }
     }
 }
 
 /// Apply a master FX parameter change
-fn apply_master_fx_param(reverb: &mut StereoReverb, local: &mut MasterFxState, param: MasterFxParamId, value: f32) {
+fn apply_master_fx_param(
+    reverb: &mut StereoReverb,
+    mid_side: &mut MidSide,
+    limiter: &mut Limiter,
+    local: &mut MasterFxState,
+    param: MasterFxParamId,
+    value: f32,
+) {
     match param {
         MasterFxParamId::ReverbDecay => {
             let v = value.clamp(0.1, 0.95);
@@ -919,6 +2959,51 @@ fn apply_master_fx_param(reverb: &mut StereoReverb, local: &mut MasterFxState, p
             reverb.set_damping(v);
             local.reverb_damping = v;
         }
+        MasterFxParamId::ReverbWidth => {
+            let v = value.clamp(0.0, 1.0);
+            reverb.set_width(v);
+            local.reverb_width = v;
+        }
+        MasterFxParamId::ReverbGateThreshold => {
+            let v = value.clamp(-60.0, 0.0);
+            reverb.set_gate_threshold(v);
+            local.reverb_gate_threshold = v;
+        }
+        MasterFxParamId::ReverbGateHold => {
+            let v = value.clamp(20.0, 500.0);
+            reverb.set_gate_hold(v);
+            local.reverb_gate_hold = v;
+        }
+        MasterFxParamId::MsMidLevel => {
+            let v = value.clamp(0.0, 2.0);
+            mid_side.set_mid_level(v);
+            local.ms_mid_level = v;
+        }
+        MasterFxParamId::MsSideLevel => {
+            let v = value.clamp(0.0, 2.0);
+            mid_side.set_side_level(v);
+            local.ms_side_level = v;
+        }
+        MasterFxParamId::MsBassMono => {
+            let v = value.clamp(0.0, 500.0);
+            mid_side.set_bass_mono(v);
+            local.ms_bass_mono = v;
+        }
+        MasterFxParamId::MsSideHigh => {
+            let v = value.clamp(0.0, 2.0);
+            mid_side.set_side_high(v);
+            local.ms_side_high = v;
+        }
+        MasterFxParamId::LimiterCeiling => {
+            let v = value.clamp(-12.0, 0.0);
+            limiter.set_ceiling(v);
+            local.limiter_ceiling = v;
+        }
+        MasterFxParamId::LimiterRelease => {
+            let v = value.clamp(10.0, 1000.0);
+            limiter.set_release(v);
+            local.limiter_release = v;
+        }
     }
 }
 
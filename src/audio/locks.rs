@@ -0,0 +1,149 @@
+use serde_json::{json, Value};
+
+use crate::sequencer::{ParamLock, Trigger, MAX_LOCKS_PER_STEP};
+use crate::synth::{SampleOptions, SoundSource};
+
+/// Key length reserved per held lock (param keys are shorter)
+const KEY_CAPACITY: usize = 32;
+
+/// Parameters a track's last trigger locked, with the values they had
+/// before, so the next trigger (or stopping) can put them back. Keys are
+/// copied into buffers reserved up front, so triggers don't allocate.
+pub struct HeldLocks {
+    keys: [String; MAX_LOCKS_PER_STEP],
+    originals: [f32; MAX_LOCKS_PER_STEP],
+    len: usize,
+}
+
+impl Default for HeldLocks {
+    fn default() -> Self {
+        Self {
+            keys: std::array::from_fn(|_| String::with_capacity(KEY_CAPACITY)),
+            originals: [0.0; MAX_LOCKS_PER_STEP],
+            len: 0,
+        }
+    }
+}
+
+impl HeldLocks {
+    /// Restore what the previous trigger locked, then apply `locks`
+    pub fn apply<'a>(&mut self, synth: &mut dyn SoundSource, locks: impl Iterator<Item = &'a ParamLock>) {
+        self.release(synth);
+        for lock in locks {
+            if self.len == MAX_LOCKS_PER_STEP || SampleOptions::KEYS.contains(&lock.key.as_str()) {
+                continue;
+            }
+            let Some(original) = synth.get_param(&lock.key) else {
+                continue;
+            };
+            if synth.set_param(&lock.key, lock.value) {
+                self.keys[self.len].clear();
+                self.keys[self.len].push_str(&lock.key);
+                self.originals[self.len] = original;
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Locked keys with the values they had before
+    fn held(&self) -> impl DoubleEndedIterator<Item = (&str, f32)> {
+        self.keys[..self.len].iter().map(String::as_str).zip(self.originals)
+    }
+
+    /// Play a due step trigger on the track's synth: a note with its
+    /// step's `locks` (held on when steps are tied onto it), or a note-off
+    pub fn play<'a>(&mut self, synth: &mut dyn SoundSource, locks: impl Iterator<Item = &'a ParamLock>, trigger: Trigger) {
+        if trigger.off {
+            synth.release();
+            return;
+        }
+        self.apply(synth, locks);
+        synth.set_articulation(trigger.slide, trigger.accent);
+        synth.trigger_with_note_velocity(trigger.note, trigger.velocity);
+        if trigger.hold {
+            synth.hold();
+        }
+    }
+
+    /// Put back every locked parameter
+    pub fn release(&mut self, synth: &mut dyn SoundSource) {
+        for (key, original) in self.held().rev() {
+            synth.set_param(key, original);
+        }
+        self.len = 0;
+    }
+
+    /// Stop holding `key` without restoring it: it was set while locked,
+    /// and that value is the one to keep
+    pub fn forget(&mut self, key: &str) {
+        if let Some(i) = self.keys[..self.len].iter().position(|k| k == key) {
+            // Keep the rest in order; the freed buffer moves to the end
+            self.keys[i..self.len].rotate_left(1);
+            self.originals[i..self.len].rotate_left(1);
+            self.len -= 1;
+        }
+    }
+
+    /// Drop everything held without restoring (the synth was replaced)
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// The synth's params with locked values replaced by the track's own,
+    /// for the state snapshot that projects are saved from
+    pub fn unlocked_params(&self, synth: &dyn SoundSource) -> Value {
+        let mut params = synth.serialize_params();
+        if let Value::Object(map) = &mut params {
+            for (key, original) in self.held() {
+                if let Some(value) = map.get_mut(key) {
+                    // Integer params (modes, counts) stay integral so they still deserialize
+                    *value = if value.is_i64() || value.is_u64() {
+                        json!(original.round() as i64)
+                    } else {
+                        json!(original)
+                    };
+                }
+            }
+        }
+        params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::Variation;
+    use crate::synth::{create_synth, SynthType};
+
+    fn lock(key: &str, value: f32) -> ParamLock {
+        ParamLock { track: 0, step: 0, variation: Variation::A, key: key.to_string(), value }
+    }
+
+    #[test]
+    fn restores_locked_params_on_the_next_trigger() {
+        let mut synth = create_synth(SynthType::Sampler, 44100.0, None);
+        let (attack, decay) = (synth.get_param("attack").unwrap(), synth.get_param("decay").unwrap());
+        let mut held = HeldLocks::default();
+
+        held.apply(synth.as_mut(), [lock("attack", 20.0), lock("decay", 250.0)].iter());
+        assert_eq!(synth.get_param("attack"), Some(20.0));
+        assert_eq!(synth.get_param("decay"), Some(250.0));
+
+        // A forgotten key keeps its value, the rest go back
+        held.forget("attack");
+        held.apply(synth.as_mut(), std::iter::empty());
+        assert_eq!(synth.get_param("attack"), Some(20.0));
+        assert_eq!(synth.get_param("decay"), Some(decay));
+        assert_ne!(attack, 20.0);
+    }
+
+    #[test]
+    fn skips_sample_load_options() {
+        let mut synth = create_synth(SynthType::Sampler, 44100.0, None);
+        let mut held = HeldLocks::default();
+        held.apply(synth.as_mut(), [lock("normalize", 1.0), lock("trim_silence", 1.0)].iter());
+        assert_eq!(held.len, 0);
+        assert_eq!(SampleOptions::from_params(&synth.serialize_params()), SampleOptions::default());
+        assert!(synth.param_descriptors().iter().any(|d| d.key == "normalize" && !d.lockable()));
+    }
+}
@@ -0,0 +1,102 @@
+/// Level counted as clipping: -0.1 dBFS
+pub const CLIP_LEVEL: f32 = 0.988_553;
+
+/// How long the peak hold stays up before following the signal again
+pub const PEAK_HOLD_SECS: f32 = 2.0;
+
+/// How fast the peak bar falls back after a peak
+const PEAK_DECAY_DB_PER_SEC: f32 = 24.0;
+
+/// Integration time of the RMS level (VU-like)
+const RMS_WINDOW_SECS: f32 = 0.3;
+
+/// Floor for dB readouts
+const FLOOR_DB: f32 = -96.0;
+
+/// Per-sample timing of the meters at the engine's sample rate
+#[derive(Clone, Copy, Debug)]
+pub struct MeterBallistics {
+    /// PEAK_HOLD_SECS in samples
+    hold_samples: u32,
+    /// Gain applied to the peak bar each sample
+    peak_decay: f32,
+    /// One-pole coefficient of the mean square
+    rms_coeff: f32,
+}
+
+impl MeterBallistics {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            hold_samples: (PEAK_HOLD_SECS * sample_rate) as u32,
+            peak_decay: 10f32.powf(-PEAK_DECAY_DB_PER_SEC / 20.0 / sample_rate),
+            rms_coeff: 1.0 - (-1.0 / (RMS_WINDOW_SECS * sample_rate)).exp(),
+        }
+    }
+}
+
+/// Peak and RMS meter with peak hold and a clip counter. Runs on the audio thread and is
+/// copied into the shared state on each sync.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LevelMeter {
+    /// Highest level since the last sync
+    pub peak: f32,
+    /// Peak level with ballistics: jumps up at once, falls at
+    /// PEAK_DECAY_DB_PER_SEC (what meter bars draw)
+    pub level: f32,
+    /// Highest level over the last PEAK_HOLD_SECS
+    pub peak_hold: f32,
+    /// RMS level over about RMS_WINDOW_SECS
+    pub rms: f32,
+    /// Samples over CLIP_LEVEL since the indicators were last reset
+    pub clips: u32,
+    hold_left: u32,
+    /// Running mean square behind `rms`
+    mean_square: f32,
+}
+
+impl LevelMeter {
+    /// Measure one sample
+    pub fn process(&mut self, sample: f32, ballistics: &MeterBallistics) {
+        let level = sample.abs();
+        self.peak = self.peak.max(level);
+        self.level = (self.level * ballistics.peak_decay).max(level);
+        self.mean_square += (level * level - self.mean_square) * ballistics.rms_coeff;
+        if level >= self.peak_hold || self.hold_left == 0 {
+            self.peak_hold = level;
+            self.hold_left = ballistics.hold_samples;
+        } else {
+            self.hold_left -= 1;
+        }
+        if level > CLIP_LEVEL {
+            self.clips = self.clips.saturating_add(1);
+        }
+    }
+
+    /// Copy for the shared state, starting a new peak window
+    pub fn take_sync(&mut self) -> Self {
+        // Settle denormals once the signal has gone
+        if self.mean_square < 1e-12 {
+            self.mean_square = 0.0;
+        }
+        if self.level < 1e-6 {
+            self.level = 0.0;
+        }
+        self.rms = self.mean_square.sqrt();
+        let meter = *self;
+        self.peak = 0.0;
+        meter
+    }
+
+    pub fn reset_clips(&mut self) {
+        self.clips = 0;
+    }
+
+    pub fn clipping(&self) -> bool {
+        self.clips > 0
+    }
+}
+
+/// Linear level in dBFS, floored at -96
+pub fn level_db(level: f32) -> f32 {
+    (20.0 * level.max(1e-9).log10()).max(FLOOR_DB)
+}
@@ -0,0 +1,44 @@
+/// Click length (seconds)
+const CLICK_SECS: f32 = 0.03;
+/// Click pitch on the bar's first beat and on the other beats (Hz)
+const ACCENT_HZ: f32 = 1760.0;
+const BEAT_HZ: f32 = 880.0;
+const CLICK_LEVEL: f32 = 0.3;
+
+/// Short sine click on every beat while playing, higher on the downbeat.
+/// Only the live output hears it, never exports.
+pub struct Metronome {
+    sample_rate: f32,
+    phase: f32,
+    freq: f32,
+    /// Samples left in the current click
+    remaining: usize,
+}
+
+impl Metronome {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            sample_rate,
+            phase: 0.0,
+            freq: BEAT_HZ,
+            remaining: 0,
+        }
+    }
+
+    pub fn trigger(&mut self, accent: bool) {
+        self.freq = if accent { ACCENT_HZ } else { BEAT_HZ };
+        self.phase = 0.0;
+        self.remaining = (CLICK_SECS * self.sample_rate) as usize;
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        if self.remaining == 0 {
+            return 0.0;
+        }
+        let length = CLICK_SECS * self.sample_rate;
+        let env = (self.remaining as f32 / length).powi(2);
+        self.remaining -= 1;
+        self.phase = (self.phase + self.freq / self.sample_rate).fract();
+        (self.phase * std::f32::consts::TAU).sin() * env * CLICK_LEVEL
+    }
+}
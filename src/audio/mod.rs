@@ -1,3 +1,17 @@
+pub mod analyzer;
+pub mod capture;
+pub mod deck;
 pub mod engine;
+pub mod locks;
+pub mod meter;
+pub mod metronome;
 
-pub use engine::{AudioEngine, SequencerState, TrackState};
+pub use analyzer::{spectrum_bands, AnalyzerTap, ANALYZER_FLOOR_DB};
+pub use capture::{capture_buffer, CaptureStatus, MAX_CAPTURE_STEPS};
+pub use deck::CROSSFADER_STEP;
+pub use engine::{
+    is_audio_thread, output_device_names, track_audible, AudioEngine, SequencerState, TrackState,
+    DEFAULT_PREVIEW_DUCK_DB, DEFAULT_SAMPLE_BUDGET_MB, DEFAULT_SAMPLE_RATE, MAX_GLYPH_CHARS, MAX_PREVIEW_DUCK_DB,
+};
+pub use locks::HeldLocks;
+pub use meter::{level_db, LevelMeter, CLIP_LEVEL};
@@ -1,23 +1,79 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+use parking_lot::Mutex;
+use tracing::{debug, warn};
 
 use super::types::{Command, CommandSource};
 
+/// Capacity of the command queue
+pub const QUEUE_CAPACITY: usize = 256;
+
+/// How many dropped commands are remembered for introspection
+const DROPPED_HISTORY: usize = 32;
+
+/// A command that was dropped because the queue was full
+#[derive(Debug, Clone)]
+pub struct DroppedCommand {
+    pub timestamp: u64,
+    pub source: CommandSource,
+    pub name: &'static str,
+    pub description: String,
+}
+
+/// Dispatch statistics collected by every CommandSender of a bus
+#[derive(Debug, Clone, Default)]
+pub struct CommandStats {
+    /// Commands accepted onto the queue, per (command name, source)
+    pub dispatched: HashMap<(&'static str, CommandSource), u64>,
+    pub dropped_total: u64,
+    /// Most recent drops, oldest first
+    pub recent_dropped: VecDeque<DroppedCommand>,
+    /// Deepest the queue has been right after a send
+    pub max_queue_depth: usize,
+    /// Commands taken off the queue by receivers
+    pub processed: u64,
+    /// Commands waiting in the queue now
+    pub queue_depth: usize,
+}
+
+/// Counters shared between a bus, its senders and its receivers
+#[derive(Default)]
+struct SharedStats {
+    stats: Mutex<CommandStats>,
+    // Updated from the audio thread, so kept out of the mutex
+    processed: AtomicU64,
+    /// Commands queued so far (counted under the stats lock, so in queue
+    /// order) and the receiver's count once it has finished applying them
+    queued: AtomicU64,
+    applied: AtomicU64,
+}
+
 /// Central command bus for dispatching commands from TUI or MCP
 pub struct CommandBus {
     tx: Sender<(Command, CommandSource)>,
     rx: Receiver<(Command, CommandSource)>,
+    stats: Arc<SharedStats>,
 }
 
 impl CommandBus {
     pub fn new() -> Self {
-        let (tx, rx) = bounded(256);
-        Self { tx, rx }
+        let (tx, rx) = bounded(QUEUE_CAPACITY);
+        Self {
+            tx,
+            rx,
+            stats: Arc::new(SharedStats::default()),
+        }
     }
 
     /// Get a sender that can be cloned and shared
     pub fn sender(&self) -> CommandSender {
         CommandSender {
             tx: self.tx.clone(),
+            stats: self.stats.clone(),
         }
     }
 
@@ -25,12 +81,17 @@ impl CommandBus {
     pub fn receiver(&self) -> CommandReceiver {
         CommandReceiver {
             rx: self.rx.clone(),
+            stats: self.stats.clone(),
         }
     }
 
     /// Try to receive a command (non-blocking)
     pub fn try_recv(&self) -> Option<(Command, CommandSource)> {
-        self.rx.try_recv().ok()
+        let received = self.rx.try_recv().ok();
+        if received.is_some() {
+            self.stats.processed.fetch_add(1, Ordering::Relaxed);
+        }
+        received
     }
 }
 
@@ -44,31 +105,101 @@ impl Default for CommandBus {
 #[derive(Clone)]
 pub struct CommandSender {
     tx: Sender<(Command, CommandSource)>,
+    stats: Arc<SharedStats>,
 }
 
 impl CommandSender {
     /// Send a command (non-blocking, drops if buffer full)
     pub fn send(&self, cmd: Command, source: CommandSource) -> bool {
+        let name = cmd.name();
+        let mut stats = self.stats.stats.lock();
         match self.tx.try_send((cmd, source)) {
-            Ok(()) => true,
-            Err(TrySendError::Full(_)) => {
-                eprintln!("Warning: Command buffer full, dropping command");
+            Ok(()) => {
+                self.stats.queued.fetch_add(1, Ordering::Relaxed);
+                let depth = self.tx.len();
+                debug!(command = name, ?source, depth, "Command sent");
+                *stats.dispatched.entry((name, source)).or_insert(0) += 1;
+                stats.max_queue_depth = stats.max_queue_depth.max(depth);
+                true
+            }
+            Err(TrySendError::Full((cmd, _))) => {
+                warn!(command = name, ?source, "Command buffer full, dropping command");
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0);
+                stats.dropped_total += 1;
+                stats.recent_dropped.push_back(DroppedCommand {
+                    timestamp,
+                    source,
+                    name,
+                    description: cmd.description(),
+                });
+                while stats.recent_dropped.len() > DROPPED_HISTORY {
+                    stats.recent_dropped.pop_front();
+                }
                 false
             }
             Err(TrySendError::Disconnected(_)) => false,
         }
     }
+
+    /// Whether the receiver has applied every command queued so far
+    pub fn all_applied(&self) -> bool {
+        let queued = self.stats.queued.load(Ordering::Relaxed);
+        self.stats.applied.load(Ordering::Acquire) >= queued
+    }
+
+    /// Snapshot of dispatch statistics for this sender's bus
+    pub fn stats(&self) -> CommandStats {
+        let mut stats = self.stats.stats.lock().clone();
+        stats.processed = self.stats.processed.load(Ordering::Relaxed);
+        stats.queue_depth = self.tx.len();
+        stats
+    }
 }
 
 /// Receiver for consuming commands
 #[derive(Clone)]
 pub struct CommandReceiver {
     rx: Receiver<(Command, CommandSource)>,
+    stats: Arc<SharedStats>,
 }
 
 impl CommandReceiver {
     /// Try to receive a command (non-blocking)
     pub fn try_recv(&self) -> Option<(Command, CommandSource)> {
-        self.rx.try_recv().ok()
+        let received = self.rx.try_recv().ok();
+        if received.is_some() {
+            self.stats.processed.fetch_add(1, Ordering::Relaxed);
+        }
+        received
+    }
+
+    /// Record that every command received so far has been applied
+    pub fn mark_applied(&self) {
+        let processed = self.stats.processed.load(Ordering::Relaxed);
+        self.stats.applied.store(processed, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_count_as_applied_once_the_receiver_marks_them() {
+        let bus = CommandBus::new();
+        let (sender, receiver) = (bus.sender(), bus.receiver());
+        assert!(sender.all_applied());
+        sender.send(Command::Play, CommandSource::Tui);
+        sender.send(Command::Stop, CommandSource::Mcp);
+        assert!(receiver.try_recv().is_some());
+        receiver.mark_applied();
+        assert!(!sender.all_applied());
+        assert!(receiver.try_recv().is_some());
+        assert!(!sender.all_applied());
+        receiver.mark_applied();
+        assert!(sender.all_applied());
     }
 }
@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::types::Command;
+
+/// Longest macro accepted (keeps a replay within the command queue)
+pub const MAX_MACRO_COMMANDS: usize = 200;
+
+/// Path of the macro file: ~/.gridoxide/macros.json
+pub fn macros_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".gridoxide").join("macros.json")
+}
+
+/// Named command sequences recorded in the TUI, shared with MCP
+#[derive(Default, Serialize, Deserialize)]
+pub struct MacroStore {
+    pub macros: BTreeMap<String, Vec<Command>>,
+}
+
+impl MacroStore {
+    /// Load the macro file (empty store if it doesn't exist yet)
+    pub fn load() -> Result<Self> {
+        let path = macros_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = macros_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize macros")?;
+        std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// First unused name of the form "macro-N"
+    pub fn next_name(&self) -> String {
+        (1..)
+            .map(|n| format!("macro-{}", n))
+            .find(|name| !self.macros.contains_key(name))
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Vec<Command>> {
+        self.macros.get(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.macros.keys().cloned().collect()
+    }
+}
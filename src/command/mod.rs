@@ -1,5 +1,7 @@
 pub mod bus;
+pub mod macros;
 pub mod types;
 
-pub use bus::{CommandBus, CommandReceiver, CommandSender};
+pub use bus::{CommandBus, CommandReceiver, CommandSender, QUEUE_CAPACITY};
+pub use macros::{MacroStore, MAX_MACRO_COMMANDS};
 pub use types::{Command, CommandSource};
@@ -1,11 +1,13 @@
 use serde::{Deserialize, Serialize};
 
 use crate::audio::SequencerState;
-use crate::fx::{FilterType, FxParamId, FxType, MasterFxParamId};
-use crate::sequencer::{PlaybackMode, Variation};
-use crate::synth::SynthType;
+use crate::fx::{FilterType, FxParamId, FxType, MasterFxParamId, ModTarget, ReverbMode};
+use crate::midi::MidiSync;
+use crate::script::Script;
+use crate::sequencer::{chord_label, pattern_label, Pattern, PlaybackMode, TrackAutomation, Variation};
+use crate::synth::{ExternalConnection, Interpolation, ParkedSynths, SampleAudio, SampleData, SampleInfo, SampleOptions, SynthType, NOTE_NAMES};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CommandSource {
     Tui,
     Mcp,
@@ -18,19 +20,60 @@ pub enum Command {
     Pause,
     Stop,
     SetBpm(f32),
+    /// Clock source: internal, slave to MIDI clock in, or master sending MIDI clock
+    SetMidiSync(MidiSync),
+    /// Delay the off-beat 16ths by a percent of half a step (0-MAX_SWING)
+    SetSwing(u8),
 
     // Pattern
     ToggleStep { track: usize, step: usize },
     ClearTrack(usize),
     FillTrack(usize),
+    /// Slice a sampler track into STEPS slices and place slice i on step i
+    ChopToSteps(usize),
+    /// Pull a track's micro-timings toward the grid by `strength` percent
+    QuantizeTrack { track: usize, strength: u8 },
+    /// Play a track's sound now; `record` also writes the hit into the
+    /// playing pattern at its position (loop-record)
+    PadHit { track: usize, velocity: u8, record: bool },
+    /// Play a note on a track now (MIDI note input)
+    PlayNote { track: usize, note: u8, velocity: u8 },
+    /// Play the hits of one step column once, with their micro-timing
+    /// and nudges (scrubbing, while stopped)
+    ScrubStep(usize),
+    /// Start the utility tone generator (not on any track or pattern)
+    PlayTone,
+    /// Fade the utility tone generator out
+    StopTone,
+    /// Set a utility tone parameter (mode, freq, level, rate, length)
+    SetToneParam { key: String, value: f32 },
+    /// Play a note on a track and write it into the playing pattern on
+    /// the nearest step (MIDI note input while recording)
+    RecordNote { track: usize, note: u8, velocity: u8 },
 
     // Per-step note, velocity, probability
     SetStepNote { track: usize, step: usize, note: u8 },
     SetStepVelocity { track: usize, step: usize, velocity: u8 },
     SetStepProbability { track: usize, step: usize, probability: u8 },
+    /// Tie a step onto the one before it (hold that note on instead of retriggering)
+    SetStepTie { track: usize, step: usize, tie: bool },
+    /// Make a step a note-off step (turning it off), or clear that
+    SetStepOff { track: usize, step: usize, off: bool },
+    /// Glide into a step's note from the sounding one (acid synth)
+    SetStepSlide { track: usize, step: usize, slide: bool },
+    /// Accent a step (acid synth)
+    SetStepAccent { track: usize, step: usize, accent: bool },
+    /// Lock a synth param on a step of the current pattern and variation
+    /// (None removes the lock)
+    SetParamLock { track: usize, step: usize, key: String, value: Option<f32> },
+    /// Remove all of a step's parameter locks
+    ClearParamLocks { track: usize, step: usize },
 
     // Dynamic track parameter (replaces old SetKickParams/SetSnareParams/etc.)
     SetTrackParam { track: usize, key: String, value: f32 },
+    /// Velocity range of a sampler's sample layer
+    SetSampleLayerRange { track: usize, layer: usize, min_velocity: u8, max_velocity: u8 },
+    RemoveSampleLayer { track: usize, layer: usize },
 
     // Dynamic track management
     AddTrack { synth_type: SynthType, name: String },
@@ -39,22 +82,80 @@ pub enum Command {
     // Mixer
     SetTrackVolume { track: usize, volume: f32 },
     SetTrackPan { track: usize, pan: f32 },
+    SetTrackNudge { track: usize, ms: f32 },
+    /// Switch a track between the 16-step grid and 12 triplet steps
+    SetTrackTriplet { track: usize, enabled: bool },
+    /// Per-track transpose in semitones (±MAX_TRACK_TRANSPOSE)
+    SetTrackTranspose { track: usize, semitones: i8 },
+    /// 1-2 char marker shown before the track name (None: from the synth type)
+    SetTrackGlyph { track: usize, glyph: Option<String> },
+
+    // Sample memory budget (MB)
+    SetSampleBudget(f32),
+    /// Stream samples larger than this many MB from disk (0 = never);
+    /// applies to samples loaded afterwards
+    SetSampleStreaming(f32),
+    /// Level (dB) the mix ducks to while a sample preview plays
+    SetPreviewDuck(f32),
+
+    // Sampler interpolation for offline rendering
+    SetRenderInterpolation(Interpolation),
+    /// Whether tone tracks sound in offline renders
+    SetExportTone(bool),
     ToggleMute(usize),
     ToggleSolo(usize),
+    /// Keep a track in the mix while other tracks are soloed
+    ToggleSoloSafe(usize),
+    /// Pre-listen a track on the cue bus
+    ToggleCue(usize),
 
     // Per-track FX
     SetFxParam { track: usize, param: FxParamId, value: f32 },
     SetFxFilterType { track: usize, filter_type: FilterType },
+    /// Track whose signal modulates `track` (None = off)
+    SetFxModSource { track: usize, source: Option<usize> },
+    SetFxModTarget { track: usize, target: ModTarget },
     ToggleFxEnabled { track: usize, fx: FxType },
+    /// Loudness-match filter/distortion so toggling them compares fairly
+    SetGainMatch(bool),
+    /// Headroom monitoring: bypass the master soft clipper
+    SetHeadroom(bool),
+    /// Click on every beat while playing (live output only)
+    SetMetronome(bool),
 
     // Master FX
     SetMasterFxParam { param: MasterFxParamId, value: f32 },
     ToggleMasterFxEnabled,
+    SetLimiterEnabled(bool),
+    /// Master reverb tail: natural or gated by the dry signal
+    SetReverbMode(ReverbMode),
+    /// DJ sweep position: -1 low-pass .. 0 neutral .. 1 high-pass
+    SetDjFilter(f32),
+    /// Glide the DJ sweep back to neutral over one beat
+    ReleaseDjFilter,
+    /// Global transpose of melodic tracks in semitones (±MAX_TRANSPOSE)
+    SetTranspose(i8),
+    /// Crossfader mode: play this pattern slot on deck B alongside the
+    /// current pattern (deck A). None leaves the mode, keeping deck B's
+    /// slot if the crossfader is past halfway.
+    SetDeckB(Option<usize>),
+    /// Crossfader position: 0 deck A .. 1 deck B
+    SetCrossfader(f32),
+    /// Clear the clip counters of every track and the master
+    ResetClipIndicators,
 
     // Pattern Bank
     SelectPattern(usize),
     CopyPattern { src: usize, dst: usize },
+    /// Copy only some tracks; `merge` keeps the destination's other steps
+    CopyPatternTracks { src: usize, dst: usize, tracks: Vec<usize>, merge: bool },
     ClearPattern(usize),
+    /// Set a pattern's number of steps (1..=MAX_STEPS)
+    SetPatternLength { pattern: usize, length: usize },
+    /// Set a track's own loop length in a pattern (0 = loop with the pattern)
+    SetTrackLength { pattern: usize, track: usize, length: usize },
+    /// Overwrite a bank slot (pattern import)
+    ReplacePattern { index: usize, pattern: Box<Pattern> },
 
     // Playback Mode
     SetPlaybackMode(PlaybackMode),
@@ -64,22 +165,61 @@ pub enum Command {
     InsertArrangement { position: usize, pattern: usize, repeats: usize },
     RemoveArrangement(usize),
     SetArrangementEntry { position: usize, pattern: usize, repeats: usize },
+    /// Replace the mixer automation applied when an entry starts
+    SetEntryAutomation { position: usize, automation: Vec<TrackAutomation> },
+    /// Chord degree for an entry (index into CHORD_DEGREES, None = off)
+    SetEntryChord { position: usize, chord: Option<u8> },
     ClearArrangement,
+    /// Bars to fade the master out over at the end of the song (0 = off)
+    SetFadeOutBars(usize),
 
     // Pattern Variations
     SetVariation(Variation),
     ToggleVariation,
     CopyVariation { from: Variation, to: Variation },
 
+    /// Project key as a pitch class (C = 0); None clears it
+    SetProjectKey(Option<u8>),
+
+    // Scripting
+    /// Add a script, or replace the one with the same name
+    SetScript(Script),
+    RemoveScript(String),
+    SetScriptEnabled { name: String, enabled: bool },
+
     // Project I/O
+    /// Replace the engine's project. The synths are built from the track
+    /// data unless the parked ones fit its tracks; the replaced synths come
+    /// back on `AudioEngine::replaced_synths`.
     #[serde(skip)]
-    LoadProject(Box<SequencerState>),
+    LoadProject(Box<SequencerState>, ParkedSynths),
 
     // Sample loading
+    /// Record `steps` steps of the hardware input into a track (as a
+    /// sampler), from the next step or at once when stopped, into `buffer`
+    /// (see `capture_buffer`); the take comes back on the capture queue
+    RecordSample {
+        track: usize,
+        steps: usize,
+        #[serde(skip)]
+        buffer: Vec<f32>,
+    },
+    /// Drop an armed or running sample capture
+    CancelSampleCapture,
+    /// Load a sample into a track (converts it to a sampler). `buffer` has
+    /// `options` already applied and `info` detected; the sampler only
+    /// records them. Large samples may stream (see SampleAudio::new).
+    #[serde(skip)]
+    LoadSample { track: usize, buffer: SampleAudio, path: String, options: SampleOptions, info: SampleInfo },
+    /// Load a velocity layer into a sampler (`layer` past the end adds one),
+    /// processed with the track's normalize/trim options
     #[serde(skip)]
-    LoadSample { track: usize, buffer: Vec<f32>, path: String },
+    LoadSampleLayer { track: usize, layer: usize, buffer: SampleData, path: String },
     #[serde(skip)]
     PreviewSample(Vec<f32>),
+    /// Attach a spawned plugin process to a track (converts it to External)
+    #[serde(skip)]
+    LoadExternalSynth { track: usize, connection: ExternalConnection },
 }
 
 impl Command {
@@ -87,10 +227,144 @@ impl Command {
     pub fn is_loggable(&self) -> bool {
         !matches!(
             self,
-            Command::LoadProject(_) | Command::LoadSample { .. } | Command::PreviewSample(_)
+            Command::LoadProject(..)
+                | Command::LoadSample { .. }
+                | Command::LoadSampleLayer { .. }
+                | Command::PreviewSample(_)
+                | Command::LoadExternalSynth { .. }
+                | Command::RecordSample { .. }
         )
     }
 
+    /// Whether the command changes what a project save would write
+    /// (transport and performance controls don't)
+    pub fn modifies_project(&self) -> bool {
+        self.is_loggable()
+            && !matches!(
+                self,
+                Command::Play
+                    | Command::Pause
+                    | Command::Stop
+                    | Command::SetMidiSync(_)
+                    | Command::SetDjFilter(_)
+                    | Command::ReleaseDjFilter
+                    | Command::SetHeadroom(_)
+                    | Command::SetMetronome(_)
+                    | Command::SetDeckB(_)
+                    | Command::SetCrossfader(_)
+                    | Command::ResetClipIndicators
+                    | Command::ToggleCue(_)
+                    | Command::PadHit { record: false, .. }
+                    | Command::PlayNote { .. }
+                    | Command::ScrubStep(_)
+                    | Command::CancelSampleCapture
+                    | Command::PlayTone
+                    | Command::StopTone
+                    | Command::SetToneParam { .. }
+            )
+    }
+
+    /// Variant name, used as the key for command statistics
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Play => "Play",
+            Command::Pause => "Pause",
+            Command::Stop => "Stop",
+            Command::SetBpm(_) => "SetBpm",
+            Command::SetMidiSync(_) => "SetMidiSync",
+            Command::SetSwing(_) => "SetSwing",
+            Command::ToggleStep { .. } => "ToggleStep",
+            Command::ClearTrack(_) => "ClearTrack",
+            Command::FillTrack(_) => "FillTrack",
+            Command::ChopToSteps(_) => "ChopToSteps",
+            Command::QuantizeTrack { .. } => "QuantizeTrack",
+            Command::PadHit { .. } => "PadHit",
+            Command::PlayNote { .. } => "PlayNote",
+            Command::ScrubStep(_) => "ScrubStep",
+            Command::RecordSample { .. } => "RecordSample",
+            Command::CancelSampleCapture => "CancelSampleCapture",
+            Command::PlayTone => "PlayTone",
+            Command::StopTone => "StopTone",
+            Command::SetToneParam { .. } => "SetToneParam",
+            Command::RecordNote { .. } => "RecordNote",
+            Command::SetStepNote { .. } => "SetStepNote",
+            Command::SetStepVelocity { .. } => "SetStepVelocity",
+            Command::SetStepProbability { .. } => "SetStepProbability",
+            Command::SetStepTie { .. } => "SetStepTie",
+            Command::SetStepOff { .. } => "SetStepOff",
+            Command::SetStepSlide { .. } => "SetStepSlide",
+            Command::SetStepAccent { .. } => "SetStepAccent",
+            Command::SetParamLock { .. } => "SetParamLock",
+            Command::ClearParamLocks { .. } => "ClearParamLocks",
+            Command::SetTrackParam { .. } => "SetTrackParam",
+            Command::SetSampleLayerRange { .. } => "SetSampleLayerRange",
+            Command::RemoveSampleLayer { .. } => "RemoveSampleLayer",
+            Command::AddTrack { .. } => "AddTrack",
+            Command::RemoveTrack(_) => "RemoveTrack",
+            Command::SetTrackVolume { .. } => "SetTrackVolume",
+            Command::SetTrackPan { .. } => "SetTrackPan",
+            Command::SetTrackNudge { .. } => "SetTrackNudge",
+            Command::SetTrackTriplet { .. } => "SetTrackTriplet",
+            Command::SetTrackTranspose { .. } => "SetTrackTranspose",
+            Command::SetTrackGlyph { .. } => "SetTrackGlyph",
+            Command::SetSampleBudget(_) => "SetSampleBudget",
+            Command::SetSampleStreaming(_) => "SetSampleStreaming",
+            Command::SetPreviewDuck(_) => "SetPreviewDuck",
+            Command::SetRenderInterpolation(_) => "SetRenderInterpolation",
+            Command::SetExportTone(_) => "SetExportTone",
+            Command::ToggleMute(_) => "ToggleMute",
+            Command::ToggleSolo(_) => "ToggleSolo",
+            Command::ToggleSoloSafe(_) => "ToggleSoloSafe",
+            Command::ToggleCue(_) => "ToggleCue",
+            Command::SetFxParam { .. } => "SetFxParam",
+            Command::SetFxFilterType { .. } => "SetFxFilterType",
+            Command::SetFxModSource { .. } => "SetFxModSource",
+            Command::SetFxModTarget { .. } => "SetFxModTarget",
+            Command::ToggleFxEnabled { .. } => "ToggleFxEnabled",
+            Command::SetMasterFxParam { .. } => "SetMasterFxParam",
+            Command::ToggleMasterFxEnabled => "ToggleMasterFxEnabled",
+            Command::SetLimiterEnabled(_) => "SetLimiterEnabled",
+            Command::SetReverbMode(_) => "SetReverbMode",
+            Command::SetGainMatch(_) => "SetGainMatch",
+            Command::SetHeadroom(_) => "SetHeadroom",
+            Command::SetMetronome(_) => "SetMetronome",
+            Command::SetDjFilter(_) => "SetDjFilter",
+            Command::ResetClipIndicators => "ResetClipIndicators",
+            Command::ReleaseDjFilter => "ReleaseDjFilter",
+            Command::SetTranspose(_) => "SetTranspose",
+            Command::SetDeckB(_) => "SetDeckB",
+            Command::SetCrossfader(_) => "SetCrossfader",
+            Command::SelectPattern(_) => "SelectPattern",
+            Command::CopyPattern { .. } => "CopyPattern",
+            Command::CopyPatternTracks { .. } => "CopyPatternTracks",
+            Command::ClearPattern(_) => "ClearPattern",
+            Command::SetPatternLength { .. } => "SetPatternLength",
+            Command::SetTrackLength { .. } => "SetTrackLength",
+            Command::ReplacePattern { .. } => "ReplacePattern",
+            Command::SetPlaybackMode(_) => "SetPlaybackMode",
+            Command::AppendArrangement { .. } => "AppendArrangement",
+            Command::InsertArrangement { .. } => "InsertArrangement",
+            Command::RemoveArrangement(_) => "RemoveArrangement",
+            Command::SetArrangementEntry { .. } => "SetArrangementEntry",
+            Command::SetEntryAutomation { .. } => "SetEntryAutomation",
+            Command::SetEntryChord { .. } => "SetEntryChord",
+            Command::ClearArrangement => "ClearArrangement",
+            Command::SetFadeOutBars(_) => "SetFadeOutBars",
+            Command::SetVariation(_) => "SetVariation",
+            Command::ToggleVariation => "ToggleVariation",
+            Command::CopyVariation { .. } => "CopyVariation",
+            Command::SetProjectKey(_) => "SetProjectKey",
+            Command::SetScript(_) => "SetScript",
+            Command::RemoveScript(_) => "RemoveScript",
+            Command::SetScriptEnabled { .. } => "SetScriptEnabled",
+            Command::LoadProject(..) => "LoadProject",
+            Command::LoadSample { .. } => "LoadSample",
+            Command::LoadSampleLayer { .. } => "LoadSampleLayer",
+            Command::PreviewSample(_) => "PreviewSample",
+            Command::LoadExternalSynth { .. } => "LoadExternalSynth",
+        }
+    }
+
     /// Human-readable description of the command
     pub fn description(&self) -> String {
         match self {
@@ -98,11 +372,37 @@ impl Command {
             Command::Pause => "Pause".to_string(),
             Command::Stop => "Stop".to_string(),
             Command::SetBpm(bpm) => format!("Set BPM to {}", bpm),
+            Command::SetMidiSync(mode) => format!("Set MIDI sync to {}", mode.name()),
+            Command::SetSwing(amount) => format!("Set swing to {}%", amount),
             Command::ToggleStep { track, step } => {
                 format!("Toggle track {} step {}", track, step)
             }
             Command::ClearTrack(track) => format!("Clear track {}", track),
             Command::FillTrack(track) => format!("Fill track {}", track),
+            Command::ChopToSteps(track) => format!("Chop track {} sample to steps", track),
+            Command::QuantizeTrack { track, strength } => {
+                format!("Quantize track {} by {}%", track, strength)
+            }
+            Command::PadHit { track, velocity, record } => format!(
+                "{} track {} (velocity {})",
+                if *record { "Record hit on" } else { "Hit" },
+                track,
+                velocity
+            ),
+            Command::PlayNote { track, note, velocity } => {
+                format!("Play note {} on track {} (velocity {})", note, track, velocity)
+            }
+            Command::ScrubStep(step) => format!("Scrub to step {}", step),
+            Command::RecordSample { track, steps, .. } => {
+                format!("Record {} steps of input into track {}", steps, track)
+            }
+            Command::CancelSampleCapture => "Cancel sample capture".to_string(),
+            Command::RecordNote { track, note, velocity } => {
+                format!("Record note {} on track {} (velocity {})", note, track, velocity)
+            }
+            Command::PlayTone => "Play test tone".to_string(),
+            Command::StopTone => "Stop test tone".to_string(),
+            Command::SetToneParam { key, value } => format!("Set tone {} to {:.2}", key, value),
             Command::SetStepNote { track, step, note } => {
                 format!("Set track {} step {} note to {}", track, step, note)
             }
@@ -112,9 +412,41 @@ impl Command {
             Command::SetStepProbability { track, step, probability } => {
                 format!("Set track {} step {} probability to {}%", track, step, probability)
             }
+            Command::SetStepTie { track, step, tie } => {
+                let action = if *tie { "Tie" } else { "Untie" };
+                format!("{} track {} step {}", action, track, step)
+            }
+            Command::SetStepOff { track, step, off } => {
+                let action = if *off { "Set note-off on" } else { "Clear note-off on" };
+                format!("{} track {} step {}", action, track, step)
+            }
+            Command::SetStepSlide { track, step, slide } => {
+                let action = if *slide { "Slide into" } else { "No slide into" };
+                format!("{} track {} step {}", action, track, step)
+            }
+            Command::SetStepAccent { track, step, accent } => {
+                let action = if *accent { "Accent" } else { "Unaccent" };
+                format!("{} track {} step {}", action, track, step)
+            }
+            Command::SetParamLock { track, step, key, value } => match value {
+                Some(value) => format!("Lock track {} step {} {} to {:.2}", track, step, key, value),
+                None => format!("Unlock track {} step {} {}", track, step, key),
+            },
+            Command::ClearParamLocks { track, step } => {
+                format!("Clear track {} step {} parameter locks", track, step)
+            }
             Command::SetTrackParam { track, key, value } => {
                 format!("Set track {} param {} to {:.2}", track, key, value)
             }
+            Command::SetSampleLayerRange { track, layer, min_velocity, max_velocity } => {
+                format!(
+                    "Set track {} sample layer {} to velocity {}-{}",
+                    track, layer, min_velocity, max_velocity
+                )
+            }
+            Command::RemoveSampleLayer { track, layer } => {
+                format!("Remove sample layer {} from track {}", layer, track)
+            }
             Command::AddTrack { synth_type, name } => {
                 format!("Add {} track '{}'", synth_type.name(), name)
             }
@@ -125,14 +457,46 @@ impl Command {
             Command::SetTrackPan { track, pan } => {
                 format!("Set track {} pan to {:.2}", track, pan)
             }
+            Command::SetTrackNudge { track, ms } => {
+                format!("Set track {} nudge to {:.1}ms", track, ms)
+            }
+            Command::SetTrackTranspose { track, semitones } => {
+                format!("Set track {} transpose to {:+}", track, semitones)
+            }
+            Command::SetTrackGlyph { track, glyph } => match glyph {
+                Some(glyph) => format!("Set track {} glyph to {}", track, glyph),
+                None => format!("Reset track {} glyph", track),
+            },
+            Command::SetTrackTriplet { track, enabled } => {
+                format!("Set track {} triplet mode {}", track, if *enabled { "on" } else { "off" })
+            }
+            Command::SetRenderInterpolation(interpolation) => {
+                format!("Set render interpolation to {}", interpolation.name())
+            }
+            Command::SetExportTone(enabled) => {
+                format!("{} tone tracks in exports", if *enabled { "Include" } else { "Exclude" })
+            }
+            Command::SetSampleBudget(mb) => format!("Set sample memory budget to {:.0} MB", mb),
+            Command::SetSampleStreaming(mb) if *mb > 0.0 => format!("Stream samples over {:.0} MB from disk", mb),
+            Command::SetSampleStreaming(_) => "Keep all samples in memory".to_string(),
+            Command::SetPreviewDuck(db) => format!("Set preview duck to {:.0} dB", db),
             Command::ToggleMute(track) => format!("Toggle mute track {}", track),
             Command::ToggleSolo(track) => format!("Toggle solo track {}", track),
+            Command::ToggleSoloSafe(track) => format!("Toggle solo safe track {}", track),
+            Command::ToggleCue(track) => format!("Toggle cue track {}", track),
             Command::SetFxParam { track, param, value } => {
                 format!("Set track {} FX {} to {:.2}", track, param.name(), value)
             }
             Command::SetFxFilterType { track, filter_type } => {
                 format!("Set track {} filter type to {}", track, filter_type.name())
             }
+            Command::SetFxModSource { track, source } => match source {
+                Some(source) => format!("Modulate track {} by track {}", track, source),
+                None => format!("Clear track {} modulation", track),
+            },
+            Command::SetFxModTarget { track, target } => {
+                format!("Set track {} modulation target to {}", track, target.name())
+            }
             Command::ToggleFxEnabled { track, fx } => {
                 format!("Toggle {} on track {}", fx.name(), track)
             }
@@ -140,11 +504,50 @@ impl Command {
                 format!("Set master {} to {:.2}", param.name(), value)
             }
             Command::ToggleMasterFxEnabled => "Toggle master reverb".to_string(),
+            Command::SetLimiterEnabled(enabled) => {
+                format!("Master limiter {}", if *enabled { "on" } else { "off" })
+            }
+            Command::SetReverbMode(mode) => format!("Set reverb mode to {}", mode.name()),
+            Command::SetGainMatch(enabled) => {
+                format!("Gain match {}", if *enabled { "on" } else { "off" })
+            }
+            Command::SetHeadroom(enabled) => {
+                format!("Headroom monitoring {}", if *enabled { "on" } else { "off" })
+            }
+            Command::SetMetronome(enabled) => {
+                format!("Metronome {}", if *enabled { "on" } else { "off" })
+            }
+            Command::SetDjFilter(position) => format!("Set DJ sweep to {:+.2}", position),
+            Command::ResetClipIndicators => "Reset clip indicators".to_string(),
+            Command::ReleaseDjFilter => "Release DJ sweep".to_string(),
+            Command::SetTranspose(semitones) => format!("Set transpose to {:+}", semitones),
+            Command::SetDeckB(Some(slot)) => format!("Play pattern {} on deck B", pattern_label(*slot)),
+            Command::SetDeckB(None) => "Leave crossfader mode".to_string(),
+            Command::SetCrossfader(position) => format!("Set crossfader to {:.2}", position),
             Command::SelectPattern(p) => format!("Select pattern {:02}", p),
             Command::CopyPattern { src, dst } => {
                 format!("Copy pattern {:02} to {:02}", src, dst)
             }
+            Command::CopyPatternTracks { src, dst, tracks, merge } => {
+                format!(
+                    "{} {} track(s) from pattern {:02} into {:02}",
+                    if *merge { "Merge" } else { "Copy" },
+                    tracks.len(),
+                    src,
+                    dst
+                )
+            }
             Command::ClearPattern(p) => format!("Clear pattern {:02}", p),
+            Command::SetPatternLength { pattern, length } => {
+                format!("Set pattern {:02} to {} steps", pattern, length)
+            }
+            Command::SetTrackLength { pattern, track, length: 0 } => {
+                format!("Loop track {} with pattern {:02}", track + 1, pattern)
+            }
+            Command::SetTrackLength { pattern, track, length } => {
+                format!("Loop track {} every {} steps in pattern {:02}", track + 1, length, pattern)
+            }
+            Command::ReplacePattern { index, .. } => format!("Import into pattern {:02}", index),
             Command::SetPlaybackMode(mode) => {
                 let name = match mode {
                     PlaybackMode::Pattern => "Pattern",
@@ -178,7 +581,21 @@ impl Command {
                     position, pattern, repeats
                 )
             }
+            Command::SetEntryChord { position, chord } => match chord {
+                Some(_) => format!("Set arrangement entry {} chord to {}", position, chord_label(*chord)),
+                None => format!("Clear arrangement entry {} chord", position),
+            },
+            Command::SetEntryAutomation { position, automation } => {
+                if automation.is_empty() {
+                    format!("Clear arrangement entry {} automation", position)
+                } else {
+                    let items: Vec<String> = automation.iter().map(|a| a.summary()).collect();
+                    format!("Set arrangement entry {} automation {}", position, items.join(" "))
+                }
+            }
             Command::ClearArrangement => "Clear arrangement".to_string(),
+            Command::SetFadeOutBars(0) => "Disable song fade-out".to_string(),
+            Command::SetFadeOutBars(bars) => format!("Set song fade-out to {} bars", bars),
             Command::SetVariation(v) => {
                 let name = match v {
                     Variation::A => "A",
@@ -198,11 +615,26 @@ impl Command {
                 };
                 format!("Copy variation {} to {}", from_name, to_name)
             }
-            Command::LoadProject(_) => "Load project".to_string(),
+            Command::SetProjectKey(Some(key)) => {
+                format!("Set project key to {}", NOTE_NAMES[*key as usize % 12])
+            }
+            Command::SetProjectKey(None) => "Clear project key".to_string(),
+            Command::SetScript(script) => format!("Set script '{}'", script.name),
+            Command::RemoveScript(name) => format!("Remove script '{}'", name),
+            Command::SetScriptEnabled { name, enabled } => {
+                format!("{} script '{}'", if *enabled { "Enable" } else { "Disable" }, name)
+            }
+            Command::LoadProject(..) => "Load project".to_string(),
             Command::LoadSample { track, ref path, .. } => {
                 format!("Load sample '{}' into track {}", path, track)
             }
+            Command::LoadSampleLayer { track, layer, ref path, .. } => {
+                format!("Load sample '{}' into track {} layer {}", path, track, layer)
+            }
             Command::PreviewSample(_) => "Preview sample".to_string(),
+            Command::LoadExternalSynth { track, connection } => {
+                format!("Load external synth '{}' into track {}", connection.command, track)
+            }
         }
     }
 }
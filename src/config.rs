@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Path of the settings file: ~/.gridoxide/config.json
+pub fn config_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".gridoxide").join("config.json")
+}
+
+/// Buffer sizes offered in the settings view (frames), after the device default
+pub const BUFFER_SIZES: [u32; 6] = [64, 128, 256, 512, 1024, 2048];
+/// Autosave intervals offered in the settings view (minutes), after off
+pub const AUTOSAVE_MINUTES: [u32; 4] = [1, 2, 5, 10];
+/// Backup counts offered in the settings view, after off
+pub const BACKUP_COUNTS: [u32; 4] = [1, 3, 5, 10];
+
+/// User settings, edited in the settings view (Ctrl+G). Command line flags
+/// override them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Project opened on startup (relative paths are from the working directory)
+    pub project: Option<PathBuf>,
+    /// Start playback once the startup project is loaded
+    pub play: bool,
+    /// Cue bus output: "channels" (channels 3/4 of the main output) or the
+    /// name of a second output device
+    pub cue: Option<String>,
+    /// Clock source: "internal", "slave" or "master"
+    pub midi_sync: Option<String>,
+    /// Open the MIDI input for playing tracks from a controller
+    pub midi_in: bool,
+    /// MIDI port for the clock and note input: the first port whose name
+    /// contains this
+    pub midi_port: Option<String>,
+    /// Interface theme (see --list-themes)
+    pub theme: Option<String>,
+    /// Main output device by name (None = system default)
+    pub output_device: Option<String>,
+    /// Output buffer size in frames (None = device default)
+    pub buffer_size: Option<u32>,
+    /// More directories searched for samples, after ./samples and
+    /// ~/.gridoxide/samples
+    pub sample_dirs: Vec<PathBuf>,
+    /// Save projects with unsaved changes every this many minutes (0 = off)
+    pub autosave_minutes: u32,
+    /// Backups kept of each project, rotated on every save (0 = off)
+    pub backups: u32,
+    /// Click on every beat while playing
+    pub metronome: bool,
+    /// Plugin commands External tracks may start without asking when a
+    /// project loads (exact match); others wait for confirmation
+    pub plugins: Vec<String>,
+}
+
+impl Config {
+    /// Load the settings file (defaults if it doesn't exist yet)
+    pub fn load() -> Result<Self> {
+        let path = config_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = config_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
@@ -0,0 +1,75 @@
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use parking_lot::RwLock;
+
+use crate::event::EventLog;
+
+/// Event log entries included in a crash report
+const CRASH_EVENT_TAIL: usize = 50;
+
+/// Directory for crash reports: ~/.gridoxide/crashes
+pub fn crash_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".gridoxide").join("crashes")
+}
+
+/// Write a crash report (panic, backtrace, recent events) and return its path
+pub fn write_crash_report(info: &PanicHookInfo, event_log: &RwLock<EventLog>) -> Result<PathBuf> {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut report = String::new();
+    let _ = writeln!(report, "gridoxide v{} crash report", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "time: {} (unix)", secs);
+    let _ = writeln!(
+        report,
+        "thread: {}",
+        std::thread::current().name().unwrap_or("<unnamed>")
+    );
+    let _ = writeln!(report, "{}", info);
+    let _ = writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture());
+
+    // The panicking thread may hold the log lock; don't wait on it forever
+    match event_log.try_read_for(Duration::from_millis(200)) {
+        Some(log) => {
+            let events = log.get_events_since(0);
+            let start = events.len().saturating_sub(CRASH_EVENT_TAIL);
+            let _ = writeln!(report, "last {} events:", events.len() - start);
+            for event in &events[start..] {
+                let _ = writeln!(
+                    report,
+                    "  #{} {} {:?} {}",
+                    event.id,
+                    event.timestamp,
+                    event.source,
+                    event.command.description()
+                );
+            }
+            for error in log.get_errors_since(0).iter().rev().take(CRASH_EVENT_TAIL).rev() {
+                let _ = writeln!(
+                    report,
+                    "  #{} {} audio error: {}",
+                    error.id, error.timestamp, error.message
+                );
+            }
+        }
+        None => {
+            let _ = writeln!(report, "event log unavailable (locked)");
+        }
+    }
+
+    let dir = crash_dir();
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("crash-{}.log", secs));
+    std::fs::write(&path, report)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
@@ -12,19 +12,45 @@ pub struct Event {
     pub command: Command,
 }
 
-/// Ring buffer of recent events for MCP "listening"
+/// Error reported by the audio thread (shares ids with command events)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorEvent {
+    pub id: u64,
+    pub timestamp: u64,
+    pub message: String,
+}
+
+/// Gaps between commands longer than this count as a break, not time spent
+const IDLE_GAP_MS: u64 = 5 * 60 * 1000;
+
+/// Ring buffer of recent events for MCP "listening", plus session counters
+/// that outlive the buffer
 pub struct EventLog {
     events: VecDeque<Event>,
+    errors: VecDeque<ErrorEvent>,
     next_id: u64,
     max_events: usize,
+    started: u64,
+    /// Commands that changed the project, in total and from agents
+    edits: u64,
+    agent_edits: u64,
+    /// Time between commands, leaving out idle gaps
+    active_ms: u64,
+    last_command: Option<u64>,
 }
 
 impl EventLog {
     pub fn new() -> Self {
         Self {
             events: VecDeque::new(),
+            errors: VecDeque::new(),
             next_id: 1,
             max_events: 500,
+            started: now_millis(),
+            edits: 0,
+            agent_edits: 0,
+            active_ms: 0,
+            last_command: None,
         }
     }
 
@@ -34,10 +60,17 @@ impl EventLog {
             return;
         }
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_millis() as u64)
-            .unwrap_or(0);
+        let timestamp = now_millis();
+        if command.modifies_project() {
+            self.edits += 1;
+            if source == CommandSource::Mcp {
+                self.agent_edits += 1;
+            }
+        }
+        if let Some(last) = self.last_command {
+            self.active_ms += timestamp.saturating_sub(last).min(IDLE_GAP_MS);
+        }
+        self.last_command = Some(timestamp);
 
         let event = Event {
             id: self.next_id,
@@ -55,6 +88,28 @@ impl EventLog {
         }
     }
 
+    /// Log an error reported by the audio thread
+    pub fn log_error(&mut self, message: String) {
+        self.errors.push_back(ErrorEvent {
+            id: self.next_id,
+            timestamp: now_millis(),
+            message,
+        });
+        self.next_id += 1;
+        while self.errors.len() > self.max_events {
+            self.errors.pop_front();
+        }
+    }
+
+    /// Get all errors since a given ID
+    pub fn get_errors_since(&self, since_id: u64) -> Vec<ErrorEvent> {
+        self.errors
+            .iter()
+            .filter(|e| e.id > since_id)
+            .cloned()
+            .collect()
+    }
+
     /// Get all events since a given ID
     pub fn get_events_since(&self, since_id: u64) -> Vec<Event> {
         self.events
@@ -64,9 +119,9 @@ impl EventLog {
             .collect()
     }
 
-    /// Get the latest event ID
+    /// Get the latest event or error ID
     pub fn latest_id(&self) -> u64 {
-        self.events.back().map(|e| e.id).unwrap_or(0)
+        self.next_id - 1
     }
 
     /// Get total event count
@@ -77,6 +132,28 @@ impl EventLog {
     pub fn is_empty(&self) -> bool {
         self.events.is_empty()
     }
+
+    /// Milliseconds since the session started
+    pub fn session_ms(&self) -> u64 {
+        now_millis().saturating_sub(self.started)
+    }
+
+    /// Time spent sending commands this session (idle gaps left out)
+    pub fn active_ms(&self) -> u64 {
+        self.active_ms
+    }
+
+    /// Project-changing commands this session: (all, from agents)
+    pub fn edits(&self) -> (u64, u64) {
+        (self.edits, self.agent_edits)
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
 }
 
 impl Default for EventLog {
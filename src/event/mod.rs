@@ -1,3 +1,5 @@
 pub mod log;
+pub mod stats;
 
 pub use log::EventLog;
+pub use stats::{format_duration, SessionStats};
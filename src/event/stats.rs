@@ -0,0 +1,61 @@
+use crate::audio::SequencerState;
+use crate::sequencer::NUM_PATTERNS;
+
+use super::EventLog;
+
+/// Session and project numbers for the stats panel and get_session_stats
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionStats {
+    /// Seconds since the session started
+    pub session_secs: u64,
+    /// Seconds spent sending commands (idle gaps over 5 minutes left out)
+    pub active_secs: u64,
+    /// Project-changing commands, all and from agents
+    pub edits: u64,
+    pub agent_edits: u64,
+    pub tracks: usize,
+    /// Pattern slots with any active step
+    pub patterns_used: usize,
+    /// Different patterns the arrangement plays
+    pub arranged_patterns: usize,
+    /// Arrangement length in bars (pattern loops)
+    pub song_bars: usize,
+    /// Arrangement length at the current tempo
+    pub song_secs: f32,
+}
+
+impl SessionStats {
+    pub fn collect(log: &EventLog, state: &SequencerState) -> Self {
+        let (edits, agent_edits) = log.edits();
+        let mut arranged: Vec<usize> = state.arrangement.entries.iter().map(|e| e.pattern).collect();
+        arranged.sort_unstable();
+        arranged.dedup();
+        let secs_per_step = 60.0 / (state.bpm * 4.0);
+        let song_steps: usize = state
+            .arrangement
+            .entries
+            .iter()
+            .map(|e| e.repeats * state.pattern_bank.get(e.pattern).length)
+            .sum();
+        Self {
+            session_secs: log.session_ms() / 1000,
+            active_secs: log.active_ms() / 1000,
+            edits,
+            agent_edits,
+            tracks: state.tracks.len(),
+            patterns_used: (0..NUM_PATTERNS).filter(|&i| state.slot_has_content(i)).count(),
+            arranged_patterns: arranged.len(),
+            song_bars: state.arrangement.total_bars(),
+            song_secs: song_steps as f32 * secs_per_step,
+        }
+    }
+}
+
+/// Seconds as "1:02:03", or "2:03" under an hour
+pub fn format_duration(secs: u64) -> String {
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
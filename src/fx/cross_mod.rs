@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// What another track's signal modulates on a track (audio rate)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ModTarget {
+    /// Ring-mod style: the track is multiplied by the source
+    #[default]
+    Amplitude,
+    /// The source's level pushes the distortion drive up
+    Drive,
+}
+
+impl ModTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModTarget::Amplitude => "amp",
+            ModTarget::Drive => "drive",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "amp" | "amplitude" => Some(ModTarget::Amplitude),
+            "drive" => Some(ModTarget::Drive),
+            _ => None,
+        }
+    }
+
+    pub fn toggled(&self) -> Self {
+        match self {
+            ModTarget::Amplitude => ModTarget::Drive,
+            ModTarget::Drive => ModTarget::Amplitude,
+        }
+    }
+}
+
+/// Blend between the dry input (depth 0) and input × source (depth 1)
+pub fn ring_mod(input: f32, source: f32, depth: f32) -> f32 {
+    input * (1.0 - depth + depth * source)
+}
+
+/// Distortion drive raised by the source's level, scaled by depth
+pub fn modulated_drive(drive: f32, source: f32, depth: f32) -> f32 {
+    (drive + depth * source.abs()).clamp(0.0, 1.0)
+}
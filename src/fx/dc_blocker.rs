@@ -0,0 +1,77 @@
+/// Cutoff of the output DC blocker in Hz
+const DC_BLOCK_HZ: f32 = 10.0;
+
+/// Values smaller than this are flushed to zero
+const DENORMAL_THRESHOLD: f32 = 1e-15;
+
+/// Flush tiny values to zero so decaying feedback loops don't reach
+/// subnormal floats (which are very slow on many CPUs).
+#[inline]
+pub fn flush_denormal(x: f32) -> f32 {
+    if x.abs() < DENORMAL_THRESHOLD {
+        0.0
+    } else {
+        x
+    }
+}
+
+/// One-pole DC-blocking high-pass: y[n] = x[n] - x[n-1] + r * y[n-1]
+pub struct DcBlocker {
+    r: f32,
+    x1: f32,
+    y1: f32,
+}
+
+impl DcBlocker {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            r: 1.0 - 2.0 * std::f32::consts::PI * DC_BLOCK_HZ / sample_rate,
+            x1: 0.0,
+            y1: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let y = input - self.x1 + self.r * self.y1;
+        self.x1 = input;
+        self.y1 = flush_denormal(y);
+        self.y1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 44100.0;
+
+    #[test]
+    fn flush_zeroes_subnormals_only() {
+        assert_eq!(flush_denormal(f32::MIN_POSITIVE / 4.0), 0.0);
+        assert_eq!(flush_denormal(-1e-20), 0.0);
+        assert_eq!(flush_denormal(1e-6), 1e-6);
+        assert_eq!(flush_denormal(-0.5), -0.5);
+    }
+
+    #[test]
+    fn dc_input_decays_to_zero() {
+        let mut blocker = DcBlocker::new(SR);
+        let mut out = 1.0;
+        for _ in 0..SR as usize * 2 {
+            out = blocker.process(0.5);
+        }
+        assert!(out.abs() < 1e-4, "DC left after 2 s: {}", out);
+    }
+
+    #[test]
+    fn tail_flushes_to_exact_zero() {
+        let mut blocker = DcBlocker::new(SR);
+        blocker.process(1.0);
+        let mut out = 1.0;
+        for _ in 0..SR as usize * 10 {
+            out = blocker.process(0.0);
+            assert!(!out.is_subnormal());
+        }
+        assert_eq!(out, 0.0);
+    }
+}
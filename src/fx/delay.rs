@@ -1,4 +1,11 @@
-/// Ring buffer delay effect with feedback and mix
+use super::dc_blocker::flush_denormal;
+
+/// Crossfade time when the delay time changes (ms)
+const CROSSFADE_MS: f32 = 20.0;
+
+/// Ring buffer delay effect with feedback and mix.
+/// Time changes crossfade between two read heads instead of sliding the
+/// read position, so they don't produce pitch glitches.
 pub struct Delay {
     buffer: Vec<f32>,
     write_pos: usize,
@@ -6,9 +13,15 @@ pub struct Delay {
     time_ms: f32,
     feedback: f32,
     mix: f32,
-    // Smoothed read position to avoid clicks
-    current_delay_samples: f32,
-    target_delay_samples: f32,
+    // Delay (in samples) of the two read heads
+    head_delays: [f32; 2],
+    // Index of the head currently heard (the other is faded out)
+    active_head: usize,
+    // Crossfade progress toward the active head (1.0 = done)
+    fade: f32,
+    fade_step: f32,
+    // Time requested while a crossfade was running
+    pending_delay: Option<f32>,
 }
 
 impl Delay {
@@ -22,14 +35,32 @@ impl Delay {
             time_ms: 200.0,
             feedback: 0.3,
             mix: 0.2,
-            current_delay_samples: sample_rate * 0.2,
-            target_delay_samples: sample_rate * 0.2,
+            head_delays: [sample_rate * 0.2; 2],
+            active_head: 0,
+            fade: 1.0,
+            fade_step: 1.0 / (CROSSFADE_MS * 0.001 * sample_rate).max(1.0),
+            pending_delay: None,
         }
     }
 
     pub fn set_time(&mut self, ms: f32) {
         self.time_ms = ms.clamp(10.0, 500.0);
-        self.target_delay_samples = self.sample_rate * self.time_ms / 1000.0;
+        let target = self.sample_rate * self.time_ms / 1000.0;
+        if self.fade < 1.0 {
+            // Finish the running crossfade first
+            self.pending_delay = Some(target);
+        } else {
+            self.start_crossfade(target);
+        }
+    }
+
+    fn start_crossfade(&mut self, target: f32) {
+        if (target - self.head_delays[self.active_head]).abs() < 0.5 {
+            return;
+        }
+        self.active_head = 1 - self.active_head;
+        self.head_delays[self.active_head] = target;
+        self.fade = 0.0;
     }
 
     pub fn set_feedback(&mut self, feedback: f32) {
@@ -40,13 +71,8 @@ impl Delay {
         self.mix = mix.clamp(0.0, 1.0);
     }
 
-    pub fn process(&mut self, input: f32) -> f32 {
-        // Smooth delay time changes to avoid clicks
-        let smooth_speed = 0.001;
-        self.current_delay_samples += (self.target_delay_samples - self.current_delay_samples) * smooth_speed;
-
-        // Read from buffer with linear interpolation
-        let delay_samples = self.current_delay_samples;
+    /// Read the ring buffer `delay_samples` behind the write position (linear interpolation)
+    fn read(&self, delay_samples: f32) -> f32 {
         let read_pos_f = self.write_pos as f32 - delay_samples;
         let buf_len = self.buffer.len() as f32;
         let read_pos_f = if read_pos_f < 0.0 {
@@ -59,13 +85,50 @@ impl Delay {
         let frac = read_pos_f - read_idx as f32;
         let idx0 = read_idx % self.buffer.len();
         let idx1 = (read_idx + 1) % self.buffer.len();
-        let delayed = self.buffer[idx0] * (1.0 - frac) + self.buffer[idx1] * frac;
+        self.buffer[idx0] * (1.0 - frac) + self.buffer[idx1] * frac
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let active = self.read(self.head_delays[self.active_head]);
+        let delayed = if self.fade < 1.0 {
+            // Equal-power crossfade from the old head to the new one
+            let old = self.read(self.head_delays[1 - self.active_head]);
+            let angle = self.fade * std::f32::consts::FRAC_PI_2;
+            self.fade = (self.fade + self.fade_step).min(1.0);
+            if self.fade >= 1.0 {
+                if let Some(target) = self.pending_delay.take() {
+                    self.start_crossfade(target);
+                }
+            }
+            old * angle.cos() + active * angle.sin()
+        } else {
+            active
+        };
 
         // Write input + feedback to buffer
-        self.buffer[self.write_pos] = input + delayed * self.feedback;
+        self.buffer[self.write_pos] = flush_denormal(input + delayed * self.feedback);
         self.write_pos = (self.write_pos + 1) % self.buffer.len();
 
         // Dry/wet mix
         input * (1.0 - self.mix) + delayed * self.mix
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feedback_tail_flushes_to_exact_zero() {
+        let mut delay = Delay::new(44100.0);
+        delay.set_time(10.0);
+        delay.set_feedback(0.9);
+        delay.process(1.0);
+        for _ in 0..441 * 400 {
+            let out = delay.process(0.0);
+            assert!(!out.is_subnormal());
+        }
+        assert!(delay.buffer.iter().all(|&s| s == 0.0));
+        assert_eq!(delay.process(0.0), 0.0);
+    }
+}
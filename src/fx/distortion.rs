@@ -16,12 +16,21 @@ impl Distortion {
         self.drive = drive.clamp(0.0, 1.0);
     }
 
+    pub fn drive(&self) -> f32 {
+        self.drive
+    }
+
     pub fn set_mix(&mut self, mix: f32) {
         self.mix = mix.clamp(0.0, 1.0);
     }
 
     pub fn process(&self, input: f32) -> f32 {
-        let gain = 1.0 + self.drive * 10.0;
+        self.process_with_drive(input, self.drive)
+    }
+
+    /// Process with a drive other than the set one (audio-rate modulation)
+    pub fn process_with_drive(&self, input: f32, drive: f32) -> f32 {
+        let gain = 1.0 + drive * 10.0;
         let norm = gain.tanh();
         let wet = (input * gain).tanh() / norm;
         input * (1.0 - self.mix) + wet * self.mix
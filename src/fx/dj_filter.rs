@@ -0,0 +1,97 @@
+use super::filter::{FilterType, SvfFilter};
+
+/// Low-pass cutoff at full left sweep
+const LP_MIN_HZ: f32 = 200.0;
+/// High-pass cutoff at full right sweep
+const HP_MAX_HZ: f32 = 5000.0;
+/// Sweep distance from centre over which the filter fades in from bypass
+const FADE_IN_RANGE: f32 = 0.1;
+/// Glide time for manual moves (keeps held keys from zippering)
+const MOVE_MS: f32 = 15.0;
+
+/// One-knob DJ mixer filter for the master bus. Position -1..0 sweeps a
+/// low-pass down, 0..1 sweeps a high-pass up; 0 is bypass.
+pub struct DjFilter {
+    sample_rate: f32,
+    position: f32,
+    target: f32,
+    /// Largest change in position per sample
+    step: f32,
+    lp: [SvfFilter; 2],
+    hp: [SvfFilter; 2],
+}
+
+impl DjFilter {
+    pub fn new(sample_rate: f32) -> Self {
+        let make = |ft: FilterType| {
+            let mut f = SvfFilter::new(sample_rate);
+            f.set_filter_type(ft);
+            // A little bite at the cutoff, like a mixer filter
+            f.set_resonance(0.3);
+            f
+        };
+        Self {
+            sample_rate,
+            position: 0.0,
+            target: 0.0,
+            step: 0.0,
+            lp: [make(FilterType::LowPass), make(FilterType::LowPass)],
+            hp: [make(FilterType::HighPass), make(FilterType::HighPass)],
+        }
+    }
+
+    /// Move toward a sweep position (-1..1)
+    pub fn set_position(&mut self, position: f32) {
+        self.target = position.clamp(-1.0, 1.0);
+        let samples = (MOVE_MS * 0.001 * self.sample_rate).max(1.0);
+        self.step = 2.0 / samples;
+    }
+
+    /// Glide back to neutral over the given number of seconds
+    pub fn release(&mut self, seconds: f32) {
+        self.target = 0.0;
+        let samples = (seconds * self.sample_rate).max(1.0);
+        self.step = self.position.abs() / samples;
+    }
+
+    /// Jump straight to a position (no glide)
+    pub fn reset(&mut self, position: f32) {
+        self.position = position.clamp(-1.0, 1.0);
+        self.target = self.position;
+        self.update_cutoffs();
+    }
+
+    fn update_cutoffs(&mut self) {
+        let amount = self.position.abs();
+        if self.position < 0.0 {
+            // Exponential sweep 20 kHz -> LP_MIN_HZ
+            let hz = 20000.0 * (LP_MIN_HZ / 20000.0).powf(amount);
+            self.lp.iter_mut().for_each(|f| f.set_cutoff(hz));
+        } else if self.position > 0.0 {
+            // Exponential sweep 20 Hz -> HP_MAX_HZ
+            let hz = 20.0 * (HP_MAX_HZ / 20.0).powf(amount);
+            self.hp.iter_mut().for_each(|f| f.set_cutoff(hz));
+        }
+    }
+
+    pub fn process_stereo(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.position != self.target {
+            let delta = (self.target - self.position).clamp(-self.step, self.step);
+            self.position += delta;
+            if (self.target - self.position).abs() < 1e-6 {
+                self.position = self.target;
+            }
+            self.update_cutoffs();
+        }
+        if self.position == 0.0 {
+            return (left, right);
+        }
+
+        let filters = if self.position < 0.0 { &mut self.lp } else { &mut self.hp };
+        let wet_l = filters[0].process(left);
+        let wet_r = filters[1].process(right);
+        // Fade in just off centre so crossing zero doesn't click
+        let mix = (self.position.abs() / FADE_IN_RANGE).min(1.0);
+        (left + (wet_l - left) * mix, right + (wet_r - right) * mix)
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::dc_blocker::flush_denormal;
+
 /// Filter type selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FilterType {
@@ -36,6 +38,7 @@ impl FilterType {
 }
 
 /// State Variable Filter (2-pole SVF)
+#[derive(Clone)]
 pub struct SvfFilter {
     sample_rate: f32,
     filter_type: FilterType,
@@ -97,8 +100,8 @@ impl SvfFilter {
         let v1 = a1 * self.band + a2 * v3;
         let v2 = self.low + a2 * self.band + a3 * v3;
 
-        self.band = 2.0 * v1 - self.band;
-        self.low = 2.0 * v2 - self.low;
+        self.band = flush_denormal(2.0 * v1 - self.band);
+        self.low = flush_denormal(2.0 * v2 - self.low);
 
         match self.filter_type {
             FilterType::LowPass => v2,
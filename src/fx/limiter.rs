@@ -0,0 +1,152 @@
+/// Oversampling factor of the true-peak detector
+const OVERSAMPLE: usize = 4;
+/// FIR taps per oversampling phase (48-tap interpolation filter)
+const PHASE_TAPS: usize = 12;
+
+/// How far ahead the limiter sees peaks coming (ms); also its latency
+const LOOKAHEAD_MS: f32 = 1.5;
+
+/// Estimates the true (inter-sample) peak of a signal by interpolating it
+/// at 4x the sample rate, as the analog output would reconstruct it. The
+/// estimate lags the input by a few samples.
+#[derive(Clone)]
+pub struct TruePeak {
+    /// Windowed-sinc interpolation filter split into one set of taps per phase
+    taps: [[f32; PHASE_TAPS]; OVERSAMPLE],
+    history: [f32; PHASE_TAPS],
+    pos: usize,
+}
+
+impl TruePeak {
+    pub fn new() -> Self {
+        let len = OVERSAMPLE * PHASE_TAPS;
+        let center = (len - 1) as f32 / 2.0;
+        let mut taps = [[0.0; PHASE_TAPS]; OVERSAMPLE];
+        for (phase, phase_taps) in taps.iter_mut().enumerate() {
+            for (k, tap) in phase_taps.iter_mut().enumerate() {
+                let n = (k * OVERSAMPLE + phase) as f32;
+                let t = (n - center) / OVERSAMPLE as f32;
+                let sinc = if t.abs() < 1e-6 {
+                    1.0
+                } else {
+                    (std::f32::consts::PI * t).sin() / (std::f32::consts::PI * t)
+                };
+                // Blackman window
+                let x = 2.0 * std::f32::consts::PI * n / (len - 1) as f32;
+                *tap = sinc * (0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos());
+            }
+            // Unity gain per phase so DC reads at its own level
+            let sum: f32 = phase_taps.iter().sum();
+            for tap in phase_taps.iter_mut() {
+                *tap /= sum;
+            }
+        }
+        Self {
+            taps,
+            history: [0.0; PHASE_TAPS],
+            pos: 0,
+        }
+    }
+
+    /// Feed one sample; returns the highest absolute level among the
+    /// interpolated points up to it
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.history[self.pos] = sample;
+        self.pos = (self.pos + 1) % PHASE_TAPS;
+        let mut peak: f32 = 0.0;
+        for phase_taps in &self.taps {
+            let mut acc = 0.0;
+            for (k, tap) in phase_taps.iter().enumerate() {
+                // history newest first
+                let i = (self.pos + PHASE_TAPS - 1 - k) % PHASE_TAPS;
+                acc += tap * self.history[i];
+            }
+            peak = peak.max(acc.abs());
+        }
+        peak
+    }
+}
+
+impl Default for TruePeak {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stereo-linked lookahead brickwall limiter for the master bus. Gain is
+/// worked out from the true peak so the reconstructed output stays under
+/// the ceiling; it ramps down over the lookahead before a peak arrives
+/// and recovers over the release time.
+pub struct Limiter {
+    sample_rate: f32,
+    /// Ceiling as a linear level
+    ceiling: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    detectors: [TruePeak; 2],
+    /// Delayed input and the gain each sample needs, over the lookahead
+    delay: [Vec<f32>; 2],
+    needed: Vec<f32>,
+    pos: usize,
+    gain: f32,
+}
+
+impl Limiter {
+    pub fn new(sample_rate: f32) -> Self {
+        let lookahead = ((LOOKAHEAD_MS * 0.001 * sample_rate) as usize).max(1);
+        let mut limiter = Self {
+            sample_rate,
+            ceiling: 1.0,
+            // Within 1% of the needed gain by the time the peak comes out
+            attack_coeff: 1.0 - (-5.0 / lookahead as f32).exp(),
+            release_coeff: 0.0,
+            detectors: [TruePeak::new(), TruePeak::new()],
+            delay: [vec![0.0; lookahead], vec![0.0; lookahead]],
+            needed: vec![1.0; lookahead],
+            pos: 0,
+            gain: 1.0,
+        };
+        limiter.set_ceiling(-1.0);
+        limiter.set_release(150.0);
+        limiter
+    }
+
+    /// Highest output level (dBFS, -12 to 0)
+    pub fn set_ceiling(&mut self, db: f32) {
+        self.ceiling = 10f32.powf(db.clamp(-12.0, 0.0) / 20.0);
+    }
+
+    /// Time to recover from gain reduction (ms, 10-1000)
+    pub fn set_release(&mut self, ms: f32) {
+        let samples = ms.clamp(10.0, 1000.0) * 0.001 * self.sample_rate;
+        self.release_coeff = 1.0 - (-1.0 / samples).exp();
+    }
+
+    /// Gain reduction being applied now (dB, 0 when idle)
+    pub fn reduction_db(&self) -> f32 {
+        -20.0 * self.gain.max(1e-6).log10()
+    }
+
+    pub fn process_stereo(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let peak = self.detectors[0].process(left).max(self.detectors[1].process(right));
+        let needed = if peak > self.ceiling { self.ceiling / peak } else { 1.0 };
+
+        // Oldest sample out, newest in
+        let out_left = self.delay[0][self.pos];
+        let out_right = self.delay[1][self.pos];
+        self.delay[0][self.pos] = left;
+        self.delay[1][self.pos] = right;
+        self.needed[self.pos] = needed;
+        self.pos = (self.pos + 1) % self.needed.len();
+
+        let target = self.needed.iter().copied().fold(1.0, f32::min);
+        let coeff = if target < self.gain { self.attack_coeff } else { self.release_coeff };
+        self.gain += (target - self.gain) * coeff;
+
+        // What the ramp didn't catch is clipped at the ceiling
+        (
+            (out_left * self.gain).clamp(-self.ceiling, self.ceiling),
+            (out_right * self.gain).clamp(-self.ceiling, self.ceiling),
+        )
+    }
+}
@@ -0,0 +1,78 @@
+use super::filter::{FilterType, SvfFilter};
+use super::smooth::SmoothedParam;
+
+/// Crossover between "low" and "high" side content for the side high shelf
+const SIDE_HIGH_CROSSOVER_HZ: f32 = 3000.0;
+
+/// Mid/side processor for the master bus. Encodes L/R to M/S, applies
+/// level per component, removes side below a mono-bass frequency and
+/// boosts/cuts side highs, then decodes back to L/R.
+/// With default params (levels 1.0, bass mono off) it is transparent.
+pub struct MidSide {
+    mid_level: SmoothedParam,
+    side_level: SmoothedParam,
+    side_high: SmoothedParam,
+    /// High-pass on the side signal (0 Hz = off)
+    bass_mono_hz: f32,
+    side_hp: SvfFilter,
+    /// One-pole lowpass state/coeff splitting side highs off
+    side_lp: f32,
+    side_lp_coeff: f32,
+}
+
+impl MidSide {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut side_hp = SvfFilter::new(sample_rate);
+        side_hp.set_filter_type(FilterType::HighPass);
+        // Butterworth-ish, no resonant bump on the side channel
+        side_hp.set_resonance(0.3);
+        Self {
+            mid_level: SmoothedParam::new(1.0, sample_rate),
+            side_level: SmoothedParam::new(1.0, sample_rate),
+            side_high: SmoothedParam::new(1.0, sample_rate),
+            bass_mono_hz: 0.0,
+            side_hp,
+            side_lp: 0.0,
+            side_lp_coeff: 1.0
+                - (-2.0 * std::f32::consts::PI * SIDE_HIGH_CROSSOVER_HZ / sample_rate).exp(),
+        }
+    }
+
+    pub fn set_mid_level(&mut self, level: f32) {
+        self.mid_level.set_target(level.clamp(0.0, 2.0));
+    }
+
+    pub fn set_side_level(&mut self, level: f32) {
+        self.side_level.set_target(level.clamp(0.0, 2.0));
+    }
+
+    /// Side content below this frequency is removed (0 = off)
+    pub fn set_bass_mono(&mut self, hz: f32) {
+        self.bass_mono_hz = hz.clamp(0.0, 500.0);
+        if self.bass_mono_hz > 0.0 {
+            self.side_hp.set_cutoff(self.bass_mono_hz);
+        }
+    }
+
+    /// Gain applied to side content above the crossover (1.0 = flat)
+    pub fn set_side_high(&mut self, gain: f32) {
+        self.side_high.set_target(gain.clamp(0.0, 2.0));
+    }
+
+    pub fn process_stereo(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let mid = (left + right) * 0.5;
+        let mut side = (left - right) * 0.5;
+
+        if self.bass_mono_hz > 0.0 {
+            side = self.side_hp.process(side);
+        }
+
+        self.side_lp += (side - self.side_lp) * self.side_lp_coeff;
+        let side_high = side - self.side_lp;
+        side += side_high * (self.side_high.next() - 1.0);
+
+        let mid = mid * self.mid_level.next();
+        let side = side * self.side_level.next();
+        (mid + side, mid - side)
+    }
+}
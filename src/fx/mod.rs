@@ -1,12 +1,24 @@
+pub mod cross_mod;
+pub mod dc_blocker;
 pub mod delay;
+pub mod dj_filter;
 pub mod distortion;
 pub mod filter;
+pub mod limiter;
+pub mod mid_side;
 pub mod reverb;
+pub mod smooth;
 
+pub use cross_mod::ModTarget;
+pub use dc_blocker::DcBlocker;
 pub use delay::Delay;
+pub use dj_filter::DjFilter;
 pub use distortion::Distortion;
 pub use filter::{FilterType, SvfFilter};
-pub use reverb::StereoReverb;
+pub use limiter::{Limiter, TruePeak};
+pub use mid_side::MidSide;
+pub use reverb::{ReverbMode, StereoReverb};
+pub use smooth::SmoothedParam;
 
 use serde::{Deserialize, Serialize};
 
@@ -38,6 +50,8 @@ pub enum FxParamId {
     DelayTime,
     DelayFeedback,
     DelayMix,
+    ModDepth,
+    ChainMix,
 }
 
 impl FxParamId {
@@ -50,6 +64,8 @@ impl FxParamId {
             FxParamId::DelayTime => "Time",
             FxParamId::DelayFeedback => "Feedback",
             FxParamId::DelayMix => "Delay Mix",
+            FxParamId::ModDepth => "Mod Depth",
+            FxParamId::ChainMix => "Chain Mix",
         }
     }
 
@@ -62,6 +78,8 @@ impl FxParamId {
             FxParamId::DelayTime => "delay_time",
             FxParamId::DelayFeedback => "delay_feedback",
             FxParamId::DelayMix => "delay_mix",
+            FxParamId::ModDepth => "mod_depth",
+            FxParamId::ChainMix => "chain_mix",
         }
     }
 
@@ -75,6 +93,8 @@ impl FxParamId {
             FxParamId::DelayTime => (10.0, 500.0, 200.0),
             FxParamId::DelayFeedback => (0.0, 0.9, 0.3),
             FxParamId::DelayMix => (0.0, 1.0, 0.2),
+            FxParamId::ModDepth => (0.0, 1.0, 1.0),
+            FxParamId::ChainMix => (0.0, 1.0, 1.0),
         }
     }
 
@@ -87,6 +107,8 @@ impl FxParamId {
             "delay_time" => Some(FxParamId::DelayTime),
             "delay_feedback" => Some(FxParamId::DelayFeedback),
             "delay_mix" => Some(FxParamId::DelayMix),
+            "mod_depth" => Some(FxParamId::ModDepth),
+            "chain_mix" => Some(FxParamId::ChainMix),
             _ => None,
         }
     }
@@ -101,6 +123,8 @@ impl FxParamId {
             FxParamId::DelayTime,
             FxParamId::DelayFeedback,
             FxParamId::DelayMix,
+            FxParamId::ModDepth,
+            FxParamId::ChainMix,
         ]
     }
 }
@@ -111,6 +135,15 @@ pub enum MasterFxParamId {
     ReverbDecay,
     ReverbMix,
     ReverbDamping,
+    ReverbWidth,
+    ReverbGateThreshold,
+    ReverbGateHold,
+    MsMidLevel,
+    MsSideLevel,
+    MsBassMono,
+    MsSideHigh,
+    LimiterCeiling,
+    LimiterRelease,
 }
 
 impl MasterFxParamId {
@@ -119,6 +152,15 @@ impl MasterFxParamId {
             MasterFxParamId::ReverbDecay => "Decay",
             MasterFxParamId::ReverbMix => "Mix",
             MasterFxParamId::ReverbDamping => "Damping",
+            MasterFxParamId::ReverbWidth => "Width",
+            MasterFxParamId::ReverbGateThreshold => "Gate Thresh",
+            MasterFxParamId::ReverbGateHold => "Gate Hold",
+            MasterFxParamId::MsMidLevel => "Mid",
+            MasterFxParamId::MsSideLevel => "Side",
+            MasterFxParamId::MsBassMono => "Bass Mono",
+            MasterFxParamId::MsSideHigh => "Side Highs",
+            MasterFxParamId::LimiterCeiling => "Ceiling",
+            MasterFxParamId::LimiterRelease => "Release",
         }
     }
 
@@ -127,6 +169,15 @@ impl MasterFxParamId {
             MasterFxParamId::ReverbDecay => "reverb_decay",
             MasterFxParamId::ReverbMix => "reverb_mix",
             MasterFxParamId::ReverbDamping => "reverb_damping",
+            MasterFxParamId::ReverbWidth => "reverb_width",
+            MasterFxParamId::ReverbGateThreshold => "reverb_gate_threshold",
+            MasterFxParamId::ReverbGateHold => "reverb_gate_hold",
+            MasterFxParamId::MsMidLevel => "ms_mid_level",
+            MasterFxParamId::MsSideLevel => "ms_side_level",
+            MasterFxParamId::MsBassMono => "ms_bass_mono",
+            MasterFxParamId::MsSideHigh => "ms_side_high",
+            MasterFxParamId::LimiterCeiling => "limiter_ceiling",
+            MasterFxParamId::LimiterRelease => "limiter_release",
         }
     }
 
@@ -135,6 +186,15 @@ impl MasterFxParamId {
             MasterFxParamId::ReverbDecay => (0.1, 0.95, 0.5),
             MasterFxParamId::ReverbMix => (0.0, 1.0, 0.3),
             MasterFxParamId::ReverbDamping => (0.0, 1.0, 0.5),
+            MasterFxParamId::ReverbWidth => (0.0, 1.0, 1.0),
+            MasterFxParamId::ReverbGateThreshold => (-60.0, 0.0, -30.0),
+            MasterFxParamId::ReverbGateHold => (20.0, 500.0, 150.0),
+            MasterFxParamId::MsMidLevel => (0.0, 2.0, 1.0),
+            MasterFxParamId::MsSideLevel => (0.0, 2.0, 1.0),
+            MasterFxParamId::MsBassMono => (0.0, 500.0, 0.0),
+            MasterFxParamId::MsSideHigh => (0.0, 2.0, 1.0),
+            MasterFxParamId::LimiterCeiling => (-12.0, 0.0, -1.0),
+            MasterFxParamId::LimiterRelease => (10.0, 1000.0, 150.0),
         }
     }
 
@@ -143,6 +203,15 @@ impl MasterFxParamId {
             "reverb_decay" => Some(MasterFxParamId::ReverbDecay),
             "reverb_mix" => Some(MasterFxParamId::ReverbMix),
             "reverb_damping" => Some(MasterFxParamId::ReverbDamping),
+            "reverb_width" => Some(MasterFxParamId::ReverbWidth),
+            "reverb_gate_threshold" => Some(MasterFxParamId::ReverbGateThreshold),
+            "reverb_gate_hold" => Some(MasterFxParamId::ReverbGateHold),
+            "ms_mid_level" => Some(MasterFxParamId::MsMidLevel),
+            "ms_side_level" => Some(MasterFxParamId::MsSideLevel),
+            "ms_bass_mono" => Some(MasterFxParamId::MsBassMono),
+            "ms_side_high" => Some(MasterFxParamId::MsSideHigh),
+            "limiter_ceiling" => Some(MasterFxParamId::LimiterCeiling),
+            "limiter_release" => Some(MasterFxParamId::LimiterRelease),
             _ => None,
         }
     }
@@ -152,6 +221,15 @@ impl MasterFxParamId {
             MasterFxParamId::ReverbDecay,
             MasterFxParamId::ReverbMix,
             MasterFxParamId::ReverbDamping,
+            MasterFxParamId::ReverbWidth,
+            MasterFxParamId::ReverbGateThreshold,
+            MasterFxParamId::ReverbGateHold,
+            MasterFxParamId::MsMidLevel,
+            MasterFxParamId::MsSideLevel,
+            MasterFxParamId::MsBassMono,
+            MasterFxParamId::MsSideHigh,
+            MasterFxParamId::LimiterCeiling,
+            MasterFxParamId::LimiterRelease,
         ]
     }
 }
@@ -170,6 +248,33 @@ pub struct TrackFxState {
     pub delay_time: f32,
     pub delay_feedback: f32,
     pub delay_mix: f32,
+    /// Track whose signal modulates this one (None = off)
+    #[serde(default)]
+    pub mod_source: Option<usize>,
+    #[serde(default)]
+    pub mod_target: ModTarget,
+    #[serde(default = "default_unity")]
+    pub mod_depth: f32,
+    /// Blend of the whole chain's output with the dry input (1 = all wet)
+    #[serde(default = "default_unity")]
+    pub chain_mix: f32,
+}
+
+impl TrackFxState {
+    /// Modulation source for `track`, ignoring stale or self references
+    pub fn modulator(&self, track: usize, num_tracks: usize) -> Option<usize> {
+        self.mod_source.filter(|&s| s != track && s < num_tracks)
+    }
+
+    /// Keep the modulation source pointing at the same track after `removed`
+    /// is deleted (cleared if it was the source)
+    pub fn remove_track(&mut self, removed: usize) {
+        self.mod_source = match self.mod_source {
+            Some(s) if s == removed => None,
+            Some(s) if s > removed => Some(s - 1),
+            other => other,
+        };
+    }
 }
 
 impl Default for TrackFxState {
@@ -186,6 +291,10 @@ impl Default for TrackFxState {
             delay_time: 200.0,
             delay_feedback: 0.3,
             delay_mix: 0.2,
+            mod_source: None,
+            mod_target: ModTarget::Amplitude,
+            mod_depth: 1.0,
+            chain_mix: 1.0,
         }
     }
 }
@@ -197,6 +306,54 @@ pub struct MasterFxState {
     pub reverb_decay: f32,
     pub reverb_mix: f32,
     pub reverb_damping: f32,
+    #[serde(default = "default_unity")]
+    pub reverb_width: f32,
+    #[serde(default)]
+    pub reverb_mode: ReverbMode,
+    /// Gated mode: dry level (dB) that holds the tail open, and for how long (ms)
+    #[serde(default = "default_gate_threshold")]
+    pub reverb_gate_threshold: f32,
+    #[serde(default = "default_gate_hold")]
+    pub reverb_gate_hold: f32,
+    // Mid/side stage (after reverb); defaults are transparent
+    #[serde(default = "default_unity")]
+    pub ms_mid_level: f32,
+    #[serde(default = "default_unity")]
+    pub ms_side_level: f32,
+    #[serde(default)]
+    pub ms_bass_mono: f32,
+    #[serde(default = "default_unity")]
+    pub ms_side_high: f32,
+    /// Brickwall limiter (last stage): true-peak ceiling (dBFS) and release (ms)
+    #[serde(default)]
+    pub limiter_enabled: bool,
+    #[serde(default = "default_limiter_ceiling")]
+    pub limiter_ceiling: f32,
+    #[serde(default = "default_limiter_release")]
+    pub limiter_release: f32,
+    /// Reserved for future features; unknown keys are kept on load/save
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+fn default_unity() -> f32 {
+    1.0
+}
+
+fn default_gate_threshold() -> f32 {
+    MasterFxParamId::ReverbGateThreshold.range().2
+}
+
+fn default_gate_hold() -> f32 {
+    MasterFxParamId::ReverbGateHold.range().2
+}
+
+fn default_limiter_ceiling() -> f32 {
+    MasterFxParamId::LimiterCeiling.range().2
+}
+
+fn default_limiter_release() -> f32 {
+    MasterFxParamId::LimiterRelease.range().2
 }
 
 impl Default for MasterFxState {
@@ -206,10 +363,43 @@ impl Default for MasterFxState {
             reverb_decay: 0.5,
             reverb_mix: 0.3,
             reverb_damping: 0.5,
+            reverb_width: 1.0,
+            reverb_mode: ReverbMode::Normal,
+            reverb_gate_threshold: default_gate_threshold(),
+            reverb_gate_hold: default_gate_hold(),
+            ms_mid_level: 1.0,
+            ms_side_level: 1.0,
+            ms_bass_mono: 0.0,
+            ms_side_high: 1.0,
+            limiter_enabled: false,
+            limiter_ceiling: default_limiter_ceiling(),
+            limiter_release: default_limiter_release(),
+            extensions: serde_json::Map::new(),
         }
     }
 }
 
+/// FX params ramped per sample by TrackFxChain (delay time crossfades inside Delay)
+const SMOOTHED_FX_PARAMS: [FxParamId; 8] = [
+    FxParamId::FilterCutoff,
+    FxParamId::FilterResonance,
+    FxParamId::DistDrive,
+    FxParamId::DistMix,
+    FxParamId::DelayFeedback,
+    FxParamId::DelayMix,
+    FxParamId::ModDepth,
+    FxParamId::ChainMix,
+];
+
+/// How long a bypassed delay keeps running so re-enabling it doesn't click
+const DELAY_WARM_SECONDS: f32 = 1.0;
+
+/// Averaging time of the gain-match RMS detectors
+const GAIN_MATCH_RMS_MS: f32 = 300.0;
+/// Gain-match correction limits (about +/-12 dB)
+const GAIN_MATCH_MIN: f32 = 0.25;
+const GAIN_MATCH_MAX: f32 = 4.0;
+
 /// Per-track FX processing chain (owns DSP instances)
 pub struct TrackFxChain {
     pub filter: SvfFilter,
@@ -218,10 +408,34 @@ pub struct TrackFxChain {
     pub filter_enabled: bool,
     pub dist_enabled: bool,
     pub delay_enabled: bool,
+    /// What a cross-track modulator (passed to `process`) drives
+    pub mod_target: ModTarget,
+    mod_depth: f32,
+    /// Dry/wet blend of the whole chain, for parallel processing
+    chain_mix: f32,
+    /// Smoothing ramps, indexed like SMOOTHED_FX_PARAMS
+    smoothers: [SmoothedParam; 8],
+    /// Soft bypass: crossfade level between dry (0) and processed (1) per effect
+    filter_level: SmoothedParam,
+    dist_level: SmoothedParam,
+    delay_level: SmoothedParam,
+    delay_warm_samples: usize,
+    delay_warm_remaining: usize,
+    /// Auto-gain: match filter+distortion output loudness to their input
+    pub gain_match: bool,
+    /// Running mean-square of the signal before/after filter+distortion
+    match_in_power: f32,
+    match_out_power: f32,
+    match_rms_coeff: f32,
+    match_gain: SmoothedParam,
 }
 
 impl TrackFxChain {
     pub fn new(sample_rate: f32) -> Self {
+        let mut smoothers = [SmoothedParam::new(0.0, sample_rate); 8];
+        for (s, param) in smoothers.iter_mut().zip(SMOOTHED_FX_PARAMS) {
+            s.reset(param.range().2);
+        }
         Self {
             filter: SvfFilter::new(sample_rate),
             distortion: Distortion::new(),
@@ -229,37 +443,180 @@ impl TrackFxChain {
             filter_enabled: false,
             dist_enabled: false,
             delay_enabled: false,
+            mod_target: ModTarget::Amplitude,
+            mod_depth: 1.0,
+            chain_mix: 1.0,
+            smoothers,
+            filter_level: SmoothedParam::new(0.0, sample_rate),
+            dist_level: SmoothedParam::new(0.0, sample_rate),
+            delay_level: SmoothedParam::new(0.0, sample_rate),
+            delay_warm_samples: (DELAY_WARM_SECONDS * sample_rate) as usize,
+            delay_warm_remaining: 0,
+            gain_match: false,
+            match_in_power: 0.0,
+            match_out_power: 0.0,
+            match_rms_coeff: 1.0 - (-1.0 / (GAIN_MATCH_RMS_MS * 0.001 * sample_rate)).exp(),
+            match_gain: SmoothedParam::new(1.0, sample_rate),
+        }
+    }
+
+    /// Jump bypass crossfades to the current enabled flags (no fade)
+    pub fn reset_bypass(&mut self) {
+        let level = |on: bool| if on { 1.0 } else { 0.0 };
+        self.filter_level.reset(level(self.filter_enabled));
+        self.dist_level.reset(level(self.dist_enabled));
+        self.delay_level.reset(level(self.delay_enabled));
+        self.delay_warm_remaining = 0;
+    }
+
+    /// Set a parameter, ramping continuous values to avoid zipper noise
+    pub fn set_param(&mut self, param: FxParamId, value: f32) {
+        match SMOOTHED_FX_PARAMS.iter().position(|&p| p == param) {
+            Some(i) => self.smoothers[i].set_target(value),
+            None => self.apply_param(param, value),
+        }
+    }
+
+    /// Set a parameter immediately, without a ramp
+    pub fn reset_param(&mut self, param: FxParamId, value: f32) {
+        if let Some(i) = SMOOTHED_FX_PARAMS.iter().position(|&p| p == param) {
+            self.smoothers[i].reset(value);
+        }
+        self.apply_param(param, value);
+    }
+
+    fn apply_param(&mut self, param: FxParamId, value: f32) {
+        match param {
+            FxParamId::FilterCutoff => self.filter.set_cutoff(value),
+            FxParamId::FilterResonance => self.filter.set_resonance(value),
+            FxParamId::DistDrive => self.distortion.set_drive(value),
+            FxParamId::DistMix => self.distortion.set_mix(value),
+            FxParamId::DelayTime => self.delay.set_time(value),
+            FxParamId::DelayFeedback => self.delay.set_feedback(value),
+            FxParamId::DelayMix => self.delay.set_mix(value),
+            FxParamId::ModDepth => self.mod_depth = value.clamp(0.0, 1.0),
+            FxParamId::ChainMix => self.chain_mix = value.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Scale `output` so its running RMS follows `input`'s, so toggling the
+    /// filter or distortion compares sound rather than loudness
+    fn apply_gain_match(&mut self, input: f32, output: f32) -> f32 {
+        if self.gain_match {
+            self.match_in_power += (input * input - self.match_in_power) * self.match_rms_coeff;
+            self.match_out_power += (output * output - self.match_out_power) * self.match_rms_coeff;
+            // Hold the last correction through silence
+            if self.match_out_power > 1e-8 {
+                let gain = (self.match_in_power / self.match_out_power).sqrt();
+                self.match_gain.set_target(gain.clamp(GAIN_MATCH_MIN, GAIN_MATCH_MAX));
+            }
+        } else {
+            self.match_gain.set_target(1.0);
         }
+        output * self.match_gain.next()
     }
 
-    /// Process a mono sample through the FX chain: Filter -> Distortion -> Delay
-    pub fn process(&mut self, input: f32) -> f32 {
-        let mut s = input;
-        if self.filter_enabled {
-            s = self.filter.process(s);
+    /// Process a mono sample through the FX chain: Filter -> Distortion -> Delay,
+    /// blended with the dry input by the chain mix.
+    /// `modulator` is the cross-track source's sample, if one is set: it
+    /// ring-modulates the input or drives the distortion (see ModTarget).
+    pub fn process(&mut self, input: f32, modulator: Option<f32>) -> f32 {
+        for i in 0..SMOOTHED_FX_PARAMS.len() {
+            if self.smoothers[i].is_ramping() {
+                let v = self.smoothers[i].next();
+                self.apply_param(SMOOTHED_FX_PARAMS[i], v);
+            }
         }
-        if self.dist_enabled {
-            s = self.distortion.process(s);
+
+        // Enable flags are targets; each effect fades between dry and processed
+        let level = |on: bool| if on { 1.0 } else { 0.0 };
+        self.filter_level.set_target(level(self.filter_enabled));
+        self.dist_level.set_target(level(self.dist_enabled));
+        self.delay_level.set_target(level(self.delay_enabled));
+
+        let mut s = match (modulator, self.mod_target) {
+            (Some(m), ModTarget::Amplitude) => cross_mod::ring_mod(input, m, self.mod_depth),
+            _ => input,
+        };
+        let pre_level = s;
+        let filter_level = self.filter_level.next();
+        if filter_level > 0.0 {
+            let wet = self.filter.process(s);
+            s += (wet - s) * filter_level;
         }
+        let dist_level = self.dist_level.next();
+        if dist_level > 0.0 {
+            let wet = match (modulator, self.mod_target) {
+                (Some(m), ModTarget::Drive) => {
+                    let drive = cross_mod::modulated_drive(self.distortion.drive(), m, self.mod_depth);
+                    self.distortion.process_with_drive(s, drive)
+                }
+                _ => self.distortion.process(s),
+            };
+            s += (wet - s) * dist_level;
+        }
+        s = self.apply_gain_match(pre_level, s);
+        // A bypassed delay keeps being fed for a while so its buffer stays warm
         if self.delay_enabled {
-            s = self.delay.process(s);
+            self.delay_warm_remaining = self.delay_warm_samples;
+        }
+        let delay_level = self.delay_level.next();
+        if delay_level > 0.0 || self.delay_warm_remaining > 0 {
+            let wet = self.delay.process(s);
+            s += (wet - s) * delay_level;
+            if !self.delay_enabled {
+                self.delay_warm_remaining = self.delay_warm_remaining.saturating_sub(1);
+            }
         }
-        s
+        // Parallel processing: the dry track under the processed one
+        input + (s - input) * self.chain_mix
     }
 }
 
+/// Configure the master reverb from a MasterFxState snapshot.
+/// Used by both the LoadProject handler and the offline renderer.
+pub fn configure_reverb(reverb: &mut StereoReverb, state: &MasterFxState) {
+    reverb.set_decay(state.reverb_decay);
+    reverb.set_mix(state.reverb_mix);
+    reverb.set_damping(state.reverb_damping);
+    reverb.set_width(state.reverb_width);
+    reverb.set_mode(state.reverb_mode);
+    reverb.set_gate_threshold(state.reverb_gate_threshold);
+    reverb.set_gate_hold(state.reverb_gate_hold);
+}
+
+/// Configure the master mid/side stage from a MasterFxState snapshot.
+/// Used by both the LoadProject handler and the offline renderer.
+pub fn configure_mid_side(ms: &mut MidSide, state: &MasterFxState) {
+    ms.set_mid_level(state.ms_mid_level);
+    ms.set_side_level(state.ms_side_level);
+    ms.set_bass_mono(state.ms_bass_mono);
+    ms.set_side_high(state.ms_side_high);
+}
+
+/// Configure the master limiter from a MasterFxState snapshot.
+/// Used by both the LoadProject handler and the offline renderer.
+pub fn configure_limiter(limiter: &mut Limiter, state: &MasterFxState) {
+    limiter.set_ceiling(state.limiter_ceiling);
+    limiter.set_release(state.limiter_release);
+}
+
 /// Configure a TrackFxChain from a TrackFxState snapshot.
 /// Used by both the LoadProject handler and the offline renderer.
 pub fn configure_fx_chain(chain: &mut TrackFxChain, state: &TrackFxState) {
     chain.filter_enabled = state.filter_enabled;
     chain.filter.set_filter_type(state.filter_type);
-    chain.filter.set_cutoff(state.filter_cutoff);
-    chain.filter.set_resonance(state.filter_resonance);
+    chain.reset_param(FxParamId::FilterCutoff, state.filter_cutoff);
+    chain.reset_param(FxParamId::FilterResonance, state.filter_resonance);
     chain.dist_enabled = state.dist_enabled;
-    chain.distortion.set_drive(state.dist_drive);
-    chain.distortion.set_mix(state.dist_mix);
+    chain.reset_param(FxParamId::DistDrive, state.dist_drive);
+    chain.reset_param(FxParamId::DistMix, state.dist_mix);
     chain.delay_enabled = state.delay_enabled;
-    chain.delay.set_time(state.delay_time);
-    chain.delay.set_feedback(state.delay_feedback);
-    chain.delay.set_mix(state.delay_mix);
+    chain.reset_param(FxParamId::DelayTime, state.delay_time);
+    chain.reset_param(FxParamId::DelayFeedback, state.delay_feedback);
+    chain.reset_param(FxParamId::DelayMix, state.delay_mix);
+    chain.mod_target = state.mod_target;
+    chain.reset_param(FxParamId::ModDepth, state.mod_depth);
+    chain.reset_param(FxParamId::ChainMix, state.chain_mix);
+    chain.reset_bypass();
 }
@@ -1,3 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::dc_blocker::flush_denormal;
+use super::smooth::SmoothedParam;
+
+/// Gate open/close times: fast enough to chop the tail, slow enough not to click
+const GATE_ATTACK_MS: f32 = 1.0;
+const GATE_RELEASE_MS: f32 = 8.0;
+
+/// Reverb tail behaviour
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ReverbMode {
+    /// Tail decays naturally
+    #[default]
+    Normal,
+    /// Tail is cut once the dry signal stays under the gate threshold for
+    /// the hold time (80s gated snare)
+    Gated,
+}
+
+impl ReverbMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReverbMode::Normal => "normal",
+            ReverbMode::Gated => "gated",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "normal" => Some(ReverbMode::Normal),
+            "gated" | "gate" => Some(ReverbMode::Gated),
+            _ => None,
+        }
+    }
+
+    pub fn toggled(&self) -> Self {
+        match self {
+            ReverbMode::Normal => ReverbMode::Gated,
+            ReverbMode::Gated => ReverbMode::Normal,
+        }
+    }
+}
+
 /// Schroeder reverb with 4 parallel comb filters and 2 series allpass filters (stereo)
 pub struct StereoReverb {
     // Left channel: 4 comb filters + 2 allpass
@@ -7,8 +51,12 @@ pub struct StereoReverb {
     comb_r: [CombFilter; 4],
     allpass_r: [AllpassFilter; 2],
     decay: f32,
-    mix: f32,
+    mix: SmoothedParam,
     damping: f32,
+    /// Stereo width of the wet signal (0 = mono, 1 = full)
+    width: SmoothedParam,
+    mode: ReverbMode,
+    gate: Gate,
 }
 
 impl StereoReverb {
@@ -63,8 +111,11 @@ impl StereoReverb {
                 AllpassFilter::new(allpass_delays_r[1]),
             ],
             decay,
-            mix: 0.3,
+            mix: SmoothedParam::new(0.3, sample_rate),
             damping,
+            width: SmoothedParam::new(1.0, sample_rate),
+            mode: ReverbMode::Normal,
+            gate: Gate::new(sample_rate),
         }
     }
 
@@ -79,7 +130,7 @@ impl StereoReverb {
     }
 
     pub fn set_mix(&mut self, mix: f32) {
-        self.mix = mix.clamp(0.0, 1.0);
+        self.mix.set_target(mix.clamp(0.0, 1.0));
     }
 
     pub fn set_damping(&mut self, damping: f32) {
@@ -92,6 +143,24 @@ impl StereoReverb {
         }
     }
 
+    pub fn set_width(&mut self, width: f32) {
+        self.width.set_target(width.clamp(0.0, 1.0));
+    }
+
+    pub fn set_mode(&mut self, mode: ReverbMode) {
+        self.mode = mode;
+    }
+
+    /// Dry level (dBFS) that keeps the gate open in gated mode
+    pub fn set_gate_threshold(&mut self, db: f32) {
+        self.gate.threshold = 10f32.powf(db / 20.0);
+    }
+
+    /// How long the gate stays open after the dry signal drops (ms)
+    pub fn set_gate_hold(&mut self, ms: f32) {
+        self.gate.hold_samples = (ms.max(0.0) * 0.001 * self.gate.sample_rate) as usize;
+    }
+
     pub fn process_stereo(&mut self, left: f32, right: f32) -> (f32, f32) {
         // Sum of 4 parallel comb filters per channel
         let mut wet_l = 0.0f32;
@@ -114,14 +183,74 @@ impl StereoReverb {
             wet_r = ap.process(wet_r);
         }
 
-        // Dry/wet mix
-        let out_l = left * (1.0 - self.mix) + wet_l * self.mix;
-        let out_r = right * (1.0 - self.mix) + wet_r * self.mix;
+        // Gated mode: the dry signal's level opens and closes the tail
+        let open = self.mode == ReverbMode::Normal || self.gate.detect(left.abs().max(right.abs()));
+        let gate = self.gate.next(open);
+        wet_l *= gate;
+        wet_r *= gate;
+
+        // Width: scale the side component of the wet signal
+        let mid = (wet_l + wet_r) * 0.5;
+        let side = (wet_l - wet_r) * 0.5 * self.width.next();
+        let wet_l = mid + side;
+        let wet_r = mid - side;
+
+        // Dry/wet mix (ramped to avoid clicks)
+        let mix = self.mix.next();
+        let out_l = left * (1.0 - mix) + wet_l * mix;
+        let out_r = right * (1.0 - mix) + wet_r * mix;
 
         (out_l, out_r)
     }
 }
 
+/// Gate on the wet signal keyed by the dry input, with hold
+struct Gate {
+    sample_rate: f32,
+    threshold: f32,
+    hold_samples: usize,
+    hold_left: usize,
+    gain: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+}
+
+impl Gate {
+    fn new(sample_rate: f32) -> Self {
+        let coeff = |ms: f32| 1.0 - (-1.0 / (ms * 0.001 * sample_rate)).exp();
+        Self {
+            sample_rate,
+            threshold: 10f32.powf(-30.0 / 20.0),
+            hold_samples: (0.15 * sample_rate) as usize,
+            hold_left: 0,
+            gain: 1.0,
+            attack_coeff: coeff(GATE_ATTACK_MS),
+            release_coeff: coeff(GATE_RELEASE_MS),
+        }
+    }
+
+    /// Whether the gate is held open by a dry level of `level`
+    fn detect(&mut self, level: f32) -> bool {
+        if level >= self.threshold {
+            self.hold_left = self.hold_samples;
+        } else {
+            self.hold_left = self.hold_left.saturating_sub(1);
+        }
+        self.hold_left > 0
+    }
+
+    /// Gain for this sample, easing toward open (1) or closed (0)
+    fn next(&mut self, open: bool) -> f32 {
+        let (target, coeff) = if open {
+            (1.0, self.attack_coeff)
+        } else {
+            (0.0, self.release_coeff)
+        };
+        self.gain = flush_denormal(self.gain + (target - self.gain) * coeff);
+        self.gain
+    }
+}
+
 /// Comb filter with damping (one-pole LP in feedback path)
 struct CombFilter {
     buffer: Vec<f32>,
@@ -154,9 +283,10 @@ impl CombFilter {
         let delayed = self.buffer[self.pos];
 
         // One-pole LP damping in feedback path
-        self.damp_state = delayed * (1.0 - self.damping) + self.damp_state * self.damping;
+        self.damp_state =
+            flush_denormal(delayed * (1.0 - self.damping) + self.damp_state * self.damping);
 
-        self.buffer[self.pos] = input + self.damp_state * self.feedback;
+        self.buffer[self.pos] = flush_denormal(input + self.damp_state * self.feedback);
         self.pos = (self.pos + 1) % self.buffer.len();
 
         delayed
@@ -182,9 +312,36 @@ impl AllpassFilter {
         let coeff = 0.5f32;
 
         let output = -input + delayed;
-        self.buffer[self.pos] = input + delayed * coeff;
+        self.buffer[self.pos] = flush_denormal(input + delayed * coeff);
         self.pos = (self.pos + 1) % self.buffer.len();
 
         output
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comb_tail_flushes_to_exact_zero() {
+        let mut comb = CombFilter::new(1116, 0.84, 0.2);
+        comb.process(1.0);
+        for _ in 0..1116 * 300 {
+            let out = comb.process(0.0);
+            assert!(!out.is_subnormal());
+        }
+        assert!(comb.buffer.iter().all(|&s| s == 0.0));
+        assert_eq!(comb.damp_state, 0.0);
+    }
+
+    #[test]
+    fn allpass_tail_flushes_to_exact_zero() {
+        let mut allpass = AllpassFilter::new(556);
+        allpass.process(1.0);
+        for _ in 0..556 * 100 {
+            assert!(!allpass.process(0.0).is_subnormal());
+        }
+        assert!(allpass.buffer.iter().all(|&s| s == 0.0));
+    }
+}
@@ -0,0 +1,55 @@
+/// Ramp time for parameter smoothing in milliseconds
+pub const SMOOTHING_MS: f32 = 5.0;
+
+/// One-pole smoothed parameter: moves toward its target every sample so
+/// abrupt value changes don't cause zipper noise or clicks.
+#[derive(Clone, Copy, Debug)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    coeff: f32,
+}
+
+impl SmoothedParam {
+    pub fn new(value: f32, sample_rate: f32) -> Self {
+        let samples = (SMOOTHING_MS * 0.001 * sample_rate).max(1.0);
+        Self {
+            current: value,
+            target: value,
+            coeff: 1.0 - (-1.0 / samples).exp(),
+        }
+    }
+
+    /// Set the value to ramp toward
+    pub fn set_target(&mut self, value: f32) {
+        self.target = value;
+    }
+
+    /// Value being ramped toward
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Jump straight to a value (no ramp)
+    pub fn reset(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+    }
+
+    /// Whether the value is still moving toward its target
+    pub fn is_ramping(&self) -> bool {
+        self.current != self.target
+    }
+
+    /// Advance one sample and return the smoothed value
+    pub fn next(&mut self) -> f32 {
+        if self.current != self.target {
+            self.current += (self.target - self.current) * self.coeff;
+            // Snap once close enough so is_ramping() settles
+            if (self.target - self.current).abs() <= 1e-5 * self.target.abs().max(1.0) {
+                self.current = self.target;
+            }
+        }
+        self.current
+    }
+}
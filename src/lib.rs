@@ -0,0 +1,17 @@
+#![recursion_limit = "256"]
+
+pub mod app;
+pub mod audio;
+pub mod command;
+pub mod config;
+pub mod crash;
+pub mod event;
+pub mod fx;
+pub mod mcp;
+pub mod midi;
+pub mod project;
+pub mod samples;
+pub mod script;
+pub mod sequencer;
+pub mod synth;
+pub mod ui;
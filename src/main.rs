@@ -1,39 +1,100 @@
-#![recursion_limit = "256"]
-
-mod app;
-mod audio;
-mod command;
-mod event;
-mod fx;
-mod mcp;
-mod project;
-mod samples;
-mod sequencer;
-mod synth;
-mod ui;
-
-use anyhow::Result;
-use clap::Parser;
-
-use app::App;
-use mcp::run_as_proxy;
-use ui::Theme;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use clap::{ArgAction, Parser, Subcommand};
+use tracing::{info, warn, Level};
+
+use gridoxide::app::App;
+use gridoxide::config::Config;
+use gridoxide::mcp::run_as_proxy;
+use gridoxide::midi::MidiSync;
+use gridoxide::project::golden::{check_golden, golden_path_for, GoldenOutcome};
+use gridoxide::ui::Theme;
+use gridoxide::{audio, mcp, midi, samples};
 
 /// Gridoxide - Terminal EDM Production Studio
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Theme to use for the interface
-    #[arg(long, default_value = "default")]
-    theme: String,
+    /// Theme to use for the interface (overrides `theme` in config.json)
+    #[arg(long)]
+    theme: Option<String>,
 
     /// List available themes and exit
     #[arg(long)]
     list_themes: bool,
 
+    /// List output devices (for --cue) and MIDI ports (for --midi-port) and exit
+    #[arg(long)]
+    list_devices: bool,
+
     /// Run in MCP server mode (JSON-RPC over stdio)
     #[arg(long)]
     mcp: bool,
+
+    /// Render projects offline and compare against their golden files
+    /// (<project>.golden.json), then exit. Non-zero exit on mismatch.
+    #[arg(long, value_name = "PROJECT", num_args = 1..)]
+    render_check: Vec<PathBuf>,
+
+    /// With --render-check: rewrite golden files from the current render
+    #[arg(long)]
+    update_golden: bool,
+
+    /// Open this project on startup (overrides `project` in config.json)
+    #[arg(long, value_name = "PATH")]
+    project: Option<PathBuf>,
+
+    /// Start playback once the startup project is loaded
+    #[arg(long)]
+    play: bool,
+
+    /// Cue bus output for headphones: "channels" (channels 3/4 of the main
+    /// output) or the name of a second output device (overrides `cue` in
+    /// config.json)
+    #[arg(long, value_name = "TARGET")]
+    cue: Option<String>,
+
+    /// Clock source: "internal", "slave" (follow MIDI clock and
+    /// start/stop in) or "master" (send MIDI clock and start/stop out)
+    /// (overrides `midi_sync` in config.json)
+    #[arg(long, value_name = "MODE")]
+    midi_sync: Option<String>,
+
+    /// Open the MIDI input to play tracks from a controller: drum channel
+    /// (10) pads from C1 play tracks 1, 2, ..., other channels the grid
+    /// cursor track. Notes are recorded while loop-recording.
+    #[arg(long)]
+    midi_in: bool,
+
+    /// MIDI port for --midi-sync and --midi-in: the first port whose name
+    /// contains this (default: the first port, or a virtual "gridoxide" port)
+    #[arg(long, value_name = "NAME")]
+    midi_port: Option<String>,
+
+    /// Append a log (engine, MCP, commands, errors) to this file
+    #[arg(long, value_name = "FILE")]
+    log_file: Option<PathBuf>,
+
+    /// More detailed logging: -v adds command traffic, -vv everything.
+    /// Without --log-file, only headless modes log (to stderr).
+    #[arg(short, long, action = ArgAction::Count)]
+    verbose: u8,
+
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+enum CliCommand {
+    /// Print the MCP tool schema (tools/list JSON) and exit
+    McpSchema {
+        /// Print a markdown reference instead of JSON
+        #[arg(long)]
+        markdown: bool,
+    },
 }
 
 fn main() -> Result<()> {
@@ -48,6 +109,43 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Handle --list-devices
+    if args.list_devices {
+        println!("Output devices:");
+        for name in audio::output_device_names()? {
+            println!("  {}", name);
+        }
+        let (inputs, outputs) = midi::port_names()?;
+        println!("MIDI inputs:");
+        for name in inputs {
+            println!("  {}", name);
+        }
+        println!("MIDI outputs:");
+        for name in outputs {
+            println!("  {}", name);
+        }
+        return Ok(());
+    }
+
+    if let Some(CliCommand::McpSchema { markdown }) = args.command {
+        if markdown {
+            print!("{}", mcp::tools::tools_markdown());
+        } else {
+            println!("{}", serde_json::to_string_pretty(&mcp::tools::list_tools_json())?);
+        }
+        return Ok(());
+    }
+
+    // The TUI owns the terminal, so it only logs to a file
+    let headless = args.mcp || !args.render_check.is_empty();
+    init_logging(args.log_file.as_deref(), args.verbose, headless)?;
+    info!(version = env!("CARGO_PKG_VERSION"), "gridoxide starting");
+
+    // Golden render regression check (headless)
+    if !args.render_check.is_empty() {
+        return run_render_check(&args.render_check, args.update_golden);
+    }
+
     // MCP server mode — requires TUI to be running (connects via socket)
     if args.mcp {
         if let Err(e) = run_as_proxy() {
@@ -72,16 +170,99 @@ fn main() -> Result<()> {
     // Ensure sample directories exist
     samples::ensure_samples_dir();
 
+    let config = Config::load().unwrap_or_else(|e| {
+        warn!("{:#}", e);
+        eprintln!("Warning: {:#}, using default settings.", e);
+        Config::default()
+    });
+    samples::set_extra_dirs(&config.sample_dirs);
+
     // Load theme
-    let theme = Theme::from_name(&args.theme).unwrap_or_else(|| {
+    let theme_name = args.theme.or(config.theme.clone()).unwrap_or_else(|| "default".to_string());
+    let theme = Theme::from_name(&theme_name).unwrap_or_else(|| {
         eprintln!(
             "Warning: Unknown theme '{}', using default. Use --list-themes to see available themes.",
-            args.theme
+            theme_name
         );
         Theme::default()
     });
 
+    let midi_sync = match args.midi_sync.or(config.midi_sync.clone()) {
+        Some(name) => MidiSync::from_name(&name).with_context(|| {
+            format!("Unknown MIDI sync mode '{}' (internal, slave or master)", name)
+        })?,
+        None => MidiSync::Internal,
+    };
+
     // Run the TUI application
-    let mut app = App::new(theme)?;
+    let cue = args.cue.or(config.cue.clone());
+    let midi_port = args.midi_port.or(config.midi_port.clone());
+    let (midi_in, project, play) = (config.midi_in, config.project.clone(), config.play);
+    let mut app = App::new(theme, cue.as_deref(), config)?;
+    app.set_midi_port(midi_port);
+    if midi_sync != MidiSync::Internal {
+        app.set_midi_sync(midi_sync);
+    }
+    if args.midi_in || midi_in {
+        app.open_midi_input();
+    }
+    if let Some(path) = args.project.or(project) {
+        app.open_on_start(path, args.play || play);
+    }
     app.run()
 }
+
+/// Install the log subscriber: to `log_file` if given, else to stderr for
+/// headless runs with -v. Otherwise logging stays off.
+fn init_logging(log_file: Option<&Path>, verbose: u8, headless: bool) -> Result<()> {
+    let level = match verbose {
+        0 => Level::INFO,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    let builder = tracing_subscriber::fmt().with_max_level(level);
+    if let Some(path) = log_file {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        builder.with_ansi(false).with_writer(Mutex::new(file)).init();
+    } else if headless && verbose > 0 {
+        builder.with_writer(std::io::stderr).init();
+    }
+    Ok(())
+}
+
+/// Render each project and compare it against its golden file
+fn run_render_check(projects: &[PathBuf], update: bool) -> Result<()> {
+    let mut failures = 0;
+    for project in projects {
+        let golden = golden_path_for(project);
+        match check_golden(project, &golden, update)? {
+            GoldenOutcome::Updated(render) => {
+                println!("WROTE    {} ({} frames, {})", golden.display(), render.frames, render.hash);
+            }
+            GoldenOutcome::Missing => {
+                println!("MISSING  {} (use --update-golden to write it)", golden.display());
+                failures += 1;
+            }
+            GoldenOutcome::Match => println!("OK       {}", project.display()),
+            GoldenOutcome::Close { max_band_diff_db } => {
+                println!(
+                    "CLOSE    {} (not bit-exact, spectrum within {:.2} dB)",
+                    project.display(),
+                    max_band_diff_db
+                );
+            }
+            GoldenOutcome::Mismatch { reason } => {
+                println!("MISMATCH {}: {}", project.display(), reason);
+                failures += 1;
+            }
+        }
+    }
+    if failures > 0 {
+        anyhow::bail!("{} of {} renders differ from golden", failures, projects.len());
+    }
+    Ok(())
+}
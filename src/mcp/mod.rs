@@ -1,5 +1,6 @@
 pub mod server;
 pub mod socket;
+pub mod tools;
 
 pub use server::GridoxideMcp;
 pub use socket::{run_as_proxy, start_socket_server};
@@ -1,24 +1,55 @@
 use std::path::Path;
 use std::sync::Arc;
 
+use crossbeam_channel::unbounded;
 use parking_lot::RwLock;
 use serde_json::{json, Value};
-
-use crate::audio::SequencerState;
-use crate::command::{Command, CommandSender, CommandSource};
-use crate::event::EventLog;
-use crate::fx::{FilterType, FxParamId, FxType, MasterFxParamId};
-use crate::project;
-use crate::project::renderer::{ExportMode, export_wav};
+use tracing::warn;
+
+use crate::audio::{
+    capture_buffer, level_db, LevelMeter, SequencerState, CLIP_LEVEL, MAX_CAPTURE_STEPS, MAX_GLYPH_CHARS, MAX_PREVIEW_DUCK_DB,
+};
+use crate::command::{Command, CommandSender, CommandSource, MacroStore, QUEUE_CAPACITY};
+use crate::config::Config;
+use crate::event::{format_duration, EventLog, SessionStats};
+use crate::fx::{FilterType, FxParamId, FxType, MasterFxParamId, ModTarget, ReverbMode};
+use crate::midi::MidiSync;
+use crate::project::{self, ProjectData};
+use crate::project::backup::rotate_backups;
+use crate::project::gain::{suggest_gain_staging, GAIN_CEILING_DB};
+use crate::project::interchange::{InterchangeFormat, PatternExchange};
+use crate::project::kit::assign_kit;
+use crate::project::midi::{export_midi, import_midi};
+use crate::project::queue::{ExportQueue, ExportTarget, JobInfo};
+use crate::project::renderer::{export_hits, export_wav, ExportMode};
+use crate::project::stems::export_stems;
+use crate::project::template::{add_template_track, TemplateStore};
 use crate::samples;
-use crate::sequencer::{PlaybackMode, Variation, NUM_PATTERNS};
-use crate::synth::{create_synth, load_wav, note_name, ParamDescriptor, SynthType};
+use crate::script::{self, Script, MAX_SCRIPTS};
+use crate::sequencer::analysis::CROWDED_HITS;
+use crate::sequencer::{
+    chord_label, pattern_label, random_seed, step_density, triplet_steps, PlaybackMode, TrackAutomation, Variation,
+    CHORD_DEGREES, MAX_FADE_OUT_BARS, MAX_LOCKS_PER_STEP, MAX_NUDGE_MS, MAX_REPEATS, MAX_STEPS, MAX_SWING, MAX_TRACKS, MAX_TRACK_TRANSPOSE,
+    MAX_TRANSPOSE, NUM_PATTERNS, PATTERNS_PER_BANK, STEPS,
+};
+use crate::synth::{
+    bpm_pitch_shift, create_synth, key_pitch_shift, load_wav, note_name, param_tuning, parse_key, prepare_sample,
+    split_cents, ExternalConnection, Interpolation, ParamDescriptor, ParkedSynths, SampleAudio, SampleInfo, SampleOptions, SynthType, ToneMode,
+    MAX_SAMPLE_LAYERS, NOTE_NAMES,
+};
+
+use super::tools;
 
 /// MCP server handler for gridoxide
 pub struct GridoxideMcp {
     command_sender: CommandSender,
     event_log: Arc<RwLock<EventLog>>,
     sequencer_state: Arc<RwLock<SequencerState>>,
+    /// Background exports, shared with the TUI
+    exports: Arc<ExportQueue>,
+    /// Plugin commands started through load_external_synth this session,
+    /// allowed when a project load asks for them again
+    trusted_plugins: RwLock<Vec<String>>,
 }
 
 impl GridoxideMcp {
@@ -26,18 +57,27 @@ impl GridoxideMcp {
         command_sender: CommandSender,
         event_log: Arc<RwLock<EventLog>>,
         sequencer_state: Arc<RwLock<SequencerState>>,
+        exports: Arc<ExportQueue>,
     ) -> Self {
         Self {
             command_sender,
             event_log,
             sequencer_state,
+            exports,
+            trusted_plugins: RwLock::new(Vec::new()),
         }
     }
 
     /// Dispatch a command and log it
     fn dispatch(&self, cmd: Command) {
+        // Normalize/trim changes re-read the sample here instead of on the audio thread
+        let loads = project::sample_option_reload(&self.sequencer_state.read(), &cmd);
+        self.sequencer_state.write().apply_shared(&cmd);
         self.event_log.write().log(cmd.clone(), CommandSource::Mcp);
         self.command_sender.send(cmd, CommandSource::Mcp);
+        for load in loads.into_iter().flatten() {
+            self.dispatch(load);
+        }
     }
 
     /// Get the current number of tracks
@@ -45,6 +85,11 @@ impl GridoxideMcp {
         self.sequencer_state.read().num_tracks()
     }
 
+    /// Output sample rate that loaded sample buffers are resampled to
+    fn sample_rate(&self) -> f32 {
+        self.sequencer_state.read().sample_rate
+    }
+
     /// Validate track index, returning error JSON if out of range
     fn validate_track(&self, track: usize) -> Option<Value> {
         let n = self.num_tracks();
@@ -55,6 +100,21 @@ impl GridoxideMcp {
         }
     }
 
+    /// Check a step exists on a track of the current pattern (its length,
+    /// or fewer steps on a triplet track)
+    fn validate_step(&self, track: usize, step: usize) -> Option<Value> {
+        let state = self.sequencer_state.read();
+        let length = state.pattern.length;
+        if step >= length {
+            return Some(json!({ "status": "error", "message": format!("Step must be 0-{}", length - 1) }));
+        }
+        let steps = triplet_steps(length);
+        if step >= steps && state.tracks.get(track).is_some_and(|t| t.triplet) {
+            return Some(json!({ "status": "error", "message": format!("Triplet tracks use steps 0-{}", steps - 1) }));
+        }
+        None
+    }
+
     /// Get track name from state
     fn track_name(&self, track: usize) -> String {
         let state = self.sequencer_state.read();
@@ -78,8 +138,15 @@ impl GridoxideMcp {
     // === Transport Tools ===
 
     pub fn play(&self) -> Value {
+        let state = self.sequencer_state.read();
+        let warnings = if state.playback_mode == PlaybackMode::Song {
+            state.song_warnings()
+        } else {
+            Vec::new()
+        };
+        drop(state);
         self.dispatch(Command::Play);
-        json!({ "status": "ok", "message": "Playback started" })
+        with_warnings(json!({ "status": "ok", "message": "Playback started" }), warnings)
     }
 
     pub fn pause(&self) -> Value {
@@ -98,6 +165,61 @@ impl GridoxideMcp {
         json!({ "status": "ok", "bpm": bpm })
     }
 
+    pub fn set_midi_sync(&self, mode: &str) -> Value {
+        let Some(sync) = MidiSync::from_name(mode) else {
+            return json!({
+                "status": "error",
+                "message": format!("Unknown MIDI sync mode '{}' (internal, slave or master)", mode)
+            });
+        };
+        self.dispatch(Command::SetMidiSync(sync));
+        json!({ "status": "ok", "midi_sync": sync.name() })
+    }
+
+    pub fn play_tone(&self, mode: Option<&str>, settings: &[(&str, f32)]) -> Value {
+        let mode = match mode.map(ToneMode::from_name) {
+            Some(None) => {
+                return json!({
+                    "status": "error",
+                    "message": "Mode must be 'sine', 'sweep', 'pink' or 'siren'"
+                });
+            }
+            Some(Some(mode)) => Some(mode),
+            None => None,
+        };
+        if let Some(mode) = mode {
+            self.dispatch(Command::SetToneParam { key: "mode".to_string(), value: mode.param() });
+        }
+        for &(key, value) in settings {
+            self.dispatch(Command::SetToneParam { key: key.to_string(), value });
+        }
+        self.dispatch(Command::PlayTone);
+        let mode = mode.unwrap_or_else(|| ToneMode::from_param(self.sequencer_state.read().tone.mode));
+        json!({ "status": "ok", "mode": mode.name() })
+    }
+
+    pub fn stop_tone(&self) -> Value {
+        self.dispatch(Command::StopTone);
+        json!({ "status": "ok" })
+    }
+
+    pub fn set_swing(&self, amount: u64) -> Value {
+        let swing = amount.min(MAX_SWING as u64) as u8;
+        self.dispatch(Command::SetSwing(swing));
+        json!({ "status": "ok", "swing": swing, "message": format!("Swing {}%", swing) })
+    }
+
+    pub fn set_transpose(&self, semitones: i64) -> Value {
+        let max = MAX_TRANSPOSE as i64;
+        let transpose = semitones.clamp(-max, max) as i8;
+        self.dispatch(Command::SetTranspose(transpose));
+        json!({
+            "status": "ok",
+            "transpose": transpose,
+            "message": format!("Transpose {:+} semitones", transpose)
+        })
+    }
+
     pub fn get_state(&self) -> Value {
         let state = self.sequencer_state.read();
         let mode_str = match state.playback_mode {
@@ -113,11 +235,166 @@ impl GridoxideMcp {
             "bpm": state.bpm,
             "current_step": state.current_step,
             "current_pattern": state.current_pattern,
+            "pattern_length": state.pattern.length,
             "playback_mode": mode_str,
             "arrangement_position": state.arrangement_position,
             "arrangement_repeat": state.arrangement_repeat,
             "num_tracks": state.tracks.len(),
-            "current_variation": var_str
+            "current_variation": var_str,
+            "transpose": state.transpose,
+            "swing": state.swing,
+            "midi_sync": state.midi_sync.name(),
+            "tone": {
+                "playing": state.tone_playing,
+                "mode": ToneMode::from_param(state.tone.mode).name(),
+                "freq": state.tone.freq,
+                "level": state.tone.level,
+                "rate": state.tone.rate,
+                "length": state.tone.length
+            },
+            "capture": state.capture.map(|c| json!({
+                "track": c.track,
+                "steps": c.steps,
+                "recording": c.progress.is_some(),
+                "progress": c.progress.unwrap_or(0.0),
+                "underruns": c.underruns
+            })),
+            "sample_rate": state.sample_rate
+        })
+    }
+
+    /// Plain-text rendering of a pattern grid with the playhead, so agents
+    /// can see the pattern the way it looks in the TUI
+    pub fn get_grid_view(&self, pattern_index: Option<usize>, variation: Option<&str>) -> Value {
+        let state = self.sequencer_state.read();
+        let index = pattern_index.unwrap_or(state.current_pattern);
+        if index >= NUM_PATTERNS {
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        let variation = match variation {
+            None => state.current_variation,
+            Some("a") | Some("A") => Variation::A,
+            Some("b") | Some("B") => Variation::B,
+            Some(_) => return json!({ "status": "error", "message": "Variation must be 'a' or 'b'" }),
+        };
+        let is_current = index == state.current_pattern;
+        let pat = if is_current {
+            &state.pattern
+        } else {
+            state.pattern_bank.get(index)
+        };
+
+        let name_width = state
+            .tracks
+            .iter()
+            .map(|t| t.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .clamp(4, 10);
+        // "NN " + name + " MS3 "
+        let prefix = 3 + name_width + 5;
+
+        let mut lines = Vec::new();
+        lines.push(format!(
+            "Pattern {:02} [{}]  {:.0} BPM  {}  step {:02}/{}",
+            index,
+            if variation == Variation::A { "A" } else { "B" },
+            state.bpm,
+            if state.playing { "PLAYING" } else { "STOPPED" },
+            state.current_step + 1,
+            pat.length
+        ));
+        let mut ruler = " ".repeat(prefix);
+        for step in 0..pat.length {
+            ruler.push(if step % 4 == 0 { '|' } else { ' ' });
+        }
+        lines.push(ruler);
+
+        for (t, track) in state.tracks.iter().enumerate().take(pat.num_tracks()) {
+            let name: String = track.name.chars().take(name_width).collect();
+            let mut row = format!(
+                "{:2} {:<width$} {}{}{} ",
+                t,
+                name,
+                if track.mute { 'M' } else { ' ' },
+                if track.solo { 'S' } else { ' ' },
+                if track.triplet { '3' } else { ' ' },
+                width = name_width
+            );
+            // Triplet steps sit at their bar position; the gaps are left blank
+            let steps = if track.triplet { triplet_steps(pat.length) } else { pat.length };
+            let mut cells = vec![' '; pat.length];
+            for step in 0..steps {
+                let sd = pat.get_step_var(t, step, variation);
+                cells[step * pat.length / steps] = match (sd.active, sd.probability < 100, sd.velocity >= 100) {
+                    (false, _, _) if sd.off => ']',
+                    (false, _, _) => '.',
+                    _ if pat.is_tied_var(t, step, pat.track_steps(t, track.triplet), variation) => '=',
+                    (true, true, _) => '?',
+                    (true, false, true) => 'X',
+                    (true, false, false) => 'x',
+                };
+            }
+            row.extend(cells);
+            lines.push(row);
+        }
+
+        // The playhead only applies to the pattern that is playing
+        if is_current && variation == state.current_variation {
+            lines.push(format!("{}^", " ".repeat(prefix + state.current_step.min(pat.length - 1))));
+        }
+        lines.push(
+            "X hit  x soft hit (vel<100)  ? probability<100  = tied  ] note-off  . off  M mute  S solo  3 triplet".to_string(),
+        );
+
+        json!({
+            "status": "ok",
+            "pattern": index,
+            "view": lines.join("\n")
+        })
+    }
+
+    /// Density per 16th step across all tracks: hits, velocity/probability
+    /// weighted energy and heat (1.0 = CROWDED_HITS full hits)
+    pub fn analyze_pattern(&self, pattern_index: Option<usize>, variation: Option<&str>) -> Value {
+        let state = self.sequencer_state.read();
+        let index = pattern_index.unwrap_or(state.current_pattern);
+        if index >= NUM_PATTERNS {
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        let variation = match variation {
+            None => state.current_variation,
+            Some("a") | Some("A") => Variation::A,
+            Some("b") | Some("B") => Variation::B,
+            Some(_) => return json!({ "status": "error", "message": "Variation must be 'a' or 'b'" }),
+        };
+        let pat = if index == state.current_pattern {
+            &state.pattern
+        } else {
+            state.pattern_bank.get(index)
+        };
+        let triplets: Vec<bool> = state.tracks.iter().map(|t| t.triplet).collect();
+        let density = step_density(pat, variation, &triplets);
+
+        let steps: Vec<Value> = density
+            .iter()
+            .enumerate()
+            .map(|(step, d)| json!({ "step": step, "hits": d.hits, "energy": d.energy, "heat": d.heat }))
+            .collect();
+        let empty: Vec<usize> = (0..density.len()).filter(|&s| density[s].hits == 0).collect();
+        let crowded: Vec<usize> = (0..density.len()).filter(|&s| density[s].heat >= 1.0).collect();
+        let busiest = (0..density.len()).max_by(|&a, &b| density[a].energy.total_cmp(&density[b].energy));
+        let total_hits: usize = density.iter().map(|d| d.hits).sum();
+        json!({
+            "status": "ok",
+            "pattern": index,
+            "variation": if variation == Variation::A { "a" } else { "b" },
+            "steps": steps,
+            "average_hits": total_hits as f32 / density.len() as f32,
+            "busiest_step": busiest.filter(|_| total_hits > 0),
+            "empty_steps": empty,
+            "crowded_steps": crowded,
+            "crowded_hits": CROWDED_HITS
         })
     }
 
@@ -127,8 +404,8 @@ impl GridoxideMcp {
         if let Some(err) = self.validate_track(track) {
             return err;
         }
-        if step >= 16 {
-            return json!({ "status": "error", "message": "Step must be 0-15" });
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
         }
 
         if let Some(n) = note {
@@ -168,8 +445,9 @@ impl GridoxideMcp {
                 } else {
                     60
                 };
-                let steps: Vec<bool> = (0..16).map(|step| pat.get(track, step)).collect();
-                let notes: Vec<Value> = (0..16)
+                let triplet = state.tracks.get(track).is_some_and(|t| t.triplet);
+                let steps: Vec<bool> = (0..pat.length).map(|step| pat.get(track, step)).collect();
+                let notes: Vec<Value> = (0..pat.length)
                     .map(|step| {
                         let sd = pat.get_step(track, step);
                         json!({
@@ -183,13 +461,18 @@ impl GridoxideMcp {
                     "name": name,
                     "steps": steps,
                     "notes": notes,
-                    "default_note": default_note
+                    "default_note": default_note,
+                    // Triplet tracks only play the first triplet_steps(length) steps
+                    "triplet": triplet,
+                    // Own loop length, 0 = loops with the pattern
+                    "track_length": pat.track_length(track)
                 })
             })
             .collect();
 
         json!({
             "pattern": display_idx,
+            "length": pat.length,
             "tracks": tracks
         })
     }
@@ -198,8 +481,8 @@ impl GridoxideMcp {
         if let Some(err) = self.validate_track(track) {
             return err;
         }
-        if step >= 16 {
-            return json!({ "status": "error", "message": "Step must be 0-15" });
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
         }
         let clamped = note.min(127);
         self.dispatch(Command::SetStepNote { track, step, note: clamped });
@@ -223,7 +506,7 @@ impl GridoxideMcp {
         let state = self.sequencer_state.read();
         let track_name = state.tracks[track].name.clone();
         let default_note = state.tracks[track].default_note;
-        let steps: Vec<Value> = (0..16)
+        let steps: Vec<Value> = (0..state.pattern.length)
             .map(|step| {
                 let sd = state.pattern.get_step(track, step);
                 json!({
@@ -232,7 +515,12 @@ impl GridoxideMcp {
                     "note": sd.note,
                     "note_name": note_name(sd.note),
                     "velocity": sd.velocity,
-                    "probability": sd.probability
+                    "probability": sd.probability,
+                    "offset": sd.offset,
+                    "tie": sd.tie,
+                    "note_off": sd.off && !sd.active,
+                    "slide": sd.slide,
+                    "accent": sd.accent
                 })
             })
             .collect();
@@ -245,12 +533,46 @@ impl GridoxideMcp {
         })
     }
 
+    pub fn pad_hit(&self, track: usize, velocity: u8, record: bool) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let velocity = velocity.clamp(1, 127);
+        let playing = self.sequencer_state.read().playing;
+        self.dispatch(Command::PadHit { track, velocity, record });
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "velocity": velocity,
+            "recorded": record && playing
+        })
+    }
+
+    pub fn scrub_step(&self, step: usize) -> Value {
+        let state = self.sequencer_state.read();
+        if state.playing {
+            return json!({ "status": "error", "message": "Stop playback to scrub" });
+        }
+        let length = state.pattern.length;
+        drop(state);
+        if step >= length {
+            return json!({ "status": "error", "message": format!("Step must be 0-{}", length - 1) });
+        }
+        self.dispatch(Command::ScrubStep(step));
+        json!({
+            "status": "ok",
+            "step": step
+        })
+    }
+
     pub fn set_step_velocity(&self, track: usize, step: usize, velocity: u8) -> Value {
         if let Some(err) = self.validate_track(track) {
             return err;
         }
-        if step >= 16 {
-            return json!({ "status": "error", "message": "Step must be 0-15" });
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
         }
         let clamped = velocity.min(127);
         self.dispatch(Command::SetStepVelocity { track, step, velocity: clamped });
@@ -269,8 +591,8 @@ impl GridoxideMcp {
         if let Some(err) = self.validate_track(track) {
             return err;
         }
-        if step >= 16 {
-            return json!({ "status": "error", "message": "Step must be 0-15" });
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
         }
         let clamped = probability.min(100);
         self.dispatch(Command::SetStepProbability { track, step, probability: clamped });
@@ -285,6 +607,178 @@ impl GridoxideMcp {
         })
     }
 
+    pub fn set_step_tie(&self, track: usize, step: usize, tie: bool) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
+        }
+        self.dispatch(Command::SetStepTie { track, step, tie });
+
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "step": step,
+            "tie": tie
+        })
+    }
+
+    pub fn set_step_off(&self, track: usize, step: usize, off: bool) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
+        }
+        self.dispatch(Command::SetStepOff { track, step, off });
+
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "step": step,
+            "note_off": off
+        })
+    }
+
+    pub fn set_step_slide(&self, track: usize, step: usize, slide: bool) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
+        }
+        self.dispatch(Command::SetStepSlide { track, step, slide });
+
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "step": step,
+            "slide": slide
+        })
+    }
+
+    pub fn set_step_accent(&self, track: usize, step: usize, accent: bool) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
+        }
+        self.dispatch(Command::SetStepAccent { track, step, accent });
+
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "step": step,
+            "accent": accent
+        })
+    }
+
+    /// Lock (or with None unlock) a synth param on a step of the current
+    /// pattern and variation
+    pub fn set_param_lock(&self, track: usize, step: usize, key: &str, value: Option<f32>) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
+        }
+        let Some(desc) = self.get_param_descriptors(track).into_iter().find(|d| d.key == key) else {
+            return json!({
+                "status": "error",
+                "message": format!("Unknown parameter '{}' for track {}. Use get_track_params to see available keys.", key, track)
+            });
+        };
+        if !desc.lockable() {
+            return json!({
+                "status": "error",
+                "message": format!("'{}' applies when a sample loads and cannot be locked per step", key)
+            });
+        }
+        let state = self.sequencer_state.read();
+        let variation = state.current_variation;
+        let locked = state.pattern.param_locks(track, step, variation).count();
+        let exists = state.pattern.param_lock(track, step, variation, key).is_some();
+        drop(state);
+        if value.is_some() && !exists && locked >= MAX_LOCKS_PER_STEP {
+            return json!({
+                "status": "error",
+                "message": format!("Step {} already has {} locks (the most a step holds)", step, MAX_LOCKS_PER_STEP)
+            });
+        }
+
+        let value = value.map(|v| v.clamp(desc.min, desc.max));
+        self.dispatch(Command::SetParamLock { track, step, key: key.to_string(), value });
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": self.track_name(track),
+            "step": step,
+            "param": key,
+            "value": value,
+            "min": desc.min,
+            "max": desc.max
+        })
+    }
+
+    pub fn clear_param_locks(&self, track: usize, step: usize) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if let Some(err) = self.validate_step(track, step) {
+            return err;
+        }
+        self.dispatch(Command::ClearParamLocks { track, step });
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": self.track_name(track),
+            "step": step
+        })
+    }
+
+    /// Parameter locks of the current pattern and variation
+    pub fn get_param_locks(&self, track: Option<usize>) -> Value {
+        if let Some(err) = track.and_then(|t| self.validate_track(t)) {
+            return err;
+        }
+        let state = self.sequencer_state.read();
+        let variation = state.current_variation;
+        let mut locks: Vec<_> = state
+            .pattern
+            .param_locks
+            .iter()
+            .filter(|l| l.variation == variation && track.is_none_or(|t| l.track == t))
+            .collect();
+        locks.sort_by_key(|l| (l.track, l.step));
+        let locks: Vec<Value> = locks
+            .into_iter()
+            .map(|l| {
+                json!({
+                    "track": l.track,
+                    "step": l.step,
+                    "param": l.key,
+                    "value": l.value,
+                    "track_value": state.tracks.get(l.track).and_then(|t| t.params_snapshot.get(&l.key))
+                })
+            })
+            .collect();
+        json!({
+            "pattern": state.current_pattern,
+            "variation": format!("{:?}", variation),
+            "locks": locks
+        })
+    }
+
     pub fn clear_track(&self, track: usize) -> Value {
         if let Some(err) = self.validate_track(track) {
             return err;
@@ -299,6 +793,22 @@ impl GridoxideMcp {
         })
     }
 
+    pub fn quantize_track(&self, track: usize, strength: u8) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let strength = strength.min(100);
+        self.dispatch(Command::QuantizeTrack { track, strength });
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "strength": strength,
+            "message": format!("Quantized {} by {}%", track_name, strength)
+        })
+    }
+
     pub fn fill_track(&self, track: usize) -> Value {
         if let Some(err) = self.validate_track(track) {
             return err;
@@ -318,12 +828,93 @@ impl GridoxideMcp {
     pub fn get_events(&self, since_id: u64) -> Value {
         let log = self.event_log.read();
         let events = log.get_events_since(since_id);
+        let errors = log.get_errors_since(since_id);
         json!({
             "events": events,
+            "errors": errors,
             "latest_id": log.latest_id()
         })
     }
 
+    pub fn get_command_stats(&self, dropped_limit: usize) -> Value {
+        let stats = self.command_sender.stats();
+
+        let mut by_type: Vec<(&str, u64, u64)> = Vec::new();
+        for (&(name, source), &count) in &stats.dispatched {
+            let idx = match by_type.iter().position(|(n, _, _)| *n == name) {
+                Some(i) => i,
+                None => {
+                    by_type.push((name, 0, 0));
+                    by_type.len() - 1
+                }
+            };
+            match source {
+                CommandSource::Tui => by_type[idx].1 += count,
+                CommandSource::Mcp => by_type[idx].2 += count,
+            }
+        }
+        by_type.sort_by(|a, b| (b.1 + b.2).cmp(&(a.1 + a.2)).then(a.0.cmp(b.0)));
+        let dispatched_total: u64 = by_type.iter().map(|(_, t, m)| t + m).sum();
+
+        let commands: Vec<Value> = by_type
+            .iter()
+            .map(|(name, tui, mcp)| {
+                json!({ "command": name, "total": tui + mcp, "tui": tui, "mcp": mcp })
+            })
+            .collect();
+        let dropped: Vec<Value> = stats
+            .recent_dropped
+            .iter()
+            .rev()
+            .take(dropped_limit)
+            .map(|d| {
+                json!({
+                    "timestamp": d.timestamp,
+                    "source": d.source,
+                    "command": d.name,
+                    "description": d.description
+                })
+            })
+            .collect();
+
+        json!({
+            "queue": {
+                "depth": stats.queue_depth,
+                "capacity": QUEUE_CAPACITY,
+                "max_depth": stats.max_queue_depth
+            },
+            "dispatched_total": dispatched_total,
+            "processed_total": stats.processed,
+            "dropped_total": stats.dropped_total,
+            "commands": commands,
+            "recent_dropped": dropped
+        })
+    }
+
+    pub fn get_session_stats(&self) -> Value {
+        let stats = SessionStats::collect(&self.event_log.read(), &self.sequencer_state.read());
+        json!({
+            "session_secs": stats.session_secs,
+            "active_secs": stats.active_secs,
+            "edits": stats.edits,
+            "agent_edits": stats.agent_edits,
+            "tracks": stats.tracks,
+            "patterns_used": stats.patterns_used,
+            "arranged_patterns": stats.arranged_patterns,
+            "song_bars": stats.song_bars,
+            "song_secs": stats.song_secs,
+            "summary": format!(
+                "{} open, {} spent, {} edits ({} by agents); {} patterns used, song {}",
+                format_duration(stats.session_secs),
+                format_duration(stats.active_secs),
+                stats.edits,
+                stats.agent_edits,
+                stats.patterns_used,
+                format_duration(stats.song_secs.round() as u64)
+            )
+        })
+    }
+
     // === Track Parameter Tools ===
 
     pub fn list_tracks(&self) -> Value {
@@ -341,6 +932,7 @@ impl GridoxideMcp {
                 json!({
                     "track": i,
                     "name": track.name,
+                    "glyph": track.display_glyph(),
                     "synth_type": track.synth_type.name(),
                     "params": param_keys,
                     "param_names": param_names
@@ -369,23 +961,31 @@ impl GridoxideMcp {
                     .get(&desc.key)
                     .and_then(|v| v.as_f64())
                     .unwrap_or(desc.default as f64) as f32;
-                json!({
+                let mut param = json!({
                     "key": desc.key,
                     "name": desc.name,
                     "value": value,
                     "min": desc.min,
                     "max": desc.max,
                     "default": desc.default
-                })
+                });
+                add_tuning(&mut param, track_state.synth_type, &desc.key, value);
+                param
             })
             .collect();
 
-        json!({
+        let mut result = json!({
             "track": track,
             "name": track_state.name,
             "synth_type": track_state.synth_type.name(),
             "params": param_values
-        })
+        });
+        if track_state.synth_type == SynthType::Sampler {
+            let detected = track_state.params_snapshot.get("detected_note").and_then(|v| v.as_f64());
+            result["detected_root"] = json!(detected.map(|n| root_json(n as f32)));
+            result["detected_bpm"] = json!(track_state.params_snapshot.get("detected_bpm"));
+        }
+        result
     }
 
     /// Set a single parameter by key. Supports both old-style prefixed keys
@@ -468,7 +1068,7 @@ impl GridoxideMcp {
                     key: key.to_string(),
                     value: clamped,
                 });
-                return json!({
+                let mut result = json!({
                     "status": "ok",
                     "track": track,
                     "param": key,
@@ -477,6 +1077,9 @@ impl GridoxideMcp {
                     "min": desc.min,
                     "max": desc.max
                 });
+                let synth_type = self.sequencer_state.read().tracks[track].synth_type;
+                add_tuning(&mut result, synth_type, key, clamped);
+                return result;
             }
         }
 
@@ -523,7 +1126,7 @@ impl GridoxideMcp {
             None => {
                 return json!({
                     "status": "error",
-                    "message": format!("Unknown synth type: '{}'. Valid: kick, snare, hihat, bass, sampler", synth_type_str)
+                    "message": format!("Unknown synth type: '{}'. Valid: kick, snare, hihat, bass, sampler, input, external, tone, stab, acid", synth_type_str)
                 });
             }
         };
@@ -532,6 +1135,9 @@ impl GridoxideMcp {
         if playing {
             return json!({ "status": "error", "message": "Cannot add track while playing. Stop playback first." });
         }
+        if self.num_tracks() >= MAX_TRACKS {
+            return json!({ "status": "error", "message": format!("Max {} tracks", MAX_TRACKS) });
+        }
 
         self.dispatch(Command::AddTrack {
             synth_type,
@@ -582,14 +1188,122 @@ impl GridoxideMcp {
                 json!({
                     "track": i,
                     "name": t.name,
+                    "glyph": t.display_glyph(),
                     "volume": t.volume,
                     "pan": t.pan,
                     "mute": t.mute,
-                    "solo": t.solo
+                    "solo": t.solo,
+                    "solo_safe": t.solo_safe,
+                    "cue": t.cue,
+                    "nudge_ms": t.nudge_ms,
+                    "transpose": t.transpose
                 })
             })
             .collect();
-        json!({ "tracks": tracks })
+        json!({ "tracks": tracks, "cue_output": state.cue_output })
+    }
+
+    /// Peak/RMS levels and clip counts per track (post-fader) and on the
+    /// master (after the limiter, before the soft clipper), with the master
+    /// true peak and limiter gain reduction. `reset_clips` clears the counts
+    /// and the true-peak maximum after reading.
+    pub fn get_levels(&self, reset_clips: bool) -> Value {
+        let state = self.sequencer_state.read();
+        let meter_json = |meter: &LevelMeter| {
+            json!({
+                "peak_db": level_db(meter.peak),
+                "peak_hold_db": level_db(meter.peak_hold),
+                "rms_db": level_db(meter.rms),
+                "clips": meter.clips,
+                "clipping": meter.clipping()
+            })
+        };
+        let tracks: Vec<Value> = state
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let mut level = meter_json(&t.meter);
+                level["track"] = json!(i);
+                level["name"] = json!(t.name);
+                level
+            })
+            .collect();
+        let mut master = meter_json(&state.master_meter);
+        master["true_peak_max_db"] = json!(level_db(state.master_true_peak));
+        master["limiter"] = json!({
+            "enabled": state.master_fx.limiter_enabled,
+            "ceiling_db": state.master_fx.limiter_ceiling,
+            "gain_reduction_db": state.limiter_reduction
+        });
+        drop(state);
+        if reset_clips {
+            self.dispatch(Command::ResetClipIndicators);
+        }
+        json!({
+            "clip_level_db": level_db(CLIP_LEVEL),
+            "tracks": tracks,
+            "master": master
+        })
+    }
+
+    pub fn suggest_gain_staging(&self, mode: Option<&str>, pattern: Option<usize>, apply: bool) -> Value {
+        let state = self.sequencer_state.read().clone();
+        let song = state.playback_mode == PlaybackMode::Song && !state.arrangement.entries.is_empty();
+        let export_mode = match mode.unwrap_or(if song { "song" } else { "pattern" }) {
+            "pattern" => {
+                let idx = pattern.unwrap_or(state.current_pattern);
+                if idx >= NUM_PATTERNS {
+                    return json!({ "status": "error", "message": format!("Pattern index must be 0-{}", NUM_PATTERNS - 1) });
+                }
+                ExportMode::Pattern(idx)
+            }
+            "song" => ExportMode::Song,
+            _ => {
+                return json!({
+                    "status": "error",
+                    "message": "Mode must be 'pattern' or 'song'"
+                })
+            }
+        };
+
+        let staging = suggest_gain_staging(&state, &export_mode, state.sample_rate as u32);
+        let tracks: Vec<Value> = staging
+            .trims
+            .iter()
+            .map(|t| {
+                json!({
+                    "track": t.track,
+                    "name": state.tracks[t.track].name,
+                    "rms_db": (t.rms_db * 10.0).round() / 10.0,
+                    "volume": t.volume,
+                    "suggested_volume": t.suggested,
+                    "trim_db": (t.trim_db() * 10.0).round() / 10.0
+                })
+            })
+            .collect();
+        let applied = apply && staging.changes();
+        if applied {
+            for trim in staging.trims.iter().filter(|t| t.suggested != t.volume) {
+                self.dispatch(Command::SetTrackVolume { track: trim.track, volume: trim.suggested });
+            }
+        }
+        json!({
+            "status": "ok",
+            "target_rms_db": (staging.target_db * 10.0).round() / 10.0,
+            "ceiling_db": GAIN_CEILING_DB,
+            "master_peak_db": (staging.peak_db * 10.0).round() / 10.0,
+            "suggested_master_peak_db": (staging.suggested_peak_db * 10.0).round() / 10.0,
+            "tracks": tracks,
+            "applied": applied,
+            "message": if applied {
+                "Suggested volumes applied".to_string()
+            } else if staging.changes() {
+                "Call again with apply: true to set these volumes".to_string()
+            } else {
+                "Levels already balanced".to_string()
+            }
+        })
     }
 
     pub fn set_volume(&self, track: usize, volume: f32) -> Value {
@@ -622,6 +1336,77 @@ impl GridoxideMcp {
         })
     }
 
+    pub fn set_track_nudge(&self, track: usize, ms: f32) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let ms = ms.clamp(0.0, MAX_NUDGE_MS);
+        self.dispatch(Command::SetTrackNudge { track, ms });
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "nudge_ms": ms
+        })
+    }
+
+    pub fn set_track_transpose(&self, track: usize, semitones: i64) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let max = MAX_TRACK_TRANSPOSE as i64;
+        let semitones = semitones.clamp(-max, max) as i8;
+        self.dispatch(Command::SetTrackTranspose { track, semitones });
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "transpose": semitones
+        })
+    }
+
+    /// Set (or with None, reset) the 1-2 char marker before a track's name
+    pub fn set_track_glyph(&self, track: usize, glyph: Option<&str>) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let glyph = glyph.map(str::trim).filter(|g| !g.is_empty());
+        if glyph.is_some_and(|g| g.chars().count() > MAX_GLYPH_CHARS) {
+            return json!({
+                "status": "error",
+                "message": format!("Glyph must be 1-{} characters", MAX_GLYPH_CHARS)
+            });
+        }
+        self.dispatch(Command::SetTrackGlyph { track, glyph: glyph.map(str::to_string) });
+        let state = self.sequencer_state.read();
+        let track_state = &state.tracks[track];
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_state.name,
+            "glyph": glyph.unwrap_or_else(|| track_state.synth_type.glyph()),
+            "custom": glyph.is_some()
+        })
+    }
+
+    pub fn set_track_triplet(&self, track: usize, enabled: bool) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        self.dispatch(Command::SetTrackTriplet { track, enabled });
+        let track_name = self.track_name(track);
+        let length = self.sequencer_state.read().pattern.length;
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "triplet": enabled,
+            "steps": if enabled { triplet_steps(length) } else { length }
+        })
+    }
+
     pub fn toggle_mute(&self, track: usize) -> Value {
         if let Some(err) = self.validate_track(track) {
             return err;
@@ -650,6 +1435,40 @@ impl GridoxideMcp {
         })
     }
 
+    pub fn toggle_solo_safe(&self, track: usize) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        self.dispatch(Command::ToggleSoloSafe(track));
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "message": format!("Toggled solo safe on {}", track_name)
+        })
+    }
+
+    pub fn toggle_cue(&self, track: usize) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if self.sequencer_state.read().cue_output.is_none() {
+            return json!({
+                "status": "error",
+                "message": "No cue output: start gridoxide with --cue"
+            });
+        }
+        self.dispatch(Command::ToggleCue(track));
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "message": format!("Toggled cue on {}", track_name)
+        })
+    }
+
     // === FX Tools ===
 
     pub fn get_fx_params(&self, track: usize) -> Value {
@@ -687,7 +1506,15 @@ impl GridoxideMcp {
                 "feedback_range": [0.0, 0.9],
                 "mix": fx.delay_mix,
                 "mix_range": [0.0, 1.0]
-            }
+            },
+            "modulation": {
+                "source": fx.modulator(track, state.tracks.len()),
+                "target": fx.mod_target.name(),
+                "depth": fx.mod_depth,
+                "depth_range": [0.0, 1.0]
+            },
+            "chain_mix": fx.chain_mix,
+            "chain_mix_range": [0.0, 1.0]
         })
     }
 
@@ -717,7 +1544,7 @@ impl GridoxideMcp {
             None => {
                 return json!({
                     "status": "error",
-                    "message": format!("Unknown FX parameter: {}. Valid: filter_cutoff, filter_resonance, filter_type, dist_drive, dist_mix, delay_time, delay_feedback, delay_mix", param_key)
+                    "message": format!("Unknown FX parameter: {}. Valid: filter_cutoff, filter_resonance, filter_type, dist_drive, dist_mix, delay_time, delay_feedback, delay_mix, mod_depth, chain_mix", param_key)
                 })
             }
         };
@@ -738,6 +1565,43 @@ impl GridoxideMcp {
         })
     }
 
+    /// Set (or with no source, clear) the track modulating `track`, and
+    /// optionally what it modulates
+    pub fn set_fx_modulation(&self, track: usize, source: Option<usize>, target: Option<&str>) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if let Some(source) = source {
+            if let Some(err) = self.validate_track(source) {
+                return err;
+            }
+            if source == track {
+                return json!({ "status": "error", "message": "A track can't modulate itself" });
+            }
+        }
+        let target = match target.map(ModTarget::from_name) {
+            Some(None) => {
+                return json!({ "status": "error", "message": "Target must be 'amp' or 'drive'" });
+            }
+            Some(Some(target)) => target,
+            None => self.sequencer_state.read().tracks[track].fx.mod_target,
+        };
+        self.dispatch(Command::SetFxModSource { track, source });
+        self.dispatch(Command::SetFxModTarget { track, target });
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "source": source,
+            "target": target.name(),
+            "message": match source {
+                Some(s) => format!("{} {} modulated by {}", track_name, target.name(), self.track_name(s)),
+                None => format!("{} modulation off", track_name),
+            }
+        })
+    }
+
     pub fn toggle_fx(&self, track: usize, fx_name: &str) -> Value {
         if let Some(err) = self.validate_track(track) {
             return err;
@@ -779,7 +1643,30 @@ impl GridoxideMcp {
                 "mix": mfx.reverb_mix,
                 "mix_range": [0.0, 1.0],
                 "damping": mfx.reverb_damping,
-                "damping_range": [0.0, 1.0]
+                "damping_range": [0.0, 1.0],
+                "width": mfx.reverb_width,
+                "width_range": [0.0, 1.0],
+                "mode": mfx.reverb_mode.name(),
+                "gate_threshold_db": mfx.reverb_gate_threshold,
+                "gate_threshold_range": [-60.0, 0.0],
+                "gate_hold_ms": mfx.reverb_gate_hold,
+                "gate_hold_range": [20.0, 500.0]
+            },
+            "mid_side": {
+                "mid_level": mfx.ms_mid_level,
+                "side_level": mfx.ms_side_level,
+                "level_range": [0.0, 2.0],
+                "bass_mono_hz": mfx.ms_bass_mono,
+                "bass_mono_range": [0.0, 500.0],
+                "side_high": mfx.ms_side_high,
+                "side_high_range": [0.0, 2.0]
+            },
+            "limiter": {
+                "enabled": mfx.limiter_enabled,
+                "ceiling_db": mfx.limiter_ceiling,
+                "ceiling_range": [-12.0, 0.0],
+                "release_ms": mfx.limiter_release,
+                "release_range": [10.0, 1000.0]
             }
         })
     }
@@ -790,7 +1677,7 @@ impl GridoxideMcp {
             None => {
                 return json!({
                     "status": "error",
-                    "message": format!("Unknown master FX parameter: {}. Valid: reverb_decay, reverb_mix, reverb_damping", param_key)
+                    "message": format!("Unknown master FX parameter: {}. Valid: reverb_decay, reverb_mix, reverb_damping, reverb_width, reverb_gate_threshold, reverb_gate_hold, ms_mid_level, ms_side_level, ms_bass_mono, ms_side_high, limiter_ceiling, limiter_release", param_key)
                 })
             }
         };
@@ -818,17 +1705,118 @@ impl GridoxideMcp {
         })
     }
 
+    pub fn set_limiter(&self, enabled: bool) -> Value {
+        self.dispatch(Command::SetLimiterEnabled(enabled));
+        json!({
+            "status": "ok",
+            "enabled": enabled,
+            "message": format!("Master limiter {}", if enabled { "on" } else { "off" })
+        })
+    }
+
+    pub fn set_reverb_mode(&self, mode: &str) -> Value {
+        let Some(mode) = ReverbMode::from_name(mode) else {
+            return json!({
+                "status": "error",
+                "message": format!("Unknown reverb mode '{}' (normal or gated)", mode)
+            });
+        };
+        self.dispatch(Command::SetReverbMode(mode));
+        json!({
+            "status": "ok",
+            "mode": mode.name(),
+            "message": format!("Reverb mode {}", mode.name())
+        })
+    }
+
+    pub fn set_gain_match(&self, enabled: bool) -> Value {
+        self.dispatch(Command::SetGainMatch(enabled));
+        json!({
+            "status": "ok",
+            "enabled": enabled,
+            "message": format!("Gain match {}", if enabled { "on" } else { "off" })
+        })
+    }
+
+    pub fn set_headroom(&self, enabled: bool) -> Value {
+        self.dispatch(Command::SetHeadroom(enabled));
+        json!({
+            "status": "ok",
+            "enabled": enabled,
+            "message": if enabled {
+                "Headroom monitoring on: the master soft clipper is bypassed; watch get_levels for overs"
+            } else {
+                "Headroom monitoring off"
+            }
+        })
+    }
+
+    pub fn dj_filter(&self, position: Option<f32>, release: bool) -> Value {
+        if release {
+            self.dispatch(Command::ReleaseDjFilter);
+            return json!({
+                "status": "ok",
+                "message": "Releasing DJ sweep to neutral over one beat"
+            });
+        }
+        let Some(position) = position else {
+            return json!({ "status": "error", "message": "Provide position (-1.0 to 1.0) or release=true" });
+        };
+        let position = position.clamp(-1.0, 1.0);
+        self.dispatch(Command::SetDjFilter(position));
+        json!({
+            "status": "ok",
+            "position": position,
+            "message": format!("Set DJ sweep to {:+.2}", position)
+        })
+    }
+
+    pub fn crossfader(&self, deck_b: Option<usize>, position: Option<f32>, off: bool) -> Value {
+        if off {
+            self.dispatch(Command::SetDeckB(None));
+            return json!({ "status": "ok", "message": "Crossfader mode off" });
+        }
+        if let Some(slot) = deck_b {
+            if slot >= NUM_PATTERNS {
+                return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
+            }
+            self.dispatch(Command::SetDeckB(Some(slot)));
+        } else if self.sequencer_state.read().deck_b.is_none() {
+            return json!({ "status": "error", "message": "Crossfader mode is off: pass deck_b to start it" });
+        }
+        if let Some(position) = position {
+            self.dispatch(Command::SetCrossfader(position.clamp(0.0, 1.0)));
+        }
+        let state = self.sequencer_state.read();
+        let deck_a = state.current_pattern;
+        let deck_b = deck_b.or(state.deck_b).unwrap_or(deck_a);
+        let position = position.map_or(state.crossfader, |p| p.clamp(0.0, 1.0));
+        json!({
+            "status": "ok",
+            "deck_a": deck_a,
+            "deck_b": deck_b,
+            "position": position,
+            "message": format!(
+                "Decks {} / {}, crossfader {:.2}",
+                pattern_label(deck_a),
+                pattern_label(deck_b),
+                position
+            )
+        })
+    }
+
     // === Pattern Bank Tools ===
 
     pub fn select_pattern(&self, pattern: usize) -> Value {
         if pattern >= NUM_PATTERNS {
-            return json!({ "status": "error", "message": "Pattern must be 0-15" });
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
         }
         self.dispatch(Command::SelectPattern(pattern));
         json!({
             "status": "ok",
             "pattern": pattern,
-            "message": format!("Selected pattern {:02}", pattern)
+            "label": pattern_label(pattern),
+            "message": format!("Selected pattern {:02} ({})", pattern, pattern_label(pattern))
         })
     }
 
@@ -838,13 +1826,16 @@ impl GridoxideMcp {
         let patterns: Vec<Value> = (0..NUM_PATTERNS)
             .map(|i| {
                 let has_content = state.pattern_bank.has_content(i);
+                let pat = state.pattern_bank.get(i);
                 let active_steps: usize = (0..num_tracks)
-                    .map(|t| (0..16).filter(|&s| state.pattern_bank.get(i).get(t, s)).count())
+                    .map(|t| (0..pat.length).filter(|&s| pat.get(t, s)).count())
                     .sum();
                 json!({
                     "index": i,
+                    "label": pattern_label(i),
                     "has_content": has_content,
                     "active_steps": active_steps,
+                    "length": pat.length,
                     "is_current": i == state.current_pattern
                 })
             })
@@ -852,13 +1843,41 @@ impl GridoxideMcp {
 
         json!({
             "current_pattern": state.current_pattern,
+            "patterns_per_bank": PATTERNS_PER_BANK,
             "patterns": patterns
         })
     }
 
-    pub fn copy_pattern(&self, src: usize, dst: usize) -> Value {
+    pub fn copy_pattern(
+        &self,
+        src: usize,
+        dst: usize,
+        tracks: Option<Vec<usize>>,
+        merge: bool,
+    ) -> Value {
         if src >= NUM_PATTERNS || dst >= NUM_PATTERNS {
-            return json!({ "status": "error", "message": "Pattern indices must be 0-15" });
+            return json!({ "status": "error", "message": format!("Pattern indices must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        if let Some(mut tracks) = tracks {
+            for &track in &tracks {
+                if let Some(err) = self.validate_track(track) {
+                    return err;
+                }
+            }
+            tracks.sort_unstable();
+            tracks.dedup();
+            let count = tracks.len();
+            self.dispatch(Command::CopyPatternTracks { src, dst, tracks, merge });
+            return json!({
+                "status": "ok",
+                "message": format!(
+                    "{} {} track(s) from pattern {:02} into {:02}",
+                    if merge { "Merged" } else { "Copied" },
+                    count,
+                    src,
+                    dst
+                )
+            });
         }
         self.dispatch(Command::CopyPattern { src, dst });
         json!({
@@ -867,51 +1886,242 @@ impl GridoxideMcp {
         })
     }
 
-    pub fn clear_pattern(&self, pattern: usize) -> Value {
-        if pattern >= NUM_PATTERNS {
-            return json!({ "status": "error", "message": "Pattern must be 0-15" });
+    pub fn export_pattern(&self, pattern: Option<usize>, format: &str, path: Option<&str>) -> Value {
+        let Some(format) = InterchangeFormat::from_name(format) else {
+            return json!({ "status": "error", "message": "Format must be 'json' or 'csv'" });
+        };
+        let exchange = {
+            let state = self.sequencer_state.read();
+            let index = pattern.unwrap_or(state.current_pattern);
+            if index >= NUM_PATTERNS {
+                return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
+            }
+            // The live pattern is newer than its bank slot
+            let pat = if index == state.current_pattern {
+                &state.pattern
+            } else {
+                state.pattern_bank.get(index)
+            };
+            let names: Vec<String> = state.tracks.iter().map(|t| t.name.clone()).collect();
+            PatternExchange::from_pattern(pat, &names)
+        };
+        let text = match exchange.encode(format) {
+            Ok(t) => t,
+            Err(e) => return json!({ "status": "error", "message": format!("{:#}", e) }),
+        };
+        if let Some(path) = path {
+            if let Err(e) = std::fs::write(path, &text) {
+                return json!({ "status": "error", "message": format!("Failed to write {}: {}", path, e) });
+            }
         }
-        self.dispatch(Command::ClearPattern(pattern));
         json!({
             "status": "ok",
-            "message": format!("Cleared pattern {:02}", pattern)
+            "events": exchange.events.len(),
+            "path": path,
+            "data": text
         })
     }
 
-    pub fn set_playback_mode(&self, mode: &str) -> Value {
-        let playback_mode = match mode {
-            "pattern" => PlaybackMode::Pattern,
-            "song" => PlaybackMode::Song,
-            _ => {
-                return json!({
-                    "status": "error",
-                    "message": "Mode must be 'pattern' or 'song'"
+    pub fn import_pattern(
+        &self,
+        pattern: usize,
+        data: Option<&str>,
+        format: Option<&str>,
+        path: Option<&str>,
+    ) -> Value {
+        if pattern >= NUM_PATTERNS {
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        let format = match format {
+            Some(name) => match InterchangeFormat::from_name(name) {
+                Some(f) => f,
+                None => {
+                    return json!({ "status": "error", "message": "Format must be 'json' or 'csv'" })
+                }
+            },
+            None => path
+                .map(|p| InterchangeFormat::from_path(Path::new(p)))
+                .unwrap_or(InterchangeFormat::Json),
+        };
+        let text = match (data, path) {
+            (Some(d), _) => d.to_string(),
+            (None, Some(p)) => match std::fs::read_to_string(p) {
+                Ok(t) => t,
+                Err(e) => {
+                    return json!({ "status": "error", "message": format!("Failed to read {}: {}", p, e) })
+                }
+            },
+            (None, None) => {
+                return json!({ "status": "error", "message": "Provide 'data' or 'path'" })
+            }
+        };
+        let default_notes: Vec<u8> = self
+            .sequencer_state
+            .read()
+            .tracks
+            .iter()
+            .map(|t| t.default_note)
+            .collect();
+        let result = PatternExchange::decode(&text, format)
+            .and_then(|ex| Ok((ex.to_pattern(&default_notes)?, ex.events.len())));
+        match result {
+            Ok((pat, events)) => {
+                self.dispatch(Command::ReplacePattern { index: pattern, pattern: Box::new(pat) });
+                json!({
+                    "status": "ok",
+                    "pattern": pattern,
+                    "events": events,
+                    "message": format!("Imported {} steps into pattern {:02}", events, pattern)
                 })
             }
+            Err(e) => json!({ "status": "error", "message": format!("{:#}", e) }),
+        }
+    }
+
+    pub fn mutate_pattern(
+        &self,
+        pattern: Option<usize>,
+        dst: Option<usize>,
+        amount: f32,
+        seed: Option<u32>,
+    ) -> Value {
+        let pattern = pattern.unwrap_or_else(|| self.sequencer_state.read().current_pattern);
+        let dst = dst.unwrap_or(pattern);
+        if pattern >= NUM_PATTERNS || dst >= NUM_PATTERNS {
+            return json!({ "status": "error", "message": format!("Pattern indices must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        let amount = amount.clamp(0.0, 1.0);
+        let seed = seed.unwrap_or_else(random_seed).max(1);
+        // Built here and sent whole so the audio thread never allocates
+        let mutated = {
+            let state = self.sequencer_state.read();
+            let src = if pattern == state.current_pattern {
+                &state.pattern
+            } else {
+                state.pattern_bank.get(pattern)
+            };
+            src.mutated(amount, seed)
         };
-        self.dispatch(Command::SetPlaybackMode(playback_mode));
+        self.dispatch(Command::ReplacePattern { index: dst, pattern: Box::new(mutated) });
         json!({
             "status": "ok",
-            "mode": mode,
-            "message": format!("Set playback mode to {}", mode)
+            "pattern": pattern,
+            "dst": dst,
+            "amount": amount,
+            "seed": seed,
+            "message": format!("Mutated pattern {:02} into {:02}", pattern, dst)
         })
     }
 
-    // === Arrangement Tools ===
+    pub fn clear_pattern(&self, pattern: usize) -> Value {
+        if pattern >= NUM_PATTERNS {
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        self.dispatch(Command::ClearPattern(pattern));
+        json!({
+            "status": "ok",
+            "message": format!("Cleared pattern {:02}", pattern)
+        })
+    }
 
-    pub fn get_arrangement(&self) -> Value {
-        let state = self.sequencer_state.read();
-        let entries: Vec<Value> = state
-            .arrangement
-            .entries
-            .iter()
-            .enumerate()
-            .map(|(i, e)| {
-                json!({
-                    "position": i,
-                    "pattern": e.pattern,
-                    "repeats": e.repeats,
-                    "is_playing": state.playback_mode == PlaybackMode::Song && i == state.arrangement_position
+    pub fn set_pattern_length(&self, pattern: Option<usize>, length: usize) -> Value {
+        let pattern = pattern.unwrap_or_else(|| self.sequencer_state.read().current_pattern);
+        if pattern >= NUM_PATTERNS {
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        if !(1..=MAX_STEPS).contains(&length) {
+            return json!({ "status": "error", "message": format!("Length must be 1-{}", MAX_STEPS) });
+        }
+        self.dispatch(Command::SetPatternLength { pattern, length });
+        json!({
+            "status": "ok",
+            "pattern": pattern,
+            "label": pattern_label(pattern),
+            "length": length,
+            "message": format!("Pattern {} is {} steps", pattern_label(pattern), length)
+        })
+    }
+
+    pub fn set_track_length(&self, pattern: Option<usize>, track: usize, length: usize) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let pattern = pattern.unwrap_or_else(|| self.sequencer_state.read().current_pattern);
+        if pattern >= NUM_PATTERNS {
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        if length > MAX_STEPS {
+            return json!({ "status": "error", "message": format!("Length must be 0-{}", MAX_STEPS) });
+        }
+        self.dispatch(Command::SetTrackLength { pattern, track, length });
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "pattern": pattern,
+            "track": track,
+            "length": length,
+            "message": if length == 0 {
+                format!("{} loops with pattern {}", track_name, pattern_label(pattern))
+            } else {
+                format!("{} loops every {} steps in pattern {}", track_name, length, pattern_label(pattern))
+            }
+        })
+    }
+
+    pub fn set_playback_mode(&self, mode: &str) -> Value {
+        let playback_mode = match mode {
+            "pattern" => PlaybackMode::Pattern,
+            "song" => PlaybackMode::Song,
+            _ => {
+                return json!({
+                    "status": "error",
+                    "message": "Mode must be 'pattern' or 'song'"
+                })
+            }
+        };
+        let warnings = if playback_mode == PlaybackMode::Song {
+            self.sequencer_state.read().song_warnings()
+        } else {
+            Vec::new()
+        };
+        self.dispatch(Command::SetPlaybackMode(playback_mode));
+        with_warnings(
+            json!({
+                "status": "ok",
+                "mode": mode,
+                "message": format!("Set playback mode to {}", mode)
+            }),
+            warnings,
+        )
+    }
+
+    // === Arrangement Tools ===
+
+    /// Add a warning to `response` when the slot an entry plays is empty
+    fn empty_slot_warning(&self, pattern: usize, response: Value) -> Value {
+        let warnings = if self.sequencer_state.read().slot_has_content(pattern) {
+            Vec::new()
+        } else {
+            vec![format!("Pattern {:02} is empty: this entry plays silence", pattern)]
+        };
+        with_warnings(response, warnings)
+    }
+
+    pub fn get_arrangement(&self) -> Value {
+        let state = self.sequencer_state.read();
+        let entries: Vec<Value> = state
+            .arrangement
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                json!({
+                    "position": i,
+                    "pattern": e.pattern,
+                    "repeats": e.repeats,
+                    "automation": e.automation,
+                    "chord": e.chord.map(|_| chord_label(e.chord)),
+                    "is_playing": state.playback_mode == PlaybackMode::Song && i == state.arrangement_position
                 })
             })
             .collect();
@@ -926,41 +2136,44 @@ impl GridoxideMcp {
             "length": state.arrangement.len(),
             "playback_mode": mode_str,
             "current_position": state.arrangement_position,
-            "current_repeat": state.arrangement_repeat
+            "current_repeat": state.arrangement_repeat,
+            "total_bars": state.arrangement.total_bars(),
+            "fade_out_bars": state.arrangement.fade_out_bars,
+            "warnings": state.song_warnings()
         })
     }
 
     pub fn append_arrangement(&self, pattern: usize, repeats: usize) -> Value {
         if pattern >= NUM_PATTERNS {
-            return json!({ "status": "error", "message": "Pattern must be 0-15" });
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
         }
-        let repeats = repeats.clamp(1, 16);
+        let repeats = repeats.clamp(1, MAX_REPEATS);
         self.dispatch(Command::AppendArrangement { pattern, repeats });
-        json!({
+        self.empty_slot_warning(pattern, json!({
             "status": "ok",
             "message": format!("Appended pattern {:02} x{} to arrangement", pattern, repeats)
-        })
+        }))
     }
 
     pub fn insert_arrangement(&self, position: usize, pattern: usize, repeats: usize) -> Value {
         if pattern >= NUM_PATTERNS {
-            return json!({ "status": "error", "message": "Pattern must be 0-15" });
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
         }
         let state = self.sequencer_state.read();
         if position > state.arrangement.len() {
             return json!({ "status": "error", "message": "Position out of range" });
         }
         drop(state);
-        let repeats = repeats.clamp(1, 16);
+        let repeats = repeats.clamp(1, MAX_REPEATS);
         self.dispatch(Command::InsertArrangement {
             position,
             pattern,
             repeats,
         });
-        json!({
+        self.empty_slot_warning(pattern, json!({
             "status": "ok",
             "message": format!("Inserted pattern {:02} x{} at position {}", pattern, repeats, position)
-        })
+        }))
     }
 
     pub fn remove_arrangement(&self, position: usize) -> Value {
@@ -978,22 +2191,117 @@ impl GridoxideMcp {
 
     pub fn set_arrangement_entry(&self, position: usize, pattern: usize, repeats: usize) -> Value {
         if pattern >= NUM_PATTERNS {
-            return json!({ "status": "error", "message": "Pattern must be 0-15" });
+            return json!({ "status": "error", "message": format!("Pattern must be 0-{}", NUM_PATTERNS - 1) });
         }
         let state = self.sequencer_state.read();
         if position >= state.arrangement.len() {
             return json!({ "status": "error", "message": "Position out of range" });
         }
         drop(state);
-        let repeats = repeats.clamp(1, 16);
+        let repeats = repeats.clamp(1, MAX_REPEATS);
         self.dispatch(Command::SetArrangementEntry {
             position,
             pattern,
             repeats,
         });
-        json!({
+        self.empty_slot_warning(pattern, json!({
             "status": "ok",
             "message": format!("Set entry {} to pattern {:02} x{}", position, pattern, repeats)
+        }))
+    }
+
+    /// Set (or with `clear`, remove) one track's automation on an entry.
+    /// Fields left as None don't change at the entry boundary.
+    pub fn set_arrangement_automation(
+        &self,
+        position: usize,
+        track: usize,
+        change: TrackAutomation,
+        clear: bool,
+    ) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let state = self.sequencer_state.read();
+        let Some(entry) = state.arrangement.entries.get(position) else {
+            return json!({ "status": "error", "message": "Position out of range" });
+        };
+        let mut automation = entry.automation.clone();
+        drop(state);
+        automation.retain(|a| a.track != track);
+        if !clear {
+            if change.is_empty() {
+                return json!({
+                    "status": "error",
+                    "message": "Give at least one of mute, solo or volume (or clear=true)"
+                });
+            }
+            automation.push(TrackAutomation { track, ..change });
+        }
+        self.dispatch(Command::SetEntryAutomation {
+            position,
+            automation: automation.clone(),
+        });
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "position": position,
+            "track_name": track_name,
+            "automation": automation,
+            "message": if clear {
+                format!("Cleared {} automation on entry {}", track_name, position)
+            } else {
+                format!("Set {} automation on entry {}", track_name, position)
+            }
+        })
+    }
+
+    /// Set an entry's chord degree by numeral ("I".."vii", case-sensitive);
+    /// None clears it
+    pub fn set_arrangement_chord(&self, position: usize, chord: Option<&str>) -> Value {
+        if position >= self.sequencer_state.read().arrangement.len() {
+            return json!({ "status": "error", "message": "Position out of range" });
+        }
+        let degree = match chord {
+            None => None,
+            Some(name) => match CHORD_DEGREES
+                .iter()
+                .position(|d| d.trim_end_matches('°') == name.trim_end_matches('°'))
+            {
+                Some(d) => Some(d as u8),
+                None => {
+                    return json!({
+                        "status": "error",
+                        "message": format!("Unknown chord '{}' (use {})", name, CHORD_DEGREES.join(", "))
+                    })
+                }
+            },
+        };
+        self.dispatch(Command::SetEntryChord { position, chord: degree });
+        json!({
+            "status": "ok",
+            "position": position,
+            "chord": degree.map(|_| chord_label(degree)),
+            "message": match degree {
+                Some(_) => format!("Entry {} plays on {}", position, chord_label(degree)),
+                None => format!("Cleared entry {} chord", position),
+            }
+        })
+    }
+
+    pub fn set_fade_out(&self, bars: usize) -> Value {
+        if bars > MAX_FADE_OUT_BARS {
+            return json!({ "status": "error", "message": format!("Bars must be 0-{}", MAX_FADE_OUT_BARS) });
+        }
+        self.dispatch(Command::SetFadeOutBars(bars));
+        json!({
+            "status": "ok",
+            "fade_out_bars": bars,
+            "message": if bars == 0 {
+                "Disabled song fade-out".to_string()
+            } else {
+                format!("Song fades out over the last {} bars", bars)
+            }
         })
     }
 
@@ -1079,16 +2387,22 @@ impl GridoxideMcp {
 
     pub fn save_project(&self, path_str: &str) -> Value {
         let path = Path::new(path_str);
+        // Backups rotate as they do for saves from the interface; a failed
+        // backup doesn't stop the save
+        let backups = Config::load().map_or(0, |config| config.backups as usize);
+        let backup_error = rotate_backups(path, backups).err().map(|e| format!("{:#}", e));
         let state = self.sequencer_state.read();
         match project::save_project(&state, path) {
             Ok(()) => json!({
                 "status": "ok",
                 "path": path_str,
-                "message": format!("Saved project to {}", path_str)
+                "message": format!("Saved project to {}", path_str),
+                "backup_error": backup_error
             }),
             Err(e) => json!({
                 "status": "error",
-                "message": format!("Failed to save: {}", e)
+                "message": format!("Failed to save, {} is unchanged: {:#}", path_str, e),
+                "backup_error": backup_error
             }),
         }
     }
@@ -1096,27 +2410,24 @@ impl GridoxideMcp {
     pub fn load_project(&self, path_str: &str) -> Value {
         let path = Path::new(path_str);
         match project::load_project(path) {
-            Ok(project_data) => {
-                // Load sample buffers for sampler tracks
-                let project_dir = path.parent().unwrap_or(Path::new("."));
-                let sample_buffers = project_data.load_sample_buffers(project_dir);
-
-                let new_state = project_data.to_state();
-                self.dispatch(Command::LoadProject(Box::new(new_state)));
+            Ok(mut project_data) => {
+                let warnings = project_data.validate_and_clamp();
 
-                // Send sample buffers to audio thread
-                for sb in sample_buffers {
-                    self.dispatch(Command::LoadSample {
-                        track: sb.track,
-                        buffer: sb.buffer,
-                        path: sb.path,
-                    });
+                let project_dir = path.parent().unwrap_or(Path::new("."));
+                let pending = self.install_project(project_data.to_state(), &project_data, project_dir);
+                let mut message = format!("Loaded project from {}", path_str);
+                if !pending.is_empty() {
+                    message.push_str(
+                        "; external synth plugins were not started: check each command and start it with load_external_synth, or add it to 'plugins' in config.json",
+                    );
                 }
 
                 json!({
                     "status": "ok",
                     "path": path_str,
-                    "message": format!("Loaded project from {}", path_str)
+                    "message": message,
+                    "warnings": warnings,
+                    "plugins_pending": pending_plugins_json(&pending)
                 })
             }
             Err(e) => json!({
@@ -1126,15 +2437,91 @@ impl GridoxideMcp {
         }
     }
 
-    pub fn export_wav_file(&self, path_str: &str, mode: &str, pattern: Option<usize>) -> Value {
+    /// Load a full state into the engine, then its samples. Plugins whose
+    /// command is allowed start in the background; the others are returned
+    /// as (track, command) for the caller to report.
+    fn install_project(&self, state: SequencerState, data: &ProjectData, project_dir: &Path) -> Vec<(usize, String)> {
+        let sample_rate = self.sequencer_state.read().sample_rate;
+        let sample_buffers = data.load_sample_buffers(project_dir, sample_rate);
+
+        // Checked here rather than on the audio thread, which only swaps it in
+        debug_assert!(state.check_invariants().is_ok(), "{:?}", state.check_invariants());
+        self.dispatch(Command::LoadProject(Box::new(state), ParkedSynths::default()));
+
+        // Send sample buffers to audio thread
+        for sb in sample_buffers {
+            self.dispatch(sb.into_command(data.sample_stream_mb));
+        }
+
+        let allowed = Config::load().map(|config| config.plugins).unwrap_or_default();
+        let trusted = self.trusted_plugins.read();
+        let (start, pending): (Vec<_>, Vec<_>) = data
+            .external_commands()
+            .into_iter()
+            .partition(|(_, command)| allowed.contains(command) || trusted.contains(command));
+        drop(trusted);
+        self.start_plugins(start);
+        pending
+    }
+
+    /// Start plugins off this thread and connect them as their handshakes
+    /// come back, unless their track has changed meanwhile
+    fn start_plugins(&self, plugins: Vec<(usize, String)>) {
+        if plugins.is_empty() {
+            return;
+        }
+        let sample_rate = self.sample_rate();
+        let (done_tx, done_rx) = unbounded();
+        for (track, command) in plugins {
+            ExternalConnection::spawn_in_background(track, command, sample_rate, done_tx.clone());
+        }
+        drop(done_tx);
+        let state = self.sequencer_state.clone();
+        let sender = self.command_sender.clone();
+        let log = self.event_log.clone();
+        std::thread::spawn(move || {
+            for start in done_rx {
+                if state.read().plugin_command(start.track) != Some(start.command.as_str()) {
+                    continue;
+                }
+                match start.connection {
+                    Ok(connection) => {
+                        let cmd = Command::LoadExternalSynth { track: start.track, connection };
+                        log.write().log(cmd.clone(), CommandSource::Mcp);
+                        sender.send(cmd, CommandSource::Mcp);
+                    }
+                    Err(e) => {
+                        warn!(track = start.track, command = start.command, "Failed to start external synth: {:#}", e);
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn export_wav_file(
+        &self,
+        path_str: &str,
+        mode: &str,
+        pattern: Option<usize>,
+        sample_rate: Option<u32>,
+        clipper: Option<bool>,
+        queue: bool,
+    ) -> Value {
         let path = Path::new(path_str);
-        let state = self.sequencer_state.read();
+        let mut state = self.sequencer_state.read().clone();
+        if let Some(clipper) = clipper {
+            state.headroom = !clipper;
+        }
+        let sample_rate = sample_rate.unwrap_or(state.sample_rate as u32);
+        if !(8000..=192000).contains(&sample_rate) {
+            return json!({ "status": "error", "message": "Sample rate must be 8000-192000" });
+        }
 
         let export_mode = match mode {
             "pattern" => {
                 let idx = pattern.unwrap_or(state.current_pattern);
                 if idx >= NUM_PATTERNS {
-                    return json!({ "status": "error", "message": "Pattern index must be 0-15" });
+                    return json!({ "status": "error", "message": format!("Pattern index must be 0-{}", NUM_PATTERNS - 1) });
                 }
                 ExportMode::Pattern(idx)
             }
@@ -1147,14 +2534,32 @@ impl GridoxideMcp {
             }
         };
 
-        match export_wav(&state, export_mode, path) {
-            Ok(result) => json!({
-                "status": "ok",
-                "path": path_str,
-                "duration_secs": result.duration_secs,
-                "samples": result.samples,
-                "message": format!("Exported {:.1}s of audio to {}", result.duration_secs, path_str)
-            }),
+        if queue {
+            return self.queue_export(state, export_mode, ExportTarget::Wav(path.to_path_buf()), sample_rate);
+        }
+        match export_wav(&state, export_mode, path, sample_rate, None) {
+            Ok(result) => {
+                let warnings = if result.peak > 1.0 {
+                    vec![format!("Peaks at {:+.1} dBFS with no clipper", level_db(result.peak))]
+                } else {
+                    Vec::new()
+                };
+                with_warnings(
+                    json!({
+                        "status": "ok",
+                        "path": path_str,
+                        "duration_secs": result.duration_secs,
+                        "samples": result.samples,
+                        "sample_rate": sample_rate,
+                        "quality": state.render_interpolation.name(),
+                        "clipper": !state.headroom,
+                        "format": if state.headroom { "32-bit float" } else { "16-bit" },
+                        "peak_db": (level_db(result.peak) * 10.0).round() / 10.0,
+                        "message": format!("Exported {:.1}s of audio to {}", result.duration_secs, path_str)
+                    }),
+                    warnings,
+                )
+            }
             Err(e) => json!({
                 "status": "error",
                 "message": format!("Failed to export: {}", e)
@@ -1162,102 +2567,924 @@ impl GridoxideMcp {
         }
     }
 
-    pub fn list_projects(&self, directory: Option<&str>) -> Value {
-        let dir = directory.unwrap_or(".");
-        let path = Path::new(dir);
-
-        if !path.is_dir() {
-            return json!({
+    pub fn export_hits_dir(&self, dir: &str, velocity: u8, sample_rate: Option<u32>) -> Value {
+        let state = self.sequencer_state.read().clone();
+        let sample_rate = sample_rate.unwrap_or(state.sample_rate as u32);
+        if !(8000..=192000).contains(&sample_rate) {
+            return json!({ "status": "error", "message": "Sample rate must be 8000-192000" });
+        }
+        match export_hits(&state, Path::new(dir), velocity, sample_rate) {
+            Ok(hits) => {
+                let files: Vec<Value> = hits
+                    .iter()
+                    .map(|hit| {
+                        json!({
+                            "track": hit.track,
+                            "name": state.tracks[hit.track].name,
+                            "note": state.tracks[hit.track].default_note,
+                            "path": hit.path.to_string_lossy(),
+                            "duration_secs": hit.duration_secs
+                        })
+                    })
+                    .collect();
+                json!({
+                    "status": "ok",
+                    "dir": dir,
+                    "velocity": velocity,
+                    "sample_rate": sample_rate,
+                    "files": files,
+                    "message": format!("Exported {} one-shot hits to {}", hits.len(), dir)
+                })
+            }
+            Err(e) => json!({
                 "status": "error",
-                "message": format!("Not a directory: {}", dir)
-            });
+                "message": format!("Failed to export: {}", e)
+            }),
         }
+    }
 
-        let mut files: Vec<String> = Vec::new();
-        match std::fs::read_dir(path) {
-            Ok(entries) => {
-                for entry in entries.flatten() {
-                    let p = entry.path();
-                    if p.extension().map(|e| e == "grox").unwrap_or(false) {
-                        if let Some(name) = p.file_name().and_then(|n| n.to_str()) {
-                            files.push(name.to_string());
-                        }
-                    }
+    pub fn export_stems_dir(
+        &self,
+        dir: &str,
+        mode: &str,
+        pattern: Option<usize>,
+        sample_rate: Option<u32>,
+        queue: bool,
+    ) -> Value {
+        let state = self.sequencer_state.read().clone();
+        let sample_rate = sample_rate.unwrap_or(state.sample_rate as u32);
+        if !(8000..=192000).contains(&sample_rate) {
+            return json!({ "status": "error", "message": "Sample rate must be 8000-192000" });
+        }
+        let export_mode = match mode {
+            "pattern" => {
+                let idx = pattern.unwrap_or(state.current_pattern);
+                if idx >= NUM_PATTERNS {
+                    return json!({ "status": "error", "message": format!("Pattern index must be 0-{}", NUM_PATTERNS - 1) });
                 }
+                ExportMode::Pattern(idx)
             }
-            Err(e) => {
+            "song" => ExportMode::Song,
+            _ => {
                 return json!({
                     "status": "error",
-                    "message": format!("Failed to read directory: {}", e)
-                });
+                    "message": "Mode must be 'pattern' or 'song'"
+                })
+            }
+        };
+
+        if queue {
+            return self.queue_export(state, export_mode, ExportTarget::Stems(dir.into()), sample_rate);
+        }
+        match export_stems(&state, &export_mode, Path::new(dir), sample_rate, None) {
+            Ok(result) => {
+                let files: Vec<Value> = result
+                    .stems
+                    .iter()
+                    .map(|stem| {
+                        json!({
+                            "track": stem.track,
+                            "name": state.tracks[stem.track].name,
+                            "path": stem.path.to_string_lossy()
+                        })
+                    })
+                    .collect();
+                let markers: Vec<Value> = result
+                    .markers
+                    .iter()
+                    .map(|m| json!({ "name": m.name, "bar": m.bar, "time_secs": m.time_secs }))
+                    .collect();
+                json!({
+                    "status": "ok",
+                    "dir": dir,
+                    "sample_rate": sample_rate,
+                    "duration_secs": result.duration_secs,
+                    "files": files,
+                    "markers": markers,
+                    "manifest": result.manifest.to_string_lossy(),
+                    "session": result.session.to_string_lossy(),
+                    "message": format!("Exported {} stems with a DAW session to {}", result.stems.len(), dir)
+                })
             }
+            Err(e) => json!({
+                "status": "error",
+                "message": format!("Failed to export: {}", e)
+            }),
         }
+    }
 
-        files.sort();
+    /// Add an export to the background queue shared with the TUI
+    fn queue_export(&self, state: SequencerState, mode: ExportMode, target: ExportTarget, sample_rate: u32) -> Value {
+        let ahead = self.exports.pending();
+        let id = self.exports.push(state, mode, target, sample_rate);
+        let label = self.exports.job(id).map(|job| job.label).unwrap_or_default();
         json!({
             "status": "ok",
-            "directory": dir,
-            "files": files,
-            "count": files.len()
+            "job": id,
+            "label": label,
+            "ahead": ahead,
+            "message": format!("Queued export #{} ({} ahead); poll get_export_jobs for its status", id, ahead)
         })
     }
 
-    /// Handle an MCP tool call
-    // === Sample Tools ===
-
-    pub fn load_sample(&self, track: usize, path_str: &str) -> Value {
-        if let Some(err) = self.validate_track(track) {
-            return err;
+    /// Status of one export job, or all of them (oldest first)
+    pub fn get_export_jobs(&self, id: Option<u64>) -> Value {
+        let to_json = |job: &JobInfo| {
+            json!({
+                "id": job.id,
+                "label": job.label,
+                "state": job.status.name(),
+                "progress": (job.progress * 1000.0).round() / 1000.0,
+                "message": job.message,
+                "warning": job.warning
+            })
+        };
+        match id {
+            Some(id) => match self.exports.job(id) {
+                Some(job) => json!({ "status": "ok", "job": to_json(&job) }),
+                None => json!({ "status": "error", "message": format!("No export job #{}", id) }),
+            },
+            None => {
+                let jobs = self.exports.jobs();
+                json!({
+                    "status": "ok",
+                    "pending": self.exports.pending(),
+                    "jobs": jobs.iter().map(to_json).collect::<Vec<_>>()
+                })
+            }
         }
+    }
 
-        // Check track is a sampler
-        let state = self.sequencer_state.read();
-        if track >= state.tracks.len() || state.tracks[track].synth_type != SynthType::Sampler {
-            return json!({
+    pub fn cancel_export_job(&self, id: u64) -> Value {
+        match self.exports.job(id) {
+            None => json!({ "status": "error", "message": format!("No export job #{}", id) }),
+            Some(_) if self.exports.cancel(id) => json!({
+                "status": "ok",
+                "job": id,
+                "message": format!("Cancelled export #{}", id)
+            }),
+            Some(job) => json!({
                 "status": "error",
-                "message": format!("Track {} is not a sampler track", track)
-            });
+                "message": format!("Export #{} is {}: only queued jobs can be cancelled", id, job.status.name())
+            }),
         }
-        drop(state);
+    }
 
-        // Resolve path
-        let dirs = samples::search_dirs();
-        let resolved = samples::resolve_sample_path(path_str, &dirs);
-        let full_path = match resolved {
-            Some(p) => p,
-            None => {
+    pub fn export_midi_file(&self, path_str: &str, mode: &str, pattern: Option<usize>) -> Value {
+        let state = self.sequencer_state.read();
+        let export_mode = match mode {
+            "pattern" => {
+                let idx = pattern.unwrap_or(state.current_pattern);
+                if idx >= NUM_PATTERNS {
+                    return json!({ "status": "error", "message": format!("Pattern index must be 0-{}", NUM_PATTERNS - 1) });
+                }
+                ExportMode::Pattern(idx)
+            }
+            "song" => ExportMode::Song,
+            _ => {
                 return json!({
                     "status": "error",
-                    "message": format!("Sample not found: '{}'. Searched in {:?}", path_str, dirs)
-                });
+                    "message": "Mode must be 'pattern' or 'song'"
+                })
             }
         };
 
-        // Load WAV
-        match load_wav(&full_path, 44100.0) {
-            Ok(buffer) => {
-                let sample_count = buffer.len();
-                let duration_secs = sample_count as f32 / 44100.0;
-                let path_string = full_path.to_string_lossy().to_string();
-                self.dispatch(Command::LoadSample {
-                    track,
-                    buffer,
+        match export_midi(&state, &export_mode, Path::new(path_str)) {
+            Ok(result) => json!({
+                "status": "ok",
+                "path": path_str,
+                "tracks": result.tracks,
+                "notes": result.notes,
+                "sections": result.sections,
+                "message": format!("Exported {} notes on {} tracks to {}", result.notes, result.tracks, path_str)
+            }),
+            Err(e) => json!({
+                "status": "error",
+                "message": format!("Failed to export: {}", e)
+            }),
+        }
+    }
+
+    pub fn import_midi_file(&self, path_str: &str, start: Option<usize>, arrange: bool) -> Value {
+        if start.is_some_and(|s| s >= NUM_PATTERNS) {
+            return json!({ "status": "error", "message": format!("Start must be 0-{}", NUM_PATTERNS - 1) });
+        }
+        let state = self.sequencer_state.read();
+        let default_notes: Vec<u8> = state.tracks.iter().map(|t| t.default_note).collect();
+        let triplets: Vec<bool> = state.tracks.iter().map(|t| t.triplet).collect();
+        let slots: Vec<usize> = match start {
+            Some(start) => (start..NUM_PATTERNS).collect(),
+            None => (0..NUM_PATTERNS)
+                .filter(|&i| !state.pattern_bank.has_content(i))
+                .collect(),
+        };
+        drop(state);
+        if slots.is_empty() {
+            return json!({ "status": "error", "message": "No empty pattern slots" });
+        }
+
+        match import_midi(Path::new(path_str), &default_notes, &triplets, slots.len()) {
+            Ok(import) => {
+                let (sections, notes, skipped) = (import.sections.len(), import.notes, import.skipped);
+                let patterns = slots[..sections].to_vec();
+                for cmd in import.commands(&slots, arrange) {
+                    self.dispatch(cmd);
+                }
+                json!({
+                    "status": "ok",
+                    "path": path_str,
+                    "patterns": patterns,
+                    "notes": notes,
+                    "skipped": skipped,
+                    "message": format!("Imported {} notes into {} patterns ({} skipped)", notes, sections, skipped)
+                })
+            }
+            Err(e) => json!({
+                "status": "error",
+                "message": format!("Failed to import: {:#}", e)
+            }),
+        }
+    }
+
+    pub fn set_render_quality(&self, quality: &str) -> Value {
+        let Some(interpolation) = Interpolation::from_name(quality) else {
+            return json!({
+                "status": "error",
+                "message": "Quality must be 'linear', 'cubic' or 'sinc'"
+            });
+        };
+        self.dispatch(Command::SetRenderInterpolation(interpolation));
+        json!({
+            "status": "ok",
+            "quality": interpolation.name()
+        })
+    }
+
+    pub fn set_export_tone(&self, enabled: bool) -> Value {
+        self.dispatch(Command::SetExportTone(enabled));
+        json!({ "status": "ok", "export_tone": enabled })
+    }
+
+    // === Macro Tools ===
+
+    pub fn list_macros(&self) -> Value {
+        let store = match MacroStore::load() {
+            Ok(store) => store,
+            Err(e) => return json!({ "status": "error", "message": format!("{:#}", e) }),
+        };
+        let macros: Vec<Value> = store
+            .macros
+            .iter()
+            .map(|(name, commands)| {
+                let actions: Vec<String> = commands.iter().map(|c| c.description()).collect();
+                json!({ "name": name, "count": commands.len(), "actions": actions })
+            })
+            .collect();
+        json!({ "macros": macros })
+    }
+
+    pub fn run_macro(&self, name: &str) -> Value {
+        let store = match MacroStore::load() {
+            Ok(store) => store,
+            Err(e) => return json!({ "status": "error", "message": format!("{:#}", e) }),
+        };
+        let Some(commands) = store.get(name) else {
+            return json!({ "status": "error", "message": format!("No macro named '{}'", name) });
+        };
+        for cmd in commands {
+            self.dispatch(cmd.clone());
+        }
+        json!({
+            "status": "ok",
+            "name": name,
+            "count": commands.len(),
+            "message": format!("Replayed '{}' ({} actions)", name, commands.len())
+        })
+    }
+
+    // === Track Template Tools ===
+
+    pub fn list_track_templates(&self) -> Value {
+        let store = match TemplateStore::load() {
+            Ok(store) => store,
+            Err(e) => return json!({ "status": "error", "message": format!("{:#}", e) }),
+        };
+        let templates: Vec<Value> = store
+            .templates
+            .iter()
+            .map(|(name, t)| {
+                json!({
+                    "name": name,
+                    "synth_type": t.synth_type.name(),
+                    "volume": t.volume,
+                    "pan": t.pan,
+                    "params": t.params,
+                    "fx": t.fx
+                })
+            })
+            .collect();
+        json!({ "templates": templates })
+    }
+
+    /// Save a track as a template (named after the track unless given)
+    pub fn save_track_template(&self, track: usize, name: Option<&str>) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let sound = self.sequencer_state.read().tracks[track].clone();
+        let name = name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(&sound.name).to_string();
+        let result = TemplateStore::load().and_then(|mut store| {
+            store.insert(&name, &sound, Path::new("."));
+            store.save()
+        });
+        match result {
+            Ok(()) => json!({
+                "status": "ok",
+                "name": name,
+                "message": format!("Saved {} as template '{}'", sound.name, name)
+            }),
+            Err(e) => json!({ "status": "error", "message": format!("{:#}", e) }),
+        }
+    }
+
+    /// Put a kit folder's WAVs on sampler tracks from track 0 on
+    pub fn load_kit(&self, folder: &str, count: Option<usize>) -> Value {
+        let dir = if Path::new(folder).is_dir() {
+            Some(Path::new(folder).to_path_buf())
+        } else {
+            samples::resolve_sample_path(folder, &samples::search_dirs()).filter(|p| p.is_dir())
+        };
+        let Some(dir) = dir else {
+            return json!({ "status": "error", "message": format!("Kit folder not found: '{}'", folder) });
+        };
+        let state = self.sequencer_state.read().clone();
+        let (state, slots) = match assign_kit(&state, &dir, count.unwrap_or(MAX_TRACKS)) {
+            Ok(assigned) => assigned,
+            Err(e) => return json!({ "status": "error", "message": format!("{:#}", e) }),
+        };
+        let num_tracks = state.tracks.len();
+        let data = ProjectData::from_state(&state);
+        let pending = self.install_project(state, &data, Path::new("."));
+        let tracks: Vec<Value> = slots
+            .iter()
+            .map(|slot| {
+                json!({
+                    "track": slot.track,
+                    "name": slot.name,
+                    "path": slot.path.to_string_lossy()
+                })
+            })
+            .collect();
+        json!({
+            "status": "ok",
+            "folder": dir.to_string_lossy(),
+            "tracks": tracks,
+            "num_tracks": num_tracks,
+            "plugins_pending": pending_plugins_json(&pending)
+        })
+    }
+
+    pub fn add_track_from_template(&self, name: &str) -> Value {
+        let store = match TemplateStore::load() {
+            Ok(store) => store,
+            Err(e) => return json!({ "status": "error", "message": format!("{:#}", e) }),
+        };
+        let Some(template) = store.get(name) else {
+            return json!({ "status": "error", "message": format!("No template named '{}'", name) });
+        };
+        let state = self.sequencer_state.read().clone();
+        if state.playing {
+            return json!({ "status": "error", "message": "Cannot add track while playing. Stop playback first." });
+        }
+        let state = match add_template_track(&state, template) {
+            Ok(state) => state,
+            Err(e) => return json!({ "status": "error", "message": format!("{:#}", e) }),
+        };
+        let track_name = state.tracks.last().map(|t| t.name.clone()).unwrap_or_default();
+        let num_tracks = state.tracks.len();
+        let data = ProjectData::from_state(&state);
+        let pending = self.install_project(state, &data, Path::new("."));
+        json!({
+            "status": "ok",
+            "track": num_tracks - 1,
+            "name": track_name,
+            "num_tracks": num_tracks,
+            "plugins_pending": pending_plugins_json(&pending),
+            "message": format!("Added track '{}' from template '{}'", track_name, name)
+        })
+    }
+
+    pub fn delete_track_template(&self, name: &str) -> Value {
+        let result = TemplateStore::load().and_then(|mut store| {
+            let removed = store.remove(name);
+            store.save().map(|_| removed)
+        });
+        match result {
+            Ok(true) => json!({ "status": "ok", "message": format!("Deleted template '{}'", name) }),
+            Ok(false) => json!({ "status": "error", "message": format!("No template named '{}'", name) }),
+            Err(e) => json!({ "status": "error", "message": format!("{:#}", e) }),
+        }
+    }
+
+    pub fn list_scripts(&self) -> Value {
+        let state = self.sequencer_state.read();
+        let scripts: Vec<Value> = state
+            .scripts
+            .iter()
+            .map(|s| {
+                json!({
+                    "name": s.name,
+                    "every": s.every,
+                    "enabled": s.enabled,
+                    "source": s.source
+                })
+            })
+            .collect();
+        json!({ "scripts": scripts, "max": MAX_SCRIPTS })
+    }
+
+    pub fn set_script(
+        &self,
+        name: &str,
+        source: Option<&str>,
+        every: Option<u32>,
+        enabled: Option<bool>,
+    ) -> Value {
+        if name.trim().is_empty() {
+            return json!({ "status": "error", "message": "Script name is empty" });
+        }
+        let state = self.sequencer_state.read();
+        let existing = state.scripts.iter().find(|s| s.name == name).cloned();
+        let count = state.scripts.len();
+        drop(state);
+
+        let mut script = match (existing, source) {
+            (Some(script), _) => script,
+            (None, Some(source)) => {
+                if count >= MAX_SCRIPTS {
+                    return json!({
+                        "status": "error",
+                        "message": format!("Script limit reached ({})", MAX_SCRIPTS)
+                    });
+                }
+                Script::new(name, source)
+            }
+            (None, None) => {
+                return json!({
+                    "status": "error",
+                    "message": format!("No script named '{}'; pass source to create it", name)
+                });
+            }
+        };
+        if let Some(source) = source {
+            if let Err(e) = script::check_syntax(source) {
+                return json!({ "status": "error", "message": format!("{:#}", e) });
+            }
+            script.source = source.to_string();
+        }
+        if let Some(every) = every {
+            script.every = every.clamp(1, 16);
+        }
+        if let Some(enabled) = enabled {
+            script.enabled = enabled;
+        }
+        let result = json!({
+            "status": "ok",
+            "name": script.name,
+            "every": script.every,
+            "enabled": script.enabled,
+            "message": format!("Saved script '{}'", script.name)
+        });
+        self.dispatch(Command::SetScript(script));
+        result
+    }
+
+    pub fn remove_script(&self, name: &str) -> Value {
+        if !self.sequencer_state.read().scripts.iter().any(|s| s.name == name) {
+            return json!({ "status": "error", "message": format!("No script named '{}'", name) });
+        }
+        self.dispatch(Command::RemoveScript(name.to_string()));
+        json!({ "status": "ok", "message": format!("Removed script '{}'", name) })
+    }
+
+    pub fn list_projects(&self, directory: Option<&str>) -> Value {
+        let dir = directory.unwrap_or(".");
+        let path = Path::new(dir);
+
+        if !path.is_dir() {
+            return json!({
+                "status": "error",
+                "message": format!("Not a directory: {}", dir)
+            });
+        }
+
+        let mut files: Vec<String> = Vec::new();
+        match std::fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries.flatten() {
+                    let p = entry.path();
+                    if p.extension().map(|e| e == "grox").unwrap_or(false) {
+                        if let Some(name) = p.file_name().and_then(|n| n.to_str()) {
+                            files.push(name.to_string());
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                return json!({
+                    "status": "error",
+                    "message": format!("Failed to read directory: {}", e)
+                });
+            }
+        }
+
+        files.sort();
+        json!({
+            "status": "ok",
+            "directory": dir,
+            "files": files,
+            "count": files.len()
+        })
+    }
+
+    /// Handle an MCP tool call
+    // === Sample Tools ===
+
+    pub fn load_external_synth(&self, track: usize, command: &str) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if command.trim().is_empty() {
+            return json!({
+                "status": "error",
+                "message": "Plugin command is empty"
+            });
+        }
+
+        match ExternalConnection::spawn(command, self.sample_rate()) {
+            Ok(connection) => {
+                self.trusted_plugins.write().push(command.to_string());
+                let name = connection.name.clone();
+                let params: Vec<&str> =
+                    connection.descriptors.iter().map(|d| d.key.as_str()).collect();
+                let result = json!({
+                    "status": "ok",
+                    "track": track,
+                    "plugin": name,
+                    "params": params,
+                    "message": format!("Loaded external synth '{}' into track {}", name, track)
+                });
+                self.dispatch(Command::LoadExternalSynth { track, connection });
+                result
+            }
+            Err(e) => json!({
+                "status": "error",
+                "message": format!("Failed to start plugin: {:#}", e)
+            }),
+        }
+    }
+
+    /// Arm a capture of the audio input into a track, or cancel the one
+    /// armed or running. The UI saves and loads the take once it's done.
+    pub fn record_sample(&self, track: usize, steps: usize, cancel: bool) -> Value {
+        if cancel {
+            self.dispatch(Command::CancelSampleCapture);
+            return json!({ "status": "ok", "message": "Sample capture cancelled" });
+        }
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        if !(1..=MAX_CAPTURE_STEPS).contains(&steps) {
+            return json!({ "status": "error", "message": format!("Steps must be 1-{}", MAX_CAPTURE_STEPS) });
+        }
+        let state = self.sequencer_state.read();
+        if let Some(capture) = state.capture {
+            return json!({
+                "status": "error",
+                "message": format!("A capture into track {} is already armed; cancel it first", capture.track)
+            });
+        }
+        let playing = state.playing;
+        let seconds = steps as f32 * 60.0 / (state.bpm * 4.0);
+        let buffer = capture_buffer(steps, state.bpm, state.sample_rate);
+        drop(state);
+        self.dispatch(Command::RecordSample { track, steps, buffer });
+
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "steps": steps,
+            "seconds": seconds,
+            "starts": if playing { "next step" } else { "now" },
+            "message": format!(
+                "Recording {:.1}s of input into track {}; the normalized take is saved to {} and loaded as a sampler when done (see 'capture' in get_state)",
+                seconds,
+                track,
+                samples::samples_dir().join("recordings").display()
+            )
+        })
+    }
+
+    pub fn load_sample(
+        &self,
+        track: usize,
+        path_str: &str,
+        normalize: Option<bool>,
+        trim_silence: Option<bool>,
+    ) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+
+        // Check track is a sampler
+        let state = self.sequencer_state.read();
+        if track >= state.tracks.len() || state.tracks[track].synth_type != SynthType::Sampler {
+            return json!({
+                "status": "error",
+                "message": format!("Track {} is not a sampler track", track)
+            });
+        }
+        drop(state);
+
+        // Resolve path
+        let dirs = samples::search_dirs();
+        let resolved = samples::resolve_sample_path(path_str, &dirs);
+        let full_path = match resolved {
+            Some(p) => p,
+            None => {
+                return json!({
+                    "status": "error",
+                    "message": format!("Sample not found: '{}'. Searched in {:?}", path_str, dirs)
+                });
+            }
+        };
+
+        // Load WAV
+        let sample_rate = self.sample_rate();
+        match load_wav(&full_path, sample_rate) {
+            Ok(buffer) => {
+                let sample_count = buffer.len();
+                let duration_secs = sample_count as f32 / sample_rate;
+                let path_string = full_path.to_string_lossy().to_string();
+                // Options left out keep the track's current setting
+                let current = self.sequencer_state.read().sample_options(track);
+                let options = SampleOptions {
+                    normalize: normalize.unwrap_or(current.normalize),
+                    trim_silence: trim_silence.unwrap_or(current.trim_silence),
+                };
+                let buffer = prepare_sample(buffer, options);
+                let info = SampleInfo::detect(&buffer, sample_rate);
+                let (detected, detected_bpm) = (info.root_note, info.bpm);
+                let stream_mb = self.sequencer_state.read().sample_stream_mb;
+                self.dispatch(Command::LoadSample {
+                    track,
+                    buffer: SampleAudio::new(buffer, stream_mb),
                     path: path_string.clone(),
+                    options,
+                    info,
+                });
+                json!({
+                    "status": "ok",
+                    "track": track,
+                    "path": path_string,
+                    "samples": sample_count,
+                    "duration_secs": duration_secs,
+                    "detected_root": detected.map(root_json),
+                    "detected_bpm": detected_bpm,
+                    "message": format!("Loaded sample into track {}", track)
+                })
+            }
+            Err(e) => json!({
+                "status": "error",
+                "message": format!("Failed to load WAV: {}", e)
+            }),
+        }
+    }
+
+    /// Number of velocity layers on a sampler track, or an error for other tracks
+    fn sample_layer_count(&self, track: usize) -> Result<usize, Value> {
+        if let Some(err) = self.validate_track(track) {
+            return Err(err);
+        }
+        let state = self.sequencer_state.read();
+        let t = &state.tracks[track];
+        if t.synth_type != SynthType::Sampler {
+            return Err(json!({
+                "status": "error",
+                "message": format!("Track {} is not a sampler track", track)
+            }));
+        }
+        Ok(t.params_snapshot.get("layers").and_then(|v| v.as_array()).map_or(0, |a| a.len()))
+    }
+
+    /// Load a WAV as a velocity layer of a sampler track. Without `layer`
+    /// a new layer is added; the range defaults to all velocities.
+    pub fn load_sample_layer(
+        &self,
+        track: usize,
+        path_str: &str,
+        layer: Option<usize>,
+        min_velocity: Option<u8>,
+        max_velocity: Option<u8>,
+    ) -> Value {
+        let count = match self.sample_layer_count(track) {
+            Ok(count) => count,
+            Err(err) => return err,
+        };
+        let layer = match layer {
+            Some(l) if l >= count => {
+                return json!({
+                    "status": "error",
+                    "message": format!("Layer {} out of range (track has {} layers)", l, count)
+                });
+            }
+            Some(l) => l,
+            None if count >= MAX_SAMPLE_LAYERS => {
+                return json!({
+                    "status": "error",
+                    "message": format!("Max {} velocity layers", MAX_SAMPLE_LAYERS)
                 });
-                json!({
-                    "status": "ok",
-                    "track": track,
-                    "path": path_string,
-                    "samples": sample_count,
-                    "duration_secs": duration_secs,
-                    "message": format!("Loaded sample into track {}", track)
+            }
+            None => count,
+        };
+
+        let dirs = samples::search_dirs();
+        let Some(full_path) = samples::resolve_sample_path(path_str, &dirs) else {
+            return json!({
+                "status": "error",
+                "message": format!("Sample not found: '{}'. Searched in {:?}", path_str, dirs)
+            });
+        };
+        let buffer = match load_wav(&full_path, self.sample_rate()) {
+            Ok(buffer) => buffer,
+            Err(e) => {
+                return json!({
+                    "status": "error",
+                    "message": format!("Failed to load WAV: {}", e)
                 })
             }
-            Err(e) => json!({
+        };
+        let path_string = full_path.to_string_lossy().to_string();
+        let options = self.sequencer_state.read().sample_options(track);
+        self.dispatch(Command::LoadSampleLayer {
+            track,
+            layer,
+            buffer: prepare_sample(buffer, options).into(),
+            path: path_string.clone(),
+        });
+        if min_velocity.is_some() || max_velocity.is_some() {
+            self.dispatch(Command::SetSampleLayerRange {
+                track,
+                layer,
+                min_velocity: min_velocity.unwrap_or(0),
+                max_velocity: max_velocity.unwrap_or(127),
+            });
+        }
+        json!({
+            "status": "ok",
+            "track": track,
+            "layer": layer,
+            "path": path_string,
+            "message": format!("Loaded velocity layer {} into track {}", layer, track)
+        })
+    }
+
+    pub fn set_sample_layer_range(&self, track: usize, layer: usize, min_velocity: u8, max_velocity: u8) -> Value {
+        let count = match self.sample_layer_count(track) {
+            Ok(count) => count,
+            Err(err) => return err,
+        };
+        if layer >= count {
+            return json!({
                 "status": "error",
-                "message": format!("Failed to load WAV: {}", e)
-            }),
+                "message": format!("Layer {} out of range (track has {} layers)", layer, count)
+            });
+        }
+        if min_velocity > max_velocity || max_velocity > 127 {
+            return json!({
+                "status": "error",
+                "message": "Velocity range must satisfy 0 <= min_velocity <= max_velocity <= 127"
+            });
+        }
+        self.dispatch(Command::SetSampleLayerRange { track, layer, min_velocity, max_velocity });
+        json!({
+            "status": "ok",
+            "message": format!(
+                "{} layer {} plays velocity {}-{}",
+                self.track_name(track), layer, min_velocity, max_velocity
+            )
+        })
+    }
+
+    pub fn remove_sample_layer(&self, track: usize, layer: usize) -> Value {
+        let count = match self.sample_layer_count(track) {
+            Ok(count) => count,
+            Err(err) => return err,
+        };
+        if layer >= count {
+            return json!({
+                "status": "error",
+                "message": format!("Layer {} out of range (track has {} layers)", layer, count)
+            });
+        }
+        self.dispatch(Command::RemoveSampleLayer { track, layer });
+        json!({
+            "status": "ok",
+            "message": format!("Removed velocity layer {} from {}", layer, self.track_name(track))
+        })
+    }
+
+    pub fn set_project_key(&self, key: &str) -> Value {
+        let key = match key.trim() {
+            "" | "none" => None,
+            name => match parse_key(name) {
+                Some(k) => Some(k),
+                None => {
+                    return json!({
+                        "status": "error",
+                        "message": format!("Unknown key '{}' (use C, C#, Db .. B, or none)", name)
+                    })
+                }
+            },
+        };
+        self.dispatch(Command::SetProjectKey(key));
+        let name = key.map(|k| NOTE_NAMES[k as usize]);
+        json!({
+            "status": "ok",
+            "key": name,
+            "message": match name {
+                Some(name) => format!("Set project key to {}", name),
+                None => "Cleared project key".to_string(),
+            }
+        })
+    }
+
+    /// Pitch-shift a sampler so its detected root plays the project key's
+    /// tonic (C without a key) from C4
+    pub fn tune_sample_to_key(&self, track: usize) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let state = self.sequencer_state.read();
+        let t = &state.tracks[track];
+        if t.synth_type != SynthType::Sampler {
+            return json!({ "status": "error", "message": "Track is not a sampler" });
+        }
+        let detected = t.params_snapshot.get("detected_note").and_then(|v| v.as_f64());
+        let key = state.key;
+        drop(state);
+        let Some(root) = detected else {
+            return json!({ "status": "error", "message": "No pitch detected in this sample" });
+        };
+        let shift = key_pitch_shift(root as f32, key);
+        self.dispatch(Command::SetTrackParam {
+            track,
+            key: "pitch_shift".to_string(),
+            value: shift,
+        });
+        let target = NOTE_NAMES[key.unwrap_or(0) as usize];
+        json!({
+            "status": "ok",
+            "track": track,
+            "detected_root": root_json(root as f32),
+            "key": target,
+            "pitch_shift": shift,
+            "message": format!("Tuned {} to {} ({:+.2} semitones)", self.track_name(track), target, shift)
+        })
+    }
+
+    /// Repitch a sampler loop so its detected tempo plays at the project BPM
+    pub fn fit_sample_to_bpm(&self, track: usize) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+        let state = self.sequencer_state.read();
+        let t = &state.tracks[track];
+        if t.synth_type != SynthType::Sampler {
+            return json!({ "status": "error", "message": "Track is not a sampler" });
         }
+        let detected = t.params_snapshot.get("detected_bpm").and_then(|v| v.as_f64());
+        let bpm = state.bpm;
+        drop(state);
+        let Some(loop_bpm) = detected else {
+            return json!({ "status": "error", "message": "No tempo detected in this sample" });
+        };
+        let shift = bpm_pitch_shift(loop_bpm as f32, bpm);
+        self.dispatch(Command::SetTrackParam {
+            track,
+            key: "pitch_shift".to_string(),
+            value: shift,
+        });
+        json!({
+            "status": "ok",
+            "track": track,
+            "detected_bpm": loop_bpm,
+            "bpm": bpm,
+            "pitch_shift": shift,
+            "message": format!("Fitted {} ({:.1} BPM) to {:.0} BPM ({:+.2} semitones)", self.track_name(track), loop_bpm, bpm, shift)
+        })
     }
 
     pub fn preview_sample(&self, path_str: &str) -> Value {
@@ -1273,9 +3500,10 @@ impl GridoxideMcp {
             }
         };
 
-        match load_wav(&full_path, 44100.0) {
+        let sample_rate = self.sample_rate();
+        match load_wav(&full_path, sample_rate) {
             Ok(buffer) => {
-                let duration_secs = buffer.len() as f32 / 44100.0;
+                let duration_secs = buffer.len() as f32 / sample_rate;
                 let path_string = full_path.to_string_lossy().to_string();
                 self.dispatch(Command::PreviewSample(buffer));
                 json!({
@@ -1292,6 +3520,143 @@ impl GridoxideMcp {
         }
     }
 
+    pub fn set_preview_duck(&self, db: f32) -> Value {
+        let db = db.clamp(MAX_PREVIEW_DUCK_DB, 0.0);
+        self.dispatch(Command::SetPreviewDuck(db));
+        json!({
+            "status": "ok",
+            "preview_duck_db": db,
+            "message": if db < 0.0 {
+                format!("Mix ducks {:.0} dB under previews", db)
+            } else {
+                "Preview ducking off".to_string()
+            }
+        })
+    }
+
+    pub fn reload_sample(&self, track: usize) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+
+        let state = self.sequencer_state.read();
+        let wav_path = state.tracks[track]
+            .params_snapshot
+            .get("wav_path")
+            .and_then(|v| v.as_str())
+            .map(|p| p.to_string());
+        let is_sampler = state.tracks[track].synth_type == SynthType::Sampler;
+        drop(state);
+
+        let wav_path = match wav_path {
+            Some(p) if is_sampler && !p.is_empty() => p,
+            _ => {
+                return json!({
+                    "status": "error",
+                    "message": format!("Track {} has no sample loaded", track)
+                });
+            }
+        };
+
+        match load_wav(Path::new(&wav_path), self.sample_rate()) {
+            Ok(buffer) => {
+                let options = self.sequencer_state.read().sample_options(track);
+                let buffer = prepare_sample(buffer, options);
+                let sample_count = buffer.len();
+                let info = SampleInfo::detect(&buffer, self.sample_rate());
+                let stream_mb = self.sequencer_state.read().sample_stream_mb;
+                self.dispatch(Command::LoadSample {
+                    track,
+                    buffer: SampleAudio::new(buffer, stream_mb),
+                    path: wav_path.clone(),
+                    options,
+                    info,
+                });
+                json!({
+                    "status": "ok",
+                    "track": track,
+                    "path": wav_path,
+                    "samples": sample_count,
+                    "message": format!("Reloaded sample on track {} from disk", track)
+                })
+            }
+            Err(e) => json!({
+                "status": "error",
+                "message": format!("Failed to reload WAV: {}", e)
+            }),
+        }
+    }
+
+    pub fn get_memory_usage(&self) -> Value {
+        let state = self.sequencer_state.read();
+        let to_mb = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+        let tracks: Vec<Value> = state
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                json!({
+                    "track": i,
+                    "name": t.name,
+                    "bytes": t.sample_bytes,
+                    "mb": to_mb(t.sample_bytes)
+                })
+            })
+            .collect();
+        let total = state.sample_memory_bytes();
+        json!({
+            "tracks": tracks,
+            "total_bytes": total,
+            "total_mb": to_mb(total),
+            "budget_mb": state.sample_budget_mb,
+            "over_budget": state.over_sample_budget(),
+            "stream_above_mb": state.sample_stream_mb
+        })
+    }
+
+    pub fn set_sample_streaming(&self, mb: f32) -> Value {
+        let mb = mb.max(0.0);
+        self.dispatch(Command::SetSampleStreaming(mb));
+        json!({
+            "status": "ok",
+            "stream_above_mb": mb
+        })
+    }
+
+    pub fn set_sample_budget(&self, mb: f32) -> Value {
+        let mb = mb.max(1.0);
+        self.dispatch(Command::SetSampleBudget(mb));
+        json!({
+            "status": "ok",
+            "budget_mb": mb
+        })
+    }
+
+    pub fn chop_to_steps(&self, track: usize) -> Value {
+        if let Some(err) = self.validate_track(track) {
+            return err;
+        }
+
+        let state = self.sequencer_state.read();
+        if state.tracks[track].synth_type != SynthType::Sampler {
+            return json!({
+                "status": "error",
+                "message": format!("Track {} is not a sampler track", track)
+            });
+        }
+        drop(state);
+
+        self.dispatch(Command::ChopToSteps(track));
+        let track_name = self.track_name(track);
+        json!({
+            "status": "ok",
+            "track": track,
+            "track_name": track_name,
+            "slices": STEPS,
+            "message": format!("Chopped {} into {} slices, slice i on step i", track_name, STEPS)
+        })
+    }
+
     pub fn list_samples(&self, directory: Option<&str>) -> Value {
         let dirs = samples::search_dirs();
         let entries = samples::scan_samples(&dirs);
@@ -1325,742 +3690,50 @@ impl GridoxideMcp {
     }
 
     pub fn handle_tool_call(&self, tool: &str, args: &Value) -> Value {
-        match tool {
-            // Transport
-            "play" => self.play(),
-            "pause" => self.pause(),
-            "stop" => self.stop(),
-            "set_bpm" => {
-                let bpm = args.get("bpm").and_then(|v| v.as_f64()).unwrap_or(120.0) as f32;
-                self.set_bpm(bpm)
-            }
-            "get_state" => self.get_state(),
-
-            // Pattern
-            "toggle_step" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let note = args.get("note").and_then(|v| v.as_u64()).map(|n| n as u8);
-                self.toggle_step(track, step, note)
-            }
-            "get_pattern" => {
-                let pattern_index = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
-                self.get_pattern(pattern_index)
-            }
-            "set_step_note" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let note = args.get("note").and_then(|v| v.as_u64()).unwrap_or(60) as u8;
-                self.set_step_note(track, step, note)
-            }
-            "get_step_notes" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.get_step_notes(track)
-            }
-            "set_step_velocity" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let velocity = args.get("velocity").and_then(|v| v.as_u64()).unwrap_or(127) as u8;
-                self.set_step_velocity(track, step, velocity)
-            }
-            "set_step_probability" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let probability = args.get("probability").and_then(|v| v.as_u64()).unwrap_or(100) as u8;
-                self.set_step_probability(track, step, probability)
-            }
-            "clear_track" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.clear_track(track)
-            }
-            "fill_track" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.fill_track(track)
-            }
-
-            // Events
-            "get_events" => {
-                let since_id = args.get("since_id").and_then(|v| v.as_u64()).unwrap_or(0);
-                self.get_events(since_id)
-            }
-
-            // Track Parameters
-            "list_tracks" => self.list_tracks(),
-            "get_track_params" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.get_track_params(track)
-            }
-            "set_param" => {
-                let param = args
-                    .get("param")
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("");
-                let value = args.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                self.set_param(param, value)
-            }
-            "set_track_param" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let key = args.get("key").and_then(|v| v.as_str()).unwrap_or("");
-                let value = args.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                self.set_track_param(track, key, value)
-            }
-            "reset_track" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.reset_track(track)
-            }
-            "add_track" => {
-                let synth_type = args.get("synth_type").and_then(|v| v.as_str()).unwrap_or("kick");
-                let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("NEW");
-                self.add_track(synth_type, name)
-            }
-            "remove_track" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.remove_track(track)
-            }
-
-            // Mixer
-            "get_mixer" => self.get_mixer(),
-            "set_volume" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let volume = args.get("volume").and_then(|v| v.as_f64()).unwrap_or(0.8) as f32;
-                self.set_volume(track, volume)
-            }
-            "set_pan" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let pan = args.get("pan").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                self.set_pan(track, pan)
-            }
-            "toggle_mute" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.toggle_mute(track)
-            }
-            "toggle_solo" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.toggle_solo(track)
-            }
-
-            // FX
-            "get_fx_params" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.get_fx_params(track)
-            }
-            "set_fx_param" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let param = args.get("param").and_then(|v| v.as_str()).unwrap_or("");
-                let value = args.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                self.set_fx_param(track, param, value)
-            }
-            "toggle_fx" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let fx = args.get("fx").and_then(|v| v.as_str()).unwrap_or("");
-                self.toggle_fx(track, fx)
-            }
-            "get_master_fx_params" => self.get_master_fx_params(),
-            "set_master_fx_param" => {
-                let param = args.get("param").and_then(|v| v.as_str()).unwrap_or("");
-                let value = args.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
-                self.set_master_fx_param(param, value)
-            }
-            "toggle_master_fx" => self.toggle_master_fx(),
-
-            // Pattern Bank
-            "select_pattern" => {
-                let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.select_pattern(pattern)
-            }
-            "get_pattern_bank" => self.get_pattern_bank(),
-            "copy_pattern" => {
-                let src = args.get("src").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let dst = args.get("dst").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.copy_pattern(src, dst)
-            }
-            "clear_pattern" => {
-                let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.clear_pattern(pattern)
-            }
-            "set_playback_mode" => {
-                let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("pattern");
-                self.set_playback_mode(mode)
-            }
-
-            // Arrangement
-            "get_arrangement" => self.get_arrangement(),
-            "append_arrangement" => {
-                let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let repeats = args.get("repeats").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-                self.append_arrangement(pattern, repeats)
-            }
-            "insert_arrangement" => {
-                let position = args.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let repeats = args.get("repeats").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-                self.insert_arrangement(position, pattern, repeats)
-            }
-            "remove_arrangement" => {
-                let position = args.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                self.remove_arrangement(position)
-            }
-            "set_arrangement_entry" => {
-                let position = args.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let repeats = args.get("repeats").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
-                self.set_arrangement_entry(position, pattern, repeats)
-            }
-            "clear_arrangement" => self.clear_arrangement(),
-
-            // Pattern Variations
-            "set_variation" => {
-                let variation = args.get("variation").and_then(|v| v.as_str()).unwrap_or("A");
-                self.set_variation(variation)
-            }
-            "toggle_variation" => self.toggle_variation(),
-            "copy_variation" => {
-                let from = args.get("from").and_then(|v| v.as_str()).unwrap_or("A");
-                let to = args.get("to").and_then(|v| v.as_str()).unwrap_or("B");
-                self.copy_variation(from, to)
-            }
-
-            // Project I/O
-            "save_project" => {
-                let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("project.grox");
-                self.save_project(path)
-            }
-            "load_project" => {
-                let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("project.grox");
-                self.load_project(path)
-            }
-            "export_wav" => {
-                let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("export.wav");
-                let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("pattern");
-                let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
-                self.export_wav_file(path, mode, pattern)
-            }
-            "list_projects" => {
-                let directory = args.get("directory").and_then(|v| v.as_str());
-                self.list_projects(directory)
-            }
-
-            // Sample tools
-            "load_sample" => {
-                let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-                let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
-                self.load_sample(track, path)
-            }
-            "preview_sample" => {
-                let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
-                self.preview_sample(path)
-            }
-            "list_samples" => {
-                let directory = args.get("directory").and_then(|v| v.as_str());
-                self.list_samples(directory)
-            }
-
-            _ => json!({ "status": "error", "message": format!("Unknown tool: {}", tool) }),
+        match tools::find(tool) {
+            Some(def) => (def.handler)(self, args),
+            None => json!({ "status": "error", "message": format!("Unknown tool: {}", tool) }),
         }
     }
 
     /// Get the list of available tools (for MCP discovery)
     pub fn list_tools() -> Value {
-        json!({
-            "tools": [
-                {
-                    "name": "play",
-                    "description": "Start playback",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "pause",
-                    "description": "Pause playback, keeping the current step position.",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "stop",
-                    "description": "Stop playback and reset to step 0",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "set_bpm",
-                    "description": "Set the tempo in BPM (60-200)",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "bpm": { "type": "number", "description": "Tempo in beats per minute (60-200)" } },
-                        "required": ["bpm"]
-                    }
-                },
-                {
-                    "name": "get_state",
-                    "description": "Get current transport state (playing, bpm, current_step, current_pattern, playback_mode, arrangement_position)",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "toggle_step",
-                    "description": "Toggle a step on/off. Tracks: 0-based index. Steps: 0-15.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "step": { "type": "integer", "description": "Step index (0-15)" },
-                            "note": { "type": "integer", "description": "Optional MIDI note (0-127) to set before toggling. If omitted, uses the step's existing note." }
-                        },
-                        "required": ["track", "step"]
-                    }
-                },
-                {
-                    "name": "get_pattern",
-                    "description": "Get the full pattern grid showing all tracks and steps. Optionally specify a pattern slot (0-15) to view.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "pattern": { "type": "integer", "description": "Optional pattern slot index (0-15). If omitted, returns the active pattern." } }
-                    }
-                },
-                {
-                    "name": "set_step_note",
-                    "description": "Set the MIDI note for a step. Each step can have its own pitch (0-127).",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "step": { "type": "integer", "description": "Step index (0-15)" },
-                            "note": { "type": "integer", "description": "MIDI note number (0-127). 60=C4, 69=A4(440Hz)." }
-                        },
-                        "required": ["track", "step", "note"]
-                    }
-                },
-                {
-                    "name": "get_step_notes",
-                    "description": "Get all step data for a track including notes, velocity, and probability. Shows data for each of the 16 steps.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "set_step_velocity",
-                    "description": "Set the velocity for a step. Velocity affects the volume/intensity of the triggered sound (0=silent, 127=full).",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "step": { "type": "integer", "description": "Step index (0-15)" },
-                            "velocity": { "type": "integer", "description": "MIDI velocity (0-127). 127=full volume, 64=half, 0=silent." }
-                        },
-                        "required": ["track", "step", "velocity"]
-                    }
-                },
-                {
-                    "name": "set_step_probability",
-                    "description": "Set the trigger probability for a step. The step will randomly trigger based on this percentage.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "step": { "type": "integer", "description": "Step index (0-15)" },
-                            "probability": { "type": "integer", "description": "Trigger probability (0-100%). 100=always, 50=half the time, 0=never." }
-                        },
-                        "required": ["track", "step", "probability"]
-                    }
-                },
-                {
-                    "name": "clear_track",
-                    "description": "Clear all steps on a track",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "fill_track",
-                    "description": "Fill all steps on a track (all 16 steps active)",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "get_events",
-                    "description": "Get recent events/commands since a given ID. Use this to 'listen' to what the human is doing.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "since_id": { "type": "integer", "description": "Return events with ID greater than this value. Use 0 to get all recent events." } }
-                    }
-                },
-                {
-                    "name": "list_tracks",
-                    "description": "List all tracks with their synth types and available parameters",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "get_track_params",
-                    "description": "Get all parameters for a specific track with current values, ranges, and defaults",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "set_param",
-                    "description": "Set a synth parameter by key. Supports prefixed keys (e.g. 'kick_pitch_start') for backward compatibility. Use list_tracks or get_track_params to see available keys.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "param": { "type": "string", "description": "Parameter key (e.g., 'kick_pitch_start', 'pitch_start')" },
-                            "value": { "type": "number", "description": "New value for the parameter (will be clamped to valid range)" }
-                        },
-                        "required": ["param", "value"]
-                    }
-                },
-                {
-                    "name": "set_track_param",
-                    "description": "Set a parameter on a specific track by key. More explicit than set_param.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "key": { "type": "string", "description": "Parameter key (e.g., 'pitch_start', 'decay')" },
-                            "value": { "type": "number", "description": "New value (will be clamped to valid range)" }
-                        },
-                        "required": ["track", "key", "value"]
-                    }
-                },
-                {
-                    "name": "reset_track",
-                    "description": "Reset all parameters on a track to their default values",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "add_track",
-                    "description": "Add a new track with the specified synth type. Only works when playback is stopped.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "synth_type": { "type": "string", "description": "Synth type: 'kick', 'snare', 'hihat', 'bass', or 'sampler'" },
-                            "name": { "type": "string", "description": "Display name for the track" }
-                        },
-                        "required": ["synth_type", "name"]
-                    }
-                },
-                {
-                    "name": "remove_track",
-                    "description": "Remove a track by index. Only works when playback is stopped. Cannot remove the last track.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "get_mixer",
-                    "description": "Get all mixer state (volumes, pans, mutes, solos) for all tracks",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "set_volume",
-                    "description": "Set track volume (0.0-1.0)",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "volume": { "type": "number", "description": "Volume level (0.0 to 1.0)", "minimum": 0.0, "maximum": 1.0 }
-                        },
-                        "required": ["track", "volume"]
-                    }
-                },
-                {
-                    "name": "set_pan",
-                    "description": "Set track pan (-1.0 left to 1.0 right, 0.0 center)",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "pan": { "type": "number", "description": "Pan position (-1.0 = full left, 0.0 = center, 1.0 = full right)", "minimum": -1.0, "maximum": 1.0 }
-                        },
-                        "required": ["track", "pan"]
-                    }
-                },
-                {
-                    "name": "toggle_mute",
-                    "description": "Toggle mute on a track. Muted tracks produce no audio.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "toggle_solo",
-                    "description": "Toggle solo on a track. When any track is soloed, only soloed tracks are audible.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "get_fx_params",
-                    "description": "Get all FX parameters for a track (filter, distortion, delay) with current values and ranges.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
-                        "required": ["track"]
-                    }
-                },
-                {
-                    "name": "set_fx_param",
-                    "description": "Set a per-track FX parameter. Params: filter_cutoff (20-20000 Hz), filter_resonance (0-0.95), filter_type (0=LP, 1=HP, 2=BP), dist_drive (0-1), dist_mix (0-1), delay_time (10-500 ms), delay_feedback (0-0.9), delay_mix (0-1).",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "param": { "type": "string", "description": "Parameter key (e.g., 'filter_cutoff', 'dist_drive', 'delay_time')" },
-                            "value": { "type": "number", "description": "New value (will be clamped to valid range)" }
-                        },
-                        "required": ["track", "param", "value"]
-                    }
-                },
-                {
-                    "name": "toggle_fx",
-                    "description": "Toggle a per-track effect on/off. Each track has filter, distortion, and delay (all off by default).",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based)" },
-                            "fx": { "type": "string", "description": "Effect name: 'filter', 'distortion', or 'delay'" }
-                        },
-                        "required": ["track", "fx"]
-                    }
-                },
-                {
-                    "name": "get_master_fx_params",
-                    "description": "Get master bus FX parameters (reverb) with current values and ranges.",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "set_master_fx_param",
-                    "description": "Set a master bus FX parameter. Params: reverb_decay (0.1-0.95), reverb_mix (0-1), reverb_damping (0-1).",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "param": { "type": "string", "description": "Parameter key: 'reverb_decay', 'reverb_mix', or 'reverb_damping'" },
-                            "value": { "type": "number", "description": "New value (will be clamped to valid range)" }
-                        },
-                        "required": ["param", "value"]
-                    }
-                },
-                {
-                    "name": "toggle_master_fx",
-                    "description": "Toggle master reverb on/off.",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "select_pattern",
-                    "description": "Switch the active pattern slot (0-15). When playing, the switch happens at the next pattern boundary.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "pattern": { "type": "integer", "description": "Pattern slot index (0-15)" } },
-                        "required": ["pattern"]
-                    }
-                },
-                {
-                    "name": "get_pattern_bank",
-                    "description": "Get an overview of all 16 pattern slots showing which have active steps.",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "copy_pattern",
-                    "description": "Copy a pattern from one slot to another.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "src": { "type": "integer", "description": "Source pattern slot (0-15)" },
-                            "dst": { "type": "integer", "description": "Destination pattern slot (0-15)" }
-                        },
-                        "required": ["src", "dst"]
-                    }
-                },
-                {
-                    "name": "clear_pattern",
-                    "description": "Clear all tracks in a pattern slot.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "pattern": { "type": "integer", "description": "Pattern slot index (0-15)" } },
-                        "required": ["pattern"]
-                    }
-                },
-                {
-                    "name": "set_playback_mode",
-                    "description": "Switch between pattern mode (loop single pattern) and song mode (play through arrangement).",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "mode": { "type": "string", "description": "Playback mode: 'pattern' or 'song'" } },
-                        "required": ["mode"]
-                    }
-                },
-                {
-                    "name": "get_arrangement",
-                    "description": "Get the full arrangement (list of pattern entries with repeat counts).",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "append_arrangement",
-                    "description": "Add a pattern entry to the end of the arrangement.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "pattern": { "type": "integer", "description": "Pattern slot index (0-15)" },
-                            "repeats": { "type": "integer", "description": "Number of times to repeat (1-16, default: 1)" }
-                        },
-                        "required": ["pattern"]
-                    }
-                },
-                {
-                    "name": "insert_arrangement",
-                    "description": "Insert a pattern entry at a specific position in the arrangement.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "position": { "type": "integer", "description": "Position to insert at (0-based)" },
-                            "pattern": { "type": "integer", "description": "Pattern slot index (0-15)" },
-                            "repeats": { "type": "integer", "description": "Number of times to repeat (1-16, default: 1)" }
-                        },
-                        "required": ["position", "pattern"]
-                    }
-                },
-                {
-                    "name": "remove_arrangement",
-                    "description": "Remove an entry from the arrangement by position.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "position": { "type": "integer", "description": "Position to remove (0-based)" } },
-                        "required": ["position"]
-                    }
-                },
-                {
-                    "name": "set_arrangement_entry",
-                    "description": "Modify an existing arrangement entry's pattern and repeat count.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "position": { "type": "integer", "description": "Position to modify (0-based)" },
-                            "pattern": { "type": "integer", "description": "Pattern slot index (0-15)" },
-                            "repeats": { "type": "integer", "description": "Number of times to repeat (1-16)" }
-                        },
-                        "required": ["position", "pattern", "repeats"]
-                    }
-                },
-                {
-                    "name": "clear_arrangement",
-                    "description": "Remove all entries from the arrangement.",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "set_variation",
-                    "description": "Set the current pattern variation ('A' or 'B'). Each pattern has two variations that can be programmed independently.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "variation": { "type": "string", "description": "Variation to select: 'A' or 'B'" }
-                        },
-                        "required": ["variation"]
-                    }
-                },
-                {
-                    "name": "toggle_variation",
-                    "description": "Toggle between pattern variation A and B.",
-                    "inputSchema": { "type": "object", "properties": {} }
-                },
-                {
-                    "name": "copy_variation",
-                    "description": "Copy one variation to another within the current pattern.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "from": { "type": "string", "description": "Source variation: 'A' or 'B'" },
-                            "to": { "type": "string", "description": "Destination variation: 'A' or 'B'" }
-                        },
-                        "required": ["from", "to"]
-                    }
-                },
-                {
-                    "name": "save_project",
-                    "description": "Save the current project state to a .grox JSON file.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "path": { "type": "string", "description": "File path to save to (e.g., 'my_song.grox')" } },
-                        "required": ["path"]
-                    }
-                },
-                {
-                    "name": "load_project",
-                    "description": "Load a project from a .grox JSON file. Stops playback and replaces all state.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "path": { "type": "string", "description": "File path to load from (e.g., 'my_song.grox')" } },
-                        "required": ["path"]
-                    }
-                },
-                {
-                    "name": "export_wav",
-                    "description": "Render and export audio as a WAV file (44100Hz, 16-bit stereo).",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "path": { "type": "string", "description": "Output WAV file path (e.g., 'export.wav')" },
-                            "mode": { "type": "string", "description": "Export mode: 'pattern' (single pattern loop) or 'song' (full arrangement)" },
-                            "pattern": { "type": "integer", "description": "Pattern index (0-15) for pattern mode. Defaults to current pattern." }
-                        },
-                        "required": ["path", "mode"]
-                    }
-                },
-                {
-                    "name": "list_projects",
-                    "description": "List .grox project files in a directory.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": { "directory": { "type": "string", "description": "Directory to search (defaults to current directory)" } }
-                    }
-                },
-                {
-                    "name": "load_sample",
-                    "description": "Load a WAV sample into a sampler track. Searches project-local ./samples/ then ~/.gridoxide/samples/, or accepts absolute paths.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "track": { "type": "integer", "description": "Track index (0-based, must be a sampler track)" },
-                            "path": { "type": "string", "description": "Sample path (relative to sample dirs or absolute)" }
-                        },
-                        "required": ["track", "path"]
-                    }
-                },
-                {
-                    "name": "preview_sample",
-                    "description": "Preview/audition a WAV sample through the master bus without loading it into a track.",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "path": { "type": "string", "description": "Sample path (relative to sample dirs or absolute)" }
-                        },
-                        "required": ["path"]
-                    }
-                },
-                {
-                    "name": "list_samples",
-                    "description": "List available WAV samples from sample directories (~/.gridoxide/samples/ and ./samples/).",
-                    "inputSchema": {
-                        "type": "object",
-                        "properties": {
-                            "directory": { "type": "string", "description": "Optional directory filter (e.g., 'kicks', 'snares')" }
-                        }
-                    }
-                }
-            ]
-        })
+        tools::list_tools_json()
+    }
+}
+
+/// Add `warnings` to a response when there are any
+fn with_warnings(mut response: Value, warnings: Vec<String>) -> Value {
+    if !warnings.is_empty() {
+        response["warnings"] = json!(warnings);
+    }
+    response
+}
+
+/// External synth plugins a load left for the caller to check and start
+fn pending_plugins_json(pending: &[(usize, String)]) -> Value {
+    pending
+        .iter()
+        .map(|(track, command)| json!({ "track": track, "command": command }))
+        .collect()
+}
+
+/// A detected sample root as note, name and cents
+fn root_json(note: f32) -> Value {
+    let (nearest, cents) = split_cents(note);
+    json!({
+        "note": nearest,
+        "note_name": note_name(nearest),
+        "cents": (cents * 10.0).round() / 10.0
+    })
+}
+
+/// Note name and cents for params that set pitch (bass frequency, sampler
+/// pitch shift), so agents can tune to a key without doing the math
+fn add_tuning(param: &mut Value, synth_type: SynthType, key: &str, value: f32) {
+    if let Some((note, cents)) = param_tuning(synth_type, key, value) {
+        param["note"] = json!(note);
+        param["note_name"] = json!(note_name(note));
+        param["cents"] = json!((cents * 10.0).round() / 10.0);
     }
 }
@@ -3,6 +3,8 @@ use std::os::unix::net::{UnixListener, UnixStream};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use tracing::{debug, info, warn};
+
 use super::GridoxideMcp;
 
 pub const SOCKET_PATH: &str = "/tmp/gridoxide.sock";
@@ -55,7 +57,11 @@ fn handle_jsonrpc_line(line: &str, mcp: &GridoxideMcp) -> Option<String> {
                 .get("arguments")
                 .cloned()
                 .unwrap_or(serde_json::json!({}));
+            debug!(tool = tool_name, "MCP tool call");
             let tool_result = mcp.handle_tool_call(tool_name, &arguments);
+            if tool_result.get("status").and_then(|s| s.as_str()) == Some("error") {
+                warn!(tool = tool_name, result = %tool_result, "MCP tool call failed");
+            }
             serde_json::json!({
                 "content": [{
                     "type": "text",
@@ -65,6 +71,7 @@ fn handle_jsonrpc_line(line: &str, mcp: &GridoxideMcp) -> Option<String> {
         }
         "notifications/initialized" => return None,
         _ => {
+            warn!(method, "Unknown MCP method");
             serde_json::json!({
                 "error": {
                     "code": -32601,
@@ -90,6 +97,7 @@ fn handle_connection(stream: UnixStream, mcp: &GridoxideMcp) {
         Err(_) => return,
     });
     let mut writer = stream;
+    info!("MCP client connected");
 
     for line in reader.lines() {
         let line = match line {
@@ -109,6 +117,7 @@ fn handle_connection(stream: UnixStream, mcp: &GridoxideMcp) {
             }
         }
     }
+    info!("MCP client disconnected");
 }
 
 /// Start the MCP socket server in a background thread.
@@ -119,8 +128,12 @@ pub fn start_socket_server(mcp: Arc<GridoxideMcp>, shutdown: Arc<AtomicBool>) {
 
     let listener = match UnixListener::bind(SOCKET_PATH) {
         Ok(l) => l,
-        Err(_) => return,
+        Err(e) => {
+            warn!(path = SOCKET_PATH, "MCP socket unavailable: {}", e);
+            return;
+        }
     };
+    info!(path = SOCKET_PATH, "MCP socket listening");
 
     // Non-blocking so we can check the shutdown flag periodically
     listener.set_nonblocking(true).ok();
@@ -149,6 +162,7 @@ pub fn start_socket_server(mcp: Arc<GridoxideMcp>, shutdown: Arc<AtomicBool>) {
 /// Returns Ok(()) on success, Err if the socket is not available.
 pub fn run_as_proxy() -> Result<(), std::io::Error> {
     let stream = UnixStream::connect(SOCKET_PATH)?;
+    info!(path = SOCKET_PATH, "MCP proxy connected");
     let mut socket_reader = BufReader::new(stream.try_clone()?);
     let mut socket_writer = stream;
 
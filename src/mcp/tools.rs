@@ -0,0 +1,2111 @@
+use serde_json::{json, Value};
+
+use super::GridoxideMcp;
+use crate::audio::{MAX_CAPTURE_STEPS, MAX_GLYPH_CHARS};
+use crate::sequencer::{
+    TrackAutomation, MAX_FADE_OUT_BARS, MAX_REPEATS, MAX_STEPS, MAX_SWING, MAX_TRACKS, MAX_TRACK_TRANSPOSE,
+    MAX_TRANSPOSE, NUM_PATTERNS, STEPS,
+};
+
+/// One MCP tool: its discovery metadata and the handler that runs it.
+/// `tools/list`, `tools/call` and `gridoxide mcp-schema` all read this
+/// registry, so a tool can't be listed without being callable (or vice versa).
+pub struct ToolDef {
+    pub name: &'static str,
+    /// Heading used when rendering documentation
+    pub category: &'static str,
+    pub description: &'static str,
+    pub input_schema: fn() -> Value,
+    pub handler: fn(&GridoxideMcp, &Value) -> Value,
+}
+
+impl ToolDef {
+    /// Entry for the `tools/list` response
+    pub fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "inputSchema": (self.input_schema)()
+        })
+    }
+}
+
+/// Integer argument limited to `min..=max`; `{range}` in the description
+/// is replaced with the range so it can't go stale when a limit changes
+fn int_arg(min: usize, max: usize, description: &str) -> Value {
+    json!({
+        "type": "integer",
+        "minimum": min,
+        "maximum": max,
+        "description": description.replace("{range}", &format!("{}-{}", min, max)),
+    })
+}
+
+/// Look up a tool by name
+pub fn find(name: &str) -> Option<&'static ToolDef> {
+    TOOLS.iter().find(|t| t.name == name)
+}
+
+/// Full `tools/list` result
+pub fn list_tools_json() -> Value {
+    let tools: Vec<Value> = TOOLS.iter().map(ToolDef::to_json).collect();
+    json!({ "tools": tools })
+}
+
+/// Markdown reference of every tool, grouped by category
+pub fn tools_markdown() -> String {
+    let mut out = String::from("# Gridoxide MCP Tools\n");
+    let mut category = "";
+    for tool in TOOLS {
+        if tool.category != category {
+            category = tool.category;
+            out.push_str(&format!("\n## {}\n", category));
+        }
+        out.push_str(&format!("\n### `{}`\n\n{}\n", tool.name, tool.description));
+
+        let schema = (tool.input_schema)();
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+        let Some(props) = schema.get("properties").and_then(|p| p.as_object()) else {
+            continue;
+        };
+        if props.is_empty() {
+            continue;
+        }
+        out.push_str("\n| Parameter | Type | Required | Description |\n|---|---|---|---|\n");
+        for (name, prop) in props {
+            let ty = prop.get("type").and_then(|t| t.as_str()).unwrap_or("any");
+            let desc = prop
+                .get("description")
+                .and_then(|d| d.as_str())
+                .unwrap_or("")
+                .replace('|', "\\|");
+            let req = if required.contains(&name.as_str()) { "yes" } else { "no" };
+            out.push_str(&format!("| `{}` | {} | {} | {} |\n", name, ty, req, desc));
+        }
+    }
+    out
+}
+
+pub static TOOLS: &[ToolDef] = &[
+    ToolDef {
+        name: "play",
+        category: "Transport",
+        description: "Start playback",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.play(),
+    },
+    ToolDef {
+        name: "pause",
+        category: "Transport",
+        description: "Pause playback, keeping the current step position.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.pause(),
+    },
+    ToolDef {
+        name: "stop",
+        category: "Transport",
+        description: "Stop playback and reset to step 0",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.stop(),
+    },
+    ToolDef {
+        name: "set_bpm",
+        category: "Transport",
+        description: "Set the tempo in BPM (60-200)",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "bpm": { "type": "number", "description": "Tempo in beats per minute (60-200)" } },
+            "required": ["bpm"]
+        }),
+        handler: |mcp, args| {
+            let bpm = args.get("bpm").and_then(|v| v.as_f64()).unwrap_or(120.0) as f32;
+            mcp.set_bpm(bpm)
+        },
+    },
+    ToolDef {
+        name: "set_midi_sync",
+        category: "Transport",
+        description: "Choose the clock source. 'slave' follows MIDI clock and start/continue/stop from a MIDI input (tempo and steps come from the pulses; play waits for them), 'master' sends MIDI clock and start/continue/stop to a MIDI output, 'internal' does neither. The port is the one given with --midi-port, else the first available. Not saved with the project.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "mode": { "type": "string", "enum": ["internal", "slave", "master"], "description": "Clock source" }
+            },
+            "required": ["mode"]
+        }),
+        handler: |mcp, args| {
+            let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("internal");
+            mcp.set_midi_sync(mode)
+        },
+    },
+    ToolDef {
+        name: "play_tone",
+        category: "Transport",
+        description: "Start the utility tone generator for speaker checks and dub sirens. It isn't on a track, so it never lands in a pattern or an export, and it keeps going when the transport stops. Omitted settings keep their current value. Not saved with the project.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "mode": { "type": "string", "enum": ["sine", "sweep", "pink", "siren"], "description": "Sine at freq, 20 Hz-20 kHz sweep, pink noise, or siren rising an octave from freq" },
+                "freq": { "type": "number", "description": "Sine and siren frequency in Hz (20-20000)" },
+                "level": { "type": "number", "description": "Output level (0.0-1.0)" },
+                "rate": { "type": "number", "description": "Siren sweeps per second (0.1-20)" },
+                "length": { "type": "number", "description": "Seconds until it stops by itself, 0 = until stop_tone (0-60). Also the sweep time." }
+            }
+        }),
+        handler: |mcp, args| {
+            let mode = args.get("mode").and_then(|v| v.as_str());
+            let settings: Vec<(&str, f32)> = ["freq", "level", "rate", "length"]
+                .into_iter()
+                .filter_map(|key| Some((key, args.get(key)?.as_f64()? as f32)))
+                .collect();
+            mcp.play_tone(mode, &settings)
+        },
+    },
+    ToolDef {
+        name: "stop_tone",
+        category: "Transport",
+        description: "Fade out the utility tone generator.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _args| mcp.stop_tone(),
+    },
+    ToolDef {
+        name: "set_transpose",
+        category: "Transport",
+        description: "Transpose the whole project by semitones without editing any notes: applied to bass, sampler and external synth tracks when their steps trigger (drum synths keep their notes). Saved with the project and included in exports.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "semitones": { "type": "integer", "minimum": -(MAX_TRANSPOSE as i32), "maximum": MAX_TRANSPOSE, "description": "Semitones up (positive) or down (negative), 0 = off" }
+            },
+            "required": ["semitones"]
+        }),
+        handler: |mcp, args| {
+            let semitones = args.get("semitones").and_then(|v| v.as_i64()).unwrap_or(0);
+            mcp.set_transpose(semitones)
+        },
+    },
+    ToolDef {
+        name: "set_swing",
+        category: "Transport",
+        description: "Set the swing: every off-beat 16th (steps 1, 3, 5, ...) is delayed by this percent of half a step. 0 is straight, about 66 a triplet shuffle, 100 a dotted feel. Applies to all tracks (triplet steps that start in an off-beat 16th move with it) and MIDI clock out stays straight. Saved with the project and included in exports.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "amount": int_arg(0, MAX_SWING as usize, "Swing percent ({range}, 0 = straight)")
+            },
+            "required": ["amount"]
+        }),
+        handler: |mcp, args| {
+            let amount = args.get("amount").and_then(|v| v.as_u64()).unwrap_or(0);
+            mcp.set_swing(amount)
+        },
+    },
+    ToolDef {
+        name: "get_state",
+        category: "Transport",
+        description: "Get current transport state (playing, bpm, current_step, current_pattern, playback_mode, arrangement_position)",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.get_state(),
+    },
+    ToolDef {
+        name: "toggle_step",
+        category: "Pattern",
+        description: "Toggle a step on/off. Tracks and steps are 0-based indices.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "note": { "type": "integer", "description": "Optional MIDI note (0-127) to set before toggling. If omitted, uses the step's existing note." }
+            },
+            "required": ["track", "step"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let note = args.get("note").and_then(|v| v.as_u64()).map(|n| n as u8);
+            mcp.toggle_step(track, step, note)
+        },
+    },
+    ToolDef {
+        name: "get_grid_view",
+        category: "Pattern",
+        description: "Get a compact ASCII rendering of a pattern grid (one row per track, playhead marker, mute/solo flags) as the human sees it in the TUI. Cheaper to read than get_pattern.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot ({range}, default: current)"),
+                "variation": { "type": "string", "description": "'a' or 'b' (default: current variation)" }
+            }
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let variation = args.get("variation").and_then(|v| v.as_str());
+            mcp.get_grid_view(pattern, variation)
+        },
+    },
+    ToolDef {
+        name: "analyze_pattern",
+        category: "Pattern",
+        description: "Groove analysis of a pattern: for each 16th step, the hits across all tracks, their energy (hits weighted by velocity and probability) and heat (energy relative to a crowded beat of 4 full hits, capped at 1.0). Also lists empty and crowded steps. Triplet steps count on the 16th they start in. The same heat map is shown in the TUI grid with H.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot ({range}, default: current)"),
+                "variation": { "type": "string", "description": "'a' or 'b' (default: current variation)" }
+            }
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let variation = args.get("variation").and_then(|v| v.as_str());
+            mcp.analyze_pattern(pattern, variation)
+        },
+    },
+    ToolDef {
+        name: "get_pattern",
+        category: "Pattern",
+        description: "Get the full pattern grid showing all tracks and steps. Optionally specify a pattern slot to view.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "pattern": int_arg(0, NUM_PATTERNS - 1, "Optional pattern slot index ({range}). If omitted, returns the active pattern.") }
+        }),
+        handler: |mcp, args| {
+            let pattern_index = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            mcp.get_pattern(pattern_index)
+        },
+    },
+    ToolDef {
+        name: "set_step_note",
+        category: "Pattern",
+        description: "Set the MIDI note for a step. Each step can have its own pitch (0-127).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "note": { "type": "integer", "description": "MIDI note number (0-127). 60=C4, 69=A4(440Hz)." }
+            },
+            "required": ["track", "step", "note"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let note = args.get("note").and_then(|v| v.as_u64()).unwrap_or(60) as u8;
+            mcp.set_step_note(track, step, note)
+        },
+    },
+    ToolDef {
+        name: "get_step_notes",
+        category: "Pattern",
+        description: "Get all step data for a track including notes, velocity, probability, ties and note-offs. Shows data for each of the 16 steps.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.get_step_notes(track)
+        },
+    },
+    ToolDef {
+        name: "set_step_velocity",
+        category: "Pattern",
+        description: "Set the velocity for a step. Velocity affects the volume/intensity of the triggered sound (0=silent, 127=full).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "velocity": { "type": "integer", "description": "MIDI velocity (0-127). 127=full volume, 64=half, 0=silent." }
+            },
+            "required": ["track", "step", "velocity"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let velocity = args.get("velocity").and_then(|v| v.as_u64()).unwrap_or(127) as u8;
+            mcp.set_step_velocity(track, step, velocity)
+        },
+    },
+    ToolDef {
+        name: "set_step_probability",
+        category: "Pattern",
+        description: "Set the trigger probability for a step. The step will randomly trigger based on this percentage.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "probability": { "type": "integer", "description": "Trigger probability (0-100%). 100=always, 50=half the time, 0=never." }
+            },
+            "required": ["track", "step", "probability"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let probability = args.get("probability").and_then(|v| v.as_u64()).unwrap_or(100) as u8;
+            mcp.set_step_probability(track, step, probability)
+        },
+    },
+    ToolDef {
+        name: "set_step_tie",
+        category: "Pattern",
+        description: "Tie a step onto the step before it. When both are on, the tied step doesn't retrigger: the note before holds through it (bass and sampler tracks sustain until the run of tied steps ends), for long bass notes and pad swells. Drum synths just ring on.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "tie": { "type": "boolean", "description": "true to tie, false to untie" }
+            },
+            "required": ["track", "step", "tie"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let tie = args.get("tie").and_then(|v| v.as_bool()).unwrap_or(true);
+            mcp.set_step_tie(track, step, tie)
+        },
+    },
+    ToolDef {
+        name: "set_step_off",
+        category: "Pattern",
+        description: "Make a step a note-off step (this turns the step off) or clear it. When playback reaches a note-off step the track's sounding note releases there, at the step's timing, whatever the sampler's hold steps: samplers go into their release, bass, stab and acid tracks fade out quickly, drum synths ring on. Use it to set exact gate lengths.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "off": { "type": "boolean", "description": "true for a note-off step, false to clear it" }
+            },
+            "required": ["track", "step", "off"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let off = args.get("off").and_then(|v| v.as_bool()).unwrap_or(true);
+            mcp.set_step_off(track, step, off)
+        },
+    },
+    ToolDef {
+        name: "set_step_slide",
+        category: "Pattern",
+        description: "Make a step slide into its note. On acid tracks a slide step glides from the note still sounding to its own over the track's glide time, without retriggering the envelopes, for 303 style slides. Other synths ignore it.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "slide": { "type": "boolean", "description": "true to slide, false to clear it" }
+            },
+            "required": ["track", "step", "slide"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let slide = args.get("slide").and_then(|v| v.as_bool()).unwrap_or(true);
+            mcp.set_step_slide(track, step, slide)
+        },
+    },
+    ToolDef {
+        name: "set_step_accent",
+        category: "Pattern",
+        description: "Accent a step. On acid tracks an accented step plays louder with a wider, snappier filter sweep, scaled by the track's accent param. Other synths ignore it.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "accent": { "type": "boolean", "description": "true to accent, false to clear it" }
+            },
+            "required": ["track", "step", "accent"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let accent = args.get("accent").and_then(|v| v.as_bool()).unwrap_or(true);
+            mcp.set_step_accent(track, step, accent)
+        },
+    },
+    ToolDef {
+        name: "set_param_lock",
+        category: "Pattern",
+        description: "Lock a synth parameter on a step of the current pattern and variation: when the step triggers the track plays with this value, and its own value comes back on its next trigger. Keys are the ones get_track_params lists; the value is clamped to the parameter's range. Omit value to remove the lock. A step holds up to 8 locks. Sampler normalize and trim_silence apply when a sample loads and cannot be locked.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)"),
+                "key": { "type": "string", "description": "Parameter key, e.g. \"filter_cutoff\"" },
+                "value": { "type": "number", "description": "Value while the step plays (omit to unlock)" }
+            },
+            "required": ["track", "step", "key"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let key = args.get("key").and_then(|v| v.as_str()).unwrap_or("");
+            let value = args.get("value").and_then(|v| v.as_f64()).map(|v| v as f32);
+            mcp.set_param_lock(track, step, key, value)
+        },
+    },
+    ToolDef {
+        name: "clear_param_locks",
+        category: "Pattern",
+        description: "Remove all parameter locks from a step of the current pattern and variation.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "step": int_arg(0, MAX_STEPS - 1, "Step index ({range}, below the pattern length)")
+            },
+            "required": ["track", "step"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.clear_param_locks(track, step)
+        },
+    },
+    ToolDef {
+        name: "get_param_locks",
+        category: "Pattern",
+        description: "List the parameter locks in the current pattern and variation, optionally for one track, with each locked parameter's own value for comparison.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Only this track (0-based, default all)" }
+            }
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).map(|v| v as usize);
+            mcp.get_param_locks(track)
+        },
+    },
+    ToolDef {
+        name: "pad_hit",
+        category: "Pattern",
+        description: "Play a track's sound immediately, like hitting a pad. With record=true and playback running, the hit is also written into the current pattern at the playhead, keeping its timing within the step as a micro-timing offset.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "velocity": { "type": "integer", "description": "Velocity (1-127, default 127)" },
+                "record": { "type": "boolean", "description": "Write the hit into the playing pattern (default false)" }
+            },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let velocity = args.get("velocity").and_then(|v| v.as_u64()).unwrap_or(127) as u8;
+            let record = args.get("record").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.pad_hit(track, velocity, record)
+        },
+    },
+    ToolDef {
+        name: "scrub_step",
+        category: "Pattern",
+        description: "Audition one step column while playback is stopped: every track's hit at that step plays once, at full probability, with its micro-timing, nudge and parameter locks, so a beat's flams and swing-pushed hits can be checked without playing the loop. Triplet hits that start within the step play at their position in it.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "step": int_arg(0, MAX_STEPS - 1, "Step column ({range}, below the pattern length)")
+            },
+            "required": ["step"]
+        }),
+        handler: |mcp, args| {
+            let step = args.get("step").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.scrub_step(step)
+        },
+    },
+    ToolDef {
+        name: "clear_track",
+        category: "Pattern",
+        description: "Clear all steps on a track",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.clear_track(track)
+        },
+    },
+    ToolDef {
+        name: "quantize_track",
+        category: "Pattern",
+        description: "Snap a track's recorded micro-timings (from loop-record or MIDI import) toward the grid in the current pattern and variation. Strength 100 lands every hit on its nearest step; lower values keep some of the feel.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "strength": int_arg(0, 100, "Percent of the distance to the grid to remove ({range}, default 100)")
+            },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let strength = args.get("strength").and_then(|v| v.as_u64()).unwrap_or(100).min(100) as u8;
+            mcp.quantize_track(track, strength)
+        },
+    },
+    ToolDef {
+        name: "fill_track",
+        category: "Pattern",
+        description: "Fill all steps on a track (all 16 steps active)",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.fill_track(track)
+        },
+    },
+    ToolDef {
+        name: "get_events",
+        category: "Events",
+        description: "Get recent events/commands since a given ID. Use this to 'listen' to what the human is doing. Audio thread errors since the ID are returned under 'errors'.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "since_id": { "type": "integer", "description": "Return events with ID greater than this value. Use 0 to get all recent events." } }
+        }),
+        handler: |mcp, args| {
+            let since_id = args.get("since_id").and_then(|v| v.as_u64()).unwrap_or(0);
+            mcp.get_events(since_id)
+        },
+    },
+    ToolDef {
+        name: "get_command_stats",
+        category: "Events",
+        description: "Command bus statistics for debugging: dispatched commands per type and source, queue depth/capacity/high-water mark, processed count and the most recently dropped commands (dropped when the queue is full).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "dropped_limit": { "type": "integer", "description": "Max dropped commands to return, newest first (default 10)" } }
+        }),
+        handler: |mcp, args| {
+            let limit = args.get("dropped_limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+            mcp.get_command_stats(limit)
+        },
+    },
+    ToolDef {
+        name: "get_session_stats",
+        category: "Events",
+        description: "Session summary: time open, time spent editing (idle gaps over 5 minutes left out), project edits in total and by agents, tracks, pattern slots used, patterns in the arrangement and the song's length in bars and seconds.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.get_session_stats(),
+    },
+    ToolDef {
+        name: "list_tracks",
+        category: "Track Parameters",
+        description: "List all tracks with their synth types and available parameters",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.list_tracks(),
+    },
+    ToolDef {
+        name: "get_track_params",
+        category: "Track Parameters",
+        description: "Get all parameters for a specific track with current values, ranges, and defaults",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.get_track_params(track)
+        },
+    },
+    ToolDef {
+        name: "set_param",
+        category: "Track Parameters",
+        description: "Set a synth parameter by key. Supports prefixed keys (e.g. 'kick_pitch_start') for backward compatibility. Use list_tracks or get_track_params to see available keys.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "param": { "type": "string", "description": "Parameter key (e.g., 'kick_pitch_start', 'pitch_start')" },
+                "value": { "type": "number", "description": "New value for the parameter (will be clamped to valid range)" }
+            },
+            "required": ["param", "value"]
+        }),
+        handler: |mcp, args| {
+            let param = args
+                .get("param")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let value = args.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            mcp.set_param(param, value)
+        },
+    },
+    ToolDef {
+        name: "set_track_param",
+        category: "Track Parameters",
+        description: "Set a parameter on a specific track by key. More explicit than set_param.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "key": { "type": "string", "description": "Parameter key (e.g., 'pitch_start', 'decay')" },
+                "value": { "type": "number", "description": "New value (will be clamped to valid range)" }
+            },
+            "required": ["track", "key", "value"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let key = args.get("key").and_then(|v| v.as_str()).unwrap_or("");
+            let value = args.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            mcp.set_track_param(track, key, value)
+        },
+    },
+    ToolDef {
+        name: "reset_track",
+        category: "Track Parameters",
+        description: "Reset all parameters on a track to their default values",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.reset_track(track)
+        },
+    },
+    ToolDef {
+        name: "add_track",
+        category: "Track Parameters",
+        description: "Add a new track with the specified synth type. Only works when playback is stopped.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "synth_type": { "type": "string", "description": "Synth type: 'kick', 'snare', 'hihat', 'bass', 'sampler', 'input' (live audio input passthrough), 'external' (plugin process, see load_external_synth), 'tone' (test tone / siren) 'stab' (chord stab: each step plays a chord on its note) or 'acid' (303 style monosynth with per-step slide and accent)" },
+                "name": { "type": "string", "description": "Display name for the track" }
+            },
+            "required": ["synth_type", "name"]
+        }),
+        handler: |mcp, args| {
+            let synth_type = args.get("synth_type").and_then(|v| v.as_str()).unwrap_or("kick");
+            let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("NEW");
+            mcp.add_track(synth_type, name)
+        },
+    },
+    ToolDef {
+        name: "load_external_synth",
+        category: "Track Parameters",
+        description: "Start a plugin process (run through the shell) and use it as the track's sound source. Converts the track to an external synth; the plugin's parameters become track params.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "command": { "type": "string", "description": "Shell command that starts the plugin" }
+            },
+            "required": ["track", "command"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let command = args.get("command").and_then(|v| v.as_str()).unwrap_or("");
+            mcp.load_external_synth(track, command)
+        },
+    },
+    ToolDef {
+        name: "remove_track",
+        category: "Track Parameters",
+        description: "Remove a track by index. Only works when playback is stopped. Cannot remove the last track.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.remove_track(track)
+        },
+    },
+    ToolDef {
+        name: "get_mixer",
+        category: "Mixer",
+        description: "Get all mixer state (volumes, pans, mutes, solos, nudges) for all tracks",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.get_mixer(),
+    },
+    ToolDef {
+        name: "get_levels",
+        category: "Mixer",
+        description: "Get peak levels (dBFS, current and 2 s hold), RMS (300 ms) and clip counts: per track post-fader, and on the master after the limiter and before the soft clipper. The master also reports its highest true (inter-sample, 4x oversampled) peak and the limiter's gain reduction. A sample over -0.1 dBFS counts as a clip; clip counts and the true-peak maximum accumulate until reset.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "reset_clips": { "type": "boolean", "description": "Clear the clip counts and true-peak maximum after reading (default false)" }
+            }
+        }),
+        handler: |mcp, args| {
+            let reset_clips = args.get("reset_clips").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.get_levels(reset_clips)
+        },
+    },
+    ToolDef {
+        name: "suggest_gain_staging",
+        category: "Mixer",
+        description: "Render the song or a pattern offline, measure each track's post-fader RMS and the master peak, and suggest volumes: each sounding track is pulled toward the mean RMS (at most 6 dB per call), then all are scaled down if the master would peak above -1 dBFS. Only reports unless apply is true, so call it once to review the trims and again with apply to set them. Silent and muted tracks are left alone.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "mode": { "type": "string", "enum": ["pattern", "song"], "description": "What to measure (default: song in song mode with an arrangement, else pattern)" },
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern index ({range}) for pattern mode. Defaults to current pattern."),
+                "apply": { "type": "boolean", "description": "Set the suggested volumes (default false)" }
+            }
+        }),
+        handler: |mcp, args| {
+            let mode = args.get("mode").and_then(|v| v.as_str());
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let apply = args.get("apply").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.suggest_gain_staging(mode, pattern, apply)
+        },
+    },
+    ToolDef {
+        name: "set_volume",
+        category: "Mixer",
+        description: "Set track volume (0.0-1.0)",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "volume": { "type": "number", "description": "Volume level (0.0 to 1.0)", "minimum": 0.0, "maximum": 1.0 }
+            },
+            "required": ["track", "volume"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let volume = args.get("volume").and_then(|v| v.as_f64()).unwrap_or(0.8) as f32;
+            mcp.set_volume(track, volume)
+        },
+    },
+    ToolDef {
+        name: "set_pan",
+        category: "Mixer",
+        description: "Set track pan (-1.0 left to 1.0 right, 0.0 center)",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "pan": { "type": "number", "description": "Pan position (-1.0 = full left, 0.0 = center, 1.0 = full right)", "minimum": -1.0, "maximum": 1.0 }
+            },
+            "required": ["track", "pan"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let pan = args.get("pan").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            mcp.set_pan(track, pan)
+        },
+    },
+    ToolDef {
+        name: "set_track_nudge",
+        category: "Mixer",
+        description: "Set a track's timing nudge in milliseconds (0-50). Positive values lay the track back behind the grid.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "ms": { "type": "number", "description": "Delay in milliseconds (0.0 to 50.0)", "minimum": 0.0, "maximum": 50.0 }
+            },
+            "required": ["track", "ms"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let ms = args.get("ms").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            mcp.set_track_nudge(track, ms)
+        },
+    },
+    ToolDef {
+        name: "set_track_transpose",
+        category: "Mixer",
+        description: "Transpose one track by semitones without editing its steps, applied when notes trigger (e.g. -12 to drop a copied bass line an octave). Adds to the global transpose on melodic tracks. Saved with the project.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "semitones": { "type": "integer", "minimum": -(MAX_TRACK_TRANSPOSE as i32), "maximum": MAX_TRACK_TRANSPOSE, "description": "Semitones up (positive) or down (negative), 0 = off" }
+            },
+            "required": ["track", "semitones"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let semitones = args.get("semitones").and_then(|v| v.as_i64()).unwrap_or(0);
+            mcp.set_track_transpose(track, semitones)
+        },
+    },
+    ToolDef {
+        name: "set_track_glyph",
+        category: "Mixer",
+        description: "Set a 1-2 character glyph shown before the track name in the grid and mixer, so tracks stay recognisable when names are cut short. Omit glyph (or pass an empty string) to go back to the one derived from the synth type (K, S, H, B, Sm, In, Ex, ~). Saved with the project.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "glyph": { "type": "string", "maxLength": MAX_GLYPH_CHARS, "description": "1-2 characters, e.g. \"Kk\" or \"*\"" }
+            },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let glyph = args.get("glyph").and_then(|v| v.as_str());
+            mcp.set_track_glyph(track, glyph)
+        },
+    },
+    ToolDef {
+        name: "set_track_triplet",
+        category: "Mixer",
+        description: "Switch a track to triplet mode: the bar is divided into 12 steps (0-11) instead of 16, for shuffled hats and triplet fills. Other tracks keep the 16-step grid. Steps 12-15 are kept but silent while triplet mode is on.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "enabled": { "type": "boolean", "description": "true for 12 triplet steps, false for 16 steps" }
+            },
+            "required": ["track", "enabled"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let enabled = args.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.set_track_triplet(track, enabled)
+        },
+    },
+    ToolDef {
+        name: "toggle_mute",
+        category: "Mixer",
+        description: "Toggle mute on a track. Muted tracks produce no audio.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.toggle_mute(track)
+        },
+    },
+    ToolDef {
+        name: "toggle_solo",
+        category: "Mixer",
+        description: "Toggle solo on a track. When any track is soloed, only soloed and solo-safe tracks are audible.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.toggle_solo(track)
+        },
+    },
+    ToolDef {
+        name: "toggle_solo_safe",
+        category: "Mixer",
+        description: "Toggle solo safe on a track: it stays audible while other tracks are soloed (e.g. an FX return or a click track), unless it is muted. Saved with the project and applied to exports.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.toggle_solo_safe(track)
+        },
+    },
+    ToolDef {
+        name: "toggle_cue",
+        category: "Mixer",
+        description: "Toggle a track on the headphone cue bus, to pre-listen it (even while muted) before bringing it into the main mix. Needs a cue output (gridoxide --cue); get_mixer reports it as cue_output. Headphones follow the master while nothing is cued; sample previews play on the cue bus.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.toggle_cue(track)
+        },
+    },
+    ToolDef {
+        name: "get_fx_params",
+        category: "FX",
+        description: "Get all FX parameters for a track (filter, distortion, delay) with current values and ranges.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.get_fx_params(track)
+        },
+    },
+    ToolDef {
+        name: "set_fx_param",
+        category: "FX",
+        description: "Set a per-track FX parameter. Params: filter_cutoff (20-20000 Hz), filter_resonance (0-0.95), filter_type (0=LP, 1=HP, 2=BP), dist_drive (0-1), dist_mix (0-1), delay_time (10-500 ms), delay_feedback (0-0.9), delay_mix (0-1), mod_depth (0-1, cross-track modulation amount), chain_mix (0-1, dry/wet of the whole chain for parallel processing; 1 = fully processed).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "param": { "type": "string", "description": "Parameter key (e.g., 'filter_cutoff', 'dist_drive', 'delay_time')" },
+                "value": { "type": "number", "description": "New value (will be clamped to valid range)" }
+            },
+            "required": ["track", "param", "value"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let param = args.get("param").and_then(|v| v.as_str()).unwrap_or("");
+            let value = args.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            mcp.set_fx_param(track, param, value)
+        },
+    },
+    ToolDef {
+        name: "set_fx_modulation",
+        category: "FX",
+        description: "Let another track's signal modulate this track at audio rate, before its FX. Target 'amp' ring-modulates the track by the source (metallic, tremolo-like tones); 'drive' pushes the distortion drive up with the source's level (needs distortion on). The source is its dry voice, so it still modulates while muted. The amount is the mod_depth FX param. Omit source to switch modulation off.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track to modulate (0-based)" },
+                "source": { "type": "integer", "description": "Modulating track (0-based, not the track itself); omit to switch off" },
+                "target": { "type": "string", "enum": ["amp", "drive"], "description": "What the source modulates (default: unchanged)" }
+            },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let source = args.get("source").and_then(|v| v.as_u64()).map(|v| v as usize);
+            let target = args.get("target").and_then(|v| v.as_str());
+            mcp.set_fx_modulation(track, source, target)
+        },
+    },
+    ToolDef {
+        name: "toggle_fx",
+        category: "FX",
+        description: "Toggle a per-track effect on/off. Each track has filter, distortion, and delay (all off by default).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "fx": { "type": "string", "description": "Effect name: 'filter', 'distortion', or 'delay'" }
+            },
+            "required": ["track", "fx"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let fx = args.get("fx").and_then(|v| v.as_str()).unwrap_or("");
+            mcp.toggle_fx(track, fx)
+        },
+    },
+    ToolDef {
+        name: "get_master_fx_params",
+        category: "FX",
+        description: "Get master bus FX parameters (reverb, mid/side, limiter) with current values and ranges.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.get_master_fx_params(),
+    },
+    ToolDef {
+        name: "set_master_fx_param",
+        category: "FX",
+        description: "Set a master bus FX parameter. Params: reverb_decay (0.1-0.95), reverb_mix (0-1), reverb_damping (0-1), reverb_width (0-1), reverb_gate_threshold (dB, -60-0; dry level that holds a gated tail open), reverb_gate_hold (ms, 20-500; how long the tail stays open after the dry signal drops), ms_mid_level (0-2), ms_side_level (0-2), ms_bass_mono (Hz, 0 = off, up to 500; side below this is removed), ms_side_high (0-2, gain on side above ~3kHz), limiter_ceiling (dBFS, -12-0; true-peak output ceiling), limiter_release (ms, 10-1000).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "param": { "type": "string", "description": "Parameter key: 'reverb_decay', 'reverb_mix', 'reverb_damping', 'reverb_width', 'reverb_gate_threshold', 'reverb_gate_hold', 'ms_mid_level', 'ms_side_level', 'ms_bass_mono', 'ms_side_high', 'limiter_ceiling', or 'limiter_release'" },
+                "value": { "type": "number", "description": "New value (will be clamped to valid range)" }
+            },
+            "required": ["param", "value"]
+        }),
+        handler: |mcp, args| {
+            let param = args.get("param").and_then(|v| v.as_str()).unwrap_or("");
+            let value = args.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            mcp.set_master_fx_param(param, value)
+        },
+    },
+    ToolDef {
+        name: "toggle_master_fx",
+        category: "FX",
+        description: "Toggle master reverb on/off.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.toggle_master_fx(),
+    },
+    ToolDef {
+        name: "set_limiter",
+        category: "FX",
+        description: "Enable/disable the master brickwall limiter, the last stage before the output: it looks 1.5 ms ahead and keeps the true peak under limiter_ceiling. Gain reduction shows in get_levels. Saved with the project and applied to exports.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "enabled": { "type": "boolean", "description": "Limiter on/off" } },
+            "required": ["enabled"]
+        }),
+        handler: |mcp, args| {
+            let enabled = args.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.set_limiter(enabled)
+        },
+    },
+    ToolDef {
+        name: "set_reverb_mode",
+        category: "FX",
+        description: "Set the master reverb mode: 'normal' lets the tail decay, 'gated' cuts it once the dry signal stays below reverb_gate_threshold for reverb_gate_hold ms (the classic gated snare). Saved with the project and applied to exports.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "mode": { "type": "string", "enum": ["normal", "gated"], "description": "Reverb mode" }
+            },
+            "required": ["mode"]
+        }),
+        handler: |mcp, args| {
+            let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("");
+            mcp.set_reverb_mode(mode)
+        },
+    },
+    ToolDef {
+        name: "set_gain_match",
+        category: "FX",
+        description: "Enable/disable gain matching: filter and distortion output is auto-leveled to its input (running RMS) so toggling them compares tone, not loudness. Applies to all tracks; not saved with the project.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "enabled": { "type": "boolean", "description": "Gain match on/off" } },
+            "required": ["enabled"]
+        }),
+        handler: |mcp, args| {
+            let enabled = args.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.set_gain_match(enabled)
+        },
+    },
+    ToolDef {
+        name: "set_headroom",
+        category: "Mixer",
+        description: "Enable/disable headroom monitoring: the master soft clipper is bypassed so the mix is heard uncolored (for external mastering), and the master clip counter in get_levels flags overs instead. WAV exports follow it, written as 32-bit float so overs survive. Not saved with the project.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "enabled": { "type": "boolean", "description": "Headroom monitoring on/off" } },
+            "required": ["enabled"]
+        }),
+        handler: |mcp, args| {
+            let enabled = args.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.set_headroom(enabled)
+        },
+    },
+    ToolDef {
+        name: "dj_filter",
+        category: "FX",
+        description: "Set the master DJ sweep filter: -1 (low-pass closed) to 0 (neutral) to 1 (high-pass closed). Pass release=true to glide back to neutral over one beat.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "position": { "type": "number", "description": "Sweep position (-1.0 to 1.0)" },
+                "release": { "type": "boolean", "description": "Glide back to neutral over one beat (ignores position)" }
+            }
+        }),
+        handler: |mcp, args| {
+            let release = args.get("release").and_then(|v| v.as_bool()).unwrap_or(false);
+            let position = args.get("position").and_then(|v| v.as_f64()).map(|v| v as f32);
+            mcp.dj_filter(position, release)
+        },
+    },
+    ToolDef {
+        name: "crossfader",
+        category: "Pattern Bank",
+        description: "DJ-style crossfader between two pattern decks. deck_b starts crossfader mode (or changes the slot): that slot plays on deck B through copies of the track voices, alongside the current pattern on deck A, through the same mixer and FX. position blends them with an equal-power curve (0 = A, 1 = B). off=true leaves the mode; past halfway, deck B's slot becomes the current pattern. Returns the deck state.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "deck_b": int_arg(0, NUM_PATTERNS - 1, "Pattern slot for deck B ({range})"),
+                "position": { "type": "number", "description": "Crossfader position (0.0 = deck A to 1.0 = deck B)" },
+                "off": { "type": "boolean", "description": "Leave crossfader mode" }
+            }
+        }),
+        handler: |mcp, args| {
+            let deck_b = args.get("deck_b").and_then(|v| v.as_u64()).map(|v| v as usize);
+            let position = args.get("position").and_then(|v| v.as_f64()).map(|v| v as f32);
+            let off = args.get("off").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.crossfader(deck_b, position, off)
+        },
+    },
+    ToolDef {
+        name: "select_pattern",
+        category: "Pattern Bank",
+        description: "Switch the active pattern slot. When playing, the switch happens at the next pattern boundary.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot index ({range})") },
+            "required": ["pattern"]
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.select_pattern(pattern)
+        },
+    },
+    ToolDef {
+        name: "get_pattern_bank",
+        category: "Pattern Bank",
+        description: "Get an overview of all pattern slots (with bank labels A01-D16) showing which have active steps.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.get_pattern_bank(),
+    },
+    ToolDef {
+        name: "copy_pattern",
+        category: "Pattern Bank",
+        description: "Copy a pattern from one slot to another. Pass 'tracks' to copy only those tracks (both variations), and 'merge' to add the source's active steps on top of the destination instead of overwriting those tracks.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "src": int_arg(0, NUM_PATTERNS - 1, "Source pattern slot ({range})"),
+                "dst": int_arg(0, NUM_PATTERNS - 1, "Destination pattern slot ({range})"),
+                "tracks": { "type": "array", "items": { "type": "integer" }, "description": "Optional track indices to copy (default: whole pattern)" },
+                "merge": { "type": "boolean", "description": "With tracks: keep the destination's other steps (default false)" }
+            },
+            "required": ["src", "dst"]
+        }),
+        handler: |mcp, args| {
+            let src = args.get("src").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let dst = args.get("dst").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let tracks = args.get("tracks").and_then(|v| v.as_array()).map(|a| {
+                a.iter()
+                    .filter_map(|t| t.as_u64())
+                    .map(|t| t as usize)
+                    .collect::<Vec<_>>()
+            });
+            let merge = args.get("merge").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.copy_pattern(src, dst, tracks, merge)
+        },
+    },
+    ToolDef {
+        name: "mutate_pattern",
+        category: "Pattern Bank",
+        description: "Write a bounded random variation of a pattern into a slot: a few hits move to nearby steps and velocities/notes drift slightly. Useful for building A/B/C sections. Returns the seed so the result can be reproduced.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Source pattern slot ({range}, default: current)"),
+                "dst": int_arg(0, NUM_PATTERNS - 1, "Destination slot ({range}, default: same as source)"),
+                "amount": { "type": "number", "description": "How much to change, 0.0-1.0 (default 0.25)" },
+                "seed": { "type": "integer", "description": "Optional seed for a reproducible result" }
+            }
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let dst = args.get("dst").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let amount = args.get("amount").and_then(|v| v.as_f64()).unwrap_or(0.25) as f32;
+            let seed = args.get("seed").and_then(|v| v.as_u64()).map(|n| n as u32);
+            mcp.mutate_pattern(pattern, dst, amount, seed)
+        },
+    },
+    ToolDef {
+        name: "clear_pattern",
+        category: "Pattern Bank",
+        description: "Clear all tracks in a pattern slot.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot index ({range})") },
+            "required": ["pattern"]
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.clear_pattern(pattern)
+        },
+    },
+    ToolDef {
+        name: "set_pattern_length",
+        category: "Pattern Bank",
+        description: "Set how many 16th steps a pattern loops over. Shortening drops the steps past the new end; new steps start off. Triplet tracks get 3 steps per 4.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot ({range}, default: current)"),
+                "length": int_arg(1, MAX_STEPS, "Steps per loop ({range}, 16 = one bar)")
+            },
+            "required": ["length"]
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let length = args.get("length").and_then(|v| v.as_u64()).unwrap_or(STEPS as u64) as usize;
+            mcp.set_pattern_length(pattern, length)
+        },
+    },
+    ToolDef {
+        name: "set_track_length",
+        category: "Pattern Bank",
+        description: "Give a track its own loop length in a pattern for polymeters (e.g. hihat every 12 steps against a 16-step kick). The track plays its first `length` steps over and over, counted from when playback started, so it drifts against the bar; steps past it are kept but silent. Triplet tracks count triplet steps. 0, or a length of at least the track's steps, loops with the pattern.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot ({range}, default: current)"),
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "length": int_arg(0, MAX_STEPS, "Steps per track loop ({range}, 0 = with the pattern)")
+            },
+            "required": ["track", "length"]
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let length = args.get("length").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.set_track_length(pattern, track, length)
+        },
+    },
+    ToolDef {
+        name: "export_pattern",
+        category: "Pattern Bank",
+        description: "Export a pattern in the gridoxide-pattern interchange format (JSON or CSV, one entry per active or note-off step in both variations, with offsets, flags and param locks, plus track loop lengths). Returns the text and optionally writes it to a file.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot ({range}, default: current)"),
+                "format": { "type": "string", "description": "'json' (default) or 'csv'" },
+                "path": { "type": "string", "description": "Optional file to write" }
+            }
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let format = args.get("format").and_then(|v| v.as_str()).unwrap_or("json");
+            let path = args.get("path").and_then(|v| v.as_str());
+            mcp.export_pattern(pattern, format, path)
+        },
+    },
+    ToolDef {
+        name: "import_pattern",
+        category: "Pattern Bank",
+        description: "Replace a pattern slot with interchange-format data (as returned by export_pattern). Track numbers map to the current project's tracks.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Destination pattern slot ({range})"),
+                "data": { "type": "string", "description": "Pattern text" },
+                "path": { "type": "string", "description": "File to read instead of data" },
+                "format": { "type": "string", "description": "'json' or 'csv' (default: from path extension, else json)" }
+            },
+            "required": ["pattern"]
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let data = args.get("data").and_then(|v| v.as_str());
+            let format = args.get("format").and_then(|v| v.as_str());
+            let path = args.get("path").and_then(|v| v.as_str());
+            mcp.import_pattern(pattern, data, format, path)
+        },
+    },
+    ToolDef {
+        name: "set_playback_mode",
+        category: "Pattern Bank",
+        description: "Switch between pattern mode (loop single pattern) and song mode (play through arrangement).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "mode": { "type": "string", "description": "Playback mode: 'pattern' or 'song'" } },
+            "required": ["mode"]
+        }),
+        handler: |mcp, args| {
+            let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("pattern");
+            mcp.set_playback_mode(mode)
+        },
+    },
+    ToolDef {
+        name: "get_arrangement",
+        category: "Arrangement",
+        description: "Get the full arrangement (list of pattern entries with repeat counts).",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.get_arrangement(),
+    },
+    ToolDef {
+        name: "append_arrangement",
+        category: "Arrangement",
+        description: "Add a pattern entry to the end of the arrangement.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot index ({range})"),
+                "repeats": int_arg(1, MAX_REPEATS, "Number of times to repeat ({range}, default: 1)")
+            },
+            "required": ["pattern"]
+        }),
+        handler: |mcp, args| {
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let repeats = args.get("repeats").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+            mcp.append_arrangement(pattern, repeats)
+        },
+    },
+    ToolDef {
+        name: "insert_arrangement",
+        category: "Arrangement",
+        description: "Insert a pattern entry at a specific position in the arrangement.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "position": { "type": "integer", "description": "Position to insert at (0-based)" },
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot index ({range})"),
+                "repeats": int_arg(1, MAX_REPEATS, "Number of times to repeat ({range}, default: 1)")
+            },
+            "required": ["position", "pattern"]
+        }),
+        handler: |mcp, args| {
+            let position = args.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let repeats = args.get("repeats").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+            mcp.insert_arrangement(position, pattern, repeats)
+        },
+    },
+    ToolDef {
+        name: "remove_arrangement",
+        category: "Arrangement",
+        description: "Remove an entry from the arrangement by position.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "position": { "type": "integer", "description": "Position to remove (0-based)" } },
+            "required": ["position"]
+        }),
+        handler: |mcp, args| {
+            let position = args.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.remove_arrangement(position)
+        },
+    },
+    ToolDef {
+        name: "set_arrangement_entry",
+        category: "Arrangement",
+        description: "Modify an existing arrangement entry's pattern and repeat count.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "position": { "type": "integer", "description": "Position to modify (0-based)" },
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern slot index ({range})"),
+                "repeats": int_arg(1, MAX_REPEATS, "Number of times to repeat ({range})")
+            },
+            "required": ["position", "pattern", "repeats"]
+        }),
+        handler: |mcp, args| {
+            let position = args.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let repeats = args.get("repeats").and_then(|v| v.as_u64()).unwrap_or(1) as usize;
+            mcp.set_arrangement_entry(position, pattern, repeats)
+        },
+    },
+    ToolDef {
+        name: "set_arrangement_automation",
+        category: "Arrangement",
+        description: "Automate a track's mixer at an arrangement entry: when the entry's first step plays in song mode (and in song exports), the track is muted/unmuted, soloed/unsoloed and/or set to a volume. With fade=true the volume ramps from its current value over the whole entry (fade-ins, fade-outs, builds). Replaces the track's previous automation on that entry; clear=true removes it. The changes stay on the mixer afterwards, like a manual move.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "position": { "type": "integer", "description": "Arrangement entry (0-based)" },
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "mute": { "type": "boolean", "description": "Mute (true) or unmute (false) the track" },
+                "solo": { "type": "boolean", "description": "Solo (true) or unsolo (false) the track" },
+                "volume": { "type": "number", "description": "Volume to reach (0.0-1.0)" },
+                "fade": { "type": "boolean", "description": "Ramp the volume over the entry instead of switching (default false)" },
+                "clear": { "type": "boolean", "description": "Remove this track's automation from the entry" }
+            },
+            "required": ["position", "track"]
+        }),
+        handler: |mcp, args| {
+            let position = args.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let change = TrackAutomation {
+                mute: args.get("mute").and_then(|v| v.as_bool()),
+                solo: args.get("solo").and_then(|v| v.as_bool()),
+                volume: args.get("volume").and_then(|v| v.as_f64()).map(|v| (v as f32).clamp(0.0, 1.0)),
+                fade: args.get("fade").and_then(|v| v.as_bool()).unwrap_or(false),
+                ..TrackAutomation::new(track)
+            };
+            let clear = args.get("clear").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.set_arrangement_automation(position, track, change, clear)
+        },
+    },
+    ToolDef {
+        name: "set_arrangement_chord",
+        category: "Arrangement",
+        description: "Put an arrangement entry on a chord degree of the major scale, so one pattern can play a progression (e.g. I, V, vi, IV). While the entry plays in song mode, melodic tracks (bass, sampler, external) move to the degree's root by the nearest interval: ii +2, iii +4, IV +5, V -5, vi -3, vii -1 semitones, on top of the global transpose. Applied to song exports and MIDI export. Omit chord to clear it.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "position": { "type": "integer", "description": "Arrangement entry (0-based)" },
+                "chord": { "type": "string", "enum": ["I", "ii", "iii", "IV", "V", "vi", "vii"], "description": "Chord degree numeral (omit to clear)" }
+            },
+            "required": ["position"]
+        }),
+        handler: |mcp, args| {
+            let position = args.get("position").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let chord = args.get("chord").and_then(|v| v.as_str());
+            mcp.set_arrangement_chord(position, chord)
+        },
+    },
+    ToolDef {
+        name: "set_fade_out",
+        category: "Arrangement",
+        description: "Fade the master out over the last N bars of the arrangement, in song-mode playback and song exports. Live playback stops at the end of the song instead of looping. 0 turns the fade-out off.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "bars": int_arg(0, MAX_FADE_OUT_BARS, "Fade-out length in bars ({range}, 0 = off)")
+            },
+            "required": ["bars"]
+        }),
+        handler: |mcp, args| {
+            let bars = args.get("bars").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.set_fade_out(bars)
+        },
+    },
+    ToolDef {
+        name: "clear_arrangement",
+        category: "Arrangement",
+        description: "Remove all entries from the arrangement.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.clear_arrangement(),
+    },
+    ToolDef {
+        name: "set_variation",
+        category: "Pattern Variations",
+        description: "Set the current pattern variation ('A' or 'B'). Each pattern has two variations that can be programmed independently.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "variation": { "type": "string", "description": "Variation to select: 'A' or 'B'" }
+            },
+            "required": ["variation"]
+        }),
+        handler: |mcp, args| {
+            let variation = args.get("variation").and_then(|v| v.as_str()).unwrap_or("A");
+            mcp.set_variation(variation)
+        },
+    },
+    ToolDef {
+        name: "toggle_variation",
+        category: "Pattern Variations",
+        description: "Toggle between pattern variation A and B.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.toggle_variation(),
+    },
+    ToolDef {
+        name: "copy_variation",
+        category: "Pattern Variations",
+        description: "Copy one variation to another within the current pattern.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "from": { "type": "string", "description": "Source variation: 'A' or 'B'" },
+                "to": { "type": "string", "description": "Destination variation: 'A' or 'B'" }
+            },
+            "required": ["from", "to"]
+        }),
+        handler: |mcp, args| {
+            let from = args.get("from").and_then(|v| v.as_str()).unwrap_or("A");
+            let to = args.get("to").and_then(|v| v.as_str()).unwrap_or("B");
+            mcp.copy_variation(from, to)
+        },
+    },
+    ToolDef {
+        name: "save_project",
+        category: "Project I/O",
+        description: "Save the current project state to a .grox JSON file. When backups are on in the settings, the file it replaces is kept as <path>.bak1 first, older backups moving down.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "path": { "type": "string", "description": "File path to save to (e.g., 'my_song.grox')" } },
+            "required": ["path"]
+        }),
+        handler: |mcp, args| {
+            let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("project.grox");
+            mcp.save_project(path)
+        },
+    },
+    ToolDef {
+        name: "load_project",
+        category: "Project I/O",
+        description: "Load a project from a .grox JSON file. Stops playback and replaces all state. External synth plugins only start if their command is in the 'plugins' list of config.json or was started with load_external_synth this session; the others are listed in plugins_pending.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "path": { "type": "string", "description": "File path to load from (e.g., 'my_song.grox')" } },
+            "required": ["path"]
+        }),
+        handler: |mcp, args| {
+            let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("project.grox");
+            mcp.load_project(path)
+        },
+    },
+    ToolDef {
+        name: "export_wav",
+        category: "Project I/O",
+        description: "Render and export audio as a WAV file (16-bit stereo, at the output device's sample rate unless given).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Output WAV file path (e.g., 'export.wav')" },
+                "mode": { "type": "string", "description": "Export mode: 'pattern' (single pattern loop) or 'song' (full arrangement)" },
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern index ({range}) for pattern mode. Defaults to current pattern."),
+                "sample_rate": { "type": "integer", "description": "Output sample rate in Hz (e.g. 44100, 48000). Defaults to the output device rate." },
+                "clipper": { "type": "boolean", "description": "Soft-clip the master (16-bit WAV); false keeps overs in a 32-bit float WAV. Defaults to off in headroom monitoring, else on." },
+                "queue": { "type": "boolean", "description": "Add to the background export queue and return a job id at once instead of waiting for the render (default false)" }
+            },
+            "required": ["path", "mode"]
+        }),
+        handler: |mcp, args| {
+            let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("export.wav");
+            let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("pattern");
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let sample_rate = args.get("sample_rate").and_then(|v| v.as_u64()).map(|n| n as u32);
+            let clipper = args.get("clipper").and_then(|v| v.as_bool());
+            let queue = args.get("queue").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.export_wav_file(path, mode, pattern, sample_rate, clipper, queue)
+        },
+    },
+    ToolDef {
+        name: "export_hits",
+        category: "Project I/O",
+        description: "Export a sample kit: each track's current sound as a one-shot mono 16-bit WAV (synth through its track FX, before the fader and pan) at its default note and the given velocity, named like '01_kick.wav'. Hits end when they fall silent (at most 4 s). Tone tracks are left out unless export_tone is on.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "dir": { "type": "string", "description": "Output directory, created if needed (e.g. 'kit')" },
+                "velocity": int_arg(1, 127, "Hit velocity ({range}, default 127)"),
+                "sample_rate": { "type": "integer", "description": "Output sample rate in Hz. Defaults to the output device rate." }
+            },
+            "required": ["dir"]
+        }),
+        handler: |mcp, args| {
+            let dir = args.get("dir").and_then(|v| v.as_str()).unwrap_or("kit");
+            let velocity = args.get("velocity").and_then(|v| v.as_u64()).unwrap_or(127).clamp(1, 127) as u8;
+            let sample_rate = args.get("sample_rate").and_then(|v| v.as_u64()).map(|n| n as u32);
+            mcp.export_hits_dir(dir, velocity, sample_rate)
+        },
+    },
+    ToolDef {
+        name: "export_stems",
+        category: "Project I/O",
+        description: "Export the pattern or song as stems: one mono 32-bit float WAV per track (after its FX, before the fader, pan and mute), named like '01_kick.wav'. Writes session.json (tempo, track volumes, pans, mute/solo, section markers) and a Reaper session.RPP next to them so a DAW rebuilds the mixer layout.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "dir": { "type": "string", "description": "Output directory, created if needed (e.g. 'stems')" },
+                "mode": { "type": "string", "description": "Export mode: 'pattern' (single pattern loop) or 'song' (full arrangement)" },
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern index ({range}) for pattern mode. Defaults to current pattern."),
+                "sample_rate": { "type": "integer", "description": "Output sample rate in Hz. Defaults to the output device rate." },
+                "queue": { "type": "boolean", "description": "Add to the background export queue and return a job id at once instead of waiting for the render (default false)" }
+            },
+            "required": ["dir", "mode"]
+        }),
+        handler: |mcp, args| {
+            let dir = args.get("dir").and_then(|v| v.as_str()).unwrap_or("stems");
+            let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("song");
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let sample_rate = args.get("sample_rate").and_then(|v| v.as_u64()).map(|n| n as u32);
+            let queue = args.get("queue").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.export_stems_dir(dir, mode, pattern, sample_rate, queue)
+        },
+    },
+    ToolDef {
+        name: "get_export_jobs",
+        category: "Project I/O",
+        description: "Status of the background export queue (exports queued from the TUI or with queue=true): each job's id, label, state (queued, running, done, failed, cancelled), progress (0-1) and, once finished, what was written or why it failed. Jobs run one at a time, oldest first. Pass job for a single one.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "job": { "type": "integer", "description": "Job id to report (default: all jobs)" }
+            }
+        }),
+        handler: |mcp, args| {
+            let job = args.get("job").and_then(|v| v.as_u64());
+            mcp.get_export_jobs(job)
+        },
+    },
+    ToolDef {
+        name: "cancel_export_job",
+        category: "Project I/O",
+        description: "Cancel a queued export before it starts. A running export can't be cancelled.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "job": { "type": "integer", "description": "Job id from export_wav/export_stems with queue=true or get_export_jobs" }
+            },
+            "required": ["job"]
+        }),
+        handler: |mcp, args| {
+            let job = args.get("job").and_then(|v| v.as_u64()).unwrap_or(0);
+            mcp.cancel_export_job(job)
+        },
+    },
+    ToolDef {
+        name: "export_midi",
+        category: "Project I/O",
+        description: "Export the pattern or song as a Standard MIDI File: one channel per audible track with notes, volume/pan/cutoff CCs, and a program change (= pattern slot) at each arrangement section. Tempo and section markers are on the first track.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Output MIDI file path (e.g., 'song.mid')" },
+                "mode": { "type": "string", "description": "Export mode: 'pattern' (single pattern loop) or 'song' (full arrangement)" },
+                "pattern": int_arg(0, NUM_PATTERNS - 1, "Pattern index ({range}) for pattern mode. Defaults to current pattern.")
+            },
+            "required": ["path", "mode"]
+        }),
+        handler: |mcp, args| {
+            let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("export.mid");
+            let mode = args.get("mode").and_then(|v| v.as_str()).unwrap_or("pattern");
+            let pattern = args.get("pattern").and_then(|v| v.as_u64()).map(|n| n as usize);
+            mcp.export_midi_file(path, mode, pattern)
+        },
+    },
+    ToolDef {
+        name: "import_midi",
+        category: "Project I/O",
+        description: "Import a Standard MIDI File: note-ons land on the grid keeping their timing within the step as a micro-timing offset (use quantize_track to tighten; triplet tracks snap to 12 steps), channel N maps to track N, and each distinct bar becomes a pattern (identical consecutive bars repeat). Fills empty pattern slots unless 'start' is given, and appends arrangement entries in file order unless arrange=false.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "MIDI file path (.mid)" },
+                "start": int_arg(0, NUM_PATTERNS - 1, "Overwrite slots from this index ({range}) instead of filling empty slots"),
+                "arrange": { "type": "boolean", "description": "Append arrangement entries for the imported bars (default true)" }
+            },
+            "required": ["path"]
+        }),
+        handler: |mcp, args| {
+            let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("import.mid");
+            let start = args.get("start").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let arrange = args.get("arrange").and_then(|v| v.as_bool()).unwrap_or(true);
+            mcp.import_midi_file(path, start, arrange)
+        },
+    },
+    ToolDef {
+        name: "set_render_quality",
+        category: "Project I/O",
+        description: "Set the sampler interpolation used when exporting WAV (saved with the project). 'cubic' and 'sinc' reduce aliasing when samples are pitched up; 'sinc' is slowest but cleanest. The same quality converts samples recorded at another rate to the export rate.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "quality": { "type": "string", "description": "'linear', 'cubic' (default) or 'sinc'" } },
+            "required": ["quality"]
+        }),
+        handler: |mcp, args| {
+            let quality = args.get("quality").and_then(|v| v.as_str()).unwrap_or("cubic");
+            mcp.set_render_quality(quality)
+        },
+    },
+    ToolDef {
+        name: "set_export_tone",
+        category: "Project I/O",
+        description: "Whether tone tracks sound in WAV exports (saved with the project). Off by default, so speaker checks and sirens on tone tracks stay out of renders.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "enabled": { "type": "boolean", "description": "Include tone tracks in exports" } },
+            "required": ["enabled"]
+        }),
+        handler: |mcp, args| {
+            let enabled = args.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.set_export_tone(enabled)
+        },
+    },
+    ToolDef {
+        name: "list_projects",
+        category: "Project I/O",
+        description: "List .grox project files in a directory.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "directory": { "type": "string", "description": "Directory to search (defaults to current directory)" } }
+        }),
+        handler: |mcp, args| {
+            let directory = args.get("directory").and_then(|v| v.as_str());
+            mcp.list_projects(directory)
+        },
+    },
+    ToolDef {
+        name: "list_macros",
+        category: "Macros",
+        description: "List macros recorded in the TUI (Ctrl+R) with their action counts and descriptions.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.list_macros(),
+    },
+    ToolDef {
+        name: "run_macro",
+        category: "Macros",
+        description: "Replay a recorded macro: its actions are dispatched in order, as if performed in the TUI.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "name": { "type": "string", "description": "Macro name (see list_macros)" } },
+            "required": ["name"]
+        }),
+        handler: |mcp, args| {
+            let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            mcp.run_macro(name)
+        },
+    },
+    ToolDef {
+        name: "list_track_templates",
+        category: "Track Templates",
+        description: "List saved track templates (favorite synth + params + FX + mixer setups, stored in ~/.gridoxide/templates.json and shared by all projects).",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.list_track_templates(),
+    },
+    ToolDef {
+        name: "save_track_template",
+        category: "Track Templates",
+        description: "Save a track's synth type, params, FX and mixer settings as a named template. Overwrites a template with the same name. Sample paths are stored absolute.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "name": { "type": "string", "description": "Template name (default: the track's name)" }
+            },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let name = args.get("name").and_then(|v| v.as_str());
+            mcp.save_track_template(track, name)
+        },
+    },
+    ToolDef {
+        name: "add_track_from_template",
+        category: "Track Templates",
+        description: "Add a new track configured from a saved template (empty in every pattern). Only works when playback is stopped.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "name": { "type": "string", "description": "Template name (see list_track_templates)" } },
+            "required": ["name"]
+        }),
+        handler: |mcp, args| {
+            let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            mcp.add_track_from_template(name)
+        },
+    },
+    ToolDef {
+        name: "delete_track_template",
+        category: "Track Templates",
+        description: "Delete a saved track template.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "name": { "type": "string", "description": "Template name" } },
+            "required": ["name"]
+        }),
+        handler: |mcp, args| {
+            let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            mcp.delete_track_template(name)
+        },
+    },
+    ToolDef {
+        name: "list_scripts",
+        category: "Scripts",
+        description: "List the project's scripts (Rhai) with their schedule, enabled state and source.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.list_scripts(),
+    },
+    ToolDef {
+        name: "set_script",
+        category: "Scripts",
+        description: "Add or update a project script. Scripts run at the end of every N bars while playing and can change steps, params, mixer and FX through a sandboxed API (see README).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "description": "Script name (replaces the script with this name)" },
+                "source": { "type": "string", "description": "Rhai source (required for new scripts)" },
+                "every": { "type": "integer", "description": "Run every N bars (1-16, default 1)" },
+                "enabled": { "type": "boolean", "description": "Whether the script runs (default true)" }
+            },
+            "required": ["name"]
+        }),
+        handler: |mcp, args| {
+            let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let source = args.get("source").and_then(|v| v.as_str());
+            let every = args.get("every").and_then(|v| v.as_u64()).map(|v| v as u32);
+            let enabled = args.get("enabled").and_then(|v| v.as_bool());
+            mcp.set_script(name, source, every, enabled)
+        },
+    },
+    ToolDef {
+        name: "remove_script",
+        category: "Scripts",
+        description: "Remove a script from the project.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "name": { "type": "string", "description": "Script name" } },
+            "required": ["name"]
+        }),
+        handler: |mcp, args| {
+            let name = args.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            mcp.remove_script(name)
+        },
+    },
+    ToolDef {
+        name: "load_sample",
+        category: "Sample tools",
+        description: "Load a WAV sample into a sampler track. Searches project-local ./samples/ then ~/.gridoxide/samples/, or accepts absolute paths.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based, must be a sampler track)" },
+                "path": { "type": "string", "description": "Sample path (relative to sample dirs or absolute)" },
+                "normalize": { "type": "boolean", "description": "Normalize peak level (reversible via the 'normalize' track param)" },
+                "trim_silence": { "type": "boolean", "description": "Trim leading/trailing silence (reversible via the 'trim_silence' track param)" }
+            },
+            "required": ["track", "path"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+            let normalize = args.get("normalize").and_then(|v| v.as_bool());
+            let trim_silence = args.get("trim_silence").and_then(|v| v.as_bool());
+            mcp.load_sample(track, path, normalize, trim_silence)
+        },
+    },
+    ToolDef {
+        name: "record_sample",
+        category: "Sample tools",
+        description: "Record the system's default audio input into a track: armed now, the capture starts on the next step while playing (at once when stopped) and runs for the given number of steps at the current tempo. The take is normalized, written as a WAV under ~/.gridoxide/samples/recordings/ and loaded into the track, which becomes a sampler. get_state shows the capture while it is armed or running.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "steps": int_arg(1, MAX_CAPTURE_STEPS, "Capture length in steps ({range}, default 16)"),
+                "cancel": { "type": "boolean", "description": "Cancel the capture armed or running instead" }
+            },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let steps = args.get("steps").and_then(|v| v.as_u64()).unwrap_or(16) as usize;
+            let cancel = args.get("cancel").and_then(|v| v.as_bool()).unwrap_or(false);
+            mcp.record_sample(track, steps, cancel)
+        },
+    },
+    ToolDef {
+        name: "load_kit",
+        category: "Sample tools",
+        description: "Start from a sample pack: put the WAVs directly in a folder (sorted by name) on sampler tracks from track 0 on, one file per track. Tracks are named by keywords in the file names (kick/bd -> KICK, snare/sd -> SNARE, clap, rim, open hat -> OHAT, hat/hh -> HIHAT, tom, crash/ride -> CYMB, perc, bass/808), else after the file. Existing tracks become samplers and keep their steps and mixer settings; missing ones are added. Stops playback.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "folder": { "type": "string", "description": "Kit folder (relative to sample dirs or absolute)" },
+                "count": int_arg(1, MAX_TRACKS, "Load only the first N files ({range}, default all)")
+            },
+            "required": ["folder"]
+        }),
+        handler: |mcp, args| {
+            let folder = args.get("folder").and_then(|v| v.as_str()).unwrap_or("");
+            let count = args.get("count").and_then(|v| v.as_u64()).map(|v| v as usize);
+            mcp.load_kit(folder, count)
+        },
+    },
+    ToolDef {
+        name: "load_sample_layer",
+        category: "Sample tools",
+        description: "Load a WAV as a velocity layer of a sampler track: hits whose velocity falls in the layer's range play its sample instead of the main one (first matching layer wins). Omit layer to add one (max 8). Layers are listed under 'layers' in get_track_params.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based, must be a sampler track)" },
+                "path": { "type": "string", "description": "Sample path (relative to sample dirs or absolute)" },
+                "layer": { "type": "integer", "description": "Existing layer to replace (0-based); omit to add a layer" },
+                "min_velocity": { "type": "integer", "description": "Lowest velocity the layer plays for (0-127, default 0)" },
+                "max_velocity": { "type": "integer", "description": "Highest velocity the layer plays for (0-127, default 127)" }
+            },
+            "required": ["track", "path"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+            let layer = args.get("layer").and_then(|v| v.as_u64()).map(|v| v as usize);
+            let min_velocity = args.get("min_velocity").and_then(|v| v.as_u64()).map(|v| v.min(127) as u8);
+            let max_velocity = args.get("max_velocity").and_then(|v| v.as_u64()).map(|v| v.min(127) as u8);
+            mcp.load_sample_layer(track, path, layer, min_velocity, max_velocity)
+        },
+    },
+    ToolDef {
+        name: "set_sample_layer_range",
+        category: "Sample tools",
+        description: "Set the velocity range a sampler track's velocity layer plays for.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "layer": { "type": "integer", "description": "Layer index (0-based)" },
+                "min_velocity": { "type": "integer", "description": "Lowest velocity (0-127)" },
+                "max_velocity": { "type": "integer", "description": "Highest velocity (0-127)" }
+            },
+            "required": ["track", "layer", "min_velocity", "max_velocity"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let layer = args.get("layer").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let min_velocity = args.get("min_velocity").and_then(|v| v.as_u64()).unwrap_or(0).min(255) as u8;
+            let max_velocity = args.get("max_velocity").and_then(|v| v.as_u64()).unwrap_or(127).min(255) as u8;
+            mcp.set_sample_layer_range(track, layer, min_velocity, max_velocity)
+        },
+    },
+    ToolDef {
+        name: "remove_sample_layer",
+        category: "Sample tools",
+        description: "Remove a velocity layer from a sampler track. Later layers move down one index.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Track index (0-based)" },
+                "layer": { "type": "integer", "description": "Layer index (0-based)" }
+            },
+            "required": ["track", "layer"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let layer = args.get("layer").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.remove_sample_layer(track, layer)
+        },
+    },
+    ToolDef {
+        name: "set_project_key",
+        category: "Sample tools",
+        description: "Set the project key (tonic only, e.g. 'A', 'F#', 'Bb'; 'none' clears it). Used by tune_sample_to_key.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "key": { "type": "string", "description": "Key name (C, C#/Db .. B) or 'none'" }
+            },
+            "required": ["key"]
+        }),
+        handler: |mcp, args| {
+            let key = args.get("key").and_then(|v| v.as_str()).unwrap_or("none");
+            mcp.set_project_key(key)
+        },
+    },
+    ToolDef {
+        name: "tune_sample_to_key",
+        category: "Sample tools",
+        description: "Set a sampler track's pitch_shift so the root detected when its sample loaded plays as the project key's tonic (C if no key is set) when triggered at C4. Fails for unpitched samples.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Sampler track index (0-based)" }
+            },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.tune_sample_to_key(track)
+        },
+    },
+    ToolDef {
+        name: "fit_sample_to_bpm",
+        category: "Sample tools",
+        description: "Set a sampler track's pitch_shift so a loop plays at the project BPM, using the tempo detected when the sample loaded (repitching: speed and pitch change together; the closest half/double time is used). Fails for one-shots.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "track": { "type": "integer", "description": "Sampler track index (0-based)" }
+            },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.fit_sample_to_bpm(track)
+        },
+    },
+    ToolDef {
+        name: "preview_sample",
+        category: "Sample tools",
+        description: "Preview/audition a WAV sample through the master bus without loading it into a track.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string", "description": "Sample path (relative to sample dirs or absolute)" }
+            },
+            "required": ["path"]
+        }),
+        handler: |mcp, args| {
+            let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("");
+            mcp.preview_sample(path)
+        },
+    },
+    ToolDef {
+        name: "set_preview_duck",
+        category: "Sample tools",
+        description: "Set how far the mix ducks while a sample preview plays during playback (dB, 0 = off, down to -36). The mix eases back when the preview ends. Saved with the project.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "db": { "type": "number", "description": "Duck level in dB (-36 to 0, default -12)" }
+            },
+            "required": ["db"]
+        }),
+        handler: |mcp, args| {
+            let db = args.get("db").and_then(|v| v.as_f64()).unwrap_or(-12.0) as f32;
+            mcp.set_preview_duck(db)
+        },
+    },
+    ToolDef {
+        name: "reload_sample",
+        category: "Sample tools",
+        description: "Reload a sampler track's sample from disk (e.g. after editing the file externally).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based, must be a sampler track)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.reload_sample(track)
+        },
+    },
+    ToolDef {
+        name: "get_memory_usage",
+        category: "Sample tools",
+        description: "Get sample memory usage per track and the project total, compared against the project's sample memory budget.",
+        input_schema: || json!({ "type": "object", "properties": {} }),
+        handler: |mcp, _| mcp.get_memory_usage(),
+    },
+    ToolDef {
+        name: "set_sample_budget",
+        category: "Sample tools",
+        description: "Set the project's sample memory budget in MB (saved with the project). Loading past the budget shows a warning.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "mb": { "type": "number", "description": "Budget in megabytes (minimum 1)" } },
+            "required": ["mb"]
+        }),
+        handler: |mcp, args| {
+            let mb = args.get("mb").and_then(|v| v.as_f64()).unwrap_or(256.0) as f32;
+            mcp.set_sample_budget(mb)
+        },
+    },
+    ToolDef {
+        name: "set_sample_streaming",
+        category: "Sample tools",
+        description: "Stream main samples larger than this many MB from disk instead of holding them in memory (0 = never, saved with the project). Applies to samples loaded afterwards; streamed samples keep about 1 MB in memory and have no crossfader deck B copy.",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "mb": { "type": "number", "description": "Size in megabytes above which samples stream (0 = off)" } },
+            "required": ["mb"]
+        }),
+        handler: |mcp, args| {
+            let mb = args.get("mb").and_then(|v| v.as_f64()).unwrap_or(0.0) as f32;
+            mcp.set_sample_streaming(mb)
+        },
+    },
+    ToolDef {
+        name: "chop_to_steps",
+        category: "Sample tools",
+        description: "Slice a sampler track's sample into 16 equal slices and write one trigger per step so slice i plays on step i (current pattern and variation).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": { "track": { "type": "integer", "description": "Track index (0-based, must be a sampler track)" } },
+            "required": ["track"]
+        }),
+        handler: |mcp, args| {
+            let track = args.get("track").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            mcp.chop_to_steps(track)
+        },
+    },
+    ToolDef {
+        name: "list_samples",
+        category: "Sample tools",
+        description: "List available WAV samples from sample directories (~/.gridoxide/samples/ and ./samples/).",
+        input_schema: || json!({
+            "type": "object",
+            "properties": {
+                "directory": { "type": "string", "description": "Optional directory filter (e.g., 'kicks', 'snares')" }
+            }
+        }),
+        handler: |mcp, args| {
+            let directory = args.get("directory").and_then(|v| v.as_str());
+            mcp.list_samples(directory)
+        },
+    },
+];
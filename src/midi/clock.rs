@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+/// MIDI clock pulses per quarter note
+pub const PULSES_PER_BEAT: u32 = 24;
+/// MIDI clock pulses per 16th step
+pub const PULSES_PER_STEP: u32 = PULSES_PER_BEAT / 4;
+
+/// MIDI realtime status bytes
+pub const CLOCK_PULSE: u8 = 0xF8;
+pub const CLOCK_START: u8 = 0xFA;
+pub const CLOCK_CONTINUE: u8 = 0xFB;
+pub const CLOCK_STOP: u8 = 0xFC;
+
+/// Where the sequencer takes its tempo from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MidiSync {
+    /// Own clock, no MIDI clock sent
+    #[default]
+    Internal,
+    /// Follow MIDI clock and Start/Continue/Stop from the input port
+    Slave,
+    /// Own clock, sent as MIDI clock and Start/Continue/Stop to the output port
+    Master,
+}
+
+impl MidiSync {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MidiSync::Internal => "internal",
+            MidiSync::Slave => "slave",
+            MidiSync::Master => "master",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "internal" | "off" => Some(MidiSync::Internal),
+            "slave" | "in" => Some(MidiSync::Slave),
+            "master" | "out" => Some(MidiSync::Master),
+            _ => None,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            MidiSync::Internal => MidiSync::Slave,
+            MidiSync::Slave => MidiSync::Master,
+            MidiSync::Master => MidiSync::Internal,
+        }
+    }
+}
+
+/// MIDI realtime message relevant to clock sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockMessage {
+    Pulse,
+    Start,
+    Continue,
+    Stop,
+}
+
+impl ClockMessage {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            CLOCK_PULSE => Some(ClockMessage::Pulse),
+            CLOCK_START => Some(ClockMessage::Start),
+            CLOCK_CONTINUE => Some(ClockMessage::Continue),
+            CLOCK_STOP => Some(ClockMessage::Stop),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+pub mod clock;
+pub mod notes;
+
+use anyhow::{anyhow, Context, Result};
+use crossbeam_channel::{Receiver, Sender};
+use midir::{Ignore, MidiInput, MidiInputConnection, MidiInputPort, MidiOutput, MidiOutputPort};
+use tracing::info;
+
+pub use clock::{ClockMessage, MidiSync};
+pub use notes::NoteOn;
+
+/// Client name seen by other MIDI software
+const CLIENT_NAME: &str = "gridoxide";
+/// Name of the virtual ports created when no hardware port is available
+const VIRTUAL_PORT: &str = "gridoxide";
+
+/// Names of the MIDI input and output ports (for --list-devices)
+pub fn port_names() -> Result<(Vec<String>, Vec<String>)> {
+    let input = MidiInput::new(CLIENT_NAME).context("Failed to open MIDI input")?;
+    let output = MidiOutput::new(CLIENT_NAME).context("Failed to open MIDI output")?;
+    let inputs = input.ports().iter().filter_map(|p| input.port_name(p).ok()).collect();
+    let outputs = output.ports().iter().filter_map(|p| output.port_name(p).ok()).collect();
+    Ok((inputs, outputs))
+}
+
+/// Open the first MIDI input whose name contains `port` (any input when
+/// None) and forward its clock messages to `clock` and its NoteOns to
+/// `notes`. Without a matching port a virtual input is created where the
+/// platform supports it. Returns the connection, which must be kept alive,
+/// and the port name.
+pub fn open_input(
+    port: Option<&str>,
+    clock: Sender<ClockMessage>,
+    notes: Sender<NoteOn>,
+) -> Result<(MidiInputConnection<()>, String)> {
+    let mut input = MidiInput::new(CLIENT_NAME).context("Failed to open MIDI input")?;
+    // Realtime messages are needed; sysex and active sensing aren't
+    input.ignore(Ignore::SysexAndActiveSense);
+    let forward = move |_: u64, message: &[u8], _: &mut ()| {
+        if let Some(note) = NoteOn::from_message(message) {
+            let _ = notes.try_send(note);
+            return;
+        }
+        for &byte in message {
+            if let Some(message) = ClockMessage::from_byte(byte) {
+                let _ = clock.try_send(message);
+            }
+        }
+    };
+    let found: Option<(MidiInputPort, String)> = input.ports().into_iter().find_map(|p| {
+        let name = input.port_name(&p).ok()?;
+        port.is_none_or(|wanted| name.contains(wanted)).then_some((p, name))
+    });
+    let (connection, name) = match found {
+        Some((p, name)) => {
+            let connection = input
+                .connect(&p, "input", forward, ())
+                .map_err(|e| anyhow!("Failed to connect to MIDI input '{}': {}", name, e))?;
+            (connection, name)
+        }
+        None => virtual_input(input, port, forward)?,
+    };
+    info!(port = name, "MIDI input opened");
+    Ok((connection, name))
+}
+
+#[cfg(unix)]
+fn virtual_input<F>(input: MidiInput, port: Option<&str>, forward: F) -> Result<(MidiInputConnection<()>, String)>
+where
+    F: FnMut(u64, &[u8], &mut ()) + Send + 'static,
+{
+    use midir::os::unix::VirtualInput;
+    if let Some(wanted) = port {
+        return Err(anyhow!("No MIDI input matching '{}'", wanted));
+    }
+    let connection = input
+        .create_virtual(VIRTUAL_PORT, forward, ())
+        .map_err(|e| anyhow!("Failed to create virtual MIDI input: {}", e))?;
+    Ok((connection, VIRTUAL_PORT.to_string()))
+}
+
+#[cfg(not(unix))]
+fn virtual_input<F>(_input: MidiInput, port: Option<&str>, _forward: F) -> Result<(MidiInputConnection<()>, String)>
+where
+    F: FnMut(u64, &[u8], &mut ()) + Send + 'static,
+{
+    match port {
+        Some(wanted) => Err(anyhow!("No MIDI input matching '{}'", wanted)),
+        None => Err(anyhow!("No MIDI input available")),
+    }
+}
+
+/// Open the first MIDI output whose name contains `port` (any output when
+/// None, or a virtual output where supported) and send it the bytes queued
+/// on `rx` from a thread of its own. Returns the port name.
+pub fn open_clock_output(port: Option<&str>, rx: Receiver<u8>) -> Result<String> {
+    let output = MidiOutput::new(CLIENT_NAME).context("Failed to open MIDI output")?;
+    let found: Option<(MidiOutputPort, String)> = output.ports().into_iter().find_map(|p| {
+        let name = output.port_name(&p).ok()?;
+        port.is_none_or(|wanted| name.contains(wanted)).then_some((p, name))
+    });
+    let (mut connection, name) = match found {
+        Some((p, name)) => {
+            let connection = output
+                .connect(&p, "clock out")
+                .map_err(|e| anyhow!("Failed to connect to MIDI output '{}': {}", name, e))?;
+            (connection, name)
+        }
+        None => virtual_output(output, port)?,
+    };
+    std::thread::Builder::new()
+        .name("midi-clock-out".to_string())
+        .spawn(move || {
+            // Ends when the engine drops its sender
+            while let Ok(byte) = rx.recv() {
+                let _ = connection.send(&[byte]);
+            }
+        })
+        .context("Failed to start the MIDI output thread")?;
+    info!(port = name, "MIDI clock output opened");
+    Ok(name)
+}
+
+#[cfg(unix)]
+fn virtual_output(output: MidiOutput, port: Option<&str>) -> Result<(midir::MidiOutputConnection, String)> {
+    use midir::os::unix::VirtualOutput;
+    if let Some(wanted) = port {
+        return Err(anyhow!("No MIDI output matching '{}'", wanted));
+    }
+    let connection = output
+        .create_virtual(VIRTUAL_PORT)
+        .map_err(|e| anyhow!("Failed to create virtual MIDI output: {}", e))?;
+    Ok((connection, VIRTUAL_PORT.to_string()))
+}
+
+#[cfg(not(unix))]
+fn virtual_output(_output: MidiOutput, port: Option<&str>) -> Result<(midir::MidiOutputConnection, String)> {
+    match port {
+        Some(wanted) => Err(anyhow!("No MIDI output matching '{}'", wanted)),
+        None => Err(anyhow!("No MIDI output available")),
+    }
+}
@@ -0,0 +1,35 @@
+/// MIDI channel (0-based) whose notes are drum pads: channel 10 in GM
+pub const DRUM_CHANNEL: u8 = 9;
+/// Pad note that plays track 1 (C1, where most pad controllers start)
+pub const PAD_BASE_NOTE: u8 = 36;
+
+/// Incoming NoteOn (a NoteOn with velocity 0 is a NoteOff and isn't one)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoteOn {
+    pub channel: u8,
+    pub note: u8,
+    pub velocity: u8,
+}
+
+impl NoteOn {
+    pub fn from_message(message: &[u8]) -> Option<Self> {
+        match *message {
+            [status, note, velocity] if status & 0xF0 == 0x90 && velocity > 0 => Some(Self {
+                channel: status & 0x0F,
+                note: note & 0x7F,
+                velocity: velocity & 0x7F,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Track a drum channel pad plays: PAD_BASE_NOTE is track 1, the pads
+    /// above it the following tracks. None for other channels and notes.
+    pub fn pad_track(&self, num_tracks: usize) -> Option<usize> {
+        if self.channel != DRUM_CHANNEL {
+            return None;
+        }
+        let track = self.note.checked_sub(PAD_BASE_NOTE)? as usize;
+        (track < num_tracks).then_some(track)
+    }
+}
@@ -0,0 +1,53 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+
+/// Most backups kept of one project
+pub const MAX_BACKUPS: usize = 10;
+
+/// Path of a project's nth backup (1 = newest): `<project>.bak<n>`
+pub fn backup_path(project: &Path, n: usize) -> PathBuf {
+    let mut name = project.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".bak{}", n));
+    project.with_file_name(name)
+}
+
+/// Before a save over `project`: shift its backups down one (dropping the
+/// oldest and any past `count`) and copy the file as it is now to
+/// `.bak1`. Nothing happens with `count` 0 or no file there yet.
+pub fn rotate_backups(project: &Path, count: usize) -> Result<()> {
+    let count = count.min(MAX_BACKUPS);
+    if count == 0 || !project.is_file() {
+        return Ok(());
+    }
+    for n in count..=MAX_BACKUPS {
+        let path = backup_path(project, n);
+        if path.exists() {
+            std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+    }
+    for n in (1..count).rev() {
+        let from = backup_path(project, n);
+        if from.exists() {
+            let to = backup_path(project, n + 1);
+            std::fs::rename(&from, &to).with_context(|| format!("Failed to rename {}", from.display()))?;
+        }
+    }
+    // Copied rather than moved, so a failed save still leaves the project in place
+    let first = backup_path(project, 1);
+    std::fs::copy(project, &first).with_context(|| format!("Failed to back up to {}", first.display()))?;
+    Ok(())
+}
+
+/// A project's backups that exist, newest first, with when each was written
+pub fn list_backups(project: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    (1..=MAX_BACKUPS)
+        .map(|n| backup_path(project, n))
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
@@ -0,0 +1,101 @@
+use crate::audio::{level_db, SequencerState};
+
+use super::renderer::{measure_levels, ExportMode};
+
+/// Highest master peak the suggestion allows (dBFS)
+pub const GAIN_CEILING_DB: f32 = -1.0;
+/// Largest balancing trim suggested for one track in one pass (dB)
+const MAX_TRIM_DB: f32 = 6.0;
+/// Tracks quieter than this (RMS, dBFS) are left alone
+const SILENT_DB: f32 = -70.0;
+
+/// Suggested volume for one track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackTrim {
+    pub track: usize,
+    /// Post-fader RMS at the current volume (dBFS)
+    pub rms_db: f32,
+    pub volume: f32,
+    pub suggested: f32,
+}
+
+impl TrackTrim {
+    /// Change from the current volume in dB (0.0 when nothing changes)
+    pub fn trim_db(&self) -> f32 {
+        if self.volume <= 0.0 || self.suggested == self.volume {
+            return 0.0;
+        }
+        level_db(self.suggested) - level_db(self.volume)
+    }
+}
+
+/// Volume trims that balance the tracks and keep the master under the ceiling
+#[derive(Debug, Clone, PartialEq)]
+pub struct GainStaging {
+    /// One per track; silent and muted tracks keep their volume
+    pub trims: Vec<TrackTrim>,
+    /// Level every sounding track is pulled toward (mean RMS, dBFS)
+    pub target_db: f32,
+    /// Master peak now and with the suggested volumes (dBFS, before the
+    /// soft clipper)
+    pub peak_db: f32,
+    pub suggested_peak_db: f32,
+}
+
+impl GainStaging {
+    /// Whether applying it changes any volume
+    pub fn changes(&self) -> bool {
+        self.trims.iter().any(|t| t.suggested != t.volume)
+    }
+}
+
+/// Render `mode` offline, then pull each sounding track's RMS toward the
+/// mean (at most MAX_TRIM_DB per pass) and scale everything down if the
+/// master would still peak above GAIN_CEILING_DB. Takes two renders.
+pub fn suggest_gain_staging(state: &SequencerState, mode: &ExportMode, sample_rate: u32) -> GainStaging {
+    let report = measure_levels(state, mode, sample_rate);
+    let rms_db: Vec<f32> = report.track_rms.iter().map(|&rms| level_db(rms)).collect();
+    let sounding: Vec<f32> = rms_db.iter().copied().filter(|&db| db > SILENT_DB).collect();
+    let target_db = if sounding.is_empty() {
+        SILENT_DB
+    } else {
+        sounding.iter().sum::<f32>() / sounding.len() as f32
+    };
+
+    let mut trims: Vec<TrackTrim> = state
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(track, t)| {
+            let suggested = if rms_db[track] > SILENT_DB {
+                let trim = (target_db - rms_db[track]).clamp(-MAX_TRIM_DB, MAX_TRIM_DB);
+                (t.volume * 10f32.powf(trim / 20.0)).clamp(0.0, 1.0)
+            } else {
+                t.volume
+            };
+            TrackTrim { track, rms_db: rms_db[track], volume: t.volume, suggested }
+        })
+        .collect();
+
+    // The master chain is linear before the clipper, so one uniform
+    // trim brings the balanced mix's peak under the ceiling
+    let mut balanced = state.clone();
+    for trim in &trims {
+        balanced.tracks[trim.track].volume = trim.suggested;
+    }
+    let mut suggested_peak = measure_levels(&balanced, mode, sample_rate).master_peak;
+    let ceiling = 10f32.powf(GAIN_CEILING_DB / 20.0);
+    let scale = if suggested_peak > ceiling { ceiling / suggested_peak } else { 1.0 };
+    suggested_peak *= scale;
+    for trim in trims.iter_mut().filter(|t| t.rms_db > SILENT_DB) {
+        // Rounded to the 0.01 the mixer shows
+        trim.suggested = (trim.suggested * scale * 100.0).round() / 100.0;
+    }
+
+    GainStaging {
+        trims,
+        target_db,
+        peak_db: level_db(report.master_peak),
+        suggested_peak_db: level_db(suggested_peak),
+    }
+}
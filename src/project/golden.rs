@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::load_project;
+use super::renderer::{render_frames, ExportMode};
+
+/// Sample rate golden renders are made at (independent of the audio device)
+const GOLDEN_SAMPLE_RATE: u32 = 44100;
+
+/// Band centers (Hz) for the coarse spectrum stored alongside the hash
+const SPECTRUM_BANDS: [f32; 10] = [
+    31.0, 63.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// Largest per-band level change (dB) tolerated when the hash differs
+const SPECTRUM_TOLERANCE_DB: f32 = 0.5;
+
+/// Reference fingerprint of an offline render
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenRender {
+    pub frames: usize,
+    /// FNV-1a hash of the 16-bit PCM output
+    pub hash: String,
+    /// Level per SPECTRUM_BANDS entry (dB, both channels summed)
+    pub spectrum_db: Vec<f32>,
+}
+
+/// Outcome of comparing a render against its golden file
+pub enum GoldenOutcome {
+    /// Golden file was (re)written
+    Updated(GoldenRender),
+    /// No golden file to compare against (and no update requested)
+    Missing,
+    /// Bit-exact match
+    Match,
+    /// Hash differs but every band is within tolerance
+    Close { max_band_diff_db: f32 },
+    /// Length or spectrum differs beyond tolerance
+    Mismatch { reason: String },
+}
+
+/// Default golden path for a project: `<project>.golden.json`
+pub fn golden_path_for(project: &Path) -> PathBuf {
+    project.with_extension("golden.json")
+}
+
+/// Render a project's song (or current pattern if it has no arrangement)
+/// and fingerprint it. Renders use the renderer's fixed PRNG seed, so the
+/// same project and code always give the same result.
+pub fn fingerprint_project(project: &Path) -> Result<GoldenRender> {
+    let mut data = load_project(project)?;
+    data.validate_and_clamp();
+    let state = data.to_state();
+    let frames = render_frames(&state, &ExportMode::Song, GOLDEN_SAMPLE_RATE);
+    Ok(fingerprint(&frames))
+}
+
+/// Compare a project's render against its golden file, or write it when
+/// `update` is set. A missing golden file is reported, never created.
+pub fn check_golden(project: &Path, golden: &Path, update: bool) -> Result<GoldenOutcome> {
+    if !update && !golden.exists() {
+        return Ok(GoldenOutcome::Missing);
+    }
+    let actual = fingerprint_project(project)?;
+
+    if update {
+        let json = serde_json::to_string_pretty(&actual)
+            .context("Failed to serialize golden render")?;
+        std::fs::write(golden, json)
+            .with_context(|| format!("Failed to write {}", golden.display()))?;
+        return Ok(GoldenOutcome::Updated(actual));
+    }
+
+    let json = std::fs::read_to_string(golden)
+        .with_context(|| format!("Failed to read {}", golden.display()))?;
+    let expected: GoldenRender = serde_json::from_str(&json)
+        .with_context(|| format!("Failed to parse {}", golden.display()))?;
+
+    if expected.hash == actual.hash && expected.frames == actual.frames {
+        return Ok(GoldenOutcome::Match);
+    }
+    if expected.frames != actual.frames {
+        return Ok(GoldenOutcome::Mismatch {
+            reason: format!("length changed: {} -> {} frames", expected.frames, actual.frames),
+        });
+    }
+
+    let max_band_diff_db = expected
+        .spectrum_db
+        .iter()
+        .zip(&actual.spectrum_db)
+        .map(|(e, a)| (e - a).abs())
+        .fold(0.0f32, f32::max);
+    if max_band_diff_db <= SPECTRUM_TOLERANCE_DB {
+        Ok(GoldenOutcome::Close { max_band_diff_db })
+    } else {
+        Ok(GoldenOutcome::Mismatch {
+            reason: format!("spectrum changed by up to {:.2} dB", max_band_diff_db),
+        })
+    }
+}
+
+fn fingerprint(frames: &[(f32, f32)]) -> GoldenRender {
+    // Hash the quantized output so it matches what export_wav writes
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (l, r) in frames {
+        for s in [l, r] {
+            let pcm = (*s * 32767.0).clamp(-32768.0, 32767.0) as i16;
+            for byte in pcm.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    let mono: Vec<f32> = frames.iter().map(|(l, r)| (l + r) * 0.5).collect();
+    let spectrum_db = SPECTRUM_BANDS
+        .iter()
+        .map(|&hz| goertzel_db(&mono, hz, GOLDEN_SAMPLE_RATE as f32))
+        .collect();
+
+    GoldenRender {
+        frames: frames.len(),
+        hash: format!("{:016x}", hash),
+        spectrum_db,
+    }
+}
+
+/// Level of one frequency over the whole signal (Goertzel), in dB
+fn goertzel_db(signal: &[f32], hz: f32, sample_rate: f32) -> f32 {
+    if signal.is_empty() {
+        return -120.0;
+    }
+    let coeff = 2.0 * (2.0 * std::f64::consts::PI * hz as f64 / sample_rate as f64).cos();
+    let (mut s1, mut s2) = (0.0f64, 0.0f64);
+    for &x in signal {
+        let s0 = x as f64 + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s0;
+    }
+    let power = (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0);
+    let magnitude = power.sqrt() / signal.len() as f64;
+    (20.0 * magnitude.max(1e-6).log10()) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fixture projects, each with a committed golden file
+    fn fixtures() -> Vec<PathBuf> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let mut projects: Vec<PathBuf> = std::fs::read_dir(dir)
+            .expect("fixtures directory")
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "grox"))
+            .collect();
+        projects.sort();
+        projects
+    }
+
+    #[test]
+    fn fixtures_match_their_goldens() {
+        let projects = fixtures();
+        assert!(!projects.is_empty(), "no fixture projects");
+        for project in projects {
+            let golden = golden_path_for(&project);
+            match check_golden(&project, &golden, false).unwrap() {
+                GoldenOutcome::Match | GoldenOutcome::Close { .. } => {}
+                GoldenOutcome::Missing => panic!("{} has no golden file", project.display()),
+                GoldenOutcome::Mismatch { reason } => panic!("{}: {}", project.display(), reason),
+                GoldenOutcome::Updated(_) => unreachable!("not updating"),
+            }
+        }
+    }
+
+    #[test]
+    fn missing_golden_fails_unless_updating() {
+        let project = &fixtures()[0];
+        let golden = std::env::temp_dir().join(format!("gridoxide-{}.golden.json", std::process::id()));
+        assert!(matches!(check_golden(project, &golden, false).unwrap(), GoldenOutcome::Missing));
+        assert!(!golden.exists());
+        assert!(matches!(check_golden(project, &golden, true).unwrap(), GoldenOutcome::Updated(_)));
+        assert!(matches!(check_golden(project, &golden, false).unwrap(), GoldenOutcome::Match));
+        std::fs::remove_file(golden).unwrap();
+    }
+}
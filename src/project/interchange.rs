@@ -0,0 +1,469 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::sequencer::{Pattern, Variation, MAX_LOCKS_PER_STEP, MAX_STEPS, MAX_TRACKS, STEPS};
+
+pub const INTERCHANGE_FORMAT: &str = "gridoxide-pattern";
+/// Version 2 added offsets, step flags, param locks and track lengths
+pub const INTERCHANGE_VERSION: u32 = 2;
+
+const CSV_HEADER: &str = "track,name,variation,step,note,velocity,probability,offset,flags,locks";
+/// Columns of version 1 files, which still import
+const CSV_V1_COLUMNS: usize = 7;
+const CSV_COLUMNS: usize = 10;
+
+/// One active or note-off step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternEvent {
+    pub track: usize,
+    /// "a" or "b"
+    pub variation: String,
+    pub step: usize,
+    pub note: u8,
+    #[serde(default = "default_velocity")]
+    pub velocity: u8,
+    #[serde(default = "default_probability")]
+    pub probability: u8,
+    /// Micro-timing, in percent of a step (0-99)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: u8,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub tie: bool,
+    /// Note-off step: releases the sounding note and doesn't play
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub off: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub slide: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub accent: bool,
+    /// Parameter locks on the step, by key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub locks: BTreeMap<String, f32>,
+}
+
+fn default_velocity() -> u8 {
+    127
+}
+
+fn default_probability() -> u8 {
+    100
+}
+
+fn is_zero(value: &u8) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl PatternEvent {
+    /// Step flags as written in the CSV `flags` column ("tie+accent")
+    fn flags(&self) -> String {
+        [("tie", self.tie), ("off", self.off), ("slide", self.slide), ("accent", self.accent)]
+            .iter()
+            .filter(|(_, on)| *on)
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    fn set_flags(&mut self, flags: &str) -> Result<()> {
+        for flag in flags.split('+').map(str::trim).filter(|f| !f.is_empty()) {
+            match flag {
+                "tie" => self.tie = true,
+                "off" => self.off = true,
+                "slide" => self.slide = true,
+                "accent" => self.accent = true,
+                other => bail!("Unknown step flag '{}'", other),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A pattern in interchange form: the track list plus one event per active
+/// or note-off step (other steps are not stored). Encoded as JSON or CSV;
+/// both are documented in the README under "Pattern interchange".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternExchange {
+    pub format: String,
+    pub version: u32,
+    pub steps: usize,
+    /// Track names, indexed by event track number
+    pub tracks: Vec<String>,
+    /// Per-track loop lengths (0 = loop with the pattern; empty = all 0)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub track_lengths: Vec<usize>,
+    pub events: Vec<PatternEvent>,
+}
+
+/// Interchange encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterchangeFormat {
+    Json,
+    Csv,
+}
+
+impl InterchangeFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(InterchangeFormat::Json),
+            "csv" => Some(InterchangeFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// Pick by file extension (`.csv`, anything else is JSON)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("csv") => InterchangeFormat::Csv,
+            _ => InterchangeFormat::Json,
+        }
+    }
+}
+
+fn variation_name(variation: Variation) -> &'static str {
+    match variation {
+        Variation::A => "a",
+        Variation::B => "b",
+    }
+}
+
+fn parse_variation(name: &str) -> Result<Variation> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "a" => Ok(Variation::A),
+        "b" => Ok(Variation::B),
+        other => bail!("Unknown variation '{}' (expected 'a' or 'b')", other),
+    }
+}
+
+impl PatternExchange {
+    /// Capture a pattern's active and note-off steps (both variations)
+    /// with their locks, and its track lengths
+    pub fn from_pattern(pattern: &Pattern, track_names: &[String]) -> Self {
+        let mut events = Vec::new();
+        for variation in [Variation::A, Variation::B] {
+            for (track, row) in pattern.steps(variation).iter().enumerate() {
+                for (step, sd) in row.iter().enumerate() {
+                    if sd.active || sd.off {
+                        events.push(PatternEvent {
+                            track,
+                            variation: variation_name(variation).to_string(),
+                            step,
+                            note: sd.note,
+                            velocity: sd.velocity,
+                            probability: sd.probability,
+                            offset: sd.offset,
+                            tie: sd.tie,
+                            off: sd.off && !sd.active,
+                            slide: sd.slide,
+                            accent: sd.accent,
+                            locks: pattern
+                                .param_locks(track, step, variation)
+                                .map(|l| (l.key.clone(), l.value))
+                                .collect(),
+                        });
+                    }
+                }
+            }
+        }
+        let tracks = (0..pattern.num_tracks())
+            .map(|t| {
+                track_names
+                    .get(t)
+                    .cloned()
+                    .unwrap_or_else(|| format!("track{}", t))
+            })
+            .collect();
+        Self {
+            format: INTERCHANGE_FORMAT.to_string(),
+            version: INTERCHANGE_VERSION,
+            steps: pattern.length,
+            tracks,
+            track_lengths: pattern.track_lengths.clone(),
+            events,
+        }
+    }
+
+    /// Build a pattern for a project with the given track default notes.
+    /// Events are mapped by track index; inactive steps use the default note.
+    pub fn to_pattern(&self, default_notes: &[u8]) -> Result<Pattern> {
+        if self.format != INTERCHANGE_FORMAT {
+            bail!("Not a {} file (format '{}')", INTERCHANGE_FORMAT, self.format);
+        }
+        if self.version > INTERCHANGE_VERSION {
+            bail!(
+                "Pattern version {} is newer than supported version {}",
+                self.version,
+                INTERCHANGE_VERSION
+            );
+        }
+        if !(1..=MAX_STEPS).contains(&self.steps) {
+            bail!("Pattern has {} steps (1-{})", self.steps, MAX_STEPS);
+        }
+
+        let mut pattern = Pattern::new_with_notes(default_notes);
+        pattern.set_length(self.steps);
+        for (track, &length) in self.track_lengths.iter().enumerate() {
+            if length > 0 && track >= default_notes.len() {
+                bail!("Track {} has a loop length but the project has {} tracks", track, default_notes.len());
+            }
+            pattern.set_track_length(track, length);
+        }
+        for (i, ev) in self.events.iter().enumerate() {
+            if ev.track >= default_notes.len() {
+                bail!(
+                    "Event {}: track {} out of range (project has {} tracks)",
+                    i,
+                    ev.track,
+                    default_notes.len()
+                );
+            }
+            if ev.step >= self.steps {
+                bail!("Event {}: step {} out of range (0-{})", i, ev.step, self.steps - 1);
+            }
+            if ev.note > 127 || ev.velocity > 127 || ev.probability > 100 || ev.offset > 99 {
+                bail!("Event {}: note/velocity must be 0-127, probability 0-100, offset 0-99", i);
+            }
+            let variation =
+                parse_variation(&ev.variation).with_context(|| format!("Event {}", i))?;
+            let sd = &mut pattern.steps_mut(variation)[ev.track][ev.step];
+            sd.active = !ev.off;
+            sd.note = ev.note;
+            sd.velocity = ev.velocity;
+            sd.probability = ev.probability;
+            sd.offset = ev.offset;
+            sd.tie = ev.tie;
+            sd.off = ev.off;
+            sd.slide = ev.slide;
+            sd.accent = ev.accent;
+            for (key, &value) in &ev.locks {
+                if !pattern.set_param_lock(ev.track, ev.step, variation, key, Some(value)) {
+                    bail!("Event {}: too many param locks (at most {})", i, MAX_LOCKS_PER_STEP);
+                }
+            }
+        }
+        Ok(pattern)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("Failed to serialize pattern")
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        serde_json::from_str(text).context("Failed to parse pattern JSON")
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = format!("# {} v{} steps={}", INTERCHANGE_FORMAT, self.version, self.steps);
+        if !self.track_lengths.is_empty() {
+            let lengths: Vec<String> = self.track_lengths.iter().map(|l| l.to_string()).collect();
+            out.push_str(&format!(" track_lengths={}", lengths.join(",")));
+        }
+        out.push_str(&format!("\n{}\n", CSV_HEADER));
+        for ev in &self.events {
+            // Names are informational; keep them from breaking columns
+            let name = self
+                .tracks
+                .get(ev.track)
+                .map(|n| n.replace([',', '\n', '\r'], " "))
+                .unwrap_or_default();
+            let locks = ev
+                .locks
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(";");
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{}\n",
+                ev.track,
+                name,
+                ev.variation,
+                ev.step,
+                ev.note,
+                ev.velocity,
+                ev.probability,
+                ev.offset,
+                ev.flags(),
+                locks
+            ));
+        }
+        out
+    }
+
+    pub fn from_csv(text: &str) -> Result<Self> {
+        let mut tracks: Vec<String> = Vec::new();
+        let mut events = Vec::new();
+        // The header comment carries the length; files without one have STEPS
+        let mut steps = STEPS;
+        let mut track_lengths = Vec::new();
+
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if let Some(comment) = line.strip_prefix('#') {
+                if let Some(n) = comment.split_whitespace().find_map(|w| w.strip_prefix("steps=")) {
+                    steps = n
+                        .parse()
+                        .with_context(|| format!("Line {}: invalid step count '{}'", line_no + 1, n))?;
+                }
+                if let Some(list) = comment.split_whitespace().find_map(|w| w.strip_prefix("track_lengths=")) {
+                    track_lengths = list
+                        .split(',')
+                        .map(|n| n.parse::<usize>())
+                        .collect::<Result<_, _>>()
+                        .with_context(|| format!("Line {}: invalid track lengths '{}'", line_no + 1, list))?;
+                }
+                continue;
+            }
+            // Header rows (this version's or version 1's)
+            if line.is_empty() || line.starts_with("track,") {
+                continue;
+            }
+            let cols: Vec<&str> = line.split(',').map(str::trim).collect();
+            if cols.len() != CSV_COLUMNS && cols.len() != CSV_V1_COLUMNS {
+                bail!("Line {}: expected {} columns, got {}", line_no + 1, CSV_COLUMNS, cols.len());
+            }
+            let num = |i: usize| -> Result<usize> {
+                cols[i]
+                    .parse::<usize>()
+                    .with_context(|| format!("Line {}: invalid number '{}'", line_no + 1, cols[i]))
+            };
+            let track = num(0)?;
+            if track >= MAX_TRACKS {
+                bail!("Line {}: track {} out of range", line_no + 1, track);
+            }
+            let mut event = PatternEvent {
+                track,
+                variation: cols[2].to_string(),
+                step: num(3)?,
+                note: num(4)?.min(255) as u8,
+                velocity: num(5)?.min(255) as u8,
+                probability: num(6)?.min(255) as u8,
+                offset: 0,
+                tie: false,
+                off: false,
+                slide: false,
+                accent: false,
+                locks: BTreeMap::new(),
+            };
+            if cols.len() == CSV_COLUMNS {
+                event.offset = num(7)?.min(255) as u8;
+                event.set_flags(cols[8]).with_context(|| format!("Line {}", line_no + 1))?;
+                for lock in cols[9].split(';').map(str::trim).filter(|l| !l.is_empty()) {
+                    let (key, value) = lock
+                        .split_once('=')
+                        .and_then(|(k, v)| Some((k.trim(), v.trim().parse::<f32>().ok()?)))
+                        .with_context(|| format!("Line {}: invalid param lock '{}'", line_no + 1, lock))?;
+                    event.locks.insert(key.to_string(), value);
+                }
+            }
+            if track >= tracks.len() {
+                tracks.resize(track + 1, String::new());
+            }
+            if tracks[track].is_empty() {
+                tracks[track] = cols[1].to_string();
+            }
+            events.push(event);
+        }
+
+        Ok(Self {
+            format: INTERCHANGE_FORMAT.to_string(),
+            version: INTERCHANGE_VERSION,
+            steps,
+            tracks,
+            track_lengths,
+            events,
+        })
+    }
+
+    pub fn encode(&self, format: InterchangeFormat) -> Result<String> {
+        match format {
+            InterchangeFormat::Json => self.to_json(),
+            InterchangeFormat::Csv => Ok(self.to_csv()),
+        }
+    }
+
+    pub fn decode(text: &str, format: InterchangeFormat) -> Result<Self> {
+        match format {
+            InterchangeFormat::Json => Self::from_json(text),
+            InterchangeFormat::Csv => Self::from_csv(text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pattern using everything the format carries
+    fn detailed_pattern() -> Pattern {
+        let mut pattern = Pattern::new_with_notes(&[36, 48]);
+        pattern.set_length(12);
+        pattern.set_track_length(1, 5);
+        {
+            let rows = pattern.steps_mut(Variation::A);
+            rows[0][0].active = true;
+            rows[0][0].offset = 30;
+            rows[0][0].accent = true;
+            rows[1][2].active = true;
+            rows[1][2].note = 50;
+            rows[1][3].active = true;
+            rows[1][3].tie = true;
+            rows[1][3].slide = true;
+            rows[1][4].off = true;
+        }
+        pattern.steps_mut(Variation::B)[0][6].active = true;
+        assert!(pattern.set_param_lock(0, 0, Variation::A, "decay", Some(0.25)));
+        assert!(pattern.set_param_lock(0, 0, Variation::A, "tone", Some(0.75)));
+        assert!(pattern.set_param_lock(0, 6, Variation::B, "decay", Some(0.5)));
+        pattern
+    }
+
+    fn assert_same(a: &Pattern, b: &Pattern) {
+        assert_eq!(a.length, b.length);
+        assert_eq!(a.track_lengths, b.track_lengths);
+        for variation in [Variation::A, Variation::B] {
+            for (row_a, row_b) in a.steps(variation).iter().zip(b.steps(variation)) {
+                assert_eq!(serde_json::to_value(&row_a[..]).unwrap(), serde_json::to_value(&row_b[..]).unwrap());
+            }
+            for track in 0..a.num_tracks() {
+                for step in 0..a.length {
+                    let locks = |p: &Pattern| {
+                        let mut locks: Vec<_> =
+                            p.param_locks(track, step, variation).map(|l| (l.key.clone(), l.value)).collect();
+                        locks.sort_by(|x, y| x.0.cmp(&y.0));
+                        locks
+                    };
+                    assert_eq!(locks(a), locks(b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_every_step_detail() {
+        let pattern = detailed_pattern();
+        let names = vec!["kick".to_string(), "bass".to_string()];
+        let exchange = PatternExchange::from_pattern(&pattern, &names);
+        for format in [InterchangeFormat::Json, InterchangeFormat::Csv] {
+            let text = exchange.encode(format).unwrap();
+            let decoded = PatternExchange::decode(&text, format).unwrap();
+            assert_same(&decoded.to_pattern(&[36, 48]).unwrap(), &pattern);
+        }
+    }
+
+    #[test]
+    fn imports_version_1_csv() {
+        let text = "# gridoxide-pattern v1 steps=16\n\
+                    track,name,variation,step,note,velocity,probability\n\
+                    0,kick,a,4,36,100,90\n";
+        let pattern = PatternExchange::from_csv(text).unwrap().to_pattern(&[36]).unwrap();
+        let sd = pattern.get_step_var(0, 4, Variation::A);
+        assert!(sd.active);
+        assert_eq!((sd.velocity, sd.probability, sd.offset), (100, 90, 0));
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::audio::{LevelMeter, SequencerState, TrackState};
+use crate::fx::TrackFxState;
+use crate::samples::folder_samples;
+use crate::sequencer::{Variation, MAX_TRACKS, NUM_PATTERNS};
+use crate::synth::{create_synth, SynthType};
+
+/// Track names for kit samples and the file name words that pick them,
+/// checked in order (open hats before closed ones)
+const KIT_ROLES: &[(&str, &[&str])] = &[
+    ("KICK", &["kick", "kik", "bd", "bassdrum"]),
+    ("SNARE", &["snare", "snr", "sd"]),
+    ("CLAP", &["clap", "clp", "cp"]),
+    ("RIM", &["rim", "rimshot", "rs"]),
+    ("OHAT", &["openhat", "open", "ohh", "oh"]),
+    ("HIHAT", &["hihat", "closedhat", "closed", "hat", "hh", "chh", "ch"]),
+    ("TOM", &["tom", "lt", "mt", "ht"]),
+    ("CYMB", &["crash", "ride", "cymbal", "cym"]),
+    ("PERC", &["perc", "shaker", "conga", "bongo", "cowbell", "clave", "tamb"]),
+    ("BASS", &["bass", "808", "sub"]),
+];
+
+/// A sample assign_kit put on a track
+pub struct KitSlot {
+    pub track: usize,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Words of a file name: split at punctuation, letter/digit changes and
+/// lower/upper case changes, so "BD_909", "Kick01" and "OpenHat" all split
+fn split_words(stem: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev: Option<char> = None;
+    for c in stem.chars() {
+        let boundary = match prev {
+            _ if !c.is_alphanumeric() => true,
+            Some(p) => p.is_ascii_digit() != c.is_ascii_digit() || (p.is_lowercase() && c.is_uppercase()),
+            None => false,
+        };
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word).to_lowercase());
+        }
+        if c.is_alphanumeric() {
+            word.push(c);
+        }
+        prev = Some(c);
+    }
+    if !word.is_empty() {
+        words.push(word.to_lowercase());
+    }
+    words
+}
+
+/// Track name for a kit sample: the first role one of its file name's
+/// words asks for (longer keywords also match inside a word), else the
+/// file name itself
+pub fn kit_track_name(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let words = split_words(&stem);
+    let lower = stem.to_lowercase();
+    KIT_ROLES
+        .iter()
+        .find(|(_, keywords)| {
+            keywords
+                .iter()
+                .any(|k| words.iter().any(|w| w == k) || (k.len() >= 4 && lower.contains(k)))
+        })
+        .map_or_else(|| stem.to_uppercase(), |(name, _)| name.to_string())
+}
+
+fn new_track(name: &str) -> TrackState {
+    TrackState {
+        synth_type: SynthType::Sampler,
+        name: name.to_string(),
+        default_note: 60,
+        params_snapshot: Value::Null,
+        volume: 0.8,
+        pan: 0.0,
+        mute: false,
+        solo: false,
+        solo_safe: false,
+        fx: TrackFxState::default(),
+        nudge_ms: 0.0,
+        triplet: false,
+        transpose: 0,
+        glyph: None,
+        sample_bytes: 0,
+        meter: LevelMeter::default(),
+        cue: false,
+        extensions: serde_json::Map::new(),
+    }
+}
+
+/// Copy of `state` with the first `count` WAVs in `dir` (sorted by name)
+/// on sampler tracks from track 1 on, named by kit_track_name. Tracks
+/// already there become samplers and keep their steps and mixer settings;
+/// missing ones are added, up to MAX_TRACKS.
+pub fn assign_kit(state: &SequencerState, dir: &Path, count: usize) -> Result<(SequencerState, Vec<KitSlot>)> {
+    let files: Vec<PathBuf> = folder_samples(dir).into_iter().take(count.min(MAX_TRACKS)).collect();
+    if files.is_empty() {
+        bail!("No WAV files in {}", dir.display());
+    }
+    let mut state = state.clone();
+    let current = state.current_pattern;
+    *state.pattern_bank.get_mut(current) = state.pattern.clone();
+
+    let sampler = create_synth(SynthType::Sampler, state.sample_rate, None);
+    let default_note = sampler.default_note();
+    let assigned = files.len();
+    let mut slots = Vec::with_capacity(assigned);
+    for (track, path) in files.into_iter().enumerate() {
+        if track == state.tracks.len() {
+            for index in 0..NUM_PATTERNS {
+                state.pattern_bank.get_mut(index).add_track(default_note);
+            }
+            state.tracks.push(new_track(""));
+        }
+        // Steps of a converted track play the sample at its own pitch; the
+        // old synth's parameter locks don't apply to it
+        if state.tracks[track].synth_type != SynthType::Sampler {
+            for index in 0..NUM_PATTERNS {
+                let pattern = state.pattern_bank.get_mut(index);
+                for variation in [Variation::A, Variation::B] {
+                    if let Some(row) = pattern.steps_mut(variation).get_mut(track) {
+                        row.iter_mut().for_each(|sd| sd.note = default_note);
+                    }
+                }
+                pattern.param_locks.retain(|lock| lock.track != track);
+            }
+        }
+
+        // Numbered when a kit track before it, or a track it leaves alone, has the name
+        let base = kit_track_name(&path);
+        let taken = |name: &str| {
+            state
+                .tracks
+                .iter()
+                .enumerate()
+                .any(|(i, t)| (i < track || i >= assigned) && t.name == name)
+        };
+        let name = if taken(&base) {
+            (2..).map(|n| format!("{} {}", base, n)).find(|name| !taken(name)).unwrap_or_default()
+        } else {
+            base
+        };
+
+        let mut params = sampler.serialize_params();
+        params["wav_path"] = Value::from(path.to_string_lossy().to_string());
+        let t = &mut state.tracks[track];
+        t.synth_type = SynthType::Sampler;
+        t.name = name.clone();
+        t.default_note = default_note;
+        t.params_snapshot = params;
+        t.glyph = None;
+        t.sample_bytes = 0;
+        slots.push(KitSlot { track, name, path });
+    }
+    state.pattern = state.pattern_bank.get(current).clone();
+    Ok((state, slots))
+}
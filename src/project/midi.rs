@@ -0,0 +1,550 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::audio::{SequencerState, TrackState};
+use crate::command::Command;
+use crate::project::renderer::ExportMode;
+use crate::sequencer::{
+    chord_label, chord_shift, played_note, triplet_steps, Pattern, Variation, MAX_ARRANGEMENT_ENTRIES,
+    STEPS, TRIPLET_STEPS,
+};
+
+/// Ticks per quarter note in exported files
+pub const MIDI_PPQ: u32 = 96;
+/// Ticks per 16th step
+const TICKS_PER_STEP: u32 = MIDI_PPQ / 4;
+/// Ticks per bar (one loop of a 16-step pattern)
+const TICKS_PER_BAR: u32 = TICKS_PER_STEP * STEPS as u32;
+
+/// Most bars one arrangement entry can repeat
+const MAX_ENTRY_REPEATS: usize = 16;
+
+/// Largest MIDI file accepted for import
+const MAX_MIDI_BYTES: u64 = 4 * 1024 * 1024;
+/// Bars past this point are dropped on import
+const MAX_IMPORT_BARS: usize = 256;
+
+const CC_VOLUME: u8 = 7;
+const CC_PAN: u8 = 10;
+/// Sound controller 5 ("brightness"), commonly mapped to filter cutoff
+const CC_CUTOFF: u8 = 74;
+
+/// Summary of a MIDI export
+pub struct MidiExportResult {
+    pub tracks: usize,
+    pub notes: usize,
+    pub sections: usize,
+}
+
+/// One timed event in a track. `order` sorts events on the same tick:
+/// note-offs, then program/CC changes, then note-ons.
+struct TimedEvent {
+    tick: u32,
+    order: u8,
+    data: Vec<u8>,
+}
+
+/// Section of the export: which pattern plays from which tick, how many
+/// times it loops, and the chord degree of its arrangement entry
+struct Section {
+    pattern: usize,
+    start_tick: u32,
+    loops: u32,
+    chord: Option<u8>,
+}
+
+/// Ticks one loop of a pattern takes
+fn loop_ticks(state: &SequencerState, pattern: usize) -> u32 {
+    state.pattern_bank.get(pattern).length as u32 * TICKS_PER_STEP
+}
+
+fn sections(state: &SequencerState, mode: &ExportMode) -> Vec<Section> {
+    match mode {
+        ExportMode::Pattern(idx) => vec![Section {
+            pattern: *idx,
+            start_tick: 0,
+            loops: 1,
+            chord: None,
+        }],
+        ExportMode::Song if state.arrangement.is_empty() => vec![Section {
+            pattern: state.current_pattern,
+            start_tick: 0,
+            loops: 1,
+            chord: None,
+        }],
+        ExportMode::Song => {
+            let mut start_tick = 0;
+            state
+                .arrangement
+                .entries
+                .iter()
+                .map(|e| {
+                    let section = Section {
+                        pattern: e.pattern,
+                        start_tick,
+                        loops: e.repeats as u32,
+                        chord: e.chord,
+                    };
+                    start_tick += e.repeats as u32 * loop_ticks(state, e.pattern);
+                    section
+                })
+                .collect()
+        }
+    }
+}
+
+/// Variable-length quantity used for delta times and meta lengths
+fn write_vlq(out: &mut Vec<u8>, mut value: u32) {
+    let mut bytes = vec![(value & 0x7F) as u8];
+    value >>= 7;
+    while value > 0 {
+        bytes.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    out.extend(bytes.iter().rev());
+}
+
+fn meta(kind: u8, payload: &[u8]) -> Vec<u8> {
+    let mut data = vec![0xFF, kind];
+    write_vlq(&mut data, payload.len() as u32);
+    data.extend_from_slice(payload);
+    data
+}
+
+/// Build an MTrk chunk from events (sorted here, end-of-track appended)
+fn track_chunk(mut events: Vec<TimedEvent>) -> Vec<u8> {
+    events.sort_by_key(|e| (e.tick, e.order));
+    let mut body = Vec::new();
+    let mut last_tick = 0;
+    for event in &events {
+        write_vlq(&mut body, event.tick - last_tick);
+        body.extend_from_slice(&event.data);
+        last_tick = event.tick;
+    }
+    write_vlq(&mut body, 0);
+    body.extend_from_slice(&[0xFF, 0x2F, 0x00]);
+
+    let mut chunk = b"MTrk".to_vec();
+    chunk.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    chunk.extend(body);
+    chunk
+}
+
+/// Mixer/FX state as (controller, value) pairs
+fn track_ccs(track: &TrackState) -> Vec<(u8, u8)> {
+    let mut ccs = vec![
+        (
+            CC_VOLUME,
+            (track.volume.clamp(0.0, 1.0) * 127.0).round() as u8,
+        ),
+        (
+            CC_PAN,
+            ((track.pan.clamp(-1.0, 1.0) + 1.0) * 63.5)
+                .round()
+                .min(127.0) as u8,
+        ),
+    ];
+    if track.fx.filter_enabled {
+        // Log map 20 Hz..20 kHz onto 0..127
+        let pos = (track.fx.filter_cutoff.clamp(20.0, 20000.0) / 20.0).ln() / 1000.0f32.ln();
+        ccs.push((CC_CUTOFF, (pos * 127.0).round() as u8));
+    }
+    ccs
+}
+
+/// Export the pattern or arrangement as a Standard MIDI File (type 1).
+/// Track 0 carries tempo and a marker per section; each audible track gets
+/// its own channel (track index mod 16) with its mixer state as CC lanes
+/// and a program change (= pattern slot) at every section boundary.
+pub fn export_midi(
+    state: &SequencerState,
+    mode: &ExportMode,
+    path: &Path,
+) -> Result<MidiExportResult> {
+    let sections = sections(state, mode);
+    let any_solo = state.tracks.iter().any(|t| t.solo);
+    let micros_per_beat = (60_000_000.0 / state.bpm) as u32;
+    let ms_to_ticks = |ms: f32| (ms * 0.001 * state.bpm / 60.0 * MIDI_PPQ as f32).round() as u32;
+
+    // Conductor track: tempo, 4/4, section markers
+    let mut conductor = vec![
+        TimedEvent {
+            tick: 0,
+            order: 1,
+            data: meta(0x51, &micros_per_beat.to_be_bytes()[1..]),
+        },
+        TimedEvent {
+            tick: 0,
+            order: 1,
+            data: meta(0x58, &[4, 2, 24, 8]),
+        },
+    ];
+    for section in &sections {
+        let mut name = format!("Pattern {:02}", section.pattern);
+        if section.chord.is_some() {
+            name = format!("{} ({})", name, chord_label(section.chord));
+        }
+        conductor.push(TimedEvent {
+            tick: section.start_tick,
+            order: 1,
+            data: meta(0x06, name.as_bytes()),
+        });
+    }
+    let mut chunks = vec![track_chunk(conductor)];
+
+    let mut notes = 0;
+    for (i, track) in state.tracks.iter().enumerate() {
+        if !track.is_audible(any_solo) {
+            continue;
+        }
+        let channel = (i % 16) as u8;
+        let mut events = vec![TimedEvent {
+            tick: 0,
+            order: 1,
+            data: meta(0x03, track.name.as_bytes()),
+        }];
+        for (controller, value) in track_ccs(track) {
+            events.push(TimedEvent {
+                tick: 0,
+                order: 1,
+                data: vec![0xB0 | channel, controller, value],
+            });
+        }
+
+        let step_ticks = if track.triplet { TICKS_PER_BAR / TRIPLET_STEPS as u32 } else { TICKS_PER_STEP };
+        let nudge = ms_to_ticks(track.nudge_ms);
+        let melodic = track.is_melodic();
+
+        for section in &sections {
+            let section_tick = section.start_tick;
+            events.push(TimedEvent {
+                tick: section_tick,
+                order: 1,
+                data: vec![0xC0 | channel, section.pattern.min(127) as u8],
+            });
+            let pattern = state.pattern_bank.get(section.pattern);
+            let transpose = state.transpose + chord_shift(section.chord);
+            let steps = if track.triplet { triplet_steps(pattern.length) } else { pattern.length };
+            for repeat in 0..section.loops {
+                let bar_tick = section_tick + repeat * loop_ticks(state, section.pattern);
+                for step in 0..steps {
+                    let sd = pattern.get_step_var(i, step, state.current_variation);
+                    // Tied steps lengthen the note before them
+                    if !sd.active || (step > 0 && pattern.is_tied_var(i, step, steps, state.current_variation)) {
+                        continue;
+                    }
+                    let length = 1 + (step + 1..steps)
+                        .take_while(|&next| pattern.is_tied_var(i, next, steps, state.current_variation))
+                        .count() as u32;
+                    let on = bar_tick + step as u32 * step_ticks + sd.offset as u32 * step_ticks / 100 + nudge;
+                    let note = played_note(sd.note, melodic, transpose, track.transpose);
+                    events.push(TimedEvent {
+                        tick: on,
+                        order: 2,
+                        data: vec![0x90 | channel, note, sd.velocity.clamp(1, 127)],
+                    });
+                    events.push(TimedEvent {
+                        tick: on + length * step_ticks - 1,
+                        order: 0,
+                        data: vec![0x80 | channel, note, 0],
+                    });
+                    notes += 1;
+                }
+            }
+        }
+        chunks.push(track_chunk(events));
+    }
+
+    let mut file = b"MThd".to_vec();
+    file.extend_from_slice(&6u32.to_be_bytes());
+    file.extend_from_slice(&1u16.to_be_bytes());
+    file.extend_from_slice(&(chunks.len() as u16).to_be_bytes());
+    file.extend_from_slice(&(MIDI_PPQ as u16).to_be_bytes());
+    for chunk in &chunks {
+        file.extend_from_slice(chunk);
+    }
+    std::fs::write(path, file)
+        .with_context(|| format!("Failed to write MIDI file: {}", path.display()))?;
+
+    Ok(MidiExportResult {
+        tracks: chunks.len() - 1,
+        notes,
+        sections: sections.len(),
+    })
+}
+
+/// A note-on read from a MIDI file
+struct MidiNote {
+    channel: u8,
+    tick: u32,
+    key: u8,
+    velocity: u8,
+}
+
+/// Bounds-checked cursor over file bytes
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let Some(bytes) = self.data.get(self.pos..self.pos + n) else {
+            bail!("Unexpected end of MIDI data at byte {}", self.pos);
+        };
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn vlq(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let b = self.u8()?;
+            value = (value << 7) | (b & 0x7F) as u32;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("Invalid variable-length value at byte {}", self.pos)
+    }
+}
+
+/// Parse a Standard MIDI File into its ticks-per-quarter and note-ons
+fn parse_notes(data: &[u8]) -> Result<(u16, Vec<MidiNote>)> {
+    let mut r = Reader { data, pos: 0 };
+    if r.take(4)? != b"MThd" {
+        bail!("Not a Standard MIDI File");
+    }
+    let header_len = r.u32()? as usize;
+    let _format = r.u16()?;
+    let num_chunks = r.u16()?;
+    let division = r.u16()?;
+    r.take(header_len.saturating_sub(6))?;
+    if division & 0x8000 != 0 || division == 0 {
+        bail!("SMPTE time division is not supported");
+    }
+
+    let mut notes = Vec::new();
+    for _ in 0..num_chunks {
+        if r.pos >= data.len() {
+            break;
+        }
+        let id = r.take(4)?;
+        let len = r.u32()? as usize;
+        let body = r.take(len)?;
+        if id != b"MTrk" {
+            continue;
+        }
+        let mut t = Reader { data: body, pos: 0 };
+        let mut tick = 0u32;
+        let mut running: Option<u8> = None;
+        while t.pos < body.len() {
+            tick = tick.saturating_add(t.vlq()?);
+            let status = match body[t.pos] {
+                b if b & 0x80 != 0 => {
+                    t.pos += 1;
+                    b
+                }
+                _ => running.context("Data byte without running status")?,
+            };
+            match status {
+                0xFF => {
+                    let kind = t.u8()?;
+                    let len = t.vlq()? as usize;
+                    t.take(len)?;
+                    if kind == 0x2F {
+                        break;
+                    }
+                    running = None;
+                }
+                0xF0 | 0xF7 => {
+                    let len = t.vlq()? as usize;
+                    t.take(len)?;
+                    running = None;
+                }
+                0x80..=0xEF => {
+                    running = Some(status);
+                    let kind = status & 0xF0;
+                    let a = t.u8()?;
+                    if kind == 0xC0 || kind == 0xD0 {
+                        continue;
+                    }
+                    let b = t.u8()?;
+                    if kind == 0x90 && b > 0 {
+                        notes.push(MidiNote {
+                            channel: status & 0x0F,
+                            tick,
+                            key: a & 0x7F,
+                            velocity: b & 0x7F,
+                        });
+                    }
+                }
+                _ => bail!("Unsupported MIDI status byte {:#04x}", status),
+            }
+        }
+    }
+    Ok((division, notes))
+}
+
+/// One imported pattern and how many bars in a row it plays
+pub struct ImportedSection {
+    pub pattern: Pattern,
+    pub repeats: usize,
+}
+
+/// Result of reading a MIDI file onto the grid
+pub struct MidiImport {
+    pub sections: Vec<ImportedSection>,
+    /// Notes placed on the grid
+    pub notes: usize,
+    /// Notes dropped (no matching track, past MAX_IMPORT_BARS or max_sections)
+    pub skipped: usize,
+}
+
+/// Read a MIDI file and place its note-ons on the grid, one bar per
+/// pattern (variation A). Notes keep their timing within the step as a
+/// micro-timing offset (see Pattern::quantize_track_var). Channel N goes to
+/// track N; if the file uses a channel beyond the project's tracks, its
+/// channels are instead assigned to tracks in ascending order. Triplet
+/// tracks quantize to their 12-step grid. Identical consecutive bars become one section with repeats; at
+/// most `max_sections` sections are returned.
+pub fn import_midi(
+    path: &Path,
+    default_notes: &[u8],
+    triplets: &[bool],
+    max_sections: usize,
+) -> Result<MidiImport> {
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .len();
+    if size > MAX_MIDI_BYTES {
+        bail!("MIDI file is too large ({} bytes)", size);
+    }
+    let data =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let (ppq, notes) =
+        parse_notes(&data).with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let num_tracks = default_notes.len();
+    let mut channels: Vec<u8> = notes.iter().map(|n| n.channel).collect();
+    channels.sort_unstable();
+    channels.dedup();
+    let direct = channels.iter().all(|&c| (c as usize) < num_tracks);
+    let track_for = |channel: u8| -> Option<usize> {
+        if direct {
+            Some(channel as usize)
+        } else {
+            channels.iter().position(|&c| c == channel).filter(|&t| t < num_tracks)
+        }
+    };
+
+    // Per bar: (track, step, note, velocity, offset), loudest note wins a step
+    let bar_ticks = ppq as f32 * 4.0;
+    let mut bars: Vec<Vec<(usize, usize, u8, u8, u8)>> = Vec::new();
+    let mut skipped = 0;
+    for n in &notes {
+        let Some(track) = track_for(n.channel) else {
+            skipped += 1;
+            continue;
+        };
+        let triplet = triplets.get(track).copied().unwrap_or(false);
+        let steps = if triplet { TRIPLET_STEPS } else { STEPS };
+        let exact = n.tick as f32 / (bar_ticks / steps as f32);
+        let (pos, offset) = match (exact.fract() * 100.0) as u8 {
+            offset if !triplet && offset < 99 => (exact as usize, offset),
+            _ => (exact.round() as usize, 0),
+        };
+        let (bar, step) = (pos / steps, pos % steps);
+        if bar >= MAX_IMPORT_BARS {
+            skipped += 1;
+            continue;
+        }
+        if bar >= bars.len() {
+            bars.resize(bar + 1, Vec::new());
+        }
+        match bars[bar].iter_mut().find(|e| e.0 == track && e.1 == step) {
+            Some(existing) if existing.3 >= n.velocity => {}
+            Some(existing) => *existing = (track, step, n.key, n.velocity, offset),
+            None => bars[bar].push((track, step, n.key, n.velocity, offset)),
+        }
+    }
+    for bar in bars.iter_mut() {
+        bar.sort_unstable();
+    }
+
+    let mut runs: Vec<(&Vec<(usize, usize, u8, u8, u8)>, usize)> = Vec::new();
+    for bar in &bars {
+        match runs.last_mut() {
+            Some((last, repeats)) if *last == bar => *repeats += 1,
+            _ => runs.push((bar, 1)),
+        }
+    }
+    for (bar, repeats) in runs.iter().skip(max_sections) {
+        skipped += bar.len() * repeats;
+    }
+    runs.truncate(max_sections);
+
+    let mut placed = 0;
+    let sections = runs
+        .into_iter()
+        .map(|(bar, repeats)| {
+            let mut pattern = Pattern::new_with_notes(default_notes);
+            for &(track, step, note, velocity, offset) in bar {
+                pattern.record_hit_var(track, step, note, velocity, offset, Variation::A);
+            }
+            placed += bar.len() * repeats;
+            ImportedSection { pattern, repeats }
+        })
+        .collect();
+
+    Ok(MidiImport {
+        sections,
+        notes: placed,
+        skipped,
+    })
+}
+
+impl MidiImport {
+    /// Commands writing section i into `slots[i]`, optionally followed by
+    /// arrangement entries that play the sections in file order
+    pub fn commands(self, slots: &[usize], arrange: bool) -> Vec<Command> {
+        let mut entries = Vec::new();
+        let mut commands: Vec<Command> = self
+            .sections
+            .into_iter()
+            .zip(slots)
+            .map(|(section, &index)| {
+                let mut repeats = section.repeats;
+                while repeats > 0 {
+                    let n = repeats.min(MAX_ENTRY_REPEATS);
+                    entries.push(Command::AppendArrangement { pattern: index, repeats: n });
+                    repeats -= n;
+                }
+                Command::ReplacePattern {
+                    index,
+                    pattern: Box::new(section.pattern),
+                }
+            })
+            .collect();
+        if arrange {
+            entries.truncate(MAX_ARRANGEMENT_ENTRIES);
+            commands.extend(entries);
+        }
+        commands
+    }
+}
@@ -1,17 +1,53 @@
+pub mod backup;
+pub mod gain;
+pub mod golden;
+pub mod interchange;
+pub mod kit;
+pub mod midi;
+pub mod queue;
 pub mod renderer;
+pub mod stems;
+pub mod template;
+pub mod view;
 
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tracing::warn;
 
-use crate::audio::{SequencerState, TrackState};
-use crate::fx::{MasterFxState, TrackFxState};
-use crate::sequencer::{Arrangement, PatternBank, PlaybackMode, Variation};
-use crate::synth::{load_wav, BassParams, HiHatParams, KickParams, SnareParams, SynthType};
+use crate::audio::{
+    LevelMeter, SequencerState, TrackState, DEFAULT_PREVIEW_DUCK_DB, DEFAULT_SAMPLE_BUDGET_MB, DEFAULT_SAMPLE_RATE,
+    MAX_GLYPH_CHARS, MAX_PREVIEW_DUCK_DB,
+};
+use crate::command::Command;
+use crate::fx::{FxParamId, MasterFxParamId, MasterFxState, TrackFxState};
+use crate::midi::MidiSync;
+use crate::script::Script;
+use crate::sequencer::{
+    pattern_label, Arrangement, PatternBank, PlaybackMode, Variation, MAX_LOCKS_PER_STEP, MAX_NUDGE_MS, MAX_STEPS,
+    MAX_TRACKS, MAX_TRACK_TRANSPOSE, MAX_SWING, MAX_TRANSPOSE, NUM_PATTERNS,
+};
+use crate::synth::{
+    create_synth, external_command, load_wav, prepare_sample, BassParams, HiHatParams, SampleAudio, SampleData, SampleInfo,
+    Interpolation, KickParams, SampleOptions, SnareParams, SynthType, ToneParams,
+};
 
-const PROJECT_VERSION: u32 = 2;
+const PROJECT_VERSION: u32 = 3;
+
+/// Upper bounds on the pretty-printed size of one step (every field set)
+/// and one parameter lock, at their depth in a project file
+const STEP_JSON_BYTES: u64 = 320;
+const LOCK_JSON_BYTES: u64 = 200;
+
+/// Largest project file accepted (guards against OOM on hostile files).
+/// Room for a full bank, every step of every track in both variations
+/// with all its locks, plus 16 MB for tracks, scripts and the rest.
+const MAX_PROJECT_BYTES: u64 = (NUM_PATTERNS * MAX_TRACKS * MAX_STEPS * 2) as u64
+    * (STEP_JSON_BYTES + MAX_LOCKS_PER_STEP as u64 * LOCK_JSON_BYTES)
+    + 16 * 1024 * 1024;
 
 /// Per-track data for v2 project files
 #[derive(Clone, Serialize, Deserialize)]
@@ -24,10 +60,73 @@ pub struct TrackProjectData {
     pub pan: f32,
     pub mute: bool,
     pub solo: bool,
+    #[serde(default)]
+    pub solo_safe: bool,
     pub fx: TrackFxState,
+    #[serde(default)]
+    pub nudge_ms: f32,
+    #[serde(default)]
+    pub triplet: bool,
+    #[serde(default)]
+    pub transpose: i8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glyph: Option<String>,
+    /// Reserved for future features; unknown keys are kept on load/save
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<&TrackState> for TrackProjectData {
+    fn from(t: &TrackState) -> Self {
+        Self {
+            synth_type: t.synth_type,
+            name: t.name.clone(),
+            default_note: t.default_note,
+            params: t.params_snapshot.clone(),
+            volume: t.volume,
+            pan: t.pan,
+            mute: t.mute,
+            solo: t.solo,
+            solo_safe: t.solo_safe,
+            fx: t.fx.clone(),
+            nudge_ms: t.nudge_ms,
+            triplet: t.triplet,
+            transpose: t.transpose,
+            glyph: t.glyph.clone(),
+            extensions: t.extensions.clone(),
+        }
+    }
 }
 
-/// Serializable project data v2 (dynamic tracks)
+impl TrackProjectData {
+    /// Runtime track state (sample memory unknown until the synth loads)
+    pub fn to_track_state(&self) -> TrackState {
+        TrackState {
+            synth_type: self.synth_type,
+            name: self.name.clone(),
+            default_note: self.default_note,
+            params_snapshot: self.params.clone(),
+            volume: self.volume,
+            pan: self.pan,
+            mute: self.mute,
+            solo: self.solo,
+            solo_safe: self.solo_safe,
+            fx: self.fx.clone(),
+            nudge_ms: self.nudge_ms,
+            triplet: self.triplet,
+            transpose: self.transpose,
+            glyph: self.glyph.clone(),
+            sample_bytes: 0,
+            meter: LevelMeter::default(),
+            cue: false,
+            extensions: self.extensions.clone(),
+        }
+    }
+}
+
+/// Serializable project data v3 (dynamic tracks).
+/// v3 adds `extensions` maps at the top level, per track, per pattern and
+/// in master_fx; v2 files load unchanged with empty maps.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ProjectData {
     pub version: u32,
@@ -40,13 +139,70 @@ pub struct ProjectData {
     pub arrangement: Arrangement,
     #[serde(default)]
     pub current_variation: Variation,
+    #[serde(default = "default_sample_budget_mb")]
+    pub sample_budget_mb: f32,
+    /// Samples larger than this (MB) stream from disk (0 = never)
+    #[serde(default)]
+    pub sample_stream_mb: f32,
+    #[serde(default = "default_preview_duck_db")]
+    pub preview_duck_db: f32,
+    #[serde(default = "default_render_interpolation")]
+    pub render_interpolation: Interpolation,
+    /// Tone tracks sound in exports
+    #[serde(default)]
+    pub export_tone: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<Script>,
+    /// Project key as a pitch class (C = 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<u8>,
+    /// Semitones added to melodic tracks' notes at trigger time
+    #[serde(default)]
+    pub transpose: i8,
+    /// Off-beat 16th delay, percent of half a step
+    #[serde(default)]
+    pub swing: u8,
+    /// Reserved for future features; unknown keys are kept on load/save
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+fn default_sample_budget_mb() -> f32 {
+    DEFAULT_SAMPLE_BUDGET_MB
+}
+
+fn default_preview_duck_db() -> f32 {
+    DEFAULT_PREVIEW_DUCK_DB
+}
+
+fn default_render_interpolation() -> Interpolation {
+    Interpolation::Cubic
 }
 
 /// Sample buffer loaded for a sampler track during project load
 pub struct SampleBuffer {
     pub track: usize,
+    /// Velocity layer the buffer belongs to (None = the main sample)
+    pub layer: Option<usize>,
     pub buffer: Vec<f32>,
     pub path: String,
+    /// Normalize/trim options already applied to `buffer`
+    pub options: SampleOptions,
+    /// Detected after the options were applied (main sample only)
+    pub info: SampleInfo,
+}
+
+impl SampleBuffer {
+    /// The command that hands this buffer to the audio engine. A main
+    /// sample larger than `stream_mb` streams from disk (0 = never).
+    pub fn into_command(self, stream_mb: f32) -> Command {
+        let (track, path, options, info) = (self.track, self.path, self.options, self.info);
+        match self.layer {
+            // Shared from here on, so deck copies of the voice don't copy the data
+            Some(layer) => Command::LoadSampleLayer { track, layer, buffer: SampleData::from(self.buffer), path },
+            None => Command::LoadSample { track, buffer: SampleAudio::new(self.buffer, stream_mb), path, options, info },
+        }
+    }
 }
 
 /// v1 project data format (for migration from old .grox files)
@@ -91,7 +247,13 @@ impl ProjectDataV1 {
                 pan: self.track_pans[i],
                 mute: self.track_mutes[i],
                 solo: self.track_solos[i],
+                solo_safe: false,
                 fx: self.track_fx[i].clone(),
+                nudge_ms: 0.0,
+                triplet: false,
+                transpose: 0,
+                glyph: None,
+                extensions: serde_json::Map::new(),
             })
             .collect();
 
@@ -105,6 +267,16 @@ impl ProjectDataV1 {
             playback_mode: self.playback_mode,
             arrangement: self.arrangement,
             current_variation: Variation::A,
+            sample_budget_mb: DEFAULT_SAMPLE_BUDGET_MB,
+            sample_stream_mb: 0.0,
+            preview_duck_db: DEFAULT_PREVIEW_DUCK_DB,
+            render_interpolation: Interpolation::Cubic,
+            export_tone: false,
+            scripts: Vec::new(),
+            key: None,
+            transpose: 0,
+            swing: 0,
+            extensions: serde_json::Map::new(),
         }
     }
 }
@@ -115,17 +287,7 @@ impl ProjectData {
         let tracks: Vec<TrackProjectData> = state
             .tracks
             .iter()
-            .map(|t| TrackProjectData {
-                synth_type: t.synth_type,
-                name: t.name.clone(),
-                default_note: t.default_note,
-                params: t.params_snapshot.clone(),
-                volume: t.volume,
-                pan: t.pan,
-                mute: t.mute,
-                solo: t.solo,
-                fx: t.fx.clone(),
-            })
+            .map(TrackProjectData::from)
             .collect();
 
         Self {
@@ -138,7 +300,198 @@ impl ProjectData {
             playback_mode: state.playback_mode,
             arrangement: state.arrangement.clone(),
             current_variation: state.current_variation,
+            sample_budget_mb: state.sample_budget_mb,
+            sample_stream_mb: state.sample_stream_mb,
+            preview_duck_db: state.preview_duck_db,
+            render_interpolation: state.render_interpolation,
+            export_tone: state.export_tone,
+            scripts: state.scripts.clone(),
+            key: state.key,
+            transpose: state.transpose,
+            swing: state.swing,
+            extensions: state.extensions.clone(),
+        }
+    }
+
+    /// Reject structurally invalid projects (bad track counts, mismatched
+    /// pattern shapes, out-of-range indices, non-finite numbers)
+    pub fn validate(&self) -> Result<()> {
+        if self.tracks.is_empty() || self.tracks.len() > MAX_TRACKS {
+            bail!("Project has {} tracks (expected 1-{})", self.tracks.len(), MAX_TRACKS);
+        }
+        self.pattern_bank.validate(self.tracks.len())?;
+        self.arrangement.validate()?;
+        if self.current_pattern >= NUM_PATTERNS {
+            bail!("Current pattern {} out of range", self.current_pattern);
+        }
+        if let Some(key) = self.key.filter(|&k| k >= 12) {
+            bail!("Project key {} out of range (0-11)", key);
+        }
+        if !(-MAX_TRANSPOSE..=MAX_TRANSPOSE).contains(&self.transpose) {
+            bail!("Transpose {} out of range (±{})", self.transpose, MAX_TRANSPOSE);
+        }
+        if self.swing > MAX_SWING {
+            bail!("Swing {} out of range (0-{})", self.swing, MAX_SWING);
+        }
+
+        check_finite("bpm", self.bpm)?;
+        check_finite("sample_budget_mb", self.sample_budget_mb)?;
+        check_finite("sample_stream_mb", self.sample_stream_mb)?;
+        check_finite("preview_duck_db", self.preview_duck_db)?;
+        let m = &self.master_fx;
+        for (name, v) in [
+            ("reverb_decay", m.reverb_decay),
+            ("reverb_mix", m.reverb_mix),
+            ("reverb_damping", m.reverb_damping),
+            ("reverb_width", m.reverb_width),
+            ("reverb_gate_threshold", m.reverb_gate_threshold),
+            ("reverb_gate_hold", m.reverb_gate_hold),
+            ("ms_mid_level", m.ms_mid_level),
+            ("ms_side_level", m.ms_side_level),
+            ("ms_bass_mono", m.ms_bass_mono),
+            ("ms_side_high", m.ms_side_high),
+        ] {
+            check_finite(name, v)?;
+        }
+
+        for (i, t) in self.tracks.iter().enumerate() {
+            let fx = &t.fx;
+            for (name, v) in [
+                ("volume", t.volume),
+                ("pan", t.pan),
+                ("nudge_ms", t.nudge_ms),
+                ("filter_cutoff", fx.filter_cutoff),
+                ("filter_resonance", fx.filter_resonance),
+                ("dist_drive", fx.dist_drive),
+                ("dist_mix", fx.dist_mix),
+                ("delay_time", fx.delay_time),
+                ("delay_feedback", fx.delay_feedback),
+                ("delay_mix", fx.delay_mix),
+                ("mod_depth", fx.mod_depth),
+                ("chain_mix", fx.chain_mix),
+            ] {
+                check_finite(name, v).with_context(|| format!("Track {}", i))?;
+            }
+            if !(-MAX_TRACK_TRANSPOSE..=MAX_TRACK_TRANSPOSE).contains(&t.transpose) {
+                bail!("Track {} transpose {} out of range (±{})", i, t.transpose, MAX_TRACK_TRANSPOSE);
+            }
+            if let Some(ref glyph) = t.glyph {
+                if glyph.trim().is_empty() || glyph.chars().count() > MAX_GLYPH_CHARS {
+                    bail!("Track {} glyph must be 1-{} characters", i, MAX_GLYPH_CHARS);
+                }
+            }
+            if fx.mod_source.is_some_and(|s| s == i || s >= self.tracks.len()) {
+                bail!("Track {} is modulated by a missing track", i);
+            }
+            if !t.params.is_object() && !t.params.is_null() {
+                bail!("Track {} params must be an object", i);
+            }
+        }
+        Ok(())
+    }
+
+    /// Clamp out-of-range values to what the engine accepts. Returns one
+    /// warning per value that had to be changed.
+    pub fn validate_and_clamp(&mut self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        let mut clamp = |label: String, value: &mut f32, min: f32, max: f32| {
+            let clamped = value.clamp(min, max);
+            if clamped != *value {
+                warnings.push(format!("{} {} clamped to {}", label, value, clamped));
+                *value = clamped;
+            }
+        };
+
+        clamp("BPM".to_string(), &mut self.bpm, 60.0, 200.0);
+        clamp("Sample budget".to_string(), &mut self.sample_budget_mb, 1.0, f32::MAX);
+        clamp("Sample streaming size".to_string(), &mut self.sample_stream_mb, 0.0, f32::MAX);
+        clamp("Preview duck".to_string(), &mut self.preview_duck_db, MAX_PREVIEW_DUCK_DB, 0.0);
+
+        let m = &mut self.master_fx;
+        for (param, value) in [
+            (MasterFxParamId::ReverbDecay, &mut m.reverb_decay),
+            (MasterFxParamId::ReverbMix, &mut m.reverb_mix),
+            (MasterFxParamId::ReverbDamping, &mut m.reverb_damping),
+            (MasterFxParamId::ReverbWidth, &mut m.reverb_width),
+            (MasterFxParamId::ReverbGateThreshold, &mut m.reverb_gate_threshold),
+            (MasterFxParamId::ReverbGateHold, &mut m.reverb_gate_hold),
+            (MasterFxParamId::MsMidLevel, &mut m.ms_mid_level),
+            (MasterFxParamId::MsSideLevel, &mut m.ms_side_level),
+            (MasterFxParamId::MsBassMono, &mut m.ms_bass_mono),
+            (MasterFxParamId::MsSideHigh, &mut m.ms_side_high),
+        ] {
+            let (min, max, _) = param.range();
+            clamp(format!("Master {}", param.key()), value, min, max);
+        }
+
+        for (i, t) in self.tracks.iter_mut().enumerate() {
+            let label = |what: &str| format!("Track {} {}", i + 1, what);
+            clamp(label("volume"), &mut t.volume, 0.0, 1.0);
+            clamp(label("pan"), &mut t.pan, -1.0, 1.0);
+            clamp(label("nudge_ms"), &mut t.nudge_ms, 0.0, MAX_NUDGE_MS);
+
+            let fx = &mut t.fx;
+            for (param, value) in [
+                (FxParamId::FilterCutoff, &mut fx.filter_cutoff),
+                (FxParamId::FilterResonance, &mut fx.filter_resonance),
+                (FxParamId::DistDrive, &mut fx.dist_drive),
+                (FxParamId::DistMix, &mut fx.dist_mix),
+                (FxParamId::DelayTime, &mut fx.delay_time),
+                (FxParamId::DelayFeedback, &mut fx.delay_feedback),
+                (FxParamId::DelayMix, &mut fx.delay_mix),
+                (FxParamId::ModDepth, &mut fx.mod_depth),
+                (FxParamId::ChainMix, &mut fx.chain_mix),
+            ] {
+                let (min, max, _) = param.range();
+                clamp(label(param.key()), value, min, max);
+            }
+
+            // Numeric synth params, using the synth's own descriptor ranges
+            if let Some(params) = t.params.as_object_mut() {
+                let synth = create_synth(t.synth_type, DEFAULT_SAMPLE_RATE, None);
+                for desc in synth.param_descriptors() {
+                    let Some(v) = params.get_mut(&desc.key) else {
+                        continue;
+                    };
+                    let Some(mut value) = v.as_f64().map(|x| x as f32) else {
+                        continue;
+                    };
+                    let original = value;
+                    clamp(label(&desc.key), &mut value, desc.min, desc.max);
+                    if value != original {
+                        // Keep integer params (modes, counts) integral so they still deserialize
+                        *v = if v.is_i64() || v.is_u64() {
+                            serde_json::json!(value.round() as i64)
+                        } else {
+                            serde_json::json!(value)
+                        };
+                    }
+                }
+            }
+        }
+
+        // Parameter locks, against the locked track's descriptor ranges
+        let descriptors: Vec<_> = self
+            .tracks
+            .iter()
+            .map(|t| create_synth(t.synth_type, DEFAULT_SAMPLE_RATE, None).param_descriptors())
+            .collect();
+        for (index, pattern) in self.pattern_bank.patterns.iter_mut().enumerate() {
+            for lock in pattern.param_locks.iter_mut() {
+                let desc = descriptors.get(lock.track).and_then(|d| d.iter().find(|d| d.key == lock.key));
+                if let Some(desc) = desc {
+                    let label = format!(
+                        "Pattern {} track {} step {} lock {}",
+                        pattern_label(index),
+                        lock.track + 1,
+                        lock.step + 1,
+                        lock.key
+                    );
+                    clamp(label, &mut lock.value, desc.min, desc.max);
+                }
+            }
         }
+        warnings
     }
 
     /// Reconstruct a SequencerState from project data (runtime fields default)
@@ -147,94 +500,178 @@ impl ProjectData {
         let tracks: Vec<TrackState> = self
             .tracks
             .iter()
-            .map(|t| TrackState {
-                synth_type: t.synth_type,
-                name: t.name.clone(),
-                default_note: t.default_note,
-                params_snapshot: t.params.clone(),
-                volume: t.volume,
-                pan: t.pan,
-                mute: t.mute,
-                solo: t.solo,
-                fx: t.fx.clone(),
-            })
+            .map(TrackProjectData::to_track_state)
             .collect();
 
         SequencerState {
             playing: false,
             bpm: self.bpm,
             current_step: 0,
+            run_steps: 0,
+            run_bars: 0,
             pattern,
             tracks,
             master_fx: self.master_fx.clone(),
             pattern_bank: self.pattern_bank.clone(),
             current_pattern: self.current_pattern,
+            pending_pattern: None,
             playback_mode: self.playback_mode,
             arrangement: self.arrangement.clone(),
             arrangement_position: 0,
             arrangement_repeat: 0,
             current_variation: self.current_variation,
+            sample_budget_mb: self.sample_budget_mb,
+            sample_stream_mb: self.sample_stream_mb,
+            preview_duck_db: self.preview_duck_db,
+            render_interpolation: self.render_interpolation,
+            export_tone: self.export_tone,
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            dj_filter: 0.0,
+            gain_match: false,
+            headroom: false,
+            metronome: false,
+            scripts: self.scripts.clone(),
+            key: self.key,
+            transpose: self.transpose,
+            swing: self.swing,
+            master_meter: LevelMeter::default(),
+            master_true_peak: 0.0,
+            limiter_reduction: 0.0,
+            cue_output: None,
+            deck_b: None,
+            crossfader: 0.0,
+            midi_sync: MidiSync::Internal,
+            tone: ToneParams::default(),
+            tone_playing: false,
+            capture: None,
+            extensions: self.extensions.clone(),
         }
     }
 
     /// Convert absolute wav_path fields to relative paths (relative to project dir)
     fn make_paths_relative(&mut self, project_dir: &Path) {
+        let relative = |path: &str| {
+            let abs = PathBuf::from(path);
+            // Paths outside the project dir are kept as-is (might be in global samples dir)
+            abs.strip_prefix(project_dir).ok().map(|rel| rel.to_string_lossy().to_string())
+        };
         for track in &mut self.tracks {
             if track.synth_type == SynthType::Sampler {
-                if let Some(wav_path) = track.params.get("wav_path").and_then(|v| v.as_str()) {
-                    let abs = PathBuf::from(wav_path);
-                    if abs.is_absolute() {
-                        // Try to make relative to project dir
-                        if let Ok(rel) = abs.strip_prefix(project_dir) {
-                            track.params["wav_path"] =
-                                Value::String(rel.to_string_lossy().to_string());
+                if let Some(rel) = track.params.get("wav_path").and_then(|v| relative(v.as_str()?)) {
+                    track.params["wav_path"] = Value::String(rel);
+                }
+                if let Some(layers) = track.params.get_mut("layers").and_then(|v| v.as_array_mut()) {
+                    for layer in layers {
+                        if let Some(rel) = layer.get("wav_path").and_then(|v| relative(v.as_str()?)) {
+                            layer["wav_path"] = Value::String(rel);
                         }
-                        // Otherwise keep as-is (might be in global samples dir)
                     }
                 }
             }
         }
     }
 
-    /// Load WAV buffers for all sampler tracks, resolving relative paths against project dir
-    pub fn load_sample_buffers(&self, project_dir: &Path) -> Vec<SampleBuffer> {
-        let mut buffers = Vec::new();
-        for (i, track) in self.tracks.iter().enumerate() {
-            if track.synth_type != SynthType::Sampler {
-                continue;
-            }
-            let wav_path = match track.params.get("wav_path").and_then(|v| v.as_str()) {
-                Some(p) if !p.is_empty() => p,
-                _ => continue,
-            };
-
-            // Resolve path: try relative to project dir first, then absolute, then sample dirs
-            let resolved = resolve_wav_path(wav_path, project_dir);
-            if let Some(full_path) = resolved {
-                match load_wav(&full_path, 44100.0) {
-                    Ok(buffer) => {
-                        buffers.push(SampleBuffer {
-                            track: i,
-                            buffer,
-                            path: full_path.to_string_lossy().to_string(),
-                        });
-                    }
-                    Err(e) => {
-                        eprintln!(
-                            "Warning: Failed to load sample for track {}: {} ({})",
-                            i, wav_path, e
-                        );
-                    }
+    /// Load WAV buffers for all sampler tracks and their velocity layers,
+    /// resolving relative paths against project dir
+    pub fn load_sample_buffers(&self, project_dir: &Path, sample_rate: f32) -> Vec<SampleBuffer> {
+        self.tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| track.synth_type == SynthType::Sampler)
+            .flat_map(|(i, track)| {
+                let options = SampleOptions::from_params(&track.params);
+                track_sample_buffers(i, &track.params, options, project_dir, sample_rate)
+            })
+            .collect()
+    }
+
+    /// Plugin commands of the external synth tracks. Nothing is started
+    /// here: a project file can name any command, so the caller decides
+    /// which ones to run (allowlisted or confirmed by the user).
+    pub fn external_commands(&self) -> Vec<(usize, String)> {
+        self.tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| track.synth_type == SynthType::External)
+            .filter_map(|(i, track)| external_command(&track.params).map(|c| (i, c.to_string())))
+            .collect()
+    }
+}
+
+/// Load a sampler's sample and velocity layers, processed with `options`
+fn track_sample_buffers(
+    track: usize,
+    params: &Value,
+    options: SampleOptions,
+    project_dir: &Path,
+    sample_rate: f32,
+) -> Vec<SampleBuffer> {
+    let main = params.get("wav_path").and_then(|v| v.as_str());
+    let layers = params
+        .get("layers")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .map(|layer| layer.get("wav_path").and_then(|v| v.as_str()));
+    let paths = std::iter::once((None, main)).chain(layers.enumerate().map(|(n, p)| (Some(n), p)));
+    let mut buffers = Vec::new();
+    for (layer, wav_path) in paths {
+        let wav_path = match wav_path {
+            Some(p) if !p.is_empty() => p,
+            _ => continue,
+        };
+
+        // Resolve path: try relative to project dir first, then absolute, then sample dirs
+        let resolved = resolve_wav_path(wav_path, project_dir);
+        if let Some(full_path) = resolved {
+            match load_wav(&full_path, sample_rate) {
+                Ok(buffer) => {
+                    let buffer = prepare_sample(buffer, options);
+                    // Layers aren't analyzed, like the sampler never did
+                    let info = match layer {
+                        Some(_) => SampleInfo::default(),
+                        None => SampleInfo::detect(&buffer, sample_rate),
+                    };
+                    buffers.push(SampleBuffer {
+                        track,
+                        layer,
+                        buffer,
+                        path: full_path.to_string_lossy().to_string(),
+                        options,
+                        info,
+                    });
+                }
+                Err(e) => {
+                    warn!(track, path = wav_path, "Failed to load sample: {:#}", e);
                 }
-            } else {
-                eprintln!(
-                    "Warning: Sample not found for track {}: {}",
-                    i, wav_path
-                );
             }
+        } else {
+            warn!(track, path = wav_path, "Sample not found");
         }
-        buffers
     }
+    buffers
+}
+
+/// Sample loads that go with a normalize/trim change on a sampler track: its
+/// sample and layers re-read from disk and processed here, off the audio
+/// thread. None when `cmd` is not such a change or the track has no sample
+/// file to re-read.
+pub fn sample_option_reload(state: &SequencerState, cmd: &Command) -> Option<Vec<Command>> {
+    let Command::SetTrackParam { track, ref key, value } = *cmd else {
+        return None;
+    };
+    let t = state.tracks.get(track).filter(|t| t.synth_type == SynthType::Sampler)?;
+    let mut options = SampleOptions::from_params(&t.params_snapshot);
+    match key.as_str() {
+        "normalize" => options.normalize = value >= 0.5,
+        "trim_silence" => options.trim_silence = value >= 0.5,
+        _ => return None,
+    }
+    let buffers = track_sample_buffers(track, &t.params_snapshot, options, Path::new("."), state.sample_rate);
+    if buffers.is_empty() {
+        return None;
+    }
+    Some(buffers.into_iter().map(|b| b.into_command(state.sample_stream_mb)).collect())
 }
 
 /// Resolve a wav path from a project file
@@ -265,21 +702,83 @@ pub fn save_project(state: &SequencerState, path: &Path) -> Result<()> {
         let abs_dir = std::fs::canonicalize(project_dir).unwrap_or_else(|_| project_dir.to_path_buf());
         project.make_paths_relative(&abs_dir);
     }
+    project.pattern_bank.trim_empty_slots(project.current_pattern);
     let json = serde_json::to_string_pretty(&project)
         .context("Failed to serialize project")?;
-    std::fs::write(path, json)
+    // Saved projects must load back to the same data
+    debug_assert!(
+        serde_json::from_str::<ProjectData>(&json)
+            .ok()
+            .and_then(|p| serde_json::to_string_pretty(&p).ok())
+            .as_deref()
+            == Some(json.as_str()),
+        "project JSON does not round-trip"
+    );
+    write_project_file(path, &json)
+}
+
+/// Write a project file so a crash or full disk never leaves it half
+/// written: the JSON goes to a temp file next to it, is synced and loaded
+/// back, and only then renamed over the target. On failure the file on
+/// disk is left as it was.
+fn write_project_file(path: &Path, json: &str) -> Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = path.with_file_name(format!(".{}.tmp", name));
+    let result = write_synced(&tmp, json)
+        .and_then(|()| load_project(&tmp).map(|_| ()).context("Saved project did not load back"))
+        .and_then(|()| {
+            std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    result?;
+    // Persist the rename too; directories can't be synced everywhere, so this is best-effort
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        let _ = std::fs::File::open(dir).and_then(|d| d.sync_all());
+    }
+    Ok(())
+}
+
+/// Write a file and flush it to disk
+fn write_synced(path: &Path, contents: &str) -> Result<()> {
+    let mut file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(contents.as_bytes())
         .with_context(|| format!("Failed to write {}", path.display()))?;
+    file.sync_all()
+        .with_context(|| format!("Failed to sync {}", path.display()))?;
     Ok(())
 }
 
 /// Load a project from a .grox JSON file (supports v1 migration)
 pub fn load_project(path: &Path) -> Result<ProjectData> {
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .len();
+    if size > MAX_PROJECT_BYTES {
+        bail!(
+            "{} is too large for a project file ({} bytes, max {})",
+            path.display(),
+            size,
+            MAX_PROJECT_BYTES
+        );
+    }
     let json = std::fs::read_to_string(path)
         .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_project(&json).with_context(|| format!("Invalid project {}", path.display()))
+}
+
+/// Parse and validate project JSON (the part of load_project that sees
+/// untrusted bytes, split out for fuzzing)
+pub fn parse_project(json: &str) -> Result<ProjectData> {
+    if json.len() as u64 > MAX_PROJECT_BYTES {
+        bail!("Project is too large ({} bytes, max {})", json.len(), MAX_PROJECT_BYTES);
+    }
 
     // Peek at version to determine format
-    let raw: Value = serde_json::from_str(&json)
-        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let raw: Value = serde_json::from_str(json).context("Failed to parse project JSON")?;
 
     let version = raw.get("version").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
 
@@ -291,15 +790,356 @@ pub fn load_project(path: &Path) -> Result<ProjectData> {
         );
     }
 
-    if version <= 1 {
-        // v1 format: migrate to v2
-        let v1: ProjectDataV1 = serde_json::from_value(raw)
-            .with_context(|| format!("Failed to parse v1 project {}", path.display()))?;
-        Ok(v1.migrate())
+    let mut project = if version <= 1 {
+        // v1 format: migrate to the current layout
+        let v1: ProjectDataV1 = serde_json::from_value(raw).context("Failed to parse v1 project")?;
+        v1.migrate()
     } else {
-        // v2 format
-        let project: ProjectData = serde_json::from_value(raw)
-            .with_context(|| format!("Failed to parse v2 project {}", path.display()))?;
-        Ok(project)
+        // v2/v3 format (v2 is v3 without extension maps and pattern
+        // lengths, so its patterns load as 16 steps)
+        serde_json::from_value(raw).with_context(|| format!("Failed to parse v{} project", version))?
+    };
+
+    // Checked before fill_slots, which builds a row per track in every slot
+    if project.tracks.is_empty() || project.tracks.len() > MAX_TRACKS {
+        bail!("Project has {} tracks (expected 1-{})", project.tracks.len(), MAX_TRACKS);
+    }
+    // Older files may lack variation B rows
+    for pat in project.pattern_bank.patterns.iter_mut() {
+        pat.ensure_variation_b();
+    }
+    // Files store only the used slots (and older ones had 16)
+    let default_notes: Vec<u8> = project.tracks.iter().map(|t| t.default_note).collect();
+    project.pattern_bank.fill_slots(&default_notes);
+    project.validate()?;
+    Ok(project)
+}
+
+fn check_finite(name: &str, value: f32) -> Result<()> {
+    if !value.is_finite() {
+        bail!("{} is not a finite number", name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequencer::pattern::StepData;
+    use crate::sequencer::{ParamLock, TrackAutomation, DEFAULT_TRACKS, MAX_ARRANGEMENT_ENTRIES, MAX_REPEATS};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    /// The default project as JSON, with one arrangement entry
+    fn project_json() -> Value {
+        let mut state = SequencerState::new();
+        state.arrangement.append(0, 1);
+        serde_json::to_value(ProjectData::from_state(&state)).unwrap()
+    }
+
+    /// The error parse_project gives for `project`, with its causes
+    fn rejection(project: &Value) -> String {
+        match parse_project(&project.to_string()) {
+            Ok(_) => panic!("project was accepted"),
+            Err(e) => format!("{:#}", e),
+        }
+    }
+
+    #[test]
+    fn default_project_parses() {
+        assert!(parse_project(&project_json().to_string()).is_ok());
+    }
+
+    /// A project from fixtures/migration, loaded as the app would
+    fn load_fixture(name: &str) -> ProjectData {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/migration").join(name);
+        let project = load_project(&path).unwrap_or_else(|e| panic!("{:#}", e));
+        assert!(project.to_state().check_invariants().is_ok());
+        project
+    }
+
+    /// Steps that are on in one row
+    fn active_steps(row: &[StepData]) -> Vec<usize> {
+        row.iter().enumerate().filter(|(_, sd)| sd.active).map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn v1_fixture_migrates() {
+        let project = load_fixture("v1.grox");
+        assert_eq!(project.version, PROJECT_VERSION);
+        assert_eq!(project.bpm, 126.0);
+        let tracks: Vec<_> = project.tracks.iter().map(|t| (t.synth_type, t.name.as_str(), t.default_note)).collect();
+        assert_eq!(
+            tracks,
+            [
+                (SynthType::Kick, "KICK", 36),
+                (SynthType::Snare, "SNARE", 50),
+                (SynthType::HiHat, "HIHAT", 60),
+                (SynthType::Bass, "BASS", 33),
+            ]
+        );
+        assert_eq!(project.tracks[2].pan, 0.3);
+        assert_eq!(project.tracks[0].params["drive"], 0.2f32);
+        assert!(project.tracks.iter().all(|t| t.extensions.is_empty()));
+
+        // 16 stored slots of 16 steps, padded to the full bank
+        let bank = &project.pattern_bank;
+        assert_eq!(bank.patterns.len(), NUM_PATTERNS);
+        assert!(bank.patterns.iter().all(|p| p.length == 16 && p.num_tracks() == 4));
+        assert_eq!(active_steps(&bank.patterns[0].steps_a[0]), [0, 4, 8, 12]);
+        assert_eq!(active_steps(&bank.patterns[1].steps_a[3]), [0, 3, 8, 11]);
+        assert_eq!(bank.patterns[1].steps_a[3][8].note, 40);
+        assert_eq!(bank.patterns[1].steps_a[3][8].velocity, 127);
+        // v1 had no variation B; it is added empty
+        assert!(bank.patterns[0].steps_b.iter().all(|row| active_steps(row).is_empty()));
+        assert!((2..NUM_PATTERNS).all(|slot| !bank.has_content(slot)));
+
+        let song: Vec<_> = project.arrangement.entries.iter().map(|e| (e.pattern, e.repeats)).collect();
+        assert_eq!(song, [(0, 2), (1, 4)]);
+        assert_eq!(project.playback_mode, PlaybackMode::Song);
+        assert_eq!(project.current_pattern, 1);
+    }
+
+    #[test]
+    fn v2_fixture_loads() {
+        let project = load_fixture("v2.grox");
+        assert_eq!(project.bpm, 132.0);
+        let names: Vec<_> = project.tracks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["KICK", "SNARE", "HIHAT", "BASS", "SUB"]);
+        assert_eq!(project.tracks[4].synth_type, SynthType::Bass);
+        assert_eq!(project.tracks[4].default_note, 28);
+
+        let bank = &project.pattern_bank;
+        assert_eq!(bank.patterns.len(), NUM_PATTERNS);
+        assert!(bank.patterns.iter().all(|p| p.length == 16 && p.num_tracks() == 5));
+        assert_eq!(active_steps(&bank.patterns[0].steps_b[2]).len(), 16);
+        assert_eq!(active_steps(&bank.patterns[1].steps_a[4]), [0, 8]);
+        assert!(active_steps(&bank.patterns[1].steps_b[0]).is_empty());
+        // Padded slots use each track's default note
+        assert_eq!(bank.patterns[40].steps_a[4][0].note, 28);
+        assert!((2..NUM_PATTERNS).all(|slot| !bank.has_content(slot)));
+
+        let song: Vec<_> = project.arrangement.entries.iter().map(|e| (e.pattern, e.repeats)).collect();
+        assert_eq!(song, [(0, 1), (1, 3), (0, 1)]);
+        assert_eq!(project.current_variation, Variation::B);
+        assert!(project.extensions.is_empty());
+    }
+
+    /// Save `state`, check it loads back with the same bank, and return the file size
+    fn save_and_reload(state: &SequencerState, name: &str) -> u64 {
+        let path = temp_project(name);
+        save_project(state, &path).unwrap_or_else(|e| panic!("{:#}", e));
+        let size = std::fs::metadata(&path).unwrap().len();
+        let loaded = load_project(&path);
+        let _ = std::fs::remove_file(&path);
+        let loaded = loaded.unwrap_or_else(|e| panic!("{:#}", e)).to_state();
+        assert_eq!(loaded.tracks.len(), state.tracks.len());
+        for (slot, pattern) in state.pattern_bank.patterns.iter().enumerate() {
+            let reloaded = loaded.pattern_bank.get(slot);
+            assert_eq!(reloaded.length, pattern.length);
+            assert_eq!(reloaded.param_locks.len(), pattern.param_locks.len());
+            assert_eq!(reloaded.steps_b[MAX_TRACKS - 1][MAX_STEPS - 1].offset, 99);
+        }
+        size
+    }
+
+    #[test]
+    fn maximal_project_saves_and_reloads() {
+        // Every track, slot and step in use with every step field set
+        let mut state = build_state(MAX_TRACKS - DEFAULT_TRACKS, 120.0, &[], &[]);
+        let full = StepData {
+            active: true,
+            note: 127,
+            velocity: 100,
+            probability: 50,
+            offset: 99,
+            tie: true,
+            off: true,
+            slide: true,
+            accent: true,
+        };
+        for pattern in state.pattern_bank.patterns.iter_mut() {
+            pattern.set_length(MAX_STEPS);
+            for variation in [Variation::A, Variation::B] {
+                pattern.steps_mut(variation).iter_mut().for_each(|row| row.fill(full));
+            }
+        }
+        for slot in 0..MAX_ARRANGEMENT_ENTRIES {
+            state.arrangement.append(slot % NUM_PATTERNS, MAX_REPEATS);
+            let automation = (0..MAX_TRACKS)
+                .map(|track| TrackAutomation { track, mute: Some(true), solo: Some(false), volume: Some(0.5), fade: true })
+                .collect();
+            state.arrangement.set_automation(slot, automation);
+        }
+        let steps = (NUM_PATTERNS * MAX_TRACKS * MAX_STEPS * 2) as u64;
+        let size = save_and_reload(&state, "maximal");
+        assert!(size <= steps * STEP_JSON_BYTES, "{} bytes for {} steps", size, steps);
+
+        // All locks on every step of one slot (a whole bank of them is too
+        // slow to write here; the limit scales this per-lock size)
+        let pattern = state.pattern_bank.get_mut(0);
+        for variation in [Variation::A, Variation::B] {
+            for track in 0..MAX_TRACKS {
+                for step in 0..MAX_STEPS {
+                    pattern.param_locks.extend((0..MAX_LOCKS_PER_STEP).map(|lock| ParamLock {
+                        track,
+                        step,
+                        variation,
+                        key: format!("snare_noise_decay_{}", lock),
+                        value: -0.123_456_79,
+                    }));
+                }
+            }
+        }
+        let locks = (MAX_TRACKS * MAX_STEPS * 2 * MAX_LOCKS_PER_STEP) as u64;
+        let locked_size = save_and_reload(&state, "maximal-locked");
+        assert!(
+            locked_size - size <= locks * LOCK_JSON_BYTES,
+            "{} bytes for {} locks",
+            locked_size - size,
+            locks
+        );
+    }
+
+    #[test]
+    fn rejects_non_finite_numbers() {
+        // JSON has no NaN literal, but out-of-range numbers read as infinite f32
+        let mut project = project_json();
+        project["bpm"] = json!(1e39);
+        assert!(rejection(&project).contains("bpm is not a finite number"));
+
+        let mut project = project_json();
+        project["tracks"][1]["volume"] = json!(-1e39);
+        assert!(rejection(&project).contains("volume is not a finite number"));
+
+        let mut project = project_json();
+        project["master_fx"]["reverb_mix"] = json!(1e39);
+        assert!(rejection(&project).contains("reverb_mix is not a finite number"));
+    }
+
+    #[test]
+    fn rejects_oversized_files() {
+        let oversized = " ".repeat(MAX_PROJECT_BYTES as usize + 1);
+        assert!(parse_project(&oversized).err().unwrap().to_string().contains("too large"));
+
+        // load_project checks the size before reading (a sparse file costs no disk)
+        let path = temp_project("oversized");
+        std::fs::File::create(&path).unwrap().set_len(MAX_PROJECT_BYTES + 1).unwrap();
+        let err = load_project(&path).err().unwrap().to_string();
+        let _ = std::fs::remove_file(&path);
+        assert!(err.contains("too large"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_huge_arrays_without_allocating_them() {
+        // An unchecked length must not size the variation B rows it implies
+        let mut project = project_json();
+        let pattern = &mut project["pattern_bank"]["patterns"][0];
+        pattern["length"] = json!(1u64 << 40);
+        pattern.as_object_mut().unwrap().remove("steps_b");
+        assert!(rejection(&project).contains("Pattern length"));
+
+        let mut project = project_json();
+        let row = project["pattern_bank"]["patterns"][0]["steps_a"][0].clone();
+        let step = row[0].clone();
+        project["pattern_bank"]["patterns"][0]["steps_a"][0] = json!(vec![step; 10_000]);
+        assert!(rejection(&project).contains("row has more than 64 steps"));
+
+        let mut project = project_json();
+        let entry = project["arrangement"]["entries"][0].clone();
+        project["arrangement"]["entries"] = json!(vec![entry; MAX_ARRANGEMENT_ENTRIES + 1]);
+        assert!(rejection(&project).contains("Arrangement has"));
+
+        let mut project = project_json();
+        let slot = project["pattern_bank"]["patterns"][0].clone();
+        project["pattern_bank"]["patterns"] = json!(vec![slot; NUM_PATTERNS + 1]);
+        assert!(rejection(&project).contains("Pattern bank has"));
+    }
+
+    #[test]
+    fn rejects_bad_track_counts() {
+        let mut project = project_json();
+        project["tracks"] = json!([]);
+        assert!(rejection(&project).contains("Project has 0 tracks"));
+
+        // Rejected before fill_slots builds rows for every track
+        let mut project = project_json();
+        let track = project["tracks"][0].clone();
+        project["tracks"] = json!(vec![track; 10_000]);
+        assert!(rejection(&project).contains("Project has 10000 tracks"));
+
+        // Patterns disagreeing with the track list
+        let mut project = project_json();
+        project["pattern_bank"]["patterns"][0]["steps_a"].as_array_mut().unwrap().pop();
+        assert!(rejection(&project).contains("Pattern 0 has"));
+    }
+
+    /// A project path in the temp dir, unique to this test process
+    fn temp_project(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("gridoxide-{}-{}.grox", name, std::process::id()))
+    }
+
+    /// Default state plus `extra` tracks, with `steps` (slot, track, step,
+    /// note) turned on and `song` (pattern, repeats) arranged
+    fn build_state(extra: usize, bpm: f32, steps: &[(usize, usize, usize, u8)], song: &[(usize, usize)]) -> SequencerState {
+        let mut state = SequencerState::new();
+        for i in 0..extra {
+            let mut track = state.tracks[i % DEFAULT_TRACKS].clone();
+            track.name = format!("T{}", i + 1);
+            state.tracks.push(track);
+            for pattern in state.pattern_bank.patterns.iter_mut() {
+                pattern.add_track(60);
+            }
+        }
+        let num_tracks = state.tracks.len();
+        for &(slot, track, step, note) in steps {
+            let pattern = state.pattern_bank.get_mut(slot);
+            let (track, step) = (track % num_tracks, step % pattern.length);
+            pattern.set(track, step, true);
+            pattern.set_note(track, step, note);
+        }
+        for &(pattern, repeats) in song {
+            state.arrangement.append(pattern, repeats);
+        }
+        state.bpm = bpm;
+        state.pattern = state.pattern_bank.get(state.current_pattern).clone();
+        state
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(24))]
+
+        #[test]
+        fn save_then_load_round_trips(
+            extra in 0..=MAX_TRACKS - DEFAULT_TRACKS,
+            bpm in 60.0f32..200.0,
+            steps in vec((0..NUM_PATTERNS, 0..MAX_TRACKS, 0..MAX_STEPS, 0u8..128), 0..40),
+            song in vec((0..NUM_PATTERNS, 1..=MAX_REPEATS), 0..10),
+        ) {
+            let state = build_state(extra, bpm, &steps, &song);
+            prop_assert!(state.check_invariants().is_ok(), "{:?}", state.check_invariants());
+
+            let (first, second) = (temp_project("roundtrip-a"), temp_project("roundtrip-b"));
+            save_project(&state, &first).unwrap();
+            let loaded = load_project(&first).unwrap().to_state();
+            prop_assert!(loaded.check_invariants().is_ok(), "{:?}", loaded.check_invariants());
+            prop_assert_eq!(loaded.tracks.len(), state.tracks.len());
+            prop_assert_eq!(loaded.bpm, state.bpm);
+            prop_assert_eq!(loaded.arrangement.len(), state.arrangement.len());
+            for &(slot, track, step, _) in &steps {
+                let (pattern, reloaded) = (state.pattern_bank.get(slot), loaded.pattern_bank.get(slot));
+                let (track, step) = (track % state.tracks.len(), step % pattern.length);
+                prop_assert!(reloaded.get(track, step));
+                let (a, b) = (&pattern.steps(Variation::A)[track][step], &reloaded.steps(Variation::A)[track][step]);
+                prop_assert_eq!(a.note, b.note, "slot {} track {} step {}", slot, track, step);
+            }
+
+            // A second save of what was loaded writes the same file
+            save_project(&loaded, &second).unwrap();
+            let (a, b) = (std::fs::read_to_string(&first).unwrap(), std::fs::read_to_string(&second).unwrap());
+            let _ = (std::fs::remove_file(&first), std::fs::remove_file(&second));
+            prop_assert_eq!(a, b);
+        }
     }
 }
@@ -0,0 +1,274 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use anyhow::Result;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use parking_lot::Mutex;
+
+use super::renderer::{export_wav, ExportMode};
+use super::stems::export_stems;
+use crate::audio::{level_db, SequencerState};
+use crate::sequencer::pattern_label;
+
+/// Finished jobs kept for the queue view and status polling
+const MAX_FINISHED_JOBS: usize = 20;
+
+/// What an export job writes
+pub enum ExportTarget {
+    /// One stereo WAV file
+    Wav(PathBuf),
+    /// A WAV per track and a DAW session, in a directory
+    Stems(PathBuf),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// An export job as the queue view and MCP see it
+#[derive(Clone, Debug)]
+pub struct JobInfo {
+    pub id: u64,
+    /// What and where, e.g. "Song WAV -> song.wav"
+    pub label: String,
+    pub status: JobStatus,
+    /// Share of the render done (0-1)
+    pub progress: f32,
+    /// What was written, or why it failed, once finished
+    pub message: String,
+    /// Finished with something to check (unclipped overs)
+    pub warning: bool,
+}
+
+struct Job {
+    info: JobInfo,
+    /// Render progress in thousandths, written by the worker
+    progress: Arc<AtomicU32>,
+    /// Finished and handed to take_finished
+    reported: bool,
+}
+
+struct Work {
+    id: u64,
+    state: Box<SequencerState>,
+    mode: ExportMode,
+    target: ExportTarget,
+    sample_rate: u32,
+    progress: Arc<AtomicU32>,
+}
+
+/// Exports run one after another on a worker thread, in the order they
+/// were queued; each renders the project as it was when queued
+pub struct ExportQueue {
+    jobs: Arc<Mutex<Vec<Job>>>,
+    work_tx: Sender<Work>,
+    next_id: AtomicU64,
+}
+
+impl ExportQueue {
+    pub fn new() -> Self {
+        let (work_tx, work_rx) = unbounded();
+        let jobs = Arc::new(Mutex::new(Vec::new()));
+        let worker_jobs = jobs.clone();
+        thread::spawn(move || run_jobs(work_rx, worker_jobs));
+        Self {
+            jobs,
+            work_tx,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Queue an export of `state`; returns the job's id
+    pub fn push(&self, state: SequencerState, mode: ExportMode, target: ExportTarget, sample_rate: u32) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let progress = Arc::new(AtomicU32::new(0));
+        let info = JobInfo {
+            id,
+            label: job_label(&mode, &target),
+            status: JobStatus::Queued,
+            progress: 0.0,
+            message: String::new(),
+            warning: false,
+        };
+        {
+            let mut jobs = self.jobs.lock();
+            jobs.push(Job { info, progress: progress.clone(), reported: false });
+            // Drop the oldest reported jobs past the limit
+            while jobs.iter().filter(|j| j.reported).count() > MAX_FINISHED_JOBS {
+                if let Some(oldest) = jobs.iter().position(|j| j.reported) {
+                    jobs.remove(oldest);
+                }
+            }
+        }
+        let _ = self.work_tx.send(Work {
+            id,
+            state: Box::new(state),
+            mode,
+            target,
+            sample_rate,
+            progress,
+        });
+        id
+    }
+
+    /// Every job listed, oldest first
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        self.jobs.lock().iter().map(Job::snapshot).collect()
+    }
+
+    pub fn job(&self, id: u64) -> Option<JobInfo> {
+        self.jobs.lock().iter().find(|j| j.info.id == id).map(Job::snapshot)
+    }
+
+    /// Jobs queued or running
+    pub fn pending(&self) -> usize {
+        self.jobs.lock().iter().filter(|j| !j.info.status.is_finished()).count()
+    }
+
+    /// Cancel a job that hasn't started; false if it's running, finished
+    /// or unknown
+    pub fn cancel(&self, id: u64) -> bool {
+        let mut jobs = self.jobs.lock();
+        match jobs.iter_mut().find(|j| j.info.id == id) {
+            Some(job) if job.info.status == JobStatus::Queued => {
+                job.info.status = JobStatus::Cancelled;
+                job.info.message = "Cancelled before it started".to_string();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget finished jobs that have been reported
+    pub fn clear_finished(&self) {
+        self.jobs.lock().retain(|j| !(j.info.status.is_finished() && j.reported));
+    }
+
+    /// Jobs finished since the last call, to report each once
+    pub fn take_finished(&self) -> Vec<JobInfo> {
+        let mut jobs = self.jobs.lock();
+        jobs.iter_mut()
+            .filter(|j| j.info.status.is_finished() && !j.reported)
+            .map(|j| {
+                j.reported = true;
+                j.snapshot()
+            })
+            .collect()
+    }
+}
+
+impl Default for ExportQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Job {
+    fn snapshot(&self) -> JobInfo {
+        let mut info = self.info.clone();
+        if info.status == JobStatus::Running {
+            info.progress = self.progress.load(Ordering::Relaxed) as f32 / 1000.0;
+        }
+        info
+    }
+}
+
+fn job_label(mode: &ExportMode, target: &ExportTarget) -> String {
+    let what = match mode {
+        ExportMode::Pattern(index) => format!("Pattern {}", pattern_label(*index)),
+        ExportMode::Song => "Song".to_string(),
+    };
+    match target {
+        ExportTarget::Wav(path) => format!("{} WAV -> {}", what, path.display()),
+        ExportTarget::Stems(dir) => format!("{} stems -> {}/", what, dir.display()),
+    }
+}
+
+/// The worker: run each job unless it was cancelled while queued
+fn run_jobs(work_rx: Receiver<Work>, jobs: Arc<Mutex<Vec<Job>>>) {
+    for work in work_rx {
+        let id = work.id;
+        let started = {
+            let mut jobs = jobs.lock();
+            match jobs.iter_mut().find(|j| j.info.id == id) {
+                Some(job) if job.info.status == JobStatus::Queued => {
+                    job.info.status = JobStatus::Running;
+                    true
+                }
+                _ => false,
+            }
+        };
+        if !started {
+            continue;
+        }
+        // A render that panics fails its job instead of stopping the queue
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| run_job(work)))
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("render thread panicked")));
+        let mut jobs = jobs.lock();
+        if let Some(job) = jobs.iter_mut().find(|j| j.info.id == id) {
+            match outcome {
+                Ok((message, warning)) => {
+                    job.info.status = JobStatus::Done;
+                    job.info.progress = 1.0;
+                    job.info.message = message;
+                    job.info.warning = warning;
+                }
+                Err(e) => {
+                    job.info.status = JobStatus::Failed;
+                    job.info.message = format!("{:#}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Render one job: what was written, and whether it needs a warning
+fn run_job(work: Work) -> Result<(String, bool)> {
+    match work.target {
+        ExportTarget::Wav(path) => {
+            let result = export_wav(&work.state, work.mode, &path, work.sample_rate, Some(work.progress))?;
+            let message = format!("{} ({:.1}s)", path.display(), result.duration_secs);
+            if result.peak > 1.0 {
+                Ok((format!("{}, peaks at {:+.1} dBFS (no clipper)", message, level_db(result.peak)), true))
+            } else {
+                Ok((message, false))
+            }
+        }
+        ExportTarget::Stems(dir) => {
+            let result = export_stems(&work.state, &work.mode, &dir, work.sample_rate, Some(work.progress))?;
+            Ok((
+                format!(
+                    "{} stems ({:.1}s) with a DAW session to {}/",
+                    result.stems.len(),
+                    result.duration_secs,
+                    dir.display()
+                ),
+                false,
+            ))
+        }
+    }
+}
@@ -1,15 +1,27 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
 
-use crate::audio::SequencerState;
-use crate::fx::{configure_fx_chain, StereoReverb, TrackFxChain};
+use crate::audio::{track_audible, HeldLocks, SequencerState};
+use crate::fx::{
+    configure_fx_chain, configure_limiter, configure_mid_side, configure_reverb, DcBlocker, DjFilter, Limiter,
+    MidSide, StereoReverb, TrackFxChain,
+};
 use crate::samples;
-use crate::sequencer::{Clock, STEPS};
-use crate::synth::{create_synth, load_wav, SoundSource, SynthType};
+use crate::sequencer::{
+    apply_automation, chord_shift, looped_step_at, played_note, Clock, Trigger, TriggerDelay, VolumeFade,
+    MAX_TRACKS,
+};
+use crate::synth::{create_synth, load_wav_at, prepare_sample, SampleInfo, SampleOptions, SoundSource, SynthType};
 
-const SAMPLE_RATE: f32 = 44100.0;
 const TAIL_SECONDS: f32 = 1.0;
+/// Longest one-shot hit, for sounds that hold or ring on
+const MAX_HIT_SECONDS: f32 = 4.0;
+/// A hit ends once it stays below this level (about -80 dBFS) for HIT_GAP_SECONDS
+const HIT_SILENCE: f32 = 1e-4;
+const HIT_GAP_SECONDS: f32 = 0.1;
 
 /// What to render
 pub enum ExportMode {
@@ -23,82 +35,154 @@ pub enum ExportMode {
 pub struct ExportResult {
     pub duration_secs: f32,
     pub samples: usize,
+    /// Loudest sample written (above 1.0 only without the clipper)
+    pub peak: f32,
 }
 
 /// Offline renderer that mirrors the real-time audio callback
 struct OfflineRenderer {
+    sample_rate: f32,
     synths: Vec<Box<dyn SoundSource>>,
     clock: Clock,
     fx_chains: Vec<TrackFxChain>,
     reverb: StereoReverb,
     reverb_enabled: bool,
+    mid_side: MidSide,
+    dj_filter: DjFilter,
+    dc_blockers: [DcBlocker; 2],
+    /// Master limiter, None when off
+    limiter: Option<Limiter>,
     volumes: Vec<f32>,
     pans: Vec<f32>,
     mutes: Vec<bool>,
     solos: Vec<bool>,
+    solo_safe: Vec<bool>,
+    triplets: Vec<bool>,
+    trigger_delays: Vec<TriggerDelay>,
+    held_locks: Vec<HeldLocks>,
+    /// Tracks holding a note on for the tied steps after it
+    tied_notes: Vec<bool>,
     prng_state: u32,
+    /// Sum of squares of each track's post-fader signal over the content
+    track_power: Vec<f64>,
+    /// Loudest master sample before the soft clipper
+    master_peak: f32,
+    /// Each track's signal after its FX, before fader, pan and mute, when
+    /// rendering stems
+    stems: Option<Vec<Vec<f32>>>,
+    /// Share of the render done, in thousandths, for a progress display
+    progress: Option<Arc<AtomicU32>>,
 }
 
 impl OfflineRenderer {
-    fn from_state(state: &SequencerState) -> Self {
+    fn from_state(state: &SequencerState, sample_rate: f32) -> Self {
         let mut synths: Vec<Box<dyn SoundSource>> = Vec::with_capacity(state.tracks.len());
         let mut volumes = Vec::with_capacity(state.tracks.len());
         let mut pans = Vec::with_capacity(state.tracks.len());
         let mut mutes = Vec::with_capacity(state.tracks.len());
         let mut solos = Vec::with_capacity(state.tracks.len());
+        let mut solo_safe = Vec::with_capacity(state.tracks.len());
+        let mut triplets = Vec::with_capacity(state.tracks.len());
         let mut fx_chains = Vec::with_capacity(state.tracks.len());
+        let mut trigger_delays = Vec::with_capacity(state.tracks.len());
 
         for track in &state.tracks {
-            let mut synth = create_synth(track.synth_type, SAMPLE_RATE, Some(&track.params_snapshot));
-            // Load sample buffer for sampler tracks
+            let mut synth = create_synth(track.synth_type, sample_rate, Some(&track.params_snapshot));
+            synth.set_interpolation(state.render_interpolation);
+            synth.set_tempo(state.bpm);
+            // Load sample buffers (main and velocity layers) for sampler tracks
             if track.synth_type == SynthType::Sampler {
-                if let Some(wav_path) = track.params_snapshot.get("wav_path").and_then(|v| v.as_str()) {
+                let options = SampleOptions::from_params(&track.params_snapshot);
+                // Try absolute, then sample dirs; files at another rate are
+                // converted at the render quality
+                let load = |wav_path: &str| {
+                    let path = std::path::PathBuf::from(wav_path);
+                    let full_path = if path.exists() {
+                        path
+                    } else {
+                        let dirs = samples::search_dirs();
+                        samples::resolve_sample_path(wav_path, &dirs)?
+                    };
+                    let buffer = load_wav_at(&full_path, sample_rate, state.render_interpolation).ok()?;
+                    Some((prepare_sample(buffer, options), full_path.to_string_lossy().to_string()))
+                };
+                let snapshot = &track.params_snapshot;
+                if let Some(wav_path) = snapshot.get("wav_path").and_then(|v| v.as_str()) {
                     if !wav_path.is_empty() {
-                        // Try absolute, then sample dirs
-                        let path = std::path::PathBuf::from(wav_path);
-                        let resolved = if path.exists() {
-                            Some(path)
-                        } else {
-                            let dirs = samples::search_dirs();
-                            samples::resolve_sample_path(wav_path, &dirs)
-                        };
-                        if let Some(full_path) = resolved {
-                            if let Ok(buffer) = load_wav(&full_path, SAMPLE_RATE) {
-                                let path_str = full_path.to_string_lossy().to_string();
-                                synth.load_buffer(buffer, &path_str);
-                            }
+                        if let Some((buffer, path_str)) = load(wav_path) {
+                            // Detected live already; no need to analyze it again
+                            synth.load_buffer(buffer.into(), &path_str, SampleInfo::from_params(snapshot));
                         }
                     }
                 }
+                let layers = snapshot.get("layers").and_then(|v| v.as_array()).into_iter().flatten();
+                for (i, layer) in layers.enumerate() {
+                    if let Some((buffer, path_str)) = layer.get("wav_path").and_then(|v| load(v.as_str()?)) {
+                        synth.load_layer_buffer(i, buffer.into(), &path_str);
+                    }
+                }
             }
             synths.push(synth);
             volumes.push(track.volume);
             pans.push(track.pan);
-            mutes.push(track.mute);
-            solos.push(track.solo);
-            let mut chain = TrackFxChain::new(SAMPLE_RATE);
+            // Tone tracks (speaker checks, sirens) stay out unless asked for
+            let excluded = track.synth_type == SynthType::Tone && !state.export_tone;
+            mutes.push(track.mute || excluded);
+            solos.push(track.solo && !excluded);
+            solo_safe.push(track.solo_safe);
+            triplets.push(track.triplet);
+            let mut delay = TriggerDelay::new();
+            delay.set_nudge_ms(track.nudge_ms, sample_rate);
+            trigger_delays.push(delay);
+            let mut chain = TrackFxChain::new(sample_rate);
             configure_fx_chain(&mut chain, &track.fx);
             fx_chains.push(chain);
         }
 
-        let clock = Clock::new(SAMPLE_RATE, state.bpm);
+        let mut clock = Clock::new(sample_rate, state.bpm);
+        clock.set_swing(state.swing);
 
-        let mut reverb = StereoReverb::new(SAMPLE_RATE);
-        reverb.set_decay(state.master_fx.reverb_decay);
-        reverb.set_mix(state.master_fx.reverb_mix);
-        reverb.set_damping(state.master_fx.reverb_damping);
+        let mut reverb = StereoReverb::new(sample_rate);
+        configure_reverb(&mut reverb, &state.master_fx);
+
+        let mut mid_side = MidSide::new(sample_rate);
+        configure_mid_side(&mut mid_side, &state.master_fx);
+
+        // A held DJ sweep is baked into the render at its current position
+        let mut dj_filter = DjFilter::new(sample_rate);
+        dj_filter.reset(state.dj_filter);
+
+        let limiter = state.master_fx.limiter_enabled.then(|| {
+            let mut limiter = Limiter::new(sample_rate);
+            configure_limiter(&mut limiter, &state.master_fx);
+            limiter
+        });
 
         Self {
+            sample_rate,
             synths,
             clock,
             fx_chains,
             reverb,
             reverb_enabled: state.master_fx.reverb_enabled,
+            mid_side,
+            dj_filter,
+            dc_blockers: [DcBlocker::new(sample_rate), DcBlocker::new(sample_rate)],
+            limiter,
             volumes,
             pans,
             mutes,
             solos,
+            solo_safe,
+            triplets,
+            trigger_delays,
+            held_locks: (0..state.tracks.len()).map(|_| HeldLocks::default()).collect(),
+            tied_notes: vec![false; state.tracks.len()],
             prng_state: 0xDEAD_BEEF,
+            track_power: vec![0.0; state.tracks.len()],
+            master_peak: 0.0,
+            stems: None,
+            progress: None,
         }
     }
 
@@ -116,30 +200,30 @@ impl OfflineRenderer {
         state: &SequencerState,
         mode: &ExportMode,
     ) -> Vec<(f32, f32)> {
-        let tail_samples = (SAMPLE_RATE * TAIL_SECONDS) as usize;
+        let tail_samples = (self.sample_rate * TAIL_SECONDS) as usize;
         let num_tracks = self.synths.len();
 
         // Calculate total pattern steps to render
         let total_steps = match mode {
-            ExportMode::Pattern(_idx) => {
-                STEPS // one loop = 16 steps
+            ExportMode::Pattern(idx) => {
+                state.pattern_bank.get(*idx).length // one loop
             }
             ExportMode::Song => {
                 if state.arrangement.is_empty() {
-                    STEPS // fallback: one pattern
+                    state.pattern_bank.get(state.current_pattern).length // fallback: one pattern
                 } else {
                     state
                         .arrangement
                         .entries
                         .iter()
-                        .map(|e| e.repeats * STEPS)
+                        .map(|e| e.repeats * state.pattern_bank.get(e.pattern).length)
                         .sum()
                 }
             }
         };
 
         // samples per step
-        let samples_per_beat = SAMPLE_RATE * 60.0 / state.bpm;
+        let samples_per_beat = self.sample_rate * 60.0 / state.bpm;
         let samples_per_step = samples_per_beat / 4.0;
         let content_samples = (total_steps as f32 * samples_per_step) as usize;
         let total_samples = content_samples + tail_samples;
@@ -159,30 +243,108 @@ impl OfflineRenderer {
         };
         let mut arrangement_pos: usize = 0;
         let mut arrangement_repeat: usize = 0;
+        // Arrangement automation: applied as each entry's first step plays
+        let mut fades = vec![VolumeFade::default(); num_tracks];
+        let mut automation_due = matches!(mode, ExportMode::Song);
+        // Master fade-out over the song's last bars
+        let fade_out = matches!(mode, ExportMode::Song) && state.arrangement.fade_out_bars > 0;
+        // Song position in bars (pattern loops): the length of each loop,
+        // the one playing and the sample it began on
+        let loop_samples: Vec<f32> = state
+            .arrangement
+            .entries
+            .iter()
+            .flat_map(|e| std::iter::repeat_n(state.pattern_bank.get(e.pattern).length as f32 * samples_per_step, e.repeats))
+            .collect();
+        let mut song_loop = 0usize;
+        let mut loop_start = 0.0f32;
 
         self.clock.play();
 
         for sample_idx in 0..total_samples {
             let in_content = sample_idx < content_samples;
+            if sample_idx % 4096 == 0 {
+                if let Some(progress) = &self.progress {
+                    progress.store((sample_idx * 1000 / total_samples) as u32, Ordering::Relaxed);
+                }
+            }
 
             if in_content {
-                // Check for step trigger
+                // Check for step trigger (wrapping at the pattern's end)
+                self.clock.set_length(state.pattern_bank.get(current_pattern_idx).length);
                 if let Some(step) = self.clock.tick() {
                     // Notify all synths of step tick (for hold_steps countdown)
                     for synth in self.synths.iter_mut() {
                         synth.step_tick();
                     }
+                    if step == 0 && automation_due {
+                        automation_due = false;
+                        if let Some(entry) = state.arrangement.entries.get(arrangement_pos) {
+                            let volumes = &self.volumes;
+                            apply_automation(
+                                &entry.automation,
+                                (entry.repeats as f32 * state.pattern_bank.get(entry.pattern).length as f32 * samples_per_step) as usize,
+                                self.sample_rate,
+                                &mut self.mutes,
+                                &mut self.solos,
+                                &mut fades,
+                                |t| volumes[t],
+                            );
+                        }
+                    }
+                    let transpose = match (mode, state.arrangement.entries.get(arrangement_pos)) {
+                        (ExportMode::Song, Some(entry)) => state.transpose + chord_shift(entry.chord),
+                        _ => state.transpose,
+                    };
                     let pat = state.pattern_bank.get(current_pattern_idx);
                     // Use the current variation from the state
                     let variation = state.current_variation;
                     for i in 0..num_tracks {
-                        let sd = pat.get_step_var(i, step, variation);
-                        if sd.active {
+                        let loop_length = pat.track_loop(i, self.triplets[i]);
+                        let Some((track_step, offset)) =
+                            looped_step_at(step, self.clock.run_step(), self.triplets[i], loop_length)
+                        else {
+                            continue;
+                        };
+                        let sd = pat.get_step_var(i, track_step, variation);
+                        // A tied step carries the held note on; any other step ends it
+                        if self.tied_notes[i] && !(sd.active && sd.tie) {
+                            self.synths[i].note_off();
+                            self.tied_notes[i] = false;
+                        }
+                        if sd.active && !self.tied_notes[i] {
                             // Check probability (100 = always trigger)
                             let should_trigger = sd.probability >= 100
                                 || (self.next_prng() % 100) < sd.probability as u32;
                             if should_trigger {
-                                self.synths[i].trigger_with_note_velocity(sd.note, sd.velocity);
+                                let offset = offset + sd.offset as f32 / 100.0;
+                                let extra = (offset * self.clock.samples_per_step()) as usize;
+                                let melodic = self.synths[i].is_melodic();
+                                let note = played_note(sd.note, melodic, transpose, state.tracks[i].transpose);
+                                let steps = pat.track_steps(i, self.triplets[i]);
+                                let hold = pat.is_tied_var(i, (track_step + 1) % steps, steps, variation);
+                                self.tied_notes[i] = hold;
+                                let trigger = Trigger {
+                                    note,
+                                    velocity: sd.velocity,
+                                    step: track_step,
+                                    hold,
+                                    off: false,
+                                    slide: sd.slide,
+                                    accent: sd.accent,
+                                };
+                                if let Some(t) = self.trigger_delays[i].schedule(trigger, extra) {
+                                    let locks = pat.param_locks(i, t.step, variation);
+                                    self.held_locks[i].play(self.synths[i].as_mut(), locks, t);
+                                }
+                            }
+                        } else if sd.off && !sd.active {
+                            // Note-off step: release the sounding note on the step's timing
+                            let extra = ((offset + sd.offset as f32 / 100.0) * self.clock.samples_per_step()) as usize;
+                            let trigger = Trigger::note_off(sd.note, track_step);
+                            if let Some(t) = self.trigger_delays[i].schedule(trigger, extra) {
+                                let locks = pat.param_locks(i, t.step, variation);
+                                self.held_locks[i].play(self.synths[i].as_mut(), locks, t);
                             }
                         }
                     }
@@ -192,11 +354,11 @@ impl OfflineRenderer {
                 if self.clock.take_pattern_wrap() {
                     if let ExportMode::Song = mode {
                         if !state.arrangement.is_empty() {
-                            let entry = state.arrangement.entries[arrangement_pos];
                             arrangement_repeat += 1;
-                            if arrangement_repeat >= entry.repeats {
+                            if arrangement_repeat >= state.arrangement.entries[arrangement_pos].repeats {
                                 arrangement_repeat = 0;
                                 arrangement_pos += 1;
+                                automation_due = true;
                                 if arrangement_pos < state.arrangement.len() {
                                     current_pattern_idx =
                                         state.arrangement.entries[arrangement_pos].pattern;
@@ -206,26 +368,54 @@ impl OfflineRenderer {
                     }
                 }
             } else {
-                // In tail: just advance clock without triggering
+                // In tail: just advance clock without triggering; tied
+                // notes still held release into it
                 self.clock.tick();
                 self.clock.take_pattern_wrap();
+                for (tied, synth) in self.tied_notes.iter_mut().zip(self.synths.iter_mut()) {
+                    if std::mem::take(tied) {
+                        synth.note_off();
+                    }
+                }
+            }
+
+            // Fire nudged triggers that are now due (may land in the tail)
+            for i in 0..num_tracks {
+                while let Some(t) = self.trigger_delays[i].pop_due() {
+                    let locks = state.pattern_bank.get(current_pattern_idx).param_locks(i, t.step, state.current_variation);
+                    // A hold landing in the tail has no tied steps left to play
+                    let hold = t.hold && self.tied_notes[i];
+                    self.held_locks[i].play(self.synths[i].as_mut(), locks, Trigger { hold, ..t });
+                }
+                self.trigger_delays[i].tick();
             }
 
             // Generate audio (always, including tail for decay)
             let any_solo = self.solos.iter().any(|&s| s);
             let mut left = 0.0f32;
             let mut right = 0.0f32;
+            // Voices first, so any track can modulate any other this sample
+            let mut voices = [0.0f32; MAX_TRACKS];
+            for (voice, synth) in voices.iter_mut().zip(self.synths.iter_mut()) {
+                *voice = synth.next_sample();
+            }
             for i in 0..num_tracks {
-                let raw = self.fx_chains[i].process(self.synths[i].next_sample());
-                let audible = if any_solo {
-                    self.solos[i]
-                } else {
-                    !self.mutes[i]
-                };
+                if let Some(v) = fades[i].next() {
+                    self.volumes[i] = v;
+                }
+                let modulator = state.tracks[i].fx.modulator(i, num_tracks).map(|s| voices[s]);
+                let raw = self.fx_chains[i].process(voices[i], modulator);
+                if let Some(stems) = self.stems.as_mut() {
+                    stems[i].push(raw);
+                }
+                let audible = track_audible(self.mutes[i], self.solos[i], self.solo_safe[i], any_solo);
                 if !audible {
                     continue;
                 }
                 let s = raw * self.volumes[i];
+                if in_content {
+                    self.track_power[i] += (s * s) as f64;
+                }
                 let angle = (self.pans[i] + 1.0) * 0.25 * std::f32::consts::PI;
                 left += s * angle.cos();
                 right += s * angle.sin();
@@ -237,8 +427,39 @@ impl OfflineRenderer {
                 right = rr;
             }
 
-            left = soft_clip(left);
-            right = soft_clip(right);
+            let (ml, mr) = self.mid_side.process_stereo(left, right);
+            left = ml;
+            right = mr;
+
+            let (dl, dr) = self.dj_filter.process_stereo(left, right);
+            left = dl;
+            right = dr;
+
+            if fade_out {
+                while song_loop + 1 < loop_samples.len() && sample_idx as f32 >= loop_start + loop_samples[song_loop] {
+                    loop_start += loop_samples[song_loop];
+                    song_loop += 1;
+                }
+                let length = loop_samples.get(song_loop).copied().unwrap_or(samples_per_step).max(1.0);
+                let bars = song_loop as f32 + (sample_idx as f32 - loop_start) / length;
+                let gain = state.arrangement.fade_out_gain(bars);
+                left *= gain;
+                right *= gain;
+            }
+
+            left = self.dc_blockers[0].process(left);
+            right = self.dc_blockers[1].process(right);
+
+            if let Some(ref mut limiter) = self.limiter {
+                (left, right) = limiter.process_stereo(left, right);
+            }
+
+            self.master_peak = self.master_peak.max(left.abs()).max(right.abs());
+            // Headroom mode leaves overs for mastering to deal with
+            if !state.headroom {
+                left = soft_clip(left);
+                right = soft_clip(right);
+            }
 
             output.push((left, right));
         }
@@ -257,39 +478,188 @@ fn soft_clip(x: f32) -> f32 {
     }
 }
 
-/// Render and export audio as a WAV file
+/// Render audio offline to stereo frames (deterministic for a given state)
+pub fn render_frames(state: &SequencerState, mode: &ExportMode, sample_rate: u32) -> Vec<(f32, f32)> {
+    let mut renderer = OfflineRenderer::from_state(state, sample_rate as f32);
+    renderer.render(state, mode)
+}
+
+/// Render each track on its own (after its FX, before fader, pan and
+/// mute, so a DAW can rebuild the mix from the mixer settings). Progress
+/// in thousandths goes to `progress` if given.
+pub fn render_stems(
+    state: &SequencerState,
+    mode: &ExportMode,
+    sample_rate: u32,
+    progress: Option<Arc<AtomicU32>>,
+) -> Vec<Vec<f32>> {
+    let mut renderer = OfflineRenderer::from_state(state, sample_rate as f32);
+    renderer.stems = Some(vec![Vec::new(); state.tracks.len()]);
+    renderer.progress = progress;
+    renderer.render(state, mode);
+    renderer.stems.unwrap_or_default()
+}
+
+/// Levels measured over an offline render
+pub struct LevelReport {
+    /// RMS of each track's post-fader signal over the content (no tail);
+    /// 0.0 for muted tracks
+    pub track_rms: Vec<f32>,
+    /// Master peak before the soft clipper (above 1.0 clips)
+    pub master_peak: f32,
+}
+
+/// Render offline and measure per-track RMS and the master peak
+pub fn measure_levels(state: &SequencerState, mode: &ExportMode, sample_rate: u32) -> LevelReport {
+    let mut renderer = OfflineRenderer::from_state(state, sample_rate as f32);
+    let frames = renderer.render(state, mode).len();
+    let content = frames.saturating_sub((sample_rate as f32 * TAIL_SECONDS) as usize).max(1);
+    LevelReport {
+        track_rms: renderer
+            .track_power
+            .iter()
+            .map(|&power| (power / content as f64).sqrt() as f32)
+            .collect(),
+        master_peak: renderer.master_peak,
+    }
+}
+
+/// WAV file name for a track, e.g. `01_kick.wav`
+pub(crate) fn track_file_name(track: usize, name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{:02}_{}.wav", track + 1, name)
+}
+
+/// One track's hit written by export_hits
+pub struct HitExport {
+    pub track: usize,
+    pub path: PathBuf,
+    pub duration_secs: f32,
+}
+
+impl OfflineRenderer {
+    /// One hit of a track's sound: the synth through its track FX, before
+    /// the fader and pan. Held notes release after their hold steps at the
+    /// project tempo; the hit ends when it falls silent. Each track's synth
+    /// can play one.
+    fn render_hit(&mut self, track: usize, note: u8, velocity: u8) -> Vec<f32> {
+        let samples_per_step = self.clock.samples_per_step();
+        let synth = &mut self.synths[track];
+        let chain = &mut self.fx_chains[track];
+        synth.trigger_with_note_velocity(note, velocity.clamp(1, 127));
+
+        let max_samples = (MAX_HIT_SECONDS * self.sample_rate) as usize;
+        let gap = (HIT_GAP_SECONDS * self.sample_rate) as usize;
+        let mut output = Vec::new();
+        let mut quiet = 0;
+        let mut next_step = samples_per_step;
+        while output.len() < max_samples && (quiet < gap || output.len() < gap) {
+            if output.len() as f32 >= next_step {
+                synth.step_tick();
+                next_step += samples_per_step;
+            }
+            let s = chain.process(synth.next_sample(), None);
+            quiet = if s.abs() < HIT_SILENCE { quiet + 1 } else { 0 };
+            output.push(s);
+        }
+        // Drop the silent gap the hit ended on
+        output.truncate(output.len() - quiet.min(output.len()));
+        output
+    }
+}
+
+/// Export each track's sound as a one-shot mono WAV in `dir` (created if
+/// needed), named by track number and name, e.g. `01_kick.wav`. Tone
+/// tracks are left out unless the project exports them, like in mixes.
+pub fn export_hits(state: &SequencerState, dir: &Path, velocity: u8, sample_rate: u32) -> Result<Vec<HitExport>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut renderer = OfflineRenderer::from_state(state, sample_rate as f32);
+    let mut exports = Vec::new();
+    for (track, t) in state.tracks.iter().enumerate() {
+        if t.synth_type == SynthType::Tone && !state.export_tone {
+            continue;
+        }
+        let path = dir.join(track_file_name(track, &t.name));
+        let samples = renderer.render_hit(track, t.default_note, velocity);
+        let mut writer = hound::WavWriter::create(&path, spec)
+            .with_context(|| format!("Failed to create WAV file: {}", path.display()))?;
+        for s in &samples {
+            writer.write_sample((soft_clip(*s) * 32767.0).clamp(-32768.0, 32767.0) as i16)?;
+        }
+        writer.finalize()
+            .with_context(|| format!("Failed to finalize WAV file: {}", path.display()))?;
+        exports.push(HitExport {
+            track,
+            path,
+            duration_secs: samples.len() as f32 / sample_rate as f32,
+        });
+    }
+    Ok(exports)
+}
+
+/// Render and export audio as a WAV file: 16-bit, or 32-bit float when
+/// the state is in headroom mode so overs past 0 dBFS survive unclipped.
+/// Render progress in thousandths goes to `progress` if given.
 pub fn export_wav(
     state: &SequencerState,
     mode: ExportMode,
     path: &Path,
+    sample_rate: u32,
+    progress: Option<Arc<AtomicU32>>,
 ) -> Result<ExportResult> {
-    let mut renderer = OfflineRenderer::from_state(state);
+    let mut renderer = OfflineRenderer::from_state(state, sample_rate as f32);
+    renderer.progress = progress;
     let samples = renderer.render(state, &mode);
 
-    let spec = hound::WavSpec {
-        channels: 2,
-        sample_rate: SAMPLE_RATE as u32,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
+    let spec = if state.headroom {
+        hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        }
+    } else {
+        hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        }
     };
 
     let mut writer = hound::WavWriter::create(path, spec)
         .with_context(|| format!("Failed to create WAV file: {}", path.display()))?;
 
     for (left, right) in &samples {
-        let l = (*left * 32767.0).clamp(-32768.0, 32767.0) as i16;
-        let r = (*right * 32767.0).clamp(-32768.0, 32767.0) as i16;
-        writer.write_sample(l)?;
-        writer.write_sample(r)?;
+        if state.headroom {
+            writer.write_sample(*left)?;
+            writer.write_sample(*right)?;
+        } else {
+            let l = (*left * 32767.0).clamp(-32768.0, 32767.0) as i16;
+            let r = (*right * 32767.0).clamp(-32768.0, 32767.0) as i16;
+            writer.write_sample(l)?;
+            writer.write_sample(r)?;
+        }
     }
 
     writer.finalize()
         .with_context(|| format!("Failed to finalize WAV file: {}", path.display()))?;
 
-    let duration_secs = samples.len() as f32 / SAMPLE_RATE;
+    let duration_secs = samples.len() as f32 / sample_rate as f32;
 
     Ok(ExportResult {
         duration_secs,
         samples: samples.len(),
+        peak: samples.iter().fold(0.0f32, |peak, (l, r)| peak.max(l.abs()).max(r.abs())),
     })
 }
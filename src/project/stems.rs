@@ -0,0 +1,188 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicU32;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use crate::audio::{level_db, SequencerState};
+use crate::sequencer::chord_label;
+use crate::synth::SynthType;
+
+use super::renderer::{render_stems, track_file_name, ExportMode};
+
+/// Manifest and Reaper session written next to the stems
+const MANIFEST_FILE: &str = "session.json";
+const REAPER_FILE: &str = "session.RPP";
+
+/// A named point in the export: the start of a pattern section
+pub struct Marker {
+    pub name: String,
+    /// Bar (pattern loop) it starts on, from 0
+    pub bar: usize,
+    pub time_secs: f32,
+}
+
+/// One track's stem written by export_stems
+pub struct StemExport {
+    pub track: usize,
+    pub path: PathBuf,
+}
+
+/// Result of export_stems
+pub struct StemsResult {
+    pub stems: Vec<StemExport>,
+    pub markers: Vec<Marker>,
+    pub duration_secs: f32,
+    pub manifest: PathBuf,
+    pub session: PathBuf,
+}
+
+/// Section markers of the export, named like the MIDI export's
+fn markers(state: &SequencerState, mode: &ExportMode) -> Vec<Marker> {
+    let single = |pattern: usize| {
+        vec![Marker { name: format!("Pattern {:02}", pattern), bar: 0, time_secs: 0.0 }]
+    };
+    match mode {
+        ExportMode::Pattern(idx) => single(*idx),
+        ExportMode::Song if state.arrangement.is_empty() => single(state.current_pattern),
+        ExportMode::Song => {
+            let secs_per_step = 60.0 / (state.bpm * 4.0);
+            let (mut bar, mut step) = (0, 0);
+            state
+                .arrangement
+                .entries
+                .iter()
+                .map(|e| {
+                    let mut name = format!("Pattern {:02}", e.pattern);
+                    if e.chord.is_some() {
+                        name = format!("{} ({})", name, chord_label(e.chord));
+                    }
+                    let marker = Marker { name, bar, time_secs: step as f32 * secs_per_step };
+                    bar += e.repeats;
+                    step += e.repeats * state.pattern_bank.get(e.pattern).length;
+                    marker
+                })
+                .collect()
+        }
+    }
+}
+
+/// Reaper project stub: tempo, markers and one track per stem with its
+/// fader, pan and mute/solo
+fn reaper_session(
+    state: &SequencerState,
+    stems: &[StemExport],
+    markers: &[Marker],
+    sample_rate: u32,
+    duration_secs: f32,
+) -> String {
+    let mut rpp = String::from("<REAPER_PROJECT 0.1 \"6.0\"\n");
+    let _ = writeln!(rpp, "  TEMPO {} 4 4", state.bpm);
+    let _ = writeln!(rpp, "  SAMPLERATE {} 0 0", sample_rate);
+    for (i, marker) in markers.iter().enumerate() {
+        let _ = writeln!(rpp, "  MARKER {} {:.6} \"{}\" 0", i + 1, marker.time_secs, marker.name);
+    }
+    for stem in stems {
+        let track = &state.tracks[stem.track];
+        let file = stem.path.file_name().map(|f| f.to_string_lossy()).unwrap_or_default();
+        let _ = writeln!(rpp, "  <TRACK");
+        let _ = writeln!(rpp, "    NAME \"{}\"", track.name.replace('"', "'"));
+        let _ = writeln!(rpp, "    VOLPAN {:.6} {:.6} -1 -1 1", track.volume, track.pan);
+        let _ = writeln!(rpp, "    MUTESOLO {} {} 0", track.mute as u8, if track.solo { 2 } else { 0 });
+        let _ = writeln!(rpp, "    <ITEM");
+        let _ = writeln!(rpp, "      POSITION 0");
+        let _ = writeln!(rpp, "      LENGTH {:.6}", duration_secs);
+        let _ = writeln!(rpp, "      NAME \"{}\"", file);
+        let _ = writeln!(rpp, "      <SOURCE WAVE");
+        let _ = writeln!(rpp, "        FILE \"{}\"", file);
+        let _ = writeln!(rpp, "      >");
+        let _ = writeln!(rpp, "    >");
+        let _ = writeln!(rpp, "  >");
+    }
+    rpp.push_str(">\n");
+    rpp
+}
+
+/// Export each track as a mono 32-bit float WAV in `dir` (created if
+/// needed), named like `01_kick.wav`. Stems are taken after the track FX
+/// and before the fader, pan and mute; `session.json` and a Reaper
+/// `session.RPP` next to them carry the mixer settings and section markers
+/// so a DAW can rebuild the mix. Tone tracks are left out unless the
+/// project exports them, like in mixes. Render progress in thousandths
+/// goes to `progress` if given.
+pub fn export_stems(
+    state: &SequencerState,
+    mode: &ExportMode,
+    dir: &Path,
+    sample_rate: u32,
+    progress: Option<Arc<AtomicU32>>,
+) -> Result<StemsResult> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let rendered = render_stems(state, mode, sample_rate, progress);
+    let frames = rendered.first().map_or(0, |s| s.len());
+    let duration_secs = frames as f32 / sample_rate as f32;
+
+    let mut stems = Vec::new();
+    for (track, samples) in rendered.iter().enumerate() {
+        let t = &state.tracks[track];
+        if t.synth_type == SynthType::Tone && !state.export_tone {
+            continue;
+        }
+        let path = dir.join(track_file_name(track, &t.name));
+        let mut writer = hound::WavWriter::create(&path, spec)
+            .with_context(|| format!("Failed to create WAV file: {}", path.display()))?;
+        for &s in samples {
+            writer.write_sample(s)?;
+        }
+        writer.finalize()
+            .with_context(|| format!("Failed to finalize WAV file: {}", path.display()))?;
+        stems.push(StemExport { track, path });
+    }
+
+    let markers = markers(state, mode);
+    let manifest = json!({
+        "bpm": state.bpm,
+        "sample_rate": sample_rate,
+        "duration_secs": duration_secs,
+        "tracks": stems.iter().map(|stem| {
+            let t = &state.tracks[stem.track];
+            json!({
+                "name": t.name,
+                "file": stem.path.file_name().map(|f| f.to_string_lossy()),
+                "synth": t.synth_type.name(),
+                "volume": t.volume,
+                "volume_db": level_db(t.volume),
+                "pan": t.pan,
+                "mute": t.mute,
+                "solo": t.solo,
+            })
+        }).collect::<Vec<_>>(),
+        "markers": markers.iter().map(|m| json!({
+            "name": m.name,
+            "bar": m.bar,
+            "time_secs": m.time_secs,
+        })).collect::<Vec<_>>(),
+    });
+    let manifest_path = dir.join(MANIFEST_FILE);
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+        .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+    let session_path = dir.join(REAPER_FILE);
+    std::fs::write(&session_path, reaper_session(state, &stems, &markers, sample_rate, duration_secs))
+        .with_context(|| format!("Failed to write {}", session_path.display()))?;
+
+    Ok(StemsResult {
+        stems,
+        markers,
+        duration_secs,
+        manifest: manifest_path,
+        session: session_path,
+    })
+}
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::TrackProjectData;
+use crate::audio::{SequencerState, TrackState};
+use crate::sequencer::{MAX_TRACKS, NUM_PATTERNS};
+
+/// Path of the template file: ~/.gridoxide/templates.json
+pub fn templates_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".gridoxide").join("templates.json")
+}
+
+/// Make a sampler's wav_path (and its layers' paths) absolute, resolved
+/// against `dir`, so the sound still finds its samples from another project
+pub fn absolute_sample_path(params: &mut Value, dir: &Path) {
+    absolute_path(params, dir);
+    if let Some(layers) = params.get_mut("layers").and_then(|v| v.as_array_mut()) {
+        for layer in layers {
+            absolute_path(layer, dir);
+        }
+    }
+}
+
+fn absolute_path(params: &mut Value, dir: &Path) {
+    let Some(wav) = params.get("wav_path").and_then(|v| v.as_str()) else {
+        return;
+    };
+    if let Some(abs) = [PathBuf::from(wav), dir.join(wav)]
+        .iter()
+        .find_map(|p| std::fs::canonicalize(p).ok())
+    {
+        params["wav_path"] = abs.to_string_lossy().to_string().into();
+    }
+}
+
+/// Favorite track setups (synth, params, FX, mixer) saved by name, shared
+/// by every project and with MCP
+#[derive(Default, Serialize, Deserialize)]
+pub struct TemplateStore {
+    pub templates: BTreeMap<String, TrackProjectData>,
+}
+
+impl TemplateStore {
+    /// Load the template file (empty store if it doesn't exist yet)
+    pub fn load() -> Result<Self> {
+        let path = templates_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = templates_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize templates")?;
+        std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Save (or overwrite) a template from a track. Mute/solo aren't kept;
+    /// sample paths are resolved against the project directory `dir`.
+    pub fn insert(&mut self, name: &str, track: &TrackState, dir: &Path) {
+        let mut template = TrackProjectData::from(track);
+        template.name = name.to_string();
+        template.mute = false;
+        template.solo = false;
+        template.fx.mod_source = None;
+        absolute_sample_path(&mut template.params, dir);
+        self.templates.insert(name.to_string(), template);
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.templates.remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TrackProjectData> {
+        self.templates.get(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.templates.keys().cloned().collect()
+    }
+}
+
+/// Copy of `state` with a template added as a new last track, empty in
+/// every pattern. Named after the template, numbered if that's taken.
+pub fn add_template_track(state: &SequencerState, template: &TrackProjectData) -> Result<SequencerState> {
+    if state.tracks.len() >= MAX_TRACKS {
+        bail!("Max {} tracks", MAX_TRACKS);
+    }
+    let mut state = state.clone();
+    let current = state.current_pattern;
+    *state.pattern_bank.get_mut(current) = state.pattern.clone();
+
+    let mut track = template.to_track_state();
+    let taken = |name: &str| state.tracks.iter().any(|t| t.name == name);
+    if taken(&track.name) {
+        track.name = (2..)
+            .map(|n| format!("{} {}", template.name, n))
+            .find(|name| !taken(name))
+            .unwrap_or_default();
+    }
+    for index in 0..NUM_PATTERNS {
+        state.pattern_bank.get_mut(index).add_track(track.default_note);
+    }
+    state.pattern = state.pattern_bank.get(current).clone();
+    state.tracks.push(track);
+    Ok(state)
+}
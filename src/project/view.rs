@@ -0,0 +1,40 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// Where the interface was when a project was last saved: the view, grid
+/// cursor and mixer selection. Kept out of the project file so moving the
+/// cursor doesn't change the project itself.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewState {
+    pub view: String,
+    pub grid_track: usize,
+    pub grid_step: usize,
+    pub mixer_track: usize,
+    pub mixer_field: usize,
+}
+
+/// Sidecar path for a project: `<project>.view.json`
+pub fn view_path_for(project: &Path) -> PathBuf {
+    project.with_extension("view.json")
+}
+
+pub fn save_view_state(project: &Path, view: &ViewState) -> Result<()> {
+    let path = view_path_for(project);
+    let json = serde_json::to_string_pretty(view)?;
+    std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// View state saved with a project, None if there is none yet
+pub fn load_view_state(project: &Path) -> Result<Option<ViewState>> {
+    let path = view_path_for(project);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let json = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let view = serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some(view))
+}
@@ -1,4 +1,10 @@
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use tracing::warn;
 
 /// Entry for a discovered sample file
 pub struct SampleEntry {
@@ -26,7 +32,31 @@ pub fn ensure_samples_dir() {
     }
 }
 
-/// Get the default search directories for samples
+/// Where a new recording from the audio input is written:
+/// `recordings/<track>_<unix time>.wav` in the global samples directory
+pub fn recording_path(track_name: &str) -> PathBuf {
+    let name: String = track_name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    samples_dir().join("recordings").join(format!("{}_{}.wav", name, secs))
+}
+
+/// Directories from the settings searched after the default ones
+static EXTRA_DIRS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Set the extra sample directories (from the settings)
+pub fn set_extra_dirs(dirs: &[PathBuf]) {
+    *EXTRA_DIRS.lock() = dirs.to_vec();
+}
+
+/// Get the search directories for samples: ./samples, the global samples
+/// directory, then any from the settings
 pub fn search_dirs() -> Vec<PathBuf> {
     let mut dirs = Vec::new();
     // Project-local samples/ first
@@ -39,6 +69,7 @@ pub fn search_dirs() -> Vec<PathBuf> {
     if global.is_dir() {
         dirs.push(global);
     }
+    dirs.extend(EXTRA_DIRS.lock().iter().filter(|d| d.is_dir()).cloned());
     dirs
 }
 
@@ -65,11 +96,7 @@ fn scan_dir(root: &Path, current: &Path, entries: &mut Vec<SampleEntry>) {
         let path = entry.path();
         if path.is_dir() {
             scan_dir(root, &path, entries);
-        } else if path
-            .extension()
-            .map(|e| e.eq_ignore_ascii_case("wav"))
-            .unwrap_or(false)
-        {
+        } else if is_wav(&path) {
             let relative = path
                 .strip_prefix(root)
                 .unwrap_or(&path)
@@ -95,6 +122,19 @@ fn scan_dir(root: &Path, current: &Path, entries: &mut Vec<SampleEntry>) {
     }
 }
 
+/// WAV files directly in `dir` (not its subfolders), sorted by name
+pub fn folder_samples(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = read_dir
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_wav(p))
+        .collect();
+    files.sort();
+    files
+}
+
 /// Resolve a sample name/path to an absolute path
 /// Searches project-local ./samples/ first, then global ~/.gridoxide/samples/
 /// Also accepts absolute paths directly
@@ -116,3 +156,63 @@ pub fn resolve_sample_path(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
 
     None
 }
+
+/// Compare two sample paths, resolving symlinks and relative components when possible
+pub fn same_sample_path(a: &Path, b: &Path) -> bool {
+    let a = a.canonicalize().unwrap_or_else(|_| a.to_path_buf());
+    let b = b.canonicalize().unwrap_or_else(|_| b.to_path_buf());
+    a == b
+}
+
+fn is_wav(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.eq_ignore_ascii_case("wav"))
+        .unwrap_or(false)
+}
+
+/// Watches sample directories and collects .wav files that were created, modified or removed
+pub struct SampleWatcher {
+    _watcher: RecommendedWatcher,
+    changed: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl SampleWatcher {
+    /// Start watching the given directories recursively. A directory that
+    /// can't be watched is skipped with a warning.
+    pub fn new(dirs: &[PathBuf]) -> Result<Self> {
+        let changed: Arc<Mutex<Vec<PathBuf>>> = Arc::new(Mutex::new(Vec::new()));
+        let changed_cb = changed.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                return;
+            }
+            let mut changed = changed_cb.lock();
+            for path in event.paths {
+                if is_wav(&path) && !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+        })?;
+        for dir in dirs {
+            let dir = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::Recursive) {
+                warn!(dir = %dir.display(), "Not watching sample dir: {}", e);
+            }
+        }
+        Ok(Self {
+            _watcher: watcher,
+            changed,
+        })
+    }
+
+    /// Take the list of changed .wav paths since the last call
+    pub fn take_changes(&self) -> Vec<PathBuf> {
+        std::mem::take(&mut *self.changed.lock())
+    }
+}
@@ -0,0 +1,517 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use anyhow::{Context, Result};
+use parking_lot::RwLock;
+use rhai::{Engine, EvalAltResult, Scope, AST};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::audio::SequencerState;
+use crate::command::Command;
+use crate::fx::FxParamId;
+use crate::sequencer::random_seed;
+
+/// Most scripts a project can hold
+pub const MAX_SCRIPTS: usize = 8;
+
+/// Starting point for new scripts
+pub const SCRIPT_TEMPLATE: &str = "\
+// Runs at the end of every `every` bars; changes land on the next bar.
+// Constants: bar, tracks, steps, pattern, bpm
+// Try: set_step(2, rand_int(0, steps - 1), rand() < 0.5);
+";
+
+/// Rhai operations allowed per run (guards against runaway loops)
+const MAX_OPERATIONS: u64 = 200_000;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+fn default_every() -> u32 {
+    1
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A user script stored in the project
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Script {
+    pub name: String,
+    pub source: String,
+    /// Run every N bars (a pattern is one bar, so 1 = every pattern loop)
+    #[serde(default = "default_every")]
+    pub every: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl Script {
+    pub fn new(name: &str, source: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            source: source.to_string(),
+            every: 1,
+            enabled: true,
+        }
+    }
+}
+
+/// Get the global scripts directory (~/.gridoxide/scripts/)
+pub fn scripts_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".gridoxide").join("scripts")
+}
+
+/// Read every .rhai file from ./scripts and ~/.gridoxide/scripts, named by
+/// file stem (project-local files win on name clashes)
+pub fn load_script_files() -> Vec<Script> {
+    let mut scripts: Vec<Script> = Vec::new();
+    for dir in [PathBuf::from("./scripts"), scripts_dir()] {
+        let Ok(read_dir) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = read_dir.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+        paths.sort();
+        for path in paths {
+            if path.extension().and_then(|e| e.to_str()) != Some("rhai") {
+                continue;
+            }
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+            if scripts.iter().any(|s| s.name == name) {
+                continue;
+            }
+            if let Ok(source) = std::fs::read_to_string(&path) {
+                scripts.push(Script::new(&name, &source));
+            }
+        }
+    }
+    scripts
+}
+
+/// Parse a script without running it
+pub fn check_syntax(source: &str) -> Result<()> {
+    sandboxed_engine()
+        .compile(source)
+        .map(|_| ())
+        .context("Script syntax error")
+}
+
+/// Rhai engine with no file/network access and bounded resource use
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(4096);
+    engine.set_max_array_size(1024);
+    engine.set_max_map_size(256);
+    engine.disable_symbol("eval");
+    engine
+}
+
+/// What a script sees and produces during one run
+struct ScriptContext {
+    /// Copy of the sequencer state, updated as the script changes things
+    state: SequencerState,
+    commands: Vec<Command>,
+    output: Vec<String>,
+    rng: u32,
+}
+
+impl ScriptContext {
+    fn track(&self, track: i64) -> ScriptResult<usize> {
+        if track >= 0 && (track as usize) < self.state.tracks.len() {
+            Ok(track as usize)
+        } else {
+            Err(format!("Track {} out of range (0-{})", track, self.state.tracks.len() - 1).into())
+        }
+    }
+
+    fn step(&self, track: i64, step: i64) -> ScriptResult<(usize, usize)> {
+        let track = self.track(track)?;
+        let length = self.state.pattern.length;
+        if step >= 0 && (step as usize) < length {
+            Ok((track, step as usize))
+        } else {
+            Err(format!("Step {} out of range (0-{})", step, length - 1).into())
+        }
+    }
+
+    fn next_random(&mut self) -> u32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        self.rng
+    }
+}
+
+/// Result of the scripts due at a bar boundary
+pub struct ScriptRun {
+    pub commands: Vec<Command>,
+    /// "name: message" for each script that started failing
+    pub errors: Vec<String>,
+}
+
+/// Last outcome of a script, shown in the Scripts view
+#[derive(Clone, Default)]
+pub struct ScriptStatus {
+    pub runs: u64,
+    pub error: Option<String>,
+    /// Last line the script printed
+    pub output: Option<String>,
+}
+
+/// Runs project scripts on the UI thread at bar boundaries and turns their
+/// API calls into ordinary commands
+pub struct ScriptHost {
+    engine: Engine,
+    ctx: Rc<RefCell<ScriptContext>>,
+    /// Compiled scripts by name, with the source they came from
+    cache: HashMap<String, (String, AST)>,
+    /// Bars whose last step had played at the previous poll
+    bar: usize,
+    pub status: HashMap<String, ScriptStatus>,
+}
+
+impl ScriptHost {
+    pub fn new() -> Self {
+        let ctx = Rc::new(RefCell::new(ScriptContext {
+            state: SequencerState::new(),
+            commands: Vec::new(),
+            output: Vec::new(),
+            rng: random_seed(),
+        }));
+        let mut engine = sandboxed_engine();
+        register_api(&mut engine, &ctx);
+        Self {
+            engine,
+            ctx,
+            cache: HashMap::new(),
+            bar: 0,
+            status: HashMap::new(),
+        }
+    }
+
+    /// Run the scripts that are due when playback reaches the last step of
+    /// a bar, so their changes are in place for the next downbeat. Bars come
+    /// from the engine's count, so 1-step patterns and several bars between
+    /// polls (high BPM, a slow frame) are not missed; a script due in any of
+    /// them runs once.
+    pub fn poll(&mut self, state: &RwLock<SequencerState>) -> Option<ScriptRun> {
+        let guard = state.read();
+        let bars = guard.run_bars;
+        // Playback restarted from the top
+        if bars < self.bar {
+            self.bar = 0;
+        }
+        if !guard.playing || bars == self.bar {
+            return None;
+        }
+        let last = std::mem::replace(&mut self.bar, bars);
+        let due: Vec<Script> = guard
+            .scripts
+            .iter()
+            .filter(|s| {
+                let every = s.every.max(1) as usize;
+                s.enabled && bars / every > last / every
+            })
+            .cloned()
+            .collect();
+        if due.is_empty() {
+            return None;
+        }
+        let snapshot = guard.clone();
+        drop(guard);
+        Some(self.run(&due, snapshot))
+    }
+
+    /// Run one script immediately (outside the bar schedule)
+    pub fn run_now(&mut self, script: &Script, state: &RwLock<SequencerState>) -> ScriptRun {
+        let snapshot = state.read().clone();
+        self.run(std::slice::from_ref(script), snapshot)
+    }
+
+    fn run(&mut self, scripts: &[Script], snapshot: SequencerState) -> ScriptRun {
+        let mut scope = Scope::new();
+        scope.push_constant("bar", self.bar as i64);
+        scope.push_constant("tracks", snapshot.tracks.len() as i64);
+        scope.push_constant("steps", snapshot.pattern.length as i64);
+        scope.push_constant("pattern", snapshot.current_pattern as i64);
+        scope.push_constant("bpm", snapshot.bpm as f64);
+        self.ctx.borrow_mut().state = snapshot;
+
+        let mut errors = Vec::new();
+        for script in scripts {
+            let status = self.status.entry(script.name.clone()).or_default();
+            status.runs += 1;
+            let result = match self.cache.get(&script.name) {
+                Some((source, ast)) if *source == script.source => Ok(ast.clone()),
+                _ => self.engine.compile(&script.source).map_err(|e| e.to_string()),
+            };
+            let result = result.and_then(|ast| {
+                self.cache
+                    .insert(script.name.clone(), (script.source.clone(), ast.clone()));
+                self.engine
+                    .run_ast_with_scope(&mut scope.clone(), &ast)
+                    .map_err(|e| e.to_string())
+            });
+            let mut ctx = self.ctx.borrow_mut();
+            if let Some(line) = ctx.output.drain(..).last() {
+                status.output = Some(line);
+            }
+            match result {
+                Ok(()) => status.error = None,
+                Err(e) => {
+                    // Keep changes made before the error; they were valid calls.
+                    // Only report errors that are new, not the same one every bar.
+                    if status.error.as_ref() != Some(&e) {
+                        errors.push(format!("{}: {}", script.name, e));
+                    }
+                    status.error = Some(e);
+                }
+            }
+        }
+        ScriptRun {
+            commands: std::mem::take(&mut self.ctx.borrow_mut().commands),
+            errors,
+        }
+    }
+}
+
+impl Default for ScriptHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Register the script API. Getters read the state the run started from,
+/// including the script's own earlier changes (except get_fx).
+fn register_api(engine: &mut Engine, ctx: &Rc<RefCell<ScriptContext>>) {
+    let c = ctx.clone();
+    engine.on_print(move |text| c.borrow_mut().output.push(text.to_string()));
+    let c = ctx.clone();
+    engine.on_debug(move |text, _, _| c.borrow_mut().output.push(text.to_string()));
+
+    // Steps (current pattern and variation)
+    let c = ctx.clone();
+    engine.register_fn("get_step", move |track: i64, step: i64| -> ScriptResult<bool> {
+        let ctx = c.borrow();
+        let (t, s) = ctx.step(track, step)?;
+        Ok(ctx.state.pattern.get_var(t, s, ctx.state.current_variation))
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_step", move |track: i64, step: i64, on: bool| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let (t, s) = ctx.step(track, step)?;
+        let variation = ctx.state.current_variation;
+        if ctx.state.pattern.get_var(t, s, variation) != on {
+            ctx.state.pattern.toggle_var(t, s, variation);
+            ctx.commands.push(Command::ToggleStep { track: t, step: s });
+        }
+        Ok(())
+    });
+    let c = ctx.clone();
+    engine.register_fn("clear_track", move |track: i64| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let t = ctx.track(track)?;
+        let variation = ctx.state.current_variation;
+        ctx.state.pattern.clear_track_var(t, variation);
+        ctx.commands.push(Command::ClearTrack(t));
+        Ok(())
+    });
+    let c = ctx.clone();
+    engine.register_fn("get_note", move |track: i64, step: i64| -> ScriptResult<i64> {
+        let ctx = c.borrow();
+        let (t, s) = ctx.step(track, step)?;
+        Ok(ctx.state.pattern.get_step_var(t, s, ctx.state.current_variation).note as i64)
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_note", move |track: i64, step: i64, note: i64| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let (t, s) = ctx.step(track, step)?;
+        let note = note.clamp(0, 127) as u8;
+        let variation = ctx.state.current_variation;
+        ctx.state.pattern.set_note_var(t, s, note, variation);
+        ctx.commands.push(Command::SetStepNote { track: t, step: s, note });
+        Ok(())
+    });
+    let c = ctx.clone();
+    engine.register_fn("get_velocity", move |track: i64, step: i64| -> ScriptResult<i64> {
+        let ctx = c.borrow();
+        let (t, s) = ctx.step(track, step)?;
+        Ok(ctx.state.pattern.get_step_var(t, s, ctx.state.current_variation).velocity as i64)
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_velocity", move |track: i64, step: i64, velocity: i64| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let (t, s) = ctx.step(track, step)?;
+        let velocity = velocity.clamp(0, 127) as u8;
+        let variation = ctx.state.current_variation;
+        ctx.state.pattern.set_velocity_var(t, s, velocity, variation);
+        ctx.commands.push(Command::SetStepVelocity { track: t, step: s, velocity });
+        Ok(())
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_probability", move |track: i64, step: i64, probability: i64| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let (t, s) = ctx.step(track, step)?;
+        let probability = probability.clamp(0, 100) as u8;
+        let variation = ctx.state.current_variation;
+        ctx.state.pattern.set_probability_var(t, s, probability, variation);
+        ctx.commands.push(Command::SetStepProbability { track: t, step: s, probability });
+        Ok(())
+    });
+
+    // Synth params
+    let c = ctx.clone();
+    engine.register_fn("get_param", move |track: i64, key: &str| -> ScriptResult<f64> {
+        let ctx = c.borrow();
+        let t = ctx.track(track)?;
+        ctx.state.tracks[t]
+            .params_snapshot
+            .get(key)
+            .and_then(|v| v.as_f64())
+            .ok_or_else(|| format!("Track {} has no param '{}'", t, key).into())
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_param", move |track: i64, key: &str, value: f64| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let t = ctx.track(track)?;
+        if let Some(params) = ctx.state.tracks[t].params_snapshot.as_object_mut() {
+            if params.contains_key(key) {
+                params.insert(key.to_string(), Value::from(value));
+            }
+        }
+        ctx.commands.push(Command::SetTrackParam { track: t, key: key.to_string(), value: value as f32 });
+        Ok(())
+    });
+
+    // Mixer
+    let c = ctx.clone();
+    engine.register_fn("get_volume", move |track: i64| -> ScriptResult<f64> {
+        let ctx = c.borrow();
+        let t = ctx.track(track)?;
+        Ok(ctx.state.tracks[t].volume as f64)
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_volume", move |track: i64, volume: f64| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let t = ctx.track(track)?;
+        let volume = (volume as f32).clamp(0.0, 1.0);
+        ctx.state.tracks[t].volume = volume;
+        ctx.commands.push(Command::SetTrackVolume { track: t, volume });
+        Ok(())
+    });
+    let c = ctx.clone();
+    engine.register_fn("get_pan", move |track: i64| -> ScriptResult<f64> {
+        let ctx = c.borrow();
+        let t = ctx.track(track)?;
+        Ok(ctx.state.tracks[t].pan as f64)
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_pan", move |track: i64, pan: f64| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let t = ctx.track(track)?;
+        let pan = (pan as f32).clamp(-1.0, 1.0);
+        ctx.state.tracks[t].pan = pan;
+        ctx.commands.push(Command::SetTrackPan { track: t, pan });
+        Ok(())
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_mute", move |track: i64, mute: bool| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let t = ctx.track(track)?;
+        if ctx.state.tracks[t].mute != mute {
+            ctx.state.tracks[t].mute = mute;
+            ctx.commands.push(Command::ToggleMute(t));
+        }
+        Ok(())
+    });
+
+    // Track FX
+    let c = ctx.clone();
+    engine.register_fn("get_fx", move |track: i64, key: &str| -> ScriptResult<f64> {
+        let ctx = c.borrow();
+        let t = ctx.track(track)?;
+        let param = FxParamId::from_key(key).ok_or_else(|| format!("Unknown FX param '{}'", key))?;
+        Ok(crate::ui::fx::get_fx_param_value(&ctx.state, t, param) as f64)
+    });
+    let c = ctx.clone();
+    engine.register_fn("set_fx", move |track: i64, key: &str, value: f64| -> ScriptResult<()> {
+        let mut ctx = c.borrow_mut();
+        let t = ctx.track(track)?;
+        let param = FxParamId::from_key(key).ok_or_else(|| format!("Unknown FX param '{}'", key))?;
+        let (min, max, _) = param.range();
+        let value = (value as f32).clamp(min, max);
+        ctx.commands.push(Command::SetFxParam { track: t, param, value });
+        Ok(())
+    });
+
+    // Transport
+    let c = ctx.clone();
+    engine.register_fn("set_bpm", move |bpm: f64| {
+        let bpm = (bpm as f32).clamp(60.0, 200.0);
+        let mut ctx = c.borrow_mut();
+        ctx.state.bpm = bpm;
+        ctx.commands.push(Command::SetBpm(bpm));
+    });
+
+    // Randomness
+    let c = ctx.clone();
+    engine.register_fn("rand", move || -> f64 {
+        (c.borrow_mut().next_random() % 1_000_000) as f64 / 1_000_000.0
+    });
+    let c = ctx.clone();
+    engine.register_fn("rand_int", move |lo: i64, hi: i64| -> i64 {
+        if hi <= lo {
+            return lo;
+        }
+        lo + (c.borrow_mut().next_random() as i64) % (hi - lo + 1)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs so far of the script named `name`
+    fn runs(host: &ScriptHost, name: &str) -> u64 {
+        host.status.get(name).map_or(0, |s| s.runs)
+    }
+
+    #[test]
+    fn runs_scripts_once_per_due_bar_from_the_bar_count() {
+        let mut every_bar = Script::new("every", "let x = 1;");
+        every_bar.every = 1;
+        let mut every_four = Script::new("four", "let x = 1;");
+        every_four.every = 4;
+        let mut state = SequencerState::new();
+        state.scripts = vec![every_bar, every_four];
+        state.playing = true;
+        let state = RwLock::new(state);
+        let mut host = ScriptHost::new();
+
+        // Nothing until a bar ends, and one run per poll however many ended
+        assert!(host.poll(&state).is_none());
+        state.write().run_bars = 3;
+        assert!(host.poll(&state).is_some());
+        assert!(host.poll(&state).is_none());
+        assert_eq!((runs(&host, "every"), runs(&host, "four")), (1, 0));
+
+        // Bar 4 was skipped over between polls but still counts
+        state.write().run_bars = 5;
+        host.poll(&state);
+        assert_eq!((runs(&host, "every"), runs(&host, "four")), (2, 1));
+
+        // Stop resets the engine's count; the next bar is bar 1 again
+        state.write().run_bars = 0;
+        host.poll(&state);
+        state.write().run_bars = 1;
+        host.poll(&state);
+        assert_eq!((runs(&host, "every"), runs(&host, "four")), (3, 1));
+    }
+}
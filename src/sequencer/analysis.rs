@@ -0,0 +1,39 @@
+use super::pattern::{triplet_steps, Pattern, Variation};
+
+/// Full-velocity hits on one 16th that count as a crowded beat (heat 1.0)
+pub const CROWDED_HITS: f32 = 4.0;
+
+/// What lands on one 16th step across all tracks
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StepDensity {
+    /// Active steps on it (triplet steps count on the 16th they start in)
+    pub hits: usize,
+    /// Hits weighted by velocity and probability
+    pub energy: f32,
+    /// Energy relative to CROWDED_HITS, 0.0 (empty) to 1.0 (crowded)
+    pub heat: f32,
+}
+
+/// Density of each 16th step of a pattern variation (one per step of its
+/// length). `triplets` marks the tracks on the triplet grid.
+pub fn step_density(pattern: &Pattern, variation: Variation, triplets: &[bool]) -> Vec<StepDensity> {
+    let length = pattern.length;
+    let mut density = vec![StepDensity::default(); length];
+    for track in 0..pattern.num_tracks() {
+        let triplet = triplets.get(track).copied().unwrap_or(false);
+        let steps = if triplet { triplet_steps(length) } else { length };
+        for step in 0..steps {
+            let data = pattern.get_step_var(track, step, variation);
+            if !data.active {
+                continue;
+            }
+            let slot = &mut density[step * length / steps];
+            slot.hits += 1;
+            slot.energy += data.velocity as f32 / 127.0 * data.probability as f32 / 100.0;
+        }
+    }
+    for slot in &mut density {
+        slot.heat = (slot.energy / CROWDED_HITS).min(1.0);
+    }
+    density
+}
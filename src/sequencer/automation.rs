@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fx::smooth::SMOOTHING_MS;
+
+/// Mixer change an arrangement entry makes to one track when it starts
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrackAutomation {
+    pub track: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mute: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solo: Option<bool>,
+    /// Volume (0-1) to reach
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f32>,
+    /// Ramp the volume over the whole entry instead of switching at its start
+    #[serde(default)]
+    pub fade: bool,
+}
+
+impl TrackAutomation {
+    pub fn new(track: usize) -> Self {
+        Self {
+            track,
+            mute: None,
+            solo: None,
+            volume: None,
+            fade: false,
+        }
+    }
+
+    /// Whether this changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.mute.is_none() && self.solo.is_none() && self.volume.is_none()
+    }
+
+    /// Short form for the Song view, e.g. "2:M" or "3:v50~"
+    pub fn summary(&self) -> String {
+        let mut s = format!("{}:", self.track + 1);
+        match self.mute {
+            Some(true) => s.push('M'),
+            Some(false) => s.push('U'),
+            None => {}
+        }
+        match self.solo {
+            Some(true) => s.push('S'),
+            Some(false) => s.push('s'),
+            None => {}
+        }
+        if let Some(volume) = self.volume {
+            s.push_str(&format!("v{:.0}", volume * 100.0));
+            if self.fade {
+                s.push('~');
+            }
+        }
+        s
+    }
+}
+
+/// Apply an entry's automation at its first step. Volume changes start a
+/// fade: over `entry_samples` when faded, otherwise a click-free
+/// SMOOTHING_MS ramp.
+pub fn apply_automation(
+    automation: &[TrackAutomation],
+    entry_samples: usize,
+    sample_rate: f32,
+    mutes: &mut [bool],
+    solos: &mut [bool],
+    fades: &mut [VolumeFade],
+    volume_of: impl Fn(usize) -> f32,
+) {
+    for a in automation {
+        let t = a.track;
+        if t >= mutes.len() || t >= solos.len() || t >= fades.len() {
+            continue;
+        }
+        if let Some(mute) = a.mute {
+            mutes[t] = mute;
+        }
+        if let Some(solo) = a.solo {
+            solos[t] = solo;
+        }
+        if let Some(volume) = a.volume {
+            let samples = if a.fade {
+                entry_samples
+            } else {
+                (SMOOTHING_MS * 0.001 * sample_rate) as usize
+            };
+            fades[t] = VolumeFade::new(volume_of(t), volume.clamp(0.0, 1.0), samples);
+        }
+    }
+}
+
+/// Linear volume ramp driven once per sample
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VolumeFade {
+    value: f32,
+    step: f32,
+    remaining: usize,
+}
+
+impl VolumeFade {
+    pub fn new(from: f32, to: f32, samples: usize) -> Self {
+        let samples = samples.max(1);
+        Self {
+            value: from,
+            step: (to - from) / samples as f32,
+            remaining: samples,
+        }
+    }
+
+    /// Whether the ramp still has samples to go
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Advance one sample; None once the ramp has finished
+    pub fn next(&mut self) -> Option<f32> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.value += self.step;
+        Some(self.value)
+    }
+}
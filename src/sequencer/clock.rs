@@ -1,4 +1,44 @@
-use super::pattern::STEPS;
+use super::pattern::{MAX_STEPS, STEPS, TRIPLET_STEPS};
+use crate::midi::clock::{PULSES_PER_BEAT, PULSES_PER_STEP};
+
+/// A gap in external clock this long restarts the tempo measurement
+const PULSE_TIMEOUT_SECS: f32 = 1.0;
+
+/// Tempo range the clock accepts
+pub const MIN_BPM: f32 = 60.0;
+pub const MAX_BPM: f32 = 200.0;
+
+/// Swing amount at which off-beat 16ths land half a step late (dotted feel)
+pub const MAX_SWING: u8 = 100;
+
+/// Which of a track's steps plays during 16th step `step`, and how far into
+/// that 16th (0..1) it falls. Triplet tracks divide the bar into
+/// TRIPLET_STEPS, so some 16ths have no triplet step and most are offset.
+/// Longer patterns continue the triplet grid into the next bars.
+pub fn track_step_at(step: usize, triplet: bool) -> Option<(usize, f32)> {
+    if !triplet {
+        return Some((step, 0.0));
+    }
+    // Triplet step i starts at 16th position i * STEPS / TRIPLET_STEPS
+    let i = (step * TRIPLET_STEPS).div_ceil(STEPS);
+    if i * STEPS < (step + 1) * TRIPLET_STEPS {
+        let offset = (i * STEPS - step * TRIPLET_STEPS) as f32 / TRIPLET_STEPS as f32;
+        Some((i, offset))
+    } else {
+        None
+    }
+}
+
+/// Like track_step_at, for a track looping every `loop_length` of its own
+/// steps (None = with the pattern). Looping tracks count from `run_step`,
+/// the 16ths since playback started, so they drift against the bar
+/// instead of restarting with it.
+pub fn looped_step_at(step: usize, run_step: usize, triplet: bool, loop_length: Option<usize>) -> Option<(usize, f32)> {
+    match loop_length {
+        Some(length) => track_step_at(run_step, triplet).map(|(i, offset)| (i % length.max(1), offset)),
+        None => track_step_at(step, triplet),
+    }
+}
 
 /// BPM timing - generates step ticks on the audio thread
 pub struct Clock {
@@ -7,8 +47,32 @@ pub struct Clock {
     samples_per_step: f32,
     sample_counter: f32,
     current_step: usize,
+    /// Steps before the pattern wraps
+    length: usize,
     playing: bool,
     pattern_wrapped: bool,
+    /// Steps follow MIDI clock pulses instead of the sample counter
+    external: bool,
+    /// External: pulses into the current step, and steps waiting to fire
+    pulse_counter: u32,
+    due_steps: u32,
+    /// External: pulses and samples since the tempo window started
+    window_pulses: u32,
+    window_samples: f32,
+    /// Internal: next MIDI clock pulse within the step, and whether one is due
+    next_pulse: u32,
+    pulse_due: bool,
+    /// Fraction of a step the off-beat 16ths are delayed (every other step
+    /// since playback started, so odd pattern lengths keep the shuffle)
+    swing: f32,
+    /// An off-beat step reached the grid and waits out its swing delay
+    swing_due: bool,
+    /// Samples since the last step triggered
+    since_step: f32,
+    /// Steps triggered since playback started (not wrapped)
+    run_steps: usize,
+    /// Times a pattern's last step triggered since playback started
+    run_bars: usize,
 }
 
 impl Clock {
@@ -19,8 +83,21 @@ impl Clock {
             samples_per_step: 0.0,
             sample_counter: 0.0,
             current_step: 0,
+            length: STEPS,
             playing: false,
             pattern_wrapped: false,
+            external: false,
+            pulse_counter: 0,
+            due_steps: 0,
+            window_pulses: 0,
+            window_samples: 0.0,
+            next_pulse: PULSES_PER_STEP,
+            pulse_due: false,
+            swing: 0.0,
+            swing_due: false,
+            since_step: 0.0,
+            run_steps: 0,
+            run_bars: 0,
         };
         clock.recalculate_timing();
         clock
@@ -40,37 +117,192 @@ impl Clock {
     }
 
     pub fn set_bpm(&mut self, bpm: f32) {
-        self.bpm = bpm.clamp(60.0, 200.0);
+        self.bpm = bpm.clamp(MIN_BPM, MAX_BPM);
         self.recalculate_timing();
     }
 
+    /// Delay the off-beat 16ths by `amount` percent of half a step
+    /// (0 straight, about 66 a triplet shuffle, MAX_SWING dotted)
+    pub fn set_swing(&mut self, amount: u8) {
+        self.swing = amount.min(MAX_SWING) as f32 / 200.0;
+    }
+
+    /// Wrap after `length` steps (the playing pattern's length). A step
+    /// past the new end wraps to step 0 next.
+    pub fn set_length(&mut self, length: usize) {
+        self.length = length.clamp(1, MAX_STEPS);
+        if self.current_step >= self.length {
+            self.current_step = 0;
+            self.pattern_wrapped = true;
+        }
+    }
+
+    pub fn samples_per_step(&self) -> f32 {
+        self.samples_per_step
+    }
+
     pub fn current_step(&self) -> usize {
         self.current_step
     }
 
+    /// The step last triggered, counted from when playback started
+    /// instead of the pattern's start
+    pub fn run_step(&self) -> usize {
+        self.run_steps.saturating_sub(1)
+    }
+
+    /// Steps triggered since playback started
+    pub fn run_steps(&self) -> usize {
+        self.run_steps
+    }
+
+    /// Bars whose last step has triggered since playback started, whatever
+    /// the pattern lengths were
+    pub fn run_bars(&self) -> usize {
+        self.run_bars
+    }
+
     pub fn is_playing(&self) -> bool {
         self.playing
     }
 
+    /// Length in grid steps of the step `run_step` steps after playback
+    /// started: swing lengthens the on-beat 16ths and shortens the off-beats
+    pub fn step_length(&self, run_step: usize) -> f32 {
+        if run_step % 2 == 0 {
+            1.0 + self.swing
+        } else {
+            1.0 - self.swing
+        }
+    }
+
+    /// Step that last triggered and how far (0..1) playback is toward the next
+    pub fn position(&self) -> (usize, f32) {
+        let step = (self.current_step + self.length - 1) % self.length;
+        let length = self.samples_per_step * self.step_length(self.run_step());
+        let fraction = self.since_step / length.max(1.0);
+        (step, fraction.clamp(0.0, 1.0))
+    }
+
     /// Called once per sample. Returns Some(step) when a new step is triggered.
     pub fn tick(&mut self) -> Option<usize> {
+        if self.external {
+            self.window_samples += 1.0;
+        }
         if !self.playing {
             return None;
         }
 
         self.sample_counter += 1.0;
+        self.since_step += 1.0;
+        // A swung step fires once its delay has passed (or right away if
+        // the next external step is already due)
+        if self.swing_due
+            && (self.sample_counter >= self.swing * self.samples_per_step
+                || (self.external && self.due_steps > 0))
+        {
+            self.swing_due = false;
+            return Some(self.advance());
+        }
+        if self.external {
+            if self.due_steps == 0 {
+                return None;
+            }
+            self.due_steps -= 1;
+            self.sample_counter = 0.0;
+            return self.grid_step();
+        }
         if self.sample_counter >= self.samples_per_step {
             self.sample_counter -= self.samples_per_step;
-            let step = self.current_step;
-            self.current_step = (self.current_step + 1) % STEPS;
-            if self.current_step == 0 {
-                self.pattern_wrapped = true;
-            }
-            return Some(step);
+            self.next_pulse = 1;
+            self.pulse_due = true;
+            return self.grid_step();
+        }
+        // MIDI clock pulses between steps (master output)
+        if self.next_pulse < PULSES_PER_STEP
+            && self.sample_counter * PULSES_PER_STEP as f32 >= self.samples_per_step * self.next_pulse as f32
+        {
+            self.next_pulse += 1;
+            self.pulse_due = true;
         }
         None
     }
 
+    /// The grid reached the next step: trigger it, or hold it for its
+    /// swing delay if it's an off-beat 16th. MIDI clock pulses stay on the
+    /// grid either way.
+    fn grid_step(&mut self) -> Option<usize> {
+        // run_steps is the run position of the step about to trigger
+        if self.run_steps % 2 == 1 && self.swing > 0.0 {
+            self.swing_due = true;
+            return None;
+        }
+        Some(self.advance())
+    }
+
+    /// Move to the next step, returning the one to trigger
+    fn advance(&mut self) -> usize {
+        let step = self.current_step;
+        self.since_step = 0.0;
+        self.run_steps += 1;
+        if step == self.length - 1 {
+            self.run_bars += 1;
+        }
+        self.current_step = (self.current_step + 1) % self.length;
+        if self.current_step == 0 {
+            self.pattern_wrapped = true;
+        }
+        step
+    }
+
+    /// Follow external MIDI clock (true) or run from the sample counter
+    pub fn set_external(&mut self, external: bool) {
+        self.external = external;
+        self.pulse_counter = 0;
+        self.due_steps = 0;
+        self.window_pulses = 0;
+        self.window_samples = 0.0;
+    }
+
+    pub fn is_external(&self) -> bool {
+        self.external
+    }
+
+    /// An external MIDI clock pulse arrived: every PULSES_PER_STEP pulses
+    /// fire a step on the next tick, and each beat of pulses sets the tempo
+    pub fn midi_pulse(&mut self) {
+        if !self.external {
+            return;
+        }
+        if self.window_samples > PULSE_TIMEOUT_SECS * self.sample_rate {
+            self.window_pulses = 0;
+        }
+        if self.window_pulses == 0 {
+            self.window_samples = 0.0;
+        }
+        self.window_pulses += 1;
+        if self.window_pulses > PULSES_PER_BEAT {
+            self.set_bpm(60.0 * self.sample_rate / self.window_samples.max(1.0));
+            self.window_pulses = 1;
+            self.window_samples = 0.0;
+        }
+
+        if self.playing {
+            if self.pulse_counter == 0 {
+                self.due_steps += 1;
+            }
+            self.pulse_counter = (self.pulse_counter + 1) % PULSES_PER_STEP;
+        }
+    }
+
+    /// Whether a MIDI clock pulse is due since the last call (internal
+    /// clock only), and clears it
+    pub fn take_midi_pulse(&mut self) -> bool {
+        let due = self.pulse_due;
+        self.pulse_due = false;
+        due
+    }
+
     /// Returns true if the pattern wrapped since last call, and clears the flag.
     pub fn take_pattern_wrap(&mut self) -> bool {
         let wrapped = self.pattern_wrapped;
@@ -81,8 +313,11 @@ impl Clock {
     pub fn play(&mut self) {
         if !self.playing {
             self.playing = true;
-            // Trigger step 0 immediately when starting
+            // Trigger step 0 immediately when starting (external: on the
+            // first pulse)
             self.sample_counter = self.samples_per_step;
+            self.pulse_counter = 0;
+            self.due_steps = 0;
         }
     }
 
@@ -91,9 +326,69 @@ impl Clock {
         self.current_step = 0;
         self.sample_counter = 0.0;
         self.pattern_wrapped = false;
+        self.pulse_counter = 0;
+        self.due_steps = 0;
+        self.pulse_due = false;
+        self.swing_due = false;
+        self.since_step = 0.0;
+        self.run_steps = 0;
+        self.run_bars = 0;
     }
 
     pub fn pause(&mut self) {
         self.playing = false;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run the clock for `steps` steps (100 samples each at 400 Hz, 60 BPM)
+    fn play_steps(clock: &mut Clock, steps: usize) {
+        let mut played = 0;
+        while played < steps {
+            if clock.tick().is_some() {
+                played += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn counts_bars_at_their_last_step() {
+        let mut clock = Clock::new(400.0, 60.0);
+        clock.play();
+        play_steps(&mut clock, 15);
+        assert_eq!(clock.run_bars(), 0);
+        play_steps(&mut clock, 1);
+        assert_eq!(clock.run_bars(), 1);
+
+        // A 1-step pattern ends a bar on every step
+        clock.set_length(1);
+        play_steps(&mut clock, 5);
+        assert_eq!(clock.run_bars(), 6);
+
+        clock.set_length(3);
+        play_steps(&mut clock, 6);
+        assert_eq!(clock.run_bars(), 8);
+
+        clock.stop();
+        assert_eq!(clock.run_bars(), 0);
+    }
+
+    #[test]
+    fn swing_alternates_across_odd_pattern_lengths() {
+        let mut clock = Clock::new(400.0, 60.0);
+        clock.set_swing(MAX_SWING);
+        clock.set_length(3);
+        clock.play();
+        // Steps fire 100 samples apart on the grid, off-beats 50 late
+        let mut fired = Vec::new();
+        for sample in 0..700 {
+            if clock.tick().is_some() {
+                fired.push(sample);
+            }
+        }
+        assert_eq!(fired, vec![0, 149, 199, 349, 399, 549, 599]);
+    }
+}
@@ -1,7 +1,19 @@
+pub mod analysis;
+pub mod automation;
 pub mod clock;
+pub mod nudge;
 pub mod pattern;
+pub mod transpose;
 
-pub use clock::Clock;
+pub use analysis::step_density;
+pub use automation::{apply_automation, TrackAutomation, VolumeFade};
+pub use clock::{looped_step_at, Clock, MAX_BPM, MAX_SWING, MIN_BPM};
+pub use nudge::{Trigger, TriggerDelay, MAX_NUDGE_MS};
 pub use pattern::{
-    Arrangement, Pattern, PatternBank, PlaybackMode, Variation, DEFAULT_TRACKS, NUM_PATTERNS, STEPS,
+    pattern_label, random_seed, triplet_steps, Arrangement, ParamLock, Pattern, PatternBank, PlaybackMode, Variation,
+    DEFAULT_TRACKS, MAX_ARRANGEMENT_ENTRIES, MAX_FADE_OUT_BARS, MAX_LOCKS_PER_STEP, MAX_REPEATS, MAX_STEPS, MAX_TRACKS, NUM_BANKS, NUM_PATTERNS,
+    PATTERNS_PER_BANK, STEPS, TRIPLET_STEPS,
+};
+pub use transpose::{
+    chord_label, chord_shift, played_note, CHORD_DEGREES, MAX_TRACK_TRANSPOSE, MAX_TRANSPOSE,
 };
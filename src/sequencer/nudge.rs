@@ -0,0 +1,189 @@
+/// Maximum per-track timing nudge in milliseconds
+pub const MAX_NUDGE_MS: f32 = 50.0;
+
+/// A step trigger on its way to the synth
+#[derive(Clone, Copy, Debug)]
+pub struct Trigger {
+    pub note: u8,
+    pub velocity: u8,
+    /// The track's step that fired it, for its parameter locks
+    pub step: usize,
+    /// The next step is tied onto this note, so the synth holds it
+    pub hold: bool,
+    /// A note-off step: release the sounding note instead of playing one
+    pub off: bool,
+    /// Glide into this note from the sounding one (acid synth)
+    pub slide: bool,
+    /// Accented step (acid synth)
+    pub accent: bool,
+}
+
+impl Trigger {
+    /// A note-off step's trigger
+    pub fn note_off(note: u8, step: usize) -> Self {
+        Self { note, velocity: 0, step, hold: false, off: true, slide: false, accent: false }
+    }
+}
+
+/// Triggers one track can have waiting at once: a nudge plus a late step
+/// offset or note-off can reach past the next step, so several overlap
+const MAX_PENDING: usize = 8;
+
+/// Per-track trigger delay used to lay a track back behind the grid.
+/// Step triggers are held for the nudge time before reaching the synth.
+#[derive(Clone)]
+pub struct TriggerDelay {
+    delay_samples: usize,
+    /// Samples ticked so far (the clock due times are measured on)
+    now: u64,
+    /// Waiting triggers with the sample they are due on
+    pending: [Option<(u64, Trigger)>; MAX_PENDING],
+}
+
+impl TriggerDelay {
+    pub fn new() -> Self {
+        Self {
+            delay_samples: 0,
+            now: 0,
+            pending: [None; MAX_PENDING],
+        }
+    }
+
+    /// Set the nudge amount (clamped to 0..=MAX_NUDGE_MS)
+    pub fn set_nudge_ms(&mut self, ms: f32, sample_rate: f32) {
+        let ms = ms.clamp(0.0, MAX_NUDGE_MS);
+        self.delay_samples = (ms * 0.001 * sample_rate) as usize;
+    }
+
+    /// Schedule a trigger `extra_samples` after the grid (for off-grid steps
+    /// such as triplets) plus the nudge. With a delay of n samples it comes
+    /// out of `pop_due` n `tick`s later. Returns a trigger to fire right now: this one when
+    /// there is no delay, or the earliest waiting one if the queue is full.
+    pub fn schedule(&mut self, trigger: Trigger, extra_samples: usize) -> Option<Trigger> {
+        let delay = self.delay_samples + extra_samples;
+        if delay == 0 {
+            return Some(trigger);
+        }
+        let due = self.now + delay as u64;
+        if let Some(slot) = self.pending.iter_mut().find(|p| p.is_none()) {
+            *slot = Some((due, trigger));
+            return None;
+        }
+        let earliest = self.earliest()?;
+        let flushed = self.pending[earliest].replace((due, trigger));
+        flushed.map(|(_, t)| t)
+    }
+
+    /// Index of the waiting trigger due first
+    fn earliest(&self) -> Option<usize> {
+        self.pending
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.map(|(due, _)| (i, due)))
+            .min_by_key(|&(_, due)| due)
+            .map(|(i, _)| i)
+    }
+
+    /// Take the next trigger due on this sample, earliest first (call until
+    /// None, before `tick`)
+    pub fn pop_due(&mut self) -> Option<Trigger> {
+        let i = self.earliest()?;
+        match self.pending[i] {
+            Some((due, trigger)) if due <= self.now => {
+                self.pending[i] = None;
+                Some(trigger)
+            }
+            _ => None,
+        }
+    }
+
+    /// Advance by one sample
+    pub fn tick(&mut self) {
+        self.now += 1;
+    }
+
+    /// Drop any held trigger (on stop)
+    pub fn clear(&mut self) {
+        self.pending = [None; MAX_PENDING];
+    }
+}
+
+impl Default for TriggerDelay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trigger(step: usize) -> Trigger {
+        Trigger { note: 60, velocity: 100, step, hold: false, off: false, slide: false, accent: false }
+    }
+
+    /// Samples until each trigger pops, running `samples` samples
+    fn run(delay: &mut TriggerDelay, samples: usize) -> Vec<(usize, usize)> {
+        let mut fired = Vec::new();
+        for sample in 0..samples {
+            while let Some(t) = delay.pop_due() {
+                fired.push((sample, t.step));
+            }
+            delay.tick();
+        }
+        fired
+    }
+
+    #[test]
+    fn fires_exactly_after_the_delay() {
+        let mut delay = TriggerDelay::new();
+        assert!(delay.schedule(trigger(0), 5).is_none());
+        assert_eq!(run(&mut delay, 20), vec![(5, 0)]);
+    }
+
+    #[test]
+    fn no_delay_fires_at_once() {
+        let mut delay = TriggerDelay::new();
+        assert_eq!(delay.schedule(trigger(3), 0).map(|t| t.step), Some(3));
+    }
+
+    #[test]
+    fn nudge_converts_ms_to_samples() {
+        let mut delay = TriggerDelay::new();
+        delay.set_nudge_ms(10.0, 1000.0);
+        delay.schedule(trigger(0), 0);
+        assert_eq!(run(&mut delay, 20), vec![(10, 0)]);
+    }
+
+    #[test]
+    fn overlapping_triggers_keep_their_timing() {
+        let mut delay = TriggerDelay::new();
+        // A late step still waiting when the next two steps arrive
+        delay.schedule(trigger(0), 30);
+        let mut fired = run(&mut delay, 10);
+        delay.schedule(trigger(1), 5);
+        fired.extend(run(&mut delay, 10).into_iter().map(|(s, t)| (s + 10, t)));
+        delay.schedule(trigger(2), 1);
+        fired.extend(run(&mut delay, 20).into_iter().map(|(s, t)| (s + 20, t)));
+        assert_eq!(fired, vec![(15, 1), (21, 2), (30, 0)]);
+    }
+
+    #[test]
+    fn full_queue_flushes_the_earliest() {
+        let mut delay = TriggerDelay::new();
+        for step in 0..MAX_PENDING {
+            assert!(delay.schedule(trigger(step), 100 + step).is_none());
+        }
+        assert_eq!(delay.schedule(trigger(99), 50).map(|t| t.step), Some(0));
+        let fired: Vec<usize> = run(&mut delay, 200).into_iter().map(|(_, t)| t).collect();
+        assert_eq!(fired, vec![99, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn clear_drops_waiting_triggers() {
+        let mut delay = TriggerDelay::new();
+        delay.schedule(trigger(0), 3);
+        delay.clear();
+        assert!(run(&mut delay, 10).is_empty());
+    }
+}
@@ -1,9 +1,40 @@
-use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
 
+use anyhow::{bail, Result};
+use serde::de::{SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::automation::TrackAutomation;
+use super::transpose::CHORD_DEGREES;
+
+/// 16ths per bar, and the length of a new pattern
 pub const STEPS: usize = 16;
+/// Longest pattern, in 16th steps
+pub const MAX_STEPS: usize = 64;
+/// Steps per bar used by tracks in triplet mode (the rest stay silent)
+pub const TRIPLET_STEPS: usize = 12;
 pub const DEFAULT_TRACKS: usize = 4;
-pub const NUM_PATTERNS: usize = 16;
+pub const MAX_TRACKS: usize = 16;
+/// Pattern slots, shown in the UI as banks of PATTERNS_PER_BANK (A-D)
+pub const NUM_PATTERNS: usize = 64;
+pub const PATTERNS_PER_BANK: usize = 16;
+pub const NUM_BANKS: usize = NUM_PATTERNS / PATTERNS_PER_BANK;
+/// Highest repeat count for an arrangement entry
+pub const MAX_REPEATS: usize = 16;
 pub const MAX_ARRANGEMENT_ENTRIES: usize = 64;
+/// Longest master fade-out at the end of the song, in bars
+pub const MAX_FADE_OUT_BARS: usize = 16;
+
+/// Base note for chopped slices: (CHOP_BASE_NOTE + i) % STEPS == i, so step i plays slice i
+pub const CHOP_BASE_NOTE: u8 = 48;
+
+/// Steps a triplet track plays in a pattern of `length` 16ths
+pub fn triplet_steps(length: usize) -> usize {
+    (length * TRIPLET_STEPS).div_ceil(STEPS)
+}
 
 /// Default MIDI notes for the 4 built-in tracks
 pub const DEFAULT_NOTES: [u8; 4] = [
@@ -20,37 +51,86 @@ pub enum PlaybackMode {
 }
 
 /// Pattern variation (A or B)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum Variation {
     #[default]
     A,
     B,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+/// Bank letter and 1-based slot of a pattern index, e.g. 17 -> "B02"
+pub fn pattern_label(index: usize) -> String {
+    let bank = (b'A' + (index / PATTERNS_PER_BANK) as u8) as char;
+    format!("{}{:02}", bank, index % PATTERNS_PER_BANK + 1)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ArrangementEntry {
-    pub pattern: usize, // 0-15
-    pub repeats: usize, // 1-16
+    pub pattern: usize, // 0..NUM_PATTERNS
+    pub repeats: usize, // 1..=MAX_REPEATS
+    /// Mixer changes applied when the entry starts (one per track)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub automation: Vec<TrackAutomation>,
+    /// Chord degree (index into CHORD_DEGREES) that transposes melodic
+    /// tracks while the entry plays
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chord: Option<u8>,
+}
+
+impl Clone for ArrangementEntry {
+    fn clone(&self) -> Self {
+        Self {
+            automation: self.automation.clone(),
+            ..*self
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.pattern = source.pattern;
+        self.repeats = source.repeats;
+        self.automation.clone_from(&source.automation);
+        self.chord = source.chord;
+    }
 }
 
 impl ArrangementEntry {
     pub fn new(pattern: usize, repeats: usize) -> Self {
         Self {
             pattern: pattern.min(NUM_PATTERNS - 1),
-            repeats: repeats.clamp(1, 16),
+            repeats: repeats.clamp(1, MAX_REPEATS),
+            automation: Vec::new(),
+            chord: None,
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Arrangement {
     pub entries: Vec<ArrangementEntry>,
+    /// Bars the master fades out over at the end of the song (0 = off)
+    #[serde(default)]
+    pub fade_out_bars: usize,
+}
+
+impl Clone for Arrangement {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            fade_out_bars: self.fade_out_bars,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.entries.clone_from(&source.entries);
+        self.fade_out_bars = source.fade_out_bars;
+    }
 }
 
 impl Arrangement {
     pub fn new() -> Self {
         Self {
             entries: Vec::with_capacity(MAX_ARRANGEMENT_ENTRIES),
+            fade_out_bars: 0,
         }
     }
 
@@ -81,15 +161,106 @@ impl Arrangement {
         }
     }
 
+    /// Change an entry's pattern and repeats, keeping its automation and chord
     pub fn set_entry(&mut self, position: usize, pattern: usize, repeats: usize) {
-        if position < self.entries.len() {
-            self.entries[position] = ArrangementEntry::new(pattern, repeats);
+        if let Some(entry) = self.entries.get_mut(position) {
+            let automation = std::mem::take(&mut entry.automation);
+            let chord = entry.chord;
+            *entry = ArrangementEntry { automation, chord, ..ArrangementEntry::new(pattern, repeats) };
+        }
+    }
+
+    /// Set or clear an entry's chord degree (out-of-range degrees clear it)
+    pub fn set_chord(&mut self, position: usize, chord: Option<u8>) {
+        if let Some(entry) = self.entries.get_mut(position) {
+            entry.chord = chord.filter(|&d| (d as usize) < CHORD_DEGREES.len());
+        }
+    }
+
+    /// Replace an entry's automation (empty items are dropped, one per track)
+    pub fn set_automation(&mut self, position: usize, mut automation: Vec<TrackAutomation>) {
+        if let Some(entry) = self.entries.get_mut(position) {
+            automation.retain(|a| !a.is_empty());
+            automation.sort_by_key(|a| a.track);
+            automation.dedup_by_key(|a| a.track);
+            entry.automation = automation;
+        }
+    }
+
+    /// Drop automation for a removed track and renumber the tracks after it
+    pub fn remove_track(&mut self, track: usize) {
+        for entry in &mut self.entries {
+            entry.automation.retain(|a| a.track != track);
+            for a in &mut entry.automation {
+                if a.track > track {
+                    a.track -= 1;
+                }
+            }
         }
     }
 
     pub fn clear(&mut self) {
         self.entries.clear();
     }
+
+    /// Length of the whole song in bars (pattern loops, whatever their length)
+    pub fn total_bars(&self) -> usize {
+        self.entries.iter().map(|e| e.repeats).sum()
+    }
+
+    /// Master gain at a song position in bars: 1 until the last
+    /// `fade_out_bars`, then a linear ramp down to 0 at the song's end
+    pub fn fade_out_gain(&self, position_bars: f32) -> f32 {
+        if self.fade_out_bars == 0 {
+            return 1.0;
+        }
+        let total = self.total_bars() as f32;
+        let fade = (self.fade_out_bars as f32).min(total);
+        if fade <= 0.0 {
+            return 1.0;
+        }
+        ((total - position_bars) / fade).clamp(0.0, 1.0)
+    }
+
+    /// Check length limit and entry ranges
+    pub fn validate(&self) -> Result<()> {
+        if self.entries.len() > MAX_ARRANGEMENT_ENTRIES {
+            bail!(
+                "Arrangement has {} entries (max {})",
+                self.entries.len(),
+                MAX_ARRANGEMENT_ENTRIES
+            );
+        }
+        if self.fade_out_bars > MAX_FADE_OUT_BARS {
+            bail!(
+                "Arrangement fade-out is {} bars (max {})",
+                self.fade_out_bars,
+                MAX_FADE_OUT_BARS
+            );
+        }
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.pattern >= NUM_PATTERNS {
+                bail!("Arrangement entry {} references pattern {}", i, entry.pattern);
+            }
+            if !(1..=MAX_REPEATS).contains(&entry.repeats) {
+                bail!(
+                    "Arrangement entry {} has {} repeats (1-{})",
+                    i,
+                    entry.repeats,
+                    MAX_REPEATS
+                );
+            }
+            if entry.chord.is_some_and(|d| d as usize >= CHORD_DEGREES.len()) {
+                bail!("Arrangement entry {} has an unknown chord degree", i);
+            }
+            for a in &entry.automation {
+                if a.volume.is_some_and(|v| !(0.0..=1.0).contains(&v)) {
+                    bail!("Arrangement entry {} automates track {} volume out of range", i, a.track);
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for Arrangement {
@@ -98,9 +269,21 @@ impl Default for Arrangement {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PatternBank {
-    pub patterns: Vec<Pattern>, // always NUM_PATTERNS length
+    pub patterns: Vec<Pattern>, // always NUM_PATTERNS length (shorter in project files)
+}
+
+impl Clone for PatternBank {
+    fn clone(&self) -> Self {
+        Self {
+            patterns: self.patterns.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.patterns.clone_from(&source.patterns);
+    }
 }
 
 impl PatternBank {
@@ -122,22 +305,66 @@ impl PatternBank {
         &mut self.patterns[index.min(NUM_PATTERNS - 1)]
     }
 
+    /// The pattern in slot `src` and a mutable one in `dst`, to copy
+    /// between slots without a temporary (None for the same or a missing slot)
+    pub fn pair_mut(&mut self, src: usize, dst: usize) -> Option<(&Pattern, &mut Pattern)> {
+        if src == dst || src >= self.patterns.len() || dst >= self.patterns.len() {
+            return None;
+        }
+        if src < dst {
+            let (head, tail) = self.patterns.split_at_mut(dst);
+            Some((&head[src], &mut tail[0]))
+        } else {
+            let (head, tail) = self.patterns.split_at_mut(src);
+            Some((&tail[0], &mut head[dst]))
+        }
+    }
+
+    /// Pad with empty patterns up to NUM_PATTERNS (older projects had 16 slots)
+    pub fn fill_slots(&mut self, default_notes: &[u8]) {
+        while self.patterns.len() < NUM_PATTERNS {
+            self.patterns.push(Pattern::new_with_notes(default_notes));
+        }
+    }
+
+    /// Drop trailing empty patterns past the first bank and `keep`, so
+    /// project files stay small (fill_slots restores them on load)
+    pub fn trim_empty_slots(&mut self, keep: usize) {
+        while self.patterns.len() > PATTERNS_PER_BANK.max(keep + 1) {
+            let last = self.patterns.len() - 1;
+            if self.has_content(last) || !self.patterns[last].extensions.is_empty() {
+                break;
+            }
+            self.patterns.pop();
+        }
+    }
+
     /// Returns true if a pattern has any active steps (in either variation)
     pub fn has_content(&self, index: usize) -> bool {
         if index >= NUM_PATTERNS {
             return false;
         }
-        let pat = &self.patterns[index];
-        for variation in [Variation::A, Variation::B] {
-            for track in 0..pat.num_tracks() {
-                for step in 0..STEPS {
-                    if pat.get_var(track, step, variation) {
-                        return true;
-                    }
-                }
+        self.patterns[index].has_content()
+    }
+
+    /// Check the bank has NUM_PATTERNS patterns, each valid with `num_tracks` tracks
+    pub fn validate(&self, num_tracks: usize) -> Result<()> {
+        if self.patterns.len() != NUM_PATTERNS {
+            bail!(
+                "Pattern bank has {} patterns (expected {})",
+                self.patterns.len(),
+                NUM_PATTERNS
+            );
+        }
+        for (i, pat) in self.patterns.iter().enumerate() {
+            if pat.num_tracks() != num_tracks {
+                bail!("Pattern {} has {} tracks (expected {})", i, pat.num_tracks(), num_tracks);
+            }
+            if let Err(e) = pat.validate() {
+                bail!("Pattern {}: {}", i, e);
             }
         }
-        false
+        Ok(())
     }
 }
 
@@ -155,6 +382,18 @@ fn default_probability() -> u8 {
     100
 }
 
+fn default_length() -> usize {
+    STEPS
+}
+
+fn is_zero(value: &u8) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct StepData {
     pub active: bool,
@@ -163,6 +402,24 @@ pub struct StepData {
     pub velocity: u8, // 0-127, default 127
     #[serde(default = "default_probability")]
     pub probability: u8, // 0-100%, default 100
+    /// Micro-timing: trigger delay after the step, in percent of a step (0-99)
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: u8,
+    /// Tied onto the step before: when both are on, this step holds that
+    /// step's note on instead of playing its own
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub tie: bool,
+    /// Note-off step (only while the step is off): releases the track's
+    /// sounding note here, whatever its hold
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub off: bool,
+    /// Glide into this step's note from the one sounding, without
+    /// retriggering (acid synth)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub slide: bool,
+    /// Accented step: louder with a harder filter sweep (acid synth)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub accent: bool,
 }
 
 impl StepData {
@@ -172,6 +429,11 @@ impl StepData {
             note,
             velocity: 127,
             probability: 100,
+            offset: 0,
+            tie: false,
+            off: false,
+            slide: false,
+            accent: false,
         }
     }
 
@@ -181,6 +443,11 @@ impl StepData {
             note,
             velocity: 127,
             probability: 100,
+            offset: 0,
+            tie: false,
+            off: false,
+            slide: false,
+            accent: false,
         }
     }
 
@@ -190,18 +457,179 @@ impl StepData {
             note,
             velocity: velocity.min(127),
             probability: 100,
+            offset: 0,
+            tie: false,
+            off: false,
+            slide: false,
+            accent: false,
+        }
+    }
+}
+
+/// One track's steps in one variation: room for MAX_STEPS inline, of which
+/// the first `len` are in use. Copying a row never allocates, so patterns
+/// can be copied on the audio thread. Derefs to the steps in use; files
+/// store just those.
+#[derive(Clone, Copy)]
+pub struct StepRow {
+    steps: [StepData; MAX_STEPS],
+    len: usize,
+}
+
+impl StepRow {
+    /// A row of `len` (at most MAX_STEPS) copies of `step`
+    pub fn new(step: StepData, len: usize) -> Self {
+        Self {
+            steps: [step; MAX_STEPS],
+            len: len.min(MAX_STEPS),
+        }
+    }
+
+    /// Change the number of steps in use (at most MAX_STEPS); new steps are `step`
+    pub fn resize(&mut self, len: usize, step: StepData) {
+        let len = len.min(MAX_STEPS);
+        if len > self.len {
+            self.steps[self.len..len].fill(step);
         }
+        self.len = len;
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl Deref for StepRow {
+    type Target = [StepData];
+
+    fn deref(&self) -> &[StepData] {
+        &self.steps[..self.len]
+    }
+}
+
+impl DerefMut for StepRow {
+    fn deref_mut(&mut self) -> &mut [StepData] {
+        &mut self.steps[..self.len]
+    }
+}
+
+impl fmt::Debug for StepRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Serialize for StepRow {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len))?;
+        for step in self.iter() {
+            seq.serialize_element(step)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for StepRow {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RowVisitor;
+
+        impl<'de> Visitor<'de> for RowVisitor {
+            type Value = StepRow;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a list of at most {} steps", MAX_STEPS)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<StepRow, A::Error> {
+                let mut row = StepRow::new(StepData::off(60), 0);
+                while let Some(step) = seq.next_element()? {
+                    if row.len == MAX_STEPS {
+                        return Err(serde::de::Error::custom(format!("row has more than {} steps", MAX_STEPS)));
+                    }
+                    row.steps[row.len] = step;
+                    row.len += 1;
+                }
+                Ok(row)
+            }
+        }
+
+        deserializer.deserialize_seq(RowVisitor)
+    }
+}
+
+/// Most parameter locks one step can carry
+pub const MAX_LOCKS_PER_STEP: usize = 8;
+
+/// Parameter lock: a synth parameter value that only applies while one
+/// step plays. The track's own value comes back on its next trigger.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParamLock {
+    pub track: usize,
+    pub step: usize,
+    #[serde(default)]
+    pub variation: Variation,
+    pub key: String,
+    pub value: f32,
+}
+
+impl Clone for ParamLock {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            ..*self
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.track = source.track;
+        self.step = source.step;
+        self.variation = source.variation;
+        self.key.clone_from(&source.key);
+        self.value = source.value;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Pattern {
+    /// Steps per loop (1..=MAX_STEPS); every row has this many
+    #[serde(default = "default_length")]
+    pub length: usize,
     /// steps_a[track][step] - variation A (dynamic number of tracks)
     #[serde(alias = "steps")]
-    pub steps_a: Vec<[StepData; STEPS]>,
+    pub steps_a: Vec<StepRow>,
     /// steps_b[track][step] - variation B (dynamic number of tracks)
     #[serde(default)]
-    pub steps_b: Vec<[StepData; STEPS]>,
+    pub steps_b: Vec<StepRow>,
+    /// Per-track loop length in the track's own steps, for polymeters
+    /// (0 or missing = loop with the pattern)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub track_lengths: Vec<usize>,
+    /// Parameter locks of all steps, in no particular order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub param_locks: Vec<ParamLock>,
+    /// Reserved for future features; unknown keys are kept on load/save
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+// Field by field so clone_from reuses the destination's buffers: the
+// audio thread copies patterns with it
+impl Clone for Pattern {
+    fn clone(&self) -> Self {
+        Self {
+            length: self.length,
+            steps_a: self.steps_a.clone(),
+            steps_b: self.steps_b.clone(),
+            track_lengths: self.track_lengths.clone(),
+            param_locks: self.param_locks.clone(),
+            extensions: self.extensions.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.length = source.length;
+        self.steps_a.clone_from(&source.steps_a);
+        self.steps_b.clone_from(&source.steps_b);
+        self.track_lengths.clone_from(&source.track_lengths);
+        self.param_locks.clone_from(&source.param_locks);
+        self.extensions.clone_from(&source.extensions);
+    }
 }
 
 impl Pattern {
@@ -218,10 +646,17 @@ impl Pattern {
             } else {
                 60 // C4 for any extra tracks
             };
-            steps_a.push([StepData::off(default_note); STEPS]);
-            steps_b.push([StepData::off(default_note); STEPS]);
+            steps_a.push(StepRow::new(StepData::off(default_note), STEPS));
+            steps_b.push(StepRow::new(StepData::off(default_note), STEPS));
+        }
+        Self {
+            length: STEPS,
+            steps_a,
+            steps_b,
+            track_lengths: Vec::new(),
+            param_locks: Vec::new(),
+            extensions: serde_json::Map::new(),
         }
-        Self { steps_a, steps_b }
     }
 
     /// Create a pattern with specific default notes per track
@@ -229,10 +664,17 @@ impl Pattern {
         let mut steps_a = Vec::with_capacity(default_notes.len());
         let mut steps_b = Vec::with_capacity(default_notes.len());
         for &note in default_notes {
-            steps_a.push([StepData::off(note); STEPS]);
-            steps_b.push([StepData::off(note); STEPS]);
+            steps_a.push(StepRow::new(StepData::off(note), STEPS));
+            steps_b.push(StepRow::new(StepData::off(note), STEPS));
+        }
+        Self {
+            length: STEPS,
+            steps_a,
+            steps_b,
+            track_lengths: Vec::new(),
+            param_locks: Vec::new(),
+            extensions: serde_json::Map::new(),
         }
-        Self { steps_a, steps_b }
     }
 
     /// Ensure steps_b has the same track count as steps_a
@@ -245,12 +687,14 @@ impl Pattern {
             } else {
                 60
             };
-            self.steps_b.push([StepData::off(default_note); STEPS]);
+            // Sized like the A row rather than from `length`, which is unchecked here
+            let steps = self.steps_a[track].len();
+            self.steps_b.push(StepRow::new(StepData::off(default_note), steps));
         }
     }
 
     /// Get steps for a specific variation
-    pub fn steps(&self, variation: Variation) -> &Vec<[StepData; STEPS]> {
+    pub fn steps(&self, variation: Variation) -> &Vec<StepRow> {
         match variation {
             Variation::A => &self.steps_a,
             Variation::B => &self.steps_b,
@@ -258,13 +702,20 @@ impl Pattern {
     }
 
     /// Get mutable steps for a specific variation
-    pub fn steps_mut(&mut self, variation: Variation) -> &mut Vec<[StepData; STEPS]> {
+    pub fn steps_mut(&mut self, variation: Variation) -> &mut Vec<StepRow> {
         match variation {
             Variation::A => &mut self.steps_a,
             Variation::B => &mut self.steps_b,
         }
     }
 
+    /// Whether any step is active in either variation
+    pub fn has_content(&self) -> bool {
+        [Variation::A, Variation::B].into_iter().any(|variation| {
+            self.steps(variation).iter().any(|row| row.iter().any(|sd| sd.active))
+        })
+    }
+
     /// Number of tracks in this pattern
     pub fn num_tracks(&self) -> usize {
         self.steps_a.len()
@@ -272,18 +723,145 @@ impl Pattern {
 
     /// Add a new track with the given default note
     pub fn add_track(&mut self, default_note: u8) {
-        self.steps_a.push([StepData::off(default_note); STEPS]);
-        self.steps_b.push([StepData::off(default_note); STEPS]);
+        self.steps_a.push(StepRow::new(StepData::off(default_note), self.length));
+        self.steps_b.push(StepRow::new(StepData::off(default_note), self.length));
+        if !self.track_lengths.is_empty() {
+            self.track_lengths.push(0);
+        }
+    }
+
+    /// A track's own loop length as set (0 = loops with the pattern)
+    pub fn track_length(&self, track: usize) -> usize {
+        self.track_lengths.get(track).copied().unwrap_or(0)
+    }
+
+    /// Set a track's loop length in its own steps (0 = loop with the
+    /// pattern). Longer than the pattern has steps also loops with it.
+    pub fn set_track_length(&mut self, track: usize, length: usize) {
+        if track >= self.num_tracks() {
+            return;
+        }
+        if self.track_lengths.len() < self.num_tracks() {
+            self.track_lengths.resize(self.num_tracks(), 0);
+        }
+        self.track_lengths[track] = length.min(MAX_STEPS);
+        if self.track_lengths.iter().all(|&l| l == 0) {
+            self.track_lengths.clear();
+        }
+    }
+
+    /// Steps a track loops over when shorter than its row (`triplet` for
+    /// tracks on the triplet grid), None when it loops with the pattern
+    pub fn track_loop(&self, track: usize, triplet: bool) -> Option<usize> {
+        let steps = if triplet { triplet_steps(self.length) } else { self.length };
+        let length = self.track_length(track);
+        (length > 0 && length < steps).then_some(length)
+    }
+
+    /// Steps a track cycles through: its own loop length, else the whole pattern
+    pub fn track_steps(&self, track: usize, triplet: bool) -> usize {
+        self.track_loop(track, triplet)
+            .unwrap_or(if triplet { triplet_steps(self.length) } else { self.length })
+    }
+
+    /// Change the number of steps (clamped to 1..=MAX_STEPS). Shortening
+    /// drops the steps past the end; new steps are off and keep the row's
+    /// first note.
+    pub fn set_length(&mut self, length: usize) {
+        let length = length.clamp(1, MAX_STEPS);
+        self.length = length;
+        for row in self.steps_a.iter_mut().chain(self.steps_b.iter_mut()) {
+            let note = row.first().map_or(60, |sd| sd.note);
+            row.resize(length, StepData::off(note));
+        }
+        self.param_locks.retain(|lock| lock.step < length);
+    }
+
+    /// Step data of one step, if the track and step exist
+    fn step_mut(&mut self, track: usize, step: usize, variation: Variation) -> Option<&mut StepData> {
+        self.steps_mut(variation).get_mut(track)?.get_mut(step)
     }
 
     /// Remove the last track (if more than 1 remain)
     pub fn remove_track(&mut self, index: usize) {
+        let removed = self.steps_a.len() > 1 && index < self.steps_a.len();
         if self.steps_a.len() > 1 && index < self.steps_a.len() {
             self.steps_a.remove(index);
         }
         if self.steps_b.len() > 1 && index < self.steps_b.len() {
             self.steps_b.remove(index);
         }
+        if index < self.track_lengths.len() && self.track_lengths.len() > self.steps_a.len() {
+            self.track_lengths.remove(index);
+        }
+        if removed {
+            self.param_locks.retain(|lock| lock.track != index);
+            for lock in self.param_locks.iter_mut().filter(|lock| lock.track > index) {
+                lock.track -= 1;
+            }
+        }
+    }
+
+    /// Parameter locks of one step
+    pub fn param_locks(&self, track: usize, step: usize, variation: Variation) -> impl Iterator<Item = &ParamLock> {
+        self.param_locks
+            .iter()
+            .filter(move |lock| lock.track == track && lock.step == step && lock.variation == variation)
+    }
+
+    /// Value a step locks `key` to, if it does
+    pub fn param_lock(&self, track: usize, step: usize, variation: Variation, key: &str) -> Option<f32> {
+        self.param_locks(track, step, variation).find(|lock| lock.key == key).map(|lock| lock.value)
+    }
+
+    pub fn has_param_locks(&self, track: usize, step: usize, variation: Variation) -> bool {
+        self.param_locks(track, step, variation).next().is_some()
+    }
+
+    /// Lock `key` to `value` on a step, or unlock it with None. Returns
+    /// false if the step doesn't exist or already has MAX_LOCKS_PER_STEP
+    /// other locks.
+    pub fn set_param_lock(
+        &mut self,
+        track: usize,
+        step: usize,
+        variation: Variation,
+        key: &str,
+        value: Option<f32>,
+    ) -> bool {
+        if track >= self.num_tracks() || step >= self.length {
+            return false;
+        }
+        let at = self
+            .param_locks
+            .iter()
+            .position(|l| l.track == track && l.step == step && l.variation == variation && l.key == key);
+        match (at, value) {
+            (Some(at), Some(value)) => self.param_locks[at].value = value,
+            (Some(at), None) => {
+                self.param_locks.remove(at);
+            }
+            (None, Some(value)) => {
+                if self.param_locks(track, step, variation).count() >= MAX_LOCKS_PER_STEP {
+                    return false;
+                }
+                self.param_locks.push(ParamLock {
+                    track,
+                    step,
+                    variation,
+                    key: key.to_string(),
+                    value,
+                });
+            }
+            (None, None) => {}
+        }
+        true
+    }
+
+    /// Remove all parameter locks of a step
+    pub fn clear_param_locks(&mut self, track: usize, step: usize, variation: Variation) {
+        self.param_locks
+            .retain(|l| !(l.track == track && l.step == step && l.variation == variation));
     }
 
     /// Toggle step active state for variation A (default). When activating, uses the step's existing note.
@@ -293,12 +871,13 @@ impl Pattern {
 
     /// Toggle step active state for a specific variation
     pub fn toggle_var(&mut self, track: usize, step: usize, variation: Variation) -> bool {
-        let steps = self.steps_mut(variation);
-        if track < steps.len() && step < STEPS {
-            steps[track][step].active = !steps[track][step].active;
-            steps[track][step].active
-        } else {
-            false
+        match self.step_mut(track, step, variation) {
+            Some(sd) => {
+                sd.active = !sd.active;
+                sd.off &= !sd.active;
+                sd.active
+            }
+            None => false,
         }
     }
 
@@ -307,9 +886,9 @@ impl Pattern {
     }
 
     pub fn set_var(&mut self, track: usize, step: usize, value: bool, variation: Variation) {
-        let steps = self.steps_mut(variation);
-        if track < steps.len() && step < STEPS {
-            steps[track][step].active = value;
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            sd.active = value;
+            sd.off &= !value;
         }
     }
 
@@ -320,12 +899,7 @@ impl Pattern {
 
     /// Returns whether a step is active for a specific variation
     pub fn get_var(&self, track: usize, step: usize, variation: Variation) -> bool {
-        let steps = self.steps(variation);
-        if track < steps.len() && step < STEPS {
-            steps[track][step].active
-        } else {
-            false
-        }
+        self.get_step_var(track, step, variation).active
     }
 
     /// Get full step data (active + note) for variation A
@@ -335,12 +909,11 @@ impl Pattern {
 
     /// Get full step data for a specific variation
     pub fn get_step_var(&self, track: usize, step: usize, variation: Variation) -> StepData {
-        let steps = self.steps(variation);
-        if track < steps.len() && step < STEPS {
-            steps[track][step]
-        } else {
-            StepData::off(60)
-        }
+        self.steps(variation)
+            .get(track)
+            .and_then(|row| row.get(step))
+            .copied()
+            .unwrap_or(StepData::off(60))
     }
 
     /// Set the MIDI note for a step (variation A)
@@ -350,9 +923,8 @@ impl Pattern {
 
     /// Set the MIDI note for a step for a specific variation
     pub fn set_note_var(&mut self, track: usize, step: usize, note: u8, variation: Variation) {
-        let steps = self.steps_mut(variation);
-        if track < steps.len() && step < STEPS {
-            steps[track][step].note = note.min(127);
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            sd.note = note.min(127);
         }
     }
 
@@ -363,9 +935,8 @@ impl Pattern {
 
     /// Set the velocity for a step for a specific variation
     pub fn set_velocity_var(&mut self, track: usize, step: usize, velocity: u8, variation: Variation) {
-        let steps = self.steps_mut(variation);
-        if track < steps.len() && step < STEPS {
-            steps[track][step].velocity = velocity.min(127);
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            sd.velocity = velocity.min(127);
         }
     }
 
@@ -376,10 +947,103 @@ impl Pattern {
 
     /// Set the probability for a step for a specific variation
     pub fn set_probability_var(&mut self, track: usize, step: usize, probability: u8, variation: Variation) {
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            sd.probability = probability.min(100);
+        }
+    }
+
+    /// Set whether a step is tied onto the one before it for a specific variation
+    pub fn set_tie_var(&mut self, track: usize, step: usize, tie: bool, variation: Variation) {
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            sd.tie = tie;
+        }
+    }
+
+    /// Set whether a step slides into its note for a specific variation
+    pub fn set_slide_var(&mut self, track: usize, step: usize, slide: bool, variation: Variation) {
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            sd.slide = slide;
+        }
+    }
+
+    /// Set whether a step is accented for a specific variation
+    pub fn set_accent_var(&mut self, track: usize, step: usize, accent: bool, variation: Variation) {
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            sd.accent = accent;
+        }
+    }
+
+    /// Make a step a note-off step (turning it off), or clear that for a
+    /// specific variation
+    pub fn set_note_off_var(&mut self, track: usize, step: usize, off: bool, variation: Variation) {
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            sd.off = off;
+            sd.active &= !off;
+        }
+    }
+
+    /// Whether a step carries on the note of the step before it (wrapping
+    /// at the track's `steps`) instead of playing: it's tied, and both are on
+    pub fn is_tied_var(&self, track: usize, step: usize, steps: usize, variation: Variation) -> bool {
+        let sd = self.get_step_var(track, step, variation);
+        let prev = (step + steps.max(1) - 1) % steps.max(1);
+        sd.active && sd.tie && prev != step && self.get_var(track, prev, variation)
+    }
+
+    /// Write a live hit. Overdubbing an active step keeps its note, the
+    /// louder velocity and the earlier timing.
+    pub fn record_hit_var(
+        &mut self,
+        track: usize,
+        step: usize,
+        note: u8,
+        velocity: u8,
+        offset: u8,
+        variation: Variation,
+    ) {
+        if let Some(sd) = self.step_mut(track, step, variation) {
+            if sd.active {
+                sd.velocity = sd.velocity.max(velocity.min(127));
+                sd.offset = sd.offset.min(offset.min(99));
+            } else {
+                *sd = StepData::with_velocity(note, velocity);
+                sd.offset = offset.min(99);
+            }
+        }
+    }
+
+    /// Move a track's micro-timed steps toward the nearest grid line by
+    /// `strength` percent. Hits that reach the next step move onto it; when
+    /// two hits land on one step the louder wins. The last step wraps to step 0.
+    pub fn quantize_track_var(&mut self, track: usize, strength: u8, variation: Variation) {
+        let strength = strength.min(100) as u32;
         let steps = self.steps_mut(variation);
-        if track < steps.len() && step < STEPS {
-            steps[track][step].probability = probability.min(100);
+        let Some(row) = steps.get_mut(track) else {
+            return;
+        };
+        let original = *row;
+        let mut out = original;
+        out.iter_mut().for_each(|sd| sd.active = false);
+        for (step, sd) in original.iter().enumerate() {
+            if !sd.active {
+                continue;
+            }
+            let offset = sd.offset as u32;
+            let (dest, offset) = if offset < 50 {
+                (step, offset - (offset * strength + 50) / 100)
+            } else {
+                let moved = offset + ((100 - offset) * strength + 50) / 100;
+                if moved >= 100 {
+                    ((step + 1) % original.len(), 0)
+                } else {
+                    (step, moved)
+                }
+            };
+            if !out[dest].active || out[dest].velocity < sd.velocity {
+                out[dest] = StepData { offset: offset as u8, ..*sd };
+            }
         }
+        *row = out;
     }
 
     /// Clear a track (variation A)
@@ -391,11 +1055,10 @@ impl Pattern {
     pub fn clear_track_var(&mut self, track: usize, variation: Variation) {
         let default_note = self.default_note_for_track(track);
         let steps = self.steps_mut(variation);
-        if track < steps.len() {
-            for step in 0..STEPS {
-                steps[track][step] = StepData::off(default_note);
-            }
+        if let Some(row) = steps.get_mut(track) {
+            row.fill(StepData::off(default_note));
         }
+        self.param_locks.retain(|l| !(l.track == track && l.variation == variation));
     }
 
     /// Fill a track (variation A)
@@ -407,10 +1070,8 @@ impl Pattern {
     pub fn fill_track_var(&mut self, track: usize, variation: Variation) {
         let default_note = self.default_note_for_track(track);
         let steps = self.steps_mut(variation);
-        if track < steps.len() {
-            for step in 0..STEPS {
-                steps[track][step] = StepData::on(default_note);
-            }
+        if let Some(row) = steps.get_mut(track) {
+            row.fill(StepData::on(default_note));
         }
     }
 
@@ -427,19 +1088,242 @@ impl Pattern {
         }
     }
 
-    /// Copy variation A to B or B to A
+    /// Write one trigger per step with notes selecting slice i on step i
+    /// (patterns longer than STEPS play the slices again from slice 0)
+    pub fn chop_track_var(&mut self, track: usize, variation: Variation) {
+        let steps = self.steps_mut(variation);
+        if let Some(row) = steps.get_mut(track) {
+            for (step, sd) in row.iter_mut().enumerate() {
+                *sd = StepData::on(CHOP_BASE_NOTE + (step % STEPS) as u8);
+            }
+        }
+    }
+
+    /// Copy variation A to B or B to A. Runs on the audio thread, so the
+    /// target's locks are overwritten in place (reusing their keys) and the
+    /// list only grows when the source has more locks than the target.
     pub fn copy_variation(&mut self, from: Variation, to: Variation) {
         match (from, to) {
             (Variation::A, Variation::B) => {
-                self.steps_b = self.steps_a.clone();
+                self.steps_b.clone_from(&self.steps_a);
             }
             (Variation::B, Variation::A) => {
-                self.steps_a = self.steps_b.clone();
+                self.steps_a.clone_from(&self.steps_b);
+            }
+            _ => return, // Same variation, no-op
+        }
+        let locks = &mut self.param_locks;
+        let len = locks.len();
+        let mut slot = 0;
+        for i in 0..len {
+            if locks[i].variation != from {
+                continue;
+            }
+            while slot < len && locks[slot].variation != to {
+                slot += 1;
+            }
+            if slot < len {
+                let (src, dst) = if i < slot {
+                    let (head, tail) = locks.split_at_mut(slot);
+                    (&head[i], &mut tail[0])
+                } else {
+                    let (head, tail) = locks.split_at_mut(i);
+                    (&tail[0], &mut head[slot])
+                };
+                dst.clone_from(src);
+                dst.variation = to;
+                slot += 1;
+            } else {
+                let copy = ParamLock { variation: to, ..locks[i].clone() };
+                locks.push(copy);
+            }
+        }
+        // Target locks left over once every source lock was copied
+        let mut index = 0;
+        locks.retain(|l| {
+            let stale = index >= slot && index < len && l.variation == to;
+            index += 1;
+            !stale
+        });
+    }
+
+    /// Copy selected tracks (both variations) from another pattern.
+    /// With `merge`, only the source's active steps (and their locks) are
+    /// written and the destination's other steps are kept; otherwise the
+    /// rows, locks and track loop lengths are replaced. The destination
+    /// keeps its length.
+    pub fn copy_tracks_from(&mut self, src: &Pattern, tracks: &[usize], merge: bool) {
+        for variation in [Variation::A, Variation::B] {
+            let src_rows = src.steps(variation);
+            let dst_rows = self.steps_mut(variation);
+            for &track in tracks {
+                if track >= src_rows.len() || track >= dst_rows.len() {
+                    continue;
+                }
+                if merge {
+                    for (dst, s) in dst_rows[track].iter_mut().zip(src_rows[track].iter()) {
+                        if s.active {
+                            *dst = *s;
+                        }
+                    }
+                } else {
+                    for (step, dst) in dst_rows[track].iter_mut().enumerate() {
+                        *dst = src_rows[track].get(step).copied().unwrap_or(StepData { active: false, ..*dst });
+                    }
+                }
+            }
+        }
+        let num_tracks = self.num_tracks();
+        let replaced = |l: &ParamLock| {
+            tracks.contains(&l.track)
+                && l.track < src.num_tracks()
+                && (!merge || src.get_step_var(l.track, l.step, l.variation).active)
+        };
+        self.param_locks.retain(|l| !replaced(l));
+        let length = self.length;
+        self.param_locks.extend(
+            src.param_locks
+                .iter()
+                .filter(|l| l.track < num_tracks && l.step < length && replaced(l))
+                .cloned(),
+        );
+        if !merge {
+            for &track in tracks {
+                self.set_track_length(track, src.track_length(track));
             }
-            _ => {} // Same variation, no-op
         }
     }
 
+    /// Bounded random variation of this pattern. `amount` (0-1) scales how
+    /// many hits move to a nearby empty step (taking their param locks)
+    /// and how far velocities and notes drift. The same seed always gives
+    /// the same result. Allocates: build it off the audio thread and send
+    /// it as ReplacePattern.
+    pub fn mutated(&self, amount: f32, seed: u32) -> Pattern {
+        let amount = amount.clamp(0.0, 1.0);
+        let mut rng = seed.max(1);
+        let mut next = move || -> u32 {
+            rng ^= rng << 13;
+            rng ^= rng >> 17;
+            rng ^= rng << 5;
+            rng
+        };
+        let chance = |rand: u32, p: f32| (rand % 10_000) as f32 / 10_000.0 < p;
+
+        let mut out = self.clone();
+        for variation in [Variation::A, Variation::B] {
+            let rows = match variation {
+                Variation::A => &mut out.steps_a,
+                Variation::B => &mut out.steps_b,
+            };
+            for (track, row) in rows.iter_mut().enumerate() {
+                let len = row.len();
+                let active: Vec<usize> = (0..len).filter(|&s| row[s].active).collect();
+                // Move hits: round(amount * hits), at least one when amount > 0
+                let moves = if amount > 0.0 && !active.is_empty() {
+                    ((amount * active.len() as f32).round() as usize).max(1)
+                } else {
+                    0
+                };
+                for _ in 0..moves {
+                    let from = active[next() as usize % active.len()];
+                    if !row[from].active {
+                        continue;
+                    }
+                    let offset = 1 + (next() % 2) as usize;
+                    let to = if next() % 2 == 0 {
+                        (from + offset) % len
+                    } else {
+                        (from + 2 * len - offset) % len
+                    };
+                    if !row[to].active {
+                        row[to] = row[from];
+                        row[from].active = false;
+                        // Locks belong to the hit, not the step it was on
+                        let at = |l: &ParamLock, step| l.track == track && l.variation == variation && l.step == step;
+                        out.param_locks.retain(|l| !at(l, to));
+                        for lock in out.param_locks.iter_mut().filter(|l| at(l, from)) {
+                            lock.step = to;
+                        }
+                    }
+                }
+
+                for sd in row.iter_mut().filter(|sd| sd.active) {
+                    if chance(next(), amount) {
+                        let max_delta = (20.0 * amount).round().max(1.0) as i32;
+                        let delta = (next() % (2 * max_delta as u32 + 1)) as i32 - max_delta;
+                        sd.velocity = (sd.velocity as i32 + delta).clamp(1, 127) as u8;
+                    }
+                    if chance(next(), amount * 0.25) {
+                        let delta = [-2, -1, 1, 2][next() as usize % 4];
+                        sd.note = (sd.note as i32 + delta).clamp(0, 127) as u8;
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Check the length, that both variations have the same track count
+    /// and rows of that length, and that track lengths and step values are
+    /// in range
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=MAX_STEPS).contains(&self.length) {
+            bail!("Pattern length {} out of range (1-{})", self.length, MAX_STEPS);
+        }
+        if self.steps_a.len() != self.steps_b.len() {
+            bail!(
+                "Variation A has {} tracks but B has {}",
+                self.steps_a.len(),
+                self.steps_b.len()
+            );
+        }
+        if self.track_lengths.len() > self.steps_a.len() {
+            bail!("{} track lengths for {} tracks", self.track_lengths.len(), self.steps_a.len());
+        }
+        if let Some(length) = self.track_lengths.iter().find(|&&l| l > MAX_STEPS) {
+            bail!("Track length {} out of range (0-{})", length, MAX_STEPS);
+        }
+        // Hashed: a full pattern has over 16k locks
+        let mut keys = HashSet::with_capacity(self.param_locks.len());
+        let mut per_step: HashMap<(usize, usize, Variation), usize> = HashMap::new();
+        for lock in &self.param_locks {
+            if lock.track >= self.steps_a.len() || lock.step >= self.length {
+                bail!("Parameter lock on track {} step {} is outside the pattern", lock.track, lock.step);
+            }
+            if lock.key.is_empty() || !lock.value.is_finite() {
+                bail!("Parameter lock on track {} step {} has no key or value", lock.track, lock.step);
+            }
+            if !keys.insert((lock.track, lock.step, lock.variation, lock.key.as_str())) {
+                bail!("Track {} step {} locks {} twice", lock.track, lock.step, lock.key);
+            }
+            let count = per_step.entry((lock.track, lock.step, lock.variation)).or_insert(0);
+            *count += 1;
+            if *count > MAX_LOCKS_PER_STEP {
+                bail!("Track {} step {} has more than {} locks", lock.track, lock.step, MAX_LOCKS_PER_STEP);
+            }
+        }
+        for (name, steps) in [("A", &self.steps_a), ("B", &self.steps_b)] {
+            for (track, row) in steps.iter().enumerate() {
+                if row.len() != self.length {
+                    bail!(
+                        "Variation {} track {} has {} steps (pattern length {})",
+                        name, track, row.len(), self.length
+                    );
+                }
+                for (step, sd) in row.iter().enumerate() {
+                    if sd.note > 127 || sd.velocity > 127 || sd.probability > 100 || sd.offset > 99 {
+                        bail!(
+                            "Variation {} track {} step {} out of range (note {}, velocity {}, probability {}, offset {})",
+                            name, track, step, sd.note, sd.velocity, sd.probability, sd.offset
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Get the default note for a track (from first step or DEFAULT_NOTES)
     fn default_note_for_track(&self, track: usize) -> u8 {
         if track < DEFAULT_NOTES.len() {
@@ -450,8 +1334,165 @@ impl Pattern {
     }
 }
 
+/// Seed for Pattern::mutated from the clock (never zero)
+pub fn random_seed() -> u32 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() ^ d.as_secs() as u32)
+        .unwrap_or(0);
+    nanos.max(1)
+}
+
 impl Default for Pattern {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Edits the engine makes to every pattern in the bank
+    #[derive(Clone, Debug)]
+    enum BankOp {
+        AddTrack(u8),
+        RemoveTrack(usize),
+        SetLength(usize),
+        SetTrackLength(usize, usize),
+        Toggle(usize, usize),
+        Lock(usize, usize, f32),
+    }
+
+    fn bank_op() -> impl Strategy<Value = BankOp> {
+        prop_oneof![
+            (0u8..128).prop_map(BankOp::AddTrack),
+            (0usize..20).prop_map(BankOp::RemoveTrack),
+            (0usize..100).prop_map(BankOp::SetLength),
+            (0usize..20, 0usize..100).prop_map(|(t, l)| BankOp::SetTrackLength(t, l)),
+            (0usize..20, 0usize..70).prop_map(|(t, s)| BankOp::Toggle(t, s)),
+            (0usize..20, 0usize..70, -1.0f32..1.0).prop_map(|(t, s, v)| BankOp::Lock(t, s, v)),
+        ]
+    }
+
+    #[derive(Clone, Debug)]
+    enum SongOp {
+        Append(usize, usize),
+        Insert(usize, usize, usize),
+        Remove(usize),
+        SetEntry(usize, usize, usize),
+    }
+
+    fn song_op() -> impl Strategy<Value = SongOp> {
+        prop_oneof![
+            (0usize..100, 0usize..30).prop_map(|(p, r)| SongOp::Append(p, r)),
+            (0usize..80, 0usize..100, 0usize..30).prop_map(|(at, p, r)| SongOp::Insert(at, p, r)),
+            (0usize..80).prop_map(SongOp::Remove),
+            (0usize..80, 0usize..100, 0usize..30).prop_map(|(at, p, r)| SongOp::SetEntry(at, p, r)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn bank_stays_valid_through_track_and_length_edits(ops in prop::collection::vec(bank_op(), 1..40)) {
+            let mut bank = PatternBank::new();
+            let mut num_tracks = DEFAULT_TRACKS;
+            for op in ops {
+                match op {
+                    // The engine caps the track count at 1..=MAX_TRACKS
+                    BankOp::AddTrack(note) if num_tracks < MAX_TRACKS => {
+                        bank.patterns.iter_mut().for_each(|p| p.add_track(note));
+                        num_tracks += 1;
+                    }
+                    BankOp::RemoveTrack(track) if num_tracks > 1 && track < num_tracks => {
+                        bank.patterns.iter_mut().for_each(|p| p.remove_track(track));
+                        num_tracks -= 1;
+                    }
+                    BankOp::SetLength(length) => bank.get_mut(0).set_length(length),
+                    BankOp::SetTrackLength(track, length) => bank.get_mut(0).set_track_length(track, length),
+                    BankOp::Toggle(track, step) if track < num_tracks && step < bank.get(0).length => {
+                        bank.get_mut(0).toggle(track, step);
+                    }
+                    BankOp::Lock(track, step, value) => {
+                        bank.get_mut(0).set_param_lock(track, step, Variation::B, "cutoff", Some(value));
+                    }
+                    _ => {}
+                }
+                prop_assert!(bank.validate(num_tracks).is_ok(), "{:?}", bank.validate(num_tracks));
+            }
+        }
+
+        #[test]
+        fn set_length_clamps_and_resizes_every_row(length in 0usize..200, tracks in 1usize..=MAX_TRACKS) {
+            let mut pattern = Pattern::new_with_tracks(tracks);
+            pattern.set_length(length);
+            prop_assert_eq!(pattern.length, length.clamp(1, MAX_STEPS));
+            for variation in [Variation::A, Variation::B] {
+                prop_assert!(pattern.steps(variation).iter().all(|row| row.len() == pattern.length));
+            }
+            prop_assert!(pattern.validate().is_ok());
+        }
+
+        #[test]
+        fn arrangement_stays_within_limits(ops in prop::collection::vec(song_op(), 1..150)) {
+            let mut song = Arrangement::new();
+            for op in ops {
+                match op {
+                    SongOp::Append(p, r) => song.append(p, r),
+                    SongOp::Insert(at, p, r) => song.insert(at, p, r),
+                    SongOp::Remove(at) => song.remove(at),
+                    SongOp::SetEntry(at, p, r) => song.set_entry(at, p, r),
+                }
+                prop_assert!(song.len() <= MAX_ARRANGEMENT_ENTRIES);
+                prop_assert!(song.validate().is_ok(), "{:?}", song.validate());
+            }
+        }
+    }
+
+    #[test]
+    fn mutation_moves_param_locks_with_their_hits() {
+        for seed in 1..64 {
+            let mut pattern = Pattern::new_with_tracks(2);
+            for (track, step) in [(0, 0), (0, 8), (1, 4)] {
+                pattern.steps_mut(Variation::A)[track][step].active = true;
+                assert!(pattern.set_param_lock(track, step, Variation::A, "decay", Some(0.5 + step as f32)));
+            }
+            let mutated = pattern.mutated(1.0, seed);
+            assert!(mutated.validate().is_ok());
+            assert_eq!(mutated.param_locks.len(), 3);
+            for lock in &mutated.param_locks {
+                assert!(mutated.steps(Variation::A)[lock.track][lock.step].active, "seed {}: {:?}", seed, lock);
+            }
+        }
+    }
+
+    #[test]
+    fn copy_variation_replaces_the_target_locks() {
+        fn locks(pattern: &Pattern, variation: Variation) -> Vec<(usize, usize, String, f32)> {
+            let mut out: Vec<_> = pattern
+                .param_locks
+                .iter()
+                .filter(|l| l.variation == variation)
+                .map(|l| (l.track, l.step, l.key.clone(), l.value))
+                .collect();
+            out.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            out
+        }
+        // Fewer, equal and more target locks than source locks
+        for targets in [0, 2, 5] {
+            let mut pattern = Pattern::new_with_tracks(2);
+            for step in 0..targets {
+                assert!(pattern.set_param_lock(1, step, Variation::B, "cutoff", Some(step as f32)));
+            }
+            for (track, step, key) in [(0, 3, "decay"), (1, 1, "cutoff"), (0, 7, "tone")] {
+                assert!(pattern.set_param_lock(track, step, Variation::A, key, Some(0.25)));
+            }
+            let source = locks(&pattern, Variation::A);
+            pattern.copy_variation(Variation::A, Variation::B);
+            assert_eq!(locks(&pattern, Variation::A), source);
+            assert_eq!(locks(&pattern, Variation::B), source, "{} target locks", targets);
+            assert_eq!(pattern.param_locks.len(), 6);
+        }
+    }
+}
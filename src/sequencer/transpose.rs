@@ -0,0 +1,28 @@
+/// Widest global transpose, in semitones either way
+pub const MAX_TRANSPOSE: i8 = 12;
+/// Widest per-track transpose, in semitones either way (two octaves)
+pub const MAX_TRACK_TRANSPOSE: i8 = 24;
+
+/// Chord degrees an arrangement entry can sit on, as major-scale numerals
+pub const CHORD_DEGREES: [&str; 7] = ["I", "ii", "iii", "IV", "V", "vi", "vii°"];
+/// Root move per degree, by the nearest interval so a progression stays
+/// around the pattern's register (V, vi and vii sit below I)
+const DEGREE_SHIFTS: [i8; 7] = [0, 2, 4, 5, -5, -3, -1];
+
+/// Semitones an entry's chord degree moves melodic tracks (0 without one)
+pub fn chord_shift(degree: Option<u8>) -> i8 {
+    degree.and_then(|d| DEGREE_SHIFTS.get(d as usize)).copied().unwrap_or(0)
+}
+
+/// Numeral for a chord degree ("-" without one)
+pub fn chord_label(degree: Option<u8>) -> &'static str {
+    degree.and_then(|d| CHORD_DEGREES.get(d as usize)).copied().unwrap_or("-")
+}
+
+/// Note a step plays: the track's own transpose, plus the global transpose
+/// on melodic tracks (drum synths use the note for small timbre shifts, not
+/// pitch)
+pub fn played_note(note: u8, melodic: bool, transpose: i8, track_transpose: i8) -> u8 {
+    let global = if melodic { transpose as i16 } else { 0 };
+    (note as i16 + global + track_transpose as i16).clamp(0, 127) as u8
+}
@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::params::midi_to_freq;
+use super::source::{ParamDescriptor, SoundSource, SynthType};
+use crate::fx::SvfFilter;
+
+/// Attack time, short enough to punch without clicking (seconds)
+const ATTACK_SECS: f32 = 0.003;
+/// Samples between filter cutoff updates
+const FILTER_UPDATE: usize = 16;
+/// Decay times reach -40 dB (ln 100)
+const DECAY_CURVE: f32 = 4.6;
+/// Fade-out when a note-off step cuts a note short (seconds)
+const RELEASE_SECS: f32 = 0.01;
+
+/// Acid synth parameters
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AcidParams {
+    /// Low-pass cutoff (40-4000 Hz)
+    pub cutoff: f32,
+    /// Filter resonance (0.0-0.95)
+    pub resonance: f32,
+    /// Filter envelope amount (0.0-1.0, up to +4 octaves)
+    pub env_mod: f32,
+    /// Filter envelope decay (0.05-2.0 s)
+    pub decay: f32,
+    /// Amp decay (0.1-3.0 s)
+    pub amp_decay: f32,
+    /// How much accented steps push level and filter (0.0-1.0)
+    pub accent: f32,
+    /// Slide time into the next note (5-500 ms)
+    pub glide: f32,
+    /// Oscillator shape: 0 = saw, 1 = square
+    pub shape: f32,
+    /// Tanh saturation after the filter (0.0-1.0)
+    pub drive: f32,
+    /// Output level (0.0-1.0)
+    pub level: f32,
+}
+
+impl Default for AcidParams {
+    fn default() -> Self {
+        Self {
+            cutoff: 300.0,
+            resonance: 0.75,
+            env_mod: 0.6,
+            decay: 0.3,
+            amp_decay: 1.0,
+            accent: 0.6,
+            glide: 60.0,
+            shape: 0.0,
+            drive: 0.2,
+            level: 0.6,
+        }
+    }
+}
+
+/// TB-303 style monosynth: one saw/square oscillator through a resonant
+/// 24 dB low-pass with an envelope on the cutoff. Accented steps hit
+/// harder with a snappier filter sweep; slide steps glide from the note
+/// before without retriggering the envelopes.
+#[derive(Clone)]
+pub struct AcidSynth {
+    params: AcidParams,
+    sample_rate: f32,
+    /// Samples since the envelopes started (None = silent)
+    phase: Option<usize>,
+    osc_phase: f32,
+    freq: f32,
+    target_freq: f32,
+    /// Two cascaded 12 dB stages, resonance on the second
+    filters: [SvfFilter; 2],
+    velocity_scale: f32,
+    /// Articulation of the next trigger
+    slide_next: bool,
+    accent_next: bool,
+    /// Whether the sounding note was accented
+    accented: bool,
+    /// Held by tied steps: the envelopes stay at their peak until note_off
+    held: bool,
+    /// Samples left in the fade-out after a note-off step
+    releasing: Option<usize>,
+}
+
+impl AcidSynth {
+    pub fn new(sample_rate: f32) -> Self {
+        let params = AcidParams::default();
+        let mut filters = [SvfFilter::new(sample_rate), SvfFilter::new(sample_rate)];
+        filters[1].set_resonance(params.resonance);
+        let freq = midi_to_freq(36);
+        Self {
+            params,
+            sample_rate,
+            phase: None,
+            osc_phase: 0.0,
+            freq,
+            target_freq: freq,
+            filters,
+            velocity_scale: 1.0,
+            slide_next: false,
+            accent_next: false,
+            accented: false,
+            held: false,
+            releasing: None,
+        }
+    }
+
+    fn start(&mut self, note: u8) {
+        self.target_freq = midi_to_freq(note);
+        let slide = std::mem::take(&mut self.slide_next);
+        self.accented = std::mem::take(&mut self.accent_next);
+        self.held = false;
+        // A slide into a sounding note only moves the pitch
+        if slide && self.phase.is_some() && self.releasing.is_none() {
+            return;
+        }
+        self.freq = self.target_freq;
+        self.phase = Some(0);
+        self.releasing = None;
+    }
+}
+
+impl SoundSource for AcidSynth {
+    fn synth_type(&self) -> SynthType { SynthType::Acid }
+    fn type_name(&self) -> &'static str { "ACID" }
+    fn default_note(&self) -> u8 { 36 }
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> { Some(Box::new(self.clone())) }
+    fn trigger(&mut self) { self.start(self.default_note()); }
+    fn trigger_with_note(&mut self, note: u8) { self.start(note); }
+
+    fn set_velocity_scale(&mut self, velocity: u8) {
+        self.velocity_scale = velocity as f32 / 127.0;
+    }
+
+    fn set_articulation(&mut self, slide: bool, accent: bool) {
+        self.slide_next = slide;
+        self.accent_next = accent;
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let Some(phase) = self.phase else {
+            return 0.0;
+        };
+        let t = phase as f32 / self.sample_rate;
+        let decay_t = (t - ATTACK_SECS).max(0.0);
+        let amp = if t < ATTACK_SECS {
+            t / ATTACK_SECS
+        } else {
+            (-decay_t * DECAY_CURVE / self.params.amp_decay).exp()
+        };
+        if (t >= ATTACK_SECS && amp < 0.0005 && !self.held) || self.releasing == Some(0) {
+            self.phase = None;
+            self.releasing = None;
+            return 0.0;
+        }
+        let fade = match self.releasing.as_mut() {
+            Some(left) => {
+                *left -= 1;
+                *left as f32 / (RELEASE_SECS * self.sample_rate)
+            }
+            None => 1.0,
+        };
+        // A held note stops its envelopes at the end of the attack
+        if !(self.held && t >= ATTACK_SECS) {
+            self.phase = Some(phase + 1);
+        }
+
+        let accent = if self.accented { self.params.accent } else { 0.0 };
+        if phase % FILTER_UPDATE == 0 {
+            // Accents sweep further and snap back sooner
+            let decay = self.params.decay * (1.0 - 0.5 * accent);
+            let env = (-decay_t * DECAY_CURVE / decay).exp();
+            let octaves = self.params.env_mod * (1.0 + accent) * 4.0 * env;
+            let cutoff = self.params.cutoff * 2.0f32.powf(octaves);
+            for filter in self.filters.iter_mut() {
+                filter.set_cutoff(cutoff);
+            }
+        }
+
+        // Glide toward the target pitch (slides), one time constant per glide time
+        let glide = 1.0 - (-1.0 / (self.params.glide * 0.001 * self.sample_rate)).exp();
+        self.freq += (self.target_freq - self.freq) * glide;
+
+        self.osc_phase = (self.osc_phase + self.freq / self.sample_rate).fract();
+        let saw = self.osc_phase * 2.0 - 1.0;
+        let square = if self.osc_phase < 0.5 { 1.0 } else { -1.0 };
+        let osc = saw * (1.0 - self.params.shape) + square * self.params.shape;
+
+        let [first, second] = &mut self.filters;
+        let mut out = second.process(first.process(osc));
+        if self.params.drive > 0.0 {
+            let gain = 1.0 + self.params.drive * 8.0;
+            out = (out * gain).tanh() / gain.tanh();
+        }
+        out * amp * fade * (1.0 + accent) * self.params.level * self.velocity_scale * 0.5
+    }
+
+    fn hold(&mut self) {
+        self.held = self.phase.is_some();
+    }
+
+    fn note_off(&mut self) {
+        self.held = false;
+    }
+
+    fn release(&mut self) {
+        self.held = false;
+        if self.phase.is_some() && self.releasing.is_none() {
+            self.releasing = Some((RELEASE_SECS * self.sample_rate) as usize);
+        }
+    }
+
+    fn stop(&mut self) {
+        self.phase = None;
+        self.held = false;
+        self.releasing = None;
+    }
+
+    fn param_descriptors(&self) -> Vec<ParamDescriptor> {
+        vec![
+            ParamDescriptor { key: "cutoff".into(), name: "Cutoff".into(), min: 40.0, max: 4000.0, default: 300.0 },
+            ParamDescriptor { key: "resonance".into(), name: "Resonance".into(), min: 0.0, max: 0.95, default: 0.75 },
+            ParamDescriptor { key: "env_mod".into(), name: "Env Mod".into(), min: 0.0, max: 1.0, default: 0.6 },
+            ParamDescriptor { key: "decay".into(), name: "Decay".into(), min: 0.05, max: 2.0, default: 0.3 },
+            ParamDescriptor { key: "amp_decay".into(), name: "Amp Decay".into(), min: 0.1, max: 3.0, default: 1.0 },
+            ParamDescriptor { key: "accent".into(), name: "Accent".into(), min: 0.0, max: 1.0, default: 0.6 },
+            ParamDescriptor { key: "glide".into(), name: "Glide ms".into(), min: 5.0, max: 500.0, default: 60.0 },
+            ParamDescriptor { key: "shape".into(), name: "Saw/Square".into(), min: 0.0, max: 1.0, default: 0.0 },
+            ParamDescriptor { key: "drive".into(), name: "Drive".into(), min: 0.0, max: 1.0, default: 0.2 },
+            ParamDescriptor { key: "level".into(), name: "Level".into(), min: 0.0, max: 1.0, default: 0.6 },
+        ]
+    }
+
+    fn get_param(&self, key: &str) -> Option<f32> {
+        match key {
+            "cutoff" => Some(self.params.cutoff),
+            "resonance" => Some(self.params.resonance),
+            "env_mod" => Some(self.params.env_mod),
+            "decay" => Some(self.params.decay),
+            "amp_decay" => Some(self.params.amp_decay),
+            "accent" => Some(self.params.accent),
+            "glide" => Some(self.params.glide),
+            "shape" => Some(self.params.shape),
+            "drive" => Some(self.params.drive),
+            "level" => Some(self.params.level),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, key: &str, value: f32) -> bool {
+        match key {
+            "cutoff" => { self.params.cutoff = value.clamp(40.0, 4000.0); true }
+            "resonance" => {
+                self.params.resonance = value.clamp(0.0, 0.95);
+                self.filters[1].set_resonance(self.params.resonance);
+                true
+            }
+            "env_mod" => { self.params.env_mod = value.clamp(0.0, 1.0); true }
+            "decay" => { self.params.decay = value.clamp(0.05, 2.0); true }
+            "amp_decay" => { self.params.amp_decay = value.clamp(0.1, 3.0); true }
+            "accent" => { self.params.accent = value.clamp(0.0, 1.0); true }
+            "glide" => { self.params.glide = value.clamp(5.0, 500.0); true }
+            "shape" => { self.params.shape = value.clamp(0.0, 1.0); true }
+            "drive" => { self.params.drive = value.clamp(0.0, 1.0); true }
+            "level" => { self.params.level = value.clamp(0.0, 1.0); true }
+            _ => false,
+        }
+    }
+
+    fn serialize_params(&self) -> Value {
+        serde_json::to_value(&self.params).unwrap_or(Value::Null)
+    }
+
+    fn deserialize_params(&mut self, params: &Value) {
+        if let Ok(p) = serde_json::from_value::<AcidParams>(params.clone()) {
+            self.filters[1].set_resonance(p.resonance);
+            self.params = p;
+        }
+    }
+}
@@ -3,8 +3,12 @@ use serde_json::Value;
 use super::params::{midi_to_freq, BassParams, DEFAULT_NOTES};
 use super::source::{ParamDescriptor, SoundSource, SynthType};
 
+/// Fade-out when a note-off step cuts a note short (seconds)
+const RELEASE_SECS: f32 = 0.01;
+
 /// Bass synthesizer
 /// Simple sine/saw at low frequency with sustain
+#[derive(Clone)]
 pub struct BassSynth {
     phase: Option<usize>,
     sample_rate: f32,
@@ -16,6 +20,10 @@ pub struct BassSynth {
     active_frequency: f32,
     /// Velocity scale (0.0-1.0) for amplitude
     velocity_scale: f32,
+    /// Held by tied steps: the envelope stays at its peak until note_off
+    held: bool,
+    /// Samples left in the fade-out after a note-off step
+    releasing: Option<usize>,
 }
 
 impl BassSynth {
@@ -31,6 +39,8 @@ impl BassSynth {
             params,
             active_frequency,
             velocity_scale: 1.0,
+            held: false,
+            releasing: None,
         }
     }
 
@@ -50,6 +60,8 @@ impl BassSynth {
         self.osc_phase = 0.0;
         self.sub_phase = 0.0;
         self.active_frequency = self.params.frequency;
+        self.held = false;
+        self.releasing = None;
     }
 
     pub fn trigger_with_note(&mut self, note: u8) {
@@ -57,6 +69,8 @@ impl BassSynth {
         self.osc_phase = 0.0;
         self.sub_phase = 0.0;
         self.active_frequency = midi_to_freq(note);
+        self.held = false;
+        self.releasing = None;
     }
 
     /// Set velocity scale from MIDI velocity (0-127)
@@ -69,8 +83,9 @@ impl BassSynth {
             return 0.0;
         };
 
-        if phase >= self.duration_samples {
+        if phase >= self.duration_samples || self.releasing == Some(0) {
             self.phase = None;
+            self.releasing = None;
             return 0.0;
         }
 
@@ -111,11 +126,23 @@ impl BassSynth {
             (-(t - attack) * self.params.decay).exp()
         };
 
-        // Advance phase
-        self.phase = Some(phase + 1);
+        // Advance phase (a held note stops at the end of the attack, so
+        // the decay starts at note_off)
+        if !(self.held && t >= attack) {
+            self.phase = Some(phase + 1);
+        }
+
+        // Fade out after a note-off step
+        let fade = match self.releasing.as_mut() {
+            Some(left) => {
+                *left -= 1;
+                *left as f32 / (RELEASE_SECS * self.sample_rate)
+            }
+            None => 1.0,
+        };
 
         // Apply velocity scaling
-        osc * amp * 0.6 * self.velocity_scale
+        osc * amp * fade * 0.6 * self.velocity_scale
     }
 }
 
@@ -123,10 +150,20 @@ impl SoundSource for BassSynth {
     fn synth_type(&self) -> SynthType { SynthType::Bass }
     fn type_name(&self) -> &'static str { "BASS" }
     fn default_note(&self) -> u8 { DEFAULT_NOTES[3] }
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> { Some(Box::new(self.clone())) }
     fn trigger(&mut self) { self.trigger(); }
     fn trigger_with_note(&mut self, note: u8) { self.trigger_with_note(note); }
     fn set_velocity_scale(&mut self, velocity: u8) { self.set_velocity(velocity); }
     fn next_sample(&mut self) -> f32 { self.next_sample() }
+    fn hold(&mut self) { self.held = self.phase.is_some(); }
+    fn note_off(&mut self) { self.held = false; }
+
+    fn release(&mut self) {
+        self.held = false;
+        if self.phase.is_some() && self.releasing.is_none() {
+            self.releasing = Some((RELEASE_SECS * self.sample_rate) as usize);
+        }
+    }
 
     fn param_descriptors(&self) -> Vec<ParamDescriptor> {
         vec![
@@ -0,0 +1,325 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{ChildStdin, ChildStdout, Command, Stdio};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use tracing::{debug, info};
+
+use super::source::{ParamDescriptor, SoundSource, SynthType};
+
+/// Samples per audio block requested from the plugin
+pub const EXTERNAL_BLOCK_SIZE: usize = 256;
+/// Blocks rendered ahead of playback (latency vs. underrun margin)
+const EXTERNAL_QUEUE_BLOCKS: usize = 4;
+/// Pending note/param events before new ones are dropped
+const EXTERNAL_EVENT_QUEUE: usize = 256;
+/// How long the plugin gets to answer `init`
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A plugin started on a background thread, for the track that asked
+pub struct PluginStart {
+    pub track: usize,
+    pub command: String,
+    pub connection: Result<ExternalConnection>,
+}
+
+/// Event sent from the audio thread to the plugin worker
+#[derive(Debug, Clone, Copy)]
+enum ExternalEvent {
+    Note { note: u8, velocity: u8 },
+    /// Parameter by index into the plugin's descriptors
+    Param { index: usize, value: f32 },
+    Stop,
+}
+
+/// The plugin's reply to `init`
+#[derive(Deserialize)]
+struct Handshake {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    params: Vec<ParamDescriptor>,
+}
+
+/// Channels to a running plugin process. Its worker thread owns the
+/// process and exits (killing it) once the synth holding this is dropped.
+#[derive(Clone)]
+pub struct ExternalConnection {
+    pub command: String,
+    pub name: String,
+    pub descriptors: Vec<ParamDescriptor>,
+    events: Sender<ExternalEvent>,
+    blocks: Receiver<Vec<f32>>,
+    recycle: Sender<Vec<f32>>,
+}
+
+impl std::fmt::Debug for ExternalConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExternalConnection({:?})", self.command)
+    }
+}
+
+impl ExternalConnection {
+    /// Start `command` through the shell and perform the `init` handshake
+    pub fn spawn(command: &str, sample_rate: f32) -> Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start '{}'", command))?;
+        let mut stdin = child.stdin.take().context("Plugin has no stdin")?;
+        let stdout = child.stdout.take().context("Plugin has no stdout")?;
+
+        writeln!(stdin, "init {} {}", sample_rate as u32, EXTERNAL_BLOCK_SIZE)
+            .and_then(|_| stdin.flush())
+            .context("Failed to send init to plugin")?;
+
+        // Read the handshake on the worker so a silent plugin can't hang the caller
+        let (events_tx, events_rx) = bounded(EXTERNAL_EVENT_QUEUE);
+        let (blocks_tx, blocks_rx) = bounded(EXTERNAL_QUEUE_BLOCKS);
+        let (recycle_tx, recycle_rx) = bounded(EXTERNAL_QUEUE_BLOCKS + 1);
+        let (hello_tx, hello_rx) = bounded(1);
+        std::thread::spawn(move || {
+            let mut stdout = BufReader::new(stdout);
+            let mut line = String::new();
+            let hello = match stdout.read_line(&mut line) {
+                Ok(n) if n > 0 => serde_json::from_str::<Handshake>(&line)
+                    .map_err(|e| format!("Invalid plugin handshake: {}", e)),
+                _ => Err("Plugin closed stdout before handshake".to_string()),
+            };
+            let keys: Vec<String> = match &hello {
+                Ok(h) => h.params.iter().map(|p| p.key.clone()).collect(),
+                Err(_) => Vec::new(),
+            };
+            let ok = hello.is_ok();
+            let _ = hello_tx.send(hello);
+            if ok {
+                run_worker(stdin, stdout, &keys, events_rx, blocks_tx, recycle_rx);
+                debug!("External synth worker stopped");
+            }
+            let _ = child.kill();
+            let _ = child.wait();
+        });
+
+        let hello = match hello_rx.recv_timeout(HANDSHAKE_TIMEOUT) {
+            Ok(Ok(hello)) => hello,
+            Ok(Err(e)) => bail!("{}", e),
+            Err(_) => bail!("Plugin '{}' did not answer init", command),
+        };
+        info!(command, name = hello.name, params = hello.params.len(), "External synth started");
+        Ok(Self {
+            command: command.to_string(),
+            name: hello.name,
+            descriptors: hello.params,
+            events: events_tx,
+            blocks: blocks_rx,
+            recycle: recycle_tx,
+        })
+    }
+
+    /// Start `command` for `track` on its own thread, so the handshake
+    /// doesn't hold up the caller; the result is sent to `done`
+    pub fn spawn_in_background(track: usize, command: String, sample_rate: f32, done: Sender<PluginStart>) {
+        std::thread::spawn(move || {
+            let connection = Self::spawn(&command, sample_rate);
+            let _ = done.send(PluginStart { track, command, connection });
+        });
+    }
+}
+
+/// Worker loop: forward events, request a block, queue it for the audio
+/// thread. Blocks on the full queue, which paces the plugin to playback.
+fn run_worker(
+    mut stdin: ChildStdin,
+    mut stdout: BufReader<ChildStdout>,
+    keys: &[String],
+    events: Receiver<ExternalEvent>,
+    blocks: Sender<Vec<f32>>,
+    recycle: Receiver<Vec<f32>>,
+) {
+    let mut bytes = vec![0u8; EXTERNAL_BLOCK_SIZE * 4];
+    loop {
+        let mut out = String::new();
+        while let Ok(event) = events.try_recv() {
+            match event {
+                ExternalEvent::Note { note, velocity } => {
+                    out.push_str(&format!("note {} {}\n", note, velocity))
+                }
+                ExternalEvent::Param { index, value } => {
+                    if let Some(key) = keys.get(index) {
+                        out.push_str(&format!("param {} {}\n", key, value));
+                    }
+                }
+                ExternalEvent::Stop => out.push_str("stop\n"),
+            }
+        }
+        out.push_str("block\n");
+        if stdin.write_all(out.as_bytes()).and_then(|_| stdin.flush()).is_err() {
+            return;
+        }
+        if stdout.read_exact(&mut bytes).is_err() {
+            return;
+        }
+        let mut block = recycle.try_recv().unwrap_or_default();
+        block.clear();
+        block.extend(
+            bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        );
+        if blocks.send(block).is_err() {
+            return;
+        }
+    }
+}
+
+/// Sound source backed by an external plugin process. Silent until a
+/// connection is attached; param values saved before that are applied on
+/// connect.
+pub struct ExternalSynth {
+    command: String,
+    connection: Option<ExternalConnection>,
+    /// Current value per connection descriptor
+    values: Vec<f32>,
+    /// Values from the project file, by key, until connected
+    saved: Map<String, Value>,
+    block: Vec<f32>,
+    pos: usize,
+}
+
+impl ExternalSynth {
+    pub fn new() -> Self {
+        Self {
+            command: String::new(),
+            connection: None,
+            values: Vec::new(),
+            saved: Map::new(),
+            block: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn send(&self, event: ExternalEvent) {
+        if let Some(conn) = &self.connection {
+            let _ = conn.events.try_send(event);
+        }
+    }
+
+    fn param_index(&self, key: &str) -> Option<usize> {
+        let conn = self.connection.as_ref()?;
+        conn.descriptors.iter().position(|d| d.key == key)
+    }
+}
+
+impl Default for ExternalSynth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plugin command stored in an External track's params
+pub fn external_command(params: &Value) -> Option<&str> {
+    params
+        .get("command")
+        .and_then(|v| v.as_str())
+        .filter(|c| !c.is_empty())
+}
+
+impl SoundSource for ExternalSynth {
+    fn synth_type(&self) -> SynthType { SynthType::External }
+    fn type_name(&self) -> &'static str { "EXTERNAL" }
+    fn default_note(&self) -> u8 { 60 }
+    fn trigger(&mut self) { self.trigger_with_note_velocity(60, 127); }
+    fn trigger_with_note(&mut self, note: u8) { self.trigger_with_note_velocity(note, 127); }
+
+    fn trigger_with_note_velocity(&mut self, note: u8, velocity: u8) {
+        self.send(ExternalEvent::Note { note, velocity });
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        if self.pos >= self.block.len() {
+            let Some(conn) = &self.connection else {
+                return 0.0;
+            };
+            // Underrun: output silence until the plugin catches up
+            let Ok(next) = conn.blocks.try_recv() else {
+                return 0.0;
+            };
+            let used = std::mem::replace(&mut self.block, next);
+            let _ = conn.recycle.try_send(used);
+            self.pos = 0;
+        }
+        let s = self.block.get(self.pos).copied().unwrap_or(0.0);
+        self.pos += 1;
+        s
+    }
+
+    fn param_descriptors(&self) -> Vec<ParamDescriptor> {
+        self.connection
+            .as_ref()
+            .map(|c| c.descriptors.clone())
+            .unwrap_or_default()
+    }
+
+    fn get_param(&self, key: &str) -> Option<f32> {
+        self.param_index(key).map(|i| self.values[i])
+    }
+
+    fn set_param(&mut self, key: &str, value: f32) -> bool {
+        let Some(index) = self.param_index(key) else {
+            return false;
+        };
+        self.values[index] = value;
+        self.send(ExternalEvent::Param { index, value });
+        true
+    }
+
+    fn serialize_params(&self) -> Value {
+        let mut params = self.saved.clone();
+        if let Some(conn) = &self.connection {
+            for (d, v) in conn.descriptors.iter().zip(&self.values) {
+                params.insert(d.key.clone(), json!(v));
+            }
+        }
+        json!({ "command": self.command, "params": params })
+    }
+
+    fn deserialize_params(&mut self, params: &Value) {
+        if let Some(command) = external_command(params) {
+            self.command = command.to_string();
+        }
+        if let Some(saved) = params.get("params").and_then(|v| v.as_object()) {
+            self.saved = saved.clone();
+        }
+    }
+
+    fn connect_external(&mut self, connection: ExternalConnection) {
+        self.command = connection.command.clone();
+        self.values = connection
+            .descriptors
+            .iter()
+            .map(|d| {
+                self.saved
+                    .get(&d.key)
+                    .and_then(|v| v.as_f64())
+                    .map_or(d.default, |v| (v as f32).clamp(d.min, d.max))
+            })
+            .collect();
+        self.connection = Some(connection);
+        self.block.clear();
+        self.pos = 0;
+        for (index, &value) in self.values.iter().enumerate() {
+            self.send(ExternalEvent::Param { index, value });
+        }
+    }
+
+    fn stop(&mut self) {
+        self.send(ExternalEvent::Stop);
+    }
+}
@@ -5,6 +5,7 @@ use super::source::{ParamDescriptor, SoundSource, SynthType};
 
 /// Hi-hat synthesizer
 /// High-passed noise with very short envelope
+#[derive(Clone)]
 pub struct HiHatSynth {
     phase: Option<usize>,
     sample_rate: f32,
@@ -119,6 +120,7 @@ impl SoundSource for HiHatSynth {
     fn synth_type(&self) -> SynthType { SynthType::HiHat }
     fn type_name(&self) -> &'static str { "HIHAT" }
     fn default_note(&self) -> u8 { DEFAULT_NOTES[2] }
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> { Some(Box::new(self.clone())) }
     fn trigger(&mut self) { self.trigger(); }
     fn trigger_with_note(&mut self, note: u8) { self.trigger_with_note(note); }
     fn set_velocity_scale(&mut self, velocity: u8) { self.set_velocity(velocity); }
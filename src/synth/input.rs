@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::source::{ParamDescriptor, SoundSource, SynthType};
+
+/// Input track parameters
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InputParams {
+    /// Input gain (0.0-2.0)
+    pub gain: f32,
+    /// Pass the input through (1) or mute it (0)
+    pub monitor: f32,
+}
+
+impl Default for InputParams {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            monitor: 1.0,
+        }
+    }
+}
+
+/// Live audio input passthrough. The engine feeds it one hardware input
+/// sample per frame; steps don't trigger anything. Offline renders never
+/// feed it, so input tracks are silent in exports.
+pub struct InputSource {
+    params: InputParams,
+    sample: f32,
+}
+
+impl InputSource {
+    pub fn new() -> Self {
+        Self {
+            params: InputParams::default(),
+            sample: 0.0,
+        }
+    }
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoundSource for InputSource {
+    fn synth_type(&self) -> SynthType { SynthType::Input }
+    fn type_name(&self) -> &'static str { "INPUT" }
+    fn default_note(&self) -> u8 { 60 }
+    fn trigger(&mut self) {}
+    fn trigger_with_note(&mut self, _note: u8) {}
+
+    fn next_sample(&mut self) -> f32 {
+        let s = self.sample;
+        // Silence if the input stream stalls
+        self.sample = 0.0;
+        if self.params.monitor >= 0.5 {
+            s * self.params.gain
+        } else {
+            0.0
+        }
+    }
+
+    fn set_input(&mut self, sample: f32) {
+        self.sample = sample;
+    }
+
+    fn param_descriptors(&self) -> Vec<ParamDescriptor> {
+        vec![
+            ParamDescriptor { key: "gain".into(), name: "Gain".into(), min: 0.0, max: 2.0, default: 1.0 },
+            ParamDescriptor { key: "monitor".into(), name: "Monitor".into(), min: 0.0, max: 1.0, default: 1.0 },
+        ]
+    }
+
+    fn get_param(&self, key: &str) -> Option<f32> {
+        match key {
+            "gain" => Some(self.params.gain),
+            "monitor" => Some(self.params.monitor),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, key: &str, value: f32) -> bool {
+        match key {
+            "gain" => { self.params.gain = value.clamp(0.0, 2.0); true }
+            "monitor" => { self.params.monitor = value.clamp(0.0, 1.0); true }
+            _ => false,
+        }
+    }
+
+    fn serialize_params(&self) -> Value {
+        serde_json::to_value(&self.params).unwrap_or(Value::Null)
+    }
+
+    fn deserialize_params(&mut self, params: &Value) {
+        if let Ok(p) = serde_json::from_value::<InputParams>(params.clone()) {
+            self.params = p;
+        }
+    }
+}
@@ -4,6 +4,7 @@ use super::params::{midi_to_freq, KickParams, DEFAULT_NOTES};
 use super::source::{ParamDescriptor, SoundSource, SynthType};
 
 /// Kick drum synthesizer state
+#[derive(Clone)]
 pub struct KickSynth {
     /// Current sample index (None = not playing)
     sample_index: Option<usize>,
@@ -128,6 +129,7 @@ impl SoundSource for KickSynth {
     fn synth_type(&self) -> SynthType { SynthType::Kick }
     fn type_name(&self) -> &'static str { "KICK" }
     fn default_note(&self) -> u8 { DEFAULT_NOTES[0] }
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> { Some(Box::new(self.clone())) }
     fn trigger(&mut self) { self.trigger(); }
     fn trigger_with_note(&mut self, note: u8) { self.trigger_with_note(note); }
     fn set_velocity_scale(&mut self, velocity: u8) { self.set_velocity(velocity); }
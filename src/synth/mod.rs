@@ -1,11 +1,33 @@
+pub mod acid;
 pub mod bass;
+pub mod external;
 pub mod hihat;
+pub mod input;
 pub mod kick;
 pub mod params;
+pub mod pitch;
+pub mod resample;
 pub mod sampler;
 pub mod snare;
 pub mod source;
+pub mod stab;
+pub mod stream;
+pub mod tempo;
+pub mod tone;
 
-pub use params::{note_name, BassParams, HiHatParams, KickParams, SnareParams};
-pub use sampler::load_wav;
-pub use source::{create_synth, ParamDescriptor, SoundSource, SynthType};
+pub use external::{external_command, ExternalConnection, PluginStart};
+pub use params::{
+    note_name, param_tuning, parse_key, split_cents, tuning_label, BassParams, HiHatParams,
+    KickParams, SnareParams, NOTE_NAMES,
+};
+pub use pitch::{detect_root_note, key_pitch_shift};
+pub use resample::{Interpolation, StreamResampler, LOAD_QUALITY};
+pub use sampler::{
+    load_wav, load_wav_at, normalize, prepare_sample, write_wav, SampleData, SampleInfo, SampleLayer, SampleOptions,
+    MAX_SAMPLE_LAYERS,
+};
+pub use source::{create_synth, ParamDescriptor, ParkedSynths, SoundSource, SynthType};
+pub use stab::chord_name;
+pub use stream::{SampleAudio, SampleStream};
+pub use tempo::{bpm_pitch_shift, detect_bpm};
+pub use tone::{ToneGenerator, ToneMode, ToneParams};
@@ -1,16 +1,70 @@
 use serde::{Deserialize, Serialize};
 
+use super::source::SynthType;
+
 /// Convert MIDI note number to frequency in Hz
 /// A4 (69) = 440 Hz
 pub fn midi_to_freq(note: u8) -> f32 {
     440.0 * 2.0f32.powf((note as f32 - 69.0) / 12.0)
 }
 
+/// Pitch class names, C = 0
+pub const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
 /// Note name from MIDI note number (e.g., 60 -> "C4", 61 -> "C#4")
 pub fn note_name(note: u8) -> String {
-    let names = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
     let octave = (note / 12) as i32 - 1;
-    format!("{}{}", names[note as usize % 12], octave)
+    format!("{}{}", NOTE_NAMES[note as usize % 12], octave)
+}
+
+/// Pitch class from a key name ("A", "f#", "Bb"); None if unrecognized
+pub fn parse_key(name: &str) -> Option<u8> {
+    let name = name.trim();
+    let mut chars = name.chars();
+    let base = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let class = match chars.as_str() {
+        "" => base,
+        "#" => base + 1,
+        "b" => base + 11,
+        _ => return None,
+    };
+    Some(class % 12)
+}
+
+/// Nearest note and cents for a fractional MIDI note (e.g. 45.2 -> (45, +20))
+pub fn split_cents(note: f32) -> (u8, f32) {
+    let nearest = note.round().clamp(0.0, 127.0);
+    (nearest as u8, (note - nearest) * 100.0)
+}
+
+/// Nearest MIDI note to a frequency and the offset from it in cents
+/// (e.g., 55 Hz -> (33, 0.0), 56 Hz -> (33, +31.2))
+pub fn freq_to_note(freq: f32) -> (u8, f32) {
+    split_cents(69.0 + 12.0 * (freq.max(1.0) / 440.0).log2())
+}
+
+/// Note and cents for display, e.g. "A1 +0c" or "C#2 -12c"
+pub fn tuning_label(note: u8, cents: f32) -> String {
+    format!("{} {:+.0}c", note_name(note), cents)
+}
+
+/// Tuner readout for a param that sets pitch: the bass frequency, or
+/// sampler pitch shift as heard when the sample is played from C4
+pub fn param_tuning(synth_type: SynthType, key: &str, value: f32) -> Option<(u8, f32)> {
+    match (synth_type, key) {
+        (SynthType::Bass, "frequency") => Some(freq_to_note(value)),
+        (SynthType::Sampler, "pitch_shift") => Some(split_cents(60.0 + value)),
+        _ => None,
+    }
 }
 
 /// Default MIDI notes per track (produce same sound as current defaults)
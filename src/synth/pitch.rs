@@ -0,0 +1,69 @@
+/// Lowest and highest fundamentals considered (Hz)
+const MIN_FREQ: f32 = 40.0;
+const MAX_FREQ: f32 = 2000.0;
+/// Length of the analysed window
+const WINDOW_SECS: f32 = 0.05;
+/// Skip this much after the onset so the attack transient doesn't dominate
+const ONSET_SKIP_SECS: f32 = 0.02;
+/// Normalized difference below this counts as a period (lower = stricter)
+const PERIOD_THRESHOLD: f32 = 0.2;
+/// Analyse every 2nd sample: plenty for fundamentals up to MAX_FREQ and
+/// keeps the search cheap enough to run when a sample loads
+const DECIMATE: usize = 2;
+/// Samples quieter than this don't count as the onset
+const ONSET_LEVEL: f32 = 0.01;
+
+/// Detect the fundamental frequency (Hz) of a mono sample using a
+/// normalized autocorrelation difference (YIN). None for noisy or unpitched
+/// material (most drums) and buffers too short to analyse.
+pub fn detect_pitch(buffer: &[f32], sample_rate: f32) -> Option<f32> {
+    let onset = buffer.iter().position(|s| s.abs() > ONSET_LEVEL)?;
+    let start = onset + (ONSET_SKIP_SECS * sample_rate) as usize;
+    let rate = sample_rate / DECIMATE as f32;
+    let window = (WINDOW_SECS * rate) as usize;
+    let max_lag = ((rate / MIN_FREQ) as usize).min(window);
+    let min_lag = ((rate / MAX_FREQ) as usize).max(2);
+    let x: Vec<f32> = buffer
+        .get(start..)?
+        .chunks_exact(DECIMATE)
+        .take(window + max_lag)
+        .map(|c| c.iter().sum::<f32>() / DECIMATE as f32)
+        .collect();
+    if x.len() < window + max_lag {
+        return None;
+    }
+
+    // Difference function, normalized by its running mean
+    let mut d = vec![1.0f32; max_lag + 1];
+    let mut sum = 0.0f32;
+    for lag in 1..=max_lag {
+        let diff: f32 = (0..window).map(|i| (x[i] - x[i + lag]).powi(2)).sum();
+        sum += diff;
+        d[lag] = if sum > 0.0 { diff * lag as f32 / sum } else { 1.0 };
+    }
+
+    // First dip under the threshold, followed down to its local minimum
+    let mut lag = (min_lag..max_lag).find(|&l| d[l] < PERIOD_THRESHOLD)?;
+    while lag + 1 < max_lag && d[lag + 1] < d[lag] {
+        lag += 1;
+    }
+
+    // Parabolic interpolation between neighbouring lags
+    let (a, b, c) = (d[lag - 1], d[lag], d[lag + 1]);
+    let denom = a - 2.0 * b + c;
+    let shift = if denom.abs() > f32::EPSILON { 0.5 * (a - c) / denom } else { 0.0 };
+    Some(rate / (lag as f32 + shift.clamp(-0.5, 0.5)))
+}
+
+/// Detected root as a fractional MIDI note (e.g. 45.2 = A2 +20 cents)
+pub fn detect_root_note(buffer: &[f32], sample_rate: f32) -> Option<f32> {
+    let freq = detect_pitch(buffer, sample_rate)?;
+    Some(69.0 + 12.0 * (freq / 440.0).log2())
+}
+
+/// Sampler pitch shift that makes a sample with this root sound the key's
+/// tonic (C without a key) when triggered at C4, by the smallest move
+pub fn key_pitch_shift(root_note: f32, key: Option<u8>) -> f32 {
+    let target = key.unwrap_or(0) as f32;
+    (target - root_note + 6.0).rem_euclid(12.0) - 6.0
+}
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+
+/// Zero crossings on each side of the windowed-sinc kernel
+const SINC_ZERO_CROSSINGS: usize = 8;
+/// Largest pitch-up ratio the sinc kernel widens for (caps the tap count)
+const SINC_MAX_RATIO: f64 = 8.0;
+/// Rate conversion used when loading samples for live playback
+pub const LOAD_QUALITY: Interpolation = Interpolation::Cubic;
+
+/// Interpolation used when reading a sample buffer at a fractional position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Interpolation {
+    /// Two-point linear (cheap, aliases when pitching up)
+    #[default]
+    Linear,
+    /// Four-point Catmull-Rom cubic
+    Cubic,
+    /// Blackman-windowed sinc, low-passed to the playback rate when pitching up
+    Sinc,
+}
+
+impl Interpolation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Interpolation::Linear => "linear",
+            Interpolation::Cubic => "cubic",
+            Interpolation::Sinc => "sinc",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Interpolation::Linear),
+            "cubic" => Some(Interpolation::Cubic),
+            "sinc" => Some(Interpolation::Sinc),
+            _ => None,
+        }
+    }
+
+    /// Read `buffer` at fractional position `pos`. `rate` is the playback
+    /// rate (samples advanced per output sample), used by sinc to band-limit.
+    pub fn read(&self, buffer: &[f32], pos: f64, rate: f64) -> f32 {
+        match self {
+            Interpolation::Linear => read_linear(buffer, pos),
+            Interpolation::Cubic => read_cubic(buffer, pos),
+            Interpolation::Sinc => read_sinc(buffer, pos, rate),
+        }
+    }
+}
+
+/// Convert a whole buffer from `from_sr` to `to_sr`, reading it with
+/// `quality` (sinc low-passes when converting down). The buffer comes back
+/// as is when the rates match.
+pub fn resample(buffer: Vec<f32>, from_sr: f32, to_sr: f32, quality: Interpolation) -> Vec<f32> {
+    if (from_sr - to_sr).abs() <= 1.0 || buffer.is_empty() || to_sr <= 0.0 {
+        return buffer;
+    }
+    let ratio = from_sr as f64 / to_sr as f64;
+    let len = (buffer.len() as f64 / ratio) as usize;
+    (0..len).map(|i| quality.read(&buffer, i as f64 * ratio, ratio)).collect()
+}
+
+/// Rate conversion for a stream arriving a sample at a time (e.g. the
+/// hardware input), with the same kernels as `resample`. Output lags the
+/// input by the kernel's reach; `push` doesn't allocate.
+pub struct StreamResampler {
+    quality: Interpolation,
+    /// Input samples advanced per output sample
+    ratio: f64,
+    /// Samples the kernel reads either side of a position
+    reach: usize,
+    /// Recent input, and the position of the next output sample in it
+    history: Vec<f32>,
+    pos: f64,
+}
+
+impl StreamResampler {
+    pub fn new(from_sr: f32, to_sr: f32, quality: Interpolation) -> Self {
+        let ratio = from_sr as f64 / to_sr as f64;
+        let reach = match quality {
+            Interpolation::Linear => 1,
+            Interpolation::Cubic => 2,
+            Interpolation::Sinc => (SINC_ZERO_CROSSINGS as f64 * ratio.clamp(1.0, SINC_MAX_RATIO)).ceil() as usize,
+        };
+        // Silence before the first sample, which is read first
+        let mut history = Vec::with_capacity(2 * reach + ratio.ceil() as usize + 2);
+        history.resize(reach, 0.0);
+        Self {
+            quality,
+            ratio,
+            reach,
+            history,
+            pos: reach as f64,
+        }
+    }
+
+    /// Add an input sample, calling `output` for each converted sample it completes
+    pub fn push(&mut self, sample: f32, mut output: impl FnMut(f32)) {
+        self.history.push(sample);
+        while self.pos as usize + self.reach < self.history.len() {
+            output(self.quality.read(&self.history, self.pos, self.ratio));
+            self.pos += self.ratio;
+        }
+        // Drop input the kernel no longer reaches
+        let done = (self.pos as usize).saturating_sub(self.reach);
+        if done > 0 {
+            self.history.drain(..done);
+            self.pos -= done as f64;
+        }
+    }
+}
+
+fn sample_at(buffer: &[f32], idx: isize) -> f32 {
+    if idx < 0 || idx as usize >= buffer.len() {
+        0.0
+    } else {
+        buffer[idx as usize]
+    }
+}
+
+fn read_linear(buffer: &[f32], pos: f64) -> f32 {
+    let idx = pos as usize;
+    let frac = (pos - idx as f64) as f32;
+    let s0 = if idx < buffer.len() { buffer[idx] } else { 0.0 };
+    let s1 = if idx + 1 < buffer.len() { buffer[idx + 1] } else { s0 };
+    s0 + (s1 - s0) * frac
+}
+
+fn read_cubic(buffer: &[f32], pos: f64) -> f32 {
+    let idx = pos.floor() as isize;
+    let t = (pos - idx as f64) as f32;
+    // Clamp neighbours at the edges so the first/last sample isn't pulled to zero
+    let last = buffer.len() as isize - 1;
+    let at = |i: isize| sample_at(buffer, i.clamp(0, last.max(0)));
+    let (y0, y1, y2, y3) = (at(idx - 1), at(idx), at(idx + 1), at(idx + 2));
+    let a = -0.5 * y0 + 1.5 * y1 - 1.5 * y2 + 0.5 * y3;
+    let b = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+    let c = -0.5 * y0 + 0.5 * y2;
+    ((a * t + b) * t + c) * t + y1
+}
+
+fn read_sinc(buffer: &[f32], pos: f64, rate: f64) -> f32 {
+    // Widen the kernel (lower the cutoff) when reading faster than 1:1
+    let ratio = rate.abs().clamp(1.0, SINC_MAX_RATIO);
+    let half_width = SINC_ZERO_CROSSINGS as f64 * ratio;
+    let center = pos.floor() as isize;
+    let reach = half_width.ceil() as isize;
+
+    let mut sum = 0.0f64;
+    let mut weight_sum = 0.0f64;
+    for i in (center - reach + 1)..=(center + reach) {
+        let x = i as f64 - pos;
+        if x.abs() >= half_width {
+            continue;
+        }
+        let w = windowed_sinc(x / ratio, x / half_width);
+        sum += sample_at(buffer, i) as f64 * w;
+        weight_sum += w;
+    }
+    // Normalize so DC gain stays at 1 regardless of fractional offset
+    if weight_sum.abs() > 1e-9 {
+        (sum / weight_sum) as f32
+    } else {
+        0.0
+    }
+}
+
+/// sinc(x) times a Blackman window evaluated at w in -1..1
+fn windowed_sinc(x: f64, w: f64) -> f64 {
+    use std::f64::consts::PI;
+    let sinc = if x.abs() < 1e-9 { 1.0 } else { (PI * x).sin() / (PI * x) };
+    let n = (w + 1.0) * 0.5;
+    let window = 0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos();
+    sinc * window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::TAU;
+
+    fn sine(freq: f32, sample_rate: f32, len: usize) -> Vec<f32> {
+        (0..len).map(|i| (TAU * freq * i as f32 / sample_rate).sin()).collect()
+    }
+
+    /// RMS away from the edges, where the kernel runs off the buffer
+    fn middle_rms(buffer: &[f32]) -> f32 {
+        let middle = &buffer[buffer.len() / 4..buffer.len() * 3 / 4];
+        (middle.iter().map(|s| s * s).sum::<f32>() / middle.len() as f32).sqrt()
+    }
+
+    /// Frequency from the upward zero crossings
+    fn frequency(buffer: &[f32], sample_rate: f32) -> f32 {
+        let crossings: Vec<usize> = (1..buffer.len())
+            .filter(|&i| buffer[i - 1] < 0.0 && buffer[i] >= 0.0)
+            .collect();
+        let cycles = crossings.len() - 1;
+        cycles as f32 * sample_rate / (crossings[cycles] - crossings[0]) as f32
+    }
+
+    #[test]
+    fn same_rate_passes_through() {
+        let buffer = sine(440.0, 48000.0, 1000);
+        for quality in [Interpolation::Linear, Interpolation::Cubic, Interpolation::Sinc] {
+            assert_eq!(resample(buffer.clone(), 48000.0, 48000.0, quality), buffer);
+        }
+    }
+
+    #[test]
+    fn upsampling_keeps_length_and_pitch() {
+        let buffer = sine(1000.0, 44100.0, 44100);
+        for quality in [Interpolation::Linear, Interpolation::Cubic, Interpolation::Sinc] {
+            let out = resample(buffer.clone(), 44100.0, 48000.0, quality);
+            assert_eq!(out.len(), 48000);
+            let freq = frequency(&out, 48000.0);
+            assert!((freq - 1000.0).abs() < 1.0, "{} read {} Hz", quality.name(), freq);
+        }
+    }
+
+    #[test]
+    fn sinc_downsampling_filters_above_the_new_nyquist() {
+        // 12 kHz folds back to 4 kHz at 16 kHz unless it's filtered out
+        let buffer = sine(12000.0, 48000.0, 4800);
+        let linear = middle_rms(&resample(buffer.clone(), 48000.0, 16000.0, Interpolation::Linear));
+        let sinc = middle_rms(&resample(buffer.clone(), 48000.0, 16000.0, Interpolation::Sinc));
+        assert!(linear > 0.3, "linear kept {}", linear);
+        assert!(sinc < 0.01, "sinc kept {}", sinc);
+        // Below the new Nyquist a tone comes through
+        let pass = sine(2000.0, 48000.0, 4800);
+        let sinc = middle_rms(&resample(pass, 48000.0, 16000.0, Interpolation::Sinc));
+        assert!((sinc - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.02, "sinc passed {}", sinc);
+    }
+
+    #[test]
+    fn streaming_matches_whole_buffer_conversion() {
+        let buffer = sine(1000.0, 48000.0, 4800);
+        for quality in [Interpolation::Linear, Interpolation::Cubic, Interpolation::Sinc] {
+            let whole = resample(buffer.clone(), 48000.0, 44100.0, quality);
+            let mut streamed = Vec::new();
+            let mut resampler = StreamResampler::new(48000.0, 44100.0, quality);
+            for &sample in &buffer {
+                resampler.push(sample, |s| streamed.push(s));
+            }
+            // Only the kernel's reach is still waiting for input
+            assert!(whole.len() - streamed.len() <= resampler.reach, "{}", quality.name());
+            let (start, end) = (100, streamed.len() - 100);
+            for (a, b) in whole[start..end].iter().zip(&streamed[start..end]) {
+                assert!((a - b).abs() < 1e-5, "{}: {} vs {}", quality.name(), a, b);
+            }
+            assert!(resampler.history.capacity() <= 2 * resampler.reach + 4);
+        }
+    }
+}
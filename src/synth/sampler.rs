@@ -1,10 +1,16 @@
 use std::path::Path;
+use std::sync::Arc;
 
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::resample::{resample, Interpolation, LOAD_QUALITY};
+use super::pitch::detect_root_note;
+use super::tempo::detect_bpm;
 use super::source::{ParamDescriptor, SoundSource, SynthType};
+use super::stream::{SampleAudio, SampleStream};
+use crate::fx::{FilterType, SvfFilter};
 
 /// Sampler synth parameters
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -30,13 +36,55 @@ pub struct SamplerParams {
     #[serde(default = "default_slice_count")]
     pub slice_count: u8,   // 1-16, default 1 (no slicing)
     #[serde(default)]
+    pub sync_bars: u8,     // 0-16, bars the start..end region lasts at the project tempo, default 0 (off)
+    #[serde(default)]
+    pub stretch: bool,     // keep the pitch when synced (time-stretch), default false (repitch)
+    #[serde(default)]
+    pub filter_mode: u8,   // 0 = off, 1 = low-pass, 2 = high-pass, default 0
+    #[serde(default = "default_cutoff")]
+    pub cutoff: f32,       // 20-20000 Hz, default 20000
+    #[serde(default)]
+    pub resonance: f32,    // 0.0-0.95, default 0
+    #[serde(default)]
+    pub filter_env: f32,   // -1.0 to 1.0, envelope amount (±4 octaves), default 0
+    #[serde(default)]
+    pub drive: f32,        // 0.0-1.0, tanh drive after the filter, default 0
+    #[serde(default)]
+    pub normalize: bool,   // scale loaded sample to full peak, default false
+    #[serde(default)]
+    pub trim_silence: bool, // drop leading/trailing silence, default false
+    #[serde(default)]
     pub wav_path: Option<String>, // for display and serialization
+    /// Root detected when the sample loaded, as a fractional MIDI note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_note: Option<f32>,
+    /// Tempo detected when the sample loaded (loops only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_bpm: Option<f32>,
+    /// Extra samples played instead of the main one for hits in their velocity range
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<SampleLayer>,
+}
+
+/// Velocity layer: a sample played for hits from min_velocity to max_velocity
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SampleLayer {
+    pub wav_path: String,
+    pub min_velocity: u8,
+    pub max_velocity: u8,
 }
 
+/// Most velocity layers per sampler track
+pub const MAX_SAMPLE_LAYERS: usize = 8;
+
 fn default_slice_count() -> u8 {
     1
 }
 
+fn default_cutoff() -> f32 {
+    20000.0
+}
+
 fn default_loop_end() -> f32 {
     1.0
 }
@@ -62,7 +110,19 @@ impl Default for SamplerParams {
             hold_steps: 4,
             reverse: false,
             slice_count: 1,
+            sync_bars: 0,
+            stretch: false,
+            filter_mode: 0,
+            cutoff: 20000.0,
+            resonance: 0.0,
+            filter_env: 0.0,
+            drive: 0.0,
+            normalize: false,
+            trim_silence: false,
             wav_path: None,
+            detected_note: None,
+            detected_bpm: None,
+            layers: Vec::new(),
         }
     }
 }
@@ -76,10 +136,34 @@ enum EnvelopePhase {
     Release, // sustain → 0 over release time (triggered by note_off or hold_steps)
 }
 
+/// Peak level that normalization scales to
+const NORMALIZE_PEAK: f32 = 0.98;
+/// Level below which samples count as silence for trimming (-60 dB)
+const SILENCE_THRESHOLD: f32 = 0.001;
+/// Longest tempo-synced length, in bars
+const MAX_SYNC_BARS: u8 = 16;
+/// Sequencer steps (16ths) in a bar
+const STEPS_PER_BAR: usize = 16;
+/// Slowest and fastest a synced loop is played (two octaves either way)
+const MIN_SYNC_SPEED: f64 = 0.25;
+const MAX_SYNC_SPEED: f64 = 4.0;
+/// Length of each of the two overlapping time-stretch grains
+const STRETCH_GRAIN_SECONDS: f32 = 0.04;
+
+/// Mono sample data, shared (not copied) by a sampler's voice copies
+pub type SampleData = Arc<[f32]>;
+
 /// Sampler synth: plays back a WAV buffer with pitch shifting
+#[derive(Clone)]
 pub struct SamplerSynth {
     sample_rate: f32,
-    buffer: Vec<f32>,           // mono f32 sample data
+    buffer: SampleData,         // normalize/trim already applied
+    /// Main sample streamed from disk, in place of `buffer`
+    stream: Option<SampleStream>,
+    /// Velocity layer samples, indexed like params.layers
+    layer_buffers: Vec<SampleData>,
+    /// Layer picked for the current hit (None = main sample)
+    active_layer: Option<usize>,
     position: Option<f64>,      // None = not playing, Some = current fractional position
     playback_rate: f64,         // computed from note + pitch_shift
     envelope: f32,              // current envelope value (0.0-1.0)
@@ -88,20 +172,35 @@ pub struct SamplerSynth {
     release_start_level: f32,   // envelope level when release started
     trigger_step: Option<usize>, // step when note was triggered (for hold_steps)
     steps_elapsed: usize,        // steps elapsed since trigger
+    /// Held by tied steps: hold_steps doesn't count until note_off
+    held: bool,
     params: SamplerParams,
     /// Velocity scale (0.0-1.0) for amplitude
     velocity_scale: f32,
+    /// Velocity of the next hit, used to pick a layer
+    velocity: u8,
     /// Active slice start (fraction of buffer, computed at trigger time)
     active_slice_start: f64,
     /// Active slice end (fraction of buffer, computed at trigger time)
     active_slice_end: f64,
+    /// Built-in per-hit filter (cutoff follows the amp envelope by filter_env)
+    filter: SvfFilter,
+    /// Buffer read interpolation (higher quality used for offline rendering)
+    interpolation: Interpolation,
+    /// Project tempo, for tempo-synced loops
+    bpm: f32,
+    /// Time-stretch grains as (source position at grain start, samples played)
+    grains: [(f64, usize); 2],
 }
 
 impl SamplerSynth {
     pub fn new(sample_rate: f32) -> Self {
-        Self {
+        let mut sampler = Self {
             sample_rate,
-            buffer: Vec::new(),
+            buffer: SampleData::default(),
+            stream: None,
+            layer_buffers: Vec::new(),
+            active_layer: None,
             position: None,
             playback_rate: 1.0,
             envelope: 0.0,
@@ -110,30 +209,106 @@ impl SamplerSynth {
             release_start_level: 0.0,
             trigger_step: None,
             steps_elapsed: 0,
+            held: false,
             params: SamplerParams::default(),
             velocity_scale: 1.0,
+            velocity: 127,
             active_slice_start: 0.0,
             active_slice_end: 1.0,
-        }
+            filter: SvfFilter::new(sample_rate),
+            interpolation: Interpolation::Linear,
+            bpm: 120.0,
+            grains: [(0.0, 0); 2],
+        };
+        sampler.update_filter();
+        sampler
     }
 
     /// Set velocity scale from MIDI velocity (0-127)
     pub fn set_velocity(&mut self, velocity: u8) {
         self.velocity_scale = velocity as f32 / 127.0;
+        self.velocity = velocity;
     }
 
-    /// Load a sample buffer and associated path
-    pub fn set_buffer(&mut self, buffer: Vec<f32>, path: &str) {
+    /// Load a sample buffer and associated path. The buffer is played as
+    /// given: normalize/trim are applied and `info` detected by the loader
+    /// (see `prepare_sample` and `SampleInfo::detect`).
+    pub fn set_buffer(&mut self, buffer: SampleData, path: &str, info: SampleInfo) {
         self.buffer = buffer;
+        self.stream = None;
         self.params.wav_path = Some(path.to_string());
+        self.params.detected_note = info.root_note;
+        self.params.detected_bpm = info.bpm;
+    }
+
+    /// Load a main sample that streams from disk (see `SampleStream`)
+    pub fn set_stream(&mut self, stream: SampleStream, path: &str, info: SampleInfo) {
+        self.set_buffer(SampleData::default(), path, info);
+        self.stream = Some(stream);
+    }
+
+    /// Load a velocity layer sample. A `layer` past the end adds a layer
+    /// covering all velocities (up to MAX_SAMPLE_LAYERS).
+    pub fn set_layer_buffer(&mut self, layer: usize, buffer: SampleData, path: &str) {
+        if layer >= self.params.layers.len() {
+            if self.params.layers.len() >= MAX_SAMPLE_LAYERS {
+                return;
+            }
+            self.params.layers.push(SampleLayer {
+                wav_path: String::new(),
+                min_velocity: 0,
+                max_velocity: 127,
+            });
+        }
+        let layer = layer.min(self.params.layers.len() - 1);
+        self.params.layers[layer].wav_path = path.to_string();
+        self.layer_buffers.resize_with(self.params.layers.len(), Default::default);
+        self.layer_buffers[layer] = buffer;
+    }
+
+    /// First loaded layer whose range holds `velocity`
+    fn layer_for(&self, velocity: u8) -> Option<usize> {
+        self.params
+            .layers
+            .iter()
+            .zip(&self.layer_buffers)
+            .position(|(l, b)| !b.is_empty() && (l.min_velocity..=l.max_velocity).contains(&velocity))
+    }
+
+    /// Buffer the current hit plays: its velocity layer or the main sample
+    /// (None when the main sample streams)
+    fn active_buffer(&self) -> Option<&[f32]> {
+        match self.active_layer.and_then(|i| self.layer_buffers.get(i)) {
+            Some(layer) => Some(layer),
+            None if self.stream.is_some() => None,
+            None => Some(&self.buffer),
+        }
+    }
+
+    /// Frames in the sample the current hit plays
+    fn active_len(&self) -> usize {
+        match (self.active_buffer(), &self.stream) {
+            (Some(buffer), _) => buffer.len(),
+            (None, Some(stream)) => stream.len(),
+            (None, None) => 0,
+        }
+    }
+
+    /// Read the current hit's sample at a fractional position
+    fn read_active(&mut self, pos: f64, rate: f64) -> f32 {
+        let interpolation = self.interpolation;
+        if let Some(buffer) = self.active_buffer() {
+            return interpolation.read(buffer, pos, rate);
+        }
+        self.stream.as_mut().map_or(0.0, |s| s.read(interpolation, pos, rate))
     }
 
     fn start_pos_samples(&self) -> f64 {
-        self.params.start_point as f64 * self.buffer.len() as f64
+        self.params.start_point as f64 * self.active_len() as f64
     }
 
     fn end_pos_samples(&self) -> f64 {
-        self.params.end_point as f64 * self.buffer.len() as f64
+        self.params.end_point as f64 * self.active_len() as f64
     }
 
     fn attack_samples(&self) -> f32 {
@@ -149,11 +324,103 @@ impl SamplerSynth {
     }
 
     fn loop_start_samples(&self) -> f64 {
-        self.params.loop_start as f64 * self.buffer.len() as f64
+        self.params.loop_start as f64 * self.active_len() as f64
     }
 
     fn loop_end_samples(&self) -> f64 {
-        self.params.loop_end as f64 * self.buffer.len() as f64
+        self.params.loop_end as f64 * self.active_len() as f64
+    }
+
+    /// Speed the start..end region plays at so it lasts `sync_bars` bars at
+    /// the project tempo (1 when not synced)
+    fn sync_speed(&self) -> f64 {
+        if self.params.sync_bars == 0 || self.bpm <= 0.0 {
+            return 1.0;
+        }
+        let region = (self.end_pos_samples() - self.start_pos_samples()).abs();
+        let target = self.params.sync_bars as f64 * 4.0 * 60.0 / self.bpm as f64 * self.sample_rate as f64;
+        if region < 1.0 {
+            return 1.0;
+        }
+        (region / target).clamp(MIN_SYNC_SPEED, MAX_SYNC_SPEED)
+    }
+
+    fn stretching(&self) -> bool {
+        self.params.sync_bars > 0 && self.params.stretch
+    }
+
+    /// Samples the play position moves per output sample: the pitch rate
+    /// scaled by the sync speed, or the sync speed alone when stretching
+    fn advance(&self) -> f64 {
+        let speed = self.sync_speed();
+        if self.stretching() {
+            speed.copysign(self.playback_rate)
+        } else {
+            self.playback_rate * speed
+        }
+    }
+
+    fn grain_len(&self) -> usize {
+        ((STRETCH_GRAIN_SECONDS * self.sample_rate) as usize).max(2)
+    }
+
+    /// Wrap a read position into the loop region while looping
+    fn wrap_loop(&self, pos: f64) -> f64 {
+        if !self.params.loop_enabled {
+            return pos;
+        }
+        let start = self.loop_start_samples();
+        let end = self.loop_end_samples().min(self.active_len() as f64);
+        if end > start {
+            start + (pos - start).rem_euclid(end - start)
+        } else {
+            pos
+        }
+    }
+
+    /// Time-stretched read around `pos`: two Hann-windowed grains half a
+    /// grain apart replay the source at the pitch rate, each restarting
+    /// from the play position (which moves at the sync speed) when it ends
+    fn read_stretched(&mut self, pos: f64) -> f32 {
+        let grain_len = self.grain_len();
+        let mut out = 0.0;
+        for i in 0..self.grains.len() {
+            let (start, age) = self.grains[i];
+            let read = self.wrap_loop(start + age as f64 * self.playback_rate);
+            let phase = age as f32 / grain_len as f32;
+            let window = (std::f32::consts::PI * phase).sin().powi(2);
+            out += window * self.read_active(read, self.playback_rate);
+            self.grains[i] = if age + 1 >= grain_len { (pos, 0) } else { (start, age + 1) };
+        }
+        out
+    }
+
+    /// Push filter mode/resonance from params into the filter
+    fn update_filter(&mut self) {
+        if self.params.filter_mode == 2 {
+            self.filter.set_filter_type(FilterType::HighPass);
+        } else {
+            self.filter.set_filter_type(FilterType::LowPass);
+        }
+        self.filter.set_resonance(self.params.resonance);
+        self.filter.set_cutoff(self.params.cutoff);
+    }
+
+    /// Apply built-in filter and drive to a voice sample
+    fn shape(&mut self, x: f32) -> f32 {
+        let mut out = x;
+        if self.params.filter_mode != 0 {
+            if self.params.filter_env != 0.0 {
+                let octaves = self.params.filter_env * 4.0 * self.envelope;
+                self.filter.set_cutoff(self.params.cutoff * 2.0f32.powf(octaves));
+            }
+            out = self.filter.process(out);
+        }
+        if self.params.drive > 0.0 {
+            let gain = 1.0 + self.params.drive * 10.0;
+            out = (out * gain).tanh() / gain.tanh();
+        }
+        out
     }
 
     /// Trigger release phase (called by hold_steps countdown or note_off)
@@ -188,7 +455,8 @@ impl SoundSource for SamplerSynth {
     }
 
     fn trigger_with_note(&mut self, note: u8) {
-        if self.buffer.is_empty() {
+        self.active_layer = self.layer_for(self.velocity);
+        if self.active_len() == 0 {
             return;
         }
 
@@ -220,20 +488,25 @@ impl SoundSource for SamplerSynth {
         self.playback_rate = if self.params.reverse { -rate } else { rate };
 
         // Calculate start position in samples
-        let start_samples = self.active_slice_start * self.buffer.len() as f64;
-        let end_samples = self.active_slice_end * self.buffer.len() as f64;
+        let start_samples = self.active_slice_start * self.active_len() as f64;
+        let end_samples = self.active_slice_end * self.active_len() as f64;
 
         // Start at end for reverse, start for forward
-        self.position = Some(if self.params.reverse {
+        let start_pos = if self.params.reverse {
             // Start just before end so we read valid samples
             (end_samples - 1.0).max(0.0)
         } else {
             start_samples
-        });
+        };
+        self.position = Some(start_pos);
+        // The second grain starts fully open, reading from the start position
+        let half = self.grain_len() / 2;
+        self.grains = [(start_pos, 0), (start_pos - half as f64 * self.playback_rate, half)];
         self.envelope = 0.0;
         self.envelope_samples = 0;
         self.release_start_level = 0.0;
         self.steps_elapsed = 0;
+        self.held = false;
         self.trigger_step = Some(0); // Will be set properly by step_tick
         if self.params.attack > 0.0 {
             self.envelope_phase = EnvelopePhase::Attack;
@@ -254,14 +527,14 @@ impl SoundSource for SamplerSynth {
             return 0.0;
         };
 
-        if self.buffer.is_empty() {
+        if self.active_len() == 0 {
             self.position = None;
             return 0.0;
         }
 
         // Use active slice region (computed at trigger time)
-        let start = self.active_slice_start * self.buffer.len() as f64;
-        let end = self.active_slice_end * self.buffer.len() as f64;
+        let start = self.active_slice_start * self.active_len() as f64;
+        let end = self.active_slice_end * self.active_len() as f64;
         let is_reverse = self.params.reverse;
 
         // Check if we've reached end of playback region
@@ -269,14 +542,14 @@ impl SoundSource for SamplerSynth {
         let out_of_bounds = if is_reverse {
             pos < start || pos < 0.0
         } else {
-            pos >= end || pos >= self.buffer.len() as f64
+            pos >= end || pos >= self.active_len() as f64
         };
 
         let new_pos = if out_of_bounds {
             if self.params.loop_enabled && self.envelope_phase != EnvelopePhase::Release {
                 // Loop mode: wrap back
                 let loop_start = self.loop_start_samples();
-                let loop_end = self.loop_end_samples().min(self.buffer.len() as f64);
+                let loop_end = self.loop_end_samples().min(self.active_len() as f64);
                 if loop_end > loop_start {
                     let loop_len = loop_end - loop_start;
                     if is_reverse {
@@ -302,26 +575,18 @@ impl SoundSource for SamplerSynth {
             pos
         };
 
-        // Linear interpolation
-        let idx = new_pos as usize;
-        let frac = (new_pos - idx as f64) as f32;
-        let s0 = if idx < self.buffer.len() {
-            self.buffer[idx]
-        } else {
-            0.0
-        };
-        let s1 = if idx + 1 < self.buffer.len() {
-            self.buffer[idx + 1]
+        let advance = self.advance();
+        let raw = if self.stretching() {
+            self.read_stretched(new_pos)
         } else {
-            s0
+            self.read_active(new_pos, advance)
         };
-        let raw = s0 + (s1 - s0) * frac;
 
         // Advance position (with loop wrapping)
-        let next_pos = new_pos + self.playback_rate; // playback_rate is negative for reverse
+        let next_pos = new_pos + advance; // negative for reverse
         if self.params.loop_enabled && self.envelope_phase != EnvelopePhase::Release {
             let loop_start = self.loop_start_samples();
-            let loop_end = self.loop_end_samples().min(self.buffer.len() as f64);
+            let loop_end = self.loop_end_samples().min(self.active_len() as f64);
             if loop_end > loop_start {
                 let loop_len = loop_end - loop_start;
                 if is_reverse && next_pos < loop_start {
@@ -382,7 +647,7 @@ impl SoundSource for SamplerSynth {
                 // Hold at sustain level
                 // For one-shot (non-looping), auto-trigger release when near end
                 if !self.params.loop_enabled {
-                    let release_time_samples = self.release_samples() as f64 * self.playback_rate.abs();
+                    let release_time_samples = self.release_samples() as f64 * advance.abs();
                     if let Some(p) = self.position {
                         let should_release = if is_reverse {
                             // For reverse, check if we're near start_point
@@ -418,8 +683,8 @@ impl SoundSource for SamplerSynth {
             }
         }
 
-        // Apply velocity scaling
-        raw * self.envelope * self.params.amplitude * self.velocity_scale
+        // Shape, then apply velocity scaling
+        self.shape(raw) * self.envelope * self.params.amplitude * self.velocity_scale
     }
 
     fn param_descriptors(&self) -> Vec<ParamDescriptor> {
@@ -522,6 +787,69 @@ impl SoundSource for SamplerSynth {
                 max: 16.0,
                 default: 1.0,
             },
+            ParamDescriptor {
+                key: "sync_bars".into(),
+                name: "Sync Bars".into(),
+                min: 0.0,
+                max: MAX_SYNC_BARS as f32,
+                default: 0.0,
+            },
+            ParamDescriptor {
+                key: "stretch".into(),
+                name: "Stretch".into(),
+                min: 0.0,
+                max: 1.0,
+                default: 0.0,
+            },
+            ParamDescriptor {
+                key: "filter_mode".into(),
+                name: "Filter (0/LP/HP)".into(),
+                min: 0.0,
+                max: 2.0,
+                default: 0.0,
+            },
+            ParamDescriptor {
+                key: "cutoff".into(),
+                name: "Cutoff (Hz)".into(),
+                min: 20.0,
+                max: 20000.0,
+                default: 20000.0,
+            },
+            ParamDescriptor {
+                key: "resonance".into(),
+                name: "Resonance".into(),
+                min: 0.0,
+                max: 0.95,
+                default: 0.0,
+            },
+            ParamDescriptor {
+                key: "filter_env".into(),
+                name: "Filter Env".into(),
+                min: -1.0,
+                max: 1.0,
+                default: 0.0,
+            },
+            ParamDescriptor {
+                key: "drive".into(),
+                name: "Drive".into(),
+                min: 0.0,
+                max: 1.0,
+                default: 0.0,
+            },
+            ParamDescriptor {
+                key: "normalize".into(),
+                name: "Normalize".into(),
+                min: 0.0,
+                max: 1.0,
+                default: 0.0,
+            },
+            ParamDescriptor {
+                key: "trim_silence".into(),
+                name: "Trim Silence".into(),
+                min: 0.0,
+                max: 1.0,
+                default: 0.0,
+            },
         ]
     }
 
@@ -541,6 +869,15 @@ impl SoundSource for SamplerSynth {
             "hold_steps" => Some(self.params.hold_steps as f32),
             "reverse" => Some(if self.params.reverse { 1.0 } else { 0.0 }),
             "slice_count" => Some(self.params.slice_count as f32),
+            "sync_bars" => Some(self.params.sync_bars as f32),
+            "stretch" => Some(if self.params.stretch { 1.0 } else { 0.0 }),
+            "filter_mode" => Some(self.params.filter_mode as f32),
+            "cutoff" => Some(self.params.cutoff),
+            "resonance" => Some(self.params.resonance),
+            "filter_env" => Some(self.params.filter_env),
+            "drive" => Some(self.params.drive),
+            "normalize" => Some(if self.params.normalize { 1.0 } else { 0.0 }),
+            "trim_silence" => Some(if self.params.trim_silence { 1.0 } else { 0.0 }),
             _ => None,
         }
     }
@@ -603,6 +940,46 @@ impl SoundSource for SamplerSynth {
                 self.params.slice_count = (value.clamp(1.0, 16.0) as u8).max(1);
                 true
             }
+            "sync_bars" => {
+                self.params.sync_bars = value.round().clamp(0.0, MAX_SYNC_BARS as f32) as u8;
+                true
+            }
+            "stretch" => {
+                self.params.stretch = value >= 0.5;
+                true
+            }
+            "filter_mode" => {
+                self.params.filter_mode = value.round().clamp(0.0, 2.0) as u8;
+                self.update_filter();
+                true
+            }
+            "cutoff" => {
+                self.params.cutoff = value.clamp(20.0, 20000.0);
+                self.update_filter();
+                true
+            }
+            "resonance" => {
+                self.params.resonance = value.clamp(0.0, 0.95);
+                self.update_filter();
+                true
+            }
+            "filter_env" => {
+                self.params.filter_env = value.clamp(-1.0, 1.0);
+                true
+            }
+            "drive" => {
+                self.params.drive = value.clamp(0.0, 1.0);
+                true
+            }
+            // Only the flags: loaders re-read the file and send the processed buffer
+            "normalize" => {
+                self.params.normalize = value >= 0.5;
+                true
+            }
+            "trim_silence" => {
+                self.params.trim_silence = value >= 0.5;
+                true
+            }
             _ => false,
         }
     }
@@ -614,28 +991,102 @@ impl SoundSource for SamplerSynth {
     fn deserialize_params(&mut self, params: &Value) {
         if let Ok(p) = serde_json::from_value::<SamplerParams>(params.clone()) {
             self.params = p;
+            self.layer_buffers.resize_with(self.params.layers.len(), Default::default);
+            self.update_filter();
+        }
+    }
+
+    fn load_buffer(&mut self, audio: SampleAudio, path: &str, info: SampleInfo) {
+        match audio {
+            SampleAudio::Memory(buffer) => self.set_buffer(buffer, path, info),
+            SampleAudio::Stream(stream) => self.set_stream(stream, path, info),
+        }
+    }
+
+    fn load_layer_buffer(&mut self, layer: usize, buffer: SampleData, path: &str) {
+        self.set_layer_buffer(layer, buffer, path);
+    }
+
+    fn set_layer_range(&mut self, layer: usize, min_velocity: u8, max_velocity: u8) {
+        if let Some(l) = self.params.layers.get_mut(layer) {
+            l.min_velocity = min_velocity.min(127);
+            l.max_velocity = max_velocity.clamp(l.min_velocity, 127);
         }
     }
 
-    fn load_buffer(&mut self, buffer: Vec<f32>, path: &str) {
-        self.set_buffer(buffer, path);
+    fn remove_layer(&mut self, layer: usize) {
+        if layer < self.params.layers.len() {
+            self.params.layers.remove(layer);
+            if layer < self.layer_buffers.len() {
+                self.layer_buffers.remove(layer);
+            }
+            self.stop();
+        }
+    }
+
+    fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.bpm = bpm;
+    }
+
+    fn is_melodic(&self) -> bool {
+        self.params.slice_count <= 1
+    }
+
+    fn sample_memory_bytes(&self) -> usize {
+        let layers: usize = self.layer_buffers.iter().map(|b| b.len()).sum();
+        let streamed = self.stream.as_ref().map_or(0, |s| s.memory_bytes());
+        (self.buffer.len() + layers) * std::mem::size_of::<f32>() + streamed
+    }
+
+    /// Streamed samples have one reader, so they get no deck B copy
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> {
+        if self.stream.is_some() {
+            return None;
+        }
+        Some(Box::new(self.clone()))
     }
 
     fn step_tick(&mut self) {
-        // Only count steps if we're playing and in attack/decay/sustain phase
+        // Only count steps if we're playing, not held and in attack/decay/sustain phase
         if self.position.is_some()
+            && !self.held
             && self.envelope_phase != EnvelopePhase::Off
             && self.envelope_phase != EnvelopePhase::Release
         {
             self.steps_elapsed += 1;
-            // Check hold_steps countdown
-            if self.steps_elapsed >= self.params.hold_steps as usize {
+            // Check hold_steps countdown; a synced loop holds for its bars
+            let mut hold = self.params.hold_steps as usize;
+            if self.params.sync_bars > 0 {
+                hold = hold.max(self.params.sync_bars as usize * STEPS_PER_BAR);
+            }
+            if self.steps_elapsed >= hold {
                 self.start_release();
             }
         }
     }
 
+    fn hold(&mut self) {
+        self.held = self.position.is_some();
+    }
+
+    fn note_off(&mut self) {
+        if self.held {
+            self.held = false;
+            self.start_release();
+        }
+    }
+
+    fn release(&mut self) {
+        self.held = false;
+        self.start_release();
+    }
+
     fn stop(&mut self) {
+        self.held = false;
         self.position = None;
         self.envelope = 0.0;
         self.envelope_phase = EnvelopePhase::Off;
@@ -643,8 +1094,118 @@ impl SoundSource for SamplerSynth {
     }
 }
 
+/// Range of a buffer between the first and last sample above the silence threshold
+fn silence_bounds(buffer: &[f32]) -> (usize, usize) {
+    let start = buffer.iter().position(|s| s.abs() > SILENCE_THRESHOLD);
+    let end = buffer.iter().rposition(|s| s.abs() > SILENCE_THRESHOLD);
+    match (start, end) {
+        (Some(start), Some(end)) => (start, end + 1),
+        // All silent: keep the buffer as-is
+        _ => (0, buffer.len()),
+    }
+}
+
+/// Processing a sampler applies to its samples when they are loaded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SampleOptions {
+    pub normalize: bool,
+    pub trim_silence: bool,
+}
+
+impl SampleOptions {
+    /// Param keys of the options. They change how a sample is processed
+    /// when it loads, so a step cannot lock them.
+    pub const KEYS: [&'static str; 2] = ["normalize", "trim_silence"];
+
+    /// Options stored in a sampler's params (snapshot or saved project)
+    pub fn from_params(params: &Value) -> Self {
+        let flag = |key: &str| params.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        Self {
+            normalize: flag("normalize"),
+            trim_silence: flag("trim_silence"),
+        }
+    }
+}
+
+/// What the loader detected in a prepared sample, handed to the sampler
+/// with it so the audio thread never analyzes buffers
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SampleInfo {
+    /// Root as a fractional MIDI note
+    pub root_note: Option<f32>,
+    /// Tempo, for loops
+    pub bpm: Option<f32>,
+}
+
+impl SampleInfo {
+    /// Analyze a buffer with its options applied. Runs on the loading thread.
+    pub fn detect(buffer: &[f32], sample_rate: f32) -> Self {
+        Self {
+            root_note: detect_root_note(buffer, sample_rate),
+            bpm: detect_bpm(buffer, sample_rate),
+        }
+    }
+
+    /// What was detected when the sample first loaded, as its params keep it
+    pub fn from_params(params: &Value) -> Self {
+        Self {
+            root_note: params.get("detected_note").and_then(|v| v.as_f64()).map(|n| n as f32),
+            bpm: params.get("detected_bpm").and_then(|v| v.as_f64()).map(|b| b as f32),
+        }
+    }
+}
+
+/// Apply the normalize/trim options to a freshly loaded buffer, in place.
+/// Runs on the loading thread so the audio thread only swaps buffers in.
+pub fn prepare_sample(mut buffer: Vec<f32>, options: SampleOptions) -> Vec<f32> {
+    if options.trim_silence {
+        let (start, end) = silence_bounds(&buffer);
+        buffer.truncate(end);
+        buffer.drain(..start);
+    }
+    if options.normalize {
+        normalize(&mut buffer);
+    }
+    buffer
+}
+
+/// Scale a buffer so its peak sits just under full scale (silent
+/// buffers are left alone)
+pub fn normalize(buffer: &mut [f32]) {
+    let peak = buffer.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+    if peak > SILENCE_THRESHOLD {
+        let gain = NORMALIZE_PEAK / peak;
+        for s in buffer.iter_mut() {
+            *s *= gain;
+        }
+    }
+}
+
+/// Write mono samples as a 32-bit float WAV
+pub fn write_wav(path: &Path, samples: &[f32], sample_rate: u32) -> Result<()> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .with_context(|| format!("Failed to create WAV file: {}", path.display()))?;
+    for &s in samples {
+        writer.write_sample(s)?;
+    }
+    writer.finalize()
+        .with_context(|| format!("Failed to finalize WAV file: {}", path.display()))?;
+    Ok(())
+}
+
 /// Load a WAV file and return mono f32 samples at the target sample rate
 pub fn load_wav(path: &Path, target_sr: f32) -> Result<Vec<f32>> {
+    load_wav_at(path, target_sr, LOAD_QUALITY)
+}
+
+/// Load a WAV file, converting its rate with the given quality
+pub fn load_wav_at(path: &Path, target_sr: f32, quality: Interpolation) -> Result<Vec<f32>> {
     let reader = hound::WavReader::open(path)
         .with_context(|| format!("Failed to open WAV: {}", path.display()))?;
 
@@ -682,21 +1243,124 @@ pub fn load_wav(path: &Path, target_sr: f32) -> Result<Vec<f32>> {
         samples
     };
 
-    // Resample if needed (simple linear interpolation)
-    if (wav_sr - target_sr).abs() > 1.0 {
-        let ratio = wav_sr as f64 / target_sr as f64;
-        let new_len = (mono.len() as f64 / ratio) as usize;
-        let mut resampled = Vec::with_capacity(new_len);
-        for i in 0..new_len {
-            let pos = i as f64 * ratio;
-            let idx = pos as usize;
-            let frac = (pos - idx as f64) as f32;
-            let s0 = mono.get(idx).copied().unwrap_or(0.0);
-            let s1 = mono.get(idx + 1).copied().unwrap_or(s0);
-            resampled.push(s0 + (s1 - s0) * frac);
-        }
-        Ok(resampled)
-    } else {
-        Ok(mono)
+    Ok(resample(mono, wav_sr, target_sr, quality))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn padded_hit() -> Vec<f32> {
+        let mut buffer = vec![0.0; 100];
+        buffer.extend([0.5, -0.25, 0.1]);
+        buffer.extend(vec![0.0; 50]);
+        buffer
+    }
+
+    #[test]
+    fn prepare_trims_and_normalizes() {
+        let options = SampleOptions {
+            normalize: true,
+            trim_silence: true,
+        };
+        let buffer = prepare_sample(padded_hit(), options);
+        assert_eq!(buffer.len(), 3);
+        assert!((buffer[0] - NORMALIZE_PEAK).abs() < 1e-6);
+        assert!((buffer[1] + NORMALIZE_PEAK / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn prepare_without_options_keeps_the_buffer() {
+        assert_eq!(prepare_sample(padded_hit(), SampleOptions::default()), padded_hit());
+    }
+
+    #[test]
+    fn sampler_plays_the_buffer_it_is_given() {
+        let mut sampler = SamplerSynth::new(44100.0);
+        sampler.set_buffer(vec![0.5; 64].into(), "hit.wav", SampleInfo::default());
+        assert!(sampler.set_param("normalize", 1.0));
+        assert_eq!(*sampler.buffer, [0.5; 64]);
+        assert_eq!(sampler.sample_memory_bytes(), 64 * std::mem::size_of::<f32>());
+    }
+
+    #[test]
+    fn sliced_samplers_are_not_transposed() {
+        let mut sampler = SamplerSynth::new(44100.0);
+        assert!(sampler.is_melodic());
+        assert!(sampler.set_param("slice_count", 8.0));
+        assert!(!sampler.is_melodic());
+    }
+
+    #[test]
+    fn voice_copies_share_the_sample_data() {
+        let mut sampler = SamplerSynth::new(44100.0);
+        sampler.set_buffer(vec![0.5; 64].into(), "hit.wav", SampleInfo::default());
+        sampler.set_layer_buffer(0, vec![0.25; 32].into(), "soft.wav");
+        let copy = sampler.clone();
+        assert!(Arc::ptr_eq(&sampler.buffer, &copy.buffer));
+        assert!(Arc::ptr_eq(&sampler.layer_buffers[0], &copy.layer_buffers[0]));
+    }
+
+    const RATE: f32 = 8000.0;
+
+    /// A sampler holding two seconds of a sine, synced to one bar
+    fn synced_sine(freq: f32, stretch: bool) -> SamplerSynth {
+        let mut sampler = SamplerSynth::new(RATE);
+        let buffer: SampleData = (0..2 * RATE as usize)
+            .map(|i| (std::f32::consts::TAU * freq * i as f32 / RATE).sin())
+            .collect();
+        sampler.set_buffer(buffer, "loop.wav", SampleInfo::default());
+        assert!(sampler.set_param("sync_bars", 1.0));
+        assert!(sampler.set_param("stretch", if stretch { 1.0 } else { 0.0 }));
+        sampler
+    }
+
+    /// Samples from the trigger until the region has played out
+    fn played_length(sampler: &mut SamplerSynth) -> usize {
+        sampler.trigger();
+        let mut len = 0;
+        while sampler.position.is_some() {
+            sampler.next_sample();
+            len += 1;
+        }
+        len
+    }
+
+    /// Frequency from the upward zero crossings
+    fn frequency(buffer: &[f32]) -> f32 {
+        let crossings: Vec<usize> = (1..buffer.len())
+            .filter(|&i| buffer[i - 1] < 0.0 && buffer[i] >= 0.0)
+            .collect();
+        let cycles = crossings.len() - 1;
+        cycles as f32 * RATE / (crossings[cycles] - crossings[0]) as f32
+    }
+
+    #[test]
+    fn synced_region_lasts_its_bars_at_the_tempo() {
+        for stretch in [false, true] {
+            let mut sampler = synced_sine(200.0, stretch);
+            // A bar at 120 BPM is the two seconds the loop already lasts
+            sampler.set_tempo(120.0);
+            assert_eq!(played_length(&mut sampler), 16000);
+            sampler.set_tempo(90.0);
+            let len = played_length(&mut sampler);
+            assert!(len.abs_diff(21333) <= 1, "{} samples at 90 BPM", len);
+        }
+    }
+
+    #[test]
+    fn stretch_keeps_the_pitch_and_repitch_does_not() {
+        // At 60 BPM the bar takes twice as long as the loop
+        let output = |stretch| {
+            let mut sampler = synced_sine(200.0, stretch);
+            sampler.set_tempo(60.0);
+            sampler.trigger();
+            let out: Vec<f32> = (0..RATE as usize).map(|_| sampler.next_sample()).collect();
+            frequency(&out)
+        };
+        let repitched = output(false);
+        let stretched = output(true);
+        assert!((repitched - 100.0).abs() < 2.0, "repitched to {} Hz", repitched);
+        assert!((stretched - 200.0).abs() < 4.0, "stretched to {} Hz", stretched);
     }
 }
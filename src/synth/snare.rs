@@ -5,6 +5,7 @@ use super::source::{ParamDescriptor, SoundSource, SynthType};
 
 /// Snare drum synthesizer
 /// Mix of noise burst and body tone with fast decay
+#[derive(Clone)]
 pub struct SnareSynth {
     phase: Option<usize>,
     sample_rate: f32,
@@ -113,6 +114,7 @@ impl SoundSource for SnareSynth {
     fn synth_type(&self) -> SynthType { SynthType::Snare }
     fn type_name(&self) -> &'static str { "SNARE" }
     fn default_note(&self) -> u8 { DEFAULT_NOTES[1] }
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> { Some(Box::new(self.clone())) }
     fn trigger(&mut self) { self.trigger(); }
     fn trigger_with_note(&mut self, note: u8) { self.trigger_with_note(note); }
     fn set_velocity_scale(&mut self, velocity: u8) { self.set_velocity(velocity); }
@@ -1,11 +1,21 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::acid::AcidSynth;
 use super::bass::BassSynth;
+use super::external::{ExternalConnection, ExternalSynth};
 use super::hihat::HiHatSynth;
+use super::input::InputSource;
+use super::resample::Interpolation;
 use super::kick::KickSynth;
-use super::sampler::SamplerSynth;
+use super::sampler::{SampleData, SampleInfo, SampleOptions, SamplerSynth};
+use super::stream::SampleAudio;
 use super::snare::SnareSynth;
+use super::stab::StabSynth;
+use super::tone::ToneGenerator;
 
 /// Identifies the type of synthesizer
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -15,6 +25,11 @@ pub enum SynthType {
     HiHat,
     Bass,
     Sampler,
+    Input,
+    External,
+    Tone,
+    Stab,
+    Acid,
 }
 
 impl SynthType {
@@ -25,6 +40,11 @@ impl SynthType {
             SynthType::HiHat => "hihat",
             SynthType::Bass => "bass",
             SynthType::Sampler => "sampler",
+            SynthType::Input => "input",
+            SynthType::External => "external",
+            SynthType::Tone => "tone",
+            SynthType::Stab => "stab",
+            SynthType::Acid => "acid",
         }
     }
 
@@ -35,9 +55,39 @@ impl SynthType {
             SynthType::HiHat => "HIHAT",
             SynthType::Bass => "BASS",
             SynthType::Sampler => "SAMPLER",
+            SynthType::Input => "INPUT",
+            SynthType::External => "EXTERNAL",
+            SynthType::Tone => "TONE",
+            SynthType::Stab => "STAB",
+            SynthType::Acid => "ACID",
+        }
+    }
+
+    /// Short marker shown before track names when the track has no glyph
+    /// of its own
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            SynthType::Kick => "K",
+            SynthType::Snare => "S",
+            SynthType::HiHat => "H",
+            SynthType::Bass => "B",
+            SynthType::Sampler => "Sm",
+            SynthType::Input => "In",
+            SynthType::External => "Ex",
+            SynthType::Tone => "~",
+            SynthType::Stab => "St",
+            SynthType::Acid => "Ac",
         }
     }
 
+    /// Whether step notes set the pitch (and follow the global transpose)
+    pub fn is_melodic(&self) -> bool {
+        matches!(
+            self,
+            SynthType::Bass | SynthType::Sampler | SynthType::External | SynthType::Stab | SynthType::Acid
+        )
+    }
+
     pub fn from_name(name: &str) -> Option<SynthType> {
         match name {
             "kick" => Some(SynthType::Kick),
@@ -45,6 +95,11 @@ impl SynthType {
             "hihat" => Some(SynthType::HiHat),
             "bass" => Some(SynthType::Bass),
             "sampler" => Some(SynthType::Sampler),
+            "input" => Some(SynthType::Input),
+            "external" => Some(SynthType::External),
+            "tone" => Some(SynthType::Tone),
+            "stab" => Some(SynthType::Stab),
+            "acid" => Some(SynthType::Acid),
             _ => None,
         }
     }
@@ -60,6 +115,13 @@ pub struct ParamDescriptor {
     pub default: f32,
 }
 
+impl ParamDescriptor {
+    /// Whether a step can lock this param (sample load options can't)
+    pub fn lockable(&self) -> bool {
+        !SampleOptions::KEYS.contains(&self.key.as_str())
+    }
+}
+
 /// Trait for all sound sources in gridoxide.
 /// Must be Send for use on the audio thread.
 pub trait SoundSource: Send {
@@ -109,13 +171,99 @@ pub trait SoundSource: Send {
     fn deserialize_params(&mut self, params: &Value);
 
     /// Load a sample buffer into this synth (only used by SamplerSynth, no-op for others)
-    fn load_buffer(&mut self, _buffer: Vec<f32>, _path: &str) {}
+    fn load_buffer(&mut self, _audio: SampleAudio, _path: &str, _info: SampleInfo) {}
+
+    /// Load a velocity layer sample; a `layer` past the end adds one (SamplerSynth only)
+    fn load_layer_buffer(&mut self, _layer: usize, _buffer: SampleData, _path: &str) {}
+
+    /// Set the velocity range a sample layer plays for (SamplerSynth only)
+    fn set_layer_range(&mut self, _layer: usize, _min_velocity: u8, _max_velocity: u8) {}
+
+    /// Remove a sample layer (SamplerSynth only)
+    fn remove_layer(&mut self, _layer: usize) {}
+
+    /// Set buffer read interpolation (only meaningful for SamplerSynth)
+    fn set_interpolation(&mut self, _interpolation: Interpolation) {}
+
+    /// Project tempo, sent as it changes (only used by SamplerSynth, for
+    /// tempo-synced loops)
+    fn set_tempo(&mut self, _bpm: f32) {}
+
+    /// Whether step notes set this source's pitch right now (and follow the
+    /// global transpose). Sliced samplers use the note to pick a slice.
+    fn is_melodic(&self) -> bool {
+        self.synth_type().is_melodic()
+    }
+
+    /// Bytes of sample data held by this synth (only non-zero for SamplerSynth)
+    fn sample_memory_bytes(&self) -> usize {
+        0
+    }
+
+    /// A second voice with the same sound and samples, for deck B of the
+    /// crossfader. None for sources that can't be copied (input, external).
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> {
+        None
+    }
 
     /// Called on each sequencer step tick. Used by samplers for hold_steps countdown.
     fn step_tick(&mut self) {}
 
+    /// Hold the note just triggered at full level until note_off, for the
+    /// steps tied onto it. Sources without a sustain ignore it.
+    fn hold(&mut self) {}
+
+    /// Slide and accent flags of the step about to trigger. Only the acid
+    /// synth uses them.
+    fn set_articulation(&mut self, _slide: bool, _accent: bool) {}
+
+    /// Release a held note (the end of a run of tied steps)
+    fn note_off(&mut self) {}
+
+    /// Release the sounding note now, held or not (a note-off step).
+    /// Sources with no release fade out quickly; drum synths ignore it.
+    fn release(&mut self) {}
+
     /// Stop/silence this synth immediately. Used when transport stops.
     fn stop(&mut self) {}
+
+    /// Feed the current hardware input sample (only used by InputSource)
+    fn set_input(&mut self, _sample: f32) {}
+
+    /// Attach a running plugin process (only used by ExternalSynth)
+    fn connect_external(&mut self, _connection: ExternalConnection) {}
+}
+
+/// A parked project's synths, handed back to the engine with it so its
+/// samples aren't read again and its plugins keep running. Clones share
+/// the synths; the first `take` gets them.
+#[derive(Clone, Default)]
+pub struct ParkedSynths(Arc<Mutex<Vec<Box<dyn SoundSource>>>>);
+
+impl ParkedSynths {
+    pub fn new(synths: Vec<Box<dyn SoundSource>>) -> Self {
+        Self(Arc::new(Mutex::new(synths)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Take the synths out (none if already taken)
+    pub fn take(&self) -> Vec<Box<dyn SoundSource>> {
+        self.0.try_lock().map(|mut synths| std::mem::take(&mut *synths)).unwrap_or_default()
+    }
+}
+
+impl std::fmt::Debug for ParkedSynths {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.0.try_lock().map_or(0, |synths| synths.len());
+        write!(f, "ParkedSynths({})", count)
+    }
 }
 
 /// Factory function: create a synth from its type, sample rate, and optional saved params
@@ -130,6 +278,11 @@ pub fn create_synth(
         SynthType::HiHat => Box::new(HiHatSynth::new(sample_rate)),
         SynthType::Bass => Box::new(BassSynth::new(sample_rate)),
         SynthType::Sampler => Box::new(SamplerSynth::new(sample_rate)),
+        SynthType::Input => Box::new(InputSource::new()),
+        SynthType::External => Box::new(ExternalSynth::new()),
+        SynthType::Tone => Box::new(ToneGenerator::new(sample_rate)),
+        SynthType::Stab => Box::new(StabSynth::new(sample_rate)),
+        SynthType::Acid => Box::new(AcidSynth::new(sample_rate)),
     };
     if let Some(params) = params_json {
         synth.deserialize_params(params);
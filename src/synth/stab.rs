@@ -0,0 +1,294 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::params::midi_to_freq;
+use super::source::{ParamDescriptor, SoundSource, SynthType};
+use crate::fx::SvfFilter;
+
+/// Chord types for the "chord" param (index), as semitones above the root
+const CHORDS: [(&str, &[u8]); 10] = [
+    ("maj", &[0, 4, 7]),
+    ("min", &[0, 3, 7]),
+    ("7", &[0, 4, 7, 10]),
+    ("maj7", &[0, 4, 7, 11]),
+    ("min7", &[0, 3, 7, 10]),
+    ("min9", &[0, 3, 7, 10, 14]),
+    ("add9", &[0, 4, 7, 14]),
+    ("sus2", &[0, 2, 7]),
+    ("sus4", &[0, 5, 7]),
+    ("dim", &[0, 3, 6]),
+];
+/// Most notes a chord has
+const MAX_VOICES: usize = 5;
+/// Attack time, short enough to punch without clicking (seconds)
+const ATTACK_SECS: f32 = 0.002;
+/// Samples between filter cutoff updates
+const FILTER_UPDATE: usize = 16;
+/// Decay times reach -40 dB (ln 100)
+const DECAY_CURVE: f32 = 4.6;
+/// Fade-out when a note-off step cuts a chord short (seconds)
+const RELEASE_SECS: f32 = 0.01;
+
+/// Chord name for a "chord" param value, e.g. "min7"
+pub fn chord_name(value: f32) -> &'static str {
+    CHORDS[chord_index(value)].0
+}
+
+fn chord_index(value: f32) -> usize {
+    (value.round().max(0.0) as usize).min(CHORDS.len() - 1)
+}
+
+/// Notes of a chord on `root`: inversion n moves the lowest n notes up an octave
+fn chord_notes(root: u8, chord: f32, inversion: f32) -> Vec<u8> {
+    let intervals = CHORDS[chord_index(chord)].1;
+    let inversion = (inversion.round().max(0.0) as usize).min(intervals.len() - 1);
+    intervals
+        .iter()
+        .enumerate()
+        .map(|(i, &interval)| {
+            let octave = if i < inversion { 12 } else { 0 };
+            root.saturating_add(interval + octave).min(127)
+        })
+        .collect()
+}
+
+/// Stab synth parameters
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StabParams {
+    /// Index into CHORDS (0-9, rounded when played)
+    pub chord: f32,
+    /// Notes moved up an octave (0-3, rounded when played)
+    pub inversion: f32,
+    /// Oscillator shape: 0 = saw, 1 = square
+    pub shape: f32,
+    /// Spread of each note's second oscillator, in cents (0-30)
+    pub detune: f32,
+    /// Low-pass cutoff (100-10000 Hz)
+    pub cutoff: f32,
+    /// Filter resonance (0.0-0.9)
+    pub resonance: f32,
+    /// Filter envelope amount (0.0-1.0, up to +4 octaves)
+    pub env_amount: f32,
+    /// Filter envelope decay (0.02-1.0 s)
+    pub filter_decay: f32,
+    /// Amp decay (0.05-2.0 s)
+    pub decay: f32,
+    /// Output level (0.0-1.0)
+    pub level: f32,
+}
+
+impl Default for StabParams {
+    fn default() -> Self {
+        Self {
+            chord: 4.0,
+            inversion: 0.0,
+            shape: 0.0,
+            detune: 8.0,
+            cutoff: 800.0,
+            resonance: 0.3,
+            env_amount: 0.6,
+            filter_decay: 0.15,
+            decay: 0.3,
+            level: 0.6,
+        }
+    }
+}
+
+/// Chord stab synth: each trigger plays a chord on the step's note, two
+/// detuned saw/square oscillators per note through a low-pass with its
+/// own decay envelope
+#[derive(Clone)]
+pub struct StabSynth {
+    params: StabParams,
+    sample_rate: f32,
+    /// Samples since the trigger (None = silent)
+    phase: Option<usize>,
+    /// Oscillator frequencies and phases, two per chord note
+    freqs: Vec<f32>,
+    osc_phases: Vec<f32>,
+    filter: SvfFilter,
+    velocity_scale: f32,
+    /// Held by tied steps: the envelopes stay at their peak until note_off
+    held: bool,
+    /// Samples left in the fade-out after a note-off step
+    releasing: Option<usize>,
+}
+
+impl StabSynth {
+    pub fn new(sample_rate: f32) -> Self {
+        let params = StabParams::default();
+        let mut filter = SvfFilter::new(sample_rate);
+        filter.set_cutoff(params.cutoff);
+        filter.set_resonance(params.resonance);
+        Self {
+            params,
+            sample_rate,
+            phase: None,
+            freqs: Vec::with_capacity(MAX_VOICES * 2),
+            osc_phases: Vec::with_capacity(MAX_VOICES * 2),
+            filter,
+            velocity_scale: 1.0,
+            held: false,
+            releasing: None,
+        }
+    }
+
+    fn start(&mut self, root: u8) {
+        let spread = 2.0f32.powf(self.params.detune / 1200.0);
+        self.freqs.clear();
+        self.osc_phases.clear();
+        for (i, note) in chord_notes(root, self.params.chord, self.params.inversion).into_iter().enumerate() {
+            let freq = midi_to_freq(note);
+            self.freqs.extend([freq * spread, freq / spread]);
+            // Staggered start phases keep the chord from peaking on its first sample
+            self.osc_phases.extend([i as f32 * 0.37 % 1.0, i as f32 * 0.61 % 1.0]);
+        }
+        self.phase = Some(0);
+        self.held = false;
+        self.releasing = None;
+    }
+}
+
+impl SoundSource for StabSynth {
+    fn synth_type(&self) -> SynthType { SynthType::Stab }
+    fn type_name(&self) -> &'static str { "STAB" }
+    fn default_note(&self) -> u8 { 60 }
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> { Some(Box::new(self.clone())) }
+    fn trigger(&mut self) { self.start(self.default_note()); }
+    fn trigger_with_note(&mut self, note: u8) { self.start(note); }
+
+    fn set_velocity_scale(&mut self, velocity: u8) {
+        self.velocity_scale = velocity as f32 / 127.0;
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let Some(phase) = self.phase else {
+            return 0.0;
+        };
+        let t = phase as f32 / self.sample_rate;
+        // A held chord stops its envelopes at the end of the attack
+        let decay_t = (t - ATTACK_SECS).max(0.0);
+        let amp = if t < ATTACK_SECS {
+            t / ATTACK_SECS
+        } else {
+            (-decay_t * DECAY_CURVE / self.params.decay).exp()
+        };
+        if (t >= ATTACK_SECS && amp < 0.0005 && !self.held) || self.releasing == Some(0) {
+            self.phase = None;
+            self.releasing = None;
+            return 0.0;
+        }
+        let fade = match self.releasing.as_mut() {
+            Some(left) => {
+                *left -= 1;
+                *left as f32 / (RELEASE_SECS * self.sample_rate)
+            }
+            None => 1.0,
+        };
+        if !(self.held && t >= ATTACK_SECS) {
+            self.phase = Some(phase + 1);
+        }
+
+        if phase % FILTER_UPDATE == 0 {
+            let env = (-decay_t * DECAY_CURVE / self.params.filter_decay).exp();
+            let octaves = self.params.env_amount * 4.0 * env;
+            self.filter.set_cutoff(self.params.cutoff * 2.0f32.powf(octaves));
+        }
+
+        let square = self.params.shape;
+        let mut sum = 0.0;
+        for (osc_phase, freq) in self.osc_phases.iter_mut().zip(&self.freqs) {
+            *osc_phase = (*osc_phase + freq / self.sample_rate).fract();
+            let saw = *osc_phase * 2.0 - 1.0;
+            let pulse = if *osc_phase < 0.5 { 1.0 } else { -1.0 };
+            sum += saw * (1.0 - square) + pulse * square;
+        }
+        // Louder chords are scaled back so every chord type sits at the same level
+        let voices = self.freqs.len().max(1) as f32;
+        let out = self.filter.process(sum / voices.sqrt());
+        out * amp * fade * self.params.level * self.velocity_scale * 0.5
+    }
+
+    fn hold(&mut self) {
+        self.held = self.phase.is_some();
+    }
+
+    fn note_off(&mut self) {
+        self.held = false;
+    }
+
+    fn release(&mut self) {
+        self.held = false;
+        if self.phase.is_some() && self.releasing.is_none() {
+            self.releasing = Some((RELEASE_SECS * self.sample_rate) as usize);
+        }
+    }
+
+    fn stop(&mut self) {
+        self.phase = None;
+        self.held = false;
+        self.releasing = None;
+    }
+
+    fn param_descriptors(&self) -> Vec<ParamDescriptor> {
+        vec![
+            ParamDescriptor { key: "chord".into(), name: "Chord".into(), min: 0.0, max: (CHORDS.len() - 1) as f32, default: 4.0 },
+            ParamDescriptor { key: "inversion".into(), name: "Inversion".into(), min: 0.0, max: 3.0, default: 0.0 },
+            ParamDescriptor { key: "shape".into(), name: "Saw/Square".into(), min: 0.0, max: 1.0, default: 0.0 },
+            ParamDescriptor { key: "detune".into(), name: "Detune".into(), min: 0.0, max: 30.0, default: 8.0 },
+            ParamDescriptor { key: "cutoff".into(), name: "Cutoff".into(), min: 100.0, max: 10000.0, default: 800.0 },
+            ParamDescriptor { key: "resonance".into(), name: "Resonance".into(), min: 0.0, max: 0.9, default: 0.3 },
+            ParamDescriptor { key: "env_amount".into(), name: "Filter Env".into(), min: 0.0, max: 1.0, default: 0.6 },
+            ParamDescriptor { key: "filter_decay".into(), name: "Filt Decay".into(), min: 0.02, max: 1.0, default: 0.15 },
+            ParamDescriptor { key: "decay".into(), name: "Decay".into(), min: 0.05, max: 2.0, default: 0.3 },
+            ParamDescriptor { key: "level".into(), name: "Level".into(), min: 0.0, max: 1.0, default: 0.6 },
+        ]
+    }
+
+    fn get_param(&self, key: &str) -> Option<f32> {
+        match key {
+            "chord" => Some(self.params.chord),
+            "inversion" => Some(self.params.inversion),
+            "shape" => Some(self.params.shape),
+            "detune" => Some(self.params.detune),
+            "cutoff" => Some(self.params.cutoff),
+            "resonance" => Some(self.params.resonance),
+            "env_amount" => Some(self.params.env_amount),
+            "filter_decay" => Some(self.params.filter_decay),
+            "decay" => Some(self.params.decay),
+            "level" => Some(self.params.level),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, key: &str, value: f32) -> bool {
+        match key {
+            "chord" => { self.params.chord = value.clamp(0.0, (CHORDS.len() - 1) as f32); true }
+            "inversion" => { self.params.inversion = value.clamp(0.0, 3.0); true }
+            "shape" => { self.params.shape = value.clamp(0.0, 1.0); true }
+            "detune" => { self.params.detune = value.clamp(0.0, 30.0); true }
+            "cutoff" => { self.params.cutoff = value.clamp(100.0, 10000.0); true }
+            "resonance" => {
+                self.params.resonance = value.clamp(0.0, 0.9);
+                self.filter.set_resonance(self.params.resonance);
+                true
+            }
+            "env_amount" => { self.params.env_amount = value.clamp(0.0, 1.0); true }
+            "filter_decay" => { self.params.filter_decay = value.clamp(0.02, 1.0); true }
+            "decay" => { self.params.decay = value.clamp(0.05, 2.0); true }
+            "level" => { self.params.level = value.clamp(0.0, 1.0); true }
+            _ => false,
+        }
+    }
+
+    fn serialize_params(&self) -> Value {
+        serde_json::to_value(&self.params).unwrap_or(Value::Null)
+    }
+
+    fn deserialize_params(&mut self, params: &Value) {
+        if let Ok(p) = serde_json::from_value::<StabParams>(params.clone()) {
+            self.filter.set_resonance(p.resonance);
+            self.params = p;
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use tracing::warn;
+
+use super::resample::Interpolation;
+use super::sampler::SampleData;
+
+/// Frames in one window read from disk (about 1.5 s at 44.1 kHz)
+const STREAM_WINDOW: usize = 1 << 16;
+/// Windows start every STEP frames, so each overlaps the next by half and
+/// the read-ahead has half a window of playback to arrive
+const STEP: usize = STREAM_WINDOW / 2;
+/// Frames kept on either side of a read for interpolation (sinc reads the
+/// widest neighbourhood)
+const MARGIN: usize = 128;
+/// Read windows per stream, besides the resident first one
+const WINDOWS: usize = 3;
+
+/// Numbers the cache files of one process
+static NEXT_CACHE: AtomicUsize = AtomicUsize::new(0);
+
+/// A stretch of a streamed sample held in memory
+#[derive(Clone)]
+struct Window {
+    start: usize,
+    data: Vec<f32>,
+}
+
+impl Window {
+    fn with_capacity(frames: usize) -> Self {
+        Self { start: 0, data: Vec::with_capacity(frames) }
+    }
+
+    /// Whether a read at `frame` and its interpolation neighbours fall
+    /// inside (edges of the sample itself count as covered)
+    fn covers(&self, frame: usize, len: usize) -> bool {
+        let end = self.start + self.data.len();
+        !self.data.is_empty()
+            && (self.start == 0 || frame >= self.start + MARGIN)
+            && frame >= self.start
+            && (end == len || frame + MARGIN < end)
+    }
+}
+
+/// A sample played from a cache file on disk instead of memory. A reader
+/// thread fills fixed windows ahead of the play position in the direction
+/// it moves; the first window stays resident so hits from the start play
+/// at once. Reads the read-ahead can't predict (a trigger mid-sample, a
+/// loop wrap) are silent until their window arrives, counted in
+/// `underruns`. Built on the loading side with `SampleStream::open`; the
+/// reader exits and removes the cache file once the stream is dropped.
+/// Clones share the reader, so only one of them may play.
+#[derive(Clone)]
+pub struct SampleStream {
+    len: usize,
+    head: Arc<Window>,
+    /// Loaded or empty read windows; the one the reader is filling is None
+    windows: [Option<Window>; WINDOWS],
+    requests: Sender<Window>,
+    filled: Receiver<Window>,
+    underruns: usize,
+}
+
+impl std::fmt::Debug for SampleStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SampleStream({} frames)", self.len)
+    }
+}
+
+impl SampleStream {
+    /// Write `buffer` to a cache file and start its reader thread. The
+    /// buffer is only needed until this returns.
+    pub fn open(buffer: &[f32]) -> Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "gridoxide-stream-{}-{}.f32",
+            std::process::id(),
+            NEXT_CACHE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let write = || -> Result<File> {
+            let mut out = BufWriter::new(File::create(&path)?);
+            for sample in buffer {
+                out.write_all(&sample.to_le_bytes())?;
+            }
+            out.flush()?;
+            Ok(File::open(&path)?)
+        };
+        let file = match write() {
+            Ok(file) => file,
+            Err(e) => {
+                let _ = std::fs::remove_file(&path);
+                return Err(e).with_context(|| format!("Failed to write stream cache {}", path.display()));
+            }
+        };
+
+        let len = buffer.len();
+        let head = Window {
+            start: 0,
+            data: buffer[..len.min(STREAM_WINDOW)].to_vec(),
+        };
+        let (requests, request_rx) = bounded::<Window>(1);
+        let (filled_tx, filled) = bounded::<Window>(1);
+        std::thread::Builder::new()
+            .name("sample-stream".to_string())
+            .spawn(move || read_windows(file, path, len, request_rx, filled_tx))
+            .context("Failed to start the sample stream reader")?;
+
+        Ok(Self {
+            len,
+            head: Arc::new(head),
+            windows: std::array::from_fn(|_| Some(Window::with_capacity(STREAM_WINDOW))),
+            requests,
+            filled,
+            underruns: 0,
+        })
+    }
+
+    /// Frames in the whole sample
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bytes of sample data held in memory (the resident first window and
+    /// the read windows)
+    pub fn memory_bytes(&self) -> usize {
+        STREAM_WINDOW * (WINDOWS + 1) * std::mem::size_of::<f32>()
+    }
+
+    /// Reads that found no window loaded and played silence
+    pub fn underruns(&self) -> usize {
+        self.underruns
+    }
+
+    /// Read at `pos` like `Interpolation::read` on the whole sample, moving
+    /// the read-ahead with the sign of `rate`. Doesn't block or allocate.
+    pub fn read(&mut self, interpolation: Interpolation, pos: f64, rate: f64) -> f32 {
+        if let Ok(window) = self.filled.try_recv() {
+            if let Some(slot) = self.windows.iter_mut().find(|w| w.is_none()) {
+                *slot = Some(window);
+            }
+        }
+        let frame = pos.max(0.0) as usize;
+        let forward = rate >= 0.0;
+        let wanted = window_start(frame, forward);
+
+        // The window with the most room ahead, or any holding the position
+        let out = self
+            .find(|w| w.start == wanted && w.covers(frame, self.len))
+            .or_else(|| self.find(|w| w.covers(frame, self.len)))
+            .map(|w| interpolation.read(&w.data, pos - w.start as f64, rate));
+        let Some(out) = out else {
+            self.underruns += 1;
+            self.request(wanted, frame);
+            return 0.0;
+        };
+
+        // Read ahead: the window after this one in the direction of play
+        let next = if forward { wanted + STEP } else { wanted.checked_sub(STEP).unwrap_or(wanted) };
+        if next < self.len {
+            self.request(next, frame);
+        }
+        out
+    }
+
+    /// First loaded window (the resident one included) matching `pred`
+    fn find(&self, pred: impl Fn(&Window) -> bool) -> Option<&Window> {
+        std::iter::once(self.head.as_ref())
+            .chain(self.windows.iter().flatten())
+            .find(|w| !w.data.is_empty() && pred(w))
+    }
+
+    /// Ask the reader for the window at `start`, reusing the window
+    /// farthest from `frame`, unless it's loaded or the reader is busy
+    fn request(&mut self, start: usize, frame: usize) {
+        let busy = self.windows.iter().any(|w| w.is_none());
+        if busy || self.find(|w| w.start == start).is_some() {
+            return;
+        }
+        let len = self.len;
+        let victim = self
+            .windows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, w)| Some((i, w.as_ref()?)))
+            .filter(|(_, w)| !w.covers(frame, len) || w.data.is_empty())
+            .max_by_key(|(_, w)| if w.data.is_empty() { usize::MAX } else { w.start.abs_diff(frame) })
+            .map(|(i, _)| i);
+        let Some(mut window) = victim.and_then(|i| self.windows[i].take()) else {
+            return;
+        };
+        window.start = start;
+        if let Err(e) = self.requests.try_send(window) {
+            if let Some(slot) = self.windows.iter_mut().find(|w| w.is_none()) {
+                *slot = Some(e.into_inner());
+            }
+        }
+    }
+}
+
+/// First frame of the window a read at `frame` should come from: the one
+/// with the most room ahead in the direction of play
+fn window_start(frame: usize, forward: bool) -> usize {
+    let step = frame / STEP;
+    if forward {
+        step * STEP
+    } else {
+        step.saturating_sub(1) * STEP
+    }
+}
+
+/// Reader thread: fill each requested window from the cache file and send
+/// it back, until the stream is dropped
+fn read_windows(mut file: File, path: PathBuf, len: usize, requests: Receiver<Window>, filled: Sender<Window>) {
+    let mut bytes = vec![0u8; STREAM_WINDOW * std::mem::size_of::<f32>()];
+    for mut window in requests {
+        let frames = STREAM_WINDOW.min(len.saturating_sub(window.start));
+        let bytes = &mut bytes[..frames * std::mem::size_of::<f32>()];
+        let read = file
+            .seek(SeekFrom::Start((window.start * std::mem::size_of::<f32>()) as u64))
+            .and_then(|_| file.read_exact(bytes));
+        window.data.clear();
+        match read {
+            Ok(()) => window.data.extend(
+                bytes
+                    .chunks_exact(std::mem::size_of::<f32>())
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            ),
+            Err(e) => warn!(path = %path.display(), "Failed to read streamed sample: {}", e),
+        }
+        if filled.send(window).is_err() {
+            break;
+        }
+    }
+    drop(file);
+    let _ = std::fs::remove_file(&path);
+}
+
+/// A sample as a loader hands it to a sampler
+#[derive(Clone, Debug)]
+pub enum SampleAudio {
+    /// Held in memory
+    Memory(SampleData),
+    /// Played from disk (larger than the project's streaming size)
+    Stream(SampleStream),
+}
+
+impl SampleAudio {
+    /// Keep `buffer` in memory, or stream it from disk when it's larger
+    /// than `stream_above_mb` (0 = never). Falls back to memory when the
+    /// cache file can't be written.
+    pub fn new(buffer: Vec<f32>, stream_above_mb: f32) -> Self {
+        let bytes = buffer.len() * std::mem::size_of::<f32>();
+        if stream_above_mb > 0.0 && bytes as f32 > stream_above_mb * 1024.0 * 1024.0 {
+            match SampleStream::open(&buffer) {
+                Ok(stream) => return SampleAudio::Stream(stream),
+                Err(e) => warn!("Keeping sample in memory: {:#}", e),
+            }
+        }
+        SampleAudio::Memory(buffer.into())
+    }
+}
+
+impl SampleAudio {
+    /// Bytes of sample data this keeps in memory
+    pub fn memory_bytes(&self) -> usize {
+        match self {
+            SampleAudio::Memory(buffer) => buffer.len() * std::mem::size_of::<f32>(),
+            SampleAudio::Stream(stream) => stream.memory_bytes(),
+        }
+    }
+}
+
+impl From<Vec<f32>> for SampleAudio {
+    fn from(buffer: Vec<f32>) -> Self {
+        SampleAudio::Memory(buffer.into())
+    }
+}
+
+impl From<SampleData> for SampleAudio {
+    fn from(buffer: SampleData) -> Self {
+        SampleAudio::Memory(buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Read the whole sample at rate 1 in one direction, waiting for each
+    /// window the first time it underruns
+    fn play(stream: &mut SampleStream, frames: impl Iterator<Item = usize>, rate: f64) -> Vec<f32> {
+        let mut out = Vec::new();
+        for frame in frames {
+            let mut sample = stream.read(Interpolation::Linear, frame as f64, rate);
+            let mut tries = 0;
+            while stream.underruns() > 0 && tries < 1000 {
+                stream.underruns = 0;
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                sample = stream.read(Interpolation::Linear, frame as f64, rate);
+                tries += 1;
+            }
+            out.push(sample);
+        }
+        out
+    }
+
+    #[test]
+    fn streams_the_sample_forwards_and_backwards() {
+        let len = STREAM_WINDOW * 3 + 1234;
+        let buffer: Vec<f32> = (0..len).map(|i| (i % 1000) as f32 / 1000.0).collect();
+        let mut stream = SampleStream::open(&buffer).unwrap();
+        assert_eq!(stream.len(), len);
+
+        assert_eq!(play(&mut stream, 0..len, 1.0), buffer);
+        let mut reversed = buffer.clone();
+        reversed.reverse();
+        assert_eq!(play(&mut stream, (0..len).rev(), -1.0), reversed);
+    }
+
+    #[test]
+    fn reads_ahead_of_forward_playback() {
+        let len = STREAM_WINDOW * 4;
+        let buffer: Vec<f32> = (0..len).map(|i| i as f32).collect();
+        let mut stream = SampleStream::open(&buffer).unwrap();
+        // Real time would give the reader far longer than this per window
+        for frame in 0..len {
+            stream.read(Interpolation::Linear, frame as f64, 1.0);
+            if frame % STEP == 0 {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+        }
+        assert_eq!(stream.underruns(), 0);
+    }
+
+    #[test]
+    fn small_samples_stay_in_memory() {
+        let audio = SampleAudio::new(vec![0.0; 1024], 1.0);
+        assert!(matches!(audio, SampleAudio::Memory(_)));
+        let audio = SampleAudio::new(vec![0.0; 1024], 0.0);
+        assert!(matches!(audio, SampleAudio::Memory(_)));
+    }
+}
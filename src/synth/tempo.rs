@@ -0,0 +1,110 @@
+/// Onset envelope resolution
+const HOP_SECS: f32 = 0.01;
+/// Shortest loop worth analysing
+const MIN_LOOP_SECS: f32 = 1.0;
+/// Only the start of long files is analysed, to bound the cost on load
+const MAX_ANALYSIS_SECS: f32 = 20.0;
+/// Tempo search range; results are folded into FOLD_MIN..FOLD_MIN*2
+const MIN_BPM: f32 = 50.0;
+const MAX_BPM: f32 = 220.0;
+const FOLD_MIN: f32 = 80.0;
+/// Periodicity needed to trust a result (autocorrelation vs. lag 0)
+const MIN_CONFIDENCE: f32 = 0.1;
+/// Snap to a whole number of beats across the loop when this close
+const SNAP_TOLERANCE: f32 = 0.03;
+/// Share of the strongest periodicity a loop-length tempo needs to win
+const LENGTH_SUPPORT: f32 = 0.5;
+
+/// Estimate the tempo of a loop from the spacing of its onsets
+/// (autocorrelation of the energy-rise envelope). None for one-shots,
+/// very short files and material without a steady pulse.
+pub fn detect_bpm(buffer: &[f32], sample_rate: f32) -> Option<f32> {
+    let duration = buffer.len() as f32 / sample_rate;
+    if duration < MIN_LOOP_SECS {
+        return None;
+    }
+    let hop = (HOP_SECS * sample_rate) as usize;
+    let analysed = &buffer[..buffer.len().min((MAX_ANALYSIS_SECS * sample_rate) as usize)];
+
+    // Onset strength: rise in log energy between hops
+    let energy: Vec<f32> = analysed
+        .chunks_exact(hop)
+        .map(|c| (c.iter().map(|s| s * s).sum::<f32>() / hop as f32 + 1e-9).ln())
+        .collect();
+    let mut onsets: Vec<f32> = energy.windows(2).map(|w| (w[1] - w[0]).max(0.0)).collect();
+    let mean = onsets.iter().sum::<f32>() / onsets.len().max(1) as f32;
+    for o in onsets.iter_mut() {
+        *o -= mean;
+    }
+
+    let frames_per_min = 60.0 / HOP_SECS;
+    let min_lag = (frames_per_min / MAX_BPM) as usize;
+    let max_lag = ((frames_per_min / MIN_BPM) as usize).min(onsets.len() / 2);
+    if max_lag <= min_lag + 1 {
+        return None;
+    }
+    let corr = |lag: usize| -> f32 { onsets.iter().zip(&onsets[lag..]).map(|(a, b)| a * b).sum() };
+    let zero = corr(0);
+    if zero <= 0.0 {
+        return None;
+    }
+    let scores: Vec<f32> = (min_lag - 1..=max_lag + 1).map(corr).collect();
+    let (best, &score) = scores[1..scores.len() - 1]
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))?;
+    if score / zero < MIN_CONFIDENCE {
+        return None;
+    }
+
+    // Loops are usually cut to a power-of-two number of beats: prefer that
+    // tempo when the onsets support it
+    let score_at = |bpm: f32| {
+        let lag = (frames_per_min / bpm).round() as usize;
+        scores[lag.clamp(min_lag, max_lag) + 1 - min_lag]
+    };
+    let from_length = [4.0, 8.0, 16.0, 32.0, 64.0]
+        .iter()
+        .map(|beats| fold_bpm(beats * 60.0 / duration))
+        .filter(|&bpm| score_at(bpm) >= LENGTH_SUPPORT * score)
+        .max_by(|a, b| score_at(*a).total_cmp(&score_at(*b)));
+    if let Some(bpm) = from_length {
+        return Some(bpm);
+    }
+
+    // Parabolic interpolation around the peak
+    let (a, b, c) = (scores[best], scores[best + 1], scores[best + 2]);
+    let denom = a - 2.0 * b + c;
+    let shift = if denom.abs() > f32::EPSILON { 0.5 * (a - c) / denom } else { 0.0 };
+    let lag = (best + min_lag) as f32 + shift.clamp(-0.5, 0.5);
+    let mut bpm = fold_bpm(frames_per_min / lag);
+
+    // Otherwise trust the length only when it agrees closely
+    let beats = (duration * bpm / 60.0).round();
+    if beats >= 4.0 {
+        let exact = beats * 60.0 / duration;
+        if ((exact - bpm) / bpm).abs() < SNAP_TOLERANCE {
+            bpm = exact;
+        }
+    }
+    Some(bpm)
+}
+
+/// Bring a tempo into FOLD_MIN..FOLD_MIN*2 by octaves
+fn fold_bpm(mut bpm: f32) -> f32 {
+    while bpm < FOLD_MIN {
+        bpm *= 2.0;
+    }
+    while bpm >= FOLD_MIN * 2.0 {
+        bpm /= 2.0;
+    }
+    bpm
+}
+
+/// Sampler pitch shift (semitones) that plays a loop recorded at
+/// `loop_bpm` in time with `project_bpm`, by repitching. Detected tempos
+/// are octave-ambiguous, so the closest half/double time is used.
+pub fn bpm_pitch_shift(loop_bpm: f32, project_bpm: f32) -> f32 {
+    let octaves = (project_bpm / loop_bpm).log2();
+    12.0 * (octaves - octaves.round())
+}
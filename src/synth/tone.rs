@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::source::{ParamDescriptor, SoundSource, SynthType};
+
+/// Fade in/out time so starting and stopping doesn't click (seconds)
+const FADE_SECS: f32 = 0.005;
+/// Sweep range and the sweep time when the tone is held
+const SWEEP_LOW_HZ: f32 = 20.0;
+const SWEEP_HIGH_HZ: f32 = 20000.0;
+const HELD_SWEEP_SECS: f32 = 10.0;
+
+/// What the tone generator plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMode {
+    /// Steady sine at the frequency
+    #[default]
+    Sine,
+    /// Logarithmic sine sweep from 20 Hz to 20 kHz
+    Sweep,
+    /// Pink noise (equal energy per octave)
+    Pink,
+    /// Dub siren: square wave whose pitch a rising LFO pushes up an octave
+    Siren,
+}
+
+impl ToneMode {
+    pub const ALL: [ToneMode; 4] = [ToneMode::Sine, ToneMode::Sweep, ToneMode::Pink, ToneMode::Siren];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneMode::Sine => "sine",
+            ToneMode::Sweep => "sweep",
+            ToneMode::Pink => "pink",
+            ToneMode::Siren => "siren",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sine" => Some(ToneMode::Sine),
+            "sweep" => Some(ToneMode::Sweep),
+            "pink" | "noise" => Some(ToneMode::Pink),
+            "siren" => Some(ToneMode::Siren),
+            _ => None,
+        }
+    }
+
+    /// Mode stored in the "mode" param (0-3)
+    pub fn from_param(value: f32) -> Self {
+        Self::ALL[(value.round().max(0.0) as usize).min(Self::ALL.len() - 1)]
+    }
+
+    pub fn param(&self) -> f32 {
+        Self::ALL.iter().position(|m| m == self).unwrap_or(0) as f32
+    }
+
+    pub fn next(&self) -> Self {
+        Self::from_param((self.param() + 1.0) % Self::ALL.len() as f32)
+    }
+}
+
+/// Tone generator parameters
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ToneParams {
+    /// ToneMode as 0-3
+    pub mode: f32,
+    /// Sine and siren base frequency (20-20000 Hz)
+    pub freq: f32,
+    /// Output level (0.0-1.0)
+    pub level: f32,
+    /// Siren LFO rate (0.1-20 Hz)
+    pub rate: f32,
+    /// Seconds before it stops by itself, 0 = until stopped (0-60).
+    /// Also the sweep time.
+    pub length: f32,
+}
+
+impl Default for ToneParams {
+    fn default() -> Self {
+        Self {
+            mode: 0.0,
+            freq: 1000.0,
+            level: 0.5,
+            rate: 2.0,
+            length: 0.0,
+        }
+    }
+}
+
+/// Utility generator for speaker checks and dub sirens: sine, sweep, pink
+/// noise or siren, running until stopped or for `length` seconds
+#[derive(Clone)]
+pub struct ToneGenerator {
+    params: ToneParams,
+    sample_rate: f32,
+    active: bool,
+    /// Fade gain, toward 1 while active and 0 once released
+    gain: f32,
+    elapsed: usize,
+    phase: f32,
+    lfo_phase: f32,
+    noise_state: u32,
+    /// Paul Kellet's economy pink filter
+    pink: [f32; 3],
+    velocity_scale: f32,
+}
+
+impl ToneGenerator {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            params: ToneParams::default(),
+            sample_rate,
+            active: false,
+            gain: 0.0,
+            elapsed: 0,
+            phase: 0.0,
+            lfo_phase: 0.0,
+            noise_state: 24680,
+            pink: [0.0; 3],
+            velocity_scale: 1.0,
+        }
+    }
+
+    pub fn params(&self) -> &ToneParams {
+        &self.params
+    }
+
+    pub fn mode(&self) -> ToneMode {
+        ToneMode::from_param(self.params.mode)
+    }
+
+    /// Whether it is sounding (including its fade-out)
+    pub fn is_active(&self) -> bool {
+        self.active || self.gain > 0.0
+    }
+
+    /// Fade out instead of cutting off
+    pub fn release(&mut self) {
+        self.active = false;
+    }
+
+    fn next_noise(&mut self) -> f32 {
+        self.noise_state = self.noise_state.wrapping_mul(1103515245).wrapping_add(12345);
+        (self.noise_state as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+
+    fn oscillator(&mut self, freq: f32) -> f32 {
+        self.phase = (self.phase + freq / self.sample_rate).fract();
+        (self.phase * std::f32::consts::TAU).sin()
+    }
+}
+
+impl SoundSource for ToneGenerator {
+    fn synth_type(&self) -> SynthType { SynthType::Tone }
+    fn type_name(&self) -> &'static str { "TONE" }
+    fn default_note(&self) -> u8 { 69 }
+
+    fn trigger(&mut self) {
+        self.active = true;
+        self.elapsed = 0;
+        self.lfo_phase = 0.0;
+    }
+
+    fn trigger_with_note(&mut self, _note: u8) {
+        self.trigger();
+    }
+
+    fn set_velocity_scale(&mut self, velocity: u8) {
+        self.velocity_scale = velocity as f32 / 127.0;
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let fade_step = 1.0 / (FADE_SECS * self.sample_rate);
+        if self.active && self.params.length > 0.0
+            && self.elapsed as f32 >= self.params.length * self.sample_rate
+        {
+            self.active = false;
+        }
+        self.gain = if self.active {
+            (self.gain + fade_step).min(1.0)
+        } else {
+            (self.gain - fade_step).max(0.0)
+        };
+        if self.gain <= 0.0 {
+            return 0.0;
+        }
+        let seconds = self.elapsed as f32 / self.sample_rate;
+        self.elapsed += 1;
+
+        let s = match self.mode() {
+            ToneMode::Sine => self.oscillator(self.params.freq),
+            ToneMode::Sweep => {
+                let sweep_secs = if self.params.length > 0.0 { self.params.length } else { HELD_SWEEP_SECS };
+                let t = (seconds / sweep_secs).fract();
+                self.oscillator(SWEEP_LOW_HZ * (SWEEP_HIGH_HZ / SWEEP_LOW_HZ).powf(t))
+            }
+            ToneMode::Pink => {
+                let white = self.next_noise();
+                self.pink[0] = 0.99765 * self.pink[0] + white * 0.0990460;
+                self.pink[1] = 0.96300 * self.pink[1] + white * 0.2965164;
+                self.pink[2] = 0.57000 * self.pink[2] + white * 1.0526913;
+                (self.pink[0] + self.pink[1] + self.pink[2] + white * 0.1848) * 0.25
+            }
+            ToneMode::Siren => {
+                self.lfo_phase = (self.lfo_phase + self.params.rate / self.sample_rate).fract();
+                let freq = self.params.freq * 2.0f32.powf(self.lfo_phase);
+                // Soft square, bright without aliasing too badly
+                (self.oscillator(freq) * 4.0).tanh()
+            }
+        };
+        s * self.params.level * self.velocity_scale * self.gain
+    }
+
+    fn stop(&mut self) {
+        self.active = false;
+        self.gain = 0.0;
+    }
+
+    fn voice_copy(&self) -> Option<Box<dyn SoundSource>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn param_descriptors(&self) -> Vec<ParamDescriptor> {
+        vec![
+            ParamDescriptor { key: "mode".into(), name: "Mode".into(), min: 0.0, max: 3.0, default: 0.0 },
+            ParamDescriptor { key: "freq".into(), name: "Freq".into(), min: 20.0, max: 20000.0, default: 1000.0 },
+            ParamDescriptor { key: "level".into(), name: "Level".into(), min: 0.0, max: 1.0, default: 0.5 },
+            ParamDescriptor { key: "rate".into(), name: "Rate".into(), min: 0.1, max: 20.0, default: 2.0 },
+            ParamDescriptor { key: "length".into(), name: "Length".into(), min: 0.0, max: 60.0, default: 0.0 },
+        ]
+    }
+
+    fn get_param(&self, key: &str) -> Option<f32> {
+        match key {
+            "mode" => Some(self.params.mode),
+            "freq" => Some(self.params.freq),
+            "level" => Some(self.params.level),
+            "rate" => Some(self.params.rate),
+            "length" => Some(self.params.length),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, key: &str, value: f32) -> bool {
+        match key {
+            "mode" => { self.params.mode = value.round().clamp(0.0, 3.0); true }
+            "freq" => { self.params.freq = value.clamp(20.0, 20000.0); true }
+            "level" => { self.params.level = value.clamp(0.0, 1.0); true }
+            "rate" => { self.params.rate = value.clamp(0.1, 20.0); true }
+            "length" => { self.params.length = value.clamp(0.0, 60.0); true }
+            _ => false,
+        }
+    }
+
+    fn serialize_params(&self) -> Value {
+        serde_json::to_value(&self.params).unwrap_or(Value::Null)
+    }
+
+    fn deserialize_params(&mut self, params: &Value) {
+        if let Ok(p) = serde_json::from_value::<ToneParams>(params.clone()) {
+            self.params = p;
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::ops::Range;
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+use crate::audio::SequencerState;
+use crate::sequencer::{pattern_label, Pattern, Variation, NUM_PATTERNS, PATTERNS_PER_BANK};
+use crate::ui::Theme;
+
+/// Slots per row of the bank grid
+const SLOTS_PER_ROW: usize = 4;
+/// Largest thumbnail: rows of tracks by columns of steps
+const THUMB_ROWS: usize = 4;
+const THUMB_COLS: usize = 16;
+
+pub struct BankState {
+    /// Slot under the cursor (0..NUM_PATTERNS)
+    pub cursor: usize,
+    /// Slot marked by the first c, copied onto the cursor slot by the second
+    pub copy_from: Option<usize>,
+}
+
+impl BankState {
+    pub fn new() -> Self {
+        Self {
+            cursor: 0,
+            copy_from: None,
+        }
+    }
+
+    /// Move within the cursor's bank (wrapping), by `dx` slots and `dy` rows
+    pub fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let bank = self.cursor / PATTERNS_PER_BANK;
+        let slot = (self.cursor % PATTERNS_PER_BANK) as isize;
+        let rows = (PATTERNS_PER_BANK / SLOTS_PER_ROW) as isize;
+        let col = (slot % SLOTS_PER_ROW as isize + dx).rem_euclid(SLOTS_PER_ROW as isize);
+        let row = (slot / SLOTS_PER_ROW as isize + dy).rem_euclid(rows);
+        self.cursor = bank * PATTERNS_PER_BANK + (row * SLOTS_PER_ROW as isize + col) as usize;
+    }
+
+    /// Same slot in the bank `delta` banks away (wrapping)
+    pub fn step_bank(&mut self, delta: isize) {
+        let step = delta * PATTERNS_PER_BANK as isize;
+        self.cursor = (self.cursor as isize + step).rem_euclid(NUM_PATTERNS as isize) as usize;
+    }
+}
+
+impl Default for BankState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Active steps in a block of tracks and steps
+fn count_active(pattern: &Pattern, variation: Variation, tracks: Range<usize>, steps: Range<usize>) -> usize {
+    tracks
+        .flat_map(|track| steps.clone().map(move |step| (track, step)))
+        .filter(|&(track, step)| pattern.get_step_var(track, step, variation).active)
+        .count()
+}
+
+/// Thumbnail of a pattern, `rows` x `cols` at most: tracks are grouped
+/// into rows and steps into columns, each cell shaded by how many of its
+/// steps are on
+fn thumbnail(pattern: &Pattern, variation: Variation, rows: usize, cols: usize, theme: &Theme) -> Vec<Line<'static>> {
+    let num_tracks = pattern.num_tracks();
+    let rows = rows.min(num_tracks);
+    let cols = cols.min(pattern.length).max(1);
+    (0..rows)
+        .map(|row| {
+            let tracks = row * num_tracks / rows..(row + 1) * num_tracks / rows;
+            let cells: Vec<Span> = (0..cols)
+                .map(|col| {
+                    let steps = col * pattern.length / cols..(col + 1) * pattern.length / cols;
+                    let cells = (tracks.len() * steps.len()).max(1);
+                    let density = count_active(pattern, variation, tracks.clone(), steps) as f32 / cells as f32;
+                    let (symbol, color) = match density {
+                        d if d <= 0.0 => ("\u{00b7}", theme.grid_inactive),
+                        d if d <= 0.25 => ("\u{2591}", theme.grid_active),
+                        d if d <= 0.5 => ("\u{2592}", theme.grid_active),
+                        d if d <= 0.75 => ("\u{2593}", theme.grid_active),
+                        _ => ("\u{2588}", theme.grid_active),
+                    };
+                    Span::styled(symbol, Style::default().fg(color))
+                })
+                .collect();
+            Line::from(cells)
+        })
+        .collect()
+}
+
+/// Render the Pattern Bank view: the 16 slots of the cursor's bank as
+/// thumbnails of the current variation, with the playing, queued and
+/// cursor slots marked
+pub fn render_bank(frame: &mut Frame, area: Rect, state: &SequencerState, bank_state: &BankState, theme: &Theme) {
+    let bank = bank_state.cursor / PATTERNS_PER_BANK;
+    let mut title = vec![Span::styled(
+        format!(" Pattern Bank {} ", (b'A' + bank as u8) as char),
+        Style::default().fg(theme.track_label),
+    )];
+    if let Some(src) = bank_state.copy_from {
+        title.push(Span::styled(
+            format!(" Copy {}: c on the target slot ", pattern_label(src)),
+            Style::default().fg(theme.highlight),
+        ));
+    }
+    let block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border))
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let grid_rows = PATTERNS_PER_BANK / SLOTS_PER_ROW;
+    let slot_width = inner.width / SLOTS_PER_ROW as u16;
+    let slot_height = inner.height / grid_rows as u16;
+    if slot_width < 6 || slot_height < 2 {
+        return;
+    }
+    let thumb_rows = THUMB_ROWS.min(slot_height as usize - 1);
+    let thumb_cols = THUMB_COLS.min(slot_width as usize - 2);
+
+    for i in 0..PATTERNS_PER_BANK {
+        let index = bank * PATTERNS_PER_BANK + i;
+        // The playing slot's steps are live in `state.pattern`
+        let pattern = if index == state.current_pattern {
+            &state.pattern
+        } else {
+            state.pattern_bank.get(index)
+        };
+        let rect = Rect::new(
+            inner.x + (i % SLOTS_PER_ROW) as u16 * slot_width,
+            inner.y + (i / SLOTS_PER_ROW) as u16 * slot_height,
+            slot_width - 1,
+            slot_height,
+        );
+
+        let is_current = index == state.current_pattern;
+        let is_queued = state.pending_pattern == Some(index);
+        let marker = if is_current {
+            "*"
+        } else if is_queued {
+            ">"
+        } else if bank_state.copy_from == Some(index) {
+            "c"
+        } else {
+            " "
+        };
+        let variation = state.current_variation;
+        let active_steps = count_active(pattern, variation, 0..pattern.num_tracks(), 0..pattern.length);
+        let label_style = if index == bank_state.cursor {
+            Style::default().fg(theme.bg).bg(theme.grid_cursor).bold()
+        } else if is_current {
+            Style::default().fg(theme.highlight).bold()
+        } else if is_queued {
+            Style::default().fg(theme.meter_mid).bold()
+        } else {
+            Style::default().fg(theme.track_label)
+        };
+        let mut lines = vec![Line::from(vec![
+            Span::styled(format!("{}{}", marker, pattern_label(index)), label_style),
+            Span::styled(
+                if pattern.has_content() {
+                    format!(" {:>3}", active_steps)
+                } else {
+                    " empty".to_string()
+                },
+                Style::default().fg(theme.dimmed),
+            ),
+        ])];
+        if pattern.has_content() {
+            lines.extend(thumbnail(pattern, variation, thumb_rows, thumb_cols, theme).into_iter().map(|line| {
+                let mut spans = vec![Span::raw(" ")];
+                spans.extend(line.spans);
+                Line::from(spans)
+            }));
+        }
+        frame.render_widget(Paragraph::new(lines), rect);
+    }
+}
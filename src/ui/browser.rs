@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 
 use crate::samples::SampleEntry;
+use crate::synth::{split_cents, tuning_label};
 use crate::ui::Theme;
 
 /// State for the sample browser modal
@@ -11,7 +14,14 @@ pub struct BrowserState {
     pub scroll: usize,
     pub target_track: usize,
     pub target_track_name: String,
+    /// Velocity layer to load into (None = the track's main sample)
+    pub target_layer: Option<usize>,
     pub previewing: Option<usize>, // index of previewing entry
+    /// Root note and tempo detected when an entry was previewed
+    pub detected: HashMap<usize, (Option<f32>, Option<f32>)>,
+    /// Load options applied to the sampler on Enter
+    pub normalize: bool,
+    pub trim_silence: bool,
 }
 
 /// An item in the browser list: either a folder header or a file
@@ -28,7 +38,11 @@ impl BrowserState {
             scroll: 0,
             target_track,
             target_track_name,
+            target_layer: None,
             previewing: None,
+            detected: HashMap::new(),
+            normalize: false,
+            trim_silence: false,
         }
     }
 
@@ -99,6 +113,8 @@ pub fn render_browser(
     frame: &mut Frame,
     area: Rect,
     browser: &BrowserState,
+    memory: (usize, f32), // (sample bytes in use, budget in MB)
+    duck_db: f32,
     theme: &Theme,
 ) {
     // Calculate modal area (centered, taking most of the content area)
@@ -107,11 +123,19 @@ pub fn render_browser(
     // Clear the background
     frame.render_widget(Clear, modal_area);
 
-    let title = format!(
-        " Load Sample for track {}: {} ",
-        browser.target_track + 1,
-        browser.target_track_name,
-    );
+    let title = match browser.target_layer {
+        Some(layer) => format!(
+            " Load velocity layer {} for track {}: {} ",
+            layer + 1,
+            browser.target_track + 1,
+            browser.target_track_name,
+        ),
+        None => format!(
+            " Load Sample for track {}: {} ",
+            browser.target_track + 1,
+            browser.target_track_name,
+        ),
+    };
 
     let block = Block::default()
         .title(Span::styled(title, Style::default().fg(theme.highlight)))
@@ -175,6 +199,19 @@ pub fn render_browser(
                 };
 
                 let preview_style = Style::default().fg(theme.grid_active);
+                let mut root = String::new();
+                if let Some((note, bpm)) = browser.detected.get(entry_idx) {
+                    match note {
+                        Some(note) => {
+                            let (note, cents) = split_cents(*note);
+                            root.push_str(&format!("  {}", tuning_label(note, cents)));
+                        }
+                        None => root.push_str("  unpitched"),
+                    }
+                    if let Some(bpm) = bpm {
+                        root.push_str(&format!("  {:.1} BPM", bpm));
+                    }
+                }
 
                 let _ = visual_idx; // suppress unused warning
 
@@ -182,6 +219,7 @@ pub fn render_browser(
                     Span::styled(format!("  {} ", cursor_char), style),
                     Span::styled(entry.name.clone(), style),
                     Span::styled(format!(".wav{}", preview_marker), if is_previewing { preview_style } else { style }),
+                    Span::styled(root, Style::default().fg(theme.dimmed)),
                 ]));
             }
         }
@@ -196,6 +234,23 @@ pub fn render_browser(
         Span::styled(" Preview  ", Style::default().fg(theme.fg)),
         Span::styled("[Enter]", Style::default().fg(theme.grid_active)),
         Span::styled(" Load  ", Style::default().fg(theme.fg)),
+        Span::styled("[K]", Style::default().fg(theme.grid_active)),
+        Span::styled(" Kit  ", Style::default().fg(theme.fg)),
+        Span::styled("[N]", Style::default().fg(theme.grid_active)),
+        Span::styled(
+            format!(" Normalize:{}  ", if browser.normalize { "on" } else { "off" }),
+            Style::default().fg(theme.fg),
+        ),
+        Span::styled("[T]", Style::default().fg(theme.grid_active)),
+        Span::styled(
+            format!(" Trim:{}  ", if browser.trim_silence { "on" } else { "off" }),
+            Style::default().fg(theme.fg),
+        ),
+        Span::styled("[D]", Style::default().fg(theme.grid_active)),
+        Span::styled(
+            if duck_db < 0.0 { format!(" Duck:{:.0}dB  ", duck_db) } else { " Duck:off  ".to_string() },
+            Style::default().fg(theme.fg),
+        ),
         Span::styled("[Esc]", Style::default().fg(theme.grid_active)),
         Span::styled(" Cancel", Style::default().fg(theme.fg)),
     ]))
@@ -208,10 +263,31 @@ pub fn render_browser(
         1,
     );
     frame.render_widget(footer, footer_area);
+
+    // Sample memory stats line above the footer
+    let (bytes, budget_mb) = memory;
+    let used_mb = bytes as f32 / (1024.0 * 1024.0);
+    let stats_style = if used_mb > budget_mb {
+        Style::default().fg(theme.highlight).bold()
+    } else {
+        Style::default().fg(theme.dimmed)
+    };
+    let stats = Paragraph::new(Span::styled(
+        format!("  Samples: {:.1} MB / {:.0} MB", used_mb, budget_mb),
+        stats_style,
+    ))
+    .style(Style::default().bg(theme.bg));
+    let stats_area = Rect::new(
+        inner.x,
+        inner.y + inner.height.saturating_sub(2),
+        inner.width,
+        1,
+    );
+    frame.render_widget(stats, stats_area);
 }
 
 /// Create a centered rect within a given area
-fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+pub(crate) fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -2,7 +2,10 @@ use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph};
 
 use crate::audio::SequencerState;
-use crate::fx::{FxParamId, MasterFxParamId};
+use crate::fx::{FxParamId, MasterFxParamId, ReverbMode};
+
+/// Master row that switches the reverb mode (not a MasterFxParamId)
+const MASTER_MODE_ROW: usize = 4;
 use crate::ui::Theme;
 
 /// State for FX editor view
@@ -35,8 +38,8 @@ impl FxEditorState {
     }
 
     /// Move parameter selection up/down
-    pub fn move_selection(&mut self, dy: i32) {
-        let count = self.param_count() as i32;
+    pub fn move_selection(&mut self, dy: i32, num_tracks: usize) {
+        let count = self.param_count(num_tracks) as i32;
         if count == 0 {
             return;
         }
@@ -44,11 +47,24 @@ impl FxEditorState {
     }
 
     /// Total number of selectable parameter rows for current track
-    fn param_count(&self) -> usize {
-        // For master we don't know num_tracks here, but master always has 3 params
-        // and track always has 8 params. The is_master check is done by caller.
-        // We default to 8 here; master callers override to 3.
-        8
+    fn param_count(&self, num_tracks: usize) -> usize {
+        // Master: 4 reverb + mode + 2 gate + 4 mid/side + 2 limiter; tracks:
+        // 3 filter + 2 dist + 3 delay + 3 modulation + chain mix
+        if self.is_master(num_tracks) {
+            13
+        } else {
+            12
+        }
+    }
+
+    /// Master FX parameter on the selected row (None on the reverb mode row)
+    pub fn master_param(&self) -> Option<MasterFxParamId> {
+        let index = match self.param_index {
+            MASTER_MODE_ROW => return None,
+            i if i > MASTER_MODE_ROW => i - 1,
+            i => i,
+        };
+        MasterFxParamId::all().get(index).copied()
     }
 
     /// Get the FX section and local param index for the current selection (track mode)
@@ -57,6 +73,8 @@ impl FxEditorState {
             0..=2 => (0, self.param_index),     // Filter: type(0), cutoff(1), resonance(2)
             3..=4 => (1, self.param_index - 3), // Dist: drive(0), mix(1)
             5..=7 => (2, self.param_index - 5), // Delay: time(0), feedback(1), mix(2)
+            8..=10 => (3, self.param_index - 8), // Mod: source(0), target(1), depth(2)
+            11 => (4, 0),                        // Chain: mix(0)
             _ => (0, 0),
         }
     }
@@ -82,6 +100,8 @@ pub fn get_fx_param_value(state: &SequencerState, track: usize, param: FxParamId
         FxParamId::DelayTime => fx.delay_time,
         FxParamId::DelayFeedback => fx.delay_feedback,
         FxParamId::DelayMix => fx.delay_mix,
+        FxParamId::ModDepth => fx.mod_depth,
+        FxParamId::ChainMix => fx.chain_mix,
     }
 }
 
@@ -91,6 +111,15 @@ pub fn get_master_fx_param_value(state: &SequencerState, param: MasterFxParamId)
         MasterFxParamId::ReverbDecay => state.master_fx.reverb_decay,
         MasterFxParamId::ReverbMix => state.master_fx.reverb_mix,
         MasterFxParamId::ReverbDamping => state.master_fx.reverb_damping,
+        MasterFxParamId::ReverbWidth => state.master_fx.reverb_width,
+        MasterFxParamId::ReverbGateThreshold => state.master_fx.reverb_gate_threshold,
+        MasterFxParamId::ReverbGateHold => state.master_fx.reverb_gate_hold,
+        MasterFxParamId::MsMidLevel => state.master_fx.ms_mid_level,
+        MasterFxParamId::MsSideLevel => state.master_fx.ms_side_level,
+        MasterFxParamId::MsBassMono => state.master_fx.ms_bass_mono,
+        MasterFxParamId::MsSideHigh => state.master_fx.ms_side_high,
+        MasterFxParamId::LimiterCeiling => state.master_fx.limiter_ceiling,
+        MasterFxParamId::LimiterRelease => state.master_fx.limiter_release,
     }
 }
 
@@ -104,11 +133,13 @@ pub fn render_fx(
 ) {
     let num_tracks = state.tracks.len();
 
+    let title = if state.gain_match {
+        " Effects [GAIN MATCH] "
+    } else {
+        " Effects "
+    };
     let block = Block::default()
-        .title(Span::styled(
-            " Effects ",
-            Style::default().fg(theme.track_label),
-        ))
+        .title(Span::styled(title, Style::default().fg(theme.track_label)))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(theme.border))
         .style(Style::default().bg(theme.bg));
@@ -321,13 +352,89 @@ fn render_track_fx_params(
         &format!("{:.2}", fx.delay_mix),
         theme,
     ));
+    row_idx += 1;
+
+    lines.push(Line::from("")); // spacer
+
+    // --- CROSS-TRACK MODULATION ---
+    let source = fx.modulator(track, state.tracks.len());
+    let (mod_status, mod_status_style) = match source {
+        Some(_) => (" ON", Style::default().fg(theme.meter_low).bold()),
+        None => ("OFF", Style::default().fg(theme.dimmed)),
+    };
+    lines.push(Line::from(vec![
+        Span::styled(
+            "  MODULATION",
+            Style::default().fg(theme.track_label).bold(),
+        ),
+        Span::raw("                                    "),
+        Span::styled(format!("[{}]", mod_status), mod_status_style),
+    ]));
+
+    let source_name = match source {
+        Some(s) => format!("{}:{}", s + 1, state.tracks[s].name),
+        None => "off".to_string(),
+    };
+    lines.push(render_param_row(
+        row_idx == editor.param_index,
+        "Source",
+        &source_name,
+        0.0,
+        true,
+        theme,
+    ));
+    row_idx += 1;
+
+    lines.push(render_param_row(
+        row_idx == editor.param_index,
+        "Target",
+        fx.mod_target.name(),
+        0.0,
+        true,
+        theme,
+    ));
+    row_idx += 1;
+
+    lines.push(render_value_row(
+        row_idx == editor.param_index,
+        "Depth",
+        fx.mod_depth,
+        &format!("{:.2}", fx.mod_depth),
+        theme,
+    ));
+    row_idx += 1;
+
+    lines.push(Line::from("")); // spacer
+
+    // --- CHAIN (dry/wet of everything above) ---
+    let (chain_status, chain_status_style) = if fx.chain_mix < 1.0 {
+        ("PAR", Style::default().fg(theme.meter_low).bold())
+    } else {
+        ("WET", Style::default().fg(theme.dimmed))
+    };
+    lines.push(Line::from(vec![
+        Span::styled(
+            "  CHAIN",
+            Style::default().fg(theme.track_label).bold(),
+        ),
+        Span::raw("                                         "),
+        Span::styled(format!("[{}]", chain_status), chain_status_style),
+    ]));
+
+    lines.push(render_value_row(
+        row_idx == editor.param_index,
+        "Dry/Wet",
+        fx.chain_mix,
+        &format!("{:.0}% wet", fx.chain_mix * 100.0),
+        theme,
+    ));
     let _ = row_idx;
 
     let para = Paragraph::new(lines).style(Style::default().bg(theme.bg));
     frame.render_widget(para, area);
 }
 
-/// Render master FX parameters (reverb)
+/// Render master FX parameters (reverb, mid/side, limiter)
 fn render_master_fx_params(
     frame: &mut Frame,
     area: Rect,
@@ -382,6 +489,113 @@ fn render_master_fx_params(
         theme,
     ));
 
+    // Width
+    lines.push(render_value_row(
+        3 == editor.param_index,
+        "Width",
+        mfx.reverb_width,
+        &format!("{:.2}", mfx.reverb_width),
+        theme,
+    ));
+
+    // Mode: natural tail or gated by the dry signal
+    let mode_label = match mfx.reverb_mode {
+        ReverbMode::Normal => "Normal",
+        ReverbMode::Gated => "Gated",
+    };
+    lines.push(render_param_row(
+        MASTER_MODE_ROW == editor.param_index,
+        "Mode",
+        mode_label,
+        0.0,
+        true,
+        theme,
+    ));
+    lines.push(render_value_row(
+        5 == editor.param_index,
+        "Gate Thresh",
+        (mfx.reverb_gate_threshold + 60.0) / 60.0,
+        &format!("{:.0}dB", mfx.reverb_gate_threshold),
+        theme,
+    ));
+    lines.push(render_value_row(
+        6 == editor.param_index,
+        "Gate Hold",
+        (mfx.reverb_gate_hold - 20.0) / 480.0,
+        &format!("{:.0}ms", mfx.reverb_gate_hold),
+        theme,
+    ));
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  MID/SIDE",
+        Style::default().fg(theme.track_label).bold(),
+    )));
+
+    lines.push(render_value_row(
+        7 == editor.param_index,
+        "Mid",
+        mfx.ms_mid_level / 2.0,
+        &format!("{:.2}", mfx.ms_mid_level),
+        theme,
+    ));
+    lines.push(render_value_row(
+        8 == editor.param_index,
+        "Side",
+        mfx.ms_side_level / 2.0,
+        &format!("{:.2}", mfx.ms_side_level),
+        theme,
+    ));
+    let bass_mono_label = if mfx.ms_bass_mono > 0.0 {
+        format!("{:.0}Hz", mfx.ms_bass_mono)
+    } else {
+        "off".to_string()
+    };
+    lines.push(render_value_row(
+        9 == editor.param_index,
+        "Bass Mono",
+        mfx.ms_bass_mono / 500.0,
+        &bass_mono_label,
+        theme,
+    ));
+    lines.push(render_value_row(
+        10 == editor.param_index,
+        "Side Highs",
+        mfx.ms_side_high / 2.0,
+        &format!("{:.2}", mfx.ms_side_high),
+        theme,
+    ));
+
+    let limiter_status = if mfx.limiter_enabled { " ON" } else { "OFF" };
+    let limiter_status_style = if mfx.limiter_enabled {
+        Style::default().fg(theme.meter_low).bold()
+    } else {
+        Style::default().fg(theme.dimmed)
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(
+            "  LIMITER",
+            Style::default().fg(theme.track_label).bold(),
+        ),
+        Span::raw("                                       "),
+        Span::styled(format!("[{}]", limiter_status), limiter_status_style),
+    ]));
+    lines.push(render_value_row(
+        11 == editor.param_index,
+        "Ceiling",
+        (mfx.limiter_ceiling + 12.0) / 12.0,
+        &format!("{:.1}dB", mfx.limiter_ceiling),
+        theme,
+    ));
+    lines.push(render_value_row(
+        12 == editor.param_index,
+        "Release",
+        (mfx.limiter_release - 10.0) / 990.0,
+        &format!("{:.0}ms", mfx.limiter_release),
+        theme,
+    ));
+
     let para = Paragraph::new(lines).style(Style::default().bg(theme.bg));
     frame.render_widget(para, area);
 }
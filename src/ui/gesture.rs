@@ -0,0 +1,111 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEventKind};
+
+/// Key held at least this long is a long-press
+pub const HOLD_TIME: Duration = Duration::from_millis(400);
+/// Second press within this is a double-tap (terminals without releases)
+pub const DOUBLE_TAP: Duration = Duration::from_millis(300);
+/// Presses of the same key closer than this are key repeat, not taps
+const REPEAT_GAP: Duration = Duration::from_millis(80);
+
+/// What a press-and-hold key did
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    /// Pressed: do the key's normal action
+    Tap,
+    /// Held (or double-tapped): undo the tap if needed and start the hold action
+    HoldStart,
+    /// Released after a hold (or tapped again after a double-tap)
+    HoldEnd,
+}
+
+/// Long-press detection for a few gesture keys. Uses key release events
+/// when the terminal reports them; otherwise a double-tap latches the hold
+/// and the next press of the key ends it. Key repeat never re-taps.
+pub struct LongPress {
+    releases: bool,
+    /// Key currently down (releases) or whose hold is latched (double-tap)
+    key: Option<KeyCode>,
+    pressed_at: Instant,
+    holding: bool,
+    last_press: Option<(KeyCode, Instant)>,
+}
+
+impl LongPress {
+    pub fn new(releases: bool) -> Self {
+        Self {
+            releases,
+            key: None,
+            pressed_at: Instant::now(),
+            holding: false,
+            last_press: None,
+        }
+    }
+
+    /// Whether holds are detected from release events
+    pub fn releases(&self) -> bool {
+        self.releases
+    }
+
+    /// Feed an event for a gesture key
+    pub fn key_event(&mut self, code: KeyCode, kind: KeyEventKind) -> Option<Gesture> {
+        let now = Instant::now();
+        if self.releases {
+            return match kind {
+                KeyEventKind::Press => {
+                    self.key = Some(code);
+                    self.pressed_at = now;
+                    self.holding = false;
+                    Some(Gesture::Tap)
+                }
+                KeyEventKind::Repeat => None,
+                KeyEventKind::Release => {
+                    if self.key != Some(code) {
+                        return None;
+                    }
+                    self.key = None;
+                    std::mem::take(&mut self.holding).then_some(Gesture::HoldEnd)
+                }
+            };
+        }
+
+        if kind != KeyEventKind::Press {
+            return None;
+        }
+        let previous = self.last_press.replace((code, now));
+        let since = previous
+            .filter(|(key, _)| *key == code)
+            .map(|(_, at)| now.duration_since(at));
+        if since.is_some_and(|d| d < REPEAT_GAP) {
+            return None;
+        }
+        if self.holding && self.key == Some(code) {
+            self.holding = false;
+            self.key = None;
+            return Some(Gesture::HoldEnd);
+        }
+        if since.is_some_and(|d| d < DOUBLE_TAP) {
+            self.holding = true;
+            self.key = Some(code);
+            return Some(Gesture::HoldStart);
+        }
+        Some(Gesture::Tap)
+    }
+
+    /// Report a hold once a key stays down past HOLD_TIME (releases only)
+    pub fn poll(&mut self) -> Option<(KeyCode, Gesture)> {
+        let key = self.key?;
+        if self.releases && !self.holding && self.pressed_at.elapsed() >= HOLD_TIME {
+            self.holding = true;
+            return Some((key, Gesture::HoldStart));
+        }
+        None
+    }
+
+    /// Forget the current gesture without a HoldEnd (e.g. a modal took over)
+    pub fn cancel(&mut self) {
+        self.key = None;
+        self.holding = false;
+    }
+}
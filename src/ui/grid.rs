@@ -1,14 +1,25 @@
 use ratatui::prelude::*;
-use ratatui::widgets::{Block, Borders};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 
-use crate::sequencer::{Pattern, PlaybackMode, Variation, DEFAULT_TRACKS, STEPS};
+use crate::audio::CaptureStatus;
+use crate::midi::MidiSync;
+use crate::sequencer::pattern::StepData;
+use crate::sequencer::{
+    pattern_label, step_density, triplet_steps, Pattern, PlaybackMode, Variation, DEFAULT_TRACKS, STEPS,
+    TRIPLET_STEPS,
+};
 use crate::synth::note_name;
 use crate::ui::{Theme, dim_color_by_velocity};
 
+/// Track label columns: glyph, name and a space
+const FULL_LABEL_WIDTH: u16 = 9;
+
 /// Grid cursor and playhead state
 pub struct GridState {
     pub cursor_track: usize,
     pub cursor_step: usize,
+    /// Color steps by how much lands on their beat across all tracks
+    pub heat_map: bool,
 }
 
 impl GridState {
@@ -16,13 +27,24 @@ impl GridState {
         Self {
             cursor_track: 0,
             cursor_step: 0,
+            heat_map: false,
         }
     }
 
-    pub fn move_cursor(&mut self, dx: i32, dy: i32, num_tracks: usize) {
+    /// Move the cursor in a pattern of `length` steps. `triplets` marks
+    /// tracks on the triplet grid; moving between grids keeps the cursor at
+    /// the same bar position.
+    pub fn move_cursor(&mut self, dx: i32, dy: i32, num_tracks: usize, triplets: &[bool], length: usize) {
         let tracks = if num_tracks == 0 { DEFAULT_TRACKS } else { num_tracks };
-        self.cursor_step = ((self.cursor_step as i32 + dx).rem_euclid(STEPS as i32)) as usize;
+        let steps_of = |track: usize| track_steps(triplets.get(track).copied().unwrap_or(false), length);
+        let old_steps = steps_of(self.cursor_track);
+        self.cursor_step = self.cursor_step.min(old_steps - 1);
+        self.cursor_step = ((self.cursor_step as i32 + dx).rem_euclid(old_steps as i32)) as usize;
         self.cursor_track = ((self.cursor_track as i32 + dy).rem_euclid(tracks as i32)) as usize;
+        let new_steps = steps_of(self.cursor_track);
+        if new_steps != old_steps {
+            self.cursor_step = (self.cursor_step * new_steps / old_steps).min(new_steps - 1);
+        }
     }
 }
 
@@ -32,6 +54,15 @@ impl Default for GridState {
     }
 }
 
+/// Steps a track has in a pattern of `length` 16ths
+pub fn track_steps(triplet: bool, length: usize) -> usize {
+    if triplet {
+        triplet_steps(length)
+    } else {
+        length
+    }
+}
+
 /// Format a note name to fit in cell_width characters
 fn format_note(note: u8, cell_width: u16) -> String {
     let name = note_name(note);
@@ -52,6 +83,17 @@ fn format_note(note: u8, cell_width: u16) -> String {
     }
 }
 
+/// Heat map color: meter colors from a light beat to a crowded one
+fn heat_color(heat: f32, theme: &Theme) -> Color {
+    if heat >= 0.75 {
+        theme.meter_high
+    } else if heat >= 0.4 {
+        theme.meter_mid
+    } else {
+        theme.meter_low
+    }
+}
+
 /// Render the step sequencer grid
 pub fn render_grid(
     frame: &mut Frame,
@@ -59,16 +101,21 @@ pub fn render_grid(
     pattern: &Pattern,
     grid_state: &GridState,
     current_step: usize,
+    run_steps: usize,
     playing: bool,
     track_names: &[String],
+    glyphs: &[String],
+    triplets: &[bool],
+    transposes: &[i8],
     theme: &Theme,
 ) {
     let num_tracks = pattern.num_tracks();
+    let heat = grid_state.heat_map.then(|| step_density(pattern, Variation::A, triplets));
 
     // Create outer block
     let block = Block::default()
         .title(Span::styled(
-            " Pattern ",
+            if heat.is_some() { " Pattern (heat map) " } else { " Pattern " },
             Style::default().fg(theme.track_label),
         ))
         .borders(Borders::ALL)
@@ -79,10 +126,17 @@ pub fn render_grid(
     frame.render_widget(block, area);
 
     // Calculate cell dimensions
-    // Track label width + 16 steps
-    let label_width = 6u16;
+    // Track label width (glyph + name, just the glyph when two-column
+    // steps wouldn't fit) + the pattern's steps (one column each when long)
+    let length = pattern.length;
+    let full_label = inner.width >= FULL_LABEL_WIDTH + 2 * STEPS as u16;
+    let label_width = if full_label { FULL_LABEL_WIDTH } else { 3 };
     let available_width = inner.width.saturating_sub(label_width);
-    let cell_width = (available_width / STEPS as u16).max(2);
+    let cell_width = if length <= STEPS {
+        (available_width / STEPS as u16).max(2)
+    } else {
+        (available_width / length as u16).max(1)
+    };
     let cell_height = if num_tracks > 0 {
         (inner.height / num_tracks as u16).max(1)
     } else {
@@ -97,26 +151,50 @@ pub fn render_grid(
             break;
         }
 
-        // Track label
-        let label = if track < track_names.len() {
-            format!("{:>5} ", track_names[track])
-        } else {
-            format!("{:>5} ", format!("TRK{}", track))
+        // Track label, with the track transpose when set (e.g. "BAS-12")
+        let name = match track_names.get(track) {
+            Some(name) => name.clone(),
+            None => format!("TRK{}", track),
+        };
+        let name_label = match transposes.get(track).copied().unwrap_or(0) {
+            0 => format!("{:>5} ", name),
+            transpose => {
+                let suffix = format!("{:+}", transpose);
+                let short: String = name.chars().take(5 - suffix.len()).collect();
+                format!("{:>5} ", format!("{}{}", short, suffix))
+            }
         };
-        let label_style = if track == grid_state.cursor_track {
+        let triplet = triplets.get(track).copied().unwrap_or(false);
+        let mut label_style = if track == grid_state.cursor_track {
             Style::default().fg(theme.highlight).bold()
         } else {
             Style::default().fg(theme.track_label)
         };
+        if triplet {
+            label_style = label_style.italic();
+        }
 
+        let glyph = glyphs.get(track).map_or("", |g| g.as_str());
+        let mut label = vec![Span::styled(format!("{:>2} ", glyph), label_style.bold())];
+        if full_label {
+            label.push(Span::styled(name_label, label_style));
+        }
+        let label = Line::from(label);
         frame.render_widget(
-            ratatui::widgets::Paragraph::new(label).style(label_style),
+            ratatui::widgets::Paragraph::new(label),
             Rect::new(inner.x, track_y, label_width, 1),
         );
 
-        // Steps
-        for step in 0..STEPS {
-            let step_x = inner.x + label_width + (step as u16 * cell_width);
+        // Steps (triplet tracks spread 12 cells a bar across the same width).
+        // Tracks with their own loop length play up to the wrap mark.
+        let steps = track_steps(triplet, length);
+        let loop_length = pattern.track_loop(track, triplet);
+        let playhead_step = match loop_length {
+            Some(loop_length) => run_steps * steps / length % loop_length,
+            None => current_step * steps / length,
+        };
+        for step in 0..steps {
+            let step_x = inner.x + label_width + (step * length) as u16 * cell_width / steps as u16;
 
             if step_x >= inner.x + inner.width {
                 break;
@@ -125,10 +203,14 @@ pub fn render_grid(
             let step_data = pattern.get_step(track, step);
             let is_active = step_data.active;
             let is_cursor = track == grid_state.cursor_track && step == grid_state.cursor_step;
-            let is_playhead = playing && step == current_step;
+            let is_playhead = playing && step == playhead_step;
+            let past_loop = loop_length.is_some_and(|l| step >= l);
 
-            // Get note display for active steps
-            let note_display = if is_active {
+            // Get note display for active steps; tied steps show the held
+            // note carrying on
+            let note_display = if pattern.is_tied_var(track, step, loop_length.unwrap_or(steps), Variation::A) {
+                "==".to_string()
+            } else if is_active {
                 format_note(step_data.note, cell_width)
             } else {
                 String::new()
@@ -166,6 +248,13 @@ pub fn render_grid(
                         Style::default().fg(theme.highlight).bg(theme.bg),
                     )
                 }
+            } else if past_loop {
+                // Unplayed steps past the wrap, which gets a bar
+                let mark = if loop_length == Some(step) { "| " } else if is_active { "o " } else { "  " };
+                (
+                    format!("{:<width$}", mark, width = display_width as usize),
+                    Style::default().fg(theme.dimmed).bg(theme.bg),
+                )
             } else if is_active {
                 // Dim color based on velocity
                 let velocity_color = dim_color_by_velocity(theme.grid_active, step_data.velocity);
@@ -173,24 +262,53 @@ pub fn render_grid(
                     format!("{:<width$}", note_display, width = display_width as usize),
                     Style::default().fg(velocity_color).bg(theme.bg),
                 )
+            } else if step_data.off {
+                // Note-off step: the gate ends here
+                (
+                    format!("{:<width$}", "] ", width = display_width as usize),
+                    Style::default().fg(theme.grid_active).bg(theme.bg),
+                )
             } else {
-                // Beat markers (every 4 steps)
-                if step % 4 == 0 {
+                // Beat markers (every 4 steps, every 3 for triplets)
+                let beat = if triplet { TRIPLET_STEPS / 4 } else { STEPS / 4 };
+                if step % beat == 0 {
                     (
                         format!("{:<width$}", ". ", width = display_width as usize),
                         Style::default().fg(theme.dimmed).bg(theme.bg),
                     )
                 } else {
                     (
-                        format!("{:<width$}", "- ", width = display_width as usize),
+                        format!("{:<width$}", if triplet { "~ " } else { "- " }, width = display_width as usize),
                         Style::default().fg(theme.grid_inactive).bg(theme.bg),
                     )
                 }
             };
 
+            // Heat map: the beat's density behind everything but the cursor and playhead
+            let style = match heat.as_ref().map(|h| h[step * length / steps].heat) {
+                Some(level) if level > 0.0 && !is_cursor && !is_playhead => {
+                    style.fg(theme.bg).bg(heat_color(level, theme))
+                }
+                _ => style,
+            };
+            // Steps with parameter locks are underlined
+            let style = if pattern.has_param_locks(track, step, Variation::A) {
+                style.underlined()
+            } else {
+                style
+            };
+            // Acid articulation: accents bold, slides italic
+            let style = match (is_active && step_data.accent, is_active && step_data.slide) {
+                (true, true) => style.bold().italic(),
+                (true, false) => style.bold(),
+                (false, true) => style.italic(),
+                (false, false) => style,
+            };
+
+            let room = inner.x + inner.width - step_x;
             frame.render_widget(
                 ratatui::widgets::Paragraph::new(symbol).style(style),
-                Rect::new(step_x, track_y, display_width, 1),
+                Rect::new(step_x, track_y, display_width.min(room), 1),
             );
         }
     }
@@ -208,15 +326,29 @@ pub struct TransportInfo {
     pub cursor_note: Option<(bool, u8, u8, u8)>, // (active, note, velocity, probability)
     pub pending_pattern: Option<usize>,
     pub current_variation: Variation,
+    /// Global transpose of melodic tracks (semitones)
+    pub transpose: i8,
+    /// Clock source (slave: BPM follows MIDI clock in)
+    pub midi_sync: MidiSync,
+    /// Off-beat 16th delay, percent of half a step
+    pub swing: u8,
+    /// Headroom monitoring (no clipper), and whether the master went over
+    pub headroom: bool,
+    pub master_over: bool,
+    /// Sample capture from the audio input, armed or recording
+    pub capture: Option<CaptureStatus>,
 }
 
-/// Render transport status bar
+/// Render transport status bar; `compact` fits it on one line without a
+/// border, with shorter labels
 pub fn render_transport(
     frame: &mut Frame,
     area: Rect,
     info: &TransportInfo,
+    compact: bool,
     theme: &Theme,
 ) {
+    let sep = if compact { " " } else { " | " };
     let status = if info.playing { "PLAY" } else { "STOP" };
     let status_style = if info.playing {
         Style::default().fg(theme.meter_high).bold()
@@ -234,41 +366,105 @@ pub fn render_transport(
         Variation::B => "B",
     };
 
+    let pat_label = if compact { "" } else { "Pat: " };
     let pat_display = if let Some(pending) = info.pending_pattern {
-        format!("Pat: {:02}{}>:{:02}", info.current_pattern, var_str, pending)
+        format!(
+            "{}{}{}>:{}",
+            pat_label,
+            pattern_label(info.current_pattern),
+            var_str,
+            pattern_label(pending)
+        )
     } else {
-        format!("Pat: {:02}{}", info.current_pattern, var_str)
+        format!("{}{}{}", pat_label, pattern_label(info.current_pattern), var_str)
     };
 
     let mut transport_text = vec![
         Span::styled(format!(" {} ", status), status_style),
-        Span::styled(" | ", Style::default().fg(theme.border)),
+        Span::styled(sep, Style::default().fg(theme.border)),
         Span::styled(
             format!("{} ", mode_str),
             Style::default().fg(theme.highlight),
         ),
-        Span::styled(" | ", Style::default().fg(theme.border)),
+        Span::styled(sep, Style::default().fg(theme.border)),
         Span::styled(
             pat_display,
             Style::default().fg(theme.fg),
         ),
-        Span::styled(" | ", Style::default().fg(theme.border)),
+        Span::styled(sep, Style::default().fg(theme.border)),
         Span::styled(
-            format!("BPM: {:.0}", info.bpm),
+            if compact { format!("{:.0}bpm", info.bpm) } else { format!("BPM: {:.0}", info.bpm) },
             Style::default().fg(theme.fg),
         ),
-        Span::styled(" | ", Style::default().fg(theme.border)),
+    ];
+    match info.midi_sync {
+        MidiSync::Internal => {}
+        MidiSync::Slave => transport_text.push(Span::styled(
+            " MIDI in",
+            Style::default().fg(theme.highlight).bold(),
+        )),
+        MidiSync::Master => transport_text.push(Span::styled(
+            " MIDI out",
+            Style::default().fg(theme.highlight),
+        )),
+    }
+    if info.swing != 0 {
+        transport_text.push(Span::styled(
+            format!(" Sw:{}%", info.swing),
+            Style::default().fg(theme.highlight),
+        ));
+    }
+    if info.transpose != 0 {
+        transport_text.push(Span::styled(
+            format!(" Tr:{:+}", info.transpose),
+            Style::default().fg(theme.highlight).bold(),
+        ));
+    }
+    if info.headroom {
+        transport_text.push(if info.master_over {
+            Span::styled(" OVER", Style::default().fg(theme.meter_high).bold())
+        } else {
+            Span::styled(" HDRM", Style::default().fg(theme.highlight))
+        });
+    }
+    if let Some(capture) = info.capture {
+        transport_text.push(match capture.progress {
+            Some(progress) if capture.underruns > 0 => Span::styled(
+                format!(" REC T{} {:.0}% {} gaps", capture.track + 1, progress * 100.0, capture.underruns),
+                Style::default().fg(theme.meter_high).bold(),
+            ),
+            Some(progress) => Span::styled(
+                format!(" REC T{} {:.0}%", capture.track + 1, progress * 100.0),
+                Style::default().fg(theme.meter_high).bold(),
+            ),
+            None => Span::styled(
+                format!(" ARM T{}", capture.track + 1),
+                Style::default().fg(theme.meter_high),
+            ),
+        });
+    }
+    transport_text.extend([
+        Span::styled(sep, Style::default().fg(theme.border)),
         Span::styled(
-            format!("Step: {:2}/16", info.current_step + 1),
+            if compact {
+                format!("{:2}/16", info.current_step + 1)
+            } else {
+                format!("Step: {:2}/16", info.current_step + 1)
+            },
             Style::default().fg(theme.fg),
         ),
-    ];
+    ]);
 
     // Show song position in song mode
     if info.playback_mode == PlaybackMode::Song && info.arrangement_len > 0 {
-        transport_text.push(Span::styled(" | ", Style::default().fg(theme.border)));
+        transport_text.push(Span::styled(sep, Style::default().fg(theme.border)));
         transport_text.push(Span::styled(
-            format!("Song: {}/{}", info.arrangement_position + 1, info.arrangement_len),
+            format!(
+                "{}{}/{}",
+                if compact { "S" } else { "Song: " },
+                info.arrangement_position + 1,
+                info.arrangement_len
+            ),
             Style::default().fg(theme.highlight),
         ));
     }
@@ -276,22 +472,80 @@ pub fn render_transport(
     // Show note/velocity/probability info when cursor is on an active step
     if let Some((active, note, velocity, probability)) = info.cursor_note {
         if active {
-            transport_text.push(Span::styled(" | ", Style::default().fg(theme.border)));
+            transport_text.push(Span::styled(sep, Style::default().fg(theme.border)));
             transport_text.push(Span::styled(
-                format!("Note: {} Vel: {} Prob: {}%", note_name(note), velocity, probability),
+                if compact {
+                    format!("{} v{} {}%", note_name(note), velocity, probability)
+                } else {
+                    format!("Note: {} Vel: {} Prob: {}%", note_name(note), velocity, probability)
+                },
                 Style::default().fg(theme.highlight),
             ));
         }
     }
 
-    let transport = ratatui::widgets::Paragraph::new(Line::from(transport_text))
-        .style(Style::default().bg(theme.bg))
-        .block(
+    let transport = ratatui::widgets::Paragraph::new(Line::from(transport_text)).style(Style::default().bg(theme.bg));
+    let transport = if compact {
+        transport
+    } else {
+        transport.block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(theme.border))
                 .style(Style::default().bg(theme.bg)),
-        );
+        )
+    };
 
     frame.render_widget(transport, area);
 }
+
+/// Render the step inspector popup (opened by holding Space on a step)
+pub fn render_step_inspector(
+    frame: &mut Frame,
+    area: Rect,
+    track_name: &str,
+    step: usize,
+    data: StepData,
+    theme: &Theme,
+) {
+    let width = 36.min(area.width);
+    let height = 15.min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" {} step {} ", track_name, step + 1),
+            Style::default().fg(theme.highlight),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.highlight))
+        .style(Style::default().bg(theme.bg));
+
+    let label = Style::default().fg(theme.dimmed);
+    let value = Style::default().fg(theme.fg).bold();
+    let row = |name: &'static str, text: String| {
+        Line::from(vec![Span::styled(format!(" {:<12}", name), label), Span::styled(text, value)])
+    };
+    let lines = vec![
+        row("Active", if data.active { "on".to_string() } else { "off".to_string() }),
+        row("Note", format!("{} ({})", note_name(data.note), data.note)),
+        row("Velocity", data.velocity.to_string()),
+        row("Probability", format!("{}%", data.probability)),
+        row("Timing", format!("+{}% of a step", data.offset)),
+        row("Tie", if data.tie { "on".to_string() } else { "off".to_string() }),
+        row("Note-off", if data.off && !data.active { "on".to_string() } else { "off".to_string() }),
+        row("Slide", if data.slide { "on".to_string() } else { "off".to_string() }),
+        row("Accent", if data.accent { "on".to_string() } else { "off".to_string() }),
+        Line::from(""),
+        Line::from(Span::styled(" Up/Dn:Vel  Lt/Rt:Prob  [/]:Note", label)),
+        Line::from(Span::styled(" T:On/off  I:Tie  O:Off  Esc:Close", label)),
+        Line::from(Span::styled(" S:Slide  A:Accent", label)),
+    ];
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
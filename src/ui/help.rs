@@ -107,16 +107,33 @@ fn build_help_lines(theme: &Theme) -> Vec<Line<'static>> {
         "  ──────────────────────────────────────",
         dim_style,
     )));
-    add_key(&mut lines, "  Tab       ", "Cycle views: Grid > Params > Mixer > FX > Song", key_style, desc_style);
+    add_key(&mut lines, "  Tab       ", "Cycle views: Grid > Params > Mixer > FX > Song > Scope > Scripts", key_style, desc_style);
     add_key(&mut lines, "  Esc       ", "Return to Grid view", key_style, desc_style);
     add_key(&mut lines, "  G         ", "Toggle Help view", key_style, desc_style);
     add_key(&mut lines, "  Q         ", "Quit", key_style, desc_style);
     add_key(&mut lines, "  P         ", "Play / Pause toggle", key_style, desc_style);
     add_key(&mut lines, "  S         ", "Stop (reset to step 0)", key_style, desc_style);
     add_key(&mut lines, "  Ctrl+S    ", "Save project (.grox)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+A    ", "Save project as (type a file name)", key_style, desc_style);
     add_key(&mut lines, "  Ctrl+O    ", "Load project (.grox)", key_style, desc_style);
-    add_key(&mut lines, "  Ctrl+E    ", "Export current pattern as WAV", key_style, desc_style);
-    add_key(&mut lines, "  Ctrl+W    ", "Export song arrangement as WAV", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+U    ", "Restore a project backup (.bak1 = newest)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+E    ", "Queue current pattern as WAV export", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+W    ", "Queue song arrangement as WAV export", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+D    ", "Queue stems with a DAW session (form)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+Q    ", "Export queue (x cancel, c clear finished)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+K    ", "Export each track's hit as a WAV kit (kit/)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+R    ", "Start/stop recording a macro", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+Y    ", "Replay a macro (pick 1-9)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+T    ", "New project tab (empty or .grox file)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+Lt/Rt", "Switch tab (stops playback)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+X    ", "Close tab (confirms unsaved changes)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+C/B  ", "Copy pattern / cursor track sound", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+V    ", "Paste pattern or sound (any tab)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+N    ", "Notification log (errors, exports, agent actions)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+P    ", "Session stats (time, edits, patterns, song length)", key_style, desc_style);
+    add_key(&mut lines, "  Ctrl+G    ", "Settings (saved to config.json, Esc returns)", key_style, desc_style);
+    add_key(&mut lines, "  Alt+Left  ", "Hold: DJ sweep low-pass (returns on release)", key_style, desc_style);
+    add_key(&mut lines, "  Alt+Right ", "Hold: DJ sweep high-pass (returns on release)", key_style, desc_style);
     lines.push(Line::from(""));
 
     // Grid
@@ -126,17 +143,45 @@ fn build_help_lines(theme: &Theme) -> Vec<Line<'static>> {
         dim_style,
     )));
     add_key(&mut lines, "  Arrows    ", "Move cursor (also H/J/K/L)", key_style, desc_style);
-    add_key(&mut lines, "  Space     ", "Toggle step on/off", key_style, desc_style);
+    add_key(&mut lines, "  Space     ", "Toggle step on/off (hold: step inspector)", key_style, desc_style);
     add_key(&mut lines, "  Enter     ", "Toggle step on/off", key_style, desc_style);
     add_key(&mut lines, "  [ / ]     ", "Note down/up 1 semitone", key_style, desc_style);
     add_key(&mut lines, "  { / }     ", "Note down/up 1 octave", key_style, desc_style);
     add_key(&mut lines, "  + / -     ", "BPM up/down by 5", key_style, desc_style);
+    add_key(&mut lines, "  t         ", "Type in a BPM", key_style, desc_style);
+    add_key(&mut lines, "  z / Z     ", "Swing down/up (±5%)", key_style, desc_style);
+    add_key(&mut lines, "  n / N     ", "Pattern length down/up one step (1-64)", key_style, desc_style);
+    add_key(&mut lines, "  w         ", "Track loops after cursor step (again: with pattern)", key_style, desc_style);
+    add_key(&mut lines, "  ( / )     ", "Transpose melodic tracks down/up (±12)", key_style, desc_style);
+    add_key(&mut lines, "  ; / '     ", "Transpose cursor track down/up (±24)", key_style, desc_style);
+    add_key(&mut lines, "  : / \"     ", "Transpose cursor track by an octave", key_style, desc_style);
+    add_key(&mut lines, "  M         ", "Clock: internal / MIDI slave / master", key_style, desc_style);
     add_key(&mut lines, "  C         ", "Clear current track", key_style, desc_style);
-    add_key(&mut lines, "  F         ", "Fill current track", key_style, desc_style);
+    add_key(&mut lines, "  F         ", "Fill current track (hold: until released)", key_style, desc_style);
+    add_key(&mut lines, "            ", "No key releases? Double-tap to hold, tap again to end", key_style, desc_style);
+    add_key(&mut lines, "  1-9       ", "Pad: play track now", key_style, desc_style);
+    add_key(&mut lines, "  0         ", "Tone pad: start/stop the test tone", key_style, desc_style);
+    add_key(&mut lines, "  W         ", "Cycle tone: sine, sweep, pink noise, siren", key_style, desc_style);
+    add_key(&mut lines, "  H         ", "Heat map: color steps by beat density", key_style, desc_style);
+    add_key(&mut lines, "  O         ", "Loop-record 4 bars into empty slot", key_style, desc_style);
+    add_key(&mut lines, "  u         ", "Undo last loop-record pass", key_style, desc_style);
+    add_key(&mut lines, "  Q         ", "Quantize track timing 50% to grid", key_style, desc_style);
     add_key(&mut lines, "  , / .     ", "Previous / next pattern", key_style, desc_style);
+    add_key(&mut lines, "  < / >     ", "Same slot in previous / next bank", key_style, desc_style);
     add_key(&mut lines, "  Shift+L   ", "Open sample browser", key_style, desc_style);
-    add_key(&mut lines, "  Shift+A   ", "Add track (pick type: 1-5)", key_style, desc_style);
+    add_key(&mut lines, "  Shift+I   ", "Record audio input into track (again: cancel)", key_style, desc_style);
+    add_key(&mut lines, "  Shift+C   ", "Chop sampler into 16 slices across steps", key_style, desc_style);
+    add_key(&mut lines, "  Shift+A   ", "Add track (type 1-9, 6 = audio input, 7 = tone, 8 = chord stab, 9 = acid; a-z template)", key_style, desc_style);
+    add_key(&mut lines, "  Shift+F   ", "Save current track as a template", key_style, desc_style);
     add_key(&mut lines, "  Shift+D   ", "Remove current track", key_style, desc_style);
+    add_key(&mut lines, "  Shift+T   ", "Toggle triplet grid (12 steps a bar) for track", key_style, desc_style);
+    add_key(&mut lines, "  i         ", "Tie step onto the one before (hold the note)", key_style, desc_style);
+    add_key(&mut lines, "  o         ", "Note-off step: release the track's note here", key_style, desc_style);
+    add_key(&mut lines, "  b         ", "Play the cursor's step column (hold + Left/Right: scrub)", key_style, desc_style);
+    add_key(&mut lines, "  a         ", "Accent step (acid tracks: louder, harder filter)", key_style, desc_style);
+    add_key(&mut lines, "  Shift+S   ", "Slide into step's note (acid tracks: glide, no retrigger)", key_style, desc_style);
+    add_key(&mut lines, "  Shift+K   ", "Parameter locks of the cursor step", key_style, desc_style);
+    add_key(&mut lines, "            ", "Up/Dn param, Lt/Rt lock (H/L x5), X unlock, C clear", key_style, desc_style);
     lines.push(Line::from(""));
 
     // Params
@@ -147,9 +192,14 @@ fn build_help_lines(theme: &Theme) -> Vec<Line<'static>> {
     )));
     add_key(&mut lines, "  1-9       ", "Select track", key_style, desc_style);
     add_key(&mut lines, "  Up/Down   ", "Select parameter", key_style, desc_style);
-    add_key(&mut lines, "  Left/Right", "Adjust value (fine)", key_style, desc_style);
+    add_key(&mut lines, "  Left/Right", "Adjust value (fine; master Mode: gated reverb)", key_style, desc_style);
     add_key(&mut lines, "  [ / ]     ", "Adjust value (coarse)", key_style, desc_style);
     add_key(&mut lines, "  Shift+L   ", "Open sample browser", key_style, desc_style);
+    add_key(&mut lines, "  Shift+R   ", "Reload sample from disk", key_style, desc_style);
+    add_key(&mut lines, "  Shift+K   ", "Cycle project key (none, C..B)", key_style, desc_style);
+    add_key(&mut lines, "  Shift+T   ", "Tune sample's detected root to the key", key_style, desc_style);
+    add_key(&mut lines, "  Shift+B   ", "Repitch loop to the project BPM", key_style, desc_style);
+    add_key(&mut lines, "  Shift+V   ", "Edit velocity layers (a add, x remove, h/l [/] range)", key_style, desc_style);
     lines.push(Line::from(""));
 
     // Sample Browser
@@ -161,6 +211,9 @@ fn build_help_lines(theme: &Theme) -> Vec<Line<'static>> {
     add_key(&mut lines, "  Up/Down   ", "Navigate files (skip folder headers)", key_style, desc_style);
     add_key(&mut lines, "  Space     ", "Preview/audition selected sample", key_style, desc_style);
     add_key(&mut lines, "  Enter     ", "Load sample into track", key_style, desc_style);
+    add_key(&mut lines, "  N / T     ", "Toggle normalize / trim silence on load", key_style, desc_style);
+    add_key(&mut lines, "  Shift+K   ", "Load the selected file's folder as a kit (tracks 1-N)", key_style, desc_style);
+    add_key(&mut lines, "  D         ", "Cycle mix duck under previews (off, -6..-24 dB)", key_style, desc_style);
     add_key(&mut lines, "  Esc       ", "Cancel and close browser", key_style, desc_style);
     lines.push(Line::from(""));
 
@@ -171,10 +224,16 @@ fn build_help_lines(theme: &Theme) -> Vec<Line<'static>> {
         dim_style,
     )));
     add_key(&mut lines, "  1-9       ", "Select track", key_style, desc_style);
-    add_key(&mut lines, "  Up/Down   ", "Select field (Vol/Pan/Mute/Solo)", key_style, desc_style);
+    add_key(&mut lines, "  Up/Down   ", "Select field (Vol/Pan/Mute/Solo/Safe/Cue/Nudge)", key_style, desc_style);
     add_key(&mut lines, "  Left/Right", "Adjust value or toggle", key_style, desc_style);
     add_key(&mut lines, "  M         ", "Toggle mute", key_style, desc_style);
     add_key(&mut lines, "  O         ", "Toggle solo", key_style, desc_style);
+    add_key(&mut lines, "  Shift+O   ", "Toggle solo safe (stays audible while others solo)", key_style, desc_style);
+    add_key(&mut lines, "  U         ", "Toggle cue (headphones, needs --cue)", key_style, desc_style);
+    add_key(&mut lines, "  I         ", "Set track glyph (empty: from synth type)", key_style, desc_style);
+    add_key(&mut lines, "  C         ", "Reset clip indicators", key_style, desc_style);
+    add_key(&mut lines, "  A         ", "Suggest gain staging (A again to apply)", key_style, desc_style);
+    add_key(&mut lines, "  H         ", "Headroom monitoring (no master clipper)", key_style, desc_style);
     lines.push(Line::from(""));
 
     // FX
@@ -188,7 +247,8 @@ fn build_help_lines(theme: &Theme) -> Vec<Line<'static>> {
     add_key(&mut lines, "  Up/Down   ", "Select parameter", key_style, desc_style);
     add_key(&mut lines, "  Left/Right", "Adjust value (fine)", key_style, desc_style);
     add_key(&mut lines, "  [ / ]     ", "Adjust value (coarse)", key_style, desc_style);
-    add_key(&mut lines, "  Space     ", "Toggle effect on/off", key_style, desc_style);
+    add_key(&mut lines, "  Space     ", "Toggle effect on/off (mod: source off; limiter rows: limiter)", key_style, desc_style);
+    add_key(&mut lines, "  A         ", "Gain match filter/distortion (fair A/B)", key_style, desc_style);
     lines.push(Line::from(""));
 
     // Song
@@ -199,14 +259,65 @@ fn build_help_lines(theme: &Theme) -> Vec<Line<'static>> {
     )));
     add_key(&mut lines, "  Up/Down   ", "Navigate arrangement entries", key_style, desc_style);
     add_key(&mut lines, "  Left/Right", "Adjust repeat count", key_style, desc_style);
+    add_key(&mut lines, "  r / R     ", "Cycle entry chord degree", key_style, desc_style);
     add_key(&mut lines, "  + / -     ", "Cycle pattern index on entry", key_style, desc_style);
     add_key(&mut lines, "  A         ", "Append current pattern to arrangement", key_style, desc_style);
     add_key(&mut lines, "  D / Del   ", "Delete entry at cursor", key_style, desc_style);
     add_key(&mut lines, "  Enter     ", "Set entry to current pattern", key_style, desc_style);
     add_key(&mut lines, "  M         ", "Toggle Pattern/Song mode", key_style, desc_style);
+    add_key(&mut lines, "  B         ", "Crossfader mode: next used slot on deck B", key_style, desc_style);
+    add_key(&mut lines, "  { / }     ", "Previous / next slot on deck B", key_style, desc_style);
+    add_key(&mut lines, "  [ / ]     ", "Crossfader toward deck A / deck B", key_style, desc_style);
     add_key(&mut lines, "  , / .     ", "Previous / next pattern", key_style, desc_style);
+    add_key(&mut lines, "  < / >     ", "Same slot in previous / next bank", key_style, desc_style);
     add_key(&mut lines, "  C         ", "Copy pattern to empty slot", key_style, desc_style);
+    add_key(&mut lines, "  Shift+C   ", "Copy/merge selected tracks into another slot", key_style, desc_style);
+    add_key(&mut lines, "  V         ", "Mutate pattern into next empty slot", key_style, desc_style);
     add_key(&mut lines, "  X         ", "Clear current pattern", key_style, desc_style);
+    add_key(&mut lines, "  T         ", "Toggle arrangement list / timeline", key_style, desc_style);
+    add_key(&mut lines, "  F         ", "Cycle song fade-out (off/1/2/4/8/16 bars)", key_style, desc_style);
+    add_key(&mut lines, "  1-9       ", "Cycle track mute automation on entry", key_style, desc_style);
+    add_key(&mut lines, "  I         ", "Import import.mid into empty slots + arrangement", key_style, desc_style);
+    lines.push(Line::from(""));
+
+    // Pattern bank
+    lines.push(Line::from(Span::styled("  PATTERN BANK VIEW", header_style)));
+    lines.push(Line::from(Span::styled(
+        "  ──────────────────────────────────────",
+        dim_style,
+    )));
+    add_key(&mut lines, "  Arrows    ", "Move between slots", key_style, desc_style);
+    add_key(&mut lines, "  < / >     ", "Previous / next bank", key_style, desc_style);
+    add_key(&mut lines, "  Enter     ", "Switch to slot (queued while playing)", key_style, desc_style);
+    add_key(&mut lines, "  C         ", "Mark slot, C on another copies it there", key_style, desc_style);
+    add_key(&mut lines, "  X / Del   ", "Clear slot", key_style, desc_style);
+    add_key(&mut lines, "  A         ", "Chain slot onto the arrangement", key_style, desc_style);
+    lines.push(Line::from(""));
+
+    // Scope
+    lines.push(Line::from(Span::styled("  SCOPE VIEW", header_style)));
+    lines.push(Line::from(Span::styled(
+        "  ──────────────────────────────────────",
+        dim_style,
+    )));
+    add_key(&mut lines, "  1-9       ", "Overlay track spectrum on master (visual only)", key_style, desc_style);
+    add_key(&mut lines, "  0         ", "Master spectrum only", key_style, desc_style);
+    lines.push(Line::from(""));
+
+    // Scripts
+    lines.push(Line::from(Span::styled("  SCRIPTS VIEW", header_style)));
+    lines.push(Line::from(Span::styled(
+        "  ──────────────────────────────────────",
+        dim_style,
+    )));
+    add_key(&mut lines, "  Up/Down   ", "Select script", key_style, desc_style);
+    add_key(&mut lines, "  Space     ", "Enable / disable script", key_style, desc_style);
+    add_key(&mut lines, "  Left/Right", "Run every fewer / more bars", key_style, desc_style);
+    add_key(&mut lines, "  N         ", "New script (opens $EDITOR)", key_style, desc_style);
+    add_key(&mut lines, "  E         ", "Edit script in $EDITOR", key_style, desc_style);
+    add_key(&mut lines, "  L         ", "Load .rhai files from ./scripts/ and ~/.gridoxide/scripts/", key_style, desc_style);
+    add_key(&mut lines, "  R         ", "Run script once now", key_style, desc_style);
+    add_key(&mut lines, "  D         ", "Delete script", key_style, desc_style);
 
     lines
 }
@@ -0,0 +1,170 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Paragraph, Wrap};
+
+use crate::ui::Theme;
+
+/// Smallest terminal the interface is drawn in
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 16;
+
+/// Below this size views drop to their compact layout: a one-line
+/// transport and abbreviated labels
+const COMPACT_WIDTH: u16 = 80;
+const COMPACT_HEIGHT: u16 = 24;
+
+pub fn is_too_small(area: Rect) -> bool {
+    area.width < MIN_WIDTH || area.height < MIN_HEIGHT
+}
+
+pub fn is_compact(area: Rect) -> bool {
+    area.width < COMPACT_WIDTH || area.height < COMPACT_HEIGHT
+}
+
+/// Split the screen into header, transport, main view and footer rows
+pub fn screen_areas(area: Rect) -> [Rect; 4] {
+    let compact = is_compact(area);
+    Layout::vertical([
+        Constraint::Length(3),                           // Header
+        Constraint::Length(if compact { 1 } else { 3 }), // Transport
+        Constraint::Min(6),                              // Main content (grid or params)
+        Constraint::Length(3),                           // Footer
+    ])
+    .areas(area)
+}
+
+/// Drawn instead of the interface when the terminal is smaller than
+/// MIN_WIDTH x MIN_HEIGHT
+pub fn render_too_small(frame: &mut Frame, area: Rect, theme: &Theme) {
+    let lines = vec![
+        Line::from(Span::styled("Terminal too small", Style::default().fg(theme.highlight).bold())),
+        Line::from(Span::styled(
+            format!("{}x{}, need at least {}x{}", area.width, area.height, MIN_WIDTH, MIN_HEIGHT),
+            Style::default().fg(theme.fg),
+        )),
+    ];
+    let height = (lines.len() as u16).min(area.height);
+    let y = area.y + (area.height - height) / 2;
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .style(Style::default().bg(theme.bg)),
+        Rect::new(area.x, y, area.width, height),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use crate::audio::{AnalyzerTap, SequencerState};
+    use crate::config::Config;
+    use crate::ui::{
+        render_bank, render_fx, render_grid, render_help, render_mixer, render_params, render_scope,
+        render_scripts, render_settings, render_song, render_transport, BankState, FxEditorState, GridState,
+        HelpState, MixerState, ParamEditorState, ScriptsState, SettingsState, SongState, TransportInfo,
+    };
+
+    const SIZES: [(u16, u16); 4] = [(40, 12), (60, 20), (80, 24), (120, 40)];
+    const VIEWS: [&str; 10] = [
+        "grid", "params", "mixer", "fx", "song", "bank", "scope", "scripts", "settings", "help",
+    ];
+
+    /// Draw a view the way the app lays out the screen
+    fn draw_view(frame: &mut Frame, view: &str, state: &SequencerState, theme: &Theme) {
+        let area = frame.area();
+        if is_too_small(area) {
+            render_too_small(frame, area, theme);
+            return;
+        }
+        let [_, transport, main, _] = screen_areas(area);
+        let info = TransportInfo {
+            playing: state.playing,
+            bpm: state.bpm,
+            current_step: state.current_step,
+            current_pattern: state.current_pattern,
+            playback_mode: state.playback_mode,
+            arrangement_position: state.arrangement_position,
+            arrangement_len: state.arrangement.len(),
+            cursor_note: Some((true, 60, 100, 100)),
+            pending_pattern: None,
+            current_variation: state.current_variation,
+            transpose: state.transpose,
+            midi_sync: state.midi_sync,
+            swing: state.swing,
+            headroom: state.headroom,
+            master_over: false,
+            capture: None,
+        };
+        render_transport(frame, transport, &info, is_compact(area), theme);
+        match view {
+            "grid" => {
+                let names: Vec<String> = state.tracks.iter().map(|t| t.name.clone()).collect();
+                let glyphs: Vec<String> = state.tracks.iter().map(|t| t.display_glyph().to_string()).collect();
+                let triplets: Vec<bool> = state.tracks.iter().map(|t| t.triplet).collect();
+                let transposes: Vec<i8> = state.tracks.iter().map(|t| t.transpose).collect();
+                render_grid(
+                    frame,
+                    main,
+                    &state.pattern,
+                    &GridState::new(),
+                    0,
+                    0,
+                    false,
+                    &names,
+                    &glyphs,
+                    &triplets,
+                    &transposes,
+                    theme,
+                );
+            }
+            "params" => render_params(frame, main, state, &ParamEditorState::new(), theme),
+            "mixer" => render_mixer(frame, main, state, &MixerState::new(), theme),
+            "fx" => render_fx(frame, main, state, &FxEditorState::new(), theme),
+            "song" => render_song(frame, main, state, &SongState::new(), theme),
+            "bank" => render_bank(frame, main, state, &BankState::new(), theme),
+            "scope" => {
+                let (master, _) = AnalyzerTap::new().snapshot();
+                render_scope(frame, main, &master, None, state.sample_rate, theme);
+            }
+            "scripts" => render_scripts(frame, main, &state.scripts, &HashMap::new(), &ScriptsState::new(), theme),
+            "settings" => {
+                let settings = SettingsState::new();
+                render_settings(frame, main, &settings, &Config::default(), state.midi_sync, theme);
+            }
+            "help" => render_help(frame, main, &HelpState::new(), theme),
+            _ => unreachable!(),
+        }
+    }
+
+    fn screen_text(terminal: &Terminal<TestBackend>) -> String {
+        terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn every_view_draws_at_every_size() {
+        let state = SequencerState::new();
+        let theme = Theme::default();
+        for (width, height) in SIZES {
+            for view in VIEWS {
+                let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+                terminal
+                    .draw(|frame| draw_view(frame, view, &state, &theme))
+                    .unwrap_or_else(|e| panic!("{} at {}x{}: {}", view, width, height, e));
+                let too_small = width < MIN_WIDTH || height < MIN_HEIGHT;
+                assert_eq!(
+                    screen_text(&terminal).contains("Terminal too small"),
+                    too_small,
+                    "{} at {}x{}",
+                    view,
+                    width,
+                    height
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::audio::SequencerState;
+use crate::ui::{get_lockable_descriptors, get_snapshot_param_value, Theme};
+
+/// Parameter lock editor, a popup on the grid cursor's step
+pub struct LockEditor {
+    /// Param under the cursor, an index into the track's descriptors
+    pub param: usize,
+}
+
+impl LockEditor {
+    pub fn new() -> Self {
+        Self { param: 0 }
+    }
+
+    /// Move the param cursor by `delta` (clamped)
+    pub fn move_cursor(&mut self, delta: isize, count: usize) {
+        let last = count.saturating_sub(1) as isize;
+        self.param = (self.param as isize + delta).clamp(0, last) as usize;
+    }
+}
+
+impl Default for LockEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render the lock editor for a step: each of the track's params with its
+/// own value and the value the step locks it to
+pub fn render_lock_editor(
+    frame: &mut Frame,
+    area: Rect,
+    state: &SequencerState,
+    track: usize,
+    step: usize,
+    editor: &LockEditor,
+    theme: &Theme,
+) {
+    let Some(track_state) = state.tracks.get(track) else {
+        return;
+    };
+    let descriptors = get_lockable_descriptors(state, track);
+    let variation = state.current_variation;
+
+    let width = 44.min(area.width);
+    let height = (descriptors.len() as u16 + 4).clamp(6, 20).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, popup);
+
+    let locked = state.pattern.param_locks(track, step, variation).count();
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" {} step {} locks ({}) ", track_state.name, step + 1, locked),
+            Style::default().fg(theme.highlight),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.highlight))
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    // Param rows above a line of key hints, scrolled to the cursor
+    let visible = inner.height.saturating_sub(2) as usize;
+    let first = (editor.param + 1).saturating_sub(visible);
+    let label = Style::default().fg(theme.dimmed);
+    let mut lines: Vec<Line> = descriptors
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(visible)
+        .map(|(i, desc)| {
+            let own = get_snapshot_param_value(state, track, &desc.key);
+            let lock = state.pattern.param_lock(track, step, variation, &desc.key);
+            let style = if i == editor.param {
+                Style::default().fg(theme.bg).bg(theme.grid_cursor).bold()
+            } else if lock.is_some() {
+                Style::default().fg(theme.highlight)
+            } else {
+                Style::default().fg(theme.fg)
+            };
+            let lock_text = lock.map_or(String::new(), |value| format!("-> {:.2}", value));
+            Line::from(Span::styled(
+                format!(" {:<16} {:>8.2} {:<10}", desc.name, own, lock_text),
+                style,
+            ))
+        })
+        .collect();
+    while lines.len() < visible {
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" Lt/Rt:Lock  X:Unlock  C:Clear  Esc:Close", label)));
+    frame.render_widget(Paragraph::new(lines), inner);
+}
@@ -1,9 +1,21 @@
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph};
 
-use crate::audio::SequencerState;
+use crate::audio::{level_db, LevelMeter, SequencerState};
+use crate::project::gain::GainStaging;
 use crate::ui::Theme;
 
+/// Width of the master meter strip right of the tracks
+const MASTER_STRIP_WIDTH: u16 = 10;
+/// Bottom of the master level meter (dBFS)
+const METER_FLOOR_DB: f32 = -48.0;
+/// Limiter gain reduction shown by a full reduction bar (dB)
+const METER_REDUCTION_DB: f32 = 12.0;
+
+/// Narrowest track column; fewer tracks are shown (scrolled to the
+/// selected one) when they don't all fit
+const MIN_COLUMN_WIDTH: u16 = 8;
+
 /// Which field is selected in the mixer
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum MixerField {
@@ -11,19 +23,25 @@ pub enum MixerField {
     Pan,
     Mute,
     Solo,
+    SoloSafe,
+    Cue,
+    Nudge,
 }
 
 impl MixerField {
     pub fn count() -> usize {
-        4
+        7
     }
 
     pub fn from_index(i: usize) -> Self {
-        match i % 4 {
+        match i % 7 {
             0 => MixerField::Volume,
             1 => MixerField::Pan,
             2 => MixerField::Mute,
             3 => MixerField::Solo,
+            4 => MixerField::SoloSafe,
+            5 => MixerField::Cue,
+            6 => MixerField::Nudge,
             _ => unreachable!(),
         }
     }
@@ -34,6 +52,9 @@ impl MixerField {
             MixerField::Pan => 1,
             MixerField::Mute => 2,
             MixerField::Solo => 3,
+            MixerField::SoloSafe => 4,
+            MixerField::Cue => 5,
+            MixerField::Nudge => 6,
         }
     }
 }
@@ -42,6 +63,9 @@ impl MixerField {
 pub struct MixerState {
     pub selected_track: usize,
     pub selected_field: MixerField,
+    /// Gain staging suggestion waiting to be applied (shown in place of
+    /// the peak row)
+    pub gain_staging: Option<GainStaging>,
 }
 
 impl MixerState {
@@ -49,6 +73,7 @@ impl MixerState {
         Self {
             selected_track: 0,
             selected_field: MixerField::Volume,
+            gain_staging: None,
         }
     }
 
@@ -80,53 +105,87 @@ pub fn render_mixer(
     theme: &Theme,
 ) {
     let num_tracks = state.tracks.len();
-
     let block = Block::default()
-        .title(Span::styled(
-            " Mixer ",
-            Style::default().fg(theme.track_label),
-        ))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(theme.border))
         .style(Style::default().bg(theme.bg));
-
     let inner = block.inner(area);
-    frame.render_widget(block, area);
+
+    // Master meters on the right when the tracks still get 8 columns each
+    let strip = inner.width >= num_tracks as u16 * MIN_COLUMN_WIDTH + MASTER_STRIP_WIDTH;
+    let (inner, strip_area) = if strip {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(MASTER_STRIP_WIDTH)])
+            .split(inner);
+        (columns[0], Some(columns[1]))
+    } else {
+        (inner, None)
+    };
+
+    // Columns for the tracks that fit, scrolled to the selected one
+    let columns = Columns::new(inner.width, num_tracks, mixer_state.selected_track);
+
+    let master = &state.master_meter;
+    let mut title = vec![
+        Span::styled(" Mixer ", Style::default().fg(theme.track_label)),
+        Span::styled(" Master ", Style::default().fg(theme.dimmed)),
+        Span::styled(format!("{} ", meter_label(master)), meter_style(master, theme)),
+    ];
+    if columns.count < num_tracks {
+        title.push(Span::styled(
+            format!(" Tracks {}-{}/{} ", columns.first + 1, columns.first + columns.count, num_tracks),
+            Style::default().fg(theme.highlight),
+        ));
+    }
+    title.push(Span::styled(
+        match &state.cue_output {
+            Some(output) => format!(" Cue: {} ", output),
+            None => " Cue: off ".to_string(),
+        },
+        Style::default().fg(theme.dimmed),
+    ));
+    if let Some(staging) = &mixer_state.gain_staging {
+        title.push(Span::styled(
+            format!(
+                " Gain staging: peak {:.1} -> {:.1}dB, a to apply ",
+                staging.peak_db, staging.suggested_peak_db
+            ),
+            Style::default().fg(theme.highlight),
+        ));
+    }
+    frame.render_widget(block.title(Line::from(title)), area);
 
     if num_tracks == 0 {
         return;
     }
+    if let Some(strip_area) = strip_area {
+        render_master_strip(frame, strip_area, state, theme);
+    }
 
     // Layout: track headers, faders, values
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1), // Track name headers
-            Constraint::Min(4),   // Volume faders
-            Constraint::Length(1), // Volume values
-            Constraint::Length(1), // Pan values
-            Constraint::Length(1), // Mute toggles
-            Constraint::Length(1), // Solo toggles
-        ])
-        .split(inner);
-
-    // Calculate column width for each track
-    let col_width = (inner.width / num_tracks as u16).max(8);
+    let chunks = strip_rows(inner);
 
     // Track headers
-    render_track_headers(frame, chunks[0], state, mixer_state, col_width, theme);
+    render_track_headers(frame, chunks[0], state, mixer_state, &columns, theme);
 
     // Volume faders (vertical bars)
-    render_volume_faders(frame, chunks[1], state, mixer_state, col_width, theme);
+    render_volume_faders(frame, chunks[1], state, mixer_state, &columns, theme);
+
+    // Peak hold and clip indicators, or the pending gain trims
+    match &mixer_state.gain_staging {
+        Some(staging) => render_gain_row(frame, chunks[2], staging, &columns, theme),
+        None => render_meter_row(frame, chunks[2], state, &columns, theme),
+    }
 
     // Volume values
     render_value_row(
         frame,
-        chunks[2],
+        chunks[3],
         state,
         mixer_state,
         MixerField::Volume,
-        col_width,
+        &columns,
         theme,
         |t| format!("{:.2}", t.volume),
         "VOL",
@@ -135,11 +194,11 @@ pub fn render_mixer(
     // Pan values
     render_value_row(
         frame,
-        chunks[3],
+        chunks[4],
         state,
         mixer_state,
         MixerField::Pan,
-        col_width,
+        &columns,
         theme,
         |t| {
             if t.pan < -0.05 {
@@ -156,11 +215,11 @@ pub fn render_mixer(
     // Mute toggles
     render_toggle_row(
         frame,
-        chunks[4],
+        chunks[5],
         state,
         mixer_state,
         MixerField::Mute,
-        col_width,
+        &columns,
         theme,
         |t| t.mute,
         "M",
@@ -170,16 +229,195 @@ pub fn render_mixer(
     // Solo toggles
     render_toggle_row(
         frame,
-        chunks[5],
+        chunks[6],
         state,
         mixer_state,
         MixerField::Solo,
-        col_width,
+        &columns,
         theme,
         |t| t.solo,
         "S",
         "SOLO",
     );
+
+    // Solo safe toggles
+    render_toggle_row(
+        frame,
+        chunks[7],
+        state,
+        mixer_state,
+        MixerField::SoloSafe,
+        &columns,
+        theme,
+        |t| t.solo_safe,
+        "*",
+        "SAFE",
+    );
+
+    // Cue toggles
+    render_toggle_row(
+        frame,
+        chunks[8],
+        state,
+        mixer_state,
+        MixerField::Cue,
+        &columns,
+        theme,
+        |t| t.cue,
+        "C",
+        "CUE",
+    );
+
+    // Timing nudge
+    render_value_row(
+        frame,
+        chunks[9],
+        state,
+        mixer_state,
+        MixerField::Nudge,
+        &columns,
+        theme,
+        |t| {
+            if t.nudge_ms > 0.0 {
+                format!("+{:.0}ms", t.nudge_ms)
+            } else {
+                "0ms".to_string()
+            }
+        },
+        "NUDGE",
+    );
+}
+
+/// The track columns that fit the mixer's width
+struct Columns {
+    /// First track shown
+    first: usize,
+    /// Tracks shown
+    count: usize,
+    width: u16,
+}
+
+impl Columns {
+    /// As many tracks as fit at MIN_COLUMN_WIDTH, scrolled so `selected`
+    /// is among them
+    fn new(area_width: u16, num_tracks: usize, selected: usize) -> Self {
+        let num_tracks = num_tracks.max(1);
+        let width = (area_width / num_tracks as u16).max(MIN_COLUMN_WIDTH);
+        let count = ((area_width / width) as usize).clamp(1, num_tracks);
+        let first = selected.min(num_tracks - 1).saturating_sub(count - 1);
+        Self { first, count, width }
+    }
+
+    /// Each shown track with its column's x offset
+    fn iter(&self) -> impl Iterator<Item = (usize, u16)> {
+        let width = self.width;
+        (self.first..self.first + self.count).zip((0..).map(move |i| i * width))
+    }
+
+    /// Columns taken up by the tracks
+    fn used(&self) -> u16 {
+        self.count as u16 * self.width
+    }
+}
+
+/// Rows of a channel strip: header, faders, peak, volume, pan, mute,
+/// solo, solo safe, cue and nudge
+fn strip_rows(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Track name headers
+            Constraint::Min(4),   // Volume faders
+            Constraint::Length(1), // Peak hold / clip indicators
+            Constraint::Length(1), // Volume values
+            Constraint::Length(1), // Pan values
+            Constraint::Length(1), // Mute toggles
+            Constraint::Length(1), // Solo toggles
+            Constraint::Length(1), // Solo safe toggles
+            Constraint::Length(1), // Cue toggles
+            Constraint::Length(1), // Nudge values
+        ])
+        .split(area)
+}
+
+/// Height of a level on a meter bar (0-1, METER_FLOOR_DB to 0 dBFS)
+fn meter_fraction(level: f32) -> f32 {
+    ((level_db(level) - METER_FLOOR_DB) / -METER_FLOOR_DB).clamp(0.0, 1.0)
+}
+
+/// Meter color for a row `fraction` of the way up a bar
+fn meter_color(fraction: f32, theme: &Theme) -> Color {
+    if fraction > 0.85 {
+        theme.meter_high
+    } else if fraction > 0.6 {
+        theme.meter_mid
+    } else {
+        theme.meter_low
+    }
+}
+
+/// Level in dB for a readout, "-inf" for silence
+fn db_label(level: f32) -> String {
+    if level < 1e-4 {
+        "-inf".to_string()
+    } else {
+        format!("{:.1}", level_db(level))
+    }
+}
+
+/// Master output meter: peak level with its hold mark and the limiter's
+/// gain reduction hanging from the top, then true peak, RMS and reduction
+/// readouts
+fn render_master_strip(frame: &mut Frame, area: Rect, state: &SequencerState, theme: &Theme) {
+    let rows = strip_rows(area);
+    let master = &state.master_meter;
+    let dimmed = Style::default().fg(theme.dimmed);
+    let line = |frame: &mut Frame, row: Rect, text: String, style: Style| {
+        frame.render_widget(Paragraph::new(format!("{:^width$}", text, width = row.width as usize)).style(style), row);
+    };
+
+    line(frame, rows[0], "MASTER".to_string(), Style::default().fg(theme.track_label));
+
+    let meter = rows[1];
+    let height = meter.height;
+    let filled = (meter_fraction(master.level) * height as f32).round() as u16;
+    let hold_row = (meter_fraction(master.peak_hold) * height as f32).round() as u16;
+    let reduction_rows = ((state.limiter_reduction / METER_REDUCTION_DB).clamp(0.0, 1.0) * height as f32).round() as u16;
+    let bar_x = meter.x + (meter.width.saturating_sub(6)) / 2;
+    for row in 0..height {
+        let y = meter.y + (height - 1 - row);
+        let color = meter_color(row as f32 / height as f32, theme);
+        let (bar, style) = if row < filled {
+            ("\u{2588}\u{2588}\u{2588}", Style::default().fg(color))
+        } else if row + 1 == hold_row {
+            ("\u{2580}\u{2580}\u{2580}", Style::default().fg(color))
+        } else {
+            ("\u{2591}\u{2591}\u{2591}", Style::default().fg(theme.grid_inactive))
+        };
+        frame.render_widget(Paragraph::new(bar).style(style.bg(theme.bg)), Rect::new(bar_x, y, 3, 1));
+        // Reduction grows down from the top
+        let (gr, gr_style) = if height - row <= reduction_rows {
+            ("\u{2588}\u{2588}", Style::default().fg(theme.highlight))
+        } else {
+            ("\u{2591}\u{2591}", Style::default().fg(theme.grid_inactive))
+        };
+        frame.render_widget(Paragraph::new(gr).style(gr_style.bg(theme.bg)), Rect::new(bar_x + 4, y, 2, 1));
+    }
+
+    line(frame, rows[2], meter_label(master), meter_style(master, theme));
+    let true_peak_style = if state.master_true_peak > 1.0 {
+        Style::default().fg(theme.meter_high).bold()
+    } else {
+        dimmed
+    };
+    line(frame, rows[3], format!("TP {}", db_label(state.master_true_peak)), true_peak_style);
+    line(frame, rows[4], format!("RMS {}", db_label(master.rms)), dimmed);
+    let limiter = if state.master_fx.limiter_enabled {
+        format!("GR {:.1}", state.limiter_reduction)
+    } else {
+        "LIM off".to_string()
+    };
+    line(frame, rows[5], limiter, dimmed);
 }
 
 fn render_track_headers(
@@ -187,15 +425,11 @@ fn render_track_headers(
     area: Rect,
     state: &SequencerState,
     mixer_state: &MixerState,
-    col_width: u16,
+    columns: &Columns,
     theme: &Theme,
 ) {
-    let num_tracks = state.tracks.len();
-    for i in 0..num_tracks {
-        let x = area.x + i as u16 * col_width;
-        if x >= area.x + area.width {
-            break;
-        }
+    for (i, offset) in columns.iter() {
+        let x = area.x + offset;
 
         let style = if i == mixer_state.selected_track {
             Style::default()
@@ -206,10 +440,15 @@ fn render_track_headers(
             Style::default().fg(theme.track_label)
         };
 
-        let label = format!("{:^width$}", state.tracks[i].name, width = col_width as usize);
+        let track = &state.tracks[i];
+        let label = format!(
+            "{:^width$}",
+            format!("{} {}", track.display_glyph(), track.name),
+            width = columns.width as usize
+        );
         frame.render_widget(
             Paragraph::new(label).style(style),
-            Rect::new(x, area.y, col_width, 1),
+            Rect::new(x, area.y, columns.width, 1),
         );
     }
 }
@@ -219,7 +458,7 @@ fn render_volume_faders(
     area: Rect,
     state: &SequencerState,
     mixer_state: &MixerState,
-    col_width: u16,
+    columns: &Columns,
     theme: &Theme,
 ) {
     let fader_height = area.height;
@@ -227,29 +466,26 @@ fn render_volume_faders(
         return;
     }
 
-    let num_tracks = state.tracks.len();
     let any_solo = state.tracks.iter().any(|t| t.solo);
 
-    for track in 0..num_tracks {
-        let x = area.x + track as u16 * col_width;
-        if x >= area.x + area.width {
-            break;
-        }
+    for (track, offset) in columns.iter() {
+        let x = area.x + offset;
 
         let volume = state.tracks[track].volume;
         let filled = (volume * fader_height as f32).round() as u16;
         let is_selected =
             track == mixer_state.selected_track && mixer_state.selected_field == MixerField::Volume;
-        let is_muted = state.tracks[track].mute;
-        let is_audible = if any_solo {
-            state.tracks[track].solo
-        } else {
-            !is_muted
-        };
-
-        // Center the fader bar in the column
-        let bar_width = (col_width - 2).max(2).min(4);
-        let bar_x = x + (col_width - bar_width) / 2;
+        let is_audible = state.tracks[track].is_audible(any_solo);
+
+        // Fader bar and the track's level meter, centered in the column
+        let bar_width = columns.width.saturating_sub(5).clamp(2, 4);
+        let bar_x = x + (columns.width.saturating_sub(bar_width + 3)) / 2;
+        render_track_meter(
+            frame,
+            Rect::new(bar_x + bar_width + 1, area.y, 2, fader_height),
+            &state.tracks[track].meter,
+            theme,
+        );
 
         for row in 0..fader_height {
             let y = area.y + (fader_height - 1 - row);
@@ -265,14 +501,7 @@ fn render_volume_faders(
                 }
             } else if is_filled {
                 // Color based on level
-                let level = row as f32 / fader_height as f32;
-                let color = if level > 0.85 {
-                    theme.meter_high
-                } else if level > 0.6 {
-                    theme.meter_mid
-                } else {
-                    theme.meter_low
-                };
+                let color = meter_color(row as f32 / fader_height as f32, theme);
                 Style::default().fg(color).bg(theme.bg)
             } else {
                 Style::default().fg(theme.grid_inactive).bg(theme.bg)
@@ -288,25 +517,111 @@ fn render_volume_faders(
     }
 }
 
+/// A track's level meter: RMS as a solid bar, the falling peak above it
+/// shaded, and the peak hold as a mark
+fn render_track_meter(frame: &mut Frame, area: Rect, meter: &LevelMeter, theme: &Theme) {
+    let height = area.height;
+    let rows = |level: f32| (meter_fraction(level) * height as f32).round() as u16;
+    let (rms_rows, peak_rows, hold_row) = (rows(meter.rms), rows(meter.level), rows(meter.peak_hold));
+    for row in 0..height {
+        let color = meter_color(row as f32 / height as f32, theme);
+        let (bar, fg) = if row < rms_rows {
+            ("\u{2588}\u{2588}", color)
+        } else if row < peak_rows {
+            ("\u{2593}\u{2593}", color)
+        } else if row + 1 == hold_row {
+            ("\u{2580}\u{2580}", color)
+        } else {
+            ("\u{2591}\u{2591}", theme.grid_inactive)
+        };
+        let y = area.y + (height - 1 - row);
+        frame.render_widget(
+            Paragraph::new(bar).style(Style::default().fg(fg).bg(theme.bg)),
+            Rect::new(area.x, y, area.width, 1),
+        );
+    }
+}
+
+/// Peak hold in dBFS, or the clip count once a track has clipped
+fn meter_label(meter: &LevelMeter) -> String {
+    if meter.clipping() {
+        format!("CLIP {}", meter.clips)
+    } else if meter.peak_hold < 1e-4 {
+        "-inf".to_string()
+    } else {
+        format!("{:.1}dB", level_db(meter.peak_hold))
+    }
+}
+
+fn meter_style(meter: &LevelMeter, theme: &Theme) -> Style {
+    if meter.clipping() {
+        Style::default().fg(theme.meter_high).bold()
+    } else {
+        Style::default().fg(theme.dimmed)
+    }
+}
+
+fn render_meter_row(frame: &mut Frame, area: Rect, state: &SequencerState, columns: &Columns, theme: &Theme) {
+    for (i, offset) in columns.iter() {
+        let Some(track) = state.tracks.get(i) else {
+            break;
+        };
+        let x = area.x + offset;
+        let display = format!("{:^width$}", meter_label(&track.meter), width = columns.width as usize);
+        frame.render_widget(
+            Paragraph::new(display).style(meter_style(&track.meter, theme)),
+            Rect::new(x, area.y, columns.width, 1),
+        );
+    }
+
+    let label_x = area.x + columns.used();
+    if label_x + 4 <= area.x + area.width {
+        frame.render_widget(
+            Paragraph::new(" PEAK").style(Style::default().fg(theme.dimmed)),
+            Rect::new(label_x, area.y, (area.width - columns.used()).min(6), 1),
+        );
+    }
+}
+
+fn render_gain_row(frame: &mut Frame, area: Rect, staging: &GainStaging, columns: &Columns, theme: &Theme) {
+    for (i, offset) in columns.iter() {
+        let Some(trim) = staging.trims.get(i) else {
+            break;
+        };
+        let x = area.x + offset;
+        let db = trim.trim_db();
+        let text = if db.abs() < 0.05 { "=".to_string() } else { format!("{:+.1}dB", db) };
+        let display = format!("{:^width$}", text, width = columns.width as usize);
+        frame.render_widget(
+            Paragraph::new(display).style(Style::default().fg(theme.highlight)),
+            Rect::new(x, area.y, columns.width, 1),
+        );
+    }
+
+    let label_x = area.x + columns.used();
+    if label_x + 4 <= area.x + area.width {
+        frame.render_widget(
+            Paragraph::new(" TRIM").style(Style::default().fg(theme.highlight)),
+            Rect::new(label_x, area.y, (area.width - columns.used()).min(6), 1),
+        );
+    }
+}
+
 fn render_value_row<F>(
     frame: &mut Frame,
     area: Rect,
     state: &SequencerState,
     mixer_state: &MixerState,
     field: MixerField,
-    col_width: u16,
+    columns: &Columns,
     theme: &Theme,
     format_fn: F,
     label: &str,
 ) where
     F: Fn(&crate::audio::TrackState) -> String,
 {
-    let num_tracks = state.tracks.len();
-    for track in 0..num_tracks {
-        let x = area.x + track as u16 * col_width;
-        if x >= area.x + area.width {
-            break;
-        }
+    for (track, offset) in columns.iter() {
+        let x = area.x + offset;
 
         let is_selected =
             track == mixer_state.selected_track && mixer_state.selected_field == field;
@@ -318,19 +633,19 @@ fn render_value_row<F>(
         };
 
         let text = format_fn(&state.tracks[track]);
-        let display = format!("{:^width$}", text, width = col_width as usize);
+        let display = format!("{:^width$}", text, width = columns.width as usize);
         frame.render_widget(
             Paragraph::new(display).style(style),
-            Rect::new(x, area.y, col_width, 1),
+            Rect::new(x, area.y, columns.width, 1),
         );
     }
 
     // Row label on the right if space allows
-    let label_x = area.x + num_tracks as u16 * col_width;
+    let label_x = area.x + columns.used();
     if label_x + label.len() as u16 <= area.x + area.width {
         frame.render_widget(
             Paragraph::new(format!(" {}", label)).style(Style::default().fg(theme.dimmed)),
-            Rect::new(label_x, area.y, (area.width - num_tracks as u16 * col_width).min(6), 1),
+            Rect::new(label_x, area.y, (area.width - columns.used()).min(6), 1),
         );
     }
 }
@@ -341,7 +656,7 @@ fn render_toggle_row<F>(
     state: &SequencerState,
     mixer_state: &MixerState,
     field: MixerField,
-    col_width: u16,
+    columns: &Columns,
     theme: &Theme,
     get_value: F,
     active_char: &str,
@@ -349,12 +664,8 @@ fn render_toggle_row<F>(
 ) where
     F: Fn(&crate::audio::TrackState) -> bool,
 {
-    let num_tracks = state.tracks.len();
-    for track in 0..num_tracks {
-        let x = area.x + track as u16 * col_width;
-        if x >= area.x + area.width {
-            break;
-        }
+    for (track, offset) in columns.iter() {
+        let x = area.x + offset;
 
         let is_selected =
             track == mixer_state.selected_track && mixer_state.selected_field == field;
@@ -381,19 +692,19 @@ fn render_toggle_row<F>(
             Style::default().fg(theme.dimmed)
         };
 
-        let display = format!("{:^width$}", text, width = col_width as usize);
+        let display = format!("{:^width$}", text, width = columns.width as usize);
         frame.render_widget(
             Paragraph::new(display).style(style),
-            Rect::new(x, area.y, col_width, 1),
+            Rect::new(x, area.y, columns.width, 1),
         );
     }
 
     // Row label
-    let label_x = area.x + num_tracks as u16 * col_width;
+    let label_x = area.x + columns.used();
     if label_x + label.len() as u16 <= area.x + area.width {
         frame.render_widget(
             Paragraph::new(format!(" {}", label)).style(Style::default().fg(theme.dimmed)),
-            Rect::new(label_x, area.y, (area.width - num_tracks as u16 * col_width).min(6), 1),
+            Rect::new(label_x, area.y, (area.width - columns.used()).min(6), 1),
         );
     }
 }
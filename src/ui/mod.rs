@@ -1,17 +1,43 @@
+pub mod bank;
 pub mod browser;
 pub mod fx;
+pub mod gesture;
 pub mod grid;
 pub mod help;
+pub mod layout;
+pub mod locks;
 pub mod mixer;
+pub mod modal;
+pub mod notifications;
 pub mod params;
+pub mod prompt;
+pub mod queue;
+pub mod scope;
+pub mod scripts;
+pub mod settings;
 pub mod song;
+pub mod stats;
 pub mod theme;
 
+pub use bank::{render_bank, BankState};
 pub use browser::{render_browser, BrowserState};
 pub use fx::{render_fx, FxEditorState};
-pub use grid::{render_grid, render_transport, GridState, TransportInfo};
+pub use gesture::{Gesture, LongPress};
+pub use grid::{
+    render_grid, render_step_inspector, render_transport, track_steps, GridState, TransportInfo,
+};
 pub use help::{render_help, HelpState};
+pub use layout::{is_compact, is_too_small, render_too_small, screen_areas};
+pub use locks::{render_lock_editor, LockEditor};
 pub use mixer::{render_mixer, MixerField, MixerState};
-pub use params::{get_param_descriptors, get_snapshot_param_value, render_params, ParamEditorState};
+pub use modal::{render_modal, FormField, Modal, ModalEvent};
+pub use notifications::{render_notifications, NotificationLog, Severity};
+pub use params::{get_lockable_descriptors, get_param_descriptors, get_snapshot_param_value, render_params, sample_layers, ParamEditorState};
+pub use prompt::{Prompt, PromptHistory, PromptKind};
+pub use queue::render_export_queue;
+pub use scope::{render_scope, ScopeState};
+pub use scripts::{render_scripts, ScriptsState};
+pub use settings::{render_settings, SettingsField, SettingsState};
 pub use song::{render_song, SongState};
+pub use stats::render_stats;
 pub use theme::{Theme, dim_color_by_velocity};
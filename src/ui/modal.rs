@@ -0,0 +1,271 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::ui::prompt::{Prompt, PromptAction, TextInput};
+use crate::ui::Theme;
+
+/// Widest a modal gets (columns)
+const MODAL_WIDTH: u16 = 60;
+/// Form label column width
+const LABEL_WIDTH: usize = 14;
+
+/// Value of one form field
+pub enum FieldValue {
+    Text(TextInput),
+    /// One of the options, cycled with Left/Right
+    Choice { options: Vec<String>, selected: usize },
+    Toggle(bool),
+}
+
+impl FieldValue {
+    /// Text of a text field, or the selected option of a choice
+    pub fn text(&self) -> &str {
+        match self {
+            FieldValue::Text(input) => input.text(),
+            FieldValue::Choice { options, selected } => &options[*selected],
+            FieldValue::Toggle(on) => if *on { "on" } else { "off" },
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        matches!(self, FieldValue::Toggle(true))
+    }
+}
+
+pub struct FormField {
+    pub label: String,
+    pub value: FieldValue,
+}
+
+impl FormField {
+    pub fn text(label: &str, value: &str) -> Self {
+        Self { label: label.to_string(), value: FieldValue::Text(TextInput::new(value)) }
+    }
+
+    pub fn choice(label: &str, options: &[&str], selected: usize) -> Self {
+        Self {
+            label: label.to_string(),
+            value: FieldValue::Choice {
+                options: options.iter().map(|o| o.to_string()).collect(),
+                selected: selected.min(options.len().saturating_sub(1)),
+            },
+        }
+    }
+
+    pub fn toggle(label: &str, on: bool) -> Self {
+        Self { label: label.to_string(), value: FieldValue::Toggle(on) }
+    }
+}
+
+/// What a modal shows and how it takes keys
+pub enum ModalBody {
+    /// Yes/no question
+    Confirm(String),
+    /// Items picked with Up/Down and Enter, or by their key
+    Picker { items: Vec<(char, String)>, cursor: usize },
+    /// Fields stepped through with Up/Down or Tab, submitted with Enter
+    Form { fields: Vec<FormField>, focus: usize },
+    Prompt(Prompt),
+}
+
+/// What a key did to a modal
+pub enum ModalEvent {
+    /// Still open
+    Open,
+    Cancel,
+    /// Yes on a confirmation
+    Confirm,
+    /// Index of the picked item
+    Picked(usize),
+    /// Enter on a form (read the fields before closing it)
+    Submit,
+    /// Accepted prompt text
+    Text(String),
+}
+
+/// Dialog drawn over the current view that takes all keys while open.
+/// `tag` tells the app what it is for.
+pub struct Modal<T> {
+    pub title: String,
+    pub body: ModalBody,
+    pub tag: T,
+}
+
+impl<T> Modal<T> {
+    pub fn confirm(title: &str, message: String, tag: T) -> Self {
+        Self { title: title.to_string(), body: ModalBody::Confirm(message), tag }
+    }
+
+    pub fn picker(title: &str, items: Vec<(char, String)>, tag: T) -> Self {
+        Self { title: title.to_string(), body: ModalBody::Picker { items, cursor: 0 }, tag }
+    }
+
+    pub fn form(title: &str, fields: Vec<FormField>, tag: T) -> Self {
+        Self { title: title.to_string(), body: ModalBody::Form { fields, focus: 0 }, tag }
+    }
+
+    pub fn prompt(title: &str, prompt: Prompt, tag: T) -> Self {
+        Self { title: title.to_string(), body: ModalBody::Prompt(prompt), tag }
+    }
+
+    /// Form field value by index
+    pub fn field(&self, index: usize) -> Option<&FieldValue> {
+        match &self.body {
+            ModalBody::Form { fields, .. } => fields.get(index).map(|f| &f.value),
+            _ => None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ModalEvent {
+        match &mut self.body {
+            ModalBody::Confirm(_) => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => ModalEvent::Confirm,
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => ModalEvent::Cancel,
+                _ => ModalEvent::Open,
+            },
+            ModalBody::Picker { items, cursor } => match key.code {
+                KeyCode::Esc => ModalEvent::Cancel,
+                KeyCode::Enter if !items.is_empty() => ModalEvent::Picked(*cursor),
+                KeyCode::Up => {
+                    *cursor = cursor.saturating_sub(1);
+                    ModalEvent::Open
+                }
+                KeyCode::Down => {
+                    *cursor = (*cursor + 1).min(items.len().saturating_sub(1));
+                    ModalEvent::Open
+                }
+                KeyCode::Char(c) => match items.iter().position(|(k, _)| *k == c) {
+                    Some(i) => ModalEvent::Picked(i),
+                    None => ModalEvent::Open,
+                },
+                _ => ModalEvent::Open,
+            },
+            ModalBody::Form { fields, focus } => {
+                match key.code {
+                    KeyCode::Esc => return ModalEvent::Cancel,
+                    KeyCode::Enter => return ModalEvent::Submit,
+                    KeyCode::Up | KeyCode::BackTab => *focus = (*focus + fields.len() - 1) % fields.len(),
+                    KeyCode::Down | KeyCode::Tab => *focus = (*focus + 1) % fields.len(),
+                    _ => match &mut fields[*focus].value {
+                        FieldValue::Text(input) => {
+                            input.handle_key(key);
+                        }
+                        FieldValue::Choice { options, selected } => match key.code {
+                            KeyCode::Left => *selected = (*selected + options.len() - 1) % options.len(),
+                            KeyCode::Right | KeyCode::Char(' ') => *selected = (*selected + 1) % options.len(),
+                            _ => {}
+                        },
+                        FieldValue::Toggle(on) => {
+                            if matches!(key.code, KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')) {
+                                *on = !*on;
+                            }
+                        }
+                    },
+                }
+                ModalEvent::Open
+            }
+            ModalBody::Prompt(prompt) => match prompt.handle_key(key) {
+                PromptAction::Editing => ModalEvent::Open,
+                PromptAction::Submit(text) => ModalEvent::Text(text),
+                PromptAction::Cancel => ModalEvent::Cancel,
+            },
+        }
+    }
+
+    /// Body lines and the key hint for a modal `width` columns wide
+    /// showing up to `rows` body lines
+    fn lines(&self, width: u16, rows: usize, theme: &Theme) -> (Vec<Line<'static>>, Option<&'static str>) {
+        let text_style = Style::default().fg(theme.fg);
+        let key_style = Style::default().fg(theme.grid_active);
+        match &self.body {
+            ModalBody::Confirm(message) => (
+                message.lines().map(|l| Line::from(Span::styled(format!(" {}", l), text_style))).collect(),
+                Some(" [y/Enter] Yes  [n/Esc] No"),
+            ),
+            ModalBody::Picker { items, cursor } => {
+                // Scroll so the cursor stays in view
+                let skip = (*cursor + 1).saturating_sub(rows.max(1));
+                let lines = items
+                    .iter()
+                    .enumerate()
+                    .skip(skip)
+                    .take(rows)
+                    .map(|(i, (key, label))| {
+                        let style = if i == *cursor {
+                            Style::default().fg(theme.bg).bg(theme.highlight)
+                        } else {
+                            text_style
+                        };
+                        Line::from(vec![
+                            Span::styled(format!(" [{}] ", key), key_style),
+                            Span::styled(label.clone(), style),
+                        ])
+                    })
+                    .collect();
+                (lines, Some(" [Up/Down] Move  [Enter/key] Pick  [Esc] Cancel"))
+            }
+            ModalBody::Form { fields, focus } => {
+                let room = (width as usize).saturating_sub(LABEL_WIDTH + 4);
+                let lines = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| {
+                        let focused = i == *focus;
+                        let label_style = if focused {
+                            Style::default().fg(theme.highlight).bold()
+                        } else {
+                            Style::default().fg(theme.track_label)
+                        };
+                        let mut spans = vec![Span::styled(
+                            format!(" {:<width$}", field.label, width = LABEL_WIDTH),
+                            label_style,
+                        )];
+                        match &field.value {
+                            FieldValue::Text(input) => spans.extend(input.spans(room, focused, theme)),
+                            FieldValue::Choice { options, selected } => spans.push(Span::styled(
+                                format!("< {} >", options[*selected]),
+                                text_style,
+                            )),
+                            FieldValue::Toggle(on) => {
+                                spans.push(Span::styled(if *on { "[x]" } else { "[ ]" }, text_style))
+                            }
+                        }
+                        Line::from(spans)
+                    })
+                    .collect();
+                (lines, Some(" [Up/Down] Field  [Left/Right] Change  [Enter] OK  [Esc] Cancel"))
+            }
+            ModalBody::Prompt(prompt) => (prompt.lines(width, theme), None),
+        }
+    }
+}
+
+/// Render a modal as a box centered in `area`, sized to its content
+pub fn render_modal<T>(frame: &mut Frame, area: Rect, modal: &Modal<T>, theme: &Theme) {
+    let width = area.width.saturating_sub(4).min(MODAL_WIDTH);
+    let inner_width = width.saturating_sub(2);
+    // Room for the body between the borders and the hint line
+    let rows = area.height.saturating_sub(3) as usize;
+    let (mut lines, hint) = modal.lines(inner_width, rows, theme);
+    if let Some(hint) = hint {
+        lines.push(Line::from(Span::styled(hint, Style::default().fg(theme.dimmed))));
+    }
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let modal_area = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(Span::styled(format!(" {} ", modal.title), Style::default().fg(theme.highlight)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.highlight))
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+    frame.render_widget(Paragraph::new(lines).style(Style::default().bg(theme.bg)), inner);
+}
@@ -0,0 +1,186 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::ui::browser::centered_rect;
+use crate::ui::Theme;
+
+/// Notifications kept for the session before the oldest are dropped
+const MAX_NOTIFICATIONS: usize = 500;
+/// How long the latest notification stays in the footer
+pub const NOTIFICATION_SHOW_TIME: Duration = Duration::from_secs(3);
+
+/// How important a notification is (sets its color)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn color(self, theme: &Theme) -> Color {
+        match self {
+            Severity::Info => theme.fg,
+            Severity::Success => theme.grid_active,
+            Severity::Warning => theme.meter_mid,
+            Severity::Error => theme.meter_high,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Success => " OK ",
+            Severity::Warning => "WARN",
+            Severity::Error => "ERR ",
+        }
+    }
+}
+
+pub struct Notification {
+    pub message: String,
+    pub severity: Severity,
+    pub at: Instant,
+}
+
+/// Session log of status messages, newest last. Also holds the scroll
+/// position of the log overlay.
+pub struct NotificationLog {
+    entries: VecDeque<Notification>,
+    started: Instant,
+    /// Entries scrolled up from the newest
+    pub scroll: usize,
+    /// Whether the log overlay is open
+    pub open: bool,
+    /// Errors/warnings added since the overlay was last opened
+    unseen: usize,
+}
+
+impl NotificationLog {
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            started: Instant::now(),
+            scroll: 0,
+            open: false,
+            unseen: 0,
+        }
+    }
+
+    pub fn push(&mut self, severity: Severity, message: String) {
+        if !self.open && matches!(severity, Severity::Warning | Severity::Error) {
+            self.unseen += 1;
+        }
+        self.entries.push_back(Notification {
+            message,
+            severity,
+            at: Instant::now(),
+        });
+        while self.entries.len() > MAX_NOTIFICATIONS {
+            self.entries.pop_front();
+        }
+        // Keep the overlay's view still while new entries arrive
+        if self.open && self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.entries.len().saturating_sub(1));
+        }
+    }
+
+    /// Latest notification, if it is still recent enough for the footer
+    pub fn current(&self) -> Option<&Notification> {
+        self.entries
+            .back()
+            .filter(|n| n.at.elapsed() < NOTIFICATION_SHOW_TIME)
+    }
+
+    /// Errors/warnings not yet seen in the overlay
+    pub fn unseen(&self) -> usize {
+        self.unseen
+    }
+
+    pub fn show(&mut self) {
+        self.open = true;
+        self.scroll = 0;
+        self.unseen = 0;
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.entries.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.scroll = 0;
+    }
+}
+
+impl Default for NotificationLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render the notification log as a modal overlay, newest at the bottom
+pub fn render_notifications(frame: &mut Frame, area: Rect, log: &NotificationLog, theme: &Theme) {
+    let modal_area = centered_rect(80, 90, area);
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" Notifications ({}) ", log.entries.len()),
+            Style::default().fg(theme.highlight),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.highlight))
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let height = inner.height.saturating_sub(1) as usize;
+    if log.entries.is_empty() {
+        let empty = Paragraph::new("  (no notifications this session)")
+            .style(Style::default().fg(theme.dimmed).bg(theme.bg));
+        frame.render_widget(empty, inner);
+    } else {
+        let end = log.entries.len() - log.scroll.min(log.entries.len() - 1);
+        let start = end.saturating_sub(height);
+        let lines: Vec<Line> = log
+            .entries
+            .range(start..end)
+            .map(|n| {
+                let secs = n.at.duration_since(log.started).as_secs();
+                let color = n.severity.color(theme);
+                Line::from(vec![
+                    Span::styled(
+                        format!("  {:02}:{:02}:{:02} ", secs / 3600, secs / 60 % 60, secs % 60),
+                        Style::default().fg(theme.dimmed),
+                    ),
+                    Span::styled(format!("[{}] ", n.severity.label()), Style::default().fg(color).bold()),
+                    Span::styled(n.message.clone(), Style::default().fg(color)),
+                ])
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).style(Style::default().bg(theme.bg)),
+            Rect::new(inner.x, inner.y, inner.width, inner.height.saturating_sub(1)),
+        );
+    }
+
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("  [Up/Down/PgUp/PgDn]", Style::default().fg(theme.grid_active)),
+        Span::styled(" Scroll  ", Style::default().fg(theme.fg)),
+        Span::styled("[C]", Style::default().fg(theme.grid_active)),
+        Span::styled(" Clear  ", Style::default().fg(theme.fg)),
+        Span::styled("[Esc]", Style::default().fg(theme.grid_active)),
+        Span::styled(" Close", Style::default().fg(theme.fg)),
+    ]))
+    .style(Style::default().bg(theme.bg));
+    frame.render_widget(
+        footer,
+        Rect::new(inner.x, inner.y + inner.height.saturating_sub(1), inner.width, 1),
+    );
+}
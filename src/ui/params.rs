@@ -1,14 +1,31 @@
+use std::path::Path;
+
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph};
 
 use crate::audio::SequencerState;
-use crate::synth::ParamDescriptor;
+use crate::synth::{
+    chord_name, load_wav, param_tuning, prepare_sample, split_cents, tuning_label, ParamDescriptor, SampleLayer,
+    SampleOptions, SynthType, NOTE_NAMES,
+};
 use crate::ui::Theme;
 
+/// Peak overview of a sampler's main sample as it plays (trimmed/normalized)
+pub struct Waveform {
+    /// File and options the peaks were computed from
+    source: (String, SampleOptions),
+    /// Peak level per column (empty if the file couldn't be read)
+    peaks: Vec<f32>,
+}
+
 /// State for parameter editor view
 pub struct ParamEditorState {
     pub track: usize,
     pub param_index: usize,
+    /// Selected sampler velocity layer while editing the layer map (None = editing params)
+    pub layer_index: Option<usize>,
+    /// Waveform of the selected track's sample, if it is a sampler
+    pub waveform: Option<Waveform>,
 }
 
 impl ParamEditorState {
@@ -16,7 +33,32 @@ impl ParamEditorState {
         Self {
             track: 0,
             param_index: 0,
+            layer_index: None,
+            waveform: None,
+        }
+    }
+
+    /// Re-read the selected sampler's sample when it or its normalize/trim
+    /// options change, so the waveform shows what the track plays
+    pub fn refresh_waveform(&mut self, state: &SequencerState) {
+        let source = state
+            .tracks
+            .get(self.track)
+            .filter(|t| t.synth_type == SynthType::Sampler)
+            .and_then(|t| {
+                let path = t.params_snapshot.get("wav_path")?.as_str()?;
+                let options = SampleOptions::from_params(&t.params_snapshot);
+                (!path.is_empty()).then(|| (path.to_string(), options))
+            });
+        if self.waveform.as_ref().map(|w| &w.source) == source.as_ref() {
+            return;
         }
+        self.waveform = source.map(|source| {
+            let peaks = load_wav(Path::new(&source.0), state.sample_rate)
+                .map(|buffer| waveform_peaks(&prepare_sample(buffer, source.1), ENVELOPE_WIDTH))
+                .unwrap_or_default();
+            Waveform { source, peaks }
+        });
     }
 
     /// Move parameter selection up/down
@@ -33,6 +75,7 @@ impl ParamEditorState {
         if track < num_tracks {
             self.track = track;
             self.param_index = 0;
+            self.layer_index = None;
         }
     }
 }
@@ -56,13 +99,32 @@ pub fn get_param_descriptors(state: &SequencerState, track: usize) -> Vec<ParamD
     synth.param_descriptors()
 }
 
+/// Descriptors of the params a step on `track` can lock
+pub fn get_lockable_descriptors(state: &SequencerState, track: usize) -> Vec<ParamDescriptor> {
+    let mut descriptors = get_param_descriptors(state, track);
+    descriptors.retain(ParamDescriptor::lockable);
+    descriptors
+}
+
 /// Get a parameter value from the state's params_snapshot
 pub fn get_snapshot_param_value(state: &SequencerState, track: usize, key: &str) -> f32 {
     if track >= state.tracks.len() {
         return 0.0;
     }
     let snapshot = &state.tracks[track].params_snapshot;
-    snapshot.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0) as f32
+    // Toggles are stored as booleans
+    let value = snapshot.get(key).and_then(|v| v.as_f64().or_else(|| v.as_bool().map(|b| b as u8 as f64)));
+    value.unwrap_or(0.0) as f32
+}
+
+/// Velocity layers of a sampler track, from the state's params_snapshot
+pub fn sample_layers(state: &SequencerState, track: usize) -> Vec<SampleLayer> {
+    state
+        .tracks
+        .get(track)
+        .and_then(|t| t.params_snapshot.get("layers"))
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
 }
 
 /// Render the parameter editor view
@@ -137,6 +199,7 @@ fn render_param_list(
     theme: &Theme,
 ) {
     let descriptors = get_param_descriptors(state, editor.track);
+    let synth_type = state.tracks.get(editor.track).map(|t| t.synth_type);
 
     let mut lines = Vec::new();
 
@@ -181,8 +244,254 @@ fn render_param_list(
             Span::styled("] ", Style::default().fg(theme.border)),
             Span::styled(value_str, style),
         ]));
+        if synth_type == Some(SynthType::Stab) && desc.key == "chord" {
+            let line = lines.last_mut().unwrap();
+            line.spans.push(Span::styled(format!("  {}", chord_name(value)), Style::default().fg(theme.meter_low)));
+        }
+        if let Some((note, cents)) = synth_type.and_then(|st| param_tuning(st, &desc.key, value)) {
+            let line = lines.last_mut().unwrap();
+            line.spans.push(Span::styled(
+                format!("  {}", tuning_label(note, cents)),
+                Style::default().fg(theme.meter_low),
+            ));
+        }
+    }
+
+    // Project key, plus the detected root for samplers
+    let key = state.key.map_or("-", |k| NOTE_NAMES[k as usize % 12]);
+    let mut spans = vec![Span::styled(format!("{:>13}  {}", "Key", key), Style::default().fg(theme.dimmed))];
+    if synth_type == Some(SynthType::Sampler) {
+        let detected = state.tracks[editor.track]
+            .params_snapshot
+            .get("detected_note")
+            .and_then(|v| v.as_f64());
+        let root = match detected {
+            Some(note) => {
+                let (note, cents) = split_cents(note as f32);
+                tuning_label(note, cents)
+            }
+            None => "unpitched".to_string(),
+        };
+        spans.push(Span::styled(format!("   Root  {}", root), Style::default().fg(theme.meter_low)));
+        spans.push(Span::styled("   [T] tune to key", Style::default().fg(theme.dimmed)));
+        let bpm = state.tracks[editor.track]
+            .params_snapshot
+            .get("detected_bpm")
+            .and_then(|v| v.as_f64());
+        if let Some(bpm) = bpm {
+            spans.push(Span::styled(format!("   Loop {:.1} BPM", bpm), Style::default().fg(theme.meter_low)));
+            spans.push(Span::styled("   [B] fit to project", Style::default().fg(theme.dimmed)));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(spans));
+
+    if synth_type == Some(SynthType::Sampler) {
+        render_waveform(&mut lines, state, editor, theme);
+    }
+
+    if let Some(st) = synth_type {
+        render_envelope(&mut lines, state, editor.track, st, theme);
+    }
+
+    if synth_type == Some(SynthType::Sampler) {
+        render_layer_map(&mut lines, state, editor, theme);
     }
 
     let para = Paragraph::new(lines).style(Style::default().bg(theme.bg));
     frame.render_widget(para, area);
 }
+
+/// Columns and rows of the envelope curve
+const ENVELOPE_WIDTH: usize = 40;
+const ENVELOPE_ROWS: usize = 3;
+/// Sampler sustain is drawn held for this share of the attack + decay + release time
+const SUSTAIN_HOLD: f32 = 0.25;
+/// Eighth-height blocks for the curve, empty to full
+const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Amplitude envelope of a track sampled across the curve width, with the
+/// time it spans in ms. The sampler's ADSR is scaled to fit; the drum and
+/// bass decays use the voice's longest length, so tweaks show as a change
+/// in shape. None for synths without an envelope.
+fn envelope_curve(state: &SequencerState, track: usize, synth_type: SynthType) -> Option<(Vec<f32>, f32)> {
+    let p = |key: &str| get_snapshot_param_value(state, track, key);
+    let (span_ms, level): (f32, Box<dyn Fn(f32) -> f32>) = match synth_type {
+        SynthType::Sampler => {
+            let (a, d, s, r) = (p("attack"), p("decay"), p("sustain"), p("release"));
+            let hold = (a + d + r) * SUSTAIN_HOLD;
+            let level = move |t: f32| {
+                if t < a {
+                    t / a
+                } else if t < a + d {
+                    1.0 - (t - a) / d * (1.0 - s)
+                } else if t < a + d + hold {
+                    s
+                } else {
+                    s * (1.0 - (t - a - d - hold) / r).max(0.0)
+                }
+            };
+            (a + d + hold + r, Box::new(level))
+        }
+        SynthType::Kick => {
+            let decay = p("amp_decay");
+            let length = 100.0 + 200.0 * (20.0 - decay) / 15.0;
+            (300.0, Box::new(move |t: f32| if t < length { (-t * 0.001 * decay).exp() } else { 0.0 }))
+        }
+        SynthType::Snare => {
+            let (tone, noise) = (p("tone_decay"), p("noise_decay"));
+            (150.0, Box::new(move |t: f32| (-t * 0.001 * tone).exp().max((-t * 0.001 * noise).exp())))
+        }
+        SynthType::HiHat => {
+            let open = p("open");
+            let decay = p("decay") * (1.0 - open * 0.7);
+            let length = if open > 0.5 { 200.0 } else { 50.0 } * (1.0 + open * 3.0);
+            (800.0, Box::new(move |t: f32| if t < length { (-t * 0.001 * decay).exp() } else { 0.0 }))
+        }
+        SynthType::Bass => {
+            let decay = p("decay");
+            let level = move |t: f32| if t < 10.0 { t / 10.0 } else { (-(t - 10.0) * 0.001 * decay).exp() };
+            (250.0, Box::new(level))
+        }
+        SynthType::Stab => {
+            let decay = p("decay") * 1000.0;
+            let level = move |t: f32| if t < 2.0 { t / 2.0 } else { (-(t - 2.0) * 4.6 / decay).exp() };
+            (decay * 1.2, Box::new(level))
+        }
+        SynthType::Acid => {
+            let decay = p("amp_decay") * 1000.0;
+            let level = move |t: f32| if t < 3.0 { t / 3.0 } else { (-(t - 3.0) * 4.6 / decay).exp() };
+            (decay * 1.2, Box::new(level))
+        }
+        _ => return None,
+    };
+    let curve = (0..ENVELOPE_WIDTH)
+        .map(|i| level((i as f32 + 0.5) / ENVELOPE_WIDTH as f32 * span_ms).clamp(0.0, 1.0))
+        .collect();
+    Some((curve, span_ms))
+}
+
+/// Amplitude envelope drawn with block characters, redrawn as its params change
+fn render_envelope(lines: &mut Vec<Line>, state: &SequencerState, track: usize, synth_type: SynthType, theme: &Theme) {
+    let Some((curve, span_ms)) = envelope_curve(state, track, synth_type) else {
+        return;
+    };
+    lines.push(Line::from(""));
+    for row in (0..ENVELOPE_ROWS).rev() {
+        let bar: String = curve
+            .iter()
+            .map(|level| {
+                let eighths = (level * (ENVELOPE_ROWS * 8) as f32).round() as usize;
+                BLOCKS[eighths.saturating_sub(row * 8).min(8)]
+            })
+            .collect();
+        let label = if row == ENVELOPE_ROWS - 1 { "Envelope" } else { "" };
+        let mut spans = vec![
+            Span::styled(format!("{:>13}  ", label), Style::default().fg(theme.dimmed)),
+            Span::styled(bar, Style::default().fg(theme.grid_active)),
+        ];
+        if row == 0 {
+            spans.push(Span::styled(format!("  {:.0} ms", span_ms), Style::default().fg(theme.dimmed)));
+        }
+        lines.push(Line::from(spans));
+    }
+}
+
+/// Largest absolute sample in each of `columns` equal slices of a buffer
+fn waveform_peaks(buffer: &[f32], columns: usize) -> Vec<f32> {
+    if buffer.is_empty() {
+        return Vec::new();
+    }
+    (0..columns)
+        .map(|i| {
+            let start = i * buffer.len() / columns;
+            let end = ((i + 1) * buffer.len() / columns).max(start + 1);
+            buffer[start..end].iter().fold(0.0f32, |m, s| m.max(s.abs()))
+        })
+        .collect()
+}
+
+/// Sample waveform as loaded, with the part outside the start/end points dimmed
+fn render_waveform(lines: &mut Vec<Line>, state: &SequencerState, editor: &ParamEditorState, theme: &Theme) {
+    let Some(waveform) = editor.waveform.as_ref().filter(|w| !w.peaks.is_empty()) else {
+        return;
+    };
+    let start = get_snapshot_param_value(state, editor.track, "start_point");
+    let end = get_snapshot_param_value(state, editor.track, "end_point");
+    let columns = waveform.peaks.len();
+    let (source, options) = &waveform.source;
+    let mut label = Vec::new();
+    if options.trim_silence {
+        label.push("trimmed");
+    }
+    if options.normalize {
+        label.push("normalized");
+    }
+    lines.push(Line::from(""));
+    for row in (0..ENVELOPE_ROWS).rev() {
+        let mut spans = vec![Span::styled(
+            format!("{:>13}  ", if row == ENVELOPE_ROWS - 1 { "Waveform" } else { "" }),
+            Style::default().fg(theme.dimmed),
+        )];
+        for (i, peak) in waveform.peaks.iter().enumerate() {
+            let eighths = (peak.clamp(0.0, 1.0) * (ENVELOPE_ROWS * 8) as f32).round() as usize;
+            let block = BLOCKS[eighths.saturating_sub(row * 8).min(8)];
+            let position = (i as f32 + 0.5) / columns as f32;
+            let color = if (start..=end).contains(&position) { theme.meter_low } else { theme.dimmed };
+            spans.push(Span::styled(block.to_string(), Style::default().fg(color)));
+        }
+        if row == 0 {
+            let name = Path::new(source).file_name().map_or(source.clone(), |n| n.to_string_lossy().to_string());
+            let label = if label.is_empty() { name } else { format!("{} ({})", name, label.join(", ")) };
+            spans.push(Span::styled(format!("  {}", label), Style::default().fg(theme.dimmed)));
+        }
+        lines.push(Line::from(spans));
+    }
+}
+
+/// Velocity layer map of a sampler track: one line per layer, main sample last
+fn render_layer_map(lines: &mut Vec<Line>, state: &SequencerState, editor: &ParamEditorState, theme: &Theme) {
+    let layers = sample_layers(state, editor.track);
+    let file_name = |path: &str| {
+        std::path::Path::new(path)
+            .file_name()
+            .map_or(path.to_string(), |n| n.to_string_lossy().to_string())
+    };
+    let hint = if editor.layer_index.is_some() {
+        "   a add  Enter replace  x remove  h/l low edge  [ ] high edge  V done"
+    } else {
+        "   [V] edit"
+    };
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(format!("{:>13}", "Vel layers"), Style::default().fg(theme.dimmed)),
+        Span::styled(hint, Style::default().fg(theme.dimmed)),
+    ]));
+    for (i, layer) in layers.iter().enumerate() {
+        let is_selected = editor.layer_index == Some(i);
+        let style = if is_selected {
+            Style::default().fg(theme.highlight).bold()
+        } else {
+            Style::default().fg(theme.fg)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(if is_selected { ">" } else { " " }, style),
+            Span::styled(format!("{:>12}", i + 1), style),
+            Span::styled(
+                format!("  vel {:>3}-{:<3}  ", layer.min_velocity, layer.max_velocity),
+                style,
+            ),
+            Span::styled(file_name(&layer.wav_path), Style::default().fg(theme.meter_low)),
+        ]));
+    }
+    let main = state.tracks[editor.track]
+        .params_snapshot
+        .get("wav_path")
+        .and_then(|v| v.as_str())
+        .map_or("-".to_string(), file_name);
+    let label = if layers.is_empty() { "all" } else { "other" };
+    lines.push(Line::from(vec![
+        Span::styled(format!("{:>13}  vel {:<7}  ", "main", label), Style::default().fg(theme.dimmed)),
+        Span::styled(main, Style::default().fg(theme.meter_low)),
+    ]));
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+
+use crate::ui::{Severity, Theme};
+
+/// Submitted entries remembered per prompt
+const MAX_PROMPT_HISTORY: usize = 20;
+
+/// Checks a prompt's text before it is accepted, returning why not
+pub type Validator = fn(&str) -> Result<(), String>;
+
+/// Single-line text being edited. The cursor counts chars, not bytes.
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    text: String,
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Input holding `text`, cursor at the end
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.chars().count(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text, cursor at the end
+    pub fn set(&mut self, text: &str) {
+        *self = Self::new(text);
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text.char_indices().nth(cursor).map_or(self.text.len(), |(i, _)| i)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_index(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Delete the char before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let at = self.byte_index(self.cursor);
+            self.text.remove(at);
+        }
+    }
+
+    /// Delete the char under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.text.chars().count() {
+            let at = self.byte_index(self.cursor);
+            self.text.remove(at);
+        }
+    }
+
+    /// Delete the word before the cursor (and the spaces after it)
+    pub fn delete_word(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        let (from, to) = (self.byte_index(start), self.byte_index(self.cursor));
+        self.text.replace_range(from..to, "");
+        self.cursor = start;
+    }
+
+    /// Delete everything before the cursor
+    pub fn clear_to_start(&mut self) {
+        let to = self.byte_index(self.cursor);
+        self.text.replace_range(..to, "");
+        self.cursor = 0;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.text.chars().count());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.chars().count();
+    }
+
+    /// Apply an editing key; false if the key isn't one
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('u') if ctrl => self.clear_to_start(),
+            KeyCode::Char('w') if ctrl => self.delete_word(),
+            KeyCode::Char('a') if ctrl => self.home(),
+            KeyCode::Char('e') if ctrl => self.end(),
+            KeyCode::Char(_) if ctrl => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// The text as spans fitting `room` columns, scrolled to keep the
+    /// cursor in view; the cursor is only drawn when focused
+    pub fn spans(&self, room: usize, focused: bool, theme: &Theme) -> Vec<Span<'static>> {
+        let text_style = Style::default().fg(theme.fg);
+        let chars: Vec<char> = self.text.chars().collect();
+        if !focused {
+            return vec![Span::styled(chars.iter().take(room).collect::<String>(), text_style)];
+        }
+        let room = room.max(1);
+        let start = (self.cursor + 1).saturating_sub(room);
+        let visible: String = chars.iter().skip(start).take(self.cursor - start).collect();
+        let under = chars.get(self.cursor).map_or(" ".to_string(), |c| c.to_string());
+        let after: String = chars
+            .iter()
+            .skip(self.cursor + 1)
+            .take(room.saturating_sub(self.cursor - start + 1))
+            .collect();
+        vec![
+            Span::styled(visible, text_style),
+            Span::styled(under, Style::default().fg(theme.bg).bg(theme.grid_cursor)),
+            Span::styled(after, text_style),
+        ]
+    }
+}
+
+/// What a prompt's text is for (each keeps its own history)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PromptKind {
+    SaveAs,
+    Bpm,
+    /// Sample directory added in the settings view
+    SampleDir,
+    /// Glyph for the mixer's selected track
+    Glyph,
+}
+
+/// Outcome of a key in a prompt
+pub enum PromptAction {
+    Editing,
+    /// Enter on text that passed validation
+    Submit(String),
+    Cancel,
+}
+
+/// One-line text prompt, shown in a modal: cursor editing, a validator
+/// checked on Enter, and Up/Down through earlier entries of the same kind
+pub struct Prompt {
+    input: TextInput,
+    validate: Validator,
+    /// Earlier entries, oldest first
+    history: Vec<String>,
+    /// History entry shown (None = the text being typed)
+    history_pos: Option<usize>,
+    /// Text typed before browsing the history
+    draft: String,
+    /// Why the last Enter was refused
+    error: Option<String>,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind, initial: &str, validate: Validator, history: &PromptHistory) -> Self {
+        Self {
+            input: TextInput::new(initial),
+            validate,
+            history: history.get(kind).to_vec(),
+            history_pos: None,
+            draft: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PromptAction {
+        match key.code {
+            KeyCode::Esc => return PromptAction::Cancel,
+            KeyCode::Enter => {
+                let text = self.input.text().trim().to_string();
+                match (self.validate)(&text) {
+                    Ok(()) => return PromptAction::Submit(text),
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            KeyCode::Up => self.recall(true),
+            KeyCode::Down => self.recall(false),
+            _ => {
+                if self.input.handle_key(key) {
+                    self.error = None;
+                    self.history_pos = None;
+                }
+            }
+        }
+        PromptAction::Editing
+    }
+
+    /// Step to an older (or newer) history entry; past the newest is the draft
+    fn recall(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let pos = match (self.history_pos, older) {
+            (None, true) => {
+                self.draft = self.input.text().to_string();
+                Some(self.history.len() - 1)
+            }
+            (None, false) => return,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < self.history.len() => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.history_pos = pos;
+        let text = pos.map_or(self.draft.clone(), |i| self.history[i].clone());
+        self.input.set(&text);
+        self.error = None;
+    }
+
+    /// Input line and a hint (or the validation error) for a modal
+    /// `width` columns wide
+    pub fn lines(&self, width: u16, theme: &Theme) -> Vec<Line<'static>> {
+        let mut input = vec![Span::styled(" > ", Style::default().fg(theme.grid_active))];
+        input.extend(self.input.spans((width as usize).saturating_sub(4), true, theme));
+        let hint = match self.error {
+            Some(ref error) => Line::from(Span::styled(
+                format!(" {}", error),
+                Style::default().fg(Severity::Error.color(theme)),
+            )),
+            None => Line::from(Span::styled(
+                " [Enter] OK  [Esc] Cancel  [Up/Down] History",
+                Style::default().fg(theme.dimmed),
+            )),
+        };
+        vec![Line::from(input), hint]
+    }
+}
+
+/// Submitted prompt entries for the session, newest last
+#[derive(Default)]
+pub struct PromptHistory {
+    entries: HashMap<PromptKind, Vec<String>>,
+}
+
+impl PromptHistory {
+    pub fn get(&self, kind: PromptKind) -> &[String] {
+        self.entries.get(&kind).map_or(&[], |v| v.as_slice())
+    }
+
+    /// Remember an entry, moving a repeat to the end
+    pub fn push(&mut self, kind: PromptKind, text: &str) {
+        let entries = self.entries.entry(kind).or_default();
+        entries.retain(|e| e != text);
+        entries.push(text.to_string());
+        if entries.len() > MAX_PROMPT_HISTORY {
+            entries.remove(0);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::project::queue::{JobInfo, JobStatus};
+use crate::ui::browser::centered_rect;
+use crate::ui::Theme;
+
+/// Render the export queue panel as a modal overlay: every job, oldest
+/// first, with a progress bar while it renders
+pub fn render_export_queue(frame: &mut Frame, area: Rect, jobs: &[JobInfo], cursor: usize, theme: &Theme) {
+    let modal_area = centered_rect(80, 60, area);
+    frame.render_widget(Clear, modal_area);
+
+    let pending = jobs.iter().filter(|j| !j.status.is_finished()).count();
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" Export Queue ({} pending) ", pending),
+            Style::default().fg(theme.highlight),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.highlight))
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let height = inner.height.saturating_sub(1) as usize;
+    if jobs.is_empty() {
+        let empty = Paragraph::new("  (no exports queued: Ctrl+E, Ctrl+W and Ctrl+D add them)")
+            .style(Style::default().fg(theme.dimmed).bg(theme.bg));
+        frame.render_widget(empty, inner);
+    } else {
+        let cursor = cursor.min(jobs.len() - 1);
+        let start = (cursor + 1).saturating_sub(height);
+        let lines: Vec<Line> = jobs
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(height)
+            .map(|(i, job)| job_line(job, i == cursor, inner.width as usize, theme))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).style(Style::default().bg(theme.bg)),
+            Rect::new(inner.x, inner.y, inner.width, inner.height.saturating_sub(1)),
+        );
+    }
+
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("  [x]", Style::default().fg(theme.grid_active)),
+        Span::styled(" Cancel queued  ", Style::default().fg(theme.fg)),
+        Span::styled("[c]", Style::default().fg(theme.grid_active)),
+        Span::styled(" Clear finished  ", Style::default().fg(theme.fg)),
+        Span::styled("[Esc]", Style::default().fg(theme.grid_active)),
+        Span::styled(" Close", Style::default().fg(theme.fg)),
+    ]))
+    .style(Style::default().bg(theme.bg));
+    frame.render_widget(
+        footer,
+        Rect::new(inner.x, inner.y + inner.height.saturating_sub(1), inner.width, 1),
+    );
+}
+
+fn job_line(job: &JobInfo, selected: bool, width: usize, theme: &Theme) -> Line<'static> {
+    let status_color = match job.status {
+        JobStatus::Queued => theme.dimmed,
+        JobStatus::Running => theme.highlight,
+        JobStatus::Done if job.warning => theme.meter_mid,
+        JobStatus::Done => theme.meter_low,
+        JobStatus::Failed => theme.meter_high,
+        JobStatus::Cancelled => theme.dimmed,
+    };
+    let marker = if selected { "> " } else { "  " };
+    let mut spans = vec![
+        Span::styled(marker, Style::default().fg(theme.highlight)),
+        Span::styled(format!("#{:<3} ", job.id), Style::default().fg(theme.dimmed)),
+        Span::styled(format!("{:<10}", job.status.name()), Style::default().fg(status_color).bold()),
+    ];
+    let label_style = if selected { Style::default().fg(theme.fg).bold() } else { Style::default().fg(theme.fg) };
+    match job.status {
+        JobStatus::Running => {
+            let bar_width = 20usize.min(width.saturating_sub(20 + job.label.len()));
+            let filled = (job.progress.clamp(0.0, 1.0) * bar_width as f32).round() as usize;
+            spans.push(Span::styled(format!("{} ", job.label), label_style));
+            spans.push(Span::styled("█".repeat(filled), Style::default().fg(theme.highlight)));
+            spans.push(Span::styled("░".repeat(bar_width - filled), Style::default().fg(theme.dimmed)));
+            spans.push(Span::styled(format!(" {:>3.0}%", job.progress * 100.0), label_style));
+        }
+        JobStatus::Queued => spans.push(Span::styled(job.label.clone(), label_style)),
+        _ => {
+            spans.push(Span::styled(format!("{}: ", job.label), label_style));
+            spans.push(Span::styled(job.message.clone(), Style::default().fg(status_color)));
+        }
+    }
+    Line::from(spans)
+}
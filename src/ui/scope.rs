@@ -0,0 +1,119 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+use crate::audio::{spectrum_bands, ANALYZER_FLOOR_DB};
+use crate::ui::Theme;
+
+/// State for the spectrum analyzer view
+pub struct ScopeState {
+    /// Track whose spectrum is drawn over the master (visual only)
+    pub overlay_track: Option<usize>,
+}
+
+impl ScopeState {
+    pub fn new() -> Self {
+        Self {
+            overlay_track: None,
+        }
+    }
+
+    /// Select an overlay track; selecting the current one clears it
+    pub fn toggle_overlay(&mut self, track: usize, num_tracks: usize) {
+        if track >= num_tracks {
+            return;
+        }
+        self.overlay_track = if self.overlay_track == Some(track) {
+            None
+        } else {
+            Some(track)
+        };
+    }
+}
+
+impl Default for ScopeState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render the master spectrum, with the overlay track's spectrum drawn as a
+/// line on top so clashing bands stand out
+pub fn render_scope(
+    frame: &mut Frame,
+    area: Rect,
+    master: &[f32],
+    overlay: Option<(&str, &[f32])>,
+    sample_rate: f32,
+    theme: &Theme,
+) {
+    let title = match overlay {
+        Some((name, _)) => format!(" Spectrum: MASTER + {} ", name),
+        None => " Spectrum: MASTER ".to_string(),
+    };
+    let block = Block::default()
+        .title(Span::styled(title, Style::default().fg(theme.track_label)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border))
+        .style(Style::default().bg(theme.bg));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if inner.width < 8 || inner.height < 3 {
+        return;
+    }
+
+    // Bottom row holds the frequency axis
+    let height = (inner.height - 1) as usize;
+    let bands = inner.width as usize;
+    let master_bands = spectrum_bands(master, sample_rate, bands);
+    let overlay_bands = overlay.map(|(_, samples)| spectrum_bands(samples, sample_rate, bands));
+
+    // dB -> bar height in rows
+    let rows = |db: f32| -> usize {
+        let level = 1.0 - db / ANALYZER_FLOOR_DB;
+        (level.clamp(0.0, 1.0) * height as f32).round() as usize
+    };
+
+    let mut lines: Vec<Line> = Vec::with_capacity(height + 1);
+    for row in (1..=height).rev() {
+        let level = row as f32 / height as f32;
+        let bar_color = if level > 0.85 {
+            theme.meter_high
+        } else if level > 0.6 {
+            theme.meter_mid
+        } else {
+            theme.meter_low
+        };
+        let spans: Vec<Span> = (0..bands)
+            .map(|b| {
+                let master_rows = rows(master_bands[b]);
+                match overlay_bands.as_ref().map(|o| rows(o[b])) {
+                    Some(r) if r == row => {
+                        Span::styled("▀", Style::default().fg(theme.highlight).bold())
+                    }
+                    _ if master_rows >= row => Span::styled("█", Style::default().fg(bar_color)),
+                    _ => Span::raw(" "),
+                }
+            })
+            .collect();
+        lines.push(Line::from(spans));
+    }
+
+    // Frequency axis labels at their log position
+    let mut axis = vec![' '; bands];
+    for (hz, label) in [(100.0, "100"), (1000.0, "1k"), (10000.0, "10k")] {
+        let pos = ((hz / 20.0f32).ln() / (1000.0f32).ln() * bands as f32) as usize;
+        for (i, c) in label.chars().enumerate() {
+            if pos + i < bands {
+                axis[pos + i] = c;
+            }
+        }
+    }
+    lines.push(Line::from(Span::styled(
+        axis.into_iter().collect::<String>(),
+        Style::default().fg(theme.dimmed),
+    )));
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
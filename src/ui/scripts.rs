@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+use crate::script::{Script, ScriptStatus, MAX_SCRIPTS};
+use crate::ui::Theme;
+
+pub struct ScriptsState {
+    pub cursor: usize,
+}
+
+impl ScriptsState {
+    pub fn new() -> Self {
+        Self { cursor: 0 }
+    }
+}
+
+impl Default for ScriptsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Render the Scripts view: project scripts on the left, the selected
+/// script's source on the right
+pub fn render_scripts(
+    frame: &mut Frame,
+    area: Rect,
+    scripts: &[Script],
+    status: &HashMap<String, ScriptStatus>,
+    scripts_state: &ScriptsState,
+    theme: &Theme,
+) {
+    let block = Block::default()
+        .title(Span::styled(
+            format!(" Scripts ({}/{}) ", scripts.len(), MAX_SCRIPTS),
+            Style::default().fg(theme.track_label),
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border))
+        .style(Style::default().bg(theme.bg));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(inner);
+
+    if scripts.is_empty() {
+        let empty_msg = Span::styled(
+            "  (no scripts - N: new, L: load ./scripts/*.rhai)",
+            Style::default().fg(theme.dimmed),
+        );
+        frame.render_widget(Paragraph::new(Line::from(empty_msg)), cols[0]);
+        return;
+    }
+
+    let mut lines = Vec::new();
+    for (i, script) in scripts.iter().enumerate() {
+        let is_cursor = i == scripts_state.cursor;
+        let style = if is_cursor {
+            Style::default().fg(theme.grid_cursor).bold()
+        } else if script.enabled {
+            Style::default().fg(theme.fg)
+        } else {
+            Style::default().fg(theme.dimmed)
+        };
+        let st = status.get(&script.name).cloned().unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(if is_cursor { "> " } else { "  " }, style),
+            Span::styled(format!("{:<14}", script.name), style),
+            Span::styled(
+                if script.enabled { " [ON] " } else { " [OFF]" },
+                if script.enabled {
+                    Style::default().fg(theme.grid_active)
+                } else {
+                    Style::default().fg(theme.dimmed)
+                },
+            ),
+            Span::styled(
+                format!(" every {:<2} bar{} ", script.every, if script.every == 1 { " " } else { "s" }),
+                style,
+            ),
+            Span::styled(format!("runs {}", st.runs), Style::default().fg(theme.dimmed)),
+        ]));
+        // Error takes precedence over printed output
+        if let Some(error) = st.error {
+            lines.push(Line::from(Span::styled(
+                format!("    ! {}", error),
+                Style::default().fg(theme.meter_high),
+            )));
+        } else if let Some(output) = st.output {
+            lines.push(Line::from(Span::styled(
+                format!("    > {}", output),
+                Style::default().fg(theme.dimmed),
+            )));
+        }
+    }
+    frame.render_widget(Paragraph::new(lines), cols[0]);
+
+    if let Some(script) = scripts.get(scripts_state.cursor) {
+        let source: Vec<Line> = script
+            .source
+            .lines()
+            .map(|l| Line::from(Span::styled(l.to_string(), Style::default().fg(theme.fg))))
+            .collect();
+        let source_block = Block::default()
+            .title(Span::styled(
+                format!(" {}.rhai (E: edit) ", script.name),
+                Style::default().fg(theme.dimmed),
+            ))
+            .borders(Borders::LEFT)
+            .border_style(Style::default().fg(theme.border));
+        frame.render_widget(Paragraph::new(source).block(source_block), cols[1]);
+    }
+}
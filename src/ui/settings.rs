@@ -0,0 +1,167 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Paragraph};
+
+use crate::config::{config_path, Config};
+use crate::midi::MidiSync;
+use crate::ui::Theme;
+
+/// Rows of the settings view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsField {
+    OutputDevice,
+    BufferSize,
+    Theme,
+    SampleDirs,
+    Autosave,
+    Backups,
+    Metronome,
+    MidiSync,
+    MidiIn,
+    MidiPort,
+}
+
+impl SettingsField {
+    pub const ALL: [SettingsField; 10] = [
+        SettingsField::OutputDevice,
+        SettingsField::BufferSize,
+        SettingsField::Theme,
+        SettingsField::SampleDirs,
+        SettingsField::Autosave,
+        SettingsField::Backups,
+        SettingsField::Metronome,
+        SettingsField::MidiSync,
+        SettingsField::MidiIn,
+        SettingsField::MidiPort,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingsField::OutputDevice => "Output device",
+            SettingsField::BufferSize => "Buffer size",
+            SettingsField::Theme => "Theme",
+            SettingsField::SampleDirs => "Sample dirs",
+            SettingsField::Autosave => "Autosave",
+            SettingsField::Backups => "Backups",
+            SettingsField::Metronome => "Metronome",
+            SettingsField::MidiSync => "MIDI sync",
+            SettingsField::MidiIn => "MIDI input",
+            SettingsField::MidiPort => "MIDI port",
+        }
+    }
+
+    /// What the keys do on this row
+    fn hint(&self) -> &'static str {
+        match self {
+            SettingsField::OutputDevice | SettingsField::BufferSize => {
+                "Left/Right: change (reopens the output, stopping playback)"
+            }
+            SettingsField::SampleDirs => "Enter: add a directory  Backspace: remove the last one",
+            SettingsField::MidiPort => "Left/Right: change (reopens the MIDI input; clock output on the next start)",
+            _ => "Left/Right: change",
+        }
+    }
+}
+
+pub struct SettingsState {
+    pub cursor: usize,
+    /// Output devices and MIDI ports found when the view was opened
+    pub devices: Vec<String>,
+    pub midi_ports: Vec<String>,
+}
+
+impl SettingsState {
+    pub fn new() -> Self {
+        Self {
+            cursor: 0,
+            devices: Vec::new(),
+            midi_ports: Vec::new(),
+        }
+    }
+
+    pub fn field(&self) -> SettingsField {
+        SettingsField::ALL[self.cursor.min(SettingsField::ALL.len() - 1)]
+    }
+
+    pub fn move_cursor(&mut self, delta: isize) {
+        let count = SettingsField::ALL.len() as isize;
+        self.cursor = (self.cursor as isize + delta).rem_euclid(count) as usize;
+    }
+}
+
+impl Default for SettingsState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Current value of a settings row as shown
+fn value_text(field: SettingsField, config: &Config, midi_sync: MidiSync, theme: &Theme) -> String {
+    let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+    match field {
+        SettingsField::OutputDevice => config.output_device.clone().unwrap_or_else(|| "(system default)".to_string()),
+        SettingsField::BufferSize => match config.buffer_size {
+            Some(frames) => format!("{} frames", frames),
+            None => "(device default)".to_string(),
+        },
+        SettingsField::Theme => theme.name.to_string(),
+        SettingsField::SampleDirs if config.sample_dirs.is_empty() => "(none)".to_string(),
+        SettingsField::SampleDirs => config
+            .sample_dirs
+            .iter()
+            .map(|d| d.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", "),
+        SettingsField::Autosave if config.autosave_minutes == 0 => "off".to_string(),
+        SettingsField::Autosave => format!("every {} min", config.autosave_minutes),
+        SettingsField::Backups if config.backups == 0 => "off".to_string(),
+        SettingsField::Backups => format!("keep {} per project", config.backups),
+        SettingsField::Metronome => on_off(config.metronome),
+        SettingsField::MidiSync => midi_sync.name().to_string(),
+        SettingsField::MidiIn => on_off(config.midi_in),
+        SettingsField::MidiPort => config.midi_port.clone().unwrap_or_else(|| "(first port)".to_string()),
+    }
+}
+
+/// Render the Settings view: one row per setting, changed in place and
+/// saved to the config file right away
+pub fn render_settings(
+    frame: &mut Frame,
+    area: Rect,
+    settings: &SettingsState,
+    config: &Config,
+    midi_sync: MidiSync,
+    theme: &Theme,
+) {
+    let block = Block::default()
+        .title(Span::styled(" Settings ", Style::default().fg(theme.track_label)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border))
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let mut lines = Vec::new();
+    for (i, field) in SettingsField::ALL.iter().enumerate() {
+        let is_cursor = i == settings.cursor;
+        let style = if is_cursor {
+            Style::default().fg(theme.grid_cursor).bold()
+        } else {
+            Style::default().fg(theme.fg)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(if is_cursor { "> " } else { "  " }, style),
+            Span::styled(format!("{:<16}", field.label()), Style::default().fg(theme.track_label)),
+            Span::styled(value_text(*field, config, midi_sync, theme), style),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("  {}", settings.field().hint()),
+        Style::default().fg(theme.dimmed),
+    )));
+    lines.push(Line::from(Span::styled(
+        format!("  Saved to {}", config_path().display()),
+        Style::default().fg(theme.dimmed),
+    )));
+    frame.render_widget(Paragraph::new(lines), inner);
+}
@@ -2,17 +2,21 @@ use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph};
 
 use crate::audio::SequencerState;
-use crate::sequencer::PlaybackMode;
+use crate::sequencer::pattern::ArrangementEntry;
+use crate::sequencer::{chord_label, pattern_label, PlaybackMode, NUM_BANKS, PATTERNS_PER_BANK};
 use crate::ui::Theme;
 
 pub struct SongState {
     pub cursor_position: usize,
+    /// Show the arrangement as a bar timeline instead of a list
+    pub timeline: bool,
 }
 
 impl SongState {
     pub fn new() -> Self {
         Self {
             cursor_position: 0,
+            timeline: false,
         }
     }
 }
@@ -49,17 +53,26 @@ pub fn render_song(
         .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
         .split(inner);
 
-    render_arrangement_list(frame, cols[0], state, song_state, theme);
+    if song_state.timeline {
+        render_arrangement_timeline(frame, cols[0], state, song_state, theme);
+    } else {
+        render_arrangement_list(frame, cols[0], state, song_state, theme);
+    }
     render_pattern_bank_grid(frame, cols[1], state, theme);
 }
 
-fn render_arrangement_list(
-    frame: &mut Frame,
-    area: Rect,
-    state: &SequencerState,
-    song_state: &SongState,
-    theme: &Theme,
-) {
+/// Automation of an entry, e.g. "  2:M 3:v50~" (empty when none)
+fn automation_summary(entry: &ArrangementEntry) -> String {
+    entry
+        .automation
+        .iter()
+        .map(|a| format!(" {}", a.summary()))
+        .collect::<String>()
+        .replacen(' ', "  ", 1)
+}
+
+/// "ARRANGEMENT [MODE]" header line, plus the fade-out when set
+fn arrangement_header(state: &SequencerState, theme: &Theme) -> Line<'static> {
     let mode_str = match state.playback_mode {
         PlaybackMode::Pattern => "PATTERN",
         PlaybackMode::Song => "SONG",
@@ -70,11 +83,28 @@ fn render_arrangement_list(
         PlaybackMode::Song => Style::default().fg(theme.meter_high).bold(),
     };
 
-    // Header line
-    let header = Line::from(vec![
+    let mut spans = vec![
         Span::styled("ARRANGEMENT ", Style::default().fg(theme.track_label).bold()),
         Span::styled(format!("[{}]", mode_str), mode_style),
-    ]);
+    ];
+    if state.arrangement.fade_out_bars > 0 {
+        spans.push(Span::styled(
+            format!(" fade {}b", state.arrangement.fade_out_bars),
+            Style::default().fg(theme.dimmed),
+        ));
+    }
+    Line::from(spans)
+}
+
+fn render_arrangement_list(
+    frame: &mut Frame,
+    area: Rect,
+    state: &SequencerState,
+    song_state: &SongState,
+    theme: &Theme,
+) {
+    // Header line
+    let header = arrangement_header(state, theme);
     frame.render_widget(Paragraph::new(header), Rect::new(area.x, area.y, area.width, 1));
 
     // Column headers
@@ -134,9 +164,11 @@ fn render_arrangement_list(
         let repeat_bar = "|".repeat(entry.repeats.min(16));
         let line = Line::from(vec![
             Span::styled(format!("{}{:2} ", cursor_marker, i + 1), line_style),
-            Span::styled(format!("  [{:02}]  ", entry.pattern), line_style),
+            Span::styled(format!("  [{}]  ", pattern_label(entry.pattern)), line_style),
+            Span::styled(format!("{:<5}", entry_chord(entry)), line_style),
             Span::styled(format!("  x{:<2} {}", entry.repeats, repeat_bar), line_style),
             Span::styled(play_marker.to_string(), Style::default().fg(theme.meter_high)),
+            Span::styled(automation_summary(entry), Style::default().fg(theme.dimmed)),
         ]);
 
         frame.render_widget(
@@ -146,27 +178,200 @@ fn render_arrangement_list(
     }
 }
 
+/// Render the arrangement as blocks on a bar ruler, one block per entry
+/// with width proportional to its repeats, scrolled to keep the cursor
+/// entry in view
+fn render_arrangement_timeline(
+    frame: &mut Frame,
+    area: Rect,
+    state: &SequencerState,
+    song_state: &SongState,
+    theme: &Theme,
+) {
+    let header = arrangement_header(state, theme);
+    frame.render_widget(Paragraph::new(header), Rect::new(area.x, area.y, area.width, 1));
+
+    let entries = &state.arrangement.entries;
+    if entries.is_empty() {
+        let empty_msg = Span::styled(
+            "  (empty - press A to add)",
+            Style::default().fg(theme.dimmed),
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from(empty_msg)),
+            Rect::new(area.x, area.y + 3, area.width, 1),
+        );
+        return;
+    }
+    if area.height < 6 {
+        return;
+    }
+
+    // Bar where each entry starts, plus the end of the song
+    let mut starts = Vec::with_capacity(entries.len() + 1);
+    let mut bar = 0;
+    for entry in entries {
+        starts.push(bar);
+        bar += entry.repeats;
+    }
+    starts.push(bar);
+    let total_bars = bar;
+
+    // Squeeze short songs into the width; longer ones scroll
+    let width = area.width as usize;
+    let bar_width = (width / total_bars.max(1)).clamp(2, 6);
+    let cursor = song_state.cursor_position.min(entries.len() - 1);
+    let cursor_start = starts[cursor] * bar_width;
+    let cursor_end = starts[cursor + 1] * bar_width;
+    let scroll = cursor_end.saturating_sub(width).min(cursor_start);
+
+    // Ruler: bar numbers (1-based) spaced so labels don't collide
+    let label_every = 4usize.div_ceil(bar_width).max(1);
+    let mut ruler = vec![' '; width];
+    let mut ticks = vec![' '; width];
+    for bar in 0..=total_bars {
+        let Some(col) = (bar * bar_width).checked_sub(scroll).filter(|&c| c < width) else {
+            continue;
+        };
+        ticks[col] = if bar % label_every == 0 { '|' } else { '.' };
+        if bar % label_every == 0 && bar < total_bars {
+            for (i, ch) in (bar + 1).to_string().chars().enumerate() {
+                if col + i < width {
+                    ruler[col + i] = ch;
+                }
+            }
+        }
+    }
+    let ruler_style = Style::default().fg(theme.dimmed);
+    for (row, chars) in [(1u16, ruler), (2, ticks)] {
+        frame.render_widget(
+            Paragraph::new(Span::styled(chars.into_iter().collect::<String>(), ruler_style)),
+            Rect::new(area.x, area.y + row, area.width, 1),
+        );
+    }
+
+    // Blocks: pattern label on the first row, repeats on the second
+    let song_playing = state.playback_mode == PlaybackMode::Song && state.playing;
+    for (i, entry) in entries.iter().enumerate() {
+        let start = starts[i] * bar_width;
+        let end = starts[i + 1] * bar_width;
+        if end <= scroll || start >= scroll + width {
+            continue;
+        }
+        let style = if i == cursor {
+            Style::default().fg(theme.bg).bg(theme.grid_cursor).bold()
+        } else if song_playing && i == state.arrangement_position {
+            Style::default().fg(theme.bg).bg(theme.highlight)
+        } else if i % 2 == 0 {
+            Style::default().fg(theme.bg).bg(theme.grid_active)
+        } else {
+            Style::default().fg(theme.bg).bg(theme.track_label)
+        };
+        // Leave a one-column gap between blocks
+        let x0 = start.max(scroll) - scroll;
+        let x1 = (end - scroll).min(width);
+        let block_width = (x1 - x0).saturating_sub(1).max(1);
+        let rows = [
+            format!("{} {}", pattern_label(entry.pattern), entry_chord(entry)),
+            format!("x{}", entry.repeats),
+        ];
+        for (row, text) in rows.iter().enumerate() {
+            let text: String = text.chars().take(block_width).collect();
+            frame.render_widget(
+                Paragraph::new(format!("{:<w$}", text, w = block_width)).style(style),
+                Rect::new(area.x + x0 as u16, area.y + 3 + row as u16, block_width as u16, 1),
+            );
+        }
+    }
+
+    // Playhead, with the position inside the bar
+    if song_playing && state.arrangement_position < entries.len() {
+        let bar = starts[state.arrangement_position] + state.arrangement_repeat;
+        let col = bar * bar_width + state.current_step * bar_width / state.pattern.length;
+        if let Some(col) = col.checked_sub(scroll).filter(|&c| c < width) {
+            frame.render_widget(
+                Paragraph::new(Span::styled("^", Style::default().fg(theme.meter_high).bold())),
+                Rect::new(area.x + col as u16, area.y + 5, 1, 1),
+            );
+        }
+    }
+
+    // Cursor entry summary
+    if area.height > 7 {
+        let entry = &entries[cursor];
+        let summary = format!(
+            "#{} {}{} x{}  bars {}-{} of {}{}",
+            cursor + 1,
+            pattern_label(entry.pattern),
+            entry.chord.map_or(String::new(), |_| format!(" {}", chord_label(entry.chord))),
+            entry.repeats,
+            starts[cursor] + 1,
+            starts[cursor + 1],
+            total_bars,
+            automation_summary(entry)
+        );
+        frame.render_widget(
+            Paragraph::new(Span::styled(summary, Style::default().fg(theme.fg))),
+            Rect::new(area.x, area.y + 7, area.width, 1),
+        );
+    }
+}
+
+/// Entry's chord numeral, empty when it has none
+fn entry_chord(entry: &ArrangementEntry) -> &'static str {
+    match entry.chord {
+        Some(_) => chord_label(entry.chord),
+        None => "",
+    }
+}
+
 fn render_pattern_bank_grid(
     frame: &mut Frame,
     area: Rect,
     state: &SequencerState,
     theme: &Theme,
 ) {
-    // Header
-    let header = Span::styled(
-        "PATTERN BANK",
+    // Header: page letters, current bank highlighted
+    let bank = state.current_pattern / PATTERNS_PER_BANK;
+    let mut header = vec![Span::styled(
+        "PATTERN BANK ",
         Style::default().fg(theme.track_label).bold(),
-    );
+    )];
+    for b in 0..NUM_BANKS {
+        let letter = format!(" {} ", (b'A' + b as u8) as char);
+        let used = (0..PATTERNS_PER_BANK)
+            .any(|i| state.pattern_bank.has_content(b * PATTERNS_PER_BANK + i));
+        let style = if b == bank {
+            Style::default().fg(theme.bg).bg(theme.highlight).bold()
+        } else if used {
+            Style::default().fg(theme.grid_active)
+        } else {
+            Style::default().fg(theme.dimmed)
+        };
+        header.push(Span::styled(letter, style));
+    }
     frame.render_widget(
         Paragraph::new(Line::from(header)),
         Rect::new(area.x, area.y, area.width, 1),
     );
 
-    // 4x4 grid of pattern slots
+    // Crossfader between the current pattern (A) and deck B
+    if let Some(deck_b) = state.deck_b {
+        let position = (state.crossfader * 8.0).round() as usize;
+        let fader: String = (0..=8).map(|i| if i == position { '|' } else { '-' }).collect();
+        let line = Line::from(vec![
+            Span::styled(format!("{} ", pattern_label(state.current_pattern)), Style::default().fg(theme.highlight)),
+            Span::styled(fader, Style::default().fg(theme.fg)),
+            Span::styled(format!(" {}", pattern_label(deck_b)), Style::default().fg(theme.meter_mid).bold()),
+        ]);
+        frame.render_widget(Paragraph::new(line), Rect::new(area.x, area.y + 1, area.width, 1));
+    }
+
+    // 4x4 grid of the current bank's slots
     let cell_width = 6u16;
     for row in 0..4 {
         for col in 0..4 {
-            let idx = row * 4 + col;
+            let idx = bank * PATTERNS_PER_BANK + row * 4 + col;
             let x = area.x + col as u16 * cell_width;
             let y = area.y + 2 + row as u16;
 
@@ -179,13 +384,15 @@ fn render_pattern_bank_grid(
 
             let style = if is_current {
                 Style::default().fg(theme.bg).bg(theme.highlight).bold()
+            } else if state.deck_b == Some(idx) {
+                Style::default().fg(theme.bg).bg(theme.meter_mid).bold()
             } else if has_content {
                 Style::default().fg(theme.grid_active)
             } else {
                 Style::default().fg(theme.dimmed)
             };
 
-            let label = format!("[{:02}]", idx);
+            let label = format!("[{}]", pattern_label(idx));
             frame.render_widget(
                 Paragraph::new(label).style(style),
                 Rect::new(x, y, cell_width, 1),
@@ -201,6 +408,18 @@ fn render_pattern_bank_grid(
                 ",/. Select pattern",
                 Style::default().fg(theme.dimmed),
             )),
+            Line::from(Span::styled(
+                "</> Switch bank",
+                Style::default().fg(theme.dimmed),
+            )),
+            Line::from(Span::styled(
+                "T   List/timeline",
+                Style::default().fg(theme.dimmed),
+            )),
+            Line::from(Span::styled(
+                "1-9 Mute automation",
+                Style::default().fg(theme.dimmed),
+            )),
             Line::from(Span::styled(
                 "A   Add to arrangement",
                 Style::default().fg(theme.dimmed),
@@ -213,10 +432,22 @@ fn render_pattern_bank_grid(
                 "H/L Adjust repeats",
                 Style::default().fg(theme.dimmed),
             )),
+            Line::from(Span::styled(
+                "R   Chord degree (I-vii)",
+                Style::default().fg(theme.dimmed),
+            )),
             Line::from(Span::styled(
                 "M   Toggle mode",
                 Style::default().fg(theme.dimmed),
             )),
+            Line::from(Span::styled(
+                "B   Deck B  {/} slot  [/] fade",
+                Style::default().fg(theme.dimmed),
+            )),
+            Line::from(Span::styled(
+                "F   Song fade-out",
+                Style::default().fg(theme.dimmed),
+            )),
             Line::from(Span::styled(
                 "C   Copy pattern",
                 Style::default().fg(theme.dimmed),
@@ -0,0 +1,60 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::event::{format_duration, SessionStats};
+use crate::ui::browser::centered_rect;
+use crate::ui::Theme;
+
+/// Render the session stats panel as a modal overlay
+pub fn render_stats(frame: &mut Frame, area: Rect, stats: &SessionStats, theme: &Theme) {
+    let modal_area = centered_rect(50, 50, area);
+    frame.render_widget(Clear, modal_area);
+
+    let block = Block::default()
+        .title(Span::styled(" Session Stats ", Style::default().fg(theme.highlight)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.highlight))
+        .style(Style::default().bg(theme.bg));
+    let inner = block.inner(modal_area);
+    frame.render_widget(block, modal_area);
+
+    let label_style = Style::default().fg(theme.track_label);
+    let value_style = Style::default().fg(theme.fg).bold();
+    let row = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("  {:<18}", label), label_style),
+            Span::styled(value, value_style),
+        ])
+    };
+    let song = if stats.song_bars == 0 {
+        "no arrangement".to_string()
+    } else {
+        format!("{} ({} bars)", format_duration(stats.song_secs.round() as u64), stats.song_bars)
+    };
+    let lines = vec![
+        Line::from(Span::styled("  Session", Style::default().fg(theme.highlight).bold())),
+        row("Time open", format_duration(stats.session_secs)),
+        row("Time spent", format_duration(stats.active_secs)),
+        row("Edits", format!("{} ({} by agents)", stats.edits, stats.agent_edits)),
+        Line::from(""),
+        Line::from(Span::styled("  Project", Style::default().fg(theme.highlight).bold())),
+        row("Tracks", stats.tracks.to_string()),
+        row("Patterns used", stats.patterns_used.to_string()),
+        row("In arrangement", stats.arranged_patterns.to_string()),
+        row("Song length", song),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines).style(Style::default().bg(theme.bg)),
+        Rect::new(inner.x, inner.y, inner.width, inner.height.saturating_sub(1)),
+    );
+
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("  [Esc]", Style::default().fg(theme.grid_active)),
+        Span::styled(" Close", Style::default().fg(theme.fg)),
+    ]))
+    .style(Style::default().bg(theme.bg));
+    frame.render_widget(
+        footer,
+        Rect::new(inner.x, inner.y + inner.height.saturating_sub(1), inner.width, 1),
+    );
+}